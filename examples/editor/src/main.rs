@@ -249,13 +249,8 @@ fn main() {
                                                     }
                                                 }
                                                 "s" => {
-                                                    let mut text = String::new();
-                                                    editor.with_buffer(|buffer| {
-                                                        for line in buffer.lines.iter() {
-                                                            text.push_str(line.text());
-                                                            text.push_str(line.ending().as_str());
-                                                        }
-                                                    });
+                                                    let text =
+                                                        editor.with_buffer(|buffer| buffer.text());
                                                     fs::write(&path, &text).unwrap();
                                                     log::info!("saved {:?}", path);
                                                 }
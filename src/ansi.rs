@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{Attrs, AttrsList, Color, DecorationLine, DecorationStyle, Style, Weight};
+
+/// Colors used for ANSI escape sequences, see [`AnsiConverter`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnsiPalette {
+    /// Colors of the 256 color palette, where the first 16 are the normal and bright colors
+    pub colors: [Color; 256],
+    /// Color of text without a foreground color, used for inverse video
+    pub foreground: Color,
+    /// Color behind text without a background color, used for inverse video
+    pub background: Color,
+}
+
+impl AnsiPalette {
+    /// Create a palette from the 16 normal and bright colors, with the rest of the 256 colors
+    /// being the 6x6x6 color cube and gray ramp of xterm
+    pub fn new(colors_16: [Color; 16], foreground: Color, background: Color) -> Self {
+        let mut colors = [Color::rgb(0, 0, 0); 256];
+        colors[..16].copy_from_slice(&colors_16);
+        let level = |i: usize| if i == 0 { 0 } else { 55 + 40 * i as u8 };
+        for i in 0..216 {
+            colors[16 + i] = Color::rgb(level(i / 36), level(i / 6 % 6), level(i % 6));
+        }
+        for i in 0..24 {
+            let gray = 8 + 10 * i as u8;
+            colors[232 + i] = Color::rgb(gray, gray, gray);
+        }
+        Self {
+            colors,
+            foreground,
+            background,
+        }
+    }
+}
+
+impl Default for AnsiPalette {
+    /// The default colors of xterm, with light gray text on black
+    fn default() -> Self {
+        Self::new(
+            [
+                Color::rgb(0, 0, 0),
+                Color::rgb(205, 0, 0),
+                Color::rgb(0, 205, 0),
+                Color::rgb(205, 205, 0),
+                Color::rgb(0, 0, 238),
+                Color::rgb(205, 0, 205),
+                Color::rgb(0, 205, 205),
+                Color::rgb(229, 229, 229),
+                Color::rgb(127, 127, 127),
+                Color::rgb(255, 0, 0),
+                Color::rgb(0, 255, 0),
+                Color::rgb(255, 255, 0),
+                Color::rgb(92, 92, 255),
+                Color::rgb(255, 0, 255),
+                Color::rgb(0, 255, 255),
+                Color::rgb(255, 255, 255),
+            ],
+            Color::rgb(229, 229, 229),
+            Color::rgb(0, 0, 0),
+        )
+    }
+}
+
+/// Graphic rendition set by SGR escape sequences
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Sgr {
+    bold: bool,
+    faint: bool,
+    italic: bool,
+    underline_opt: Option<DecorationStyle>,
+    underline_color_opt: Option<Color>,
+    strikethrough: bool,
+    inverse: bool,
+    foreground_opt: Option<Color>,
+    background_opt: Option<Color>,
+}
+
+impl Sgr {
+    /// Apply the rendition to attributes
+    fn attrs<'a>(&self, mut attrs: Attrs<'a>, palette: &AnsiPalette) -> Attrs<'a> {
+        if self.bold {
+            attrs = attrs.weight(Weight::BOLD);
+        } else if self.faint {
+            attrs = attrs.weight(Weight::LIGHT);
+        }
+        if self.italic {
+            attrs = attrs.style(Style::Italic);
+        }
+        if let Some(style) = self.underline_opt {
+            attrs.decoration.underline = Some(DecorationLine {
+                style,
+                color_opt: self.underline_color_opt,
+            });
+        }
+        if self.strikethrough {
+            attrs = attrs.strikethrough(true);
+        }
+        let (foreground_opt, background_opt) = if self.inverse {
+            (
+                Some(self.background_opt.unwrap_or(palette.background)),
+                Some(self.foreground_opt.unwrap_or(palette.foreground)),
+            )
+        } else {
+            (self.foreground_opt, self.background_opt)
+        };
+        if let Some(color) = foreground_opt {
+            attrs = attrs.color(color);
+        }
+        if let Some(color) = background_opt {
+            attrs = attrs.background_color(color);
+        }
+        attrs
+    }
+
+    /// Apply the parameters of an SGR sequence, as groups of colon separated values
+    fn apply(&mut self, params: &[Vec<u32>], palette: &AnsiPalette) {
+        let mut groups = params.iter();
+        while let Some(group) = groups.next() {
+            let code = group.first().copied().unwrap_or(0);
+            match code {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.faint = true,
+                3 => self.italic = true,
+                4 => {
+                    self.underline_opt = match group.get(1) {
+                        None | Some(1) => Some(DecorationStyle::Solid),
+                        Some(2) => Some(DecorationStyle::Double),
+                        Some(3) => Some(DecorationStyle::Wavy),
+                        Some(4) => Some(DecorationStyle::Dotted),
+                        Some(5) => Some(DecorationStyle::Dashed),
+                        Some(_) => None,
+                    }
+                }
+                7 => self.inverse = true,
+                9 => self.strikethrough = true,
+                21 => self.underline_opt = Some(DecorationStyle::Double),
+                22 => {
+                    self.bold = false;
+                    self.faint = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline_opt = None,
+                27 => self.inverse = false,
+                29 => self.strikethrough = false,
+                30..=37 => self.foreground_opt = Some(palette.colors[code as usize - 30]),
+                38 => self.foreground_opt = extended_color(group, &mut groups, palette),
+                39 => self.foreground_opt = None,
+                40..=47 => self.background_opt = Some(palette.colors[code as usize - 40]),
+                48 => self.background_opt = extended_color(group, &mut groups, palette),
+                49 => self.background_opt = None,
+                58 => self.underline_color_opt = extended_color(group, &mut groups, palette),
+                59 => self.underline_color_opt = None,
+                90..=97 => self.foreground_opt = Some(palette.colors[code as usize - 90 + 8]),
+                100..=107 => self.background_opt = Some(palette.colors[code as usize - 100 + 8]),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parse an extended color like `38;5;n`, `38;2;r;g;b` or their colon separated forms, taking
+/// the values that follow the group from `groups` if they are separated by semicolons
+fn extended_color<'p>(
+    group: &[u32],
+    groups: &mut impl Iterator<Item = &'p Vec<u32>>,
+    palette: &AnsiPalette,
+) -> Option<Color> {
+    let values: Vec<u32> = if group.len() > 1 {
+        let mut values = group[1..].to_vec();
+        // The colon separated form of true color may have a color space id before the channels
+        if values.first() == Some(&2) && values.len() > 4 {
+            values.remove(1);
+        }
+        values
+    } else {
+        let mode = groups.next()?.first().copied().unwrap_or(0);
+        let count = if mode == 2 { 3 } else { 1 };
+        core::iter::once(mode)
+            .chain(
+                groups
+                    .take(count)
+                    .map(|group| group.first().copied().unwrap_or(0)),
+            )
+            .collect()
+    };
+    let channel = |i: usize| values.get(i).map(|&value| value.min(255) as u8);
+    match values.first()? {
+        5 => palette.colors.get(*values.get(1)? as usize).copied(),
+        2 => Some(Color::rgb(channel(1)?, channel(2)?, channel(3)?)),
+        _ => None,
+    }
+}
+
+/// Converter of text with ANSI escape sequences into plain text with [`AttrsList`] spans, for
+/// terminal emulators and log viewers
+///
+/// SGR sequences set the color, background color, weight, style, underline and strikethrough
+/// of the text after them, with 16, 256 and true colors. Other escape sequences are removed.
+/// The rendition is kept from one call of [`AnsiConverter::convert`] to the next, like in a
+/// terminal, so lines may be converted one at a time.
+#[derive(Clone, Debug, Default)]
+pub struct AnsiConverter {
+    palette: AnsiPalette,
+    sgr: Sgr,
+}
+
+impl AnsiConverter {
+    /// Create a converter with a palette for 16 and 256 color sequences
+    pub fn new(palette: AnsiPalette) -> Self {
+        Self {
+            palette,
+            sgr: Sgr::default(),
+        }
+    }
+
+    /// Get the palette
+    pub fn palette(&self) -> &AnsiPalette {
+        &self.palette
+    }
+
+    /// Reset the rendition, as if `ESC [ 0 m` was converted
+    pub fn reset(&mut self) {
+        self.sgr = Sgr::default();
+    }
+
+    /// Convert text into the text without escape sequences, and its spans of attributes that
+    /// differ from `attrs`
+    pub fn convert(&mut self, text: &str, attrs: Attrs) -> (String, AttrsList) {
+        let mut plain = String::with_capacity(text.len());
+        let mut spans: Vec<(Range<usize>, Attrs)> = Vec::new();
+        let mut rest = text;
+        loop {
+            let len = rest.find('\x1b').unwrap_or(rest.len());
+            if len > 0 {
+                let span_attrs = self.sgr.attrs(attrs, &self.palette);
+                let start = plain.len();
+                plain.push_str(&rest[..len]);
+                match spans.last_mut() {
+                    Some((range, last_attrs))
+                        if range.end == start && *last_attrs == span_attrs =>
+                    {
+                        range.end = plain.len();
+                    }
+                    _ => spans.push((start..plain.len(), span_attrs)),
+                }
+            }
+            rest = &rest[len..];
+            let Some(sequence) = rest.strip_prefix('\x1b') else {
+                break;
+            };
+            rest = self.escape_sequence(sequence);
+        }
+
+        let mut attrs_list = AttrsList::new(attrs);
+        for (range, span_attrs) in spans {
+            if span_attrs != attrs {
+                attrs_list.add_span(range, span_attrs);
+            }
+        }
+        (plain, attrs_list)
+    }
+
+    /// Apply an escape sequence after its `ESC`, returning the text after it
+    fn escape_sequence<'t>(&mut self, sequence: &'t str) -> &'t str {
+        let mut chars = sequence.chars();
+        match chars.next() {
+            // Control sequence, made of parameters, intermediate bytes and a final byte
+            Some('[') => {
+                let body = chars.as_str();
+                let Some(end) = body.find(|c: char| ('\x40'..='\x7e').contains(&c)) else {
+                    return "";
+                };
+                let params = &body[..end];
+                if body[end..].starts_with('m')
+                    && params
+                        .bytes()
+                        .all(|b| b.is_ascii_digit() || b == b';' || b == b':')
+                {
+                    let params: Vec<Vec<u32>> = params
+                        .split(';')
+                        .map(|group| {
+                            group
+                                .split(':')
+                                .map(|value| value.parse().unwrap_or(0))
+                                .collect()
+                        })
+                        .collect();
+                    self.sgr.apply(&params, &self.palette);
+                }
+                &body[end + 1..]
+            }
+            // Operating system command, ended by BEL or ST
+            Some(']') => {
+                let body = chars.as_str();
+                match (body.find('\x07'), body.find("\x1b\\")) {
+                    (Some(bel), Some(st)) if st < bel => &body[st + 2..],
+                    (Some(bel), _) => &body[bel + 1..],
+                    (None, Some(st)) => &body[st + 2..],
+                    (None, None) => "",
+                }
+            }
+            Some(_) => chars.as_str(),
+            None => "",
+        }
+    }
+}
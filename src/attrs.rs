@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::collections::BTreeMap;
 #[cfg(not(feature = "std"))]
 use alloc::{
     string::{String, ToString},
@@ -8,12 +9,13 @@ use alloc::{
 use core::ops::Range;
 use rangemap::RangeMap;
 
-use crate::CacheKeyFlags;
+use crate::{math, CacheKeyFlags, Payload, Placeholder, Script};
 
 pub use fontdb::{Family, Stretch, Style, Weight};
 
 /// Text color
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, Eq, Hash, PartialEq)]
+#[repr(transparent)]
 pub struct Color(pub u32);
 
 impl Color {
@@ -64,6 +66,464 @@ impl Color {
     pub fn a(&self) -> u8 {
         ((self.0 & 0xFF_00_00_00) >> 24) as u8
     }
+
+    /// Parse a hex color string in `RGB`, `RGBA`, `RRGGBB`, or `RRGGBBAA` form, with an optional
+    /// leading `#`
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.is_ascii() {
+            return None;
+        }
+
+        let nibble = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 | 4 => {
+                let mut c = [0xFF; 4];
+                for (i, c) in c.iter_mut().enumerate().take(hex.len()) {
+                    *c = nibble(i)? * 0x11;
+                }
+                Some(Self::rgba(c[0], c[1], c[2], c[3]))
+            }
+            6 | 8 => {
+                let mut c = [0xFF; 4];
+                for (i, c) in c.iter_mut().enumerate().take(hex.len() / 2) {
+                    *c = byte(i * 2)?;
+                }
+                Some(Self::rgba(c[0], c[1], c[2], c[3]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Create new color from hue in degrees, and saturation and value from `0.0` to `1.0`
+    #[inline]
+    pub fn hsv(h: f32, s: f32, v: f32) -> Self {
+        Self::hsva(h, s, v, 0xFF)
+    }
+
+    /// Create new color from hue in degrees, saturation and value from `0.0` to `1.0`, and alpha
+    pub fn hsva(h: f32, s: f32, v: f32, a: u8) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+        let chroma = v * s;
+        Self::from_hue(h, chroma, v - chroma, a)
+    }
+
+    /// Create new color from hue in degrees, and saturation and lightness from `0.0` to `1.0`
+    #[inline]
+    pub fn hsl(h: f32, s: f32, l: f32) -> Self {
+        Self::hsla(h, s, l, 0xFF)
+    }
+
+    /// Create new color from hue in degrees, saturation and lightness from `0.0` to `1.0`, and
+    /// alpha
+    pub fn hsla(h: f32, s: f32, l: f32, a: u8) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Self::from_hue(h, chroma, l - chroma / 2.0, a)
+    }
+
+    fn from_hue(h: f32, chroma: f32, m: f32, a: u8) -> Self {
+        let h = h - 360.0 * math::floorf(h / 360.0);
+        let h = h / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::rgba(
+            Self::unit_to_u8(r + m),
+            Self::unit_to_u8(g + m),
+            Self::unit_to_u8(b + m),
+            a,
+        )
+    }
+
+    /// Multiply the red, green, and blue components by alpha
+    ///
+    /// Colors passed to the callbacks of `draw` are not premultiplied, use this when the host
+    /// blends with premultiplied alpha.
+    pub fn premultiplied(self) -> Self {
+        let a = self.a() as u32;
+        let mul = |c: u8| ((c as u32 * a + 127) / 255) as u8;
+        Self::rgba(mul(self.r()), mul(self.g()), mul(self.b()), self.a())
+    }
+
+    /// Divide the red, green, and blue components by alpha, reversing [`Self::premultiplied`]
+    ///
+    /// Fully transparent colors become transparent black.
+    pub fn unpremultiplied(self) -> Self {
+        let a = self.a() as u32;
+        if a == 0 {
+            return Self(0);
+        }
+        let div = |c: u8| ((c as u32 * 255 + a / 2) / a).min(0xFF) as u8;
+        Self::rgba(div(self.r()), div(self.g()), div(self.b()), self.a())
+    }
+
+    /// Get the components in linear sRGB, from `0.0` to `1.0`, in `[r, g, b, a]` order
+    ///
+    /// Alpha is not gamma encoded, so it is only scaled.
+    pub fn to_linear(self) -> [f32; 4] {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                math::powf((c + 0.055) / 1.055, 2.4)
+            }
+        };
+        [
+            linear(self.r()),
+            linear(self.g()),
+            linear(self.b()),
+            self.a() as f32 / 255.0,
+        ]
+    }
+
+    /// Create new color from linear sRGB components, from `0.0` to `1.0`, in `[r, g, b, a]` order
+    pub fn from_linear(linear: [f32; 4]) -> Self {
+        let encode = |c: f32| {
+            let c = c.clamp(0.0, 1.0);
+            let c = if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * math::powf(c, 1.0 / 2.4) - 0.055
+            };
+            Self::unit_to_u8(c)
+        };
+        Self::rgba(
+            encode(linear[0]),
+            encode(linear[1]),
+            encode(linear[2]),
+            Self::unit_to_u8(linear[3]),
+        )
+    }
+
+    #[inline]
+    fn unit_to_u8(c: f32) -> u8 {
+        math::roundf(c.clamp(0.0, 1.0) * 255.0) as u8
+    }
+}
+
+/// Style of a decoration line
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum DecorationStyle {
+    /// A single solid line
+    #[default]
+    Solid,
+    /// Two parallel solid lines
+    Double,
+    /// Square dots as wide as the line is thick
+    Dotted,
+    /// Dashes three times as long as the line is thick
+    Dashed,
+    /// A zigzag line, for example for spelling mistakes
+    Wavy,
+}
+
+/// A line drawn under, over, or through text, see [`Decoration`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DecorationLine {
+    pub style: DecorationStyle,
+    /// Color of the line, or the color of the text if None
+    pub color_opt: Option<Color>,
+}
+
+impl DecorationLine {
+    /// Create a line with a style, in the color of the text
+    pub const fn new(style: DecorationStyle) -> Self {
+        Self {
+            style,
+            color_opt: None,
+        }
+    }
+
+    /// Set [Color]
+    pub const fn color(mut self, color: Color) -> Self {
+        self.color_opt = Some(color);
+        self
+    }
+}
+
+/// Lines drawn with text, see [`Attrs::decoration`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Decoration {
+    pub underline: Option<DecorationLine>,
+    pub overline: Option<DecorationLine>,
+    pub strikethrough: Option<DecorationLine>,
+}
+
+/// Extra advance added to whitespace, in em of the font size, see [`Attrs::word_spacing`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WordSpacing(pub f32);
+
+impl PartialEq for WordSpacing {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for WordSpacing {}
+
+impl core::hash::Hash for WordSpacing {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// Extra advance added to every character, in em of the font size, see
+/// [`Attrs::letter_spacing`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LetterSpacing(pub f32);
+
+impl PartialEq for LetterSpacing {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for LetterSpacing {}
+
+impl core::hash::Hash for LetterSpacing {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// Skew of synthesized italic, see [`Attrs::oblique`]
+#[derive(Clone, Copy, Debug)]
+pub struct Oblique {
+    /// Angle in degrees that the tops of glyphs lean to the right, negative to lean left
+    pub angle: f32,
+    /// Lean the other way in right-to-left text, so that glyphs lean in the direction of reading
+    pub mirror_rtl: bool,
+}
+
+impl Oblique {
+    /// Create an oblique that leans to the right in all text
+    pub const fn new(angle: f32) -> Self {
+        Self {
+            angle,
+            mirror_rtl: false,
+        }
+    }
+
+    /// Set if the oblique leans the other way in right-to-left text
+    pub const fn mirror_rtl(mut self, mirror_rtl: bool) -> Self {
+        self.mirror_rtl = mirror_rtl;
+        self
+    }
+
+    /// Get the angle in degrees to skew a glyph at the given bidirectional level
+    pub fn angle(&self, level: unicode_bidi::Level) -> f32 {
+        if self.mirror_rtl && level.is_rtl() {
+            -self.angle
+        } else {
+            self.angle
+        }
+    }
+}
+
+impl PartialEq for Oblique {
+    fn eq(&self, other: &Self) -> bool {
+        self.angle.to_bits() == other.angle.to_bits() && self.mirror_rtl == other.mirror_rtl
+    }
+}
+
+impl Eq for Oblique {}
+
+impl core::hash::Hash for Oblique {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.angle.to_bits().hash(state);
+        self.mirror_rtl.hash(state);
+    }
+}
+
+/// An OpenType feature setting applied when shaping, see [`Attrs::font_features`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FontFeature {
+    /// Tag of the feature, like `liga` or `tnum`
+    pub tag: [u8; 4],
+    /// Value of the feature, 0 to disable it, 1 to enable it, or the index of an alternate
+    pub value: u32,
+}
+
+impl FontFeature {
+    /// Create a feature setting from a tag and a value
+    pub const fn new(tag: [u8; 4], value: u32) -> Self {
+        Self { tag, value }
+    }
+
+    /// Enable a feature, like `tnum` for tabular numerals
+    pub const fn enable(tag: [u8; 4]) -> Self {
+        Self::new(tag, 1)
+    }
+
+    /// Disable a feature, like `liga` for ligatures in code
+    pub const fn disable(tag: [u8; 4]) -> Self {
+        Self::new(tag, 0)
+    }
+
+    /// Parse a feature setting like `liga off`, `tnum on`, `cv01 2`, `+smcp`, `-liga` or
+    /// `ss01=1`, where a tag alone enables the feature
+    pub fn parse(setting: &str) -> Option<Self> {
+        let setting = setting.trim();
+        let (setting, sign_value) = match setting.as_bytes().first()? {
+            b'+' => (&setting[1..], Some(1)),
+            b'-' => (&setting[1..], Some(0)),
+            _ => (setting, None),
+        };
+        let (tag, value) = match setting.find(|c: char| c == '=' || c.is_whitespace()) {
+            Some(i) => (&setting[..i], setting[i..].trim_start_matches('=').trim()),
+            None => (setting, ""),
+        };
+        let tag: [u8; 4] = tag.as_bytes().try_into().ok()?;
+        if !tag.iter().all(|b| (0x20..=0x7e).contains(b)) {
+            return None;
+        }
+        let value = match (sign_value, value) {
+            (Some(value), "") => value,
+            (Some(_), _) => return None,
+            (None, "" | "on") => 1,
+            (None, "off") => 0,
+            (None, value) => value.parse().ok()?,
+        };
+        Some(Self::new(tag, value))
+    }
+}
+
+/// A drop shadow drawn behind text, see [`Attrs::shadow`]
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Shadow {
+    pub color: Color,
+    /// Horizontal offset of the shadow from the text, positive to the right
+    pub x: f32,
+    /// Vertical offset of the shadow from the text, positive below it
+    pub y: f32,
+    /// Radius of the blur of the shadow, or 0 for a sharp shadow
+    pub blur: f32,
+}
+
+impl Shadow {
+    /// Create a shadow with a color, an offset, and a blur radius
+    pub const fn new(color: Color, x: f32, y: f32, blur: f32) -> Self {
+        Self { color, x, y, blur }
+    }
+}
+
+impl PartialEq for Shadow {
+    fn eq(&self, other: &Self) -> bool {
+        self.color == other.color
+            && self.x.to_bits() == other.x.to_bits()
+            && self.y.to_bits() == other.y.to_bits()
+            && self.blur.to_bits() == other.blur.to_bits()
+    }
+}
+
+impl Eq for Shadow {}
+
+impl core::hash::Hash for Shadow {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.color.hash(state);
+        self.x.to_bits().hash(state);
+        self.y.to_bits().hash(state);
+        self.blur.to_bits().hash(state);
+    }
+}
+
+/// Change of case applied when shaping, see [`Attrs::text_transform`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TextTransform {
+    /// Keep the case of the text
+    #[default]
+    None,
+    /// Show all letters as capitals
+    Uppercase,
+    /// Show all letters as lowercase
+    Lowercase,
+    /// Show the first letter of every word as a capital
+    Capitalize,
+}
+
+impl TextTransform {
+    /// Pass the characters that `c` is shown as to `f`, where `prev_opt` is the character
+    /// before `c` in the text
+    pub fn apply(self, c: char, prev_opt: Option<char>, mut f: impl FnMut(char)) {
+        match self {
+            Self::None => f(c),
+            Self::Uppercase => c.to_uppercase().for_each(f),
+            Self::Lowercase => c.to_lowercase().for_each(f),
+            Self::Capitalize => {
+                // Letters after apostrophes are inside words, like in "don't"
+                let word_start = prev_opt.map_or(true, |prev| {
+                    !prev.is_alphanumeric() && prev != '\'' && prev != '\u{2019}'
+                });
+                if word_start {
+                    c.to_uppercase().for_each(f);
+                } else {
+                    f(c);
+                }
+            }
+        }
+    }
+}
+
+/// Vertical shift of text from the baseline, see [`Attrs::baseline_shift`]
+#[derive(Clone, Copy, Debug)]
+pub enum BaselineShift {
+    /// Raise the text by an amount in em of the font size, or lower it if negative
+    Em(f32),
+    /// Raise the text and scale it down, with the superscript metrics of its font
+    Superscript,
+    /// Lower the text and scale it down, with the subscript metrics of its font
+    Subscript,
+}
+
+impl PartialEq for BaselineShift {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Em(a), Self::Em(b)) => a.to_bits() == b.to_bits(),
+            (Self::Superscript, Self::Superscript) | (Self::Subscript, Self::Subscript) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for BaselineShift {}
+
+impl core::hash::Hash for BaselineShift {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        if let Self::Em(em) = self {
+            em.to_bits().hash(state);
+        }
+    }
+}
+
+/// Font size in pixels of a span of text, see [`Attrs::font_size`]
+#[derive(Clone, Copy, Debug)]
+pub struct FontSize(pub f32);
+
+impl PartialEq for FontSize {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for FontSize {}
+
+impl core::hash::Hash for FontSize {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
 }
 
 /// An owned version of [`Family`]
@@ -101,17 +561,91 @@ impl FamilyOwned {
     }
 }
 
+/// A named instance of a variable font, like `Display Light`, see [`Attrs::font_instance`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FontInstance<'a> {
+    /// Index of the instance in the `fvar` table of the font
+    Index(u16),
+    /// Name or PostScript name of the instance, ignoring ASCII case
+    Name(&'a str),
+}
+
+/// An owned version of [`FontInstance`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FontInstanceOwned {
+    Index(u16),
+    Name(String),
+}
+
+impl FontInstanceOwned {
+    pub fn new(font_instance: FontInstance) -> Self {
+        match font_instance {
+            FontInstance::Index(index) => FontInstanceOwned::Index(index),
+            FontInstance::Name(name) => FontInstanceOwned::Name(name.to_string()),
+        }
+    }
+
+    pub fn as_font_instance(&self) -> FontInstance<'_> {
+        match self {
+            FontInstanceOwned::Index(index) => FontInstance::Index(*index),
+            FontInstanceOwned::Name(name) => FontInstance::Name(name),
+        }
+    }
+}
+
 /// Text attributes
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Attrs<'a> {
     //TODO: should this be an option?
     pub color_opt: Option<Color>,
+    /// Color of the rectangle behind the text, see [`crate::LayoutRun::backgrounds`]
+    pub background_color_opt: Option<Color>,
+    /// Drop shadow drawn behind the text
+    pub shadow_opt: Option<Shadow>,
+    /// Font size overriding the font size of the buffer
+    pub font_size_opt: Option<FontSize>,
+    /// Keep the text from making its line taller, see [`Attrs::clamp_line_height`]
+    pub clamp_line_height: bool,
     pub family: Family<'a>,
     pub stretch: Stretch,
     pub style: Style,
     pub weight: Weight,
+    /// Named instance of variable fonts, see [`Attrs::font_instance`]
+    pub font_instance_opt: Option<FontInstance<'a>>,
     pub metadata: usize,
+    /// Opaque id of a custom fill such as a gradient or texture, 0 for none
+    pub fill_id: u64,
     pub cache_key_flags: CacheKeyFlags,
+    /// Skew of synthesized italic, see [`Attrs::oblique`]
+    pub oblique_opt: Option<Oblique>,
+    /// BCP 47 language tag, like `tr` or `sr-Latn`, for language-specific shaping and fallback
+    pub lang_opt: Option<&'a str>,
+    /// Script used for shaping and font fallback instead of the detected one
+    pub script_opt: Option<Script>,
+    /// Use small capitals for lowercase letters, synthesized if the font lacks `smcp`
+    pub small_caps: bool,
+    /// Use the font's vertical alternates, from the `vert` and `vrt2` features
+    pub vertical_forms: bool,
+    /// Set the text upright across vertical lines, see [`Attrs::combine_upright`]
+    pub combine_upright: bool,
+    /// Change of case applied when shaping, see [`Attrs::text_transform`]
+    pub text_transform: TextTransform,
+    /// OpenType features set when shaping, see [`Attrs::font_features`]
+    pub font_features: &'a [FontFeature],
+    /// Lines drawn under, over, or through the text
+    pub decoration: Decoration,
+    /// Extra advance added to whitespace clusters
+    pub word_spacing_opt: Option<WordSpacing>,
+    /// Extra advance added to every cluster
+    pub letter_spacing_opt: Option<LetterSpacing>,
+    /// Inline box laid out in place of the text
+    pub placeholder_opt: Option<Placeholder>,
+    /// Vertical shift of the text from the baseline
+    pub baseline_shift_opt: Option<BaselineShift>,
+    /// Target of a hyperlink, see [`crate::Buffer::hit_link`]
+    pub link_opt: Option<&'a str>,
+    /// Value carried to the glyphs of the text, see [`Attrs::payload`]
+    pub payload_opt: Option<&'a Payload>,
 }
 
 impl<'a> Attrs<'a> {
@@ -121,12 +655,33 @@ impl<'a> Attrs<'a> {
     pub fn new() -> Self {
         Self {
             color_opt: None,
+            background_color_opt: None,
+            shadow_opt: None,
+            font_size_opt: None,
+            clamp_line_height: false,
             family: Family::SansSerif,
             stretch: Stretch::Normal,
             style: Style::Normal,
             weight: Weight::NORMAL,
+            font_instance_opt: None,
             metadata: 0,
+            fill_id: 0,
             cache_key_flags: CacheKeyFlags::empty(),
+            oblique_opt: None,
+            lang_opt: None,
+            script_opt: None,
+            small_caps: false,
+            vertical_forms: false,
+            combine_upright: false,
+            text_transform: TextTransform::None,
+            font_features: &[],
+            decoration: Decoration::default(),
+            word_spacing_opt: None,
+            letter_spacing_opt: None,
+            placeholder_opt: None,
+            baseline_shift_opt: None,
+            link_opt: None,
+            payload_opt: None,
         }
     }
 
@@ -136,6 +691,45 @@ impl<'a> Attrs<'a> {
         self
     }
 
+    /// Set the [Color] of the rectangle behind the text, covering the height of its line
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.background_color_opt = Some(color);
+        self
+    }
+
+    /// Set a [`Shadow`] drawn behind the text
+    pub fn shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow_opt = Some(shadow);
+        self
+    }
+
+    /// Set the font size in pixels, overriding the font size of the [`crate::Metrics`] of the
+    /// buffer
+    ///
+    /// Lines with text larger than the font size of the buffer are taller in proportion, so
+    /// that the text keeps the same line height relative to its size.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `font_size` is not finite and positive.
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        assert!(
+            font_size.is_finite() && font_size > 0.0,
+            "font size must be finite and positive"
+        );
+        self.font_size_opt = Some(FontSize(font_size));
+        self
+    }
+
+    /// Keep the text from making its line taller than the rest of the line needs
+    ///
+    /// Clamped text, such as a large inline emoji or a superscript, does not add to the height
+    /// of its line and may overlap neighbouring lines instead.
+    pub fn clamp_line_height(mut self, clamp_line_height: bool) -> Self {
+        self.clamp_line_height = clamp_line_height;
+        self
+    }
+
     /// Set [Family]
     pub fn family(mut self, family: Family<'a>) -> Self {
         self.family = family;
@@ -160,18 +754,196 @@ impl<'a> Attrs<'a> {
         self
     }
 
+    /// Set the named instance of variable fonts, like `Display Light`, instead of their
+    /// default instance
+    ///
+    /// Fonts without the instance use their default instance, see
+    /// [`crate::Font::named_instances`] for the instances of a font.
+    pub fn font_instance(mut self, font_instance: FontInstance<'a>) -> Self {
+        self.font_instance_opt = Some(font_instance);
+        self
+    }
+
     /// Set metadata
     pub fn metadata(mut self, metadata: usize) -> Self {
         self.metadata = metadata;
         self
     }
 
+    /// Set the opaque id of a custom fill, copied to [`crate::LayoutGlyph::fill_id`] so that
+    /// renderers can paint the glyphs with a gradient or texture
+    pub fn fill_id(mut self, fill_id: u64) -> Self {
+        self.fill_id = fill_id;
+        self
+    }
+
     /// Set [`CacheKeyFlags`]
     pub fn cache_key_flags(mut self, cache_key_flags: CacheKeyFlags) -> Self {
         self.cache_key_flags = cache_key_flags;
         self
     }
 
+    /// Synthesize italic by skewing glyphs with an [`Oblique`] angle, instead of the default
+    /// 14 degrees of [`CacheKeyFlags::FAKE_ITALIC`]
+    ///
+    /// This adds [`CacheKeyFlags::FAKE_ITALIC`] to the cache key flags, and the angle is applied
+    /// when rasterizing and when getting outlines with [`crate::SwashCache`].
+    pub fn oblique(mut self, oblique: Oblique) -> Self {
+        self.oblique_opt = Some(oblique);
+        self.cache_key_flags |= CacheKeyFlags::FAKE_ITALIC;
+        self
+    }
+
+    /// Set the BCP 47 language tag, used to apply language-specific OpenType features such as
+    /// `locl`, and to choose fallback fonts with the right Han glyphs for Chinese, Japanese, or
+    /// Korean instead of the locale of the [`crate::FontSystem`]
+    pub fn lang(mut self, lang: &'a str) -> Self {
+        self.lang_opt = Some(lang);
+        self
+    }
+
+    /// Set the [`Script`] used for shaping and font fallback, instead of detecting it from the
+    /// characters
+    ///
+    /// This settles characters shared by scripts, like treating CJK text as Han or Kana, or
+    /// shapes code as Latin whatever it contains.
+    pub fn script(mut self, script: Script) -> Self {
+        self.script_opt = Some(script);
+        self
+    }
+
+    /// Set small capitals, synthesized by scaling capitals if the font lacks the `smcp` feature
+    ///
+    /// Synthesized small capitals are marked with [`crate::LayoutGlyph::synthetic_small_caps`].
+    pub fn small_caps(mut self, small_caps: bool) -> Self {
+        self.small_caps = small_caps;
+        self
+    }
+
+    /// Set vertical forms, substituting glyphs such as brackets and small kana with the forms
+    /// the font provides for vertical text
+    pub fn vertical_forms(mut self, vertical_forms: bool) -> Self {
+        self.vertical_forms = vertical_forms;
+        self
+    }
+
+    /// Set the text upright across vertical lines in the space of one character, like
+    /// tate-chu-yoko for numbers in Japanese, see [`crate::Buffer::set_writing_mode`]
+    ///
+    /// Consecutive glyphs with this set are laid out horizontally, centered on the line, and
+    /// scaled down to fit in one em if they are wider.
+    pub fn combine_upright(mut self, combine_upright: bool) -> Self {
+        self.combine_upright = combine_upright;
+        self
+    }
+
+    /// Set a [`TextTransform`] to show the text in another case, like uppercase labels
+    ///
+    /// The text itself is unchanged, and glyphs keep the byte indices of the characters they
+    /// are shaped from, so cursors and selections work on the original text. With
+    /// [`crate::Shaping::Basic`], characters that change to several characters, like `ß` to
+    /// `SS`, only show the first of them.
+    pub fn text_transform(mut self, text_transform: TextTransform) -> Self {
+        self.text_transform = text_transform;
+        self
+    }
+
+    /// Set OpenType features, like disabling `liga` in code or enabling `tnum` for tabular
+    /// numerals
+    ///
+    /// Features are applied after the ones the shaper enables by default, so they may disable
+    /// them. Text is only shaped together with text that has the same features.
+    pub fn font_features(mut self, font_features: &'a [FontFeature]) -> Self {
+        self.font_features = font_features;
+        self
+    }
+
+    /// Set [`Decoration`]
+    pub fn decoration(mut self, decoration: Decoration) -> Self {
+        self.decoration = decoration;
+        self
+    }
+
+    /// Set a solid underline in the color of the text, or remove the underline
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.decoration.underline =
+            underline.then_some(DecorationLine::new(DecorationStyle::Solid));
+        self
+    }
+
+    /// Set a solid overline in the color of the text, or remove the overline
+    pub fn overline(mut self, overline: bool) -> Self {
+        self.decoration.overline = overline.then_some(DecorationLine::new(DecorationStyle::Solid));
+        self
+    }
+
+    /// Set a solid strikethrough in the color of the text, or remove the strikethrough
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.decoration.strikethrough =
+            strikethrough.then_some(DecorationLine::new(DecorationStyle::Solid));
+        self
+    }
+
+    /// Set extra advance added to whitespace clusters, in em of the font size
+    ///
+    /// Negative values bring words closer together. The spacing is part of the width of the
+    /// whitespace, so it is taken into account when wrapping and justifying.
+    pub fn word_spacing(mut self, em: f32) -> Self {
+        self.word_spacing_opt = Some(WordSpacing(em));
+        self
+    }
+
+    /// Set extra advance added after every character, in em of the font size
+    ///
+    /// This is also known as tracking, and is often used to space out all caps text. The
+    /// spacing is part of the width of the text, so it is taken into account when wrapping and
+    /// justifying.
+    pub fn letter_spacing(mut self, em: f32) -> Self {
+        self.letter_spacing_opt = Some(LetterSpacing(em));
+        self
+    }
+
+    /// Set a [`Placeholder`] laid out in place of the text, which is usually U+FFFC OBJECT
+    /// REPLACEMENT CHARACTER
+    ///
+    /// The placeholder wraps like a word and its ascent and descent are part of the height of
+    /// its line, so that it sits on the baseline. Its glyph is not drawn, the caller draws the
+    /// object with the id at the boxes from [`crate::LayoutRun::placeholders`].
+    pub fn placeholder(mut self, placeholder: Placeholder) -> Self {
+        self.placeholder_opt = Some(placeholder);
+        self
+    }
+
+    /// Set a [`BaselineShift`] that raises or lowers the text, for superscripts, subscripts, and
+    /// footnote markers
+    ///
+    /// Shifted text is part of the height of its line, so lines with it are taller if it would
+    /// not fit otherwise. [`BaselineShift::Superscript`] and [`BaselineShift::Subscript`] also
+    /// scale the text down.
+    pub fn baseline_shift(mut self, baseline_shift: BaselineShift) -> Self {
+        self.baseline_shift_opt = Some(baseline_shift);
+        self
+    }
+
+    /// Set the target of a hyperlink, like a URL or an id chosen by the caller
+    ///
+    /// Links are not drawn differently, use [`crate::Buffer::hit_link`] to find the link under
+    /// the pointer and the rectangles to highlight on hover.
+    pub fn link(mut self, link: &'a str) -> Self {
+        self.link_opt = Some(link);
+        self
+    }
+
+    /// Set a payload with its id carried to the glyphs of the text in
+    /// [`crate::LayoutGlyph::payload_id`]
+    ///
+    /// Unlike [`Attrs::metadata`], a payload may hold a value of any type, like a diagnostic or
+    /// a widget handle, downcast with [`Payload::downcast_ref`].
+    pub fn payload(mut self, payload: &'a Payload) -> Self {
+        self.payload_opt = Some(payload);
+        self
+    }
+
     /// Check if font matches
     pub fn matches(&self, face: &fontdb::FaceInfo) -> bool {
         //TODO: smarter way of including emoji
@@ -185,6 +957,15 @@ impl<'a> Attrs<'a> {
             && self.stretch == other.stretch
             && self.style == other.style
             && self.weight == other.weight
+            && self.font_instance_opt == other.font_instance_opt
+            && self.lang_opt == other.lang_opt
+            && self.script_opt == other.script_opt
+            && self.small_caps == other.small_caps
+            && self.vertical_forms == other.vertical_forms
+            && self.text_transform == other.text_transform
+            && self.font_features == other.font_features
+            && self.placeholder_opt == other.placeholder_opt
+            && self.baseline_shift_opt == other.baseline_shift_opt
     }
 }
 
@@ -213,46 +994,236 @@ impl<'a> From<Attrs<'a>> for FontMatchAttrs {
 pub struct AttrsOwned {
     //TODO: should this be an option?
     pub color_opt: Option<Color>,
+    pub background_color_opt: Option<Color>,
+    pub shadow_opt: Option<Shadow>,
+    pub font_size_opt: Option<FontSize>,
+    pub clamp_line_height: bool,
     pub family_owned: FamilyOwned,
     pub stretch: Stretch,
     pub style: Style,
     pub weight: Weight,
+    pub font_instance_opt: Option<FontInstanceOwned>,
     pub metadata: usize,
+    pub fill_id: u64,
     pub cache_key_flags: CacheKeyFlags,
+    pub oblique_opt: Option<Oblique>,
+    pub lang_opt: Option<String>,
+    pub script_opt: Option<Script>,
+    pub small_caps: bool,
+    pub vertical_forms: bool,
+    pub combine_upright: bool,
+    pub text_transform: TextTransform,
+    pub font_features: Vec<FontFeature>,
+    pub decoration: Decoration,
+    pub word_spacing_opt: Option<WordSpacing>,
+    pub letter_spacing_opt: Option<LetterSpacing>,
+    pub placeholder_opt: Option<Placeholder>,
+    pub baseline_shift_opt: Option<BaselineShift>,
+    pub link_opt: Option<String>,
+    pub payload_opt: Option<Payload>,
 }
 
 impl AttrsOwned {
     pub fn new(attrs: Attrs) -> Self {
         Self {
             color_opt: attrs.color_opt,
+            background_color_opt: attrs.background_color_opt,
+            shadow_opt: attrs.shadow_opt,
+            font_size_opt: attrs.font_size_opt,
+            clamp_line_height: attrs.clamp_line_height,
             family_owned: FamilyOwned::new(attrs.family),
             stretch: attrs.stretch,
             style: attrs.style,
             weight: attrs.weight,
+            font_instance_opt: attrs.font_instance_opt.map(FontInstanceOwned::new),
             metadata: attrs.metadata,
+            fill_id: attrs.fill_id,
             cache_key_flags: attrs.cache_key_flags,
+            oblique_opt: attrs.oblique_opt,
+            lang_opt: attrs.lang_opt.map(String::from),
+            script_opt: attrs.script_opt,
+            small_caps: attrs.small_caps,
+            vertical_forms: attrs.vertical_forms,
+            combine_upright: attrs.combine_upright,
+            text_transform: attrs.text_transform,
+            font_features: attrs.font_features.to_vec(),
+            decoration: attrs.decoration,
+            word_spacing_opt: attrs.word_spacing_opt,
+            letter_spacing_opt: attrs.letter_spacing_opt,
+            placeholder_opt: attrs.placeholder_opt,
+            baseline_shift_opt: attrs.baseline_shift_opt,
+            link_opt: attrs.link_opt.map(String::from),
+            payload_opt: attrs.payload_opt.cloned(),
         }
     }
 
     pub fn as_attrs(&self) -> Attrs {
         Attrs {
             color_opt: self.color_opt,
+            background_color_opt: self.background_color_opt,
+            shadow_opt: self.shadow_opt,
+            font_size_opt: self.font_size_opt,
+            clamp_line_height: self.clamp_line_height,
             family: self.family_owned.as_family(),
             stretch: self.stretch,
             style: self.style,
             weight: self.weight,
+            font_instance_opt: self
+                .font_instance_opt
+                .as_ref()
+                .map(FontInstanceOwned::as_font_instance),
             metadata: self.metadata,
+            fill_id: self.fill_id,
             cache_key_flags: self.cache_key_flags,
+            oblique_opt: self.oblique_opt,
+            lang_opt: self.lang_opt.as_deref(),
+            script_opt: self.script_opt,
+            small_caps: self.small_caps,
+            vertical_forms: self.vertical_forms,
+            combine_upright: self.combine_upright,
+            text_transform: self.text_transform,
+            font_features: &self.font_features,
+            decoration: self.decoration,
+            word_spacing_opt: self.word_spacing_opt,
+            letter_spacing_opt: self.letter_spacing_opt,
+            placeholder_opt: self.placeholder_opt,
+            baseline_shift_opt: self.baseline_shift_opt,
+            link_opt: self.link_opt.as_deref(),
+            payload_opt: self.payload_opt.as_ref(),
+        }
+    }
+}
+
+/// Attributes of a span in a layer of an [`AttrsList`], set over the attributes below it
+///
+/// Attributes that are None are left as they are, so that layers such as syntax highlighting,
+/// selection and diagnostics can each change only what they need.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AttrsOverride {
+    pub color_opt: Option<Color>,
+    pub background_color_opt: Option<Color>,
+    pub family_opt: Option<FamilyOwned>,
+    pub weight_opt: Option<Weight>,
+    pub style_opt: Option<Style>,
+    pub underline_opt: Option<DecorationLine>,
+    pub overline_opt: Option<DecorationLine>,
+    pub strikethrough_opt: Option<DecorationLine>,
+    pub metadata_opt: Option<usize>,
+    pub payload_opt: Option<Payload>,
+}
+
+impl AttrsOverride {
+    /// Create an override that leaves all attributes as they are
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [Color]
+    pub fn color(mut self, color: Color) -> Self {
+        self.color_opt = Some(color);
+        self
+    }
+
+    /// Set the [Color] of the rectangle behind the text
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.background_color_opt = Some(color);
+        self
+    }
+
+    /// Set [Family]
+    pub fn family(mut self, family: Family) -> Self {
+        self.family_opt = Some(FamilyOwned::new(family));
+        self
+    }
+
+    /// Set [Weight]
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.weight_opt = Some(weight);
+        self
+    }
+
+    /// Set [Style]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style_opt = Some(style);
+        self
+    }
+
+    /// Set the underline
+    pub fn underline(mut self, line: DecorationLine) -> Self {
+        self.underline_opt = Some(line);
+        self
+    }
+
+    /// Set the overline
+    pub fn overline(mut self, line: DecorationLine) -> Self {
+        self.overline_opt = Some(line);
+        self
+    }
+
+    /// Set the strikethrough
+    pub fn strikethrough(mut self, line: DecorationLine) -> Self {
+        self.strikethrough_opt = Some(line);
+        self
+    }
+
+    /// Set metadata
+    pub fn metadata(mut self, metadata: usize) -> Self {
+        self.metadata_opt = Some(metadata);
+        self
+    }
+
+    /// Set [`Payload`]
+    pub fn payload(mut self, payload: Payload) -> Self {
+        self.payload_opt = Some(payload);
+        self
+    }
+
+    /// Apply the attributes that are set over `attrs`
+    pub fn apply<'a>(&'a self, mut attrs: Attrs<'a>) -> Attrs<'a> {
+        if let Some(color) = self.color_opt {
+            attrs.color_opt = Some(color);
+        }
+        if let Some(color) = self.background_color_opt {
+            attrs.background_color_opt = Some(color);
+        }
+        if let Some(family) = &self.family_opt {
+            attrs.family = family.as_family();
+        }
+        if let Some(weight) = self.weight_opt {
+            attrs.weight = weight;
+        }
+        if let Some(style) = self.style_opt {
+            attrs.style = style;
         }
+        if let Some(line) = self.underline_opt {
+            attrs.decoration.underline = Some(line);
+        }
+        if let Some(line) = self.overline_opt {
+            attrs.decoration.overline = Some(line);
+        }
+        if let Some(line) = self.strikethrough_opt {
+            attrs.decoration.strikethrough = Some(line);
+        }
+        if let Some(metadata) = self.metadata_opt {
+            attrs.metadata = metadata;
+        }
+        if let Some(payload) = &self.payload_opt {
+            attrs.payload_opt = Some(payload);
+        }
+        attrs
     }
 }
 
 /// List of text attributes to apply to a line
+///
+/// Besides its spans, the list may have layers of [`AttrsOverride`] spans on top of them,
+/// applied in order of their layer numbers when getting the attributes of a span.
 //TODO: have this clean up the spans when changes are made
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct AttrsList {
     defaults: AttrsOwned,
     pub(crate) spans: RangeMap<usize, AttrsOwned>,
+    layers: BTreeMap<usize, RangeMap<usize, AttrsOverride>>,
 }
 
 impl AttrsList {
@@ -261,6 +1232,7 @@ impl AttrsList {
         Self {
             defaults: AttrsOwned::new(defaults),
             spans: RangeMap::new(),
+            layers: BTreeMap::new(),
         }
     }
 
@@ -289,48 +1261,231 @@ impl AttrsList {
         self.spans.insert(range, AttrsOwned::new(attrs));
     }
 
+    /// Get the numbers of the layers with spans, from bottom to top
+    pub fn layers(&self) -> Vec<usize> {
+        self.layers.keys().copied().collect()
+    }
+
+    /// Get the spans of a layer
+    pub fn layer_spans(&self, layer: usize) -> Vec<(&Range<usize>, &AttrsOverride)> {
+        self.layers
+            .get(&layer)
+            .map(|spans| spans.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Clear the spans of a layer
+    pub fn clear_layer(&mut self, layer: usize) {
+        self.layers.remove(&layer);
+    }
+
+    /// Add a span to a layer, removing any previous matching parts of spans of the layer
+    ///
+    /// Layers with higher numbers are applied over those with lower numbers.
+    pub fn add_layer_span(
+        &mut self,
+        layer: usize,
+        range: Range<usize>,
+        attrs_override: AttrsOverride,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        self.layers
+            .entry(layer)
+            .or_default()
+            .insert(range, attrs_override);
+    }
+
     /// Get the attribute span for an index
     ///
-    /// This returns a span that contains the index
+    /// This returns a span that contains the index, with the spans of layers applied over it
     pub fn get_span(&self, index: usize) -> Attrs {
-        self.spans
+        let mut attrs = self
+            .spans
             .get(&index)
             .map(|v| v.as_attrs())
-            .unwrap_or(self.defaults.as_attrs())
+            .unwrap_or(self.defaults.as_attrs());
+        for spans in self.layers.values() {
+            if let Some(attrs_override) = spans.get(&index) {
+                attrs = attrs_override.apply(attrs);
+            }
+        }
+        attrs
     }
 
-    /// Split attributes list at an offset
-    pub fn split_off(&mut self, index: usize) -> Self {
-        let mut new = Self::new(self.defaults.as_attrs());
-        let mut removes = Vec::new();
+    /// Get the spans overlapping a range with the spans of layers applied over them, skipping
+    /// parts with the default attributes
+    #[cfg(feature = "shape-run-cache")]
+    pub(crate) fn resolved_spans(&self, range: &Range<usize>) -> Vec<(Range<usize>, AttrsOwned)> {
+        let mut bounds = vec![range.start, range.end];
+        for (span_range, _) in self.spans.overlapping(range) {
+            bounds.extend([span_range.start, span_range.end]);
+        }
+        for spans in self.layers.values() {
+            for (span_range, _) in spans.overlapping(range) {
+                bounds.extend([span_range.start, span_range.end]);
+            }
+        }
+        bounds.retain(|bound| range.contains(bound) || *bound == range.end);
+        bounds.sort_unstable();
+        bounds.dedup();
 
-        //get the keys we need to remove or fix.
-        for span in self.spans.iter() {
-            if span.0.end <= index {
+        let mut resolved: Vec<(Range<usize>, AttrsOwned)> = Vec::new();
+        for window in bounds.windows(2) {
+            let attrs = AttrsOwned::new(self.get_span(window[0]));
+            if attrs == self.defaults {
                 continue;
-            } else if span.0.start >= index {
-                removes.push((span.0.clone(), false));
-            } else {
-                removes.push((span.0.clone(), true));
+            }
+            match resolved.last_mut() {
+                Some((last_range, last_attrs))
+                    if last_range.end == window[0] && *last_attrs == attrs =>
+                {
+                    last_range.end = window[1];
+                }
+                _ => resolved.push((window[0]..window[1], attrs)),
             }
         }
+        resolved
+    }
 
-        for (key, resize) in removes {
-            let (range, attrs) = self
-                .spans
-                .get_key_value(&key.start)
-                .map(|v| (v.0.clone(), v.1.clone()))
-                .expect("attrs span not found");
-            self.spans.remove(key);
+    /// Get the payload with an id from the default attributes, the spans or the layers, see
+    /// [`crate::LayoutGlyph::payload_id`]
+    pub fn payload(&self, id: usize) -> Option<&Payload> {
+        if id == 0 {
+            return None;
+        }
+        let spans = self
+            .spans
+            .iter()
+            .filter_map(|(_, attrs)| attrs.payload_opt.as_ref());
+        let layers = self
+            .layers
+            .values()
+            .flat_map(|spans| spans.iter())
+            .filter_map(|(_, attrs_override)| attrs_override.payload_opt.as_ref());
+        self.defaults
+            .payload_opt
+            .iter()
+            .chain(spans)
+            .chain(layers)
+            .find(|payload| payload.id() == id)
+    }
 
-            if resize {
-                new.spans.insert(0..range.end - index, attrs.clone());
-                self.spans.insert(range.start..index, attrs);
-            } else {
-                new.spans
-                    .insert(range.start - index..range.end - index, attrs);
+    /// Split attributes list at an offset
+    pub fn split_off(&mut self, index: usize) -> Self {
+        let mut new = Self::new(self.defaults.as_attrs());
+        new.spans = split_spans(&mut self.spans, index);
+        for (layer, spans) in self.layers.iter_mut() {
+            let new_spans = split_spans(spans, index);
+            if !new_spans.is_empty() {
+                new.layers.insert(*layer, new_spans);
             }
         }
+        self.layers.retain(|_, spans| !spans.is_empty());
         new
     }
 }
+
+/// Split spans at an offset, returning the spans after it moved to start at 0
+fn split_spans<V: Clone + Eq>(spans: &mut RangeMap<usize, V>, index: usize) -> RangeMap<usize, V> {
+    let mut new = RangeMap::new();
+    let mut removes = Vec::new();
+
+    //get the keys we need to remove or fix.
+    for span in spans.iter() {
+        if span.0.end <= index {
+            continue;
+        } else if span.0.start >= index {
+            removes.push((span.0.clone(), false));
+        } else {
+            removes.push((span.0.clone(), true));
+        }
+    }
+
+    for (key, resize) in removes {
+        let (range, value) = spans
+            .get_key_value(&key.start)
+            .map(|v| (v.0.clone(), v.1.clone()))
+            .expect("attrs span not found");
+        spans.remove(key);
+
+        if resize {
+            new.insert(0..range.end - index, value.clone());
+            spans.insert(range.start..index, value);
+        } else {
+            new.insert(range.start - index..range.end - index, value);
+        }
+    }
+    new
+}
+
+#[test]
+fn test_color_conversions() {
+    assert_eq!(Color::from_hex("#fff"), Some(Color::rgb(0xFF, 0xFF, 0xFF)));
+    assert_eq!(
+        Color::from_hex("1234"),
+        Some(Color::rgba(0x11, 0x22, 0x33, 0x44))
+    );
+    assert_eq!(
+        Color::from_hex("#FF8000"),
+        Some(Color::rgb(0xFF, 0x80, 0x00))
+    );
+    assert_eq!(
+        Color::from_hex("ff800080"),
+        Some(Color::rgba(0xFF, 0x80, 0x00, 0x80))
+    );
+    assert_eq!(
+        Color::from_hex("#ff80"),
+        Some(Color::rgba(0xFF, 0xFF, 0x88, 0x00))
+    );
+    assert_eq!(Color::from_hex("#ff800"), None);
+    assert_eq!(Color::from_hex("#gggggg"), None);
+    assert_eq!(Color::from_hex("#ÿÿÿ"), None);
+
+    assert_eq!(Color::hsv(0.0, 1.0, 1.0), Color::rgb(0xFF, 0x00, 0x00));
+    assert_eq!(Color::hsv(120.0, 1.0, 1.0), Color::rgb(0x00, 0xFF, 0x00));
+    assert_eq!(Color::hsv(-120.0, 1.0, 1.0), Color::rgb(0x00, 0x00, 0xFF));
+    assert_eq!(Color::hsl(60.0, 1.0, 0.5), Color::rgb(0xFF, 0xFF, 0x00));
+    assert_eq!(Color::hsl(0.0, 0.0, 1.0), Color::rgb(0xFF, 0xFF, 0xFF));
+    assert_eq!(
+        Color::hsla(300.0, 1.0, 0.25, 0x80),
+        Color::rgba(0x80, 0x00, 0x80, 0x80)
+    );
+
+    let color = Color::rgba(0xFF, 0x80, 0x00, 0x80);
+    assert_eq!(color.premultiplied(), Color::rgba(0x80, 0x40, 0x00, 0x80));
+    assert_eq!(
+        color.premultiplied().unpremultiplied(),
+        Color::rgba(0xFF, 0x80, 0x00, 0x80)
+    );
+    assert_eq!(
+        Color::rgba(0xFF, 0xFF, 0xFF, 0x00).unpremultiplied(),
+        Color(0)
+    );
+
+    for c in 0..=0xFF {
+        let color = Color::rgba(c, c, c, c);
+        assert_eq!(Color::from_linear(color.to_linear()), color);
+    }
+    assert_eq!(Color::rgb(0xFF, 0xFF, 0xFF).to_linear(), [1.0; 4]);
+}
+
+#[test]
+fn test_attrs_lang() {
+    let attrs = Attrs::new().lang("tr");
+    let owned = AttrsOwned::new(attrs);
+    assert_eq!(owned.lang_opt.as_deref(), Some("tr"));
+    assert_eq!(owned.as_attrs(), attrs);
+
+    // Text in different languages is shaped separately
+    assert!(!attrs.compatible(&Attrs::new()));
+    assert!(attrs.compatible(&Attrs::new().lang("tr")));
+}
+
+#[test]
+#[should_panic(expected = "font size must be finite and positive")]
+fn test_attrs_font_size_invalid() {
+    let _ = Attrs::new().font_size(f32::NAN);
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Color, LayoutGlyph, LayoutRun};
+
+/// A rectangle behind glyphs of a [`LayoutRun`] with a background color, see
+/// [`LayoutRun::backgrounds`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackgroundRect {
+    pub color: Color,
+    /// X position of the left of the rectangle, including [`LayoutRun::line_x`]
+    pub x: f32,
+    /// Y position of the top of the rectangle, which is [`LayoutRun::line_top`]
+    pub y: f32,
+    /// Width of the rectangle
+    pub w: f32,
+    /// Height of the rectangle, which is the line height
+    pub h: f32,
+}
+
+impl<'a> LayoutRun<'a> {
+    /// Get the background rectangles of this horizontal run from left to right, covering the
+    /// glyphs with a background color from [`crate::Attrs::background_color`] and the full
+    /// `line_height`, usually [`crate::Buffer::line_height`]
+    ///
+    /// Neighboring glyphs with the same color share a rectangle.
+    pub fn backgrounds(&self, line_height: f32) -> Vec<BackgroundRect> {
+        let mut glyphs: Vec<&LayoutGlyph> = self.glyphs.iter().collect();
+        glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        let mut rects: Vec<BackgroundRect> = Vec::new();
+        let mut extend = false;
+        for glyph in glyphs {
+            let Some(color) = glyph.background_color_opt() else {
+                extend = false;
+                continue;
+            };
+            let x = self.line_x + glyph.x;
+            match rects.last_mut() {
+                Some(rect) if extend && rect.color == color => {
+                    rect.w = rect.w.max(x + glyph.w - rect.x);
+                }
+                _ => rects.push(BackgroundRect {
+                    color,
+                    x,
+                    y: self.line_top,
+                    w: glyph.w,
+                    h: line_height,
+                }),
+            }
+            extend = true;
+        }
+        rects
+    }
+}
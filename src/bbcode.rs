@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use fontdb::Family;
+
+use crate::{markup::hex_color, Attrs, BaselineShift, Color, RichText, Style, Weight};
+
+impl<'a> RichText<'a> {
+    /// Parse `BBCode` into rich text, using `attrs` outside of tags
+    ///
+    /// The tags `[b]`, `[i]`, `[u]`, `[s]`, `[sup]`, `[sub]`, `[code]`, `[color=...]`,
+    /// `[bgcolor=...]`, `[size=...]` in pixels, `[font=...]` and `[url]` or `[url=...]` are
+    /// supported, not case sensitive. Colors are `#rgb`, `#rrggbb`, `#rrggbbaa` or a basic color
+    /// name like `red`. Text in `[code]` is not parsed.
+    ///
+    /// Malformed `BBCode` is never an error: unknown tags and closing tags without a matching
+    /// opening tag are kept as text, a closing tag also closes the tags opened after its opening
+    /// tag, and tags left open apply to the end of the text.
+    pub fn from_bbcode(bbcode: &'a str, attrs: Attrs<'a>) -> Self {
+        let mut rich_text = RichText::new(attrs);
+        // Open tags with their names and attributes
+        let mut tags: Vec<(&'a str, Attrs<'a>)> = Vec::new();
+        // Start of the text not appended yet
+        let mut start = 0;
+        let mut i = 0;
+        while let Some(offset) = bbcode[i..].find('[') {
+            let open = i + offset;
+            let Some(len) = bbcode[open + 1..].find(']') else {
+                break;
+            };
+            let close = open + 1 + len;
+            let tag = &bbcode[open + 1..close];
+
+            let current = tags.last().map_or(attrs, |&(_, attrs)| attrs);
+            let in_code = tags
+                .last()
+                .map_or(false, |(name, _)| name.eq_ignore_ascii_case("code"));
+            let mut known = false;
+            if let Some(name) = tag.strip_prefix('/') {
+                let name = name.trim();
+                if !in_code || name.eq_ignore_ascii_case("code") {
+                    if let Some(index) = tags
+                        .iter()
+                        .rposition(|(open_name, _)| open_name.eq_ignore_ascii_case(name))
+                    {
+                        rich_text = push(rich_text, &bbcode[start..open], current);
+                        tags.truncate(index);
+                        known = true;
+                    }
+                }
+            } else if !in_code {
+                if let Some(tag) = tag_attrs(tag, current, &bbcode[close + 1..]) {
+                    rich_text = push(rich_text, &bbcode[start..open], current);
+                    tags.push(tag);
+                    known = true;
+                }
+            }
+
+            if known {
+                start = close + 1;
+                i = close + 1;
+            } else {
+                // The bracket is text, but may be followed by a tag
+                i = open + 1;
+            }
+        }
+        let current = tags.last().map_or(attrs, |&(_, attrs)| attrs);
+        push(rich_text, &bbcode[start..], current)
+    }
+}
+
+/// Append text unless it is empty
+fn push<'a>(rich_text: RichText<'a>, text: &'a str, attrs: Attrs<'a>) -> RichText<'a> {
+    if text.is_empty() {
+        rich_text
+    } else {
+        rich_text.span(text, attrs)
+    }
+}
+
+/// Get the name and attributes of an opening tag without its brackets, or None if it is not
+/// supported, with `after` being the text after the tag
+fn tag_attrs<'a>(tag: &'a str, attrs: Attrs<'a>, after: &'a str) -> Option<(&'a str, Attrs<'a>)> {
+    let (name, value_opt) = match tag.split_once('=') {
+        Some((name, value)) => {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            (name.trim(), Some(value))
+        }
+        None => (tag.trim(), None),
+    };
+    let attrs = match (name.to_ascii_lowercase().as_str(), value_opt) {
+        ("b", None) => attrs.weight(Weight::BOLD),
+        ("i", None) => attrs.style(Style::Italic),
+        ("u", None) => attrs.underline(true),
+        ("s", None) => attrs.strikethrough(true),
+        ("sup", None) => attrs.baseline_shift(BaselineShift::Superscript),
+        ("sub", None) => attrs.baseline_shift(BaselineShift::Subscript),
+        ("code", None) => attrs.family(Family::Monospace),
+        ("color", Some(value)) => attrs.color(color(value)?),
+        ("bgcolor", Some(value)) => attrs.background_color(color(value)?),
+        ("size", Some(value)) => {
+            let size: f32 = value.parse().ok()?;
+            if size.is_nan() || size <= 0.0 {
+                return None;
+            }
+            attrs.font_size(size)
+        }
+        ("font", Some(value)) => attrs.family(Family::Name(value)),
+        ("url", Some(value)) => attrs.link(value),
+        ("url", None) => {
+            // The target is the text of the link
+            let len = after.to_ascii_lowercase().find("[/url]")?;
+            attrs.link(after[..len].trim())
+        }
+        _ => return None,
+    };
+    Some((name, attrs))
+}
+
+/// Parse a hexadecimal color or a basic color name
+fn color(value: &str) -> Option<Color> {
+    Some(match value.to_ascii_lowercase().as_str() {
+        "black" => Color::rgb(0, 0, 0),
+        "white" => Color::rgb(255, 255, 255),
+        "gray" | "grey" => Color::rgb(128, 128, 128),
+        "red" => Color::rgb(255, 0, 0),
+        "green" => Color::rgb(0, 128, 0),
+        "blue" => Color::rgb(0, 0, 255),
+        "yellow" => Color::rgb(255, 255, 0),
+        "cyan" => Color::rgb(0, 255, 255),
+        "magenta" => Color::rgb(255, 0, 255),
+        "orange" => Color::rgb(255, 165, 0),
+        "purple" => Color::rgb(128, 0, 128),
+        _ => return hex_color(value),
+    })
+}
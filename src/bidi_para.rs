@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use core::ops::Range;
 use unicode_bidi::{bidi_class, BidiClass, BidiInfo, ParagraphInfo};
 
 /// An iterator over the paragraphs in the input text.
@@ -18,23 +19,60 @@ impl<'text> BidiParagraphs<'text> {
         let info = info.paragraphs.into_iter();
         Self { text, info }
     }
+
+    /// Get the byte range of the next paragraph, excluding the newline that ends it, and whether
+    /// its base direction is right-to-left.
+    ///
+    /// A `\r\n` ends a single paragraph.
+    ///
+    /// This advances the same iterator as [`Iterator::next`].
+    pub fn next_range(&mut self) -> Option<(Range<usize>, bool)> {
+        let para = self.info.next()?;
+        let rtl = para.level.is_rtl();
+        let mut range = para.range;
+        // `para.range` includes the newline that splits the line, so remove it if present
+        if let Some(c) = self.text[range.clone()].chars().next_back() {
+            // `BidiClass::B` is a Paragraph_Separator (various newline characters)
+            if bidi_class(c) == BidiClass::B {
+                // `\r\n` ends one paragraph, not two with an empty one between them
+                if c == '\r' && self.text[range.end..].starts_with('\n') {
+                    self.info.next();
+                }
+                range.end -= c.len_utf8();
+            }
+        }
+        Some((range, rtl))
+    }
 }
 
 impl<'text> Iterator for BidiParagraphs<'text> {
     type Item = &'text str;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let para = self.info.next()?;
-        let paragraph = &self.text[para.range];
-        // `para.range` includes the newline that splits the line, so remove it if present
-        let mut char_indices = paragraph.char_indices();
-        if let Some(i) = char_indices.next_back().and_then(|(i, c)| {
-            // `BidiClass::B` is a Paragraph_Separator (various newline characters)
-            (bidi_class(c) == BidiClass::B).then_some(i)
-        }) {
-            Some(&paragraph[0..i])
-        } else {
-            Some(paragraph)
-        }
+        let (range, _rtl) = self.next_range()?;
+        Some(&self.text[range])
     }
 }
+
+#[test]
+fn test_bidi_paragraphs_next_range() {
+    let text = "abc\n\u{05D0}\u{05D1}\n\nd";
+    let mut paragraphs = BidiParagraphs::new(text);
+    assert_eq!(paragraphs.next_range(), Some((0..3, false)));
+    assert_eq!(paragraphs.next_range(), Some((4..8, true)));
+    assert_eq!(paragraphs.next(), Some(""));
+    assert_eq!(paragraphs.next_range(), Some((10..11, false)));
+    assert_eq!(paragraphs.next_range(), None);
+}
+
+#[test]
+fn test_bidi_paragraphs_crlf() {
+    let text = "abc\r\n\r\nd\n\re";
+    let mut paragraphs = BidiParagraphs::new(text);
+    assert_eq!(paragraphs.next_range(), Some((0..3, false)));
+    assert_eq!(paragraphs.next_range(), Some((5..5, false)));
+    assert_eq!(paragraphs.next_range(), Some((7..8, false)));
+    assert_eq!(paragraphs.next_range(), Some((9..9, false)));
+    assert_eq!(paragraphs.next_range(), Some((10..11, false)));
+    assert_eq!(paragraphs.next_range(), None);
+}
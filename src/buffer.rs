@@ -1,14 +1,21 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 #[cfg(not(feature = "std"))]
-use alloc::{string::String, vec::Vec};
-use core::{cmp, fmt};
+use alloc::{string::String, vec, vec::Vec};
+use core::{
+    cmp, fmt, mem,
+    ops::{Deref, DerefMut, Range},
+};
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    Affinity, Attrs, AttrsList, BidiParagraphs, BorrowedWithFontSystem, BufferLine, Color, Cursor,
-    FontSystem, LayoutCursor, LayoutGlyph, LayoutLine, LineEnding, LineIter, Motion, Scroll,
-    ShapeBuffer, ShapeLine, Shaping, Wrap,
+    Affinity, Attrs, AttrsList, AttrsOverride, BaselineAlign, BatchGlyph, BidiParagraphs,
+    BorrowedWithFontSystem, BufferLine, Color, Cursor, Exclusion, ExclusionBands, FontSystem,
+    HashMap, LayoutCursor, LayoutGlyph, LayoutLine, LayoutOptions, LeadingTrim,
+    LineBreakStrictness, LineEnding, LineHeight, LineIter, LineLayoutParams, LineWidth, LinkHit,
+    LinkRect, MarkupError, MissingGlyph, Motion, PageRange, ParagraphAttrs, PseudoLocale, RichText,
+    RubyRun, Script, Scroll, ShapeBuffer, ShapeLine, Shaping, TextOrientation, Truncate,
+    Truncation, VerticalAlign, VerticalMetrics, Wrap, WritingMode,
 };
 
 /// A line of visible text for rendering
@@ -22,12 +29,36 @@ pub struct LayoutRun<'a> {
     pub rtl: bool,
     /// The array of layout glyphs to draw
     pub glyphs: &'a [LayoutGlyph],
-    /// Y offset to baseline of line
+    /// X offset to the center of a vertical line, or to the left of the column of a horizontal
+    /// line
+    ///
+    /// Glyphs of vertical lines are centered on it, and their y position is where they start
+    /// along the line, see [`Buffer::set_writing_mode`]. Glyphs of horizontal lines are placed
+    /// from it, see [`Buffer::set_columns`].
+    pub line_x: f32,
+    /// Y offset to baseline of line, or from the top of each glyph to its baseline for vertical
+    /// lines
     pub line_y: f32,
-    /// Y offset to top of line
+    /// Y offset to top of line, or 0 for vertical lines
     pub line_top: f32,
-    /// Width of line
+    /// Width of line, or its height for vertical lines
     pub line_w: f32,
+    /// Index of the column of the line, see [`Buffer::set_columns`]
+    pub column: usize,
+    /// Ruby text of the line, placed like its glyphs, see [`BufferLine::set_ruby`]
+    pub ruby: &'a [RubyRun],
+    /// Glyphs of the drop cap of the paragraph, placed like the glyphs of its first line, see
+    /// [`ParagraphAttrs::drop_cap`]
+    pub drop_cap: &'a [LayoutGlyph],
+    /// Glyphs of the list marker of the paragraph, placed like the glyphs of its first line, see
+    /// [`BufferLine::set_marker`]
+    pub marker: &'a [LayoutGlyph],
+    /// Ascent that the baseline was placed with, see [`Buffer::set_baseline_align`]
+    pub ascent: f32,
+    /// Descent that the baseline was placed with, see [`Buffer::set_baseline_align`]
+    pub descent: f32,
+    /// Writing mode of the line, see [`Buffer::set_writing_mode`]
+    pub writing_mode: WritingMode,
 }
 
 impl<'a> LayoutRun<'a> {
@@ -35,25 +66,29 @@ impl<'a> LayoutRun<'a> {
     /// and `cursor_end` within this run, or None if the cursor range does not intersect this run.
     /// This may return widths of zero if `cursor_start == cursor_end`, if the run is empty, or if the
     /// region's left start boundary is the same as the cursor's end boundary or vice versa.
+    ///
+    /// The span is along the line, so it is a span of y positions for vertical lines, see
+    /// [`Self::line_rect`].
     pub fn highlight(&self, cursor_start: Cursor, cursor_end: Cursor) -> Option<(f32, f32)> {
         let mut x_start = None;
         let mut x_end = None;
         let rtl_factor = if self.rtl { 1. } else { 0. };
         let ltr_factor = 1. - rtl_factor;
         for glyph in self.glyphs.iter() {
+            let x = self.glyph_position(glyph);
             let cursor = self.cursor_from_glyph_left(glyph);
             if cursor >= cursor_start && cursor <= cursor_end {
                 if x_start.is_none() {
-                    x_start = Some(glyph.x + glyph.w * rtl_factor);
+                    x_start = Some(x + glyph.w * rtl_factor);
                 }
-                x_end = Some(glyph.x + glyph.w * rtl_factor);
+                x_end = Some(x + glyph.w * rtl_factor);
             }
             let cursor = self.cursor_from_glyph_right(glyph);
             if cursor >= cursor_start && cursor <= cursor_end {
                 if x_start.is_none() {
-                    x_start = Some(glyph.x + glyph.w * ltr_factor);
+                    x_start = Some(x + glyph.w * ltr_factor);
                 }
-                x_end = Some(glyph.x + glyph.w * ltr_factor);
+                x_end = Some(x + glyph.w * ltr_factor);
             }
         }
         if let Some(x_start) = x_start {
@@ -69,6 +104,157 @@ impl<'a> LayoutRun<'a> {
         }
     }
 
+    /// Get the position of a glyph along the line, which is its x position for horizontal lines
+    /// and its y position for vertical lines
+    pub fn glyph_position(&self, glyph: &LayoutGlyph) -> f32 {
+        if self.writing_mode.is_vertical() {
+            glyph.y
+        } else {
+            glyph.x
+        }
+    }
+
+    /// Get the rectangle `(x, y, w, h)` from `start` to `end` along the line, like a span from
+    /// [`Self::highlight`], and `line_height` across it
+    ///
+    /// Rectangles of vertical lines are centered on [`Self::line_x`].
+    pub fn line_rect(&self, start: f32, end: f32, line_height: f32) -> (f32, f32, f32, f32) {
+        if self.writing_mode.is_vertical() {
+            (
+                self.line_x - line_height / 2.0,
+                start,
+                line_height,
+                end - start,
+            )
+        } else {
+            (self.line_x + start, self.line_top, end - start, line_height)
+        }
+    }
+
+    /// Get the index in [`Self::glyphs`] of the first glyph that is partially or fully outside
+    /// of `0.0..width`, or None if all glyphs fit
+    ///
+    /// Glyphs are in reading order, so for lines that clip instead of wrap, this is where a
+    /// renderer may start to fade out the text. Glyphs after it may fit again if the line has
+    /// text in both directions.
+    pub fn overflow(&self, width: f32) -> Option<usize> {
+        self.glyphs
+            .iter()
+            .position(|glyph| glyph.x < 0.0 || glyph.x + glyph.w > width)
+    }
+
+    /// Split the glyphs of this run into ranges with the same script and direction, in the
+    /// order of [`Self::glyphs`]
+    ///
+    /// Glyphs of common characters, like spaces between words, are merged into the range before
+    /// them.
+    pub fn script_runs(&self) -> Vec<ScriptRun> {
+        let mut runs: Vec<ScriptRun> = Vec::new();
+        for (i, glyph) in self.glyphs.iter().enumerate() {
+            let rtl = glyph.level.is_rtl();
+            let script = glyph.script();
+            if let Some(run) = runs.last_mut() {
+                if run.rtl == rtl {
+                    if run.script == Script::Common {
+                        run.script = script;
+                    }
+                    if run.script == script || script == Script::Common {
+                        run.glyphs.end = i + 1;
+                        continue;
+                    }
+                }
+            }
+            runs.push(ScriptRun {
+                script,
+                rtl,
+                glyphs: i..i + 1,
+            });
+        }
+        runs
+    }
+
+    /// Get the positions where a caret can be placed in this run, from left to right
+    ///
+    /// Stops are placed at the edges of each cluster and between the grapheme clusters inside
+    /// it, so a caret can move through ligatures like "ffi" one letter at a time but never into
+    /// an Indic conjunct. Positions inside a cluster divide its width evenly, like [`Buffer::hit`].
+    /// A logical position is listed twice where the direction of the text changes. The run has no
+    /// stops if it has no glyphs.
+    pub fn caret_stops(&self) -> Vec<CaretStop> {
+        let mut stops: Vec<CaretStop> = Vec::new();
+        let mut glyph_i = 0;
+        while let Some(glyph) = self.glyphs.get(glyph_i) {
+            // Glyphs of the same cluster are next to each other
+            let mut x_end = glyph.x + glyph.w;
+            glyph_i += 1;
+            while let Some(next) = self.glyphs.get(glyph_i) {
+                if next.start != glyph.start || next.end != glyph.end {
+                    break;
+                }
+                x_end = x_end.max(next.x + next.w);
+                glyph_i += 1;
+            }
+
+            let cluster = &self.text[glyph.start..glyph.end];
+            let mut boundaries: Vec<usize> = cluster
+                .grapheme_indices(true)
+                .map(|(i, _)| glyph.start + i)
+                .collect();
+            boundaries.push(glyph.end);
+            let egc_w = (x_end - glyph.x) / (boundaries.len() - 1).max(1) as f32;
+            if glyph.level.is_rtl() {
+                boundaries.reverse();
+            }
+
+            for (i, index) in boundaries.into_iter().enumerate() {
+                stops.push(CaretStop {
+                    index,
+                    x: glyph.x + egc_w * i as f32,
+                });
+            }
+        }
+
+        // Clusters are in logical order, and neighbors share the stop between them
+        stops.sort_by(|a, b| a.x.total_cmp(&b.x));
+        stops.dedup_by(|a, b| a.index == b.index);
+        stops
+    }
+
+    /// Map glyphs from left to right to the text they come from, and bytes of the text back to
+    /// glyphs from left to right, after bidirectional reordering
+    ///
+    /// This follows the x positions of the glyphs, as [`Self::glyphs`] keeps right-to-left text
+    /// in logical order.
+    pub fn visual_order(&self) -> VisualOrder {
+        let mut visual: Vec<&LayoutGlyph> = self.glyphs.iter().collect();
+        visual.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        let start = visual.iter().map(|glyph| glyph.start).min().unwrap_or(0);
+        let end = visual.iter().map(|glyph| glyph.end).max().unwrap_or(0);
+        let mut logical_to_visual = vec![usize::MAX; end - start];
+        for (visual_i, glyph) in visual.iter().enumerate() {
+            for index in glyph.start..glyph.end {
+                // Right-to-left clusters start at their rightmost glyph
+                let entry = &mut logical_to_visual[index - start];
+                if glyph.level.is_rtl() || *entry == usize::MAX {
+                    *entry = visual_i;
+                }
+            }
+        }
+        // Bytes without a glyph belong to the glyph before them
+        for index in 1..logical_to_visual.len() {
+            if logical_to_visual[index] == usize::MAX {
+                logical_to_visual[index] = logical_to_visual[index - 1];
+            }
+        }
+
+        VisualOrder {
+            visual_to_logical: visual.iter().map(|glyph| glyph.start).collect(),
+            start,
+            logical_to_visual,
+        }
+    }
+
     fn cursor_from_glyph_left(&self, glyph: &LayoutGlyph) -> Cursor {
         if self.rtl {
             Cursor::new_with_affinity(self.line_i, glyph.end, Affinity::Before)
@@ -86,35 +272,97 @@ impl<'a> LayoutRun<'a> {
     }
 }
 
+/// A position where a caret can be placed, see [`LayoutRun::caret_stops`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct CaretStop {
+    /// Byte offset in [`LayoutRun::text`]
+    pub index: usize,
+    /// X position of the caret
+    pub x: f32,
+}
+
+/// Mapping between the visual order of glyphs in a [`LayoutRun`] and its text, see
+/// [`LayoutRun::visual_order`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VisualOrder {
+    /// Byte offset in [`LayoutRun::text`] of the cluster of each glyph, from left to right
+    pub visual_to_logical: Vec<usize>,
+    /// Byte offset in [`LayoutRun::text`] of the first entry of [`Self::logical_to_visual`]
+    pub start: usize,
+    /// Index in [`Self::visual_to_logical`] of the glyph showing each byte of the text in the
+    /// run, starting at [`Self::start`]
+    pub logical_to_visual: Vec<usize>,
+}
+
+/// A range of glyphs in a [`LayoutRun`] with the same script and direction
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptRun {
+    /// The script, [`Script::Common`] if the glyphs are only spaces, punctuation, or symbols
+    pub script: Script,
+    /// True if the glyphs are right-to-left
+    pub rtl: bool,
+    /// The range of glyphs in [`LayoutRun::glyphs`]
+    pub glyphs: Range<usize>,
+}
+
 /// An iterator of visible text lines, see [`LayoutRun`]
 #[derive(Debug)]
 pub struct LayoutRunIter<'b> {
     buffer: &'b Buffer,
     line_i: usize,
     layout_i: usize,
+    /// Number of layout lines before the current line, see [`Truncation::hidden`]
+    line_layout_start: usize,
     remaining_len: usize,
     total_layout: i32,
+    /// Paragraph spacing above the current line
+    total_space: f32,
+    /// Column of the current line, see [`Buffer::set_columns`]
+    column: usize,
+    /// Distance from the top of the first column to the top of the current column, if the
+    /// columns were stacked
+    column_top: f32,
+    /// Distance the lines are moved down by, see [`Buffer::set_vertical_align`] and
+    /// [`Buffer::set_leading_trim`]
+    align_top: f32,
 }
 
 impl<'b> LayoutRunIter<'b> {
     pub fn new(buffer: &'b Buffer) -> Self {
-        let total_layout_lines: usize = buffer
+        let layout_len = |line: &BufferLine| {
+            line.layout_opt()
+                .as_ref()
+                .map(|layout| layout.len())
+                .unwrap_or_default()
+        };
+        let line_layout_start: usize = buffer
+            .lines
+            .iter()
+            .take(buffer.scroll.line)
+            .map(layout_len)
+            .sum();
+        let mut total_layout_lines: usize = buffer
             .lines
             .iter()
             .skip(buffer.scroll.line)
-            .map(|line| {
-                line.layout_opt()
-                    .as_ref()
-                    .map(|layout| layout.len())
-                    .unwrap_or_default()
-            })
+            .map(layout_len)
             .sum();
+        if let Some(truncation) = &buffer.truncation_opt {
+            // Hidden lines are replaced by a single line
+            let hidden_start = truncation.hidden.start.max(line_layout_start);
+            if hidden_start < truncation.hidden.end {
+                total_layout_lines =
+                    total_layout_lines.saturating_sub(truncation.hidden.end - hidden_start - 1);
+            }
+        }
         let top_cropped_layout_lines =
             total_layout_lines.saturating_sub(buffer.scroll.layout.try_into().unwrap_or_default());
-        let maximum_lines = if buffer.metrics.line_height == 0.0 {
+        let maximum_lines = if buffer.line_height() == 0.0 {
             0
         } else {
-            (buffer.height / buffer.metrics.line_height) as i32
+            (buffer.inline_block_size().1 / buffer.line_height()) as i32
+                * buffer.column_count() as i32
         };
         let bottom_cropped_layout_lines =
             if top_cropped_layout_lines > maximum_lines.try_into().unwrap_or_default() {
@@ -127,8 +375,13 @@ impl<'b> LayoutRunIter<'b> {
             buffer,
             line_i: buffer.scroll.line,
             layout_i: 0,
+            line_layout_start,
             remaining_len: bottom_cropped_layout_lines,
             total_layout: 0,
+            total_space: 0.0,
+            column: 0,
+            column_top: 0.0,
+            align_top: buffer.vertical_align_offset() - buffer.leading_trim_top(),
         }
     }
 }
@@ -144,43 +397,116 @@ impl<'b> Iterator for LayoutRunIter<'b> {
         while let Some(line) = self.buffer.lines.get(self.line_i) {
             let shape = line.shape_opt().as_ref()?;
             let layout = line.layout_opt().as_ref()?;
+            let paragraph_attrs = line.paragraph_attrs();
             while let Some(layout_line) = layout.get(self.layout_i) {
+                let first = self.layout_i == 0;
+                let layout_global_i = self.line_layout_start + self.layout_i;
                 self.layout_i += 1;
 
+                let (mut run_line_i, mut run_line, mut run_rtl, mut run_layout_line) =
+                    (self.line_i, line, shape.rtl, layout_line);
+                if let Some(truncation) = &self.buffer.truncation_opt {
+                    if truncation.hidden.contains(&layout_global_i) {
+                        if layout_global_i != truncation.hidden.start {
+                            continue;
+                        }
+                        run_line_i = truncation.line_i;
+                        run_line = self.buffer.lines.get(run_line_i)?;
+                        run_rtl = run_line.shape_opt().as_ref()?.rtl;
+                        run_layout_line = &truncation.layout;
+                    }
+                }
+
                 let scrolled = self.total_layout < self.buffer.scroll.layout;
                 self.total_layout += 1;
                 if scrolled {
                     continue;
                 }
+                if first {
+                    self.total_space += paragraph_attrs.space_before;
+                }
+
+                // Ruby text is given room above its line, and taller fonts room around it
+                let ruby_height = run_layout_line.ruby_height;
+                let extra_height = run_layout_line.extra_height;
+                self.total_space += ruby_height + extra_height / 2.0;
 
+                let line_height = self.buffer.line_height();
                 let line_top = self
                     .total_layout
                     .saturating_sub(self.buffer.scroll.layout)
                     .saturating_sub(1) as f32
-                    * self.buffer.metrics.line_height;
-                let glyph_height = layout_line.max_ascent + layout_line.max_descent;
-                let centering_offset = (self.buffer.metrics.line_height - glyph_height) / 2.0;
-                let line_y = line_top + centering_offset + layout_line.max_ascent;
+                    * line_height
+                    + self.total_space;
+                self.total_space += extra_height / 2.0;
+                let (max_ascent, max_descent) = run_layout_line.baseline_ascent_descent(
+                    self.buffer.vertical_metrics,
+                    self.buffer.baseline_align,
+                );
+                let glyph_height = max_ascent + max_descent;
+                let centering_offset = (line_height - glyph_height) / 2.0;
+
+                // Lines that do not fit in a column move to the top of the next one, with room for
+                // their ruby
+                let block_size = self.buffer.inline_block_size().1;
+                let mut line_top = line_top - self.column_top + self.align_top;
+                if self.column + 1 < self.buffer.column_count()
+                    && line_top > ruby_height
+                    && line_top + line_height > block_size
+                {
+                    self.column += 1;
+                    self.column_top += line_top - ruby_height;
+                    line_top = ruby_height;
+                }
 
-                if line_top + centering_offset > self.buffer.height {
+                if line_top + centering_offset > block_size {
                     return None;
                 }
 
+                // Vertical lines start at the right or the left, and glyphs are placed along them
+                let half_line_height = line_height / 2.0;
+                let (line_x, line_y, line_top) = match self.buffer.writing_mode {
+                    WritingMode::HorizontalTb => (
+                        self.column as f32 * (self.buffer.column_width() + self.buffer.column_gap),
+                        line_top + centering_offset + max_ascent,
+                        line_top,
+                    ),
+                    WritingMode::VerticalRl => (
+                        self.buffer.width - line_top - half_line_height,
+                        max_ascent,
+                        0.0,
+                    ),
+                    WritingMode::VerticalLr => (line_top + half_line_height, max_ascent, 0.0),
+                };
+
                 return self.remaining_len.checked_sub(1).map(|num| {
                     self.remaining_len = num;
                     LayoutRun {
-                        line_i: self.line_i,
-                        text: line.text(),
-                        rtl: shape.rtl,
-                        glyphs: &layout_line.glyphs,
+                        line_i: run_line_i,
+                        text: run_line.text(),
+                        rtl: run_rtl,
+                        glyphs: &run_layout_line.glyphs,
+                        line_x,
                         line_y,
                         line_top,
-                        line_w: layout_line.w,
+                        line_w: run_layout_line.w,
+                        column: self.column,
+                        ruby: &run_layout_line.ruby,
+                        drop_cap: &run_layout_line.drop_cap,
+                        marker: &run_layout_line.marker,
+                        ascent: max_ascent,
+                        descent: max_descent,
+                        writing_mode: self.buffer.writing_mode,
                     }
                 });
             }
+            // Space after a paragraph is only added once part of it has been shown
+            if self.total_layout > self.buffer.scroll.layout {
+                self.total_space += paragraph_attrs.space_after;
+            }
             self.line_i += 1;
             self.layout_i = 0;
+            self.line_layout_start += layout.len();
         }
 
         None
@@ -189,8 +515,70 @@ impl<'b> Iterator for LayoutRunIter<'b> {
 
 impl<'b> ExactSizeIterator for LayoutRunIter<'b> {}
 
+/// Glyphs sharing a font, font size, and color, see [`Buffer::glyph_batches`]
+#[derive(Clone, Debug)]
+pub struct GlyphBatch {
+    /// Font id of the glyphs
+    pub font_id: fontdb::ID,
+    /// Font size of the glyphs
+    pub font_size: f32,
+    /// Optional color override of the glyphs
+    pub color_opt: Option<Color>,
+    /// Opaque id of the custom fill of the glyphs, see [`crate::Attrs::fill_id`]
+    pub fill_id: u64,
+    /// Glyphs in reading order, unless sorted with [`Self::sort_by_cache_key`]
+    pub glyphs: Vec<BatchGlyph>,
+}
+
+impl GlyphBatch {
+    /// Sort glyphs by their [`crate::CacheKey`], so that glyphs sharing a rasterized image are
+    /// adjacent
+    ///
+    /// Glyphs with the same cache key keep their reading order, and [`BatchGlyph::logical_i`] maps
+    /// back to it.
+    pub fn sort_by_cache_key(&mut self) {
+        self.glyphs
+            .sort_by_cached_key(|glyph| glyph.glyph.physical((0., 0.), 1.0).cache_key);
+    }
+}
+
+/// A rectangle in pixels that drawing is limited to, see [`Buffer::draw_clipped`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[repr(C)]
+pub struct ClipRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl ClipRect {
+    /// Create a new clip rectangle
+    pub const fn new(x: i32, y: i32, w: u32, h: u32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// Cut a rectangle to the part inside this one, or None if they do not overlap
+    pub fn intersect(&self, x: i32, y: i32, w: u32, h: u32) -> Option<(i32, i32, u32, u32)> {
+        let left = x.max(self.x) as i64;
+        let top = y.max(self.y) as i64;
+        let right = (x as i64 + w as i64).min(self.x as i64 + self.w as i64);
+        let bottom = (y as i64 + h as i64).min(self.y as i64 + self.h as i64);
+        if right <= left || bottom <= top {
+            return None;
+        }
+        Some((
+            left as i32,
+            top as i32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        ))
+    }
+}
+
 /// Metrics of text
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
 pub struct Metrics {
     /// Font size in pixels
     pub font_size: f32,
@@ -220,6 +608,73 @@ impl fmt::Display for Metrics {
     }
 }
 
+/// Settings of a [`Buffer`] that its lines are shaped with
+#[derive(Clone, Debug, Default)]
+struct ShapeSettings {
+    missing_glyph: Option<MissingGlyph>,
+    line_break_strictness: LineBreakStrictness,
+    vertical_forms: bool,
+    #[cfg(feature = "hyphenation")]
+    hyphenation: Option<String>,
+    #[cfg(feature = "shape-run-cache")]
+    shape_run_cache: crate::ShapeRunCacheMode,
+}
+
+impl ShapeSettings {
+    fn swap(&mut self, font_system: &mut FontSystem) {
+        mem::swap(&mut self.missing_glyph, &mut font_system.missing_glyph);
+        mem::swap(
+            &mut self.line_break_strictness,
+            &mut font_system.line_break_strictness,
+        );
+        mem::swap(&mut self.vertical_forms, &mut font_system.vertical_forms);
+        #[cfg(feature = "hyphenation")]
+        mem::swap(&mut self.hyphenation, &mut font_system.hyphenation);
+        #[cfg(feature = "shape-run-cache")]
+        mem::swap(
+            &mut self.shape_run_cache,
+            &mut font_system.shape_run_cache_mode,
+        );
+    }
+}
+
+/// A [`FontSystem`] with the [`ShapeSettings`] of a buffer swapped in, which are swapped back
+/// when it is dropped
+struct ShapeSettingsGuard<'a> {
+    font_system: &'a mut FontSystem,
+    settings: &'a mut ShapeSettings,
+}
+
+impl<'a> ShapeSettingsGuard<'a> {
+    fn new(font_system: &'a mut FontSystem, settings: &'a mut ShapeSettings) -> Self {
+        settings.swap(font_system);
+        Self {
+            font_system,
+            settings,
+        }
+    }
+}
+
+impl Deref for ShapeSettingsGuard<'_> {
+    type Target = FontSystem;
+
+    fn deref(&self) -> &FontSystem {
+        self.font_system
+    }
+}
+
+impl DerefMut for ShapeSettingsGuard<'_> {
+    fn deref_mut(&mut self) -> &mut FontSystem {
+        self.font_system
+    }
+}
+
+impl Drop for ShapeSettingsGuard<'_> {
+    fn drop(&mut self) {
+        self.settings.swap(self.font_system);
+    }
+}
+
 /// A buffer of text that is shaped and laid out
 #[derive(Debug)]
 pub struct Buffer {
@@ -233,6 +688,29 @@ pub struct Buffer {
     redraw: bool,
     wrap: Wrap,
     monospace_width: Option<f32>,
+    tabular_digits: bool,
+    hanging_punctuation: bool,
+    pseudo_locale: Option<PseudoLocale>,
+    #[cfg(feature = "nfc-normalization")]
+    nfc_normalization: bool,
+    #[cfg(feature = "nfc-normalization")]
+    nfc_text_opt: Option<crate::NfcText>,
+    line_ending_opt: Option<LineEnding>,
+    vertical_metrics: VerticalMetrics,
+    shape_settings: ShapeSettings,
+    max_lines: Option<usize>,
+    truncate: Truncate,
+    truncation_opt: Option<Truncation>,
+    vertical_align: VerticalAlign,
+    leading_trim: bool,
+    line_height_mode: LineHeight,
+    baseline_align: BaselineAlign,
+    writing_mode: WritingMode,
+    text_orientation: TextOrientation,
+    exclusions: Vec<Exclusion>,
+    line_width_opt: Option<LineWidth>,
+    columns: usize,
+    column_gap: f32,
 
     /// Scratch buffer for shaping and laying out.
     scratch: ShapeBuffer,
@@ -249,6 +727,29 @@ impl Clone for Buffer {
             redraw: self.redraw,
             wrap: self.wrap,
             monospace_width: self.monospace_width,
+            tabular_digits: self.tabular_digits,
+            hanging_punctuation: self.hanging_punctuation,
+            pseudo_locale: self.pseudo_locale,
+            #[cfg(feature = "nfc-normalization")]
+            nfc_normalization: self.nfc_normalization,
+            #[cfg(feature = "nfc-normalization")]
+            nfc_text_opt: self.nfc_text_opt.clone(),
+            line_ending_opt: self.line_ending_opt,
+            vertical_metrics: self.vertical_metrics,
+            shape_settings: self.shape_settings.clone(),
+            max_lines: self.max_lines,
+            truncate: self.truncate,
+            truncation_opt: self.truncation_opt.clone(),
+            vertical_align: self.vertical_align,
+            leading_trim: self.leading_trim,
+            line_height_mode: self.line_height_mode,
+            baseline_align: self.baseline_align,
+            writing_mode: self.writing_mode,
+            text_orientation: self.text_orientation,
+            exclusions: self.exclusions.clone(),
+            line_width_opt: self.line_width_opt.clone(),
+            columns: self.columns,
+            column_gap: self.column_gap,
             scratch: ShapeBuffer::default(),
         }
     }
@@ -278,6 +779,29 @@ impl Buffer {
             wrap: Wrap::WordOrGlyph,
             scratch: ShapeBuffer::default(),
             monospace_width: None,
+            tabular_digits: false,
+            hanging_punctuation: false,
+            pseudo_locale: None,
+            #[cfg(feature = "nfc-normalization")]
+            nfc_normalization: false,
+            #[cfg(feature = "nfc-normalization")]
+            nfc_text_opt: None,
+            line_ending_opt: None,
+            vertical_metrics: VerticalMetrics::default(),
+            shape_settings: ShapeSettings::default(),
+            max_lines: None,
+            truncate: Truncate::default(),
+            truncation_opt: None,
+            vertical_align: VerticalAlign::default(),
+            leading_trim: false,
+            line_height_mode: LineHeight::default(),
+            baseline_align: BaselineAlign::default(),
+            writing_mode: WritingMode::default(),
+            text_orientation: TextOrientation::default(),
+            exclusions: Vec::new(),
+            line_width_opt: None,
+            columns: 1,
+            column_gap: 0.0,
         }
     }
 
@@ -307,17 +831,18 @@ impl Buffer {
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
+        // Lines around exclusions or with line widths are laid out in order when needed, from the
+        // top of the text
+        let around_exclusions = self.around_exclusions();
+        let params = self.line_layout_params();
+        let font_system = &mut ShapeSettingsGuard::new(font_system, &mut self.shape_settings);
         for line in &mut self.lines {
             if line.shape_opt().is_some() {
                 line.reset_layout();
-                line.layout_in_buffer(
-                    &mut self.scratch,
-                    font_system,
-                    self.metrics.font_size,
-                    self.width,
-                    self.wrap,
-                    self.monospace_width,
-                );
+                if around_exclusions {
+                    continue;
+                }
+                line.layout_in_writing_mode(&mut self.scratch, font_system, &params);
             }
         }
 
@@ -436,11 +961,95 @@ impl Buffer {
             }
         }
 
+        if self.max_lines.is_some() || self.truncation_opt.is_some() {
+            self.truncate_lines(font_system);
+        }
+
         if old_scroll != self.scroll {
             self.redraw = true;
         }
     }
 
+    /// Lay out all lines and replace the layout lines that do not fit in `max_lines`, or the
+    /// last line shown if it is wider than the buffer, with a line ending in an ellipsis
+    fn truncate_lines(&mut self, font_system: &mut FontSystem) {
+        self.truncation_opt = None;
+        let Some(max_lines) = self.max_lines else {
+            return;
+        };
+        if self.writing_mode.is_vertical() {
+            return;
+        }
+
+        // Index of the first layout line of each line, counted across all lines
+        let mut layout_starts = Vec::with_capacity(self.lines.len());
+        let mut total_layout = 0;
+        for line_i in 0..self.lines.len() {
+            layout_starts.push(total_layout);
+            total_layout += self
+                .line_layout(font_system, line_i)
+                .map_or(0, |layout| layout.len());
+        }
+        if total_layout == 0 {
+            return;
+        }
+
+        // Layout lines shown before and after the line with the ellipsis
+        let shown = max_lines.clamp(1, total_layout);
+        let (before, after) = match self.truncate {
+            Truncate::End => (shown - 1, 0),
+            Truncate::Middle => (shown / 2, shown - 1 - shown / 2),
+            Truncate::Start => (0, shown - 1),
+        };
+        let hidden = before..total_layout - after;
+
+        let layout_line = |layout_global_i: usize| {
+            let line_i = layout_starts.partition_point(|&start| start <= layout_global_i) - 1;
+            let layout = self.lines[line_i].layout_opt().as_ref()?;
+            Some((line_i, layout.get(layout_global_i - layout_starts[line_i])?))
+        };
+        let head_opt = match self.truncate {
+            Truncate::End | Truncate::Middle => layout_line(hidden.start),
+            Truncate::Start => None,
+        };
+        let mut tail_opt = match self.truncate {
+            Truncate::End => None,
+            Truncate::Middle | Truncate::Start => layout_line(hidden.end - 1),
+        };
+        // Text after the ellipsis has to be in the same line as the text before it
+        if let (Some((head_line_i, _)), Some((tail_line_i, _))) = (head_opt, tail_opt) {
+            if head_line_i != tail_line_i {
+                tail_opt = None;
+            }
+        }
+
+        let overflows = [head_opt, tail_opt]
+            .into_iter()
+            .flatten()
+            .any(|(_, layout_line)| layout_line.w > self.width);
+        if total_layout <= max_lines && !overflows {
+            return;
+        }
+        let Some((line_i, _)) = head_opt.or(tail_opt) else {
+            return;
+        };
+
+        let layout = crate::truncate::ellipsize(
+            &mut self.scratch,
+            font_system,
+            &self.lines[line_i],
+            head_opt.map(|(_, layout_line)| layout_line),
+            tail_opt.map(|(_, layout_line)| layout_line),
+            self.metrics.font_size,
+            self.width,
+        );
+        self.truncation_opt = Some(Truncation {
+            hidden,
+            line_i,
+            layout,
+        });
+    }
+
     /// Convert a [`Cursor`] to a [`LayoutCursor`]
     pub fn layout_cursor(
         &mut self,
@@ -480,7 +1089,9 @@ impl Buffer {
         line_i: usize,
     ) -> Option<&ShapeLine> {
         let line = self.lines.get_mut(line_i)?;
-        Some(line.shape_in_buffer(&mut self.scratch, font_system))
+        let font_system = &mut ShapeSettingsGuard::new(font_system, &mut self.shape_settings);
+        let shape = line.shape_in_buffer(&mut self.scratch, font_system);
+        Some(shape)
     }
 
     /// Lay out the provided line index and return the result
@@ -489,15 +1100,88 @@ impl Buffer {
         font_system: &mut FontSystem,
         line_i: usize,
     ) -> Option<&[LayoutLine]> {
+        if self.around_exclusions() {
+            self.layout_around_exclusions(font_system, line_i);
+        }
+        self.line_layout_at(font_system, line_i, None)
+    }
+
+    /// Check if lines are laid out around exclusions or with line widths
+    fn around_exclusions(&self) -> bool {
+        (!self.exclusions.is_empty() || self.line_width_opt.is_some())
+            && !self.writing_mode.is_vertical()
+    }
+
+    /// Lay out the lines up to `last_line_i` around exclusions, again for lines that moved since
+    /// they were laid out
+    fn layout_around_exclusions(&mut self, font_system: &mut FontSystem, last_line_i: usize) {
+        let mut top = 0.0;
+        let mut first_line_i = 0;
+        for line_i in 0..=last_line_i.min(self.lines.len().saturating_sub(1)) {
+            let paragraph_attrs = self.lines[line_i].paragraph_attrs();
+            top += paragraph_attrs.space_before;
+            if self.lines[line_i].layout_top_opt() != Some(top) {
+                self.lines[line_i].reset_layout();
+            }
+            let layout_lines = self
+                .line_layout_at(font_system, line_i, Some((top, first_line_i)))
+                .map_or(0, |layout| layout.len());
+            top += layout_lines as f32 * self.line_height() + paragraph_attrs.space_after;
+            first_line_i += layout_lines;
+        }
+    }
+
+    /// Lay out a line, around exclusions if `top_opt` is the top of the line and the index of
+    /// its first visual line
+    fn line_layout_at(
+        &mut self,
+        font_system: &mut FontSystem,
+        line_i: usize,
+        top_opt: Option<(f32, usize)>,
+    ) -> Option<&[LayoutLine]> {
+        let params = self.line_layout_params();
+        let bands_opt = top_opt.map(|(top, first_line_i)| ExclusionBands {
+            exclusions: &self.exclusions,
+            line_width_opt: self.line_width_opt.as_ref(),
+            first_line_i,
+            top,
+            line_height: params.line_height,
+            width: params.width,
+        });
         let line = self.lines.get_mut(line_i)?;
-        Some(line.layout_in_buffer(
+        let font_system = &mut ShapeSettingsGuard::new(font_system, &mut self.shape_settings);
+        let layout = line.layout_in_writing_mode(
             &mut self.scratch,
             font_system,
-            self.metrics.font_size,
-            self.width,
-            self.wrap,
-            self.monospace_width,
-        ))
+            &LineLayoutParams {
+                bands_opt: bands_opt.as_ref(),
+                ..params
+            },
+        );
+        Some(layout)
+    }
+
+    /// Get the parameters that lines are laid out with, without exclusions
+    fn line_layout_params(&self) -> LineLayoutParams<'static> {
+        let (inline_size, _) = self.inline_block_size();
+        LineLayoutParams {
+            line_height: self.line_height(),
+            line_height_mode: self.line_height_mode,
+            baseline_align: self.baseline_align,
+            writing_mode: self.writing_mode,
+            text_orientation: self.text_orientation,
+            ..LineLayoutParams::new(
+                self.metrics.font_size,
+                inline_size,
+                self.wrap,
+                LayoutOptions {
+                    match_mono_width: self.monospace_width,
+                    tabular_digits: self.tabular_digits,
+                    hanging_punctuation: self.hanging_punctuation,
+                    ..LayoutOptions::new()
+                },
+            )
+        }
     }
 
     /// Get the current [`Metrics`]
@@ -505,6 +1189,67 @@ impl Buffer {
         self.metrics
     }
 
+    /// Get the distance between lines, from the [`Metrics`] and the [`LineHeight`]
+    ///
+    /// Lines with fonts that need more room are taller, see [`LayoutLine::extra_height`].
+    pub fn line_height(&self) -> f32 {
+        self.line_height_mode.resolve(self.metrics)
+    }
+
+    /// Get the current [`LineHeight`]
+    pub fn line_height_mode(&self) -> LineHeight {
+        self.line_height_mode
+    }
+
+    /// Set the [`LineHeight`], which resolves the height of each line from the [`Metrics`] and
+    /// the fonts of the line
+    ///
+    /// Lines are placed [`Buffer::line_height`] apart, with room added around lines whose
+    /// tallest font needs more for [`LineHeight::Normal`] and [`LineHeight::AtLeast`]. Vertical
+    /// lines are not given more room.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the resolved line height is zero.
+    pub fn set_line_height_mode(
+        &mut self,
+        font_system: &mut FontSystem,
+        line_height_mode: LineHeight,
+    ) {
+        if line_height_mode != self.line_height_mode {
+            assert_ne!(
+                line_height_mode.resolve(self.metrics),
+                0.0,
+                "line height cannot be 0"
+            );
+            self.line_height_mode = line_height_mode;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current [`BaselineAlign`]
+    pub fn baseline_align(&self) -> BaselineAlign {
+        self.baseline_align
+    }
+
+    /// Set the [`BaselineAlign`], which places the baseline of lines that mix fonts with
+    /// different ascents and descents
+    ///
+    /// The ascent and descent each line was placed with are in [`LayoutRun::ascent`] and
+    /// [`LayoutRun::descent`].
+    pub fn set_baseline_align(
+        &mut self,
+        font_system: &mut FontSystem,
+        baseline_align: BaselineAlign,
+    ) {
+        if baseline_align != self.baseline_align {
+            self.baseline_align = baseline_align;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
     /// Set the current [`Metrics`]
     ///
     /// # Panics
@@ -546,70 +1291,757 @@ impl Buffer {
         }
     }
 
-    /// Get the current buffer dimensions (width, height)
-    pub fn size(&self) -> (f32, f32) {
-        (self.width, self.height)
+    /// Get the current `tabular_digits`
+    pub fn tabular_digits(&self) -> bool {
+        self.tabular_digits
     }
 
-    /// Set the current buffer dimensions
-    pub fn set_size(&mut self, font_system: &mut FontSystem, width: f32, height: f32) {
-        self.set_metrics_and_size(font_system, self.metrics, width, height);
+    /// Set if ASCII digits should all be as wide as the widest digit of their font, so that
+    /// numbers keep their width as they change, even with fonts lacking the `tnum` feature
+    pub fn set_tabular_digits(&mut self, font_system: &mut FontSystem, tabular_digits: bool) {
+        if tabular_digits != self.tabular_digits {
+            self.tabular_digits = tabular_digits;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
     }
 
-    /// Set the current [`Metrics`] and buffer dimensions at the same time
-    ///
-    /// # Panics
+    /// Get the current `hanging_punctuation`
+    pub fn hanging_punctuation(&self) -> bool {
+        self.hanging_punctuation
+    }
+
+    /// Set if stops, commas, and closing quotes at the end of a line may hang past its width, so
+    /// that the edges of the text look aligned
     ///
-    /// Will panic if `metrics.font_size` is zero.
-    pub fn set_metrics_and_size(
+    /// Hanging punctuation is not counted when fitting words in a line or aligning it, except by
+    /// the breaks of `Wrap::Optimal`.
+    pub fn set_hanging_punctuation(
         &mut self,
         font_system: &mut FontSystem,
-        metrics: Metrics,
-        width: f32,
-        height: f32,
+        hanging_punctuation: bool,
     ) {
-        let clamped_width = width.max(0.0);
-        let clamped_height = height.max(0.0);
-
-        if metrics != self.metrics || clamped_width != self.width || clamped_height != self.height {
-            assert_ne!(metrics.font_size, 0.0, "font size cannot be 0");
-            self.metrics = metrics;
-            self.width = clamped_width;
-            self.height = clamped_height;
+        if hanging_punctuation != self.hanging_punctuation {
+            self.hanging_punctuation = hanging_punctuation;
             self.relayout(font_system);
             self.shape_until_scroll(font_system, false);
         }
     }
 
-    /// Get the current scroll location
-    pub fn scroll(&self) -> Scroll {
-        self.scroll
+    /// Get the current [`PseudoLocale`]
+    pub fn pseudo_locale(&self) -> Option<PseudoLocale> {
+        self.pseudo_locale
     }
 
-    /// Set the current scroll location
-    pub fn set_scroll(&mut self, scroll: Scroll) {
-        if scroll != self.scroll {
-            self.scroll = scroll;
+    /// Set the [`PseudoLocale`] used to transform text. `None` disables pseudo-localization.
+    ///
+    /// This only applies to text set after this call, by [`Buffer::set_text`] or
+    /// [`Buffer::set_rich_text`].
+    pub fn set_pseudo_locale(&mut self, pseudo_locale: Option<PseudoLocale>) {
+        self.pseudo_locale = pseudo_locale;
+    }
+
+    /// Check if text is converted to Unicode Normalization Form C
+    #[cfg(feature = "nfc-normalization")]
+    pub fn nfc_normalization(&self) -> bool {
+        self.nfc_normalization
+    }
+
+    /// Set if text is converted to Unicode Normalization Form C, so that decomposed text shows
+    /// and compares the same as precomposed text
+    ///
+    /// This only applies to text set after this call, by [`Buffer::set_text`], and to text
+    /// inserted by an [`crate::Editor`]. See [`Buffer::nfc_text`] to map offsets back to the
+    /// text that was set.
+    #[cfg(feature = "nfc-normalization")]
+    pub fn set_nfc_normalization(&mut self, nfc_normalization: bool) {
+        self.nfc_normalization = nfc_normalization;
+    }
+
+    /// Get the normalization of the text last set with [`Buffer::set_text`], if it was
+    /// normalized
+    ///
+    /// Its offsets are into the lines of the buffer joined by their line endings, until the text
+    /// is edited. See [`Buffer::cursor_to_original`].
+    #[cfg(feature = "nfc-normalization")]
+    pub fn nfc_text(&self) -> Option<&crate::NfcText> {
+        self.nfc_text_opt.as_ref()
+    }
+
+    /// Convert a cursor to a byte offset into the text last set with [`Buffer::set_text`],
+    /// before it was normalized
+    pub fn cursor_to_original(&self, cursor: Cursor) -> usize {
+        let mut offset = 0;
+        for line in self.lines.iter().take(cursor.line) {
+            offset += line.text().len() + line.ending().as_str().len();
+        }
+        if let Some(line) = self.lines.get(cursor.line) {
+            offset += cursor.index.min(line.text().len());
+        }
+        #[cfg(feature = "nfc-normalization")]
+        if let Some(nfc_text) = &self.nfc_text_opt {
+            return nfc_text.original_index(offset);
+        }
+        offset
+    }
+
+    /// Get the current [`VerticalMetrics`]
+    pub fn vertical_metrics(&self) -> VerticalMetrics {
+        self.vertical_metrics
+    }
+
+    /// Set the [`VerticalMetrics`] used to place the baseline in each line
+    ///
+    /// Use [`crate::Font::line_height_em`] to get a line height with the same convention.
+    pub fn set_vertical_metrics(&mut self, vertical_metrics: VerticalMetrics) {
+        if vertical_metrics != self.vertical_metrics {
+            self.vertical_metrics = vertical_metrics;
             self.redraw = true;
         }
     }
 
-    /// Get the number of lines that can be viewed in the buffer
-    pub fn visible_lines(&self) -> i32 {
-        (self.height / self.metrics.line_height) as i32
+    /// Get the current [`MissingGlyph`]
+    pub fn missing_glyph(&self) -> Option<MissingGlyph> {
+        self.shape_settings.missing_glyph
     }
 
-    /// Set text of buffer, using provided attributes for each line by default
-    pub fn set_text(
+    /// Set what replaces characters that no font supports, or None to use the `.notdef` glyph
+    /// of the first font
+    pub fn set_missing_glyph(
         &mut self,
         font_system: &mut FontSystem,
-        text: &str,
-        attrs: Attrs,
-        shaping: Shaping,
+        missing_glyph: Option<MissingGlyph>,
     ) {
-        self.lines.clear();
-        for (range, ending) in LineIter::new(text) {
-            self.lines.push(BufferLine::new(
+        if missing_glyph != self.shape_settings.missing_glyph {
+            self.shape_settings.missing_glyph = missing_glyph;
+            for line in self.lines.iter_mut() {
+                line.reset_shaping();
+            }
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current [`LineBreakStrictness`]
+    pub fn line_break_strictness(&self) -> LineBreakStrictness {
+        self.shape_settings.line_break_strictness
+    }
+
+    /// Set the [`LineBreakStrictness`] of the rules for breaking lines of Chinese and Japanese
+    /// text
+    pub fn set_line_break_strictness(
+        &mut self,
+        font_system: &mut FontSystem,
+        line_break_strictness: LineBreakStrictness,
+    ) {
+        if line_break_strictness != self.shape_settings.line_break_strictness {
+            self.shape_settings.line_break_strictness = line_break_strictness;
+            for line in self.lines.iter_mut() {
+                line.reset_shaping();
+            }
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the language of the hyphenation patterns used to break words
+    #[cfg(feature = "hyphenation")]
+    pub fn hyphenation(&self) -> Option<&str> {
+        self.shape_settings.hyphenation.as_deref()
+    }
+
+    /// Set the language tag, like `en-US`, of the hyphenation patterns used to break words that
+    /// wrap, or None to not break words with hyphens, see [`crate::Hyphenator`]
+    #[cfg(feature = "hyphenation")]
+    pub fn set_hyphenation(&mut self, font_system: &mut FontSystem, lang: Option<&str>) {
+        if lang != self.shape_settings.hyphenation.as_deref() {
+            self.shape_settings.hyphenation = lang.map(String::from);
+            for line in self.lines.iter_mut() {
+                line.reset_shaping();
+            }
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the number of clusters in the shaped lines with characters that no font supports,
+    /// which are drawn with the [`MissingGlyph`] if one is set
+    pub fn missing_glyphs(&self) -> usize {
+        self.lines
+            .iter()
+            .filter_map(|line| line.shape_opt().as_ref())
+            .map(|shape| shape.missing_glyphs())
+            .sum()
+    }
+
+    /// Get the current [`crate::ShapeRunCacheMode`]
+    #[cfg(feature = "shape-run-cache")]
+    pub fn shape_run_cache(&self) -> crate::ShapeRunCacheMode {
+        self.shape_settings.shape_run_cache
+    }
+
+    /// Set which shape run cache is used when shaping lines of this buffer
+    ///
+    /// Static labels benefit from the cache, while text that changes every frame only fills it
+    /// with runs that are never used again. Lines already shaped are kept.
+    #[cfg(feature = "shape-run-cache")]
+    pub fn set_shape_run_cache(&mut self, shape_run_cache: crate::ShapeRunCacheMode) {
+        self.shape_settings.shape_run_cache = shape_run_cache;
+    }
+
+    /// Get the maximum number of layout lines shown
+    pub fn max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    /// Set the maximum number of layout lines shown, or None to show all lines
+    ///
+    /// If the text needs more lines, or the line with the ellipsis is wider than the buffer, text
+    /// is elided with an ellipsis as set by [`Buffer::set_truncate`]. At least one line is shown.
+    /// All lines are laid out to find where text is elided, so this is meant for labels rather
+    /// than long documents. Text in vertical lines is not elided.
+    pub fn set_max_lines(&mut self, font_system: &mut FontSystem, max_lines: Option<usize>) {
+        if max_lines != self.max_lines {
+            self.max_lines = max_lines;
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current [`Truncate`] mode
+    pub fn truncate(&self) -> Truncate {
+        self.truncate
+    }
+
+    /// Set where text is elided when it does not fit in [`Buffer::set_max_lines`]
+    pub fn set_truncate(&mut self, font_system: &mut FontSystem, truncate: Truncate) {
+        if truncate != self.truncate {
+            self.truncate = truncate;
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current [`WritingMode`]
+    pub fn writing_mode(&self) -> WritingMode {
+        self.writing_mode
+    }
+
+    /// Set the [`WritingMode`], laying out lines as vertical columns if it is vertical
+    ///
+    /// Vertical lines wrap at the height of the buffer and are placed next to each other, one
+    /// line height apart. Glyphs are set upright or sideways by [`Self::set_text_orientation`],
+    /// and all text is shaped with the font's vertical alternates, as with
+    /// [`Attrs::vertical_forms`]. Spans with [`Attrs::combine_upright`] are set upright across
+    /// the line. Hit testing, cursor motion, and highlights follow the columns, see
+    /// [`Self::cursor_motion`] and [`LayoutRun::highlight`], while decorations from
+    /// [`Attrs::decoration`] are not drawn along vertical lines.
+    pub fn set_writing_mode(&mut self, font_system: &mut FontSystem, writing_mode: WritingMode) {
+        if writing_mode != self.writing_mode {
+            self.writing_mode = writing_mode;
+            if writing_mode.is_vertical() != self.shape_settings.vertical_forms {
+                self.shape_settings.vertical_forms = writing_mode.is_vertical();
+                for line in self.lines.iter_mut() {
+                    line.reset_shaping();
+                }
+            }
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current [`TextOrientation`]
+    pub fn text_orientation(&self) -> TextOrientation {
+        self.text_orientation
+    }
+
+    /// Set the [`TextOrientation`] of glyphs in vertical lines
+    ///
+    /// By default, characters of scripts like Latin are rotated 90 degrees clockwise while CJK
+    /// characters stay upright, by their [`crate::VerticalOrientation`].
+    pub fn set_text_orientation(
+        &mut self,
+        font_system: &mut FontSystem,
+        text_orientation: TextOrientation,
+    ) {
+        if text_orientation != self.text_orientation {
+            self.text_orientation = text_orientation;
+            if self.writing_mode.is_vertical() {
+                self.relayout(font_system);
+                self.shape_until_scroll(font_system, false);
+            }
+        }
+    }
+
+    /// Get the current [`VerticalAlign`]
+    pub fn vertical_align(&self) -> VerticalAlign {
+        self.vertical_align
+    }
+
+    /// Set the [`VerticalAlign`], moving the lines down to center them in the height of the
+    /// buffer or to place them at its bottom
+    ///
+    /// Lines taller than the buffer are placed at the top, so that they scroll as usual. Vertical
+    /// lines and lines in several columns are always placed at the top.
+    pub fn set_vertical_align(&mut self, vertical_align: VerticalAlign) {
+        if vertical_align != self.vertical_align {
+            self.vertical_align = vertical_align;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the distance that lines are moved down by to follow the [`VerticalAlign`]
+    fn vertical_align_offset(&self) -> f32 {
+        if self.vertical_align == VerticalAlign::Top
+            || self.writing_mode.is_vertical()
+            || self.column_count() > 1
+        {
+            return 0.0;
+        }
+
+        // Height of the laid out lines, with the same spacing as layout runs
+        let mut height = 0.0;
+        let mut layout_global_i = 0;
+        for line in &self.lines {
+            let Some(layout) = line.layout_opt() else {
+                break;
+            };
+            let paragraph_attrs = line.paragraph_attrs();
+            let mut shown = false;
+            for layout_line in layout {
+                let mut layout_line = layout_line;
+                if let Some(truncation) = &self.truncation_opt {
+                    if truncation.hidden.contains(&layout_global_i) {
+                        if layout_global_i != truncation.hidden.start {
+                            layout_global_i += 1;
+                            continue;
+                        }
+                        layout_line = &truncation.layout;
+                    }
+                }
+                layout_global_i += 1;
+                if !shown {
+                    height += paragraph_attrs.space_before;
+                    shown = true;
+                }
+                height += layout_line.ruby_height + layout_line.extra_height + self.line_height();
+            }
+            if shown {
+                height += paragraph_attrs.space_after;
+            }
+        }
+
+        let leading_trim = self.leading_trim_amounts();
+        height -= leading_trim.top + leading_trim.bottom;
+
+        let free = (self.height - height).max(0.0);
+        match self.vertical_align {
+            VerticalAlign::Top => 0.0,
+            VerticalAlign::Middle => free / 2.0,
+            VerticalAlign::Bottom => free,
+        }
+    }
+
+    /// Check if leading is trimmed from the top and the bottom of the text
+    pub fn leading_trim(&self) -> bool {
+        self.leading_trim
+    }
+
+    /// Set if the space above the cap height of the first line and below the baseline of the last
+    /// line is trimmed, so that the text lines up with a design grid
+    ///
+    /// Lines are moved up by [`LeadingTrim::top`] while the first line is shown. Lines without
+    /// glyphs, vertical lines, and a first line with ruby text are not trimmed.
+    pub fn set_leading_trim(&mut self, leading_trim: bool) {
+        if leading_trim != self.leading_trim {
+            self.leading_trim = leading_trim;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the space trimmed from the top and the bottom of the text, see
+    /// [`Buffer::set_leading_trim`]
+    pub fn leading_trim_amounts(&self) -> LeadingTrim {
+        if !self.leading_trim || self.writing_mode.is_vertical() {
+            return LeadingTrim::default();
+        }
+
+        let mut shown = self
+            .lines
+            .iter()
+            .map_while(|line| line.layout_opt().as_ref())
+            .flatten()
+            .enumerate()
+            .filter_map(
+                |(layout_global_i, layout_line)| match &self.truncation_opt {
+                    Some(truncation) if truncation.hidden.contains(&layout_global_i) => {
+                        (layout_global_i == truncation.hidden.start).then_some(&truncation.layout)
+                    }
+                    _ => Some(layout_line),
+                },
+            );
+        let Some(first) = shown.next() else {
+            return LeadingTrim::default();
+        };
+        let last = shown.last().unwrap_or(first);
+
+        // Space between the top of a line and its baseline, and its baseline and its bottom
+        let line_height = self.line_height();
+        let above_below = |layout_line: &LayoutLine| {
+            let (ascent, descent) =
+                layout_line.baseline_ascent_descent(self.vertical_metrics, self.baseline_align);
+            let half_leading = (line_height - ascent - descent + layout_line.extra_height) / 2.0;
+            (half_leading + ascent, half_leading + descent)
+        };
+        LeadingTrim {
+            top: if first.glyphs.is_empty() || first.ruby_height > 0.0 {
+                0.0
+            } else {
+                (above_below(first).0 - first.max_cap_height).max(0.0)
+            },
+            bottom: if last.glyphs.is_empty() {
+                0.0
+            } else {
+                above_below(last).1.max(0.0)
+            },
+        }
+    }
+
+    /// Get the distance that lines are moved up by to trim leading, if the first line is shown
+    fn leading_trim_top(&self) -> f32 {
+        if self.scroll == Scroll::default() {
+            self.leading_trim_amounts().top
+        } else {
+            0.0
+        }
+    }
+
+    /// Get the current exclusions
+    pub fn exclusions(&self) -> &[Exclusion] {
+        &self.exclusions
+    }
+
+    /// Set rectangles that text flows around, like floated images
+    ///
+    /// Each line is placed in the widest part of the width that no exclusion overlaps, so text
+    /// wraps next to exclusions on either side. A line next to exclusions that cover the whole
+    /// width is not narrowed. While there are exclusions, lines are laid out from the top of the
+    /// text, as the position of each line depends on the lines before it. Vertical lines do not
+    /// flow around exclusions.
+    pub fn set_exclusions(&mut self, font_system: &mut FontSystem, exclusions: Vec<Exclusion>) {
+        if exclusions != self.exclusions {
+            self.exclusions = exclusions;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the callback that gives the width of each visual line, if any
+    pub fn line_width(&self) -> Option<&LineWidth> {
+        self.line_width_opt.as_ref()
+    }
+
+    /// Set a callback that gives the width to wrap each visual line to, or `None` to wrap all
+    /// lines to the width of the buffer
+    ///
+    /// Visual lines are counted from the top of the text, and keep their left edge when they are
+    /// narrower than the buffer. Widths are limited to the width of the buffer. Like with
+    /// exclusions, lines are laid out from the top of the text, and vertical lines are not
+    /// affected.
+    pub fn set_line_width(
+        &mut self,
+        font_system: &mut FontSystem,
+        line_width_opt: Option<LineWidth>,
+    ) {
+        self.line_width_opt = line_width_opt;
+        self.relayout(font_system);
+        self.shape_until_scroll(font_system, false);
+    }
+
+    /// Get the size of the buffer along its lines and across them, which is the width and the
+    /// height unless lines are vertical
+    pub(crate) fn inline_block_size(&self) -> (f32, f32) {
+        if self.writing_mode.is_vertical() {
+            (self.height, self.width)
+        } else {
+            (self.column_width(), self.height)
+        }
+    }
+
+    /// Get the number of columns and the gap between them
+    pub fn columns(&self) -> (usize, f32) {
+        (self.columns, self.column_gap)
+    }
+
+    /// Set the number of columns that lines are distributed across, and the gap between them
+    ///
+    /// Lines wrap at the width of a column, and fill each column from the top to the height of
+    /// the buffer before moving to the next one, see [`LayoutRun::column`]. Scrolling moves
+    /// through the text of all columns at once. Vertical lines are not split into columns.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `columns` is zero.
+    pub fn set_columns(&mut self, font_system: &mut FontSystem, columns: usize, gap: f32) {
+        assert_ne!(columns, 0, "columns cannot be 0");
+        if columns != self.columns || gap != self.column_gap {
+            self.columns = columns;
+            self.column_gap = gap;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the width of each column, which is the width of the buffer if there is one column
+    pub fn column_width(&self) -> f32 {
+        let columns = self.column_count();
+        if columns == 1 {
+            self.width
+        } else {
+            let gaps = self.column_gap * (columns - 1) as f32;
+            ((self.width - gaps) / columns as f32).max(0.0)
+        }
+    }
+
+    /// Get the number of columns that lines are distributed across
+    fn column_count(&self) -> usize {
+        if self.writing_mode.is_vertical() {
+            1
+        } else {
+            self.columns
+        }
+    }
+
+    /// Get the current buffer dimensions (width, height)
+    pub fn size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Set the current buffer dimensions
+    pub fn set_size(&mut self, font_system: &mut FontSystem, width: f32, height: f32) {
+        self.set_metrics_and_size(font_system, self.metrics, width, height);
+    }
+
+    /// Set the current [`Metrics`] and buffer dimensions at the same time
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `metrics.font_size` is zero.
+    pub fn set_metrics_and_size(
+        &mut self,
+        font_system: &mut FontSystem,
+        metrics: Metrics,
+        width: f32,
+        height: f32,
+    ) {
+        let clamped_width = width.max(0.0);
+        let clamped_height = height.max(0.0);
+
+        if metrics != self.metrics || clamped_width != self.width || clamped_height != self.height {
+            assert_ne!(metrics.font_size, 0.0, "font size cannot be 0");
+            self.metrics = metrics;
+            self.width = clamped_width;
+            self.height = clamped_height;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Find the largest font size between `min_font_size` and `max_font_size` at which all text
+    /// fits inside `width` and `height`, and apply it along with the new size.
+    ///
+    /// The ratio between line height and font size of the current [`Metrics`] is preserved.
+    /// Shaping is independent of font size, so only layout is redone for each attempt. Text fits
+    /// if no line is wider than its column, the lines fill no more than the columns of one page,
+    /// and none are hidden by [`Buffer::set_max_lines`]. If the text does not fit even at
+    /// `min_font_size`, that size is used. Returns the applied [`Metrics`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `min_font_size` is zero.
+    pub fn shrink_to_fit(
+        &mut self,
+        font_system: &mut FontSystem,
+        width: f32,
+        height: f32,
+        min_font_size: f32,
+        max_font_size: f32,
+    ) -> Metrics {
+        // Stop searching when the candidate sizes are this close, in pixels
+        const TOLERANCE: f32 = 0.25;
+
+        let line_height_ratio = self.metrics.line_height / self.metrics.font_size;
+        let metrics_for = |font_size: f32| Metrics::new(font_size, font_size * line_height_ratio);
+
+        let mut fits = |buffer: &mut Self, font_size: f32| -> bool {
+            buffer.set_metrics_and_size(font_system, metrics_for(font_size), width, height);
+            let (layout_w, layout_h) = buffer.layout_size(font_system);
+            let (inline_size, block_size) = buffer.inline_block_size();
+            let fits_block = if buffer.column_count() > 1 {
+                buffer.paginate(font_system, block_size).len() <= 1
+            } else {
+                layout_h <= block_size
+            };
+            layout_w <= inline_size && fits_block && buffer.truncation_opt.is_none()
+        };
+
+        let mut low = min_font_size.min(max_font_size);
+        let mut high = max_font_size.max(min_font_size);
+        if !fits(self, high) {
+            while high - low > TOLERANCE {
+                let mid = (low + high) / 2.0;
+                if fits(self, mid) {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            self.set_metrics_and_size(font_system, metrics_for(low), width, height);
+        }
+
+        self.metrics
+    }
+
+    /// Lay out all lines and return the maximum line width and the total height
+    fn layout_size(&mut self, font_system: &mut FontSystem) -> (f32, f32) {
+        let mut layout_w: f32 = 0.0;
+        let mut layout_lines = 0;
+        let mut layout_space = 0.0;
+        for line_i in 0..self.lines.len() {
+            let paragraph_attrs = self.lines[line_i].paragraph_attrs();
+            layout_space += paragraph_attrs.space_before + paragraph_attrs.space_after;
+            if let Some(layout) = self.line_layout(font_system, line_i) {
+                layout_lines += layout.len();
+                for layout_line in layout {
+                    layout_w = layout_w.max(layout_line.w);
+                    layout_space += layout_line.ruby_height + layout_line.extra_height;
+                }
+            }
+        }
+        (
+            layout_w,
+            layout_lines as f32 * self.line_height() + layout_space,
+        )
+    }
+
+    /// Split the text into pages `height` tall, without splitting a layout line across pages
+    ///
+    /// All lines are laid out. Lines fill the columns of [`Buffer::set_columns`] before moving
+    /// to the next page, and a page holds at least one line even if it is taller than `height`.
+    /// Paragraph spacing is kept at the top of a page like at the top of the buffer when scrolled
+    /// to it, so a page is drawn by setting the scroll to [`PageRange::start`] and the height of
+    /// the buffer to `height`.
+    pub fn paginate(&mut self, font_system: &mut FontSystem, height: f32) -> Vec<PageRange> {
+        let line_height = self.line_height();
+        let columns = self.column_count();
+
+        let mut pages = Vec::new();
+        let mut page = PageRange::default();
+        let mut page_lines = 0;
+        let mut column = 0;
+        // Bottom of the previous line in the current column
+        let mut column_bottom = 0.0;
+        // Top of the next line if the text was not split into pages
+        let mut text_y = 0.0;
+        for line_i in 0..self.lines.len() {
+            let paragraph_attrs = self.lines[line_i].paragraph_attrs();
+            let extra_heights: Vec<(f32, f32)> =
+                self.line_layout(font_system, line_i)
+                    .map_or(Vec::new(), |layout| {
+                        layout
+                            .iter()
+                            .map(|layout_line| (layout_line.ruby_height, layout_line.extra_height))
+                            .collect()
+                    });
+            let layout_len = extra_heights.len();
+            for (layout_i, (ruby_height, extra_height)) in extra_heights.into_iter().enumerate() {
+                let space_before = if layout_i == 0 {
+                    paragraph_attrs.space_before
+                } else {
+                    0.0
+                } + ruby_height
+                    + extra_height / 2.0;
+                let space_after = extra_height / 2.0;
+                let mut line_top = column_bottom + space_before;
+                if page_lines > 0 && line_top + line_height + space_after > height {
+                    if column + 1 < columns {
+                        // Spacing is dropped at the top of a column, but not the room for ruby
+                        // and taller fonts
+                        column += 1;
+                        line_top = ruby_height + extra_height / 2.0;
+                    } else {
+                        page.end = Scroll::new(line_i, layout_i as i32);
+                        pages.push(page);
+                        page = PageRange {
+                            start: page.end,
+                            y: text_y,
+                            ..PageRange::default()
+                        };
+                        page_lines = 0;
+                        column = 0;
+                        line_top = space_before;
+                    }
+                }
+                column_bottom = line_top + line_height + space_after;
+                page.height = page.height.max(column_bottom);
+                page_lines += 1;
+                text_y += space_before + line_height + space_after;
+            }
+            if layout_len > 0 {
+                column_bottom += paragraph_attrs.space_after;
+                text_y += paragraph_attrs.space_after;
+            }
+        }
+        page.end = Scroll::new(self.lines.len(), 0);
+        pages.push(page);
+        pages
+    }
+
+    /// Get the current scroll location
+    pub fn scroll(&self) -> Scroll {
+        self.scroll
+    }
+
+    /// Set the current scroll location
+    pub fn set_scroll(&mut self, scroll: Scroll) {
+        if scroll != self.scroll {
+            self.scroll = scroll;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the number of lines that can be viewed in the buffer
+    pub fn visible_lines(&self) -> i32 {
+        (self.inline_block_size().1 / self.line_height()) as i32 * self.column_count() as i32
+    }
+
+    /// Set text of buffer, using provided attributes for each line by default
+    pub fn set_text(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+    ) {
+        #[cfg(feature = "nfc-normalization")]
+        let text = {
+            self.nfc_text_opt = self.nfc_normalization.then(|| crate::NfcText::new(text));
+            self.nfc_text_opt
+                .as_ref()
+                .map_or(text, crate::NfcText::text)
+        };
+        let pseudo_text;
+        let text = match self.pseudo_locale {
+            Some(pseudo_locale) => {
+                pseudo_text = pseudo_locale.transform(text);
+                &pseudo_text
+            }
+            None => text,
+        };
+
+        self.lines.clear();
+        for (range, ending) in LineIter::new(text) {
+            self.lines.push(BufferLine::new(
                 &text[range],
                 ending,
                 AttrsList::new(attrs),
@@ -655,7 +2087,193 @@ impl Buffer {
         I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
     {
         self.lines.clear();
+        #[cfg(feature = "nfc-normalization")]
+        {
+            self.nfc_text_opt = None;
+        }
+        self.push_rich_text(spans, default_attrs, shaping);
+        self.scroll = Scroll::default();
+        self.shape_until_scroll(font_system, false);
+    }
+
+    /// Set text of buffer, using an iterator of paragraphs, each with styled spans and
+    /// [`ParagraphAttrs`] for all of its lines
+    ///
+    /// Each paragraph starts a new line, even if the text before it does not end with one.
+    ///
+    /// ```
+    /// # use cosmic_text::{Align, Attrs, Buffer, FontSystem, Metrics, ParagraphAttrs, Shaping};
+    /// # let mut font_system = FontSystem::new();
+    /// let mut buffer = Buffer::new_empty(Metrics::new(32.0, 44.0));
+    /// let attrs = Attrs::new();
+    /// buffer.set_rich_paragraphs(
+    ///     &mut font_system,
+    ///     [
+    ///         ([("Title", attrs)], ParagraphAttrs::new().align(Align::Center)),
+    ///         ([("Body text", attrs)], ParagraphAttrs::new().indent(20.0)),
+    ///     ],
+    ///     attrs,
+    ///     Shaping::Advanced,
+    /// );
+    /// ```
+    pub fn set_rich_paragraphs<'r, 's, I, J>(
+        &mut self,
+        font_system: &mut FontSystem,
+        paragraphs: I,
+        default_attrs: Attrs,
+        shaping: Shaping,
+    ) where
+        I: IntoIterator<Item = (J, ParagraphAttrs)>,
+        J: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+    {
+        self.lines.clear();
+        #[cfg(feature = "nfc-normalization")]
+        {
+            self.nfc_text_opt = None;
+        }
+        for (spans, paragraph_attrs) in paragraphs {
+            let start = self.lines.len();
+            self.push_rich_text(spans, default_attrs, shaping);
+            for line in self.lines[start..].iter_mut() {
+                line.set_paragraph_attrs(paragraph_attrs);
+            }
+        }
+        if self.lines.is_empty() {
+            self.lines.push(BufferLine::new(
+                "",
+                LineEnding::default(),
+                AttrsList::new(default_attrs),
+                shaping,
+            ));
+        }
+        self.scroll = Scroll::default();
+        self.shape_until_scroll(font_system, false);
+    }
+
+    /// Set text of buffer from [`RichText`], see [`Buffer::set_rich_paragraphs`]
+    pub fn set_rich(
+        &mut self,
+        font_system: &mut FontSystem,
+        rich_text: &RichText,
+        shaping: Shaping,
+    ) {
+        self.set_rich_paragraphs(
+            font_system,
+            rich_text.paragraphs(),
+            rich_text.default_attrs(),
+            shaping,
+        );
+    }
+
+    /// Set text of buffer from Markdown, see [`RichText::from_markdown`]
+    #[cfg(feature = "markdown")]
+    pub fn set_markdown(
+        &mut self,
+        font_system: &mut FontSystem,
+        markdown: &str,
+        attrs: Attrs,
+        style: &crate::MarkdownStyle,
+        shaping: Shaping,
+    ) {
+        let rich_text = RichText::from_markdown(markdown, attrs, style);
+        self.set_rich(font_system, &rich_text, shaping);
+    }
+
+    /// Set text of buffer from `BBCode`, see [`RichText::from_bbcode`]
+    pub fn set_bbcode(
+        &mut self,
+        font_system: &mut FontSystem,
+        bbcode: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+    ) {
+        let rich_text = RichText::from_bbcode(bbcode, attrs);
+        self.set_rich(font_system, &rich_text, shaping);
+    }
+
+    /// Set text of buffer from Pango-style markup, see [`RichText::from_markup`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MarkupError`] if the markup is malformed, leaving the buffer unchanged.
+    pub fn set_markup(
+        &mut self,
+        font_system: &mut FontSystem,
+        markup: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+    ) -> Result<(), MarkupError> {
+        let rich_text = RichText::from_markup(markup, attrs)?;
+        self.set_rich(font_system, &rich_text, shaping);
+        Ok(())
+    }
+
+    /// Set [`ParagraphAttrs`] of all lines
+    pub fn set_paragraph_attrs(
+        &mut self,
+        font_system: &mut FontSystem,
+        paragraph_attrs: ParagraphAttrs,
+    ) {
+        let mut reset = false;
+        for line in self.lines.iter_mut() {
+            reset |= line.set_paragraph_attrs(paragraph_attrs);
+        }
+        if reset {
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Set the spans of a layer of the attributes of all lines, given as
+    /// `(line_i, range, attrs_override)`, see [`AttrsList::add_layer_span`]
+    ///
+    /// Lines without spans have the layer cleared. Only lines where the layer changed are shaped
+    /// again, so layers such as a selection or diagnostics may change without changing the
+    /// spans of the text below them.
+    pub fn set_attrs_layer<I>(&mut self, font_system: &mut FontSystem, layer: usize, spans: I)
+    where
+        I: IntoIterator<Item = (usize, Range<usize>, AttrsOverride)>,
+    {
+        let mut line_spans: Vec<Vec<(Range<usize>, AttrsOverride)>> =
+            vec![Vec::new(); self.lines.len()];
+        for (line_i, range, attrs_override) in spans {
+            if let Some(spans) = line_spans.get_mut(line_i) {
+                spans.push((range, attrs_override));
+            }
+        }
+
+        let mut reset = false;
+        for (line, spans) in self.lines.iter_mut().zip(line_spans) {
+            reset |= line.set_attrs_layer(layer, spans);
+        }
+        if reset {
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Append lines of styled spans, transformed by the pseudo-locale if one is set
+    fn push_rich_text<'r, 's, I>(&mut self, spans: I, default_attrs: Attrs, shaping: Shaping)
+    where
+        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+    {
+        match self.pseudo_locale {
+            Some(pseudo_locale) => {
+                let spans = pseudo_locale.transform_spans(spans);
+                self.push_rich_text_inner(
+                    spans.iter().map(|(text, attrs)| (text.as_str(), *attrs)),
+                    default_attrs,
+                    shaping,
+                );
+            }
+            None => self.push_rich_text_inner(spans, default_attrs, shaping),
+        }
+    }
 
+    fn push_rich_text_inner<'r, 's, I>(&mut self, spans: I, default_attrs: Attrs, shaping: Shaping)
+    where
+        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+    {
         let mut attrs_list = AttrsList::new(default_attrs);
         let mut line_string = String::new();
         let mut end = 0;
@@ -671,23 +2289,42 @@ impl Buffer {
         let mut spans_iter = spans_data.into_iter();
         let mut maybe_span = spans_iter.next();
 
-        // split the string into lines, as ranges
-        let string_start = string.as_ptr() as usize;
-        let mut lines_iter = BidiParagraphs::new(&string).map(|line: &str| {
-            let start = line.as_ptr() as usize - string_start;
-            let end = start + line.len();
-            start..end
-        });
+        // split the string into lines, as ranges with the line ending that follows them
+        let mut lines = Vec::new();
+        let mut paragraphs = BidiParagraphs::new(&string);
+        while let Some((range, _rtl)) = paragraphs.next_range() {
+            let after = &string[range.end..];
+            let ending = match after.chars().next() {
+                None => LineEnding::None,
+                Some('\r') if after.starts_with("\r\n") => LineEnding::CrLf,
+                Some('\r') => LineEnding::Cr,
+                // `\n\r\n` is `\n` followed by `\r\n`, which ends a paragraph of its own
+                Some('\n') if after.starts_with("\n\r") && !after.starts_with("\n\r\n") => {
+                    // skip the empty paragraph ended by the `\r`
+                    paragraphs.next_range();
+                    LineEnding::LfCr
+                }
+                Some('\n') => LineEnding::Lf,
+                Some('\u{85}') => LineEnding::Nel,
+                Some('\u{2029}') => LineEnding::ParagraphSeparator,
+                Some('\u{1C}') => LineEnding::FileSeparator,
+                Some('\u{1D}') => LineEnding::GroupSeparator,
+                Some('\u{1E}') => LineEnding::RecordSeparator,
+                Some(_) => LineEnding::default(),
+            };
+            lines.push((range, ending));
+        }
+        let mut lines_iter = lines.into_iter();
         let mut maybe_line = lines_iter.next();
-        //TODO: set this based on information from spans
-        let line_ending = LineEnding::default();
 
         loop {
-            let (Some(line_range), Some((attrs, span_range))) = (&maybe_line, &maybe_span) else {
+            let (Some((line_range, line_ending)), Some((attrs, span_range))) =
+                (&maybe_line, &maybe_span)
+            else {
                 // this is reached only if this text is empty
                 self.lines.push(BufferLine::new(
                     String::new(),
-                    line_ending,
+                    LineEnding::default(),
                     AttrsList::new(default_attrs),
                     shaping,
                 ));
@@ -716,6 +2353,7 @@ impl Buffer {
             if span_range.end < line_range.end {
                 maybe_span = spans_iter.next();
             } else {
+                let line_ending = *line_ending;
                 maybe_line = lines_iter.next();
                 if maybe_line.is_some() {
                     // finalize this line and start a new line
@@ -734,10 +2372,81 @@ impl Buffer {
                 }
             }
         }
+    }
+
+    /// Get the line ending used by [`Self::text`], or `None` if each line keeps its own
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        self.line_ending_opt
+    }
+
+    /// Set the line ending used by [`Self::text`], or `None` to keep the line ending of each line
+    ///
+    /// Editors can keep the line endings of a loaded document by setting this to
+    /// [`LineEnding::detect`] of its text when it is uniform, or to `None` when it is mixed.
+    pub fn set_line_ending(&mut self, line_ending_opt: Option<LineEnding>) {
+        self.line_ending_opt = line_ending_opt;
+    }
+
+    /// Get the text of all lines, joined by their line endings
+    ///
+    /// If a line ending is set with [`Self::set_line_ending`], it replaces the ending of every
+    /// line that has one. Lines other than the last without an ending, such as those split by
+    /// editing, are ended with the default line ending.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for (line_i, line) in self.lines.iter().enumerate() {
+            text.push_str(line.text());
+            text.push_str(self.text_ending(line_i).as_str());
+        }
+        text
+    }
+
+    /// Line ending written after a line by [`Self::text`]
+    fn text_ending(&self, line_i: usize) -> LineEnding {
+        let mut ending = self.lines[line_i].ending();
+        if let Some(line_ending) = self.line_ending_opt {
+            if ending != LineEnding::None {
+                ending = line_ending;
+            }
+        }
+        if ending == LineEnding::None && line_i + 1 < self.lines.len() {
+            ending = self.line_ending_opt.unwrap_or_default();
+        }
+        ending
+    }
 
-        self.scroll = Scroll::default();
+    /// Convert a cursor to an offset in UTF-16 code units into the text of [`Self::text`]
+    ///
+    /// The maps of lines are cached, see [`BufferLine::utf16_map`].
+    pub fn cursor_to_utf16(&mut self, cursor: Cursor) -> usize {
+        let mut offset = 0;
+        for line_i in 0..cursor.line.min(self.lines.len()) {
+            offset += self.lines[line_i].utf16_map().len_utf16();
+            offset += self.text_ending(line_i).as_str().len();
+        }
+        if let Some(line) = self.lines.get_mut(cursor.line) {
+            offset += line.utf16_map().to_utf16(cursor.index);
+        }
+        offset
+    }
 
-        self.shape_until_scroll(font_system, false);
+    /// Convert an offset in UTF-16 code units into the text of [`Self::text`] to a cursor
+    ///
+    /// Offsets inside of a line ending map to the end of the line before it, and offsets past
+    /// the end of the text map to the end of the last line.
+    pub fn cursor_from_utf16(&mut self, offset: usize) -> Cursor {
+        let mut start = 0;
+        let lines_len = self.lines.len();
+        for line_i in 0..lines_len {
+            let ending_len = self.text_ending(line_i).as_str().len();
+            let map = self.lines[line_i].utf16_map();
+            let end = start + map.len_utf16();
+            if offset < end + ending_len || line_i + 1 == lines_len {
+                return Cursor::new(line_i, map.to_utf8(offset.saturating_sub(start)));
+            }
+            start = end + ending_len;
+        }
+        Cursor::new(0, 0)
     }
 
     /// True if a redraw is needed
@@ -755,17 +2464,87 @@ impl Buffer {
         LayoutRunIter::new(self)
     }
 
+    /// Group the glyphs of the visible layout runs by font, font size, color, and fill, so that
+    /// renderers can draw each group without switching state
+    ///
+    /// Batches are ordered by the first appearance of their glyphs. Placeholders are left out,
+    /// see [`LayoutRun::placeholders`].
+    pub fn glyph_batches(&self) -> Vec<GlyphBatch> {
+        let mut batches: Vec<GlyphBatch> = Vec::new();
+        let mut batch_indices = HashMap::default();
+        let mut logical_i = 0;
+        for run in self.layout_runs() {
+            let ruby_glyphs = run.ruby.iter().flat_map(|ruby| ruby.glyphs.iter());
+            let extra_glyphs = ruby_glyphs.chain(run.drop_cap).chain(run.marker);
+            for glyph in run.glyphs.iter().chain(extra_glyphs) {
+                if glyph.has_placeholder {
+                    logical_i += 1;
+                    continue;
+                }
+                let key = (
+                    glyph.font_id,
+                    glyph.font_size.to_bits(),
+                    glyph.color_opt,
+                    glyph.fill_id,
+                );
+                let batch_i = *batch_indices.entry(key).or_insert_with(|| {
+                    batches.push(GlyphBatch {
+                        font_id: glyph.font_id,
+                        font_size: glyph.font_size,
+                        color_opt: glyph.color_opt,
+                        fill_id: glyph.fill_id,
+                        glyphs: Vec::new(),
+                    });
+                    batches.len() - 1
+                });
+                batches[batch_i].glyphs.push(BatchGlyph {
+                    glyph: glyph.clone(),
+                    line_i: run.line_i,
+                    line_x: run.line_x,
+                    line_y: run.line_y,
+                    logical_i,
+                });
+                logical_i += 1;
+            }
+        }
+        batches
+    }
+
     /// Convert x, y position to Cursor (hit detection)
     pub fn hit(&self, x: f32, y: f32) -> Option<Cursor> {
+        if self.writing_mode.is_vertical() {
+            return self.hit_vertical(x, y);
+        }
+
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
         let font_size = self.metrics.font_size;
-        let line_height = self.metrics.line_height;
+        let line_height = self.line_height();
+
+        // Find the column at x, then the position within it
+        let column_step = self.column_width() + self.column_gap;
+        let column = if column_step > 0.0 {
+            ((x / column_step).max(0.0) as usize).min(self.column_count() - 1)
+        } else {
+            0
+        };
+        let x = x - column as f32 * column_step;
+        if column > 0 && !self.layout_runs().any(|run| run.column == column) {
+            // Past the end of the text
+            let run = self.layout_runs().last()?;
+            return Some(match run.glyphs.last() {
+                Some(glyph) => run.cursor_from_glyph_right(glyph),
+                None => Cursor::new(run.line_i, 0),
+            });
+        }
 
         let mut new_cursor_opt = None;
 
-        let mut runs = self.layout_runs().peekable();
+        let mut runs = self
+            .layout_runs()
+            .filter(|run| run.column == column)
+            .peekable();
         let mut first_run = true;
         while let Some(run) = runs.next() {
             let line_y = run.line_y;
@@ -856,8 +2635,127 @@ impl Buffer {
         new_cursor_opt
     }
 
+    /// Convert x, y position to Cursor in vertical lines, choosing the closest line and the
+    /// closest side of the glyph at `y`
+    fn hit_vertical(&self, x: f32, y: f32) -> Option<Cursor> {
+        let run = self
+            .layout_runs()
+            .min_by(|a, b| (a.line_x - x).abs().total_cmp(&(b.line_x - x).abs()))?;
+
+        let mut cursor = Cursor::new(run.line_i, 0);
+        if let Some(glyph) = run.glyphs.iter().find(|glyph| y < glyph.y + glyph.w) {
+            if y >= glyph.y + glyph.w / 2.0 {
+                cursor = run.cursor_from_glyph_right(glyph);
+            } else {
+                cursor = run.cursor_from_glyph_left(glyph);
+            }
+        } else if let Some(glyph) = run.glyphs.last() {
+            cursor = run.cursor_from_glyph_right(glyph);
+        }
+        Some(cursor)
+    }
+
+    /// Find the link from [`Attrs::link`] under the x, y position, with the rectangles covering
+    /// all of its text, or `None` if there is no linked glyph at the position
+    ///
+    /// Neighboring characters with the same link target are part of the same link. Only
+    /// horizontal lines are hit tested.
+    pub fn hit_link(&self, x: f32, y: f32) -> Option<LinkHit<'_>> {
+        if self.writing_mode.is_vertical() {
+            return None;
+        }
+
+        let line_height = self.line_height();
+        let (line_i, index) = self.layout_runs().find_map(|run| {
+            if y < run.line_top || y >= run.line_top + line_height {
+                return None;
+            }
+            let glyph = run.glyphs.iter().find(|glyph| {
+                let glyph_x = run.line_x + glyph.x;
+                x >= glyph_x && x < glyph_x + glyph.w
+            })?;
+            Some((run.line_i, glyph.start))
+        })?;
+        let line = &self.lines[line_i];
+        let attrs_list = line.attrs_list();
+        let link = attrs_list.get_span(index).link_opt?;
+
+        let text = line.text();
+        let is_link = |i: usize| attrs_list.get_span(i).link_opt == Some(link);
+        let start = text[..index]
+            .char_indices()
+            .rev()
+            .take_while(|&(i, _)| is_link(i))
+            .last()
+            .map_or(index, |(i, _)| i);
+        let end = text[index..]
+            .char_indices()
+            .find(|&(i, _)| !is_link(index + i))
+            .map_or(text.len(), |(i, _)| index + i);
+        let range = start..end;
+
+        // Glyphs of the link next to each other in a run share a rectangle
+        let mut rects: Vec<LinkRect> = Vec::new();
+        for run in self.layout_runs().filter(|run| run.line_i == line_i) {
+            let mut glyphs: Vec<&LayoutGlyph> = run.glyphs.iter().collect();
+            glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut extend = false;
+            for glyph in glyphs {
+                if !range.contains(&glyph.start) {
+                    extend = false;
+                    continue;
+                }
+                let x = run.line_x + glyph.x;
+                match rects.last_mut() {
+                    Some(rect) if extend => rect.w = rect.w.max(x + glyph.w - rect.x),
+                    _ => rects.push(LinkRect {
+                        x,
+                        y: run.line_top,
+                        w: glyph.w,
+                        h: line_height,
+                    }),
+                }
+                extend = true;
+            }
+        }
+
+        Some(LinkHit {
+            link,
+            line_i,
+            range,
+            rects,
+        })
+    }
+
     /// Apply a [`Motion`] to a [`Cursor`]
+    ///
+    /// In vertical lines, [`Motion::Up`] and [`Motion::Down`] move along the line, while
+    /// [`Motion::Left`] and [`Motion::Right`] move to the line on that side, see
+    /// [`Self::set_writing_mode`].
     pub fn cursor_motion(
+        &mut self,
+        font_system: &mut FontSystem,
+        cursor: Cursor,
+        cursor_x_opt: Option<i32>,
+        motion: Motion,
+    ) -> Option<(Cursor, Option<i32>)> {
+        let motion =
+            match (self.writing_mode, motion) {
+                (WritingMode::HorizontalTb, motion) => motion,
+                (_, Motion::Up) => Motion::Previous,
+                (_, Motion::Down) => Motion::Next,
+                (WritingMode::VerticalRl, Motion::Left)
+                | (WritingMode::VerticalLr, Motion::Right) => Motion::Down,
+                (WritingMode::VerticalRl, Motion::Right)
+                | (WritingMode::VerticalLr, Motion::Left) => Motion::Up,
+                (_, motion) => motion,
+            };
+        self.line_cursor_motion(font_system, cursor, cursor_x_opt, motion)
+    }
+
+    /// Apply a [`Motion`] to a [`Cursor`], with [`Motion::Up`] and [`Motion::Down`] moving to
+    /// the layout line before or after the cursor
+    fn line_cursor_motion(
         &mut self,
         font_system: &mut FontSystem,
         mut cursor: Cursor,
@@ -941,10 +2839,14 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) =
-                            self.cursor_motion(font_system, cursor, cursor_x_opt, Motion::Next)?;
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
+                            font_system,
+                            cursor,
+                            cursor_x_opt,
+                            Motion::Next,
+                        )?;
                     } else {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
                             font_system,
                             cursor,
                             cursor_x_opt,
@@ -959,15 +2861,19 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
                             font_system,
                             cursor,
                             cursor_x_opt,
                             Motion::Previous,
                         )?;
                     } else {
-                        (cursor, cursor_x_opt) =
-                            self.cursor_motion(font_system, cursor, cursor_x_opt, Motion::Next)?;
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
+                            font_system,
+                            cursor,
+                            cursor_x_opt,
+                            Motion::Next,
+                        )?;
                     }
                 }
             }
@@ -991,7 +2897,7 @@ impl Buffer {
                     layout_cursor.glyph = cursor_x as usize; //TODO: glyph x position
                 }
 
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, cursor_x_opt) = self.line_cursor_motion(
                     font_system,
                     cursor,
                     cursor_x_opt,
@@ -1020,7 +2926,7 @@ impl Buffer {
                     layout_cursor.glyph = cursor_x as usize; //TODO: glyph x position
                 }
 
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, cursor_x_opt) = self.line_cursor_motion(
                     font_system,
                     cursor,
                     cursor_x_opt,
@@ -1032,7 +2938,7 @@ impl Buffer {
                 layout_cursor.glyph = 0;
                 #[allow(unused_assignments)]
                 {
-                    (cursor, cursor_x_opt) = self.cursor_motion(
+                    (cursor, cursor_x_opt) = self.line_cursor_motion(
                         font_system,
                         cursor,
                         cursor_x_opt,
@@ -1056,7 +2962,7 @@ impl Buffer {
                 layout_cursor.glyph = usize::max_value();
                 #[allow(unused_assignments)]
                 {
-                    (cursor, cursor_x_opt) = self.cursor_motion(
+                    (cursor, cursor_x_opt) = self.line_cursor_motion(
                         font_system,
                         cursor,
                         cursor_x_opt,
@@ -1074,19 +2980,19 @@ impl Buffer {
                 cursor_x_opt = None;
             }
             Motion::PageUp => {
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, cursor_x_opt) = self.line_cursor_motion(
                     font_system,
                     cursor,
                     cursor_x_opt,
-                    Motion::Vertical(-self.size().1 as i32),
+                    Motion::Vertical(-self.inline_block_size().1 as i32),
                 )?;
             }
             Motion::PageDown => {
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, cursor_x_opt) = self.line_cursor_motion(
                     font_system,
                     cursor,
                     cursor_x_opt,
-                    Motion::Vertical(self.size().1 as i32),
+                    Motion::Vertical(self.inline_block_size().1 as i32),
                 )?;
             }
             Motion::Vertical(px) => {
@@ -1095,13 +3001,17 @@ impl Buffer {
                 match lines.cmp(&0) {
                     cmp::Ordering::Less => {
                         for _ in 0..-lines {
-                            (cursor, cursor_x_opt) =
-                                self.cursor_motion(font_system, cursor, cursor_x_opt, Motion::Up)?;
+                            (cursor, cursor_x_opt) = self.line_cursor_motion(
+                                font_system,
+                                cursor,
+                                cursor_x_opt,
+                                Motion::Up,
+                            )?;
                         }
                     }
                     cmp::Ordering::Greater => {
                         for _ in 0..lines {
-                            (cursor, cursor_x_opt) = self.cursor_motion(
+                            (cursor, cursor_x_opt) = self.line_cursor_motion(
                                 font_system,
                                 cursor,
                                 cursor_x_opt,
@@ -1149,14 +3059,14 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
                             font_system,
                             cursor,
                             cursor_x_opt,
                             Motion::NextWord,
                         )?;
                     } else {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
                             font_system,
                             cursor,
                             cursor_x_opt,
@@ -1171,14 +3081,14 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
                             font_system,
                             cursor,
                             cursor_x_opt,
                             Motion::PreviousWord,
                         )?;
                     } else {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, cursor_x_opt) = self.line_cursor_motion(
                             font_system,
                             cursor,
                             cursor_x_opt,
@@ -1200,7 +3110,7 @@ impl Buffer {
             Motion::GotoLine(line) => {
                 let mut layout_cursor = self.layout_cursor(font_system, cursor)?;
                 layout_cursor.line = line;
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, cursor_x_opt) = self.line_cursor_motion(
                     font_system,
                     cursor,
                     cursor_x_opt,
@@ -1218,19 +3128,156 @@ impl Buffer {
         font_system: &mut FontSystem,
         cache: &mut crate::SwashCache,
         color: Color,
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_inner(font_system, cache, color, None, f);
+    }
+
+    /// Draw the part of the buffer inside `clip`
+    ///
+    /// Lines and glyphs outside of it are skipped without rasterizing them, and rectangles are
+    /// cut at its edges, so drawing a small viewport of a large buffer only costs the visible
+    /// text.
+    #[cfg(feature = "swash")]
+    pub fn draw_clipped<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        clip: ClipRect,
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_inner(font_system, cache, color, Some(clip), f);
+    }
+
+    #[cfg(feature = "swash")]
+    fn draw_inner<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        clip_opt: Option<ClipRect>,
         mut f: F,
     ) where
         F: FnMut(i32, i32, u32, u32, Color),
     {
+        let mut f = |x: i32, y: i32, w: u32, h: u32, color: Color| match clip_opt {
+            Some(clip) => {
+                if let Some((x, y, w, h)) = clip.intersect(x, y, w, h) {
+                    f(x, y, w, h, color);
+                }
+            }
+            None => f(x, y, w, h, color),
+        };
+
+        // Glyphs may extend past their line and their advance, by up to about the font size
+        let margin = self.metrics.font_size;
+        let hex_boxes = font_system.last_resort() == crate::LastResort::HexBox;
+        let vertical = self.writing_mode.is_vertical();
+        let line_height = self.line_height();
+        let columns = self.column_count();
         for run in self.layout_runs() {
+            if let Some(clip) = clip_opt {
+                if vertical {
+                    if run.line_x + line_height / 2.0 + margin <= clip.x as f32
+                        || run.line_x - line_height / 2.0 - margin >= clip.x as f32 + clip.w as f32
+                    {
+                        continue;
+                    }
+                } else {
+                    if run.line_top - margin >= clip.y as f32 + clip.h as f32 {
+                        // The next column starts again from the top
+                        if columns == 1 {
+                            break;
+                        }
+                        continue;
+                    }
+                    if run.line_top + line_height + margin <= clip.y as f32 {
+                        continue;
+                    }
+                }
+            }
+
+            // Backgrounds are only drawn behind horizontal lines
+            if !vertical {
+                for rect in run.backgrounds(line_height) {
+                    f(
+                        rect.x as i32,
+                        rect.y as i32,
+                        rect.w as u32,
+                        rect.h as u32,
+                        rect.color,
+                    );
+                }
+            }
+
+            // Shadows are drawn under all the glyphs of the line
+            for glyph in run.glyphs.iter() {
+                let Some(shadow) = glyph.shadow_opt() else {
+                    continue;
+                };
+                if glyph.has_placeholder {
+                    continue;
+                }
+                let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
+                cache.with_shadow_pixels(
+                    font_system,
+                    physical_glyph.cache_key,
+                    shadow,
+                    |x, y, color| {
+                        f(
+                            physical_glyph.x + x,
+                            run.line_y as i32 + physical_glyph.y + y,
+                            1,
+                            1,
+                            color,
+                        );
+                    },
+                );
+            }
+
             for glyph in run.glyphs.iter() {
-                let physical_glyph = glyph.physical((0., 0.), 1.0);
+                // Placeholders are drawn by the caller
+                if glyph.has_placeholder {
+                    continue;
+                }
+                if let Some(clip) = clip_opt {
+                    // Glyphs of vertical lines are placed along y
+                    let (start, end, clip_start, clip_end) = if vertical {
+                        (glyph.y, glyph.y + glyph.w, clip.y, clip.y + clip.h as i32)
+                    } else {
+                        let x = run.line_x + glyph.x;
+                        (x, x + glyph.w, clip.x, clip.x + clip.w as i32)
+                    };
+                    if end + margin <= clip_start as f32 || start - margin >= clip_end as f32 {
+                        continue;
+                    }
+                }
+
+                let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
 
                 let glyph_color = match glyph.color_opt {
                     Some(some) => some,
                     None => color,
                 };
 
+                if hex_boxes
+                    && crate::draw_glyph_hex_box(
+                        run.text,
+                        glyph,
+                        &physical_glyph,
+                        run.line_y,
+                        glyph_color,
+                        &mut f,
+                    )
+                {
+                    continue;
+                }
+
                 cache.with_pixels(
                     font_system,
                     physical_glyph.cache_key,
@@ -1246,6 +3293,32 @@ impl Buffer {
                     },
                 );
             }
+
+            // Ruby text, drop caps, and list markers are drawn like the glyphs of the line
+            let ruby_glyphs = run.ruby.iter().flat_map(|ruby| ruby.glyphs.iter());
+            for glyph in ruby_glyphs.chain(run.drop_cap).chain(run.marker) {
+                let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
+                cache.with_pixels(
+                    font_system,
+                    physical_glyph.cache_key,
+                    glyph.color_opt.unwrap_or(color),
+                    |x, y, color| {
+                        f(
+                            physical_glyph.x + x,
+                            run.line_y as i32 + physical_glyph.y + y,
+                            1,
+                            1,
+                            color,
+                        );
+                    },
+                );
+            }
+
+            for mut span in run.decorations(font_system) {
+                let span_color = span.color_opt.unwrap_or(color);
+                span.x += run.line_x;
+                crate::draw_decoration(&span, run.line_y, |x, y, w, h| f(x, y, w, h, span_color));
+            }
         }
     }
 }
@@ -1281,11 +3354,98 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
         self.inner.set_metrics(self.font_system, metrics);
     }
 
+    /// Set the [`LineHeight`], which resolves the height of each line
+    pub fn set_line_height_mode(&mut self, line_height_mode: LineHeight) {
+        self.inner
+            .set_line_height_mode(self.font_system, line_height_mode);
+    }
+
+    /// Set the [`BaselineAlign`] of lines that mix fonts
+    pub fn set_baseline_align(&mut self, baseline_align: BaselineAlign) {
+        self.inner
+            .set_baseline_align(self.font_system, baseline_align);
+    }
+
     /// Set the current [`Wrap`]
     pub fn set_wrap(&mut self, wrap: Wrap) {
         self.inner.set_wrap(self.font_system, wrap);
     }
 
+    /// Split the text into pages `height` tall, without splitting a layout line across pages
+    pub fn paginate(&mut self, height: f32) -> Vec<PageRange> {
+        self.inner.paginate(self.font_system, height)
+    }
+
+    /// Set if ASCII digits should all be as wide as the widest digit of their font
+    pub fn set_tabular_digits(&mut self, tabular_digits: bool) {
+        self.inner
+            .set_tabular_digits(self.font_system, tabular_digits);
+    }
+
+    /// Set if punctuation at the end of a line may hang past its width
+    pub fn set_hanging_punctuation(&mut self, hanging_punctuation: bool) {
+        self.inner
+            .set_hanging_punctuation(self.font_system, hanging_punctuation);
+    }
+
+    /// Set what replaces characters that no font supports
+    pub fn set_missing_glyph(&mut self, missing_glyph: Option<MissingGlyph>) {
+        self.inner
+            .set_missing_glyph(self.font_system, missing_glyph);
+    }
+
+    /// Set the strictness of the rules for breaking lines of Chinese and Japanese text
+    pub fn set_line_break_strictness(&mut self, line_break_strictness: LineBreakStrictness) {
+        self.inner
+            .set_line_break_strictness(self.font_system, line_break_strictness);
+    }
+
+    /// Set the language of the hyphenation patterns used to break words
+    #[cfg(feature = "hyphenation")]
+    pub fn set_hyphenation(&mut self, lang: Option<&str>) {
+        self.inner.set_hyphenation(self.font_system, lang);
+    }
+
+    /// Set the maximum number of layout lines shown, or None to show all lines
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>) {
+        self.inner.set_max_lines(self.font_system, max_lines);
+    }
+
+    /// Set where text is elided when it does not fit in the maximum number of lines
+    pub fn set_truncate(&mut self, truncate: Truncate) {
+        self.inner.set_truncate(self.font_system, truncate);
+    }
+
+    /// Set the [`WritingMode`], laying out lines as vertical columns if it is vertical
+    pub fn set_writing_mode(&mut self, writing_mode: WritingMode) {
+        self.inner.set_writing_mode(self.font_system, writing_mode);
+    }
+
+    /// Set the number of columns that lines are distributed across, and the gap between them
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `columns` is zero.
+    pub fn set_columns(&mut self, columns: usize, gap: f32) {
+        self.inner.set_columns(self.font_system, columns, gap);
+    }
+
+    /// Set rectangles that text flows around, like floated images
+    pub fn set_exclusions(&mut self, exclusions: Vec<Exclusion>) {
+        self.inner.set_exclusions(self.font_system, exclusions);
+    }
+
+    /// Set a callback that gives the width to wrap each visual line to
+    pub fn set_line_width(&mut self, line_width_opt: Option<LineWidth>) {
+        self.inner.set_line_width(self.font_system, line_width_opt);
+    }
+
+    /// Set the [`TextOrientation`] of glyphs in vertical lines
+    pub fn set_text_orientation(&mut self, text_orientation: TextOrientation) {
+        self.inner
+            .set_text_orientation(self.font_system, text_orientation);
+    }
+
     /// Set the current buffer dimensions
     pub fn set_size(&mut self, width: f32, height: f32) {
         self.inner.set_size(self.font_system, width, height);
@@ -1301,6 +3461,28 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
             .set_metrics_and_size(self.font_system, metrics, width, height);
     }
 
+    /// Find the largest font size at which all text fits inside `width` and `height`, and apply
+    /// it along with the new size. See [`Buffer::shrink_to_fit`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `min_font_size` is zero.
+    pub fn shrink_to_fit(
+        &mut self,
+        width: f32,
+        height: f32,
+        min_font_size: f32,
+        max_font_size: f32,
+    ) -> Metrics {
+        self.inner.shrink_to_fit(
+            self.font_system,
+            width,
+            height,
+            min_font_size,
+            max_font_size,
+        )
+    }
+
     /// Set text of buffer, using provided attributes for each line by default
     pub fn set_text(&mut self, text: &str, attrs: Attrs, shaping: Shaping) {
         self.inner.set_text(self.font_system, text, attrs, shaping);
@@ -1331,6 +3513,75 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
             .set_rich_text(self.font_system, spans, default_attrs, shaping);
     }
 
+    /// Set text of buffer, using an iterator of paragraphs, each with styled spans and
+    /// [`ParagraphAttrs`] for all of its lines
+    pub fn set_rich_paragraphs<'r, 's, I, J>(
+        &mut self,
+        paragraphs: I,
+        default_attrs: Attrs,
+        shaping: Shaping,
+    ) where
+        I: IntoIterator<Item = (J, ParagraphAttrs)>,
+        J: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+    {
+        self.inner
+            .set_rich_paragraphs(self.font_system, paragraphs, default_attrs, shaping);
+    }
+
+    /// Set text of buffer from [`RichText`]
+    pub fn set_rich(&mut self, rich_text: &RichText, shaping: Shaping) {
+        self.inner.set_rich(self.font_system, rich_text, shaping);
+    }
+
+    /// Set text of buffer from Markdown, see [`RichText::from_markdown`]
+    #[cfg(feature = "markdown")]
+    pub fn set_markdown(
+        &mut self,
+        markdown: &str,
+        attrs: Attrs,
+        style: &crate::MarkdownStyle,
+        shaping: Shaping,
+    ) {
+        self.inner
+            .set_markdown(self.font_system, markdown, attrs, style, shaping);
+    }
+
+    /// Set text of buffer from `BBCode`, see [`RichText::from_bbcode`]
+    pub fn set_bbcode(&mut self, bbcode: &str, attrs: Attrs, shaping: Shaping) {
+        self.inner
+            .set_bbcode(self.font_system, bbcode, attrs, shaping);
+    }
+
+    /// Set text of buffer from Pango-style markup, see [`RichText::from_markup`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MarkupError`] if the markup is malformed, leaving the buffer unchanged.
+    pub fn set_markup(
+        &mut self,
+        markup: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+    ) -> Result<(), MarkupError> {
+        self.inner
+            .set_markup(self.font_system, markup, attrs, shaping)
+    }
+
+    /// Set [`ParagraphAttrs`] of all lines
+    pub fn set_paragraph_attrs(&mut self, paragraph_attrs: ParagraphAttrs) {
+        self.inner
+            .set_paragraph_attrs(self.font_system, paragraph_attrs);
+    }
+
+    /// Set the spans of a layer of the attributes of all lines, given as
+    /// `(line_i, range, attrs_override)`, see [`AttrsList::add_layer_span`]
+    pub fn set_attrs_layer<I>(&mut self, layer: usize, spans: I)
+    where
+        I: IntoIterator<Item = (usize, Range<usize>, AttrsOverride)>,
+    {
+        self.inner.set_attrs_layer(self.font_system, layer, spans);
+    }
+
     /// Apply a [`Motion`] to a [`Cursor`]
     pub fn cursor_motion(
         &mut self,
@@ -1350,4 +3601,19 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
     {
         self.inner.draw(self.font_system, cache, color, f);
     }
+
+    /// Draw the part of the buffer inside `clip`
+    #[cfg(feature = "swash")]
+    pub fn draw_clipped<F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        clip: ClipRect,
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner
+            .draw_clipped(self.font_system, cache, color, clip, f);
+    }
 }
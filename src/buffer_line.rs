@@ -1,8 +1,13 @@
 #[cfg(not(feature = "std"))]
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
+use core::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    Align, AttrsList, FontSystem, LayoutLine, LineEnding, ShapeBuffer, ShapeLine, Shaping, Wrap,
+    Align, AttrsList, AttrsOverride, BaselineAlign, ExclusionBands, FontSize, FontSystem,
+    LayoutLine, LayoutOptions, LineEnding, LineHeight, ListMarker, ParagraphAttrs, Ruby, RubyRun,
+    ShapeBuffer, ShapeLine, Shaping, TextOrientation, Utf16Map, VerticalMetrics,
+    VerticalOrientation, Wrap, WritingMode, RUBY_SCALE,
 };
 
 /// A line (or paragraph) of text that is shaped and laid out
@@ -11,9 +16,23 @@ pub struct BufferLine {
     text: String,
     ending: LineEnding,
     attrs_list: AttrsList,
-    align: Option<Align>,
+    paragraph_attrs: ParagraphAttrs,
+    ruby: Vec<Ruby>,
+    marker_opt: Option<ListMarker>,
     shape_opt: Option<ShapeLine>,
+    /// Shaped text of each ruby annotation, cached with `shape_opt`
+    ruby_shapes: Vec<ShapeLine>,
+    /// Shaped text of the drop cap, cached with `shape_opt`
+    drop_cap_shape_opt: Option<ShapeLine>,
+    /// Shaped text of the list marker, cached with `shape_opt`
+    marker_shape_opt: Option<ShapeLine>,
+    /// Font size the shaped text was scaled from to the font sizes of its spans, see
+    /// [`ShapeLine::apply_font_sizes`]
+    sized_font_size_opt: Option<f32>,
     layout_opt: Option<Vec<LayoutLine>>,
+    /// Top of the line in its buffer if it was laid out around exclusions
+    layout_top_opt: Option<f32>,
+    utf16_map_opt: Option<Utf16Map>,
     shaping: Shaping,
     metadata: Option<usize>,
 }
@@ -32,9 +51,17 @@ impl BufferLine {
             text: text.into(),
             ending,
             attrs_list,
-            align: None,
+            paragraph_attrs: ParagraphAttrs::new(),
+            ruby: Vec::new(),
+            marker_opt: None,
             shape_opt: None,
+            ruby_shapes: Vec::new(),
+            drop_cap_shape_opt: None,
+            marker_shape_opt: None,
+            sized_font_size_opt: None,
             layout_opt: None,
+            layout_top_opt: None,
+            utf16_map_opt: None,
             shaping,
             metadata: None,
         }
@@ -68,6 +95,12 @@ impl BufferLine {
         }
     }
 
+    /// Get the map between byte offsets and UTF-16 offsets of the text, cached until the text changes
+    pub fn utf16_map(&mut self) -> &Utf16Map {
+        self.utf16_map_opt
+            .get_or_insert_with(|| Utf16Map::new(&self.text))
+    }
+
     /// Consume this line, returning only its text contents as a String.
     pub fn into_text(self) -> String {
         self.text
@@ -97,6 +130,22 @@ impl BufferLine {
         &self.attrs_list
     }
 
+    /// Set the spans of a layer of the attributes list, see [`AttrsList::add_layer_span`]
+    ///
+    /// Will reset shape and layout if the layer differs from the current one.
+    /// Returns true if the line was reset
+    pub fn set_attrs_layer<I>(&mut self, layer: usize, spans: I) -> bool
+    where
+        I: IntoIterator<Item = (Range<usize>, AttrsOverride)>,
+    {
+        let mut attrs_list = self.attrs_list.clone();
+        attrs_list.clear_layer(layer);
+        for (range, attrs_override) in spans {
+            attrs_list.add_layer_span(layer, range, attrs_override);
+        }
+        self.set_attrs_list(attrs_list)
+    }
+
     /// Set attributes list
     ///
     /// Will reset shape and layout if it differs from current attributes list.
@@ -111,9 +160,80 @@ impl BufferLine {
         }
     }
 
+    /// Get paragraph attributes
+    pub fn paragraph_attrs(&self) -> ParagraphAttrs {
+        self.paragraph_attrs
+    }
+
+    /// Set paragraph attributes
+    ///
+    /// Will reset shape if the direction or drop cap differs, or layout if other attributes
+    /// differ from the current paragraph attributes.
+    /// Returns true if the line was reset
+    pub fn set_paragraph_attrs(&mut self, paragraph_attrs: ParagraphAttrs) -> bool {
+        if paragraph_attrs != self.paragraph_attrs {
+            let reshape = paragraph_attrs.direction != self.paragraph_attrs.direction
+                || paragraph_attrs.drop_cap != self.paragraph_attrs.drop_cap;
+            self.paragraph_attrs = paragraph_attrs;
+            if reshape {
+                self.reset_shaping();
+            } else {
+                self.reset_layout();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the ruby annotations of the line
+    pub fn ruby(&self) -> &[Ruby] {
+        &self.ruby
+    }
+
+    /// Set ruby annotations, shaped at [`RUBY_SCALE`] of the size of their base and laid out
+    /// over it, see [`crate::LayoutRun::ruby`]
+    ///
+    /// Only the first line of the text of an annotation is shown. Will reset shape and layout if
+    /// it differs from the current annotations. Returns true if the line was reset
+    pub fn set_ruby(&mut self, ruby: Vec<Ruby>) -> bool {
+        if ruby != self.ruby {
+            self.ruby = ruby;
+            self.reset_shaping();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the list marker of the paragraph
+    pub fn marker(&self) -> Option<&ListMarker> {
+        self.marker_opt.as_ref()
+    }
+
+    /// Set the list marker of the paragraph, laid out on the baseline of its first line and
+    /// ending before its start, in the indent, see [`crate::LayoutRun::marker`]
+    ///
+    /// Only the first line of the text of the marker is shown. Will reset shape and layout if it
+    /// differs from the current marker. Returns true if the line was reset
+    pub fn set_marker(&mut self, marker_opt: Option<ListMarker>) -> bool {
+        if marker_opt != self.marker_opt {
+            self.marker_opt = marker_opt;
+            self.reset_shaping();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the [`Shaping`] strategy of the line
+    pub fn shaping(&self) -> Shaping {
+        self.shaping
+    }
+
     /// Get the Text alignment
     pub fn align(&self) -> Option<Align> {
-        self.align
+        self.paragraph_attrs.align
     }
 
     /// Set the text alignment
@@ -122,13 +242,10 @@ impl BufferLine {
     /// Setting to None will use `Align::Right` for RTL lines, and `Align::Left` for LTR lines.
     /// Returns true if the line was reset
     pub fn set_align(&mut self, align: Option<Align>) -> bool {
-        if align != self.align {
-            self.align = align;
-            self.reset_layout();
-            true
-        } else {
-            false
-        }
+        self.set_paragraph_attrs(ParagraphAttrs {
+            align,
+            ..self.paragraph_attrs
+        })
     }
 
     /// Append line at end of this line
@@ -150,6 +267,14 @@ impl BufferLine {
             self.attrs_list.add_span(range, attrs.as_attrs());
         }
 
+        for layer in other.attrs_list.layers() {
+            for (other_range, attrs_override) in other.attrs_list.layer_spans(layer) {
+                let range = other_range.start + len..other_range.end + len;
+                self.attrs_list
+                    .add_layer_span(layer, range, attrs_override.clone());
+            }
+        }
+
         self.reset();
     }
 
@@ -160,19 +285,21 @@ impl BufferLine {
         self.reset();
 
         let mut new = Self::new(text, self.ending, attrs_list, self.shaping);
-        new.align = self.align;
+        new.paragraph_attrs = self.paragraph_attrs;
         new
     }
 
-    /// Reset shaping, layout, and metadata caches
+    /// Reset shaping, layout, UTF-16 map, and metadata caches
     pub fn reset(&mut self) {
         self.metadata = None;
+        self.utf16_map_opt = None;
         self.reset_shaping();
     }
 
     /// Reset shaping and layout caches
     pub fn reset_shaping(&mut self) {
         self.shape_opt = None;
+        self.sized_font_size_opt = None;
         self.reset_layout();
     }
 
@@ -193,13 +320,58 @@ impl BufferLine {
         font_system: &mut FontSystem,
     ) -> &ShapeLine {
         if self.shape_opt.is_none() {
-            self.shape_opt = Some(ShapeLine::new_in_buffer(
+            self.shape_opt = Some(ShapeLine::new_in_buffer_with_direction(
                 scratch,
                 font_system,
                 &self.text,
                 &self.attrs_list,
                 self.shaping,
+                self.paragraph_attrs.direction,
             ));
+            self.ruby_shapes = self
+                .ruby
+                .iter()
+                .map(|ruby| {
+                    // Ruby text takes its size from its base
+                    let mut attrs = self.attrs_list.get_span(ruby.range.start);
+                    attrs.placeholder_opt = None;
+                    attrs.font_size_opt = None;
+                    ShapeLine::new_in_buffer(
+                        scratch,
+                        font_system,
+                        ruby.text.lines().next().unwrap_or_default(),
+                        &AttrsList::new(attrs),
+                        self.shaping,
+                    )
+                })
+                .collect();
+            self.drop_cap_shape_opt = self
+                .text
+                .graphemes(true)
+                .next()
+                .filter(|_| self.paragraph_attrs.drop_cap > 1)
+                .map(|initial| {
+                    ShapeLine::new_in_buffer_with_direction(
+                        scratch,
+                        font_system,
+                        initial,
+                        &self.attrs_list,
+                        self.shaping,
+                        self.paragraph_attrs.direction,
+                    )
+                });
+            self.marker_shape_opt = self.marker_opt.as_ref().map(|marker| {
+                let mut attrs = self.attrs_list.get_span(0);
+                attrs.placeholder_opt = None;
+                ShapeLine::new_in_buffer_with_direction(
+                    scratch,
+                    font_system,
+                    marker.text.lines().next().unwrap_or_default(),
+                    &AttrsList::new(attrs),
+                    self.shaping,
+                    self.paragraph_attrs.direction,
+                )
+            });
             self.layout_opt = None;
         }
         self.shape_opt.as_ref().expect("shape not found")
@@ -239,29 +411,446 @@ impl BufferLine {
         wrap: Wrap,
         match_mono_width: Option<f32>,
     ) -> &[LayoutLine] {
+        self.layout_in_buffer_with_options(
+            scratch,
+            font_system,
+            font_size,
+            width,
+            wrap,
+            &LayoutOptions {
+                match_mono_width,
+                ..LayoutOptions::new()
+            },
+        )
+    }
+
+    /// Layout line with [`LayoutOptions`], will cache results
+    ///
+    /// The indents of the line are those of its [`ParagraphAttrs`], not those of the options.
+    pub fn layout_with_options(
+        &mut self,
+        font_system: &mut FontSystem,
+        font_size: f32,
+        width: f32,
+        wrap: Wrap,
+        options: &LayoutOptions,
+    ) -> &[LayoutLine] {
+        self.layout_in_buffer_with_options(
+            &mut ShapeBuffer::default(),
+            font_system,
+            font_size,
+            width,
+            wrap,
+            options,
+        )
+    }
+
+    /// Layout a line with [`LayoutOptions`] using a pre-existing shape buffer, will cache results
+    ///
+    /// The indents of the line are those of its [`ParagraphAttrs`], not those of the options.
+    pub fn layout_in_buffer_with_options(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        font_size: f32,
+        width: f32,
+        wrap: Wrap,
+        options: &LayoutOptions,
+    ) -> &[LayoutLine] {
+        self.layout_in_bands(
+            scratch,
+            font_system,
+            &LineLayoutParams::new(font_size, width, wrap, *options),
+            None,
+        )
+    }
+
+    /// Lay out a line around the exclusions of the parameters, if any, and with its drop cap if
+    /// `line_height_opt` gives the distance between lines, will cache results
+    fn layout_in_bands(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        params: &LineLayoutParams,
+        line_height_opt: Option<f32>,
+    ) -> &[LayoutLine] {
+        let LineLayoutParams {
+            font_size,
+            width,
+            wrap,
+            bands_opt,
+            ..
+        } = *params;
         if self.layout_opt.is_none() {
-            let align = self.align;
-            let shape = self.shape_in_buffer(scratch, font_system);
+            let ParagraphAttrs {
+                align,
+                indent,
+                hanging_indent,
+                drop_cap,
+                ..
+            } = self.paragraph_attrs;
+            // Text with its own font size is scaled once for the font size of the buffer, and
+            // shaped again when that changes
+            if self
+                .sized_font_size_opt
+                .map_or(false, |sized_font_size| sized_font_size != font_size)
+            {
+                self.reset_shaping();
+            }
+            self.shape_in_buffer(scratch, font_system);
+            if self.sized_font_size_opt.is_none() {
+                for shape in self
+                    .shape_opt
+                    .iter_mut()
+                    .chain(self.drop_cap_shape_opt.iter_mut())
+                    .chain(self.marker_shape_opt.iter_mut())
+                {
+                    if shape.has_font_sizes() {
+                        shape.apply_font_sizes(font_size);
+                        self.sized_font_size_opt = Some(font_size);
+                    }
+                }
+            }
+            let mut shape = self.shape_opt.as_ref().expect("shape not found");
+            let rtl = shape.rtl;
+            // Insets of each line from the start and the end of the paragraph direction
+            let insets = |line_i: usize| {
+                let indent = if line_i == 0 { indent } else { hanging_indent };
+                let (left, right) = bands_opt.map_or((0.0, 0.0), |bands| bands.insets(line_i));
+                if rtl {
+                    (indent + right, left)
+                } else {
+                    (indent + left, right)
+                }
+            };
+
+            // The initial is laid out on its own, and the text it replaces takes no space
+            let initial_len = self.text.graphemes(true).next().map_or(0, str::len);
+            let drop_cap_opt = line_height_opt
+                .zip(self.drop_cap_shape_opt.as_ref())
+                .and_then(|(line_height, drop_cap_shape)| {
+                    Self::layout_drop_cap(drop_cap_shape, font_size, line_height, drop_cap)
+                });
+            let cleared_shape;
+            if drop_cap_opt.is_some() {
+                let mut cleared = shape.clone();
+                cleared.clear_advances(0..initial_len);
+                cleared_shape = cleared;
+                shape = &cleared_shape;
+            }
+            let drop_cap_w = drop_cap_opt.as_ref().map_or(0.0, |drop_cap| drop_cap.w);
+
             let mut layout = Vec::with_capacity(1);
-            shape.layout_to_buffer(
+            shape.layout_to_buffer_with_insets(
                 scratch,
                 font_size,
                 width,
                 wrap,
                 align,
+                &mut |line_i| {
+                    let (start, end) = insets(line_i);
+                    if line_i < drop_cap {
+                        (start + drop_cap_w, end)
+                    } else {
+                        (start, end)
+                    }
+                },
                 &mut layout,
-                match_mono_width,
+                &params.options,
             );
+            if let (Some(mut drop_cap_line), Some(first_line), Some(line_height)) =
+                (drop_cap_opt, layout.first_mut(), line_height_opt)
+            {
+                first_line.glyphs.retain(|glyph| glyph.start >= initial_len);
+                // Place the initial at the start of the first line, on the baseline of the last
+                // line it spans
+                let (start, _) = insets(0);
+                let x = if rtl {
+                    width - start - drop_cap_w
+                } else {
+                    start
+                };
+                let drop_cap_x = drop_cap_line
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.x)
+                    .fold(f32::INFINITY, f32::min);
+                for glyph in drop_cap_line.glyphs.iter_mut() {
+                    glyph.x += x - drop_cap_x;
+                    glyph.y += (drop_cap - 1) as f32 * line_height;
+                }
+                first_line.drop_cap = drop_cap_line.glyphs;
+            }
+            let marker_line_opt = self.marker_shape_opt.as_ref().and_then(|marker_shape| {
+                marker_shape
+                    .layout(font_size, 0.0, Wrap::None, None, None)
+                    .into_iter()
+                    .next()
+            });
+            if let (Some(mut marker_line), Some(marker), Some(first_line)) = (
+                marker_line_opt,
+                self.marker_opt.as_ref(),
+                layout.first_mut(),
+            ) {
+                // Place the marker before the start of the first line, on its baseline
+                let (start, _) = insets(0);
+                let x = if rtl {
+                    width - start + marker.gap
+                } else {
+                    start - marker.gap - marker_line.w
+                };
+                let marker_x = marker_line
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.x)
+                    .fold(f32::INFINITY, f32::min);
+                for glyph in marker_line.glyphs.iter_mut() {
+                    glyph.x += x - marker_x;
+                }
+                first_line.marker = marker_line.glyphs;
+            }
             self.layout_opt = Some(layout);
+            self.layout_top_opt = bands_opt.map(|bands| bands.top);
+            self.layout_ruby(false);
         }
         self.layout_opt.as_ref().expect("layout not found")
     }
 
+    /// Lay out a line in a [`WritingMode`], will cache results
+    ///
+    /// Vertical lines are laid out with `width` as their height, see [`LayoutLine::make_vertical`].
+    /// Horizontal lines are laid out around the exclusions of `bands_opt`, if any.
+    pub(crate) fn layout_in_writing_mode(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        params: &LineLayoutParams,
+    ) -> &[LayoutLine] {
+        let LineLayoutParams {
+            font_size,
+            line_height,
+            line_height_mode,
+            baseline_align,
+            writing_mode,
+            text_orientation,
+            ..
+        } = *params;
+        if self.layout_opt.is_none() {
+            let vertical = writing_mode.is_vertical();
+            self.layout_in_bands(
+                scratch,
+                font_system,
+                &LineLayoutParams {
+                    bands_opt: params.bands_opt.filter(|_| !vertical),
+                    ..*params
+                },
+                Some(line_height).filter(|_| !vertical),
+            );
+            if writing_mode.is_vertical() {
+                let text = &self.text;
+                let attrs_list = &self.attrs_list;
+                for layout_line in self.layout_opt.iter_mut().flatten() {
+                    layout_line.make_vertical(
+                        |glyph| {
+                            let Some(c) = text[glyph.start..].chars().next() else {
+                                return false;
+                            };
+                            match text_orientation {
+                                TextOrientation::Upright => false,
+                                TextOrientation::Sideways => true,
+                                TextOrientation::Mixed => match VerticalOrientation::of(c) {
+                                    VerticalOrientation::Upright
+                                    | VerticalOrientation::TransformedUpright => false,
+                                    VerticalOrientation::Rotated => true,
+                                    // Upright if the font replaced the glyph with a vertical alternate
+                                    VerticalOrientation::TransformedRotated => font_system
+                                        .get_font(glyph.font_id)
+                                        .and_then(|font| font.rustybuzz().glyph_index(c))
+                                        .map_or(true, |id| id.0 == glyph.glyph_id),
+                                },
+                            }
+                        },
+                        |glyph| attrs_list.get_span(glyph.start).combine_upright,
+                    );
+                }
+                // Markers are kept upright before the start of the first line
+                for glyph in self
+                    .layout_opt
+                    .iter_mut()
+                    .flatten()
+                    .flat_map(|layout_line| layout_line.marker.iter_mut())
+                {
+                    glyph.y += glyph.x;
+                    glyph.x = -glyph.w / 2.0;
+                }
+                self.layout_ruby(true);
+            } else {
+                // The baseline may be placed with the metrics of the primary font
+                let primary_opt = font_system
+                    .get_font_matches(self.attrs_list.defaults())
+                    .first()
+                    .and_then(|key| font_system.get_font(key.id))
+                    .map(|font| {
+                        (
+                            font.ascent_descent_em(VerticalMetrics::Typographic),
+                            font.ascent_descent_em(VerticalMetrics::Gdi),
+                        )
+                    });
+
+                // Lines with taller fonts are given more room, and the cap height of their fonts
+                // is kept for trimming leading
+                for layout_line in self.layout_opt.iter_mut().flatten() {
+                    let mut font_height: f32 = 0.0;
+                    let mut cap_height: f32 = 0.0;
+                    let mut font_id_opt = None;
+                    let mut line_height_em: f32 = 0.0;
+                    let mut descent: f32 = 0.0;
+                    let mut max_font_size = font_size;
+                    for glyph in layout_line.glyphs.iter_mut() {
+                        if font_id_opt != Some(glyph.font_id) {
+                            font_id_opt = Some(glyph.font_id);
+                            if let Some(font) = font_system.get_font(glyph.font_id) {
+                                line_height_em = font.line_height_em(VerticalMetrics::Typographic);
+                                cap_height = cap_height.max(font.cap_height_em() * glyph.font_size);
+                                descent = font.ascent_descent_em(VerticalMetrics::Typographic).1;
+                            }
+                        }
+                        // Text clamped to its line does not make it taller
+                        let attrs = self.attrs_list.get_span(glyph.start);
+                        if !attrs.clamp_line_height {
+                            font_height = font_height.max(line_height_em * glyph.font_size);
+                            if let Some(FontSize(glyph_font_size)) = attrs.font_size_opt {
+                                max_font_size = max_font_size.max(glyph_font_size);
+                            }
+                        }
+                        // Glyphs of fonts with shorter descents move down to the bottom of the
+                        // line's em box
+                        if baseline_align == BaselineAlign::Ideographic {
+                            glyph.y +=
+                                (layout_line.max_descent - descent * glyph.font_size).max(0.0);
+                        }
+                    }
+                    // Text larger than the font size needs as much more of the line height
+                    layout_line.extra_height = line_height_mode
+                        .extra_height(line_height, font_height)
+                        .max(line_height * (max_font_size / font_size - 1.0));
+                    layout_line.max_cap_height = cap_height;
+                    if let Some(((ascent, descent), (win_ascent, win_descent))) = primary_opt {
+                        layout_line.primary_ascent = ascent * font_size;
+                        layout_line.primary_descent = descent * font_size;
+                        layout_line.primary_win_ascent = win_ascent * font_size;
+                        layout_line.primary_win_descent = win_descent * font_size;
+                    }
+                }
+            }
+        }
+        self.layout_opt.as_ref().expect("layout not found")
+    }
+
+    /// Lay out the initial of a drop cap spanning `lines` lines `line_height` apart, scaled so
+    /// its ascent reaches from the top of the first line to the baseline of the last
+    fn layout_drop_cap(
+        shape: &ShapeLine,
+        font_size: f32,
+        line_height: f32,
+        lines: usize,
+    ) -> Option<LayoutLine> {
+        let layout = |font_size| {
+            shape
+                .layout(font_size, 0.0, Wrap::None, None, None)
+                .into_iter()
+                .next()
+        };
+        let ascent = layout(font_size)?.max_ascent;
+        if ascent <= 0.0 {
+            return None;
+        }
+        layout(font_size * (1.0 + (lines - 1) as f32 * line_height / ascent))
+    }
+
+    /// Lay out the ruby text of the line on the first line of its base, centered over it or
+    /// beside it on the right in `vertical` lines
+    fn layout_ruby(&mut self, vertical: bool) {
+        let Some(layout) = self.layout_opt.as_mut() else {
+            return;
+        };
+        let mut shown = vec![false; self.ruby.len()];
+        for layout_line in layout.iter_mut() {
+            layout_line.ruby.clear();
+            layout_line.ruby_height = 0.0;
+            for (ruby_i, (ruby, shape)) in self.ruby.iter().zip(self.ruby_shapes.iter()).enumerate()
+            {
+                // Extent of the base along the line
+                let mut base_opt: Option<(f32, f32, f32)> = None;
+                for glyph in layout_line.glyphs.iter() {
+                    if glyph.start >= ruby.range.end || glyph.end <= ruby.range.start {
+                        continue;
+                    }
+                    let start = if vertical { glyph.y } else { glyph.x };
+                    let (base_start, base_end, _) =
+                        base_opt.get_or_insert((start, start + glyph.w, glyph.font_size));
+                    *base_start = base_start.min(start);
+                    *base_end = base_end.max(start + glyph.w);
+                }
+                let Some((base_start, base_end, base_size)) = base_opt else {
+                    continue;
+                };
+                if shown[ruby_i] {
+                    continue;
+                }
+                shown[ruby_i] = true;
+
+                let font_size = base_size * RUBY_SCALE;
+                let Some(mut ruby_line) = shape
+                    .layout(font_size, 0.0, Wrap::None, None, None)
+                    .into_iter()
+                    .next()
+                else {
+                    continue;
+                };
+                let ruby_start = ruby_line
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.x)
+                    .fold(f32::INFINITY, f32::min);
+                let offset = base_start + (base_end - base_start - ruby_line.w) / 2.0 - ruby_start;
+                for glyph in ruby_line.glyphs.iter_mut() {
+                    glyph.x += offset;
+                }
+                if vertical {
+                    ruby_line.make_vertical(|_| false, |_| false);
+                    for glyph in ruby_line.glyphs.iter_mut() {
+                        glyph.x += (base_size + font_size) / 2.0;
+                        glyph.y -= layout_line.max_ascent - ruby_line.max_ascent;
+                    }
+                } else {
+                    for glyph in ruby_line.glyphs.iter_mut() {
+                        glyph.y -= layout_line.max_ascent + ruby_line.max_descent;
+                    }
+                }
+
+                layout_line.ruby_height = layout_line
+                    .ruby_height
+                    .max(ruby_line.max_ascent + ruby_line.max_descent);
+                layout_line.ruby.push(RubyRun {
+                    ruby_i,
+                    glyphs: ruby_line.glyphs,
+                });
+            }
+        }
+    }
+
     /// Get line layout cache
     pub fn layout_opt(&self) -> &Option<Vec<LayoutLine>> {
         &self.layout_opt
     }
 
+    /// Get the top of the line in its buffer if its layout is cached and was laid out around
+    /// exclusions
+    pub(crate) fn layout_top_opt(&self) -> Option<f32> {
+        self.layout_opt.as_ref().and(self.layout_top_opt)
+    }
+
     /// Get line metadata. This will be None if [`BufferLine::set_metadata`] has not been called
     /// after the last reset of shaping and layout caches
     pub fn metadata(&self) -> Option<usize> {
@@ -273,3 +862,39 @@ impl BufferLine {
         self.metadata = Some(metadata);
     }
 }
+
+/// Settings of a [`crate::Buffer`] that its lines are laid out with
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LineLayoutParams<'a> {
+    pub font_size: f32,
+    /// Width of the lines, or their height for vertical lines
+    pub width: f32,
+    pub wrap: Wrap,
+    pub options: LayoutOptions,
+    /// Distance between lines
+    pub line_height: f32,
+    pub line_height_mode: LineHeight,
+    pub baseline_align: BaselineAlign,
+    pub writing_mode: WritingMode,
+    pub text_orientation: TextOrientation,
+    /// Exclusions that horizontal lines are laid out around, if any
+    pub bands_opt: Option<&'a ExclusionBands<'a>>,
+}
+
+impl LineLayoutParams<'_> {
+    /// Create parameters for horizontal lines without exclusions
+    pub fn new(font_size: f32, width: f32, wrap: Wrap, options: LayoutOptions) -> Self {
+        Self {
+            font_size,
+            width,
+            wrap,
+            options,
+            line_height: 0.0,
+            line_height_mode: LineHeight::default(),
+            baseline_align: BaselineAlign::default(),
+            writing_mode: WritingMode::default(),
+            text_orientation: TextOrientation::default(),
+            bands_opt: None,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Hit and miss counters and entry count of a cache
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// Number of lookups that found an entry
+    pub hits: u64,
+    /// Number of lookups that did not find an entry
+    pub misses: u64,
+    /// Number of entries in the cache
+    pub entries: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that found an entry, or `0.0` if there were no lookups
+    pub fn hit_rate(&self) -> f32 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f32 / lookups as f32
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{math, Color, DecorationStyle, FontSystem, LayoutGlyph, LayoutRun};
+
+/// Which line of a [`crate::Decoration`] a [`DecorationSpan`] draws
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DecorationKind {
+    Underline,
+    Overline,
+    Strikethrough,
+}
+
+impl DecorationKind {
+    /// Position of the top of the line above the baseline and its thickness in em, for fonts
+    /// that do not define them
+    pub(crate) fn default_em(self) -> (f32, f32) {
+        match self {
+            Self::Underline => (-0.1, 0.05),
+            Self::Overline => (0.9, 0.05),
+            Self::Strikethrough => (0.3, 0.05),
+        }
+    }
+}
+
+/// A decoration line across glyphs of a [`LayoutRun`], see [`LayoutRun::decorations`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecorationSpan {
+    pub kind: DecorationKind,
+    pub style: DecorationStyle,
+    /// Color of the line, or None to use the default text color
+    pub color_opt: Option<Color>,
+    /// X position of the start of the line
+    pub x: f32,
+    /// Width of the line
+    pub w: f32,
+    /// Y position of the top of the line, relative to the baseline with positive values below it
+    pub y: f32,
+    /// Thickness of the line
+    pub thickness: f32,
+}
+
+impl<'a> LayoutRun<'a> {
+    /// Get the position of the top of the underline relative to the baseline, with positive
+    /// values below it, and its thickness, or None if no glyph of this run is underlined
+    ///
+    /// The metrics come from the `post` tables of the fonts of the underlined glyphs. When
+    /// fallback mixes fonts, the lowest position and the largest thickness are used, so that the
+    /// underline stays straight across font switches.
+    pub fn underline_metrics(&self, font_system: &mut FontSystem) -> Option<(f32, f32)> {
+        self.shared_metrics(font_system, DecorationKind::Underline)
+    }
+
+    /// Get the position of the top of the overline relative to the baseline, with negative
+    /// values above it, and its thickness, or None if no glyph of this run is overlined
+    ///
+    /// Overlines sit at the ascent of the fonts of the overlined glyphs. When fallback mixes
+    /// fonts, the highest position and the largest thickness are used, so that the overline
+    /// stays straight across font switches.
+    pub fn overline_metrics(&self, font_system: &mut FontSystem) -> Option<(f32, f32)> {
+        self.shared_metrics(font_system, DecorationKind::Overline)
+    }
+
+    /// Get the metrics of an underline or overline shared by the glyphs with it, furthest from
+    /// the baseline
+    fn shared_metrics(
+        &self,
+        font_system: &mut FontSystem,
+        kind: DecorationKind,
+    ) -> Option<(f32, f32)> {
+        let mut metrics_opt: Option<(f32, f32)> = None;
+        for glyph in self.glyphs.iter() {
+            let line = match kind {
+                DecorationKind::Underline => glyph.underline,
+                DecorationKind::Overline => glyph.overline,
+                DecorationKind::Strikethrough => glyph.strikethrough,
+            };
+            if !line.enabled {
+                continue;
+            }
+            let (position, thickness) = match font_system.get_font(glyph.font_id) {
+                Some(font) => font.decoration_em(kind),
+                None => kind.default_em(),
+            };
+            let y = -position * glyph.font_size;
+            let thickness = thickness * glyph.font_size;
+            metrics_opt = Some(match metrics_opt {
+                Some((shared_y, shared_thickness)) => (
+                    if kind == DecorationKind::Overline {
+                        shared_y.min(y)
+                    } else {
+                        shared_y.max(y)
+                    },
+                    shared_thickness.max(thickness),
+                ),
+                None => (y, thickness),
+            });
+        }
+        metrics_opt
+    }
+
+    /// Get the position of the top of the strikethrough relative to the baseline, with negative
+    /// values above it, and its thickness, or None if no glyph of this run is struck through
+    ///
+    /// The metrics come from the `OS/2` table of the largest font of the struck glyphs, like the
+    /// strikethroughs of [`Self::decorations`] with a single span.
+    pub fn strikethrough_metrics(&self, font_system: &mut FontSystem) -> Option<(f32, f32)> {
+        let glyph = self
+            .glyphs
+            .iter()
+            .filter(|glyph| glyph.strikethrough.enabled)
+            .reduce(|largest, glyph| {
+                if glyph.font_size > largest.font_size {
+                    glyph
+                } else {
+                    largest
+                }
+            })?;
+        let (position, thickness) = match font_system.get_font(glyph.font_id) {
+            Some(font) => font.decoration_em(DecorationKind::Strikethrough),
+            None => DecorationKind::Strikethrough.default_em(),
+        };
+        Some((-position * glyph.font_size, thickness * glyph.font_size))
+    }
+
+    /// Get the decoration lines of this run, underlines first, then overlines, then
+    /// strikethroughs, each from left to right
+    ///
+    /// Neighboring glyphs with the same line share a span, placed with the metrics of the largest
+    /// font in it. Underlines and overlines are all placed with [`Self::underline_metrics`] and
+    /// [`Self::overline_metrics`].
+    ///
+    /// Decorations are only placed along horizontal lines, so vertical runs have none, see
+    /// [`crate::Buffer::set_writing_mode`].
+    pub fn decorations(&self, font_system: &mut FontSystem) -> Vec<DecorationSpan> {
+        if self.writing_mode.is_vertical() {
+            return Vec::new();
+        }
+        let mut glyphs: Vec<&LayoutGlyph> = self.glyphs.iter().collect();
+        glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let underline_metrics_opt = self.underline_metrics(font_system);
+        let overline_metrics_opt = self.overline_metrics(font_system);
+
+        let mut spans = Vec::new();
+        for kind in [
+            DecorationKind::Underline,
+            DecorationKind::Overline,
+            DecorationKind::Strikethrough,
+        ] {
+            // The span being extended and the largest font size in it
+            let mut current: Option<(DecorationSpan, f32)> = None;
+            for glyph in glyphs.iter() {
+                let line_opt = match kind {
+                    DecorationKind::Underline => glyph.underline,
+                    DecorationKind::Overline => glyph.overline,
+                    DecorationKind::Strikethrough => glyph.strikethrough,
+                }
+                .line_opt();
+                let Some(line) = line_opt else {
+                    spans.extend(current.take().map(|(span, _)| span));
+                    continue;
+                };
+
+                let color_opt = line.color_opt.or(glyph.color_opt);
+                let (position, thickness) = match font_system.get_font(glyph.font_id) {
+                    Some(font) => font.decoration_em(kind),
+                    None => kind.default_em(),
+                };
+                let shared_metrics_opt = match kind {
+                    DecorationKind::Underline => underline_metrics_opt,
+                    DecorationKind::Overline => overline_metrics_opt,
+                    DecorationKind::Strikethrough => None,
+                };
+                let (y, thickness) = shared_metrics_opt
+                    .unwrap_or((-position * glyph.font_size, thickness * glyph.font_size));
+
+                match &mut current {
+                    Some((span, font_size))
+                        if span.style == line.style && span.color_opt == color_opt =>
+                    {
+                        span.w = span.w.max(glyph.x + glyph.w - span.x);
+                        if glyph.font_size > *font_size {
+                            *font_size = glyph.font_size;
+                            span.y = y;
+                            span.thickness = thickness;
+                        }
+                    }
+                    _ => {
+                        spans.extend(current.take().map(|(span, _)| span));
+                        current = Some((
+                            DecorationSpan {
+                                kind,
+                                style: line.style,
+                                color_opt,
+                                x: glyph.x,
+                                w: glyph.w,
+                                y,
+                                thickness,
+                            },
+                            glyph.font_size,
+                        ));
+                    }
+                }
+            }
+            spans.extend(current.take().map(|(span, _)| span));
+        }
+        spans
+    }
+}
+
+/// Draw a decoration span of a line with its baseline at `line_y`, as rectangles passed to `f`
+/// with their x, y, width, and height
+///
+/// Dots, dashes, and waves are aligned to a grid starting at x 0, so that neighboring spans
+/// continue the same pattern.
+pub fn draw_decoration<F>(span: &DecorationSpan, line_y: f32, mut f: F)
+where
+    F: FnMut(i32, i32, u32, u32),
+{
+    let start = math::roundf(span.x) as i32;
+    let end = math::roundf(span.x + span.w) as i32;
+    if end <= start {
+        return;
+    }
+    let w = (end - start) as u32;
+    let thickness = math::roundf(span.thickness).max(1.0) as u32;
+    let t = thickness as i32;
+    let y = math::roundf(line_y + span.y) as i32;
+
+    match span.style {
+        DecorationStyle::Solid => f(start, y, w, thickness),
+        DecorationStyle::Double => {
+            f(start, y, w, thickness);
+            f(start, y + 2 * t, w, thickness);
+        }
+        DecorationStyle::Dotted => draw_dashes(start..end, y, thickness, t, 2 * t, &mut f),
+        DecorationStyle::Dashed => draw_dashes(start..end, y, thickness, 3 * t, 5 * t, &mut f),
+        DecorationStyle::Wavy => {
+            // A zigzag one column at a time, rising and falling by one pixel per column
+            let period = 4 * t;
+            for x in start..end {
+                let offset = (x.rem_euclid(period) - 2 * t).abs() - t;
+                f(x, y + offset, 1, thickness);
+            }
+        }
+    }
+}
+
+/// Draw dashes of `length` repeating every `period` pixels from x 0, clipped to `range`
+fn draw_dashes<F>(range: Range<i32>, y: i32, thickness: u32, length: i32, period: i32, f: &mut F)
+where
+    F: FnMut(i32, i32, u32, u32),
+{
+    let mut dash_x = range.start - range.start.rem_euclid(period);
+    while dash_x < range.end {
+        let dash_start = dash_x.max(range.start);
+        let dash_end = (dash_x + length).min(range.end);
+        if dash_end > dash_start {
+            f(dash_start, y, (dash_end - dash_start) as u32, thickness);
+        }
+        dash_x += period;
+    }
+}
+
+#[test]
+fn test_draw_decoration() {
+    fn rects(style: DecorationStyle, x: f32, w: f32) -> Vec<(i32, i32, u32, u32)> {
+        let span = DecorationSpan {
+            kind: DecorationKind::Underline,
+            style,
+            color_opt: None,
+            x,
+            w,
+            y: 2.0,
+            thickness: 1.0,
+        };
+        let mut rects = Vec::new();
+        draw_decoration(&span, 10.0, |x, y, w, h| rects.push((x, y, w, h)));
+        rects
+    }
+
+    assert_eq!(rects(DecorationStyle::Solid, 0.0, 10.0), [(0, 12, 10, 1)]);
+    assert_eq!(
+        rects(DecorationStyle::Double, 0.0, 10.0),
+        [(0, 12, 10, 1), (0, 14, 10, 1)]
+    );
+    assert_eq!(
+        rects(DecorationStyle::Dotted, 1.0, 5.0),
+        [(2, 12, 1, 1), (4, 12, 1, 1)]
+    );
+    assert_eq!(rects(DecorationStyle::Dashed, 3.0, 7.0), [(5, 12, 3, 1)]);
+
+    // Waves stay within their height, and cover every column once
+    let wavy = rects(DecorationStyle::Wavy, 0.0, 12.0);
+    assert_eq!(wavy.len(), 12);
+    assert!(wavy.iter().all(|(_, y, _, _)| (11..=13).contains(y)));
+    assert!(wavy
+        .windows(2)
+        .all(|pair| (pair[0].1 - pair[1].1).abs() == 1));
+
+    assert!(rects(DecorationStyle::Solid, 0.0, 0.0).is_empty());
+}
@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
+use unicode_script::{Script, UnicodeScript};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Default)]
+struct DictionaryNode {
+    /// Sorted by character
+    children: Vec<(char, u32)>,
+    word: bool,
+}
+
+/// A word list used to find line breaks in scripts that do not separate words with spaces, such
+/// as Thai, Lao, Khmer, and Burmese
+///
+/// The script of a dictionary is the script of the first letter in its words. Once added with
+/// [`crate::FontSystem::add_line_break_dictionary`], runs of text in that script are split
+/// into words from the dictionary, so that lines can wrap between them. Text that does not
+/// match any word is kept together.
+pub struct LineBreakDictionary {
+    nodes: Vec<DictionaryNode>,
+    script: Script,
+    len: usize,
+}
+
+impl fmt::Debug for LineBreakDictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineBreakDictionary")
+            .field("script", &self.script)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl LineBreakDictionary {
+    /// Create a dictionary from a list of words
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut dictionary = Self {
+            nodes: vec![DictionaryNode::default()],
+            script: Script::Unknown,
+            len: 0,
+        };
+        for word in words {
+            dictionary.insert(word.as_ref());
+        }
+        dictionary
+    }
+
+    /// Create a dictionary from text with one word per line
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Self {
+        Self::from_words(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        )
+    }
+
+    fn insert(&mut self, word: &str) {
+        if word.is_empty() {
+            return;
+        }
+
+        if self.script == Script::Unknown {
+            if let Some(script) = word
+                .chars()
+                .map(|c| c.script())
+                .find(|script| !matches!(script, Script::Common | Script::Inherited))
+            {
+                self.script = script;
+            }
+        }
+
+        let mut node_i = 0;
+        for c in word.chars() {
+            node_i = match self.nodes[node_i]
+                .children
+                .binary_search_by_key(&c, |(child_c, _)| *child_c)
+            {
+                Ok(child_i) => self.nodes[node_i].children[child_i].1 as usize,
+                Err(child_i) => {
+                    let new_i = self.nodes.len();
+                    self.nodes.push(DictionaryNode::default());
+                    self.nodes[node_i]
+                        .children
+                        .insert(child_i, (c, new_i as u32));
+                    new_i
+                }
+            };
+        }
+
+        if !self.nodes[node_i].word {
+            self.nodes[node_i].word = true;
+            self.len += 1;
+        }
+    }
+
+    fn child(&self, node_i: usize, c: char) -> Option<usize> {
+        let children = &self.nodes[node_i].children;
+        children
+            .binary_search_by_key(&c, |(child_c, _)| *child_c)
+            .ok()
+            .map(|child_i| children[child_i].1 as usize)
+    }
+
+    /// Get the script this dictionary applies to
+    pub fn script(&self) -> Script {
+        self.script
+    }
+
+    /// Get the number of words
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if there are no words
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check if a word is in the dictionary
+    pub fn contains(&self, word: &str) -> bool {
+        let mut node_i = 0;
+        for c in word.chars() {
+            match self.child(node_i, c) {
+                Some(child_i) => node_i = child_i,
+                None => return false,
+            }
+        }
+        self.nodes[node_i].word
+    }
+
+    /// Find word boundaries in text of this dictionary's script, returning the byte offsets
+    /// between words, excluding the start and end of the text
+    ///
+    /// The segmentation with the least text outside of known words is chosen, and then the one
+    /// with the fewest words. Boundaries only fall between grapheme clusters.
+    pub fn segment(&self, text: &str) -> Vec<usize> {
+        let mut boundaries: Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+        boundaries.push(text.len());
+        let count = boundaries.len() - 1;
+
+        // Best (unknown graphemes, words, previous boundary, known word) ending at each boundary
+        let mut best: Vec<Option<(usize, usize, usize, bool)>> = vec![None; count + 1];
+        best[0] = Some((0, 0, 0, false));
+        for start in 0..count {
+            let Some((unknown, words, _, _)) = best[start] else {
+                continue;
+            };
+
+            let mut update = |end: usize, candidate: (usize, usize, usize, bool)| {
+                let better = match best[end] {
+                    Some((end_unknown, end_words, _, _)) => {
+                        (candidate.0, candidate.1) < (end_unknown, end_words)
+                    }
+                    None => true,
+                };
+                if better {
+                    best[end] = Some(candidate);
+                }
+            };
+
+            update(start + 1, (unknown + 1, words + 1, start, false));
+
+            let mut node_i = 0;
+            for (i, c) in text[boundaries[start]..].char_indices() {
+                match self.child(node_i, c) {
+                    Some(child_i) => node_i = child_i,
+                    None => break,
+                }
+                if self.nodes[node_i].word {
+                    let end_byte = boundaries[start] + i + c.len_utf8();
+                    if let Ok(end) = boundaries.binary_search(&end_byte) {
+                        update(end, (unknown, words + 1, start, true));
+                    }
+                }
+            }
+        }
+
+        // Walk back from the end, keeping runs of unknown text together
+        let mut breaks = Vec::new();
+        let mut end = count;
+        let mut next_known = true;
+        while let Some((_, _, start, known)) = best[end] {
+            if end < count && (known || next_known) {
+                breaks.push(boundaries[end]);
+            }
+            if start == 0 {
+                break;
+            }
+            next_known = known;
+            end = start;
+        }
+        breaks.reverse();
+        breaks
+    }
+}
+
+/// Find word boundaries in runs of text covered by one of the dictionaries
+pub(crate) fn dictionary_breaks(dictionaries: &[LineBreakDictionary], text: &str) -> Vec<usize> {
+    let mut breaks = Vec::new();
+    if dictionaries.is_empty() {
+        return breaks;
+    }
+
+    let dictionary_for = |c: char| {
+        let script = c.script();
+        dictionaries
+            .iter()
+            .position(|dictionary| dictionary.script == script)
+    };
+
+    let mut run_opt: Option<(usize, usize)> = None;
+    for (i, c) in text
+        .char_indices()
+        .map(|(i, c)| (i, Some(c)))
+        .chain(core::iter::once((text.len(), None)))
+    {
+        let dictionary_opt = c.and_then(dictionary_for);
+        if let Some((run_start, run_dictionary)) = run_opt {
+            // Marks continue the run of the letter they belong to
+            let inherited = c.map_or(false, |c| c.script() == Script::Inherited);
+            if dictionary_opt == Some(run_dictionary) || inherited {
+                continue;
+            }
+            breaks.extend(
+                dictionaries[run_dictionary]
+                    .segment(&text[run_start..i])
+                    .into_iter()
+                    .map(|offset| run_start + offset),
+            );
+            run_opt = None;
+        }
+        if let Some(dictionary) = dictionary_opt {
+            run_opt = Some((i, dictionary));
+        }
+    }
+    breaks
+}
+
+/// Scripts split by the built-in dictionaries of `icu_segmenter`
+#[cfg(feature = "icu-segmenter")]
+const SEGMENTER_SCRIPTS: [Script; 4] = [Script::Thai, Script::Lao, Script::Khmer, Script::Myanmar];
+
+/// Check if `icu_segmenter` should split text in the script of c, as no dictionary covers it
+#[cfg(feature = "icu-segmenter")]
+fn is_segmented(dictionaries: &[LineBreakDictionary], c: char) -> bool {
+    let script = c.script();
+    SEGMENTER_SCRIPTS.contains(&script)
+        && !dictionaries
+            .iter()
+            .any(|dictionary| dictionary.script == script)
+}
+
+/// Check if text has characters that `icu_segmenter` would split
+#[cfg(feature = "icu-segmenter")]
+pub(crate) fn has_segmenter_script(dictionaries: &[LineBreakDictionary], text: &str) -> bool {
+    text.chars().any(|c| is_segmented(dictionaries, c))
+}
+
+/// Find word boundaries with `segmenter` in runs of text in the scripts it has dictionaries for,
+/// unless one of `dictionaries` covers them
+#[cfg(feature = "icu-segmenter")]
+pub(crate) fn segmenter_breaks(
+    segmenter: &icu_segmenter::LineSegmenter,
+    dictionaries: &[LineBreakDictionary],
+    text: &str,
+) -> Vec<usize> {
+    let segmented = |c: char| is_segmented(dictionaries, c);
+
+    let mut breaks = Vec::new();
+    let mut run_start_opt: Option<usize> = None;
+    for (i, c) in text
+        .char_indices()
+        .map(|(i, c)| (i, Some(c)))
+        .chain(core::iter::once((text.len(), None)))
+    {
+        if let Some(run_start) = run_start_opt {
+            // Marks continue the run of the letter they belong to
+            let inherited = c.map_or(false, |c| c.script() == Script::Inherited);
+            if c.map_or(false, segmented) || inherited {
+                continue;
+            }
+            let run = &text[run_start..i];
+            breaks.extend(
+                segmenter
+                    .segment_str(run)
+                    .filter(|&offset| offset > 0 && offset < run.len())
+                    .map(|offset| run_start + offset),
+            );
+            run_start_opt = None;
+        }
+        if c.map_or(false, segmented) {
+            run_start_opt = Some(i);
+        }
+    }
+    breaks
+}
+
+#[test]
+fn test_line_break_dictionary() {
+    let dictionary = LineBreakDictionary::parse(
+        "# Thai\n\u{0E09}\u{0E31}\u{0E19}\n\u{0E01}\u{0E34}\u{0E19}\n\n\u{0E02}\u{0E49}\u{0E32}\u{0E27}\n",
+    );
+    assert_eq!(dictionary.len(), 3);
+    assert_eq!(dictionary.script(), Script::Thai);
+    assert!(dictionary.contains("\u{0E01}\u{0E34}\u{0E19}"));
+    assert!(!dictionary.contains("\u{0E01}"));
+
+    // "I eat rice"
+    let text = "\u{0E09}\u{0E31}\u{0E19}\u{0E01}\u{0E34}\u{0E19}\u{0E02}\u{0E49}\u{0E32}\u{0E27}";
+    assert_eq!(dictionary.segment(text), [9, 18]);
+
+    // "I go eat rice", with "go" missing from the dictionary
+    let text = "\u{0E09}\u{0E31}\u{0E19}\u{0E44}\u{0E1B}\u{0E01}\u{0E34}\u{0E19}\u{0E02}\u{0E49}\u{0E32}\u{0E27}";
+    assert_eq!(dictionary.segment(text), [9, 15, 24]);
+
+    // Only runs of Thai are segmented
+    let dictionaries = [dictionary];
+    let text = "ok \u{0E09}\u{0E31}\u{0E19}\u{0E01}\u{0E34}\u{0E19}!";
+    assert_eq!(dictionary_breaks(&dictionaries, text), [12]);
+    assert!(dictionary_breaks(&[], text).is_empty());
+}
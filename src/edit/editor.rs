@@ -56,12 +56,12 @@ impl<'buffer> Editor<'buffer> {
     ) where
         F: FnMut(i32, i32, u32, u32, Color),
     {
+        let hex_boxes = font_system.last_resort() == crate::LastResort::HexBox;
         self.with_buffer(|buffer| {
-            let line_height = buffer.metrics().line_height;
+            let line_height = buffer.line_height();
             for run in buffer.layout_runs() {
                 let line_i = run.line_i;
                 let line_y = run.line_y;
-                let line_top = run.line_top;
 
                 let cursor_glyph_opt = |cursor: &Cursor| -> Option<(usize, f32)> {
                     if cursor.line == line_i {
@@ -99,6 +99,17 @@ impl<'buffer> Editor<'buffer> {
                     None
                 };
 
+                // Backgrounds of spans are drawn under the selection
+                for rect in run.backgrounds(line_height) {
+                    f(
+                        rect.x as i32,
+                        rect.y as i32,
+                        rect.w as u32,
+                        rect.h as u32,
+                        rect.color,
+                    );
+                }
+
                 // Highlight selection
                 if let Some((start, end)) = self.selection_bounds() {
                     if line_i >= start.line && line_i <= end.line {
@@ -107,7 +118,7 @@ impl<'buffer> Editor<'buffer> {
                             // Guess x offset based on characters
                             let cluster = &run.text[glyph.start..glyph.end];
                             let total = cluster.grapheme_indices(true).count();
-                            let mut c_x = glyph.x;
+                            let mut c_x = run.glyph_position(glyph);
                             let c_w = glyph.w / total as f32;
                             for (i, c) in cluster.grapheme_indices(true) {
                                 let c_start = glyph.start + i;
@@ -123,11 +134,13 @@ impl<'buffer> Editor<'buffer> {
                                         None => Some((c_x as i32, (c_x + c_w) as i32)),
                                     };
                                 } else if let Some((min, max)) = range_opt.take() {
+                                    let (x, y, w, h) =
+                                        run.line_rect(min as f32, max as f32, line_height);
                                     f(
-                                        min,
-                                        line_top as i32,
-                                        cmp::max(0, max - min) as u32,
-                                        line_height as u32,
+                                        x as i32,
+                                        y as i32,
+                                        w.max(0.0) as u32,
+                                        h as u32,
                                         selection_color,
                                     );
                                 }
@@ -137,7 +150,7 @@ impl<'buffer> Editor<'buffer> {
 
                         if run.glyphs.is_empty() && end.line > line_i {
                             // Highlight all of internal empty lines
-                            range_opt = Some((0, buffer.size().0 as i32));
+                            range_opt = Some((0, buffer.inline_block_size().0 as i32));
                         }
 
                         if let Some((mut min, mut max)) = range_opt.take() {
@@ -146,14 +159,15 @@ impl<'buffer> Editor<'buffer> {
                                 if run.rtl {
                                     min = 0;
                                 } else {
-                                    max = buffer.size().0 as i32;
+                                    max = buffer.inline_block_size().0 as i32;
                                 }
                             }
+                            let (x, y, w, h) = run.line_rect(min as f32, max as f32, line_height);
                             f(
-                                min,
-                                line_top as i32,
-                                cmp::max(0, max - min) as u32,
-                                line_height as u32,
+                                x as i32,
+                                y as i32,
+                                w.max(0.0) as u32,
+                                h as u32,
                                 selection_color,
                             );
                         }
@@ -166,18 +180,18 @@ impl<'buffer> Editor<'buffer> {
                         Some(glyph) => {
                             // Start of detected glyph
                             if glyph.level.is_rtl() {
-                                (glyph.x + glyph.w - cursor_glyph_offset) as i32
+                                (run.glyph_position(glyph) + glyph.w - cursor_glyph_offset) as i32
                             } else {
-                                (glyph.x + cursor_glyph_offset) as i32
+                                (run.glyph_position(glyph) + cursor_glyph_offset) as i32
                             }
                         }
                         None => match run.glyphs.last() {
                             Some(glyph) => {
                                 // End of last glyph
                                 if glyph.level.is_rtl() {
-                                    glyph.x as i32
+                                    run.glyph_position(glyph) as i32
                                 } else {
-                                    (glyph.x + glyph.w) as i32
+                                    (run.glyph_position(glyph) + glyph.w) as i32
                                 }
                             }
                             None => {
@@ -187,17 +201,59 @@ impl<'buffer> Editor<'buffer> {
                         },
                     };
 
-                    f(x, line_top as i32, 1, line_height as u32, cursor_color);
+                    let (x, y, w, h) = run.line_rect(x as f32, x as f32 + 1.0, line_height);
+                    f(x as i32, y as i32, w as u32, h as u32, cursor_color);
+                }
+
+                // Shadows are drawn under all the glyphs of the line
+                for glyph in run.glyphs.iter() {
+                    let Some(shadow) = glyph.shadow_opt() else {
+                        continue;
+                    };
+                    if glyph.has_placeholder {
+                        continue;
+                    }
+                    let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
+                    cache.with_shadow_pixels(
+                        font_system,
+                        physical_glyph.cache_key,
+                        shadow,
+                        |x, y, color| {
+                            f(
+                                physical_glyph.x + x,
+                                line_y as i32 + physical_glyph.y + y,
+                                1,
+                                1,
+                                color,
+                            );
+                        },
+                    );
                 }
 
                 for glyph in run.glyphs.iter() {
-                    let physical_glyph = glyph.physical((0., 0.), 1.0);
+                    if glyph.has_placeholder {
+                        continue;
+                    }
+                    let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
 
                     let glyph_color = match glyph.color_opt {
                         Some(some) => some,
                         None => text_color,
                     };
 
+                    if hex_boxes
+                        && crate::draw_glyph_hex_box(
+                            run.text,
+                            glyph,
+                            &physical_glyph,
+                            line_y,
+                            glyph_color,
+                            &mut f,
+                        )
+                    {
+                        continue;
+                    }
+
                     cache.with_pixels(
                         font_system,
                         physical_glyph.cache_key,
@@ -213,6 +269,32 @@ impl<'buffer> Editor<'buffer> {
                         },
                     );
                 }
+
+                // Ruby text, drop caps, and list markers are drawn like the glyphs of the line
+                let ruby_glyphs = run.ruby.iter().flat_map(|ruby| ruby.glyphs.iter());
+                for glyph in ruby_glyphs.chain(run.drop_cap).chain(run.marker) {
+                    let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
+                    cache.with_pixels(
+                        font_system,
+                        physical_glyph.cache_key,
+                        glyph.color_opt.unwrap_or(text_color),
+                        |x, y, color| {
+                            f(
+                                physical_glyph.x + x,
+                                line_y as i32 + physical_glyph.y + y,
+                                1,
+                                1,
+                                color,
+                            );
+                        },
+                    );
+                }
+
+                for mut span in run.decorations(font_system) {
+                    let span_color = span.color_opt.unwrap_or(text_color);
+                    span.x += run.line_x;
+                    crate::draw_decoration(&span, line_y, |x, y, w, h| f(x, y, w, h, span_color));
+                }
             }
         });
     }
@@ -351,6 +433,19 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         data: &str,
         attrs_list: Option<AttrsList>,
     ) -> Cursor {
+        #[cfg(feature = "nfc-normalization")]
+        let nfc_text_opt = self
+            .with_buffer(|buffer| buffer.nfc_normalization())
+            .then(|| crate::NfcText::new(data));
+        #[cfg(feature = "nfc-normalization")]
+        let (data, attrs_list) = match &nfc_text_opt {
+            Some(nfc_text) => (
+                nfc_text.text(),
+                attrs_list.map(|attrs_list| nfc_text.normalize_attrs_list(&attrs_list)),
+            ),
+            None => (data, attrs_list),
+        };
+
         let mut remaining_split_len = data.len();
         if remaining_split_len == 0 {
             return cursor;
@@ -387,6 +482,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             let line: &mut BufferLine = &mut buffer.lines[cursor.line];
             let insert_line = cursor.line + 1;
             let ending = line.ending();
+            let paragraph_attrs = line.paragraph_attrs();
 
             // Collect text after insertion as a line
             let after: BufferLine = line.split_off(cursor.index);
@@ -427,6 +523,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                     final_attrs.split_off(remaining_split_len),
                     Shaping::Advanced,
                 );
+                tmp.set_paragraph_attrs(paragraph_attrs);
                 tmp.append(after);
                 buffer.lines.insert(insert_line, tmp);
                 cursor.line += 1;
@@ -435,7 +532,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             }
             for data_line in lines_iter.rev() {
                 remaining_split_len -= data_line.len();
-                let tmp = BufferLine::new(
+                let mut tmp = BufferLine::new(
                     data_line
                         .strip_suffix(char::is_control)
                         .unwrap_or(data_line),
@@ -443,6 +540,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                     final_attrs.split_off(remaining_split_len),
                     Shaping::Advanced,
                 );
+                tmp.set_paragraph_attrs(paragraph_attrs);
                 buffer.lines.insert(insert_line, tmp);
                 cursor.line += 1;
             }
@@ -8,8 +8,8 @@ use syntect::highlighting::{
 use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
 
 use crate::{
-    Action, AttrsList, BorrowedWithFontSystem, BufferRef, Change, Color, Cursor, Edit, Editor,
-    FontSystem, Selection, Shaping, Style, Weight,
+    Action, AttrsList, BorrowedWithFontSystem, BufferRef, Change, Color, Cursor,
+    DetectedLineEnding, Edit, Editor, FontSystem, LineEnding, Selection, Shaping, Style, Weight,
 };
 
 pub use syntect::highlighting::Theme as SyntaxTheme;
@@ -125,7 +125,12 @@ impl<'syntax_system, 'buffer> SyntaxEditor<'syntax_system, 'buffer> {
 
         let text = fs::read_to_string(path)?;
         self.editor.with_buffer_mut(|buffer| {
-            buffer.set_text(font_system, &text, attrs, Shaping::Advanced)
+            buffer.set_text(font_system, &text, attrs, Shaping::Advanced);
+            // Write back uniform line endings, including for lines added by editing
+            buffer.set_line_ending(match LineEnding::detect(&text) {
+                DetectedLineEnding::Uniform(line_ending) => Some(line_ending),
+                _ => None,
+            });
         });
 
         //TODO: re-use text
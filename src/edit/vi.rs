@@ -308,15 +308,15 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
         let foreground_color = self.foreground_color();
         let cursor_color = self.cursor_color();
         let selection_color = self.selection_color();
+        let hex_boxes = font_system.last_resort() == crate::LastResort::HexBox;
         self.with_buffer(|buffer| {
             let size = buffer.size();
             f(0, 0, size.0 as u32, size.1 as u32, background_color);
             let font_size = buffer.metrics().font_size;
-            let line_height = buffer.metrics().line_height;
+            let line_height = buffer.line_height();
             for run in buffer.layout_runs() {
                 let line_i = run.line_i;
                 let line_y = run.line_y;
-                let line_top = run.line_top;
 
                 let cursor_glyph_opt = |cursor: &Cursor| -> Option<(usize, f32, f32)> {
                     //TODO: better calculation of width
@@ -363,7 +363,7 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                             // Guess x offset based on characters
                             let cluster = &run.text[glyph.start..glyph.end];
                             let total = cluster.grapheme_indices(true).count();
-                            let mut c_x = glyph.x;
+                            let mut c_x = run.glyph_position(glyph);
                             let c_w = glyph.w / total as f32;
                             for (i, c) in cluster.grapheme_indices(true) {
                                 let c_start = glyph.start + i;
@@ -379,11 +379,13 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                                         None => Some((c_x as i32, (c_x + c_w) as i32)),
                                     };
                                 } else if let Some((min, max)) = range_opt.take() {
+                                    let (x, y, w, h) =
+                                        run.line_rect(min as f32, max as f32, line_height);
                                     f(
-                                        min,
-                                        line_top as i32,
-                                        cmp::max(0, max - min) as u32,
-                                        line_height as u32,
+                                        x as i32,
+                                        y as i32,
+                                        w.max(0.0) as u32,
+                                        h as u32,
                                         selection_color,
                                     );
                                 }
@@ -393,7 +395,7 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
 
                         if run.glyphs.is_empty() && end.line > line_i {
                             // Highlight all of internal empty lines
-                            range_opt = Some((0, buffer.size().0 as i32));
+                            range_opt = Some((0, buffer.inline_block_size().0 as i32));
                         }
 
                         if let Some((mut min, mut max)) = range_opt.take() {
@@ -402,14 +404,15 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                                 if run.rtl {
                                     min = 0;
                                 } else {
-                                    max = buffer.size().0 as i32;
+                                    max = buffer.inline_block_size().0 as i32;
                                 }
                             }
+                            let (x, y, w, h) = run.line_rect(min as f32, max as f32, line_height);
                             f(
-                                min,
-                                line_top as i32,
-                                cmp::max(0, max - min) as u32,
-                                line_height as u32,
+                                x as i32,
+                                y as i32,
+                                w.max(0.0) as u32,
+                                h as u32,
                                 selection_color,
                             );
                         }
@@ -434,14 +437,20 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                             // Start of detected glyph
                             if glyph.level.is_rtl() {
                                 (
-                                    (glyph.x + glyph.w - cursor_glyph_offset) as i32,
-                                    (glyph.x + glyph.w - cursor_glyph_offset - cursor_glyph_width)
+                                    (run.glyph_position(glyph) + glyph.w - cursor_glyph_offset)
+                                        as i32,
+                                    (run.glyph_position(glyph) + glyph.w
+                                        - cursor_glyph_offset
+                                        - cursor_glyph_width)
                                         as i32,
                                 )
                             } else {
                                 (
-                                    (glyph.x + cursor_glyph_offset) as i32,
-                                    (glyph.x + cursor_glyph_offset + cursor_glyph_width) as i32,
+                                    (run.glyph_position(glyph) + cursor_glyph_offset) as i32,
+                                    (run.glyph_position(glyph)
+                                        + cursor_glyph_offset
+                                        + cursor_glyph_width)
+                                        as i32,
                                 )
                             }
                         }
@@ -449,11 +458,15 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                             Some(glyph) => {
                                 // End of last glyph
                                 if glyph.level.is_rtl() {
-                                    (glyph.x as i32, (glyph.x - cursor_glyph_width) as i32)
+                                    (
+                                        run.glyph_position(glyph) as i32,
+                                        (glyph.x - cursor_glyph_width) as i32,
+                                    )
                                 } else {
                                     (
-                                        (glyph.x + glyph.w) as i32,
-                                        (glyph.x + glyph.w + cursor_glyph_width) as i32,
+                                        (run.glyph_position(glyph) + glyph.w) as i32,
+                                        (run.glyph_position(glyph) + glyph.w + cursor_glyph_width)
+                                            as i32,
                                     )
                                 }
                             }
@@ -467,32 +480,40 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                     if block_cursor {
                         let left_x = cmp::min(start_x, end_x);
                         let right_x = cmp::max(start_x, end_x);
-                        f(
-                            left_x,
-                            line_top as i32,
-                            (right_x - left_x) as u32,
-                            line_height as u32,
-                            selection_color,
-                        );
+                        let (x, y, w, h) =
+                            run.line_rect(left_x as f32, right_x as f32, line_height);
+                        f(x as i32, y as i32, w as u32, h as u32, selection_color);
                     } else {
-                        f(
-                            start_x,
-                            line_top as i32,
-                            1,
-                            line_height as u32,
-                            cursor_color,
-                        );
+                        let (x, y, w, h) =
+                            run.line_rect(start_x as f32, start_x as f32 + 1.0, line_height);
+                        f(x as i32, y as i32, w as u32, h as u32, cursor_color);
                     }
                 }
 
                 for glyph in run.glyphs.iter() {
-                    let physical_glyph = glyph.physical((0., 0.), 1.0);
+                    if glyph.has_placeholder {
+                        continue;
+                    }
+                    let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
 
                     let glyph_color = match glyph.color_opt {
                         Some(some) => some,
                         None => foreground_color,
                     };
 
+                    if hex_boxes
+                        && crate::draw_glyph_hex_box(
+                            run.text,
+                            glyph,
+                            &physical_glyph,
+                            line_y,
+                            glyph_color,
+                            &mut f,
+                        )
+                    {
+                        continue;
+                    }
+
                     cache.with_pixels(
                         font_system,
                         physical_glyph.cache_key,
@@ -508,6 +529,26 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                         },
                     );
                 }
+
+                // Ruby text, drop caps, and list markers are drawn like the glyphs of the line
+                let ruby_glyphs = run.ruby.iter().flat_map(|ruby| ruby.glyphs.iter());
+                for glyph in ruby_glyphs.chain(run.drop_cap).chain(run.marker) {
+                    let physical_glyph = glyph.physical((run.line_x, 0.), 1.0);
+                    cache.with_pixels(
+                        font_system,
+                        physical_glyph.cache_key,
+                        glyph.color_opt.unwrap_or(foreground_color),
+                        |x, y, color| {
+                            f(
+                                physical_glyph.x + x,
+                                line_y as i32 + physical_glyph.y + y,
+                                1,
+                                1,
+                                color,
+                            );
+                        },
+                    );
+                }
             }
         });
     }
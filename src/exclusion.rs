@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A rectangle that text flows around, like a floated image, see [`crate::Buffer::set_exclusions`]
+///
+/// Positions are in pixels from the top left of the text, which is not moved by scrolling.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Exclusion {
+    /// X position of the left of the rectangle
+    pub x: f32,
+    /// Y position of the top of the rectangle
+    pub y: f32,
+    /// Width of the rectangle
+    pub w: f32,
+    /// Height of the rectangle
+    pub h: f32,
+}
+
+impl Exclusion {
+    /// Create an exclusion from its position and size
+    pub const fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h }
+    }
+}
+
+/// Callback that gives the width to wrap each visual line of a buffer to, from the index of the
+/// line counted from the top of the text, see [`crate::Buffer::set_line_width`]
+#[derive(Clone)]
+pub struct LineWidth(Arc<dyn Fn(usize) -> f32 + Send + Sync>);
+
+impl LineWidth {
+    /// Create a line width callback from a function of the visual line index
+    pub fn new<F: Fn(usize) -> f32 + Send + Sync + 'static>(f: F) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Get the width of the visual line at `line_i`
+    pub fn width(&self, line_i: usize) -> f32 {
+        (self.0)(line_i)
+    }
+}
+
+impl fmt::Debug for LineWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LineWidth")
+    }
+}
+
+/// The lines of a paragraph laid out around exclusions, one line height apart from `top`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ExclusionBands<'a> {
+    pub exclusions: &'a [Exclusion],
+    /// Width of each visual line, if it is not the whole width
+    pub line_width_opt: Option<&'a LineWidth>,
+    /// Index of the first visual line of the paragraph, counted from the top of the text
+    pub first_line_i: usize,
+    /// Top of the first line of the paragraph
+    pub top: f32,
+    pub line_height: f32,
+    pub width: f32,
+}
+
+impl<'a> ExclusionBands<'a> {
+    /// Get the space left free on the left and on the right of a line of the paragraph
+    ///
+    /// Lines are placed in the widest part of the width that no exclusion overlaps, and keep the
+    /// whole width if exclusions overlap all of it. A line narrowed by its line width keeps its
+    /// left edge.
+    pub fn insets(&self, line_i: usize) -> (f32, f32) {
+        let width = self.line_width_opt.map_or(self.width, |line_width| {
+            line_width
+                .width(self.first_line_i + line_i)
+                .clamp(0.0, self.width)
+        });
+        let top = self.top + line_i as f32 * self.line_height;
+        let bottom = top + self.line_height;
+        let mut covered: Vec<(f32, f32)> = self
+            .exclusions
+            .iter()
+            .filter(|exclusion| exclusion.y < bottom && exclusion.y + exclusion.h > top)
+            .map(|exclusion| (exclusion.x.max(0.0), (exclusion.x + exclusion.w).min(width)))
+            .filter(|(start, end)| start < end)
+            .collect();
+        covered.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Find the widest gap between covered ranges, the first one if several are as wide
+        let mut free: Option<(f32, f32)> = None;
+        let mut gap_start: f32 = 0.0;
+        for (start, end) in covered.into_iter().chain([(width, width)]) {
+            if start > gap_start
+                && free.map_or(true, |(free_start, free_end)| {
+                    start - gap_start > free_end - free_start
+                })
+            {
+                free = Some((gap_start, start));
+            }
+            gap_start = gap_start.max(end);
+        }
+        match free {
+            Some((start, end)) => (start, self.width - end),
+            None => (0.0, self.width - width),
+        }
+    }
+}
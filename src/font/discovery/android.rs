@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::string::String;
+use std::vec::Vec;
+
+use super::CatalogFace;
+
+/// Directories of system fonts, including those moved to the product partition
+#[cfg(target_os = "android")]
+const FONT_DIRS: &[&str] = &["/system/fonts", "/product/fonts"];
+
+/// Font configuration files, newest first
+#[cfg(target_os = "android")]
+const FONTS_XML: &[&str] = &["/system/etc/font_fallback.xml", "/system/etc/fonts.xml"];
+
+/// Load the fonts of `/system/fonts`, classified and assigned to generic families as
+/// `fonts.xml` lists them
+#[cfg(target_os = "android")]
+pub(crate) fn load_system_fonts(db: &mut fontdb::Database) {
+    for dir in FONT_DIRS {
+        db.load_fonts_dir(dir);
+    }
+
+    let Some(xml) = FONTS_XML
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+    else {
+        log::warn!("failed to find fonts.xml");
+        return;
+    };
+    let catalog = parse_fonts_xml(&xml);
+    super::reclassify_faces(db, &catalog);
+
+    if let Some(family) = super::generic_family(db, &catalog, "sans-serif") {
+        db.set_sans_serif_family(family);
+    }
+    if let Some(family) = super::generic_family(db, &catalog, "serif") {
+        db.set_serif_family(family);
+    }
+    if let Some(family) = super::generic_family(db, &catalog, "monospace") {
+        db.set_monospace_family(family);
+    }
+    if let Some(family) = super::generic_family(db, &catalog, "cursive") {
+        db.set_cursive_family(family);
+    }
+}
+
+/// Parse the faces listed by `fonts.xml`, with the name of their family if it has one
+pub(crate) fn parse_fonts_xml(xml: &str) -> Vec<CatalogFace> {
+    let document = match roxmltree::Document::parse(xml) {
+        Ok(document) => document,
+        Err(err) => {
+            log::warn!("failed to parse fonts.xml: {}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut faces = Vec::new();
+    for family in document
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("family"))
+    {
+        let family_name = family.attribute("name").map(String::from);
+        for font in family.children().filter(|node| node.has_tag_name("font")) {
+            // The file name comes before any axis elements
+            let Some(file_name) = font
+                .children()
+                .find(|node| node.is_text())
+                .and_then(|node| node.text())
+                .map(str::trim)
+                .filter(|file_name| !file_name.is_empty())
+            else {
+                continue;
+            };
+            faces.push(CatalogFace {
+                family: family_name.clone(),
+                file_name: file_name.into(),
+                index: Some(
+                    font.attribute("index")
+                        .and_then(|index| index.parse().ok())
+                        .unwrap_or(0),
+                ),
+                post_script_name: None,
+                weight: font
+                    .attribute("weight")
+                    .and_then(|weight| weight.parse().ok())
+                    .unwrap_or(400),
+                italic: font.attribute("style") == Some("italic"),
+            });
+        }
+    }
+    faces
+}
+
+#[test]
+fn test_parse_fonts_xml() {
+    let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<familyset version="23">
+    <family name="sans-serif">
+        <font weight="100" style="normal">Roboto-Regular.ttf
+            <axis tag="wght" stylevalue="100" />
+        </font>
+        <font weight="400" style="italic">Roboto-Italic.ttf</font>
+    </family>
+    <alias name="arial" to="sans-serif" />
+    <family lang="zh-Hans">
+        <font weight="400" style="normal" index="2" postScriptName="NotoSansCJKjp-Regular">
+            NotoSansCJK-Regular.ttc
+        </font>
+    </family>
+</familyset>"#;
+    let faces = parse_fonts_xml(xml);
+    assert_eq!(
+        faces,
+        [
+            CatalogFace {
+                family: Some("sans-serif".into()),
+                file_name: "Roboto-Regular.ttf".into(),
+                index: Some(0),
+                post_script_name: None,
+                weight: 100,
+                italic: false,
+            },
+            CatalogFace {
+                family: Some("sans-serif".into()),
+                file_name: "Roboto-Italic.ttf".into(),
+                index: Some(0),
+                post_script_name: None,
+                weight: 400,
+                italic: true,
+            },
+            CatalogFace {
+                family: None,
+                file_name: "NotoSansCJK-Regular.ttc".into(),
+                index: Some(2),
+                post_script_name: None,
+                weight: 400,
+                italic: false,
+            },
+        ]
+    );
+
+    assert_eq!(parse_fonts_xml("<familyset>"), []);
+}
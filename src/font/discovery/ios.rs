@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::ffi::c_void;
+use core::ptr;
+use std::path::PathBuf;
+use std::vec::Vec;
+
+use core_foundation::array::{CFArray, CFArrayRef};
+use core_foundation::base::{CFRelease, CFType, CFTypeRef, TCFType};
+use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+use core_foundation::number::CFNumber;
+use core_foundation::string::{CFString, CFStringRef};
+use core_foundation::url::CFURL;
+
+use super::CatalogFace;
+
+type CTFontCollectionRef = *const c_void;
+type CTFontDescriptorRef = *const c_void;
+
+/// `kCTFontItalicTrait` of `CTFontSymbolicTraits`
+const CT_FONT_ITALIC_TRAIT: u32 = 1 << 0;
+
+#[link(name = "CoreText", kind = "framework")]
+extern "C" {
+    static kCTFontURLAttribute: CFStringRef;
+    static kCTFontNameAttribute: CFStringRef;
+    static kCTFontTraitsAttribute: CFStringRef;
+    static kCTFontWeightTrait: CFStringRef;
+    static kCTFontSymbolicTrait: CFStringRef;
+
+    fn CTFontCollectionCreateFromAvailableFonts(options: CFDictionaryRef) -> CTFontCollectionRef;
+    fn CTFontCollectionCreateMatchingFontDescriptors(collection: CTFontCollectionRef)
+        -> CFArrayRef;
+    fn CTFontDescriptorCopyAttribute(
+        descriptor: CTFontDescriptorRef,
+        attribute: CFStringRef,
+    ) -> CFTypeRef;
+}
+
+/// Directory of the fonts shipped with iOS, which CoreText lists only in part
+const FONT_DIR: &str = "/System/Library/Fonts";
+
+/// Weight traits of CoreText for the standard weights, from `UIFontWeightUltraLight` to
+/// `UIFontWeightBlack`
+const WEIGHT_TRAITS: [(f64, u16); 9] = [
+    (-0.8, 100),
+    (-0.6, 200),
+    (-0.4, 300),
+    (0.0, 400),
+    (0.23, 500),
+    (0.3, 600),
+    (0.4, 700),
+    (0.56, 800),
+    (0.62, 900),
+];
+
+/// Load the fonts listed by CoreText, classified the way CoreText classifies them
+pub(crate) fn load_system_fonts(db: &mut fontdb::Database) {
+    db.load_fonts_dir(FONT_DIR);
+
+    let catalog = catalog_faces();
+    let mut paths: Vec<&PathBuf> = catalog
+        .iter()
+        .map(|(path, _)| path)
+        .filter(|path| !path.starts_with(FONT_DIR))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    for path in paths {
+        if let Err(err) = db.load_font_file(path) {
+            log::warn!("failed to load font {:?}: {}", path, err);
+        }
+    }
+
+    let catalog: Vec<CatalogFace> = catalog.into_iter().map(|(_, face)| face).collect();
+    super::reclassify_faces(db, &catalog);
+
+    let has_family = |db: &fontdb::Database, family: &str| {
+        db.faces()
+            .any(|face| face.families.iter().any(|(name, _)| name == family))
+    };
+    if has_family(db, "Helvetica") {
+        db.set_sans_serif_family("Helvetica");
+    }
+    if has_family(db, "Times New Roman") {
+        db.set_serif_family("Times New Roman");
+    }
+    if has_family(db, "Menlo") {
+        db.set_monospace_family("Menlo");
+    }
+}
+
+/// Faces of all fonts available to CoreText, with the paths of their files
+fn catalog_faces() -> Vec<(PathBuf, CatalogFace)> {
+    let descriptors = unsafe {
+        let collection = CTFontCollectionCreateFromAvailableFonts(ptr::null());
+        if collection.is_null() {
+            return Vec::new();
+        }
+        let descriptors = CTFontCollectionCreateMatchingFontDescriptors(collection);
+        CFRelease(collection);
+        if descriptors.is_null() {
+            return Vec::new();
+        }
+        CFArray::<CFType>::wrap_under_create_rule(descriptors)
+    };
+
+    descriptors
+        .iter()
+        .filter_map(|descriptor| {
+            let descriptor = descriptor.as_CFTypeRef();
+            let path = copy_attribute(descriptor, unsafe { kCTFontURLAttribute })?
+                .downcast::<CFURL>()?
+                .to_path()?;
+            let file_name = path.file_name()?.to_string_lossy().into_owned();
+            let post_script_name = copy_attribute(descriptor, unsafe { kCTFontNameAttribute })
+                .and_then(|name| name.downcast::<CFString>())
+                .map(|name| name.to_string());
+
+            let traits = copy_attribute(descriptor, unsafe { kCTFontTraitsAttribute })
+                .and_then(|traits| traits.downcast::<CFDictionary>());
+            let number = |key: CFStringRef| {
+                let value = traits.as_ref()?.find(key as *const c_void)?;
+                unsafe { CFType::wrap_under_get_rule(*value) }.downcast::<CFNumber>()
+            };
+            let weight = number(unsafe { kCTFontWeightTrait })
+                .and_then(|weight| weight.to_f64())
+                .map_or(400, weight_from_trait);
+            let symbolic = number(unsafe { kCTFontSymbolicTrait })
+                .and_then(|symbolic| symbolic.to_i64())
+                .unwrap_or(0);
+
+            Some((
+                path,
+                CatalogFace {
+                    family: None,
+                    file_name,
+                    index: None,
+                    post_script_name,
+                    weight,
+                    italic: symbolic as u32 & CT_FONT_ITALIC_TRAIT != 0,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn copy_attribute(descriptor: CTFontDescriptorRef, attribute: CFStringRef) -> Option<CFType> {
+    let value = unsafe { CTFontDescriptorCopyAttribute(descriptor, attribute) };
+    if value.is_null() {
+        None
+    } else {
+        Some(unsafe { CFType::wrap_under_create_rule(value) })
+    }
+}
+
+/// The standard weight closest to a CoreText weight trait
+fn weight_from_trait(value: f64) -> u16 {
+    WEIGHT_TRAITS
+        .iter()
+        .min_by(|(a, _), (b, _)| (a - value).abs().total_cmp(&(b - value).abs()))
+        .map_or(400, |(_, weight)| *weight)
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Discovery of system fonts on platforms where `fontdb` finds few or misclassified faces
+
+use std::string::String;
+use std::vec::Vec;
+
+#[cfg(any(target_os = "android", test))]
+mod android;
+
+#[cfg(target_os = "ios")]
+mod ios;
+
+#[cfg(target_os = "android")]
+pub(crate) use self::android::load_system_fonts;
+
+#[cfg(target_os = "ios")]
+pub(crate) use self::ios::load_system_fonts;
+
+/// A face listed by the font catalog of the platform, with the weight and style it uses
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CatalogFace {
+    /// Family the catalog lists the face under, which may be a generic family like `sans-serif`
+    pub family: Option<String>,
+    /// Name of the font file
+    pub file_name: String,
+    /// Index of the face in a font collection, if the catalog tells
+    pub index: Option<u32>,
+    /// PostScript name of the face, if the catalog tells
+    pub post_script_name: Option<String>,
+    pub weight: u16,
+    pub italic: bool,
+}
+
+impl CatalogFace {
+    fn matches(&self, face: &fontdb::FaceInfo) -> bool {
+        let path = match &face.source {
+            fontdb::Source::File(path) | fontdb::Source::SharedFile(path, _) => path,
+            fontdb::Source::Binary(_) => return false,
+        };
+        path.file_name()
+            .map_or(false, |file_name| file_name == self.file_name.as_str())
+            && self.index.map_or(true, |index| index == face.index)
+            && self
+                .post_script_name
+                .as_ref()
+                .map_or(true, |name| *name == face.post_script_name)
+    }
+}
+
+/// Set the weight and style of faces in `db` to those of the catalog
+///
+/// Faces the catalog lists more than once, such as variable fonts listed once per weight, are
+/// left as parsed, since only their default instance is loaded.
+pub(crate) fn reclassify_faces(db: &mut fontdb::Database, catalog: &[CatalogFace]) {
+    let mut reclassified = Vec::new();
+    for face in db.faces() {
+        let mut matches = catalog
+            .iter()
+            .filter(|catalog_face| catalog_face.matches(face));
+        let (Some(catalog_face), None) = (matches.next(), matches.next()) else {
+            continue;
+        };
+
+        let weight = fontdb::Weight(catalog_face.weight);
+        let style = match (catalog_face.italic, face.style) {
+            (false, _) => fontdb::Style::Normal,
+            (true, fontdb::Style::Oblique) => fontdb::Style::Oblique,
+            (true, _) => fontdb::Style::Italic,
+        };
+        if face.weight != weight || face.style != style {
+            let mut info = face.clone();
+            info.weight = weight;
+            info.style = style;
+            reclassified.push(info);
+        }
+    }
+
+    for mut info in reclassified {
+        log::debug!(
+            "Reclassifying {} as {:?} {:?}",
+            info.post_script_name,
+            info.weight,
+            info.style
+        );
+        db.remove_face(info.id);
+        info.id = fontdb::ID::dummy();
+        db.push_face_info(info);
+    }
+}
+
+/// Family name of the regular face the catalog lists under `generic`, such as `sans-serif`
+pub(crate) fn generic_family(
+    db: &fontdb::Database,
+    catalog: &[CatalogFace],
+    generic: &str,
+) -> Option<String> {
+    let catalog_face = catalog
+        .iter()
+        .filter(|catalog_face| catalog_face.family.as_deref() == Some(generic))
+        .min_by_key(|catalog_face| (catalog_face.italic, catalog_face.weight.abs_diff(400)))?;
+    let face = db.faces().find(|face| catalog_face.matches(face))?;
+    face.families.first().map(|(name, _)| name.clone())
+}
+
+#[test]
+fn test_reclassify_faces() {
+    let mut db = fontdb::Database::new();
+    db.push_face_info(fontdb::FaceInfo {
+        id: fontdb::ID::dummy(),
+        source: fontdb::Source::File("/system/fonts/NotoSerif-BoldItalic.ttf".into()),
+        index: 0,
+        families: vec![("Noto Serif".into(), fontdb::Language::English_UnitedStates)],
+        post_script_name: "NotoSerif-BoldItalic".into(),
+        style: fontdb::Style::Normal,
+        weight: fontdb::Weight::NORMAL,
+        stretch: fontdb::Stretch::Normal,
+        monospaced: false,
+    });
+    let catalog_face = |weight, italic| CatalogFace {
+        family: Some("serif".into()),
+        file_name: "NotoSerif-BoldItalic.ttf".into(),
+        index: Some(0),
+        post_script_name: None,
+        weight,
+        italic,
+    };
+
+    let catalog = [catalog_face(700, true)];
+    reclassify_faces(&mut db, &catalog);
+    let face = db.faces().next().unwrap();
+    assert_eq!(face.weight, fontdb::Weight::BOLD);
+    assert_eq!(face.style, fontdb::Style::Italic);
+    assert_eq!(
+        generic_family(&db, &catalog, "serif").as_deref(),
+        Some("Noto Serif")
+    );
+    assert_eq!(generic_family(&db, &catalog, "monospace"), None);
+
+    // Listed twice, like a variable font, so it keeps its classification
+    reclassify_faces(
+        &mut db,
+        &[catalog_face(100, false), catalog_face(900, false)],
+    );
+    let face = db.faces().next().unwrap();
+    assert_eq!(face.weight, fontdb::Weight::BOLD);
+    assert_eq!(db.len(), 1);
+}
@@ -6,23 +6,26 @@ use alloc::vec::Vec;
 use fontdb::Family;
 use unicode_script::Script;
 
-use crate::{Font, FontMatchKey, FontSystem, ShapePlanCache};
+use crate::{Font, FontMatchKey, FontSystem, LastResort, ShapePlanCache};
 
 use self::platform::*;
 
-#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows",)))]
+#[cfg(any(
+    feature = "no-system-fonts",
+    not(any(target_os = "linux", target_os = "macos", target_os = "windows",))
+))]
 #[path = "other.rs"]
 mod platform;
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", not(feature = "no-system-fonts")))]
 #[path = "macos.rs"]
 mod platform;
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(feature = "no-system-fonts")))]
 #[path = "unix.rs"]
 mod platform;
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", not(feature = "no-system-fonts")))]
 #[path = "windows.rs"]
 mod platform;
 
@@ -49,10 +52,13 @@ pub struct FontFallbackIter<'a> {
     monospace_fallbacks: BTreeSet<MonospaceFallbackInfo>,
     default_i: usize,
     scripts: &'a [Script],
+    lang_opt: Option<&'a str>,
     word: &'a str,
+    rule_i: usize,
     script_i: (usize, usize),
     common_i: usize,
     other_i: usize,
+    last_resort_i: usize,
     end: bool,
 }
 
@@ -62,6 +68,7 @@ impl<'a> FontFallbackIter<'a> {
         font_match_keys: &'a [FontMatchKey],
         default_families: &'a [&'a Family<'a>],
         scripts: &'a [Script],
+        lang_opt: Option<&'a str>,
         word: &'a str,
     ) -> Self {
         Self {
@@ -71,27 +78,35 @@ impl<'a> FontFallbackIter<'a> {
             monospace_fallbacks: BTreeSet::new(),
             default_i: 0,
             scripts,
+            lang_opt,
             word,
+            rule_i: 0,
             script_i: (0, 0),
             common_i: 0,
             other_i: 0,
+            last_resort_i: 0,
             end: false,
         }
     }
 
+    /// Get the language of the text if it is set, or the locale of the font system
+    fn locale(&self) -> &str {
+        self.lang_opt.unwrap_or(self.font_system.locale())
+    }
+
     pub fn check_missing(&mut self, word: &str) {
         if self.end {
             missing_warn!(
                 "Failed to find any fallback for {:?} locale '{}': '{}'",
                 self.scripts,
-                self.font_system.locale(),
+                self.locale(),
                 word
             );
         } else if self.other_i > 0 {
             missing_warn!(
                 "Failed to find preset fallback for {:?} locale '{}', used '{}': '{}'",
                 self.scripts,
-                self.font_system.locale(),
+                self.locale(),
                 self.face_name(self.font_match_keys[self.other_i - 1].id),
                 word
             );
@@ -100,7 +115,7 @@ impl<'a> FontFallbackIter<'a> {
             missing_warn!(
                 "Failed to find script fallback for {:?} locale '{}', used '{}': '{}'",
                 self.scripts,
-                self.font_system.locale(),
+                self.locale(),
                 family,
                 word
             );
@@ -255,10 +270,37 @@ impl<'a> Iterator for FontFallbackIter<'a> {
             }
         }
 
+        while self.rule_i < self.font_system.fallback_rules().len() {
+            let rule = &self.font_system.fallback_rules()[self.rule_i];
+            self.rule_i += 1;
+            if !rule.matches(self.word) {
+                continue;
+            }
+            // Prefer the face closest to the weight of the text
+            let id_opt = self
+                .font_match_keys
+                .iter()
+                .filter(|m_key| self.face_contains_family(m_key.id, &rule.family))
+                .min_by_key(|m_key| m_key.font_weight_diff)
+                .map(|m_key| m_key.id);
+            match id_opt {
+                Some(id) => {
+                    if let Some(font) = self.font_system.get_font(id) {
+                        return Some(font);
+                    }
+                }
+                None => log::debug!("failed to find family '{}' of fallback rule", rule.family),
+            }
+        }
+
         while self.script_i.0 < self.scripts.len() {
             let script = self.scripts[self.script_i.0];
 
-            let script_families = script_fallback(script, self.font_system.locale());
+            let script_families = if self.font_system.system_fallback() {
+                script_fallback(script, han_locale(self.locale()))
+            } else {
+                &[]
+            };
             while self.script_i.1 < script_families.len() {
                 let script_family = script_families[self.script_i.1];
                 self.script_i.1 += 1;
@@ -273,7 +315,7 @@ impl<'a> Iterator for FontFallbackIter<'a> {
                     "failed to find family '{}' for script {:?} and locale '{}'",
                     script_family,
                     script,
-                    self.font_system.locale(),
+                    self.locale(),
                 );
             }
 
@@ -281,7 +323,11 @@ impl<'a> Iterator for FontFallbackIter<'a> {
             self.script_i.1 = 0;
         }
 
-        let common_families = common_fallback();
+        let common_families = if self.font_system.system_fallback() {
+            common_fallback()
+        } else {
+            &[]
+        };
         while self.common_i < common_families.len() {
             let common_family = common_families[self.common_i];
             self.common_i += 1;
@@ -298,12 +344,18 @@ impl<'a> Iterator for FontFallbackIter<'a> {
         //TODO: do we need to do this?
         //TODO: do not evaluate fonts more than once!
         let forbidden_families = forbidden_fallback();
+        let last_resort_id_opt = match self.font_system.last_resort() {
+            LastResort::Font(id) => Some(id),
+            _ => None,
+        };
         while self.other_i < self.font_match_keys.len() {
             let id = self.font_match_keys[self.other_i].id;
             self.other_i += 1;
-            if forbidden_families
-                .iter()
-                .all(|family_name| !self.face_contains_family(id, family_name))
+            // The last resort face is only used once all other fonts have been tried
+            if Some(id) != last_resort_id_opt
+                && forbidden_families
+                    .iter()
+                    .all(|family_name| !self.face_contains_family(id, family_name))
             {
                 if let Some(font) = self.font_system.get_font(id) {
                     return Some(font);
@@ -311,7 +363,57 @@ impl<'a> Iterator for FontFallbackIter<'a> {
             }
         }
 
+        if self.last_resort_i == 0 {
+            self.last_resort_i += 1;
+            if let Some(id) = last_resort_id_opt {
+                if let Some(font) = self.font_system.get_font(id) {
+                    return Some(font);
+                }
+            }
+        }
+
         self.end = true;
         None
     }
 }
+
+/// Get the locale of the Han fonts used for a BCP 47 language tag, which is one of `ja`, `ko`,
+/// `zh-HK`, `zh-TW`, or `zh-CN` for Simplified Chinese and other languages
+fn han_locale(lang: &str) -> &'static str {
+    let mut subtags = lang.split(['-', '_', '.']);
+    let language = subtags.next().unwrap_or_default();
+    if language.eq_ignore_ascii_case("ja") {
+        return "ja";
+    } else if language.eq_ignore_ascii_case("ko") {
+        return "ko";
+    }
+
+    // Traditional Chinese is chosen by region or script
+    let mut traditional = false;
+    for subtag in subtags {
+        if subtag.eq_ignore_ascii_case("hk") || subtag.eq_ignore_ascii_case("mo") {
+            return "zh-HK";
+        } else if subtag.eq_ignore_ascii_case("tw") || subtag.eq_ignore_ascii_case("hant") {
+            traditional = true;
+        }
+    }
+    if traditional {
+        "zh-TW"
+    } else {
+        "zh-CN"
+    }
+}
+
+#[test]
+fn test_han_locale() {
+    assert_eq!(han_locale("ja"), "ja");
+    assert_eq!(han_locale("ja-JP"), "ja");
+    assert_eq!(han_locale("ko_KR.UTF-8"), "ko");
+    assert_eq!(han_locale("zh-HK"), "zh-HK");
+    assert_eq!(han_locale("zh-Hant-MO"), "zh-HK");
+    assert_eq!(han_locale("zh-TW"), "zh-TW");
+    assert_eq!(han_locale("zh-Hant"), "zh-TW");
+    assert_eq!(han_locale("zh-Hans"), "zh-CN");
+    assert_eq!(han_locale("zh"), "zh-CN");
+    assert_eq!(han_locale("en-US"), "zh-CN");
+}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::FamilyOwned;
+
+/// Character that the font chosen for its text does not have, recorded while shaping when
+/// [`crate::FontSystem::set_fallback_log`] is enabled
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FallbackEvent {
+    /// First character of the cluster that fell back
+    pub codepoint: char,
+    /// Family requested by the attributes of the text
+    pub family: FamilyOwned,
+    /// Font chosen for the text, which does not have the character
+    pub font_id: fontdb::ID,
+    /// Font chosen by fallback, or None if no font has the character
+    pub fallback_id_opt: Option<fontdb::ID>,
+}
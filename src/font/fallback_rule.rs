@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::ops::RangeInclusive;
+use unicode_script::{Script, UnicodeScript};
+
+/// Characters that a [`FallbackRule`] applies to
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FallbackTarget {
+    /// Characters in a range, like `'\u{4E00}'..='\u{9FFF}'` for CJK ideographs
+    Range(RangeInclusive<char>),
+    /// Characters of a script
+    Script(Script),
+    /// Emoji and other pictographs, from the symbol and dingbat blocks and the pictograph blocks
+    /// from U+1F000 to U+1FAFF, and characters followed by the emoji variation selector
+    Emoji,
+}
+
+impl FallbackTarget {
+    /// Check if a character is a target, where `next_opt` is the character after it
+    pub fn contains(&self, c: char, next_opt: Option<char>) -> bool {
+        match self {
+            Self::Range(range) => range.contains(&c),
+            Self::Script(script) => c.script() == *script,
+            Self::Emoji => {
+                matches!(c, '\u{2600}'..='\u{27BF}' | '\u{1F000}'..='\u{1FAFF}')
+                    || next_opt == Some('\u{FE0F}')
+            }
+        }
+    }
+}
+
+/// Family preferred by font fallback for some characters, see
+/// [`crate::FontSystem::add_fallback_rule`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FallbackRule {
+    /// Characters the rule applies to
+    pub target: FallbackTarget,
+    /// Name of the family used for them, like `Noto Sans CJK SC`
+    pub family: String,
+}
+
+impl FallbackRule {
+    /// Create a rule to use a family for the characters of a target
+    pub fn new(target: FallbackTarget, family: impl Into<String>) -> Self {
+        Self {
+            target,
+            family: family.into(),
+        }
+    }
+
+    /// Check if the rule applies to any character of a word
+    pub fn matches(&self, word: &str) -> bool {
+        let mut chars = word.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.target.contains(c, chars.peek().copied()) {
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -1,15 +1,25 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
+#[cfg(all(feature = "std", any(target_os = "android", target_os = "ios", test)))]
+pub(crate) mod discovery;
 pub(crate) mod fallback;
 
 use core::fmt;
 
 use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 
 use rustybuzz::Face as RustybuzzFace;
 use self_cell::self_cell;
 
+use crate::{BaselineShift, DecorationKind, FontInstance, VerticalMetrics, Weight};
+
+pub use self::fallback_event::*;
+mod fallback_event;
+
+pub use self::fallback_rule::*;
+mod fallback_rule;
+
 pub use self::system::*;
 mod system;
 
@@ -22,6 +32,17 @@ self_cell!(
     }
 );
 
+/// A named instance of a variable font, from its `fvar` table
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedInstance {
+    /// Name of the instance, like `Display Light`
+    pub name: String,
+    /// PostScript name of the instance, if the font has one
+    pub postscript_name_opt: Option<String>,
+    /// Value of every axis of the font, by tag
+    pub coords: Vec<([u8; 4], f32)>,
+}
+
 /// A font
 pub struct Font {
     #[cfg(feature = "swash")]
@@ -29,7 +50,12 @@ pub struct Font {
     rustybuzz: OwnedFace,
     data: Arc<dyn AsRef<[u8]> + Send + Sync>,
     id: fontdb::ID,
+    index: u32,
+    weight: Weight,
+    named_instances: Vec<NamedInstance>,
     monospace_em_width: Option<f32>,
+    digit_em_width: Option<f32>,
+    small_caps: bool,
     scripts: Vec<[u8; 4]>,
     unicode_codepoints: Vec<u32>,
 }
@@ -47,10 +73,135 @@ impl Font {
         self.id
     }
 
+    /// Get the weight of the face
+    pub fn weight(&self) -> Weight {
+        self.weight
+    }
+
     pub fn monospace_em_width(&self) -> Option<f32> {
         self.monospace_em_width
     }
 
+    /// Get the advance of the widest ASCII digit, in em, or None if a digit is missing
+    pub fn digit_em_width(&self) -> Option<f32> {
+        self.digit_em_width
+    }
+
+    /// Check if the font has the `smcp` feature for small capitals
+    pub fn has_small_caps(&self) -> bool {
+        self.small_caps
+    }
+
+    /// Get the ascent and descent in em, with the given convention
+    ///
+    /// Fonts without an `OS/2` table use their typographic metrics for [`VerticalMetrics::Gdi`].
+    pub fn ascent_descent_em(&self, vertical_metrics: VerticalMetrics) -> (f32, f32) {
+        let face = self.rustybuzz();
+        let units_per_em = face.units_per_em() as f32;
+        match (vertical_metrics, face.tables().os2) {
+            (VerticalMetrics::Gdi, Some(os2)) => (
+                f32::from(os2.windows_ascender()) / units_per_em,
+                -f32::from(os2.windows_descender()) / units_per_em,
+            ),
+            _ => (
+                f32::from(face.ascender()) / units_per_em,
+                -f32::from(face.descender()) / units_per_em,
+            ),
+        }
+    }
+
+    /// Get the default line height in em, with the given convention
+    ///
+    /// This is the ascent plus descent, with the line gap of the `hhea` table for
+    /// [`VerticalMetrics::Typographic`], as GDI leaves out the line gap.
+    pub fn line_height_em(&self, vertical_metrics: VerticalMetrics) -> f32 {
+        let (ascent, descent) = self.ascent_descent_em(vertical_metrics);
+        match vertical_metrics {
+            VerticalMetrics::Typographic => {
+                let face = self.rustybuzz();
+                ascent + descent + f32::from(face.line_gap()) / face.units_per_em() as f32
+            }
+            VerticalMetrics::Gdi => ascent + descent,
+        }
+    }
+
+    /// Get the height of capital letters above the baseline in em
+    ///
+    /// This comes from the `OS/2` table, or the top of the glyph for `H` if the table does not
+    /// have it.
+    pub fn cap_height_em(&self) -> f32 {
+        let face = self.rustybuzz();
+        let units_per_em = face.units_per_em() as f32;
+        let cap_height = face
+            .capital_height()
+            .filter(|&cap_height| cap_height > 0)
+            .or_else(|| {
+                face.glyph_index('H')
+                    .and_then(|glyph_id| face.glyph_bounding_box(glyph_id))
+                    .map(|bbox| bbox.y_max)
+            });
+        match cap_height {
+            Some(cap_height) => f32::from(cap_height) / units_per_em,
+            // Capitals are usually about this much of the ascent
+            None => self.ascent_descent_em(VerticalMetrics::Typographic).0 * 0.7,
+        }
+    }
+
+    /// Get the factor applied to the font size and the distance raised above the baseline in em
+    /// for a [`BaselineShift`]
+    ///
+    /// Superscripts and subscripts come from the `OS/2` table, with common defaults if it does not
+    /// have them.
+    pub fn baseline_shift_em(&self, baseline_shift: BaselineShift) -> (f32, f32) {
+        let face = self.rustybuzz();
+        let units_per_em = face.units_per_em() as f32;
+        let metrics = match baseline_shift {
+            BaselineShift::Em(em) => return (1.0, em),
+            BaselineShift::Superscript => face.superscript_metrics(),
+            BaselineShift::Subscript => face.subscript_metrics(),
+        };
+        match metrics.filter(|metrics| metrics.y_size > 0) {
+            Some(metrics) => {
+                let scale = f32::from(metrics.y_size) / units_per_em;
+                let offset = f32::from(metrics.y_offset) / units_per_em;
+                match baseline_shift {
+                    BaselineShift::Subscript => (scale, -offset),
+                    _ => (scale, offset),
+                }
+            }
+            None => match baseline_shift {
+                BaselineShift::Subscript => (0.65, -0.15),
+                _ => (0.65, 0.35),
+            },
+        }
+    }
+
+    /// Get the position of the top of a decoration line above the baseline and its thickness,
+    /// in em
+    ///
+    /// Underlines come from the `post` table and strikethroughs from the `OS/2` table, with
+    /// common defaults if they are missing. Overlines sit at the ascent, as fonts do not define
+    /// them.
+    pub fn decoration_em(&self, kind: DecorationKind) -> (f32, f32) {
+        let face = self.rustybuzz();
+        let units_per_em = face.units_per_em() as f32;
+        let metrics = match kind {
+            DecorationKind::Strikethrough => face.strikeout_metrics(),
+            DecorationKind::Underline | DecorationKind::Overline => face.underline_metrics(),
+        };
+        let (position, thickness) = match metrics {
+            Some(metrics) if metrics.thickness > 0 => (
+                f32::from(metrics.position) / units_per_em,
+                f32::from(metrics.thickness) / units_per_em,
+            ),
+            _ => kind.default_em(),
+        };
+        match kind {
+            DecorationKind::Overline => (f32::from(face.ascender()) / units_per_em, thickness),
+            DecorationKind::Underline | DecorationKind::Strikethrough => (position, thickness),
+        }
+    }
+
     pub fn scripts(&self) -> &[[u8; 4]] {
         &self.scripts
     }
@@ -67,6 +218,43 @@ impl Font {
         self.rustybuzz.borrow_dependent()
     }
 
+    /// Get the named instances of a variable font, empty for other fonts
+    pub fn named_instances(&self) -> &[NamedInstance] {
+        &self.named_instances
+    }
+
+    /// Get the index of a named instance, if the font has it
+    pub fn find_instance(&self, font_instance: FontInstance) -> Option<u16> {
+        match font_instance {
+            FontInstance::Index(index) => {
+                (usize::from(index) < self.named_instances.len()).then_some(index)
+            }
+            FontInstance::Name(name) => self
+                .named_instances
+                .iter()
+                .position(|instance| {
+                    instance.name.eq_ignore_ascii_case(name)
+                        || instance
+                            .postscript_name_opt
+                            .as_ref()
+                            .map_or(false, |postscript_name| {
+                                postscript_name.eq_ignore_ascii_case(name)
+                            })
+                })
+                .and_then(|index| u16::try_from(index).ok()),
+        }
+    }
+
+    /// Create a face with the axis values of a named instance, for shaping with its metrics
+    pub fn rustybuzz_instance(&self, instance: u16) -> Option<RustybuzzFace<'_>> {
+        let named_instance = self.named_instances.get(usize::from(instance))?;
+        let mut face = RustybuzzFace::from_slice(self.data(), self.index)?;
+        for (tag, value) in named_instance.coords.iter() {
+            face.set_variation(ttf_parser::Tag::from_bytes(tag), *value);
+        }
+        Some(face)
+    }
+
     #[cfg(feature = "swash")]
     pub fn as_swash(&self) -> swash::FontRef<'_> {
         let swash = &self.swash;
@@ -82,7 +270,14 @@ impl Font {
     pub fn new(db: &fontdb::Database, id: fontdb::ID) -> Option<Self> {
         let info = db.face(id)?;
 
-        let (monospace_em_width, scripts, unicode_codepoints) = {
+        let (
+            monospace_em_width,
+            digit_em_width,
+            small_caps,
+            scripts,
+            unicode_codepoints,
+            named_instances,
+        ) = {
             db.with_face_data(id, |font_data, face_index| {
                 let face = ttf_parser::Face::parse(font_data, face_index).ok()?;
                 let monospace_em_width = info
@@ -98,6 +293,18 @@ impl Font {
                     None?;
                 }
 
+                let digit_em_width = ('0'..='9')
+                    .map(|digit| face.glyph_hor_advance(face.glyph_index(digit)?))
+                    .collect::<Option<Vec<u16>>>()
+                    .and_then(|advances| advances.into_iter().max())
+                    .map(|hor_advance| hor_advance as f32 / face.units_per_em() as f32);
+
+                let small_caps = face.tables().gsub.map_or(false, |gsub| {
+                    gsub.features
+                        .into_iter()
+                        .any(|feature| feature.tag == ttf_parser::Tag::from_bytes(b"smcp"))
+                });
+
                 let scripts = face
                     .tables()
                     .gpos
@@ -125,7 +332,14 @@ impl Font {
 
                 unicode_codepoints.shrink_to_fit();
 
-                Some((monospace_em_width, scripts, unicode_codepoints))
+                Some((
+                    monospace_em_width,
+                    digit_em_width,
+                    small_caps,
+                    scripts,
+                    unicode_codepoints,
+                    named_instances(&face),
+                ))
             })?
         }?;
 
@@ -142,7 +356,12 @@ impl Font {
 
         Some(Self {
             id: info.id,
+            index: info.index,
+            weight: info.weight,
+            named_instances,
             monospace_em_width,
+            digit_em_width,
+            small_caps,
             scripts,
             unicode_codepoints,
             #[cfg(feature = "swash")]
@@ -158,3 +377,84 @@ impl Font {
         })
     }
 }
+
+/// Read the named instances of a variable font from its `fvar` table
+fn named_instances(face: &ttf_parser::Face) -> Vec<NamedInstance> {
+    let Some(fvar) = face.raw_face().table(ttf_parser::Tag::from_bytes(b"fvar")) else {
+        return Vec::new();
+    };
+    let read_u16 = |offset: usize| {
+        fvar.get(offset..offset + 2)
+            .map(|bytes| usize::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+    };
+    let (
+        Some(axes_offset),
+        Some(axis_count),
+        Some(axis_size),
+        Some(instance_count),
+        Some(instance_size),
+    ) = (
+        read_u16(4),
+        read_u16(8),
+        read_u16(10),
+        read_u16(12),
+        read_u16(14),
+    )
+    else {
+        return Vec::new();
+    };
+    let Some(tags) = (0..axis_count)
+        .map(|axis| {
+            let start = axes_offset + axis * axis_size;
+            fvar.get(start..start + 4)?.try_into().ok()
+        })
+        .collect::<Option<Vec<[u8; 4]>>>()
+    else {
+        return Vec::new();
+    };
+
+    // Instances follow the axes, with the PostScript name id only if the records have room
+    let instances_offset = axes_offset + axis_count * axis_size;
+    (0..instance_count)
+        .filter_map(|instance| {
+            let start = instances_offset + instance * instance_size;
+            let name_id = read_u16(start)?;
+            let coords = tags
+                .iter()
+                .enumerate()
+                .map(|(axis, tag)| {
+                    let offset = start + 4 + axis * 4;
+                    let bytes = fvar.get(offset..offset + 4)?;
+                    let fixed = i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    Some((*tag, fixed as f32 / 65536.0))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let postscript_name_opt = (instance_size >= 4 + axis_count * 4 + 2)
+                .then(|| read_u16(start + 4 + axis_count * 4))
+                .flatten()
+                .and_then(|name_id| face_name(face, name_id));
+            Some(NamedInstance {
+                name: face_name(face, name_id)?,
+                postscript_name_opt,
+                coords,
+            })
+        })
+        .collect()
+}
+
+/// Get a name from the `name` table, preferring English
+fn face_name(face: &ttf_parser::Face, name_id: usize) -> Option<String> {
+    face.names()
+        .into_iter()
+        .filter(|name| usize::from(name.name_id) == name_id && name.is_unicode())
+        .max_by_key(|name| name.language_id == 0x0409)
+        .and_then(|name| {
+            let units = name
+                .name
+                .chunks_exact(2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]));
+            char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .ok()
+        })
+}
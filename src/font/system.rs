@@ -1,4 +1,7 @@
-use crate::{Attrs, Font, FontMatchAttrs, HashMap, ShapePlanCache};
+use crate::{
+    Attrs, CacheStats, Font, FontMatchAttrs, HashMap, LastResort, LineBreaker, ShapeBuffer,
+    ShapeHook, ShapePlanCache,
+};
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
@@ -9,6 +12,19 @@ use core::ops::{Deref, DerefMut};
 pub use fontdb;
 pub use rustybuzz;
 
+/// Fonts bundled with the `embedded-fonts` feature, with the generic families they stand in for
+#[cfg(feature = "embedded-fonts")]
+const EMBEDDED_FONTS: &[(&[u8], &[fontdb::Family<'static>])] = &[
+    (
+        include_bytes!("../../fonts/NotoSans-Regular.ttf"),
+        &[fontdb::Family::SansSerif, fontdb::Family::Serif],
+    ),
+    (
+        include_bytes!("../../fonts/FiraMono-Medium.ttf"),
+        &[fontdb::Family::Monospace],
+    ),
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FontMatchKey {
     pub(crate) font_weight_diff: u16,
@@ -106,6 +122,82 @@ pub struct FontSystem {
     /// Cache for shaped runs
     #[cfg(feature = "shape-run-cache")]
     pub shape_run_cache: crate::ShapeRunCache,
+
+    /// Caches for shaped runs selected by [`crate::ShapeRunCacheMode::Shared`]
+    #[cfg(feature = "shape-run-cache")]
+    shared_shape_run_caches: HashMap<u32, crate::ShapeRunCache>,
+
+    /// Cache used while a buffer shapes its lines
+    #[cfg(feature = "shape-run-cache")]
+    pub(crate) shape_run_cache_mode: crate::ShapeRunCacheMode,
+
+    /// Dictionaries for line breaking in scripts without spaces between words
+    #[cfg(feature = "line-break-dictionary")]
+    line_break_dictionaries: Vec<crate::LineBreakDictionary>,
+
+    /// Segmenter with built-in dictionaries for Thai, Lao, Khmer, and Burmese, created when
+    /// first needed
+    #[cfg(feature = "icu-segmenter")]
+    line_segmenter_opt: Option<icu_segmenter::LineSegmenter>,
+
+    /// Patterns for breaking words with hyphens, by language
+    #[cfg(feature = "hyphenation")]
+    hyphenators: Vec<crate::Hyphenator>,
+
+    /// Language of the hyphenation patterns used while a buffer shapes its lines
+    #[cfg(feature = "hyphenation")]
+    pub(crate) hyphenation: Option<String>,
+
+    /// What to show for characters that no font supports
+    last_resort: LastResort,
+
+    /// Replacement for characters that no font supports, used while a buffer shapes its lines
+    pub(crate) missing_glyph: Option<crate::MissingGlyph>,
+
+    /// Strictness of line breaking, used while a buffer shapes its lines
+    pub(crate) line_break_strictness: crate::LineBreakStrictness,
+
+    /// Use vertical alternates for all text, set while a buffer shapes vertical lines
+    pub(crate) vertical_forms: bool,
+
+    /// If fallback may look for the system fonts of the platform by name
+    system_fallback: bool,
+
+    /// Families preferred by fallback for some characters, in the order they are tried
+    fallback_rules: Vec<crate::FallbackRule>,
+
+    /// Characters that fell back from the font of their text, if they are recorded
+    fallback_log_opt: Option<Vec<crate::FallbackEvent>>,
+
+    /// If bold is synthesized for text set with faces that are not bold
+    synthesize_bold: bool,
+
+    /// Callback that adjusts glyphs after shaping
+    shape_hook: Option<ShapeHook>,
+
+    /// Finds line break opportunities, instead of Unicode line breaking
+    line_breaker: Option<Arc<dyn LineBreaker>>,
+
+    /// Scratch buffers for shaping text without a [`crate::Buffer`]
+    pub(crate) scratch: ShapeBuffer,
+}
+
+/// Statistics of the fonts and caches of a [`FontSystem`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FontSystemStats {
+    /// Number of faces in the database
+    pub faces: usize,
+    /// Number of faces parsed into a [`Font`]
+    pub parsed_fonts: usize,
+    /// Bytes of font data held by parsed fonts, counting data shared by several faces once
+    pub font_data_bytes: usize,
+    /// Number of cached font match lists
+    pub font_matches: usize,
+    /// Statistics of the shape plan cache
+    pub shape_plan_cache: CacheStats,
+    /// Statistics of the shape run cache
+    #[cfg(feature = "shape-run-cache")]
+    pub shape_run_cache: CacheStats,
 }
 
 impl fmt::Debug for FontSystem {
@@ -121,6 +213,9 @@ impl FontSystem {
     const FONT_MATCHES_CACHE_SIZE_LIMIT: usize = 256;
     /// Create a new [`FontSystem`], that allows access to any installed system fonts
     ///
+    /// With the `no-system-fonts` feature, this is the same as
+    /// [`FontSystem::new_without_system_fonts`] without any fonts.
+    ///
     /// # Timing
     ///
     /// This function takes some time to run. On the release build, it can take up to a second,
@@ -132,6 +227,22 @@ impl FontSystem {
 
     /// Create a new [`FontSystem`] with a pre-specified set of fonts.
     pub fn new_with_fonts(fonts: impl IntoIterator<Item = fontdb::Source>) -> Self {
+        Self::new_with_fonts_inner(fonts, cfg!(not(feature = "no-system-fonts")))
+    }
+
+    /// Create a new [`FontSystem`] with only a pre-specified set of fonts, and those bundled with
+    /// the `embedded-fonts` feature
+    ///
+    /// System fonts are not enumerated, so this starts without delay, and fallback only tries
+    /// the given fonts instead of looking for the system fonts of the platform.
+    pub fn new_without_system_fonts(fonts: impl IntoIterator<Item = fontdb::Source>) -> Self {
+        Self::new_with_fonts_inner(fonts, false)
+    }
+
+    fn new_with_fonts_inner(
+        fonts: impl IntoIterator<Item = fontdb::Source>,
+        system_fonts: bool,
+    ) -> Self {
         let locale = Self::get_locale();
         log::debug!("Locale: {}", locale);
 
@@ -142,9 +253,55 @@ impl FontSystem {
         db.set_sans_serif_family("Fira Sans");
         db.set_serif_family("DejaVu Serif");
 
-        Self::load_fonts(&mut db, fonts.into_iter());
+        if system_fonts {
+            Self::load_system_fonts(&mut db);
+        }
+        for source in fonts {
+            db.load_font_source(source);
+        }
 
-        Self::new_with_locale_and_db(locale, db)
+        #[cfg(feature = "embedded-fonts")]
+        Self::load_embedded_fonts(&mut db);
+
+        let mut font_system = Self::new_with_locale_and_db(locale, db);
+        font_system.system_fallback = system_fonts;
+        font_system
+    }
+
+    /// Load the fonts bundled with the `embedded-fonts` feature into `db`, and use them for the
+    /// generic families that no font in `db` provides
+    ///
+    /// This is done by [`FontSystem::new`] and [`FontSystem::new_with_fonts`], so text renders the
+    /// same even without any system fonts.
+    #[cfg(feature = "embedded-fonts")]
+    pub fn load_embedded_fonts(db: &mut fontdb::Database) {
+        for (data, generic_families) in EMBEDDED_FONTS {
+            let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(*data)));
+            let Some(name) = ids
+                .first()
+                .and_then(|id| db.face(*id))
+                .and_then(|face| face.families.first())
+                .map(|(name, _)| name.clone())
+            else {
+                continue;
+            };
+
+            for generic_family in generic_families.iter() {
+                let current = db.family_name(generic_family);
+                let available = db
+                    .faces()
+                    .any(|face| face.families.iter().any(|(name, _)| name == current));
+                if available {
+                    continue;
+                }
+                match generic_family {
+                    fontdb::Family::Serif => db.set_serif_family(name.as_str()),
+                    fontdb::Family::SansSerif => db.set_sans_serif_family(name.as_str()),
+                    fontdb::Family::Monospace => db.set_monospace_family(name.as_str()),
+                    _ => {}
+                }
+            }
+        }
     }
 
     /// Create a new [`FontSystem`] with a pre-specified locale and font database.
@@ -171,6 +328,29 @@ impl FontSystem {
             shape_plan_cache: ShapePlanCache::default(),
             #[cfg(feature = "shape-run-cache")]
             shape_run_cache: crate::ShapeRunCache::default(),
+            #[cfg(feature = "shape-run-cache")]
+            shared_shape_run_caches: Default::default(),
+            #[cfg(feature = "shape-run-cache")]
+            shape_run_cache_mode: crate::ShapeRunCacheMode::default(),
+            #[cfg(feature = "line-break-dictionary")]
+            line_break_dictionaries: Vec::new(),
+            #[cfg(feature = "icu-segmenter")]
+            line_segmenter_opt: None,
+            #[cfg(feature = "hyphenation")]
+            hyphenators: Vec::new(),
+            #[cfg(feature = "hyphenation")]
+            hyphenation: None,
+            last_resort: LastResort::default(),
+            missing_glyph: None,
+            line_break_strictness: crate::LineBreakStrictness::default(),
+            vertical_forms: false,
+            system_fallback: cfg!(not(feature = "no-system-fonts")),
+            fallback_rules: Vec::new(),
+            fallback_log_opt: None,
+            synthesize_bold: true,
+            shape_hook: None,
+            line_breaker: None,
+            scratch: ShapeBuffer::default(),
         };
 
         cloned_monospace_font_ids.into_iter().for_each(|id| {
@@ -201,6 +381,316 @@ impl FontSystem {
         &mut self.shape_plan_cache
     }
 
+    /// Get hit and miss counters and the entry count of the shape plan cache.
+    pub fn shape_plan_cache_stats(&self) -> CacheStats {
+        self.shape_plan_cache.stats()
+    }
+
+    /// Reset hit and miss counters of the shape plan cache.
+    pub fn reset_shape_plan_cache_stats(&mut self) {
+        self.shape_plan_cache.reset_stats();
+    }
+
+    /// Get the number of loaded and parsed fonts, the bytes they hold, and the sizes of caches.
+    pub fn stats(&self) -> FontSystemStats {
+        let mut font_data = Vec::new();
+        for font in self.font_cache.values().flatten() {
+            let data = Arc::as_ptr(&font.data) as *const u8;
+            if !font_data.iter().any(|(ptr, _len)| *ptr == data) {
+                font_data.push((data, font.data().len()));
+            }
+        }
+
+        FontSystemStats {
+            faces: self.db.len(),
+            parsed_fonts: self.font_cache.values().flatten().count(),
+            font_data_bytes: font_data.iter().map(|(_ptr, len)| len).sum(),
+            font_matches: self.font_matches_cache.len(),
+            shape_plan_cache: self.shape_plan_cache.stats(),
+            #[cfg(feature = "shape-run-cache")]
+            shape_run_cache: self.shape_run_cache.stats(),
+        }
+    }
+
+    /// Drop parsed fonts and clear all caches, to release memory when text is not needed for a
+    /// while, for example when the application is in the background.
+    ///
+    /// Fonts are parsed again when needed. Fonts still referenced elsewhere, such as by a
+    /// [`crate::SwashCache`], are only released once those references are dropped.
+    pub fn trim_caches(&mut self) {
+        #[cfg(feature = "std")]
+        for id in self.font_cache.keys() {
+            self.db.make_face_data_unshared(*id);
+        }
+        self.font_cache.clear();
+        self.font_codepoint_support_info_cache.clear();
+        self.font_matches_cache.clear();
+        self.shape_plan_cache.clear();
+        #[cfg(feature = "shape-run-cache")]
+        {
+            self.shape_run_cache.clear();
+            self.shared_shape_run_caches.clear();
+        }
+    }
+
+    /// Get the shared shape run cache with this ID, creating it if needed
+    #[cfg(feature = "shape-run-cache")]
+    pub fn shared_shape_run_cache(&mut self, id: u32) -> &mut crate::ShapeRunCache {
+        self.shared_shape_run_caches.entry(id).or_default()
+    }
+
+    /// Remove the shared shape run cache with this ID, releasing its memory.
+    ///
+    /// Buffers still using the ID create a new cache when they shape text again.
+    #[cfg(feature = "shape-run-cache")]
+    pub fn remove_shared_shape_run_cache(&mut self, id: u32) {
+        self.shared_shape_run_caches.remove(&id);
+    }
+
+    /// Trim the default and all shared shape run caches, see [`crate::ShapeRunCache::trim`]
+    #[cfg(feature = "shape-run-cache")]
+    pub fn trim_shape_run_caches(&mut self, keep_ages: u64) {
+        self.shape_run_cache.trim(keep_ages);
+        for cache in self.shared_shape_run_caches.values_mut() {
+            cache.trim(keep_ages);
+        }
+    }
+
+    /// Get the shape run cache selected by the buffer shaping text, if any
+    #[cfg(feature = "shape-run-cache")]
+    pub(crate) fn active_shape_run_cache(&mut self) -> Option<&mut crate::ShapeRunCache> {
+        match self.shape_run_cache_mode {
+            crate::ShapeRunCacheMode::Disabled => None,
+            crate::ShapeRunCacheMode::Default => Some(&mut self.shape_run_cache),
+            crate::ShapeRunCacheMode::Shared(id) => Some(self.shared_shape_run_cache(id)),
+        }
+    }
+
+    /// Add a dictionary used to break lines between words of its script, replacing any
+    /// dictionary for the same script.
+    ///
+    /// Buffers must be shaped again for the dictionary to apply to text they already hold.
+    #[cfg(feature = "line-break-dictionary")]
+    pub fn add_line_break_dictionary(&mut self, dictionary: crate::LineBreakDictionary) {
+        self.line_break_dictionaries
+            .retain(|other| other.script() != dictionary.script());
+        self.line_break_dictionaries.push(dictionary);
+    }
+
+    /// Remove all line break dictionaries.
+    #[cfg(feature = "line-break-dictionary")]
+    pub fn clear_line_break_dictionaries(&mut self) {
+        self.line_break_dictionaries.clear();
+    }
+
+    /// Get the byte offsets between dictionary words in text, excluding its start and end.
+    ///
+    /// With the `icu-segmenter` feature, text in Thai, Lao, Khmer, or Burmese that no added
+    /// dictionary covers is split with the built-in dictionaries of `icu_segmenter`.
+    #[cfg(feature = "line-break-dictionary")]
+    pub(crate) fn line_break_dictionary_breaks(&mut self, text: &str) -> Vec<usize> {
+        #[allow(unused_mut)]
+        let mut breaks = crate::dictionary::dictionary_breaks(&self.line_break_dictionaries, text);
+        #[cfg(feature = "icu-segmenter")]
+        if crate::dictionary::has_segmenter_script(&self.line_break_dictionaries, text) {
+            let segmenter = self
+                .line_segmenter_opt
+                .get_or_insert_with(icu_segmenter::LineSegmenter::new_dictionary);
+            breaks.extend(crate::dictionary::segmenter_breaks(
+                segmenter,
+                &self.line_break_dictionaries,
+                text,
+            ));
+            breaks.sort_unstable();
+        }
+        breaks
+    }
+
+    /// Add hyphenation patterns, replacing any patterns for the same language.
+    ///
+    /// Buffers must be shaped again for the patterns to apply to text they already hold.
+    #[cfg(feature = "hyphenation")]
+    pub fn add_hyphenator(&mut self, hyphenator: crate::Hyphenator) {
+        self.hyphenators
+            .retain(|other| !other.lang().eq_ignore_ascii_case(hyphenator.lang()));
+        self.hyphenators.push(hyphenator);
+    }
+
+    /// Remove all hyphenation patterns.
+    #[cfg(feature = "hyphenation")]
+    pub fn clear_hyphenators(&mut self) {
+        self.hyphenators.clear();
+    }
+
+    /// Get the byte offsets where a word may be broken with a hyphen, using the patterns for
+    /// the language of the buffer being shaped.
+    #[cfg(feature = "hyphenation")]
+    pub(crate) fn hyphenation_breaks(&self, word: &str) -> Vec<usize> {
+        let lang = match &self.hyphenation {
+            Some(lang) => lang,
+            None => return Vec::new(),
+        };
+        // Prefer patterns for the exact language over those for its primary language
+        self.hyphenators
+            .iter()
+            .filter(|hyphenator| hyphenator.matches_lang(lang))
+            .max_by_key(|hyphenator| hyphenator.lang().len())
+            .map(|hyphenator| hyphenator.hyphenate(word))
+            .unwrap_or_default()
+    }
+
+    /// Get what is shown for characters that no font supports.
+    pub fn last_resort(&self) -> LastResort {
+        self.last_resort
+    }
+
+    /// If fallback may look for the system fonts of the platform by name
+    pub(crate) fn system_fallback(&self) -> bool {
+        self.system_fallback
+    }
+
+    /// Set what is shown for characters that no font supports.
+    ///
+    /// Buffers must be shaped again for a change to apply to text they already hold.
+    pub fn set_last_resort(&mut self, last_resort: LastResort) {
+        if self.last_resort != last_resort {
+            self.last_resort = last_resort;
+            #[cfg(feature = "shape-run-cache")]
+            {
+                self.shape_run_cache.clear();
+                self.shared_shape_run_caches.clear();
+            }
+        }
+    }
+
+    /// Get the rules added with [`Self::add_fallback_rule`].
+    pub fn fallback_rules(&self) -> &[crate::FallbackRule] {
+        &self.fallback_rules
+    }
+
+    /// Add a rule to prefer a family for some characters, like CJK ideographs or emoji, when
+    /// they fall back from the family of their text.
+    ///
+    /// Rules are tried in the order they are added, after the family of the text and before the
+    /// fallback families of the platform, for words with any character the rule applies to.
+    /// Families that are not in the database are skipped.
+    ///
+    /// Buffers must be shaped again for a change to apply to text they already hold.
+    pub fn add_fallback_rule(&mut self, rule: crate::FallbackRule) {
+        self.fallback_rules.push(rule);
+        #[cfg(feature = "shape-run-cache")]
+        {
+            self.shape_run_cache.clear();
+            self.shared_shape_run_caches.clear();
+        }
+    }
+
+    /// Remove all fallback rules.
+    pub fn clear_fallback_rules(&mut self) {
+        self.fallback_rules.clear();
+        #[cfg(feature = "shape-run-cache")]
+        {
+            self.shape_run_cache.clear();
+            self.shared_shape_run_caches.clear();
+        }
+    }
+
+    /// Check if fallback is recorded, see [`Self::set_fallback_log`].
+    pub fn fallback_log_enabled(&self) -> bool {
+        self.fallback_log_opt.is_some()
+    }
+
+    /// Set if every character that the font chosen for its text does not have is recorded while
+    /// shaping, with the font chosen by fallback for it. Disabled by default.
+    ///
+    /// Enabling the log starts it empty, and disabling it drops what it holds. The log grows with
+    /// every run that is shaped, so it should be taken with [`Self::take_fallback_log`] from time
+    /// to time. Runs are recorded when they are shaped, not when they are taken from the shape
+    /// run cache, so the cache is cleared when the log is enabled.
+    pub fn set_fallback_log(&mut self, enabled: bool) {
+        if self.fallback_log_opt.is_some() == enabled {
+            return;
+        }
+        self.fallback_log_opt = enabled.then(Vec::new);
+        #[cfg(feature = "shape-run-cache")]
+        if enabled {
+            self.shape_run_cache.clear();
+            self.shared_shape_run_caches.clear();
+        }
+    }
+
+    /// Get the characters that fell back since the log was enabled or last taken, in the order
+    /// they were shaped.
+    pub fn fallback_log(&self) -> &[crate::FallbackEvent] {
+        self.fallback_log_opt.as_deref().unwrap_or_default()
+    }
+
+    /// Take the characters that fell back since the log was enabled or last taken, leaving the
+    /// log empty.
+    pub fn take_fallback_log(&mut self) -> Vec<crate::FallbackEvent> {
+        self.fallback_log_opt
+            .as_mut()
+            .map(core::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn fallback_log_mut(&mut self) -> Option<&mut Vec<crate::FallbackEvent>> {
+        self.fallback_log_opt.as_mut()
+    }
+
+    /// Check if bold is synthesized, see [`Self::set_synthesize_bold`].
+    pub fn synthesize_bold(&self) -> bool {
+        self.synthesize_bold
+    }
+
+    /// Set if bold is synthesized for text with a weight of at least [`crate::Weight::SEMIBOLD`]
+    /// set with a lighter face, because no bold face matches. Enabled by default.
+    ///
+    /// Such glyphs have [`crate::CacheKeyFlags::FAKE_BOLD`] and are emboldened when rasterized,
+    /// without changing their advances.
+    ///
+    /// Buffers must be shaped again for a change to apply to text they already hold.
+    pub fn set_synthesize_bold(&mut self, synthesize_bold: bool) {
+        if self.synthesize_bold != synthesize_bold {
+            self.synthesize_bold = synthesize_bold;
+            #[cfg(feature = "shape-run-cache")]
+            {
+                self.shape_run_cache.clear();
+                self.shared_shape_run_caches.clear();
+            }
+        }
+    }
+
+    /// Get the callback that adjusts glyphs after shaping.
+    pub fn shape_hook(&self) -> Option<&ShapeHook> {
+        self.shape_hook.as_ref()
+    }
+
+    /// Set a callback that adjusts the glyphs of each shaped run before layout, see [`ShapeHook`].
+    ///
+    /// Buffers must be shaped again for a change to apply to text they already hold.
+    pub fn set_shape_hook(&mut self, shape_hook: Option<ShapeHook>) {
+        self.shape_hook = shape_hook;
+        #[cfg(feature = "shape-run-cache")]
+        {
+            self.shape_run_cache.clear();
+            self.shared_shape_run_caches.clear();
+        }
+    }
+
+    /// Get the line breaker set with [`Self::set_line_breaker`].
+    pub fn line_breaker(&self) -> Option<&Arc<dyn LineBreaker>> {
+        self.line_breaker.as_ref()
+    }
+
+    /// Set the line breaker that finds where lines may break, or `None` to use
+    /// [`crate::UnicodeLineBreaker`].
+    ///
+    /// Buffers must be shaped again for a change to apply to text they already hold.
+    pub fn set_line_breaker(&mut self, line_breaker: Option<Arc<dyn LineBreaker>>) {
+        self.line_breaker = line_breaker;
+    }
+
     /// Get a mutable reference to the database.
     pub fn db_mut(&mut self) -> &mut fontdb::Database {
         self.font_matches_cache.clear();
@@ -322,16 +812,16 @@ impl FontSystem {
         String::from("en-US")
     }
 
-    #[cfg(feature = "std")]
-    fn load_fonts(db: &mut fontdb::Database, fonts: impl Iterator<Item = fontdb::Source>) {
+    #[cfg(all(feature = "std", not(feature = "no-system-fonts")))]
+    fn load_system_fonts(db: &mut fontdb::Database) {
         #[cfg(not(target_arch = "wasm32"))]
         let now = std::time::Instant::now();
 
         db.load_system_fonts();
 
-        for source in fonts {
-            db.load_font_source(source);
-        }
+        // fontdb finds no fonts on Android and iOS
+        #[cfg(any(target_os = "android", target_os = "ios"))]
+        crate::font::discovery::load_system_fonts(db);
 
         #[cfg(not(target_arch = "wasm32"))]
         log::debug!(
@@ -341,12 +831,8 @@ impl FontSystem {
         );
     }
 
-    #[cfg(not(feature = "std"))]
-    fn load_fonts(db: &mut fontdb::Database, fonts: impl Iterator<Item = fontdb::Source>) {
-        for source in fonts {
-            db.load_font_source(source);
-        }
-    }
+    #[cfg(any(not(feature = "std"), feature = "no-system-fonts"))]
+    fn load_system_fonts(_db: &mut fontdb::Database) {}
 }
 
 /// A value borrowed together with an [`FontSystem`]
@@ -5,8 +5,12 @@ bitflags::bitflags! {
     #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
     #[repr(transparent)]
     pub struct CacheKeyFlags: u32 {
-        /// Skew by 14 degrees to synthesize italic
+        /// Skew to synthesize italic, by [`CacheKey::oblique_angle_bits`]
         const FAKE_ITALIC = 1;
+        /// Rotate 90 degrees clockwise, for glyphs set sideways in vertical lines
+        const SIDEWAYS = 2;
+        /// Embolden outlines to synthesize bold, see [`crate::FontSystem::set_synthesize_bold`]
+        const FAKE_BOLD = 4;
     }
 }
 
@@ -16,6 +20,8 @@ bitflags::bitflags! {
 pub struct CacheKey {
     /// Font ID
     pub font_id: fontdb::ID,
+    /// Index of the named instance of the font, see [`crate::Font::named_instances`]
+    pub font_instance_opt: Option<u16>,
     /// Glyph ID
     pub glyph_id: u16,
     /// `f32` bits of font size
@@ -26,8 +32,14 @@ pub struct CacheKey {
     pub y_bin: SubpixelBin,
     /// [`CacheKeyFlags`]
     pub flags: CacheKeyFlags,
+    /// `f32` bits of the angle in degrees of [`CacheKeyFlags::FAKE_ITALIC`], see
+    /// [`crate::Attrs::oblique`]
+    pub oblique_angle_bits: u32,
 }
 
+/// Angle in degrees of [`CacheKeyFlags::FAKE_ITALIC`] without an [`crate::Oblique`]
+pub const DEFAULT_OBLIQUE_ANGLE: f32 = 14.0;
+
 impl CacheKey {
     pub fn new(
         font_id: fontdb::ID,
@@ -41,11 +53,13 @@ impl CacheKey {
         (
             Self {
                 font_id,
+                font_instance_opt: None,
                 glyph_id,
                 font_size_bits: font_size.to_bits(),
                 x_bin,
                 y_bin,
                 flags,
+                oblique_angle_bits: DEFAULT_OBLIQUE_ANGLE.to_bits(),
             },
             x,
             y,
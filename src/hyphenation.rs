@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::HashMap;
+
+/// Hyphenation patterns for a language, used to break long words at the end of lines
+///
+/// Patterns use the format of Liang's algorithm as used by TeX, like those in the `hyph-*.pat.txt`
+/// files of the `hyph-utf8` project. Once added with [`crate::FontSystem::add_hyphenator`], words
+/// in buffers with a matching [`crate::Buffer::set_hyphenation`] language may wrap at
+/// hyphenation points, with a hyphen at the end of the line.
+pub struct Hyphenator {
+    lang: String,
+    /// Values between the letters of each pattern, by its letters
+    patterns: HashMap<String, Vec<u8>>,
+    /// Allowed break positions in characters, by word
+    exceptions: HashMap<String, Vec<usize>>,
+    /// Length of the longest pattern, in characters
+    max_len: usize,
+    left_min: usize,
+    right_min: usize,
+}
+
+impl fmt::Debug for Hyphenator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hyphenator")
+            .field("lang", &self.lang)
+            .field("patterns", &self.patterns.len())
+            .field("exceptions", &self.exceptions.len())
+            .finish()
+    }
+}
+
+impl Hyphenator {
+    /// Create a hyphenator for a language tag, like `en-US`, from whitespace separated patterns
+    ///
+    /// Patterns look like `.ach4` or `1ba`, where odd numbers allow a break between letters and
+    /// `.` marks the start or end of a word. Entries containing `-`, like `ta-ble`, are
+    /// exceptions that give the breaks of the whole word. Text after `%` on a line is ignored.
+    pub fn parse(lang: &str, patterns: &str) -> Self {
+        let mut hyphenator = Self {
+            lang: lang.to_string(),
+            patterns: HashMap::default(),
+            exceptions: HashMap::default(),
+            max_len: 0,
+            left_min: 2,
+            right_min: 3,
+        };
+        for line in patterns.lines() {
+            let line = line.split('%').next().unwrap_or_default();
+            for entry in line.split_whitespace() {
+                if entry.contains('-') {
+                    hyphenator.insert_exception(entry);
+                } else {
+                    hyphenator.insert_pattern(entry);
+                }
+            }
+        }
+        hyphenator
+    }
+
+    /// Set the minimum number of characters kept before and after a hyphen, 2 and 3 by default
+    pub fn with_min(mut self, left_min: usize, right_min: usize) -> Self {
+        self.left_min = left_min.max(1);
+        self.right_min = right_min.max(1);
+        self
+    }
+
+    fn insert_pattern(&mut self, pattern: &str) {
+        let mut letters = String::new();
+        let mut values = vec![0];
+        for c in pattern.chars() {
+            match c.to_digit(10) {
+                Some(value) => {
+                    let last = values.len() - 1;
+                    values[last] = value as u8;
+                }
+                None => {
+                    letters.push(c.to_lowercase().next().unwrap_or(c));
+                    values.push(0);
+                }
+            }
+        }
+        if !letters.is_empty() {
+            self.max_len = self.max_len.max(values.len() - 1);
+            self.patterns.insert(letters, values);
+        }
+    }
+
+    fn insert_exception(&mut self, exception: &str) {
+        let mut word = String::new();
+        let mut breaks = Vec::new();
+        let mut len = 0;
+        for c in exception.chars() {
+            if c == '-' {
+                breaks.push(len);
+            } else {
+                word.push(c.to_lowercase().next().unwrap_or(c));
+                len += 1;
+            }
+        }
+        self.exceptions.insert(word, breaks);
+    }
+
+    /// Get the language tag of the patterns
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    /// Get the number of patterns
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Check if there are no patterns
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check if the patterns apply to a language tag, either exactly or as its primary language
+    pub(crate) fn matches_lang(&self, lang: &str) -> bool {
+        let normalize = |c: char| {
+            if c == '_' {
+                '-'
+            } else {
+                c.to_ascii_lowercase()
+            }
+        };
+        let mut tag = lang.chars().map(normalize);
+        let mut own = self.lang.chars().map(normalize);
+        loop {
+            match (own.next(), tag.next()) {
+                (None, None | Some('-')) => return true,
+                (Some(a), Some(b)) if a == b => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// Get the byte offsets in a word where it may be broken with a hyphen
+    ///
+    /// Punctuation before and after the letters of the word is skipped, and words with other
+    /// characters between their letters are not broken.
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let mut breaks = Vec::new();
+        let start = word
+            .char_indices()
+            .find(|(_, c)| c.is_alphabetic())
+            .map_or(word.len(), |(i, _)| i);
+        let end = word
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_alphabetic())
+            .map_or(start, |(i, c)| i + c.len_utf8());
+        if start >= end {
+            return breaks;
+        }
+        let letters = &word[start..end];
+        if !letters.chars().all(char::is_alphabetic) {
+            return breaks;
+        }
+
+        // One lowercase character for each character of the word, so positions line up
+        let lower: Vec<char> = letters
+            .chars()
+            .map(|c| c.to_lowercase().next().unwrap_or(c))
+            .collect();
+        let len = lower.len();
+        if len < self.left_min + self.right_min {
+            return breaks;
+        }
+
+        let positions: Vec<usize> = match self.exceptions.get(&lower.iter().collect::<String>()) {
+            Some(positions) => positions.clone(),
+            None => {
+                let mut padded = Vec::with_capacity(len + 2);
+                padded.push('.');
+                padded.extend_from_slice(&lower);
+                padded.push('.');
+
+                // Values before each character of the padded word
+                let mut values = vec![0u8; padded.len() + 1];
+                let mut key = String::new();
+                for i in 0..padded.len() {
+                    key.clear();
+                    for c in &padded[i..padded.len().min(i + self.max_len)] {
+                        key.push(*c);
+                        if let Some(pattern) = self.patterns.get(&key) {
+                            for (k, value) in pattern.iter().enumerate() {
+                                values[i + k] = values[i + k].max(*value);
+                            }
+                        }
+                    }
+                }
+                // A break before character `i` of the word is before character `i + 1` of the
+                // padded word
+                (1..len).filter(|i| values[i + 1] % 2 == 1).collect()
+            }
+        };
+
+        let offsets: Vec<usize> = letters.char_indices().map(|(i, _)| start + i).collect();
+        breaks.extend(
+            positions
+                .into_iter()
+                .filter(|i| *i >= self.left_min && *i + self.right_min <= len)
+                .filter_map(|i| offsets.get(i).copied()),
+        );
+        breaks
+    }
+}
+
+#[test]
+fn test_hyphenator() {
+    // Patterns from the TeX hyphenation of English that cover "hyphenation"
+    let hyphenator = Hyphenator::parse(
+        "en-us",
+        "% English\nhy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n\nta-ble",
+    );
+    assert_eq!(hyphenator.len(), 9);
+    assert_eq!(hyphenator.hyphenate("hyphenation"), [2, 6]);
+    assert_eq!(hyphenator.hyphenate("(Hyphenation),"), [3, 7]);
+    assert_eq!(hyphenator.hyphenate("table"), [2]);
+    assert!(hyphenator.hyphenate("hy").is_empty());
+    assert!(hyphenator.hyphenate("hyphen-ation").is_empty());
+
+    assert!(hyphenator.matches_lang("en-US"));
+    assert!(hyphenator.matches_lang("EN_us"));
+    assert!(hyphenator.matches_lang("en-US-x-custom"));
+    assert!(!hyphenator.matches_lang("en"));
+    assert!(Hyphenator::parse("en", "").matches_lang("en-GB"));
+}
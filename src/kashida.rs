@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Arabic joining analysis for kashida justification
+
+/// Tatweel, the character used to elongate joined Arabic letters
+pub(crate) const TATWEEL: char = '\u{0640}';
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Joining {
+    /// Joins on both sides
+    Dual,
+    /// Joins only to the preceding letter
+    Right,
+    /// Does not affect joining, like combining marks
+    Transparent,
+    /// Does not join
+    None,
+}
+
+fn joining(c: char) -> Joining {
+    match c {
+        '\u{0622}'..='\u{0625}'
+        | '\u{0627}'
+        | '\u{0629}'
+        | '\u{062F}'..='\u{0632}'
+        | '\u{0648}'
+        | '\u{0671}'..='\u{0673}'
+        | '\u{0675}'..='\u{0677}'
+        | '\u{0688}'..='\u{0699}'
+        | '\u{06C0}'
+        | '\u{06C3}'..='\u{06CB}'
+        | '\u{06CD}'
+        | '\u{06CF}'
+        | '\u{06D2}'..='\u{06D3}'
+        | '\u{06D5}'
+        | '\u{06EE}'..='\u{06EF}'
+        | '\u{0759}'..='\u{075B}'
+        | '\u{076B}'..='\u{076C}'
+        | '\u{0771}'
+        | '\u{0773}'..='\u{0774}'
+        | '\u{0778}'..='\u{0779}' => Joining::Right,
+        '\u{0620}'
+        | '\u{0626}'
+        | '\u{0628}'
+        | '\u{062A}'..='\u{062E}'
+        | '\u{0633}'..='\u{063F}'
+        | '\u{0641}'..='\u{0647}'
+        | '\u{0649}'..='\u{064A}'
+        | '\u{066E}'..='\u{066F}'
+        | '\u{0678}'..='\u{0687}'
+        | '\u{069A}'..='\u{06BF}'
+        | '\u{06C1}'..='\u{06C2}'
+        | '\u{06CC}'
+        | '\u{06CE}'
+        | '\u{06D0}'..='\u{06D1}'
+        | '\u{06FA}'..='\u{06FC}'
+        | '\u{06FF}'
+        | '\u{0750}'..='\u{077F}' => Joining::Dual,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0610}'..='\u{061A}'
+        | '\u{064B}'..='\u{065F}'
+        | '\u{0670}'
+        | '\u{06D6}'..='\u{06DC}'
+        | '\u{06DF}'..='\u{06E4}'
+        | '\u{06E7}'..='\u{06E8}'
+        | '\u{06EA}'..='\u{06ED}' => Joining::Transparent,
+        _ => Joining::None,
+    }
+}
+
+fn is_alef(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0622}' | '\u{0623}' | '\u{0625}' | '\u{0627}' | '\u{0671}'..='\u{0673}' | '\u{0675}'
+    )
+}
+
+/// Byte offsets of letters that are joined to the preceding letter, where a kashida may be
+/// inserted before them
+///
+/// Lam-alef pairs are skipped, as elongating them would break the ligature.
+pub(crate) fn opportunities(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut prev_opt: Option<char> = None;
+    text.char_indices().filter_map(move |(i, c)| {
+        let c_joining = joining(c);
+        if c_joining == Joining::Transparent {
+            return None;
+        }
+        let prev = prev_opt.replace(c)?;
+        if joining(prev) == Joining::Dual
+            && matches!(c_joining, Joining::Dual | Joining::Right)
+            && !(prev == '\u{0644}' && is_alef(c))
+        {
+            Some(i)
+        } else {
+            None
+        }
+    })
+}
+
+#[test]
+fn test_kashida_opportunities() {
+    // Beh, fatha, beh, alef
+    let text = "\u{0628}\u{064E}\u{0628}\u{0627}";
+    assert_eq!(opportunities(text).collect::<Vec<_>>(), [4, 6]);
+
+    // Alef does not join to the following letter
+    let text = "\u{0627}\u{0628}";
+    assert_eq!(opportunities(text).count(), 0);
+
+    // Lam-alef ligature
+    let text = "\u{0644}\u{0627}";
+    assert_eq!(opportunities(text).count(), 0);
+
+    // Letters separated by a space do not join
+    let text = "\u{0628} \u{0628}";
+    assert_eq!(opportunities(text).count(), 0);
+}
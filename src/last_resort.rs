@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(feature = "swash")]
+use crate::{Color, LayoutGlyph, PhysicalGlyph};
+
+/// What to show for characters that no font supports, set with
+/// [`crate::FontSystem::set_last_resort`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LastResort {
+    /// Use the `.notdef` glyph of the first font
+    #[default]
+    Notdef,
+    /// Shape missing characters with this face, such as the Unicode Last Resort font, once all
+    /// other fallback fonts have been tried
+    Font(fontdb::ID),
+    /// Keep the `.notdef` glyph for layout, but draw a box showing the codepoint of the missing
+    /// character in its place, see [`draw_hex_box`]
+    HexBox,
+}
+
+/// What replaces characters that no font supports in a [`crate::Buffer`], set with
+/// [`crate::Buffer::set_missing_glyph`]
+///
+/// This is used after all fallback fonts, including a [`LastResort::Font`], have been tried, and
+/// takes the place of the `.notdef` glyph and of [`LastResort::HexBox`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MissingGlyph {
+    /// Use this character, such as `'\u{FFFD}'` or `'□'`, from the font of the missing character
+    /// or else from the first font matching its attributes that supports it
+    Char(char),
+    /// Use this glyph of a font, such as a custom glyph in a private use area
+    Glyph(fontdb::ID, u16),
+}
+
+/// Digits 0 to F in a 3x5 pixel font, one row of three bits per line from the top
+const HEX_DIGITS: [[u8; 5]; 16] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b010, 0b101, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b110, 0b101, 0b110],
+    [0b011, 0b100, 0b100, 0b100, 0b011],
+    [0b110, 0b101, 0b101, 0b101, 0b110],
+    [0b111, 0b100, 0b110, 0b100, 0b111],
+    [0b111, 0b100, 0b110, 0b100, 0b100],
+];
+
+/// Draw a box showing the hexadecimal codepoint of a character, as rectangles passed to `f`
+/// with their x, y, width, and height
+///
+/// Codepoints in the Basic Multilingual Plane are drawn as two rows of two digits, and others as
+/// two rows of three digits. If the box is too small to fit the digits, only its outline is
+/// drawn.
+pub fn draw_hex_box<F>(c: char, x: i32, y: i32, width: u32, height: u32, mut f: F)
+where
+    F: FnMut(i32, i32, u32, u32),
+{
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let codepoint = c as u32;
+    let cols: u32 = if codepoint > 0xFFFF { 3 } else { 2 };
+
+    // Border, padding, digits with one unit between them, padding, border
+    let scale = (width / (cols * 4 + 3)).min(height / 15);
+    let border = scale.max(1);
+
+    // Outline
+    f(x, y, width, border);
+    f(x, y + (height - border) as i32, width, border);
+    f(x, y, border, height);
+    f(x + (width - border) as i32, y, border, height);
+
+    if scale == 0 {
+        return;
+    }
+
+    let digits_w = (cols * 4 - 1) * scale;
+    let digits_h = 11 * scale;
+    let digits_x = x + ((width - digits_w) / 2) as i32;
+    let digits_y = y + ((height - digits_h) / 2) as i32;
+    for row in 0..2 {
+        for col in 0..cols {
+            let shift = 4 * (2 * cols - 1 - (row * cols + col));
+            let digit = &HEX_DIGITS[((codepoint >> shift) & 0xF) as usize];
+            let digit_x = digits_x + (col * 4 * scale) as i32;
+            let digit_y = digits_y + (row * 6 * scale) as i32;
+            for (bits_y, bits) in digit.iter().enumerate() {
+                for bit_x in 0..3 {
+                    if bits & (0b100 >> bit_x) != 0 {
+                        f(
+                            digit_x + (bit_x * scale) as i32,
+                            digit_y + (bits_y as u32 * scale) as i32,
+                            scale,
+                            scale,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draw a hex box in place of a glyph if it is a `.notdef` glyph, returning true if drawn
+#[cfg(feature = "swash")]
+pub(crate) fn draw_glyph_hex_box<F>(
+    text: &str,
+    glyph: &LayoutGlyph,
+    physical_glyph: &PhysicalGlyph,
+    line_y: f32,
+    color: Color,
+    f: &mut F,
+) -> bool
+where
+    F: FnMut(i32, i32, u32, u32, Color),
+{
+    if glyph.glyph_id != 0 {
+        return false;
+    }
+    let Some(c) = text[glyph.start..glyph.end].chars().next() else {
+        return false;
+    };
+
+    // Roughly the height of capitals, so the box sits on the baseline
+    let height = (glyph.font_size * 0.75).round();
+    draw_hex_box(
+        c,
+        physical_glyph.x,
+        (line_y - height).round() as i32 + physical_glyph.y,
+        glyph.w.round() as u32,
+        height as u32,
+        |x, y, w, h| f(x, y, w, h, color),
+    );
+    true
+}
+
+#[test]
+fn test_draw_hex_box() {
+    // Count the pixels of each digit to check which digits are drawn
+    fn pixels(c: char, width: u32, height: u32) -> u32 {
+        let mut count = 0;
+        draw_hex_box(c, 0, 0, width, height, |_x, _y, w, h| count += w * h);
+        count
+    }
+    let digit_pixels = |digit: usize| -> u32 {
+        HEX_DIGITS[digit]
+            .iter()
+            .map(|bits| (*bits as u32).count_ones())
+            .sum()
+    };
+    let outline = |width: u32, height: u32| 2 * width + 2 * height;
+
+    // U+00E9 is drawn as 00 E9
+    assert_eq!(
+        pixels('\u{E9}', 11, 15),
+        outline(11, 15) + 2 * digit_pixels(0) + digit_pixels(0xE) + digit_pixels(9)
+    );
+
+    // U+1F600 is drawn as 01F 600
+    assert_eq!(
+        pixels('\u{1F600}', 15, 15),
+        outline(15, 15)
+            + 3 * digit_pixels(0)
+            + digit_pixels(1)
+            + digit_pixels(0xF)
+            + digit_pixels(6)
+    );
+
+    // Too small for digits
+    assert_eq!(pixels('\u{E9}', 10, 15), outline(10, 15));
+    assert_eq!(pixels('\u{E9}', 0, 15), 0);
+}
@@ -5,7 +5,10 @@ use core::fmt::Display;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
-use crate::{math, CacheKey, CacheKeyFlags, Color};
+use crate::{
+    math, CacheKey, CacheKeyFlags, Color, Decoration, DecorationLine, DecorationStyle, Placeholder,
+    RubyRun, Script, Shadow,
+};
 
 /// A laid out glyph
 #[derive(Clone, Debug)]
@@ -53,6 +56,151 @@ pub struct LayoutGlyph {
     pub glyph_id: u16,
     /// Unicode BiDi embedding level, character is left-to-right if `level` is divisible by 2
     pub level: unicode_bidi::Level,
+    /// A drop shadow is drawn behind the glyph, see [`Self::shadow_opt`]
+    pub has_shadow: bool,
+    /// Color of the drop shadow
+    pub shadow_color: Color,
+    /// Horizontal offset of the drop shadow
+    pub shadow_x: f32,
+    /// Vertical offset of the drop shadow
+    pub shadow_y: f32,
+    /// Blur radius of the drop shadow
+    pub shadow_blur: f32,
+    /// Underline from `Attrs`, see [`Self::decoration`]
+    pub underline: GlyphDecorationLine,
+    /// Overline from `Attrs`
+    pub overline: GlyphDecorationLine,
+    /// Strikethrough from `Attrs`
+    pub strikethrough: GlyphDecorationLine,
+    /// ISO 15924 tag of the script of the glyph's cluster as a big-endian integer, see
+    /// [`Self::script`]
+    pub script_tag: u32,
+    /// The glyph stands for an inline box from `Attrs`, see [`Self::placeholder_opt`]
+    pub has_placeholder: bool,
+    /// Identifier of the inline box
+    pub placeholder_id: usize,
+    /// Advance of the inline box, in em of the font size
+    pub placeholder_width: f32,
+    /// Height of the inline box above the baseline, in em of the font size
+    pub placeholder_ascent: f32,
+    /// Depth of the inline box below the baseline, in em of the font size
+    pub placeholder_descent: f32,
+    /// Id of the payload from `Attrs`, or 0 if it has none, see [`crate::Attrs::payload`] and
+    /// [`crate::AttrsList::payload`]
+    pub payload_id: usize,
+    /// The glyph uses a named instance of its font, see [`Self::font_instance_opt`]
+    pub has_font_instance: bool,
+    /// Index of the named instance of the font, see [`crate::Attrs::font_instance`]
+    pub font_instance: u16,
+    /// The glyph has a rectangle behind it, see [`Self::background_color_opt`]
+    pub has_background_color: bool,
+    /// Color of the rectangle behind the glyph, see [`crate::LayoutRun::backgrounds`]
+    pub background_color: Color,
+    /// Opaque id of a custom fill, see [`crate::Attrs::fill_id`]
+    pub fill_id: u64,
+    /// Angle in degrees of [`CacheKeyFlags::FAKE_ITALIC`], see [`crate::Attrs::oblique`]
+    pub oblique_angle: f32,
+    /// Capital scaled down to synthesize a small capital, because the font lacks the `smcp`
+    /// feature, see [`crate::Attrs::small_caps`]
+    pub synthetic_small_caps: bool,
+}
+
+/// A decoration line of a [`LayoutGlyph`], see [`LayoutGlyph::decoration`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(C)]
+pub struct GlyphDecorationLine {
+    /// The glyph has this line
+    pub enabled: bool,
+    /// [`DecorationStyle`] of the line, 0 solid, 1 double, 2 dotted, 3 dashed, or 4 wavy
+    pub style: u8,
+    /// The line has its own color, instead of the color of the text
+    pub has_color: bool,
+    /// Color of the line
+    pub color: Color,
+}
+
+impl Default for GlyphDecorationLine {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            style: 0,
+            has_color: false,
+            color: Color(0),
+        }
+    }
+}
+
+impl GlyphDecorationLine {
+    /// Get the line, or None if the glyph does not have it
+    pub fn line_opt(self) -> Option<DecorationLine> {
+        self.enabled.then(|| DecorationLine {
+            style: match self.style {
+                1 => DecorationStyle::Double,
+                2 => DecorationStyle::Dotted,
+                3 => DecorationStyle::Dashed,
+                4 => DecorationStyle::Wavy,
+                _ => DecorationStyle::Solid,
+            },
+            color_opt: self.has_color.then_some(self.color),
+        })
+    }
+}
+
+impl From<Option<DecorationLine>> for GlyphDecorationLine {
+    fn from(line_opt: Option<DecorationLine>) -> Self {
+        let Some(line) = line_opt else {
+            return Self::default();
+        };
+        Self {
+            enabled: true,
+            style: match line.style {
+                DecorationStyle::Solid => 0,
+                DecorationStyle::Double => 1,
+                DecorationStyle::Dotted => 2,
+                DecorationStyle::Dashed => 3,
+                DecorationStyle::Wavy => 4,
+            },
+            has_color: line.color_opt.is_some(),
+            color: line.color_opt.unwrap_or(Color(0)),
+        }
+    }
+}
+
+/// A laid out glyph with the position of its line, see [`crate::GlyphBatch`]
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct BatchGlyph {
+    /// The laid out glyph
+    pub glyph: LayoutGlyph,
+    /// The index of the original text line
+    pub line_i: usize,
+    /// X offset to the center of a vertical line, see [`crate::LayoutRun::line_x`]
+    pub line_x: f32,
+    /// Y offset to baseline of line
+    pub line_y: f32,
+    /// Index of the glyph in reading order, across all glyphs of the visible layout runs
+    pub logical_i: usize,
+}
+
+/// A laid out glyph with the position of its line, see [`crate::layout_text`]
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct PositionedGlyph {
+    /// The laid out glyph, with `start` and `end` as byte offsets into the whole text
+    pub glyph: LayoutGlyph,
+    /// The index of the paragraph in the text
+    pub line_i: usize,
+    /// Y offset to baseline of line
+    pub line_y: f32,
+}
+
+impl PositionedGlyph {
+    /// Position of the glyph in pixels, as drawn by [`crate::Buffer::draw`]
+    pub fn physical(&self) -> PhysicalGlyph {
+        let mut physical_glyph = self.glyph.physical((0., 0.), 1.0);
+        physical_glyph.y += self.line_y as i32;
+        physical_glyph
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -70,7 +218,7 @@ impl LayoutGlyph {
         let x_offset = self.font_size * self.x_offset;
         let y_offset = self.font_size * self.y_offset;
 
-        let (cache_key, x, y) = CacheKey::new(
+        let (mut cache_key, x, y) = CacheKey::new(
             self.font_id,
             self.glyph_id,
             self.font_size * scale,
@@ -80,9 +228,65 @@ impl LayoutGlyph {
             ),
             self.cache_key_flags,
         );
+        cache_key.font_instance_opt = self.font_instance_opt();
+        cache_key.oblique_angle_bits = self.oblique_angle.to_bits();
 
         PhysicalGlyph { cache_key, x, y }
     }
+
+    /// Get the index of the named instance of the font, see [`crate::Attrs::font_instance`]
+    pub fn font_instance_opt(&self) -> Option<u16> {
+        self.has_font_instance.then_some(self.font_instance)
+    }
+
+    /// Get the color of the rectangle behind the glyph, see [`crate::LayoutRun::backgrounds`]
+    pub fn background_color_opt(&self) -> Option<Color> {
+        self.has_background_color.then_some(self.background_color)
+    }
+
+    /// Get the drop shadow drawn behind the glyph, see
+    /// [`crate::SwashCache::with_shadow_pixels`]
+    pub fn shadow_opt(&self) -> Option<Shadow> {
+        self.has_shadow.then(|| {
+            Shadow::new(
+                self.shadow_color,
+                self.shadow_x,
+                self.shadow_y,
+                self.shadow_blur,
+            )
+        })
+    }
+
+    /// Get the decoration lines from `Attrs`, see [`crate::LayoutRun::decorations`]
+    pub fn decoration(&self) -> Decoration {
+        Decoration {
+            underline: self.underline.line_opt(),
+            overline: self.overline.line_opt(),
+            strikethrough: self.strikethrough.line_opt(),
+        }
+    }
+
+    /// Get the script of the glyph's cluster, common characters like punctuation take the
+    /// script of their word
+    pub fn script(&self) -> Script {
+        core::str::from_utf8(&self.script_tag.to_be_bytes())
+            .ok()
+            .and_then(Script::from_short_name)
+            .unwrap_or(Script::Unknown)
+    }
+
+    /// Get the inline box from `Attrs` to be drawn by the caller instead of the glyph, see
+    /// [`crate::LayoutRun::placeholders`]
+    pub fn placeholder_opt(&self) -> Option<Placeholder> {
+        self.has_placeholder.then(|| {
+            Placeholder::new(
+                self.placeholder_id,
+                self.placeholder_width,
+                self.placeholder_ascent,
+                self.placeholder_descent,
+            )
+        })
+    }
 }
 
 /// A line of laid out glyphs
@@ -94,8 +298,272 @@ pub struct LayoutLine {
     pub max_ascent: f32,
     /// Maximum descent of the glyphs in line
     pub max_descent: f32,
+    /// Maximum ascent of the glyphs in line, from the `usWinAscent` of their fonts
+    pub max_win_ascent: f32,
+    /// Maximum descent of the glyphs in line, from the `usWinDescent` of their fonts
+    pub max_win_descent: f32,
+    /// Ascent of the primary font of the paragraph, the first font matched for its default
+    /// attributes, or 0 if it is not known
+    pub primary_ascent: f32,
+    /// Descent of the primary font of the paragraph
+    pub primary_descent: f32,
+    /// Ascent of the primary font of the paragraph, from its `usWinAscent`
+    pub primary_win_ascent: f32,
+    /// Descent of the primary font of the paragraph, from its `usWinDescent`
+    pub primary_win_descent: f32,
     /// Glyphs in line
     pub glyphs: Vec<LayoutGlyph>,
+    /// Ruby text of the line, see [`crate::BufferLine::set_ruby`]
+    pub ruby: Vec<RubyRun>,
+    /// Height of the tallest ruby text of the line, added above it
+    pub ruby_height: f32,
+    /// Maximum cap height of the fonts of the glyphs in line, see [`crate::Font::cap_height_em`]
+    pub max_cap_height: f32,
+    /// Height added to the line, half above and half below it, for fonts that need more room
+    /// than the distance between lines, see [`crate::Buffer::set_line_height_mode`]
+    pub extra_height: f32,
+    /// Glyphs of the drop cap of the paragraph on its first line, see
+    /// [`crate::ParagraphAttrs::drop_cap`]
+    pub drop_cap: Vec<LayoutGlyph>,
+    /// Glyphs of the list marker of the paragraph on its first line, see
+    /// [`crate::BufferLine::set_marker`]
+    pub marker: Vec<LayoutGlyph>,
+}
+
+impl LayoutLine {
+    /// Turn a line laid out from left to right into a line from top to bottom, for
+    /// [`WritingMode::VerticalRl`] and [`WritingMode::VerticalLr`]
+    ///
+    /// The x position of each glyph moves to y, and glyphs are centered horizontally on x = 0.
+    /// Glyphs for which `sideways` returns true are rotated 90 degrees clockwise instead, with
+    /// the middle of the line's ascent and descent on x = 0. Runs of glyphs for which
+    /// `combine_upright` returns true stay horizontal, centered on x = 0 in one em along the
+    /// line, and are scaled down if they are wider.
+    pub(crate) fn make_vertical(
+        &mut self,
+        mut sideways: impl FnMut(&LayoutGlyph) -> bool,
+        mut combine_upright: impl FnMut(&LayoutGlyph) -> bool,
+    ) {
+        // Distance that glyphs after combined runs move back along the line, as the runs take
+        // one em instead of their width
+        let mut shift = 0.0;
+        let mut i = 0;
+        while i < self.glyphs.len() {
+            let combined = self.glyphs[i..]
+                .iter()
+                .take_while(|glyph| combine_upright(glyph))
+                .count();
+            if combined > 0 {
+                let glyphs = &mut self.glyphs[i..i + combined];
+                let start = glyphs.iter().map(|glyph| glyph.x).fold(f32::MAX, f32::min);
+                let end = glyphs
+                    .iter()
+                    .map(|glyph| glyph.x + glyph.w)
+                    .fold(f32::MIN, f32::max);
+                let em = glyphs[0].font_size;
+                let scale = if end - start > em {
+                    em / (end - start)
+                } else {
+                    1.0
+                };
+                for glyph in glyphs.iter_mut() {
+                    glyph.x = (glyph.x - (start + end) / 2.0) * scale;
+                    glyph.y += start - shift;
+                    glyph.font_size *= scale;
+                    glyph.w = em;
+                }
+                shift += end - start - em;
+                i += combined;
+                continue;
+            }
+
+            let glyph = &mut self.glyphs[i];
+            i += 1;
+            glyph.x -= shift;
+            if sideways(glyph) {
+                // The glyph is drawn from the top of its advance, which is not offset by the
+                // ascent of the line like the baseline of upright glyphs
+                let (x_offset, y_offset) = (glyph.x_offset, glyph.y_offset);
+                glyph.x_offset = y_offset;
+                glyph.y_offset = self.max_ascent / glyph.font_size - x_offset;
+                glyph.y += glyph.x;
+                glyph.x = (self.max_descent - self.max_ascent) / 2.0;
+                glyph.cache_key_flags |= CacheKeyFlags::SIDEWAYS;
+            } else {
+                glyph.y += glyph.x;
+                glyph.x = -glyph.w / 2.0;
+            }
+        }
+        self.w -= shift;
+    }
+
+    /// Get the maximum ascent and descent of the glyphs in line, with the given convention
+    pub fn ascent_descent(&self, vertical_metrics: VerticalMetrics) -> (f32, f32) {
+        match vertical_metrics {
+            VerticalMetrics::Typographic => (self.max_ascent, self.max_descent),
+            VerticalMetrics::Gdi => (self.max_win_ascent, self.max_win_descent),
+        }
+    }
+
+    /// Get the ascent and descent that the baseline of the line is placed with, with the given
+    /// convention and [`BaselineAlign`]
+    ///
+    /// Lines use their maximum ascent and descent if the primary font is not known.
+    pub fn baseline_ascent_descent(
+        &self,
+        vertical_metrics: VerticalMetrics,
+        baseline_align: BaselineAlign,
+    ) -> (f32, f32) {
+        let primary = match vertical_metrics {
+            VerticalMetrics::Typographic => (self.primary_ascent, self.primary_descent),
+            VerticalMetrics::Gdi => (self.primary_win_ascent, self.primary_win_descent),
+        };
+        match baseline_align {
+            BaselineAlign::Primary if primary.0 + primary.1 > 0.0 => primary,
+            _ => self.ascent_descent(vertical_metrics),
+        }
+    }
+}
+
+/// Options for laying out a [`crate::ShapeLine`], see [`crate::ShapeLine::layout_with_options`]
+///
+/// Lengths are in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LayoutOptions {
+    /// Indent of the first line from the start of the paragraph direction, see
+    /// [`crate::ParagraphAttrs::indent`]
+    pub indent: f32,
+    /// Indent of the lines after the first from the start of the paragraph direction, see
+    /// [`crate::ParagraphAttrs::hanging_indent`]
+    pub hanging_indent: f32,
+    /// Width that glyphs of monospace fonts are resized to, if any, see
+    /// [`crate::Buffer::set_monospace_width`]
+    pub match_mono_width: Option<f32>,
+    /// Give digits the same advance, see [`crate::Buffer::set_tabular_digits`]
+    pub tabular_digits: bool,
+    /// Hang punctuation at the end of lines past their width, see
+    /// [`crate::Buffer::set_hanging_punctuation`]
+    pub hanging_punctuation: bool,
+}
+
+impl LayoutOptions {
+    /// Create new layout options with default values
+    pub const fn new() -> Self {
+        Self {
+            indent: 0.0,
+            hanging_indent: 0.0,
+            match_mono_width: None,
+            tabular_digits: false,
+            hanging_punctuation: false,
+        }
+    }
+
+    /// Set first line indent
+    pub fn indent(mut self, indent: f32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Set the indent of the lines after the first
+    pub fn hanging_indent(mut self, hanging_indent: f32) -> Self {
+        self.hanging_indent = hanging_indent;
+        self
+    }
+
+    /// Set the width that glyphs of monospace fonts are resized to
+    pub fn match_mono_width(mut self, match_mono_width: f32) -> Self {
+        self.match_mono_width = Some(match_mono_width);
+        self
+    }
+
+    /// Set whether digits have the same advance
+    pub fn tabular_digits(mut self, tabular_digits: bool) -> Self {
+        self.tabular_digits = tabular_digits;
+        self
+    }
+
+    /// Set whether punctuation at the end of lines hangs past their width
+    pub fn hanging_punctuation(mut self, hanging_punctuation: bool) -> Self {
+        self.hanging_punctuation = hanging_punctuation;
+        self
+    }
+}
+
+/// Space trimmed from the top and the bottom of the text, see [`crate::Buffer::set_leading_trim`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LeadingTrim {
+    /// Space trimmed above the cap height of the first line
+    pub top: f32,
+    /// Space trimmed below the baseline of the last line
+    pub bottom: f32,
+}
+
+/// How the height of each line is resolved, see [`crate::Buffer::set_line_height_mode`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineHeight {
+    /// The line height of the [`crate::Metrics`] of the buffer
+    #[default]
+    Metrics,
+    /// A multiple of the font size
+    Relative(f32),
+    /// Exactly this many pixels
+    Exact(f32),
+    /// The ascent, descent, and line gap of the tallest font in each line, and at least the
+    /// font size
+    Normal,
+    /// At least this many pixels, or the ascent, descent, and line gap of the tallest font in
+    /// each line if it is taller
+    AtLeast(f32),
+}
+
+impl LineHeight {
+    /// Get the distance between lines, before any height added for the fonts of a line
+    pub fn resolve(self, metrics: crate::Metrics) -> f32 {
+        match self {
+            Self::Metrics => metrics.line_height,
+            Self::Relative(factor) => metrics.font_size * factor,
+            Self::Exact(line_height) | Self::AtLeast(line_height) => line_height,
+            Self::Normal => metrics.font_size,
+        }
+    }
+
+    /// Get the height added to a line with fonts that need `font_height`, when lines are
+    /// `line_height` apart
+    pub(crate) fn extra_height(self, line_height: f32, font_height: f32) -> f32 {
+        match self {
+            Self::Normal | Self::AtLeast(_) => (font_height - line_height).max(0.0),
+            Self::Metrics | Self::Relative(_) | Self::Exact(_) => 0.0,
+        }
+    }
+}
+
+/// How the baseline of a line is placed when its glyphs come from fonts with different ascents
+/// and descents, see [`crate::Buffer::set_baseline_align`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BaselineAlign {
+    /// Center the tallest ascent and descent of the fonts of the line, and align their
+    /// alphabetic baselines
+    #[default]
+    LineMax,
+    /// Center the ascent and descent of the primary font of the paragraph, so that lines keep
+    /// the same baseline whatever fallback fonts they use
+    Primary,
+    /// Center the tallest ascent and descent of the fonts of the line, and align the bottoms of
+    /// their em boxes, taken as their descents, like the ideographic baseline of Chinese and
+    /// Japanese text
+    Ideographic,
+}
+
+/// Convention for the ascent and descent of fonts, which places the baseline in each line
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum VerticalMetrics {
+    /// Use the ascender and descender of the `hhea` table, or those of the `OS/2` table if the
+    /// font sets `USE_TYPO_METRICS`
+    #[default]
+    Typographic,
+    /// Use `usWinAscent` and `usWinDescent` of the `OS/2` table, like GDI on Windows, to line up
+    /// with native Windows controls
+    Gdi,
 }
 
 /// Wrapping mode
@@ -109,6 +577,15 @@ pub enum Wrap {
     Word,
     /// Wraps at the word level, or fallback to glyph level if a word can't fit on a line by itself
     WordOrGlyph,
+    /// Wraps like [`Wrap::WordOrGlyph`], then narrows the lines so that they are about as wide
+    /// as each other without adding a line, for short paragraphs like headlines and tooltips
+    ///
+    /// Paragraphs of more than a few lines are wrapped like [`Wrap::WordOrGlyph`].
+    Balanced,
+    /// Wraps at the word level, choosing the breaks of the whole paragraph that make its lines
+    /// the most even, see [`crate::OptimalWrap`]
+    #[cfg(feature = "optimal-wrap")]
+    Optimal(crate::OptimalWrap),
 }
 
 impl Display for Wrap {
@@ -118,6 +595,38 @@ impl Display for Wrap {
             Self::Word => write!(f, "Word Wrap"),
             Self::WordOrGlyph => write!(f, "Word Wrap or Character"),
             Self::Glyph => write!(f, "Character"),
+            Self::Balanced => write!(f, "Balanced"),
+            #[cfg(feature = "optimal-wrap")]
+            Self::Optimal(_) => write!(f, "Optimal"),
+        }
+    }
+}
+
+/// Direction of lines, and of the text in them
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum WritingMode {
+    /// Horizontal lines from top to bottom
+    #[default]
+    HorizontalTb,
+    /// Vertical lines from right to left, as in Japanese and traditional Chinese
+    VerticalRl,
+    /// Vertical lines from left to right, as in Mongolian
+    VerticalLr,
+}
+
+impl WritingMode {
+    /// Check if lines are vertical
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Self::VerticalRl | Self::VerticalLr)
+    }
+}
+
+impl Display for WritingMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::HorizontalTb => write!(f, "Horizontal"),
+            Self::VerticalRl => write!(f, "Vertical Right to Left"),
+            Self::VerticalLr => write!(f, "Vertical Left to Right"),
         }
     }
 }
@@ -128,6 +637,8 @@ pub enum Align {
     Left,
     Right,
     Center,
+    /// Widen spaces, or elongate Arabic words with kashida, so that each line fills the width,
+    /// except the last line of a paragraph. Glyph advances include the added width
     Justified,
     End,
 }
@@ -143,3 +654,39 @@ impl Display for Align {
         }
     }
 }
+
+/// Position of the lines of text within the height of a buffer, see
+/// [`crate::Buffer::set_vertical_align`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl Display for VerticalAlign {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Top => write!(f, "Top"),
+            Self::Middle => write!(f, "Middle"),
+            Self::Bottom => write!(f, "Bottom"),
+        }
+    }
+}
+
+/// Base direction of a paragraph
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::LeftToRight => write!(f, "Left to Right"),
+            Self::RightToLeft => write!(f, "Right to Left"),
+        }
+    }
+}
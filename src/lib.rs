@@ -98,9 +98,19 @@ compile_error!("Either the `std` or `no_std` feature must be enabled");
 
 use fontdb::ID;
 
+pub use unicode_script::Script;
+
+pub use self::ansi::*;
+mod ansi;
+
 pub use self::attrs::*;
 mod attrs;
 
+pub use self::background::*;
+mod background;
+
+mod bbcode;
+
 pub use self::bidi_para::*;
 mod bidi_para;
 
@@ -110,38 +120,125 @@ mod buffer;
 pub use self::buffer_line::*;
 mod buffer_line;
 
+pub use self::cache_stats::*;
+mod cache_stats;
+
 pub use self::glyph_cache::*;
 mod glyph_cache;
 
 pub use self::cursor::*;
 mod cursor;
 
+pub use self::decoration::*;
+mod decoration;
+
+#[cfg(feature = "line-break-dictionary")]
+pub use self::dictionary::*;
+#[cfg(feature = "line-break-dictionary")]
+mod dictionary;
+
 pub use self::edit::*;
 mod edit;
 
+pub use self::exclusion::*;
+mod exclusion;
+
 pub use self::font::*;
 mod font;
 
+#[cfg(feature = "hyphenation")]
+pub use self::hyphenation::*;
+#[cfg(feature = "hyphenation")]
+mod hyphenation;
+
+mod kashida;
+
+pub use self::last_resort::*;
+mod last_resort;
+
 pub use self::layout::*;
 mod layout;
 
+pub use self::line_break::*;
+mod line_break;
+
 pub use self::line_ending::*;
 mod line_ending;
 
+pub use self::link::*;
+mod link;
+
+pub use self::list_marker::*;
+mod list_marker;
+
+#[cfg(feature = "markdown")]
+pub use self::markdown::*;
+#[cfg(feature = "markdown")]
+mod markdown;
+
+pub use self::markup::*;
+mod markup;
+
+#[cfg(feature = "nfc-normalization")]
+pub use self::normalization::*;
+#[cfg(feature = "nfc-normalization")]
+mod normalization;
+
+#[cfg(feature = "optimal-wrap")]
+pub use self::optimal_wrap::*;
+#[cfg(feature = "optimal-wrap")]
+mod optimal_wrap;
+
+pub use self::page::*;
+mod page;
+
+pub use self::payload::*;
+mod payload;
+
+pub use self::paragraph_attrs::*;
+mod paragraph_attrs;
+
+pub use self::placeholder::*;
+mod placeholder;
+
+pub use self::pseudo_locale::*;
+mod pseudo_locale;
+
+pub use self::rich_text::*;
+mod rich_text;
+
+pub use self::ruby::*;
+mod ruby;
+
 pub use self::shape::*;
 mod shape;
 
+pub use self::shape_hook::*;
+mod shape_hook;
+
 use self::shape_plan_cache::*;
 mod shape_plan_cache;
 
 pub use self::shape_run_cache::*;
 mod shape_run_cache;
 
+pub use self::shape_text::*;
+mod shape_text;
+
 #[cfg(feature = "swash")]
 pub use self::swash::*;
 #[cfg(feature = "swash")]
 mod swash;
 
+pub use self::truncate::*;
+mod truncate;
+
+pub use self::utf16::*;
+mod utf16;
+
+pub use self::vertical_orientation::*;
+mod vertical_orientation;
+
 mod math;
 
 type BuildHasher = core::hash::BuildHasherDefault<rustc_hash::FxHasher>;
@@ -222,6 +319,75 @@ impl ByteBuffer {
     }
 }
 
+/// Memory for transient output arrays of export functions, created once by the host with
+/// `arena_new`
+///
+/// Arrays are copied into chunks that are reused after `arena_reset`, so pointers returned by
+/// `*_arena` functions stay valid until the next reset, and the host never frees them one by one.
+#[derive(Debug, Default)]
+pub struct FfiArena {
+    /// Chunks of 8 byte aligned memory, which never move once allocated
+    chunks: Vec<Box<[u64]>>,
+    /// Index of the chunk being filled
+    chunk_i: usize,
+    /// Bytes used in the chunk being filled
+    used: usize,
+}
+
+impl FfiArena {
+    const CHUNK_WORDS: usize = 8192;
+
+    /// Copy items into the arena, returning a pointer that is valid until [`Self::reset`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if items need to be dropped, as they never are, or need an alignment above 8 bytes.
+    pub fn alloc_slice<T: Clone>(&mut self, items: &[T]) -> *const T {
+        assert!(!std::mem::needs_drop::<T>());
+        assert!(std::mem::align_of::<T>() <= std::mem::align_of::<u64>());
+        let bytes = std::mem::size_of_val(items);
+        if bytes == 0 {
+            return std::ptr::NonNull::dangling().as_ptr();
+        }
+
+        let align = std::mem::align_of::<T>();
+        let mut start = (self.used + align - 1) & !(align - 1);
+        let fits = |chunk: &[u64], start: usize| start + bytes <= chunk.len() * 8;
+        if !self.chunks.get(self.chunk_i).map_or(false, |chunk| fits(chunk, start)) {
+            // Move to the next chunk, inserting a new one if it is missing or too small
+            if !self.chunks.is_empty() {
+                self.chunk_i += 1;
+            }
+            start = 0;
+            if !self.chunks.get(self.chunk_i).map_or(false, |chunk| fits(chunk, 0)) {
+                let words = Self::CHUNK_WORDS.max((bytes + 7) / 8);
+                let chunk_i = self.chunk_i.min(self.chunks.len());
+                self.chunk_i = chunk_i;
+                self.chunks.insert(chunk_i, vec![0; words].into_boxed_slice());
+            }
+        }
+
+        let chunk = &mut self.chunks[self.chunk_i];
+        let ptr = unsafe { (chunk.as_mut_ptr() as *mut u8).add(start) as *mut T };
+        for (i, item) in items.iter().enumerate() {
+            unsafe { ptr.add(i).write(item.clone()) };
+        }
+        self.used = start + bytes;
+        ptr
+    }
+
+    /// Invalidate all pointers into the arena, keeping its memory for reuse
+    pub fn reset(&mut self) {
+        self.chunk_i = 0;
+        self.used = 0;
+    }
+
+    /// Bytes of memory held by the arena
+    pub fn capacity(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len() * 8).sum()
+    }
+}
+
 // FontSystem ---------------------------------------------------------
 #[no_mangle]
 pub extern "C" fn fontsystem_new() -> *mut FontSystem {
@@ -230,6 +396,16 @@ pub extern "C" fn fontsystem_new() -> *mut FontSystem {
     Box::into_raw(ctx)
 }
 
+/// Create a font system with only the fonts registered later, and those of the `embedded-fonts`
+/// feature, without enumerating system fonts
+#[no_mangle]
+pub extern "C" fn fontsystem_new_without_system_fonts() -> *mut FontSystem {
+    let font_system = FontSystem::new_without_system_fonts(core::iter::empty());
+    let ctx: Box<_> = Box::new(font_system);
+    Box::into_raw(ctx)
+}
+
+#[cfg(not(feature = "no-system-fonts"))]
 #[no_mangle]
 pub extern "C" fn fontsystem_load_system_fonts(ctx: *mut FontSystem) {
     let font_system: &mut FontSystem = unsafe { &mut *ctx };
@@ -260,6 +436,362 @@ pub extern "C" fn fontsystem_get_font(ctx: *mut FontSystem, font_id: ID) -> *con
     return font;
 }
 
+/// Get the default line height of a font from `fontsystem_get_font` in em, multiply by the font
+/// size to get pixels
+#[no_mangle]
+pub extern "C" fn font_line_height_em(font: *const Font, vertical_metrics: FfiVerticalMetrics) -> f32 {
+    let font = unsafe { &*font };
+    font.line_height_em(vertical_metrics.into())
+}
+
+#[no_mangle]
+pub extern "C" fn fontsystem_trim_caches(ctx: *mut FontSystem) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.trim_caches();
+}
+
+/// Add a line break dictionary from UTF-16 text with one word per line, returns false if the
+/// text has no words
+#[cfg(feature = "line-break-dictionary")]
+#[no_mangle]
+pub extern "C" fn fontsystem_add_line_break_dictionary(
+    ctx: *mut FontSystem,
+    words: *const u16,
+    words_len: usize,
+) -> bool {
+    let font_system = unsafe { &mut *ctx };
+    let words = unsafe { std::slice::from_raw_parts(words, words_len) };
+    let dictionary = LineBreakDictionary::parse(&String::from_utf16_lossy(words));
+    if dictionary.is_empty() {
+        return false;
+    }
+    font_system.add_line_break_dictionary(dictionary);
+    true
+}
+
+#[cfg(feature = "line-break-dictionary")]
+#[no_mangle]
+pub extern "C" fn fontsystem_clear_line_break_dictionaries(ctx: *mut FontSystem) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.clear_line_break_dictionaries();
+}
+
+/// Add hyphenation patterns for a language tag, both UTF-16, returns false if there are no
+/// patterns
+#[cfg(feature = "hyphenation")]
+#[no_mangle]
+pub extern "C" fn fontsystem_add_hyphenator(
+    ctx: *mut FontSystem,
+    lang: *const u16,
+    lang_len: usize,
+    patterns: *const u16,
+    patterns_len: usize,
+) -> bool {
+    let font_system = unsafe { &mut *ctx };
+    let lang = unsafe { std::slice::from_raw_parts(lang, lang_len) };
+    let patterns = unsafe { std::slice::from_raw_parts(patterns, patterns_len) };
+    let hyphenator = Hyphenator::parse(
+        &String::from_utf16_lossy(lang),
+        &String::from_utf16_lossy(patterns),
+    );
+    if hyphenator.is_empty() {
+        return false;
+    }
+    font_system.add_hyphenator(hyphenator);
+    true
+}
+
+#[cfg(feature = "hyphenation")]
+#[no_mangle]
+pub extern "C" fn fontsystem_clear_hyphenators(ctx: *mut FontSystem) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.clear_hyphenators();
+}
+
+/// Trim the default and all shared shape run caches, keeping runs used within the last
+/// `keep_ages` trims, does nothing without the `shape-run-cache` feature
+#[no_mangle]
+pub extern "C" fn fontsystem_trim_shape_run_caches(ctx: *mut FontSystem, keep_ages: u64) {
+    let font_system = unsafe { &mut *ctx };
+    #[cfg(feature = "shape-run-cache")]
+    font_system.trim_shape_run_caches(keep_ages);
+    #[cfg(not(feature = "shape-run-cache"))]
+    let _ = (font_system, keep_ages);
+}
+
+/// Remove the shared shape run cache with the given ID, does nothing without the
+/// `shape-run-cache` feature
+#[no_mangle]
+pub extern "C" fn fontsystem_remove_shared_shape_run_cache(ctx: *mut FontSystem, id: u32) {
+    let font_system = unsafe { &mut *ctx };
+    #[cfg(feature = "shape-run-cache")]
+    font_system.remove_shared_shape_run_cache(id);
+    #[cfg(not(feature = "shape-run-cache"))]
+    let _ = (font_system, id);
+}
+
+/// Register a font and use its first face for characters no other font supports, returns false
+/// if the data has no faces
+#[no_mangle]
+pub extern "C" fn fontsystem_register_last_resort_font(ctx: *mut FontSystem, font_data: *const u8, font_data_len: usize) -> bool {
+    let font_system = unsafe { &mut *ctx };
+
+    let font_data = unsafe { std::slice::from_raw_parts(font_data, font_data_len) };
+    let font_data = Vec::from(font_data);
+
+    let ids = font_system.db_mut().load_font_source(fontdb::Source::Binary(alloc::sync::Arc::new(font_data)));
+    match ids.first() {
+        Some(id) => {
+            font_system.set_last_resort(LastResort::Font(*id));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Draw boxes showing the codepoint of characters no font supports, instead of `.notdef` glyphs
+#[no_mangle]
+pub extern "C" fn fontsystem_set_last_resort_hex_box(ctx: *mut FontSystem, hex_box: bool) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_last_resort(if hex_box {
+        LastResort::HexBox
+    } else {
+        LastResort::Notdef
+    });
+}
+
+/// Synthesize bold for bold text set with faces that are not bold, enabled by default
+#[no_mangle]
+pub extern "C" fn fontsystem_set_synthesize_bold(ctx: *mut FontSystem, synthesize_bold: bool) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_synthesize_bold(synthesize_bold);
+}
+
+/// Prefer a UTF-16 family for fallback of the characters from `first` to `last`, returns false
+/// if they are not valid characters
+#[no_mangle]
+pub extern "C" fn fontsystem_add_fallback_rule_range(
+    ctx: *mut FontSystem,
+    first: u32,
+    last: u32,
+    family: *const u16,
+    family_len: usize,
+) -> bool {
+    let font_system = unsafe { &mut *ctx };
+    let family = unsafe { std::slice::from_raw_parts(family, family_len) };
+    match (char::from_u32(first), char::from_u32(last)) {
+        (Some(first), Some(last)) => {
+            font_system.add_fallback_rule(FallbackRule::new(
+                FallbackTarget::Range(first..=last),
+                String::from_utf16_lossy(family),
+            ));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Prefer a UTF-16 family for fallback of the characters of a script, given by its UTF-16 ISO
+/// 15924 code like `Hani`, returns false if the script is not known
+#[no_mangle]
+pub extern "C" fn fontsystem_add_fallback_rule_script(
+    ctx: *mut FontSystem,
+    script: *const u16,
+    script_len: usize,
+    family: *const u16,
+    family_len: usize,
+) -> bool {
+    let font_system = unsafe { &mut *ctx };
+    let script = unsafe { std::slice::from_raw_parts(script, script_len) };
+    let family = unsafe { std::slice::from_raw_parts(family, family_len) };
+    match Script::from_short_name(&String::from_utf16_lossy(script)) {
+        Some(script) => {
+            font_system.add_fallback_rule(FallbackRule::new(
+                FallbackTarget::Script(script),
+                String::from_utf16_lossy(family),
+            ));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Prefer a UTF-16 family for fallback of emoji
+#[no_mangle]
+pub extern "C" fn fontsystem_add_fallback_rule_emoji(
+    ctx: *mut FontSystem,
+    family: *const u16,
+    family_len: usize,
+) {
+    let font_system = unsafe { &mut *ctx };
+    let family = unsafe { std::slice::from_raw_parts(family, family_len) };
+    font_system.add_fallback_rule(FallbackRule::new(
+        FallbackTarget::Emoji,
+        String::from_utf16_lossy(family),
+    ));
+}
+
+#[no_mangle]
+pub extern "C" fn fontsystem_clear_fallback_rules(ctx: *mut FontSystem) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.clear_fallback_rules();
+}
+
+/// Record characters that the font of their text does not have while shaping, disabled by
+/// default
+#[no_mangle]
+pub extern "C" fn fontsystem_set_fallback_log(ctx: *mut FontSystem, enabled: bool) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_fallback_log(enabled);
+}
+
+/// A character that fell back from the font of its text, see `fontsystem_take_fallback_log`
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiFallbackEvent {
+    pub codepoint: u32,
+    /// The font chosen for the text, which does not have the character
+    pub font_id: ID,
+    /// The font chosen by fallback, only valid if `has_fallback` is true
+    pub fallback_id: ID,
+    pub has_fallback: bool,
+}
+
+#[no_mangle]
+pub extern "C" fn fontsystem_fallback_log_len(ctx: *mut FontSystem) -> usize {
+    let font_system = unsafe { &mut *ctx };
+    font_system.fallback_log().len()
+}
+
+/// Take up to `capacity` of the oldest recorded characters into `out`, returns how many were
+/// taken
+#[no_mangle]
+pub extern "C" fn fontsystem_take_fallback_log(
+    ctx: *mut FontSystem,
+    out: *mut FfiFallbackEvent,
+    capacity: usize,
+) -> usize {
+    let font_system = unsafe { &mut *ctx };
+    let Some(fallback_log) = font_system.fallback_log_mut() else {
+        return 0;
+    };
+    let len = fallback_log.len().min(capacity);
+    let out = unsafe { std::slice::from_raw_parts_mut(out, len) };
+    for (out, event) in out.iter_mut().zip(fallback_log.drain(..len)) {
+        *out = FfiFallbackEvent {
+            codepoint: event.codepoint as u32,
+            font_id: event.font_id,
+            fallback_id: event.fallback_id_opt.unwrap_or(event.font_id),
+            has_fallback: event.fallback_id_opt.is_some(),
+        };
+    }
+    len
+}
+
+/// A run of text shaped with one font, passed to the callback of `fontsystem_set_shape_hook`
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiShapeHookRun {
+    /// The font the run was shaped with, valid during the callback
+    pub font: *const Font,
+    /// The text of the whole line, as UTF-8
+    pub line: *const u8,
+    pub line_len: usize,
+    /// Start of the run in the line, in bytes, the clusters of glyph infos are relative to it
+    pub start: usize,
+    /// End of the run in the line, in bytes
+    pub end: usize,
+    pub rtl: bool,
+    /// Metadata from the attributes at the start of the run
+    pub metadata: usize,
+}
+
+/// Set a callback that adjusts the glyph infos and positions of each shaped run before layout
+///
+/// The callback may change glyphs in place, but not their count. Glyphs set to glyph ID 0 are
+/// shaped again with fallback fonts. Buffers must be shaped again for the callback to apply to
+/// text they already hold.
+#[no_mangle]
+pub extern "C" fn fontsystem_set_shape_hook(
+    ctx: *mut FontSystem,
+    callback: extern "C" fn(
+        *const FfiShapeHookRun,
+        *mut rustybuzz::GlyphInfo,
+        *mut rustybuzz::GlyphPosition,
+        usize,
+    ),
+) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_shape_hook(Some(alloc::sync::Arc::new(
+        move |run: &ShapeHookRun,
+              glyph_infos: &mut Vec<rustybuzz::GlyphInfo>,
+              glyph_positions: &mut Vec<rustybuzz::GlyphPosition>| {
+            let ffi_run = FfiShapeHookRun {
+                font: run.font,
+                line: run.line.as_ptr(),
+                line_len: run.line.len(),
+                start: run.range.start,
+                end: run.range.end,
+                rtl: run.rtl,
+                metadata: run.attrs.metadata,
+            };
+            let len = glyph_infos.len().min(glyph_positions.len());
+            callback(
+                &ffi_run,
+                glyph_infos.as_mut_ptr(),
+                glyph_positions.as_mut_ptr(),
+                len,
+            );
+        },
+    )));
+}
+
+#[no_mangle]
+pub extern "C" fn fontsystem_clear_shape_hook(ctx: *mut FontSystem) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_shape_hook(None);
+}
+
+/// Line breaker that calls back into C, see `fontsystem_set_line_breaker`
+struct FfiLineBreaker {
+    callback: extern "C" fn(*const u8, usize, FfiLineBreakStrictness, *mut usize) -> usize,
+}
+
+impl LineBreaker for FfiLineBreaker {
+    fn line_breaks(&self, text: &str, strictness: LineBreakStrictness) -> Vec<usize> {
+        let mut breaks = vec![0; text.len()];
+        let len = (self.callback)(
+            text.as_ptr(),
+            text.len(),
+            strictness.into(),
+            breaks.as_mut_ptr(),
+        );
+        breaks.truncate(len);
+        breaks
+    }
+}
+
+/// Set a callback that finds where lines may break in UTF-8 text, instead of Unicode line
+/// breaking
+///
+/// The callback writes the byte offsets after which a line may break, in increasing order, to
+/// an array with room for as many offsets as the text has bytes, and returns how many it wrote.
+/// Buffers must be shaped again for the callback to apply to text they already hold.
+#[no_mangle]
+pub extern "C" fn fontsystem_set_line_breaker(
+    ctx: *mut FontSystem,
+    callback: extern "C" fn(*const u8, usize, FfiLineBreakStrictness, *mut usize) -> usize,
+) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_line_breaker(Some(alloc::sync::Arc::new(FfiLineBreaker { callback })));
+}
+
+#[no_mangle]
+pub extern "C" fn fontsystem_clear_line_breaker(ctx: *mut FontSystem) {
+    let font_system = unsafe { &mut *ctx };
+    font_system.set_line_breaker(None);
+}
+
 #[no_mangle]
 pub extern "C" fn fontsystem_free(ctx: *mut FontSystem) {
     unsafe { Box::from_raw(ctx) };
@@ -268,9 +800,40 @@ pub extern "C" fn fontsystem_free(ctx: *mut FontSystem) {
 
 
 // SwashCache ---------------------------------------------------------
+
+// Raster options given to every SwashCache created by swashcache_new
+static RASTER_DEFAULTS: std::sync::Mutex<RasterOptions> = std::sync::Mutex::new(RasterOptions {
+    hinting: true,
+    subpixel_mode: SubpixelMode::None,
+    gamma: 1.0,
+});
+
+/// Set the raster options of each [`SwashCache`] created after this call, existing caches keep
+/// theirs
+#[no_mangle]
+pub extern "C" fn cosmic_set_raster_defaults(
+    hinting: bool,
+    subpixel_mode: FfiSubpixelMode,
+    gamma: f32,
+) {
+    let mut defaults = RASTER_DEFAULTS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    *defaults = RasterOptions {
+        hinting,
+        subpixel_mode: subpixel_mode.into(),
+        gamma,
+    };
+}
+
 #[no_mangle]
 pub extern "C" fn swashcache_new() -> *mut SwashCache {
-    let swash_cache = SwashCache::new();
+    let mut swash_cache = SwashCache::new();
+    swash_cache.set_raster_options(
+        *RASTER_DEFAULTS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()),
+    );
     let ctx: Box<_> = Box::new(swash_cache);
     Box::into_raw(ctx)
 }
@@ -285,6 +848,21 @@ pub extern "C" fn swashcache_free(ctx: *mut SwashCache) {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn swashcache_set_raster_options(
+    ctx: *mut SwashCache,
+    hinting: bool,
+    subpixel_mode: FfiSubpixelMode,
+    gamma: f32,
+) {
+    let swash_cache = unsafe { &mut *ctx };
+    swash_cache.set_raster_options(RasterOptions {
+        hinting,
+        subpixel_mode: subpixel_mode.into(),
+        gamma,
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn swashcache_get_image_uncached(ctx: *mut SwashCache, font_system: *mut FontSystem, cache_key: CacheKey, outSwashImage: *mut SwashImage) -> bool {
     let swash_cache = unsafe { &mut *ctx };
@@ -328,129 +906,1822 @@ pub extern "C" fn swashimage_free(image: SwashImage) {
 
 // ---------------------------------------------------------
 
-// ---------------------------------------------------------
-
-// Metrics ---------------------------------------------------------
+// FfiArena ---------------------------------------------------------
 #[no_mangle]
-pub extern "C" fn metrics_new(font_size: f32, line_height: f32) -> *mut Metrics {
-    let metrics = Metrics::new(font_size, line_height);
-    let ctx: Box<_> = Box::new(metrics);
+pub extern "C" fn arena_new() -> *mut FfiArena {
+    let arena = FfiArena::default();
+    let ctx: Box<_> = Box::new(arena);
     Box::into_raw(ctx)
 }
 
+/// Invalidate all arrays returned by `*_arena` functions with this arena, reusing its memory
+#[no_mangle]
+pub extern "C" fn arena_reset(ctx: *mut FfiArena) {
+    let arena = unsafe { &mut *ctx };
+    arena.reset();
+}
+
 #[no_mangle]
-pub extern "C" fn metrics_free(ctx: *mut Metrics) {
+pub extern "C" fn arena_capacity(ctx: *const FfiArena) -> usize {
+    let arena = unsafe { &*ctx };
+    arena.capacity()
+}
+
+#[no_mangle]
+pub extern "C" fn arena_free(ctx: *mut FfiArena) {
     if ctx.is_null() {
         return;
     }
     unsafe {
-        Box::from_raw(ctx);
+        drop(Box::from_raw(ctx));
     }
 }
-// ---------------------------------------------------------
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// An image rasterized by `swashcache_get_image_arena`, with data owned by the arena
+#[derive(Debug)]
 #[repr(C)]
-pub enum Family<'a> {
-    /// The name of a font family of choice.
-    ///
-    /// This must be a *Typographic Family* (ID 16) or a *Family Name* (ID 1) in terms of TrueType.
-    /// Meaning you have to pass a family without any additional suffixes like _Bold_, _Italic_,
-    /// _Regular_, etc.
-    ///
-    /// Localized names are allowed.
-    Name(&'a str),
+pub struct FfiArenaImage {
+    pub data: *const u8,
+    pub data_len: usize,
+    pub content: SwashContent,
+    pub placement: Placement,
+}
 
-    /// Serif fonts represent the formal text style for a script.
-    Serif,
+/// Like `swashcache_get_image_uncached`, with the image data allocated from `arena`
+#[no_mangle]
+pub extern "C" fn swashcache_get_image_arena(
+    ctx: *mut SwashCache,
+    font_system: *mut FontSystem,
+    arena: *mut FfiArena,
+    cache_key: CacheKey,
+    out_image: *mut FfiArenaImage,
+) -> bool {
+    let swash_cache = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let arena = unsafe { &mut *arena };
+    let Some(image) = swash_cache.get_image_uncached(font_system, cache_key) else {
+        return false;
+    };
 
-    /// Glyphs in sans-serif fonts, as the term is used in CSS, are generally low contrast
-    /// and have stroke endings that are plain — without any flaring, cross stroke,
-    /// or other ornamentation.
-    SansSerif,
+    let ffi_image = FfiArenaImage {
+        data: arena.alloc_slice(&image.data),
+        data_len: image.data.len(),
+        content: image.content,
+        placement: image.placement,
+    };
+    unsafe { *out_image = ffi_image };
+    true
+}
+// ---------------------------------------------------------
 
-    /// Glyphs in cursive fonts generally use a more informal script style,
-    /// and the result looks more like handwritten pen or brush writing than printed letterwork.
-    Cursive,
+// FFI enums ---------------------------------------------------------
+//
+// Mirrors of library types with fixed discriminants, so the generated bindings keep their values
+// even when the Rust definitions are reordered or gain feature-gated variants.
 
-    /// Fantasy fonts are primarily decorative or expressive fonts that
-    /// contain decorative or expressive representations of characters.
-    Fantasy,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct FfiWeight {
+    pub value: u16,
+}
 
-    /// The sole criterion of a monospace font is that all glyphs have the same fixed width.
-    Monospace,
+impl From<Weight> for FfiWeight {
+    fn from(weight: Weight) -> Self {
+        Self { value: weight.0 }
+    }
+}
+
+impl From<FfiWeight> for Weight {
+    fn from(weight: FfiWeight) -> Self {
+        Self(weight.value)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(C)]
-pub struct PrimAttrs {
-    pub color: Color,
-    pub family: *const u16,
-    pub family_len: usize,
-    pub stretch: Stretch,
-    pub style: Style,
-    pub weight: Weight,
-    pub metadata: usize,
-    pub cache_key_flags: CacheKeyFlags,
+pub enum FfiStyle {
+    Normal = 0,
+    Italic = 1,
+    Oblique = 2,
 }
 
-// Buffer ---------------------------------------------------------
-#[no_mangle]
-pub extern "C" fn buffer_new(font_system: *mut FontSystem, metrics: *mut Metrics) -> *mut Buffer {
-    let font_system = unsafe { &mut *font_system };
-    let metrics = unsafe { &mut *metrics };
-    let buffer = Buffer::new(font_system, *metrics);
-    let ctx: Box<_> = Box::new(buffer);
-    Box::into_raw(ctx)
+impl From<Style> for FfiStyle {
+    fn from(style: Style) -> Self {
+        match style {
+            Style::Normal => Self::Normal,
+            Style::Italic => Self::Italic,
+            Style::Oblique => Self::Oblique,
+        }
+    }
 }
 
-#[no_mangle]
-pub extern "C" fn buffer_free(ctx: *mut Buffer) {
-    if ctx.is_null() {
-        return;
-    }
-    unsafe {
-        Box::from_raw(ctx);
+impl From<FfiStyle> for Style {
+    fn from(style: FfiStyle) -> Self {
+        match style {
+            FfiStyle::Normal => Self::Normal,
+            FfiStyle::Italic => Self::Italic,
+            FfiStyle::Oblique => Self::Oblique,
+        }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn buffer_set_size(ctx: *mut Buffer, font_system: *mut FontSystem, width: f32, height: f32) {
-    let buffer = unsafe { &mut *ctx };
-    let font_system = unsafe { &mut *font_system };
-    buffer.set_size(font_system, width, height);
+/// Values match the `usWidthClass` field of the OS/2 table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiStretch {
+    UltraCondensed = 1,
+    ExtraCondensed = 2,
+    Condensed = 3,
+    SemiCondensed = 4,
+    Normal = 5,
+    SemiExpanded = 6,
+    Expanded = 7,
+    ExtraExpanded = 8,
+    UltraExpanded = 9,
+}
+
+impl From<Stretch> for FfiStretch {
+    fn from(stretch: Stretch) -> Self {
+        match stretch {
+            Stretch::UltraCondensed => Self::UltraCondensed,
+            Stretch::ExtraCondensed => Self::ExtraCondensed,
+            Stretch::Condensed => Self::Condensed,
+            Stretch::SemiCondensed => Self::SemiCondensed,
+            Stretch::Normal => Self::Normal,
+            Stretch::SemiExpanded => Self::SemiExpanded,
+            Stretch::Expanded => Self::Expanded,
+            Stretch::ExtraExpanded => Self::ExtraExpanded,
+            Stretch::UltraExpanded => Self::UltraExpanded,
+        }
+    }
+}
+
+impl From<FfiStretch> for Stretch {
+    fn from(stretch: FfiStretch) -> Self {
+        match stretch {
+            FfiStretch::UltraCondensed => Self::UltraCondensed,
+            FfiStretch::ExtraCondensed => Self::ExtraCondensed,
+            FfiStretch::Condensed => Self::Condensed,
+            FfiStretch::SemiCondensed => Self::SemiCondensed,
+            FfiStretch::Normal => Self::Normal,
+            FfiStretch::SemiExpanded => Self::SemiExpanded,
+            FfiStretch::Expanded => Self::Expanded,
+            FfiStretch::ExtraExpanded => Self::ExtraExpanded,
+            FfiStretch::UltraExpanded => Self::UltraExpanded,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiWrap {
+    None = 0,
+    Glyph = 1,
+    Word = 2,
+    WordOrGlyph = 3,
+    Balanced = 4,
+    /// Optimal wrapping with the parameters of `buffer_set_optimal_wrap`, or `WordOrGlyph`
+    /// without the `optimal-wrap` feature
+    Optimal = 5,
+}
+
+impl From<Wrap> for FfiWrap {
+    fn from(wrap: Wrap) -> Self {
+        match wrap {
+            Wrap::None => Self::None,
+            Wrap::Glyph => Self::Glyph,
+            Wrap::Word => Self::Word,
+            Wrap::WordOrGlyph => Self::WordOrGlyph,
+            Wrap::Balanced => Self::Balanced,
+            #[cfg(feature = "optimal-wrap")]
+            Wrap::Optimal(_) => Self::Optimal,
+        }
+    }
+}
+
+impl From<FfiWrap> for Wrap {
+    fn from(wrap: FfiWrap) -> Self {
+        match wrap {
+            FfiWrap::None => Self::None,
+            FfiWrap::Glyph => Self::Glyph,
+            FfiWrap::Word => Self::Word,
+            FfiWrap::WordOrGlyph => Self::WordOrGlyph,
+            FfiWrap::Balanced => Self::Balanced,
+            #[cfg(feature = "optimal-wrap")]
+            FfiWrap::Optimal => Self::Optimal(OptimalWrap::default()),
+            #[cfg(not(feature = "optimal-wrap"))]
+            FfiWrap::Optimal => Self::WordOrGlyph,
+        }
+    }
+}
+
+/// Parameters of optimal wrapping, see [`OptimalWrap`] for their meaning
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct FfiOptimalWrap {
+    pub tolerance: f32,
+    pub line_penalty: f32,
+    pub hyphen_penalty: f32,
+    pub consecutive_hyphen_demerits: f32,
+    pub looseness: i32,
+}
+
+#[cfg(feature = "optimal-wrap")]
+impl From<OptimalWrap> for FfiOptimalWrap {
+    fn from(optimal: OptimalWrap) -> Self {
+        Self {
+            tolerance: optimal.tolerance,
+            line_penalty: optimal.line_penalty,
+            hyphen_penalty: optimal.hyphen_penalty,
+            consecutive_hyphen_demerits: optimal.consecutive_hyphen_demerits,
+            looseness: optimal.looseness,
+        }
+    }
+}
+
+#[cfg(feature = "optimal-wrap")]
+impl From<FfiOptimalWrap> for OptimalWrap {
+    fn from(optimal: FfiOptimalWrap) -> Self {
+        Self {
+            tolerance: optimal.tolerance,
+            line_penalty: optimal.line_penalty,
+            hyphen_penalty: optimal.hyphen_penalty,
+            consecutive_hyphen_demerits: optimal.consecutive_hyphen_demerits,
+            looseness: optimal.looseness,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiAlign {
+    Left = 0,
+    Right = 1,
+    Center = 2,
+    Justified = 3,
+    End = 4,
+}
+
+impl From<Align> for FfiAlign {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Left => Self::Left,
+            Align::Right => Self::Right,
+            Align::Center => Self::Center,
+            Align::Justified => Self::Justified,
+            Align::End => Self::End,
+        }
+    }
+}
+
+impl From<FfiAlign> for Align {
+    fn from(align: FfiAlign) -> Self {
+        match align {
+            FfiAlign::Left => Self::Left,
+            FfiAlign::Right => Self::Right,
+            FfiAlign::Center => Self::Center,
+            FfiAlign::Justified => Self::Justified,
+            FfiAlign::End => Self::End,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiVerticalAlign {
+    Top = 0,
+    Middle = 1,
+    Bottom = 2,
+}
+
+impl From<VerticalAlign> for FfiVerticalAlign {
+    fn from(vertical_align: VerticalAlign) -> Self {
+        match vertical_align {
+            VerticalAlign::Top => Self::Top,
+            VerticalAlign::Middle => Self::Middle,
+            VerticalAlign::Bottom => Self::Bottom,
+        }
+    }
+}
+
+impl From<FfiVerticalAlign> for VerticalAlign {
+    fn from(vertical_align: FfiVerticalAlign) -> Self {
+        match vertical_align {
+            FfiVerticalAlign::Top => Self::Top,
+            FfiVerticalAlign::Middle => Self::Middle,
+            FfiVerticalAlign::Bottom => Self::Bottom,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiBaselineAlign {
+    LineMax = 0,
+    Primary = 1,
+    Ideographic = 2,
+}
+
+impl From<BaselineAlign> for FfiBaselineAlign {
+    fn from(baseline_align: BaselineAlign) -> Self {
+        match baseline_align {
+            BaselineAlign::LineMax => Self::LineMax,
+            BaselineAlign::Primary => Self::Primary,
+            BaselineAlign::Ideographic => Self::Ideographic,
+        }
+    }
+}
+
+impl From<FfiBaselineAlign> for BaselineAlign {
+    fn from(baseline_align: FfiBaselineAlign) -> Self {
+        match baseline_align {
+            FfiBaselineAlign::LineMax => Self::LineMax,
+            FfiBaselineAlign::Primary => Self::Primary,
+            FfiBaselineAlign::Ideographic => Self::Ideographic,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiDirection {
+    LeftToRight = 0,
+    RightToLeft = 1,
+}
+
+impl From<Direction> for FfiDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::LeftToRight => Self::LeftToRight,
+            Direction::RightToLeft => Self::RightToLeft,
+        }
+    }
+}
+
+impl From<FfiDirection> for Direction {
+    fn from(direction: FfiDirection) -> Self {
+        match direction {
+            FfiDirection::LeftToRight => Self::LeftToRight,
+            FfiDirection::RightToLeft => Self::RightToLeft,
+        }
+    }
+}
+
+/// `Basic` falls back to `Advanced` when the `swash` feature is disabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiShaping {
+    Basic = 0,
+    Advanced = 1,
+}
+
+impl From<Shaping> for FfiShaping {
+    fn from(shaping: Shaping) -> Self {
+        match shaping {
+            #[cfg(feature = "swash")]
+            Shaping::Basic => Self::Basic,
+            Shaping::Advanced => Self::Advanced,
+        }
+    }
+}
+
+impl From<FfiShaping> for Shaping {
+    fn from(shaping: FfiShaping) -> Self {
+        match shaping {
+            #[cfg(feature = "swash")]
+            FfiShaping::Basic => Self::Basic,
+            #[cfg(not(feature = "swash"))]
+            FfiShaping::Basic => Self::Advanced,
+            FfiShaping::Advanced => Self::Advanced,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiAffinity {
+    Before = 0,
+    After = 1,
+}
+
+impl From<Affinity> for FfiAffinity {
+    fn from(affinity: Affinity) -> Self {
+        match affinity {
+            Affinity::Before => Self::Before,
+            Affinity::After => Self::After,
+        }
+    }
+}
+
+impl From<FfiAffinity> for Affinity {
+    fn from(affinity: FfiAffinity) -> Self {
+        match affinity {
+            FfiAffinity::Before => Self::Before,
+            FfiAffinity::After => Self::After,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct FfiCursor {
+    pub line: usize,
+    pub index: usize,
+    pub affinity: FfiAffinity,
+}
+
+impl From<Cursor> for FfiCursor {
+    fn from(cursor: Cursor) -> Self {
+        Self {
+            line: cursor.line,
+            index: cursor.index,
+            affinity: cursor.affinity.into(),
+        }
+    }
+}
+
+impl From<FfiCursor> for Cursor {
+    fn from(cursor: FfiCursor) -> Self {
+        Self::new_with_affinity(cursor.line, cursor.index, cursor.affinity.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiLineEnding {
+    Lf = 0,
+    CrLf = 1,
+    Cr = 2,
+    LfCr = 3,
+    None = 4,
+    Nel = 5,
+    ParagraphSeparator = 6,
+    FileSeparator = 7,
+    GroupSeparator = 8,
+    RecordSeparator = 9,
+}
+
+impl From<LineEnding> for FfiLineEnding {
+    fn from(line_ending: LineEnding) -> Self {
+        match line_ending {
+            LineEnding::Lf => Self::Lf,
+            LineEnding::CrLf => Self::CrLf,
+            LineEnding::Cr => Self::Cr,
+            LineEnding::LfCr => Self::LfCr,
+            LineEnding::None => Self::None,
+            LineEnding::Nel => Self::Nel,
+            LineEnding::ParagraphSeparator => Self::ParagraphSeparator,
+            LineEnding::FileSeparator => Self::FileSeparator,
+            LineEnding::GroupSeparator => Self::GroupSeparator,
+            LineEnding::RecordSeparator => Self::RecordSeparator,
+        }
+    }
+}
+
+impl From<FfiLineEnding> for LineEnding {
+    fn from(line_ending: FfiLineEnding) -> Self {
+        match line_ending {
+            FfiLineEnding::Lf => Self::Lf,
+            FfiLineEnding::CrLf => Self::CrLf,
+            FfiLineEnding::Cr => Self::Cr,
+            FfiLineEnding::LfCr => Self::LfCr,
+            FfiLineEnding::None => Self::None,
+            FfiLineEnding::Nel => Self::Nel,
+            FfiLineEnding::ParagraphSeparator => Self::ParagraphSeparator,
+            FfiLineEnding::FileSeparator => Self::FileSeparator,
+            FfiLineEnding::GroupSeparator => Self::GroupSeparator,
+            FfiLineEnding::RecordSeparator => Self::RecordSeparator,
+        }
+    }
+}
+
+/// Line endings used by a document, `line_ending` is `None` if it has a single line, and the
+/// most common line ending if `mixed` is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct FfiDetectedLineEnding {
+    pub line_ending: FfiLineEnding,
+    pub mixed: bool,
+}
+
+impl From<DetectedLineEnding> for FfiDetectedLineEnding {
+    fn from(detected: DetectedLineEnding) -> Self {
+        match detected {
+            DetectedLineEnding::None => Self {
+                line_ending: FfiLineEnding::None,
+                mixed: false,
+            },
+            DetectedLineEnding::Uniform(line_ending) => Self {
+                line_ending: line_ending.into(),
+                mixed: false,
+            },
+            DetectedLineEnding::Mixed(line_ending) => Self {
+                line_ending: line_ending.into(),
+                mixed: true,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiVerticalMetrics {
+    Typographic = 0,
+    Gdi = 1,
+}
+
+impl From<VerticalMetrics> for FfiVerticalMetrics {
+    fn from(vertical_metrics: VerticalMetrics) -> Self {
+        match vertical_metrics {
+            VerticalMetrics::Typographic => Self::Typographic,
+            VerticalMetrics::Gdi => Self::Gdi,
+        }
+    }
+}
+
+impl From<FfiVerticalMetrics> for VerticalMetrics {
+    fn from(vertical_metrics: FfiVerticalMetrics) -> Self {
+        match vertical_metrics {
+            FfiVerticalMetrics::Typographic => Self::Typographic,
+            FfiVerticalMetrics::Gdi => Self::Gdi,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiLineBreakStrictness {
+    Loose = 0,
+    Normal = 1,
+    Strict = 2,
+}
+
+impl From<LineBreakStrictness> for FfiLineBreakStrictness {
+    fn from(strictness: LineBreakStrictness) -> Self {
+        match strictness {
+            LineBreakStrictness::Loose => Self::Loose,
+            LineBreakStrictness::Normal => Self::Normal,
+            LineBreakStrictness::Strict => Self::Strict,
+        }
+    }
+}
+
+impl From<FfiLineBreakStrictness> for LineBreakStrictness {
+    fn from(strictness: FfiLineBreakStrictness) -> Self {
+        match strictness {
+            FfiLineBreakStrictness::Loose => Self::Loose,
+            FfiLineBreakStrictness::Normal => Self::Normal,
+            FfiLineBreakStrictness::Strict => Self::Strict,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiSubpixelMode {
+    None = 0,
+    Rgb = 1,
+    Bgr = 2,
+}
+
+impl From<SubpixelMode> for FfiSubpixelMode {
+    fn from(subpixel_mode: SubpixelMode) -> Self {
+        match subpixel_mode {
+            SubpixelMode::None => Self::None,
+            SubpixelMode::Rgb => Self::Rgb,
+            SubpixelMode::Bgr => Self::Bgr,
+        }
+    }
+}
+
+impl From<FfiSubpixelMode> for SubpixelMode {
+    fn from(subpixel_mode: FfiSubpixelMode) -> Self {
+        match subpixel_mode {
+            FfiSubpixelMode::None => Self::None,
+            FfiSubpixelMode::Rgb => Self::Rgb,
+            FfiSubpixelMode::Bgr => Self::Bgr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiDecorationKind {
+    Underline = 0,
+    Overline = 1,
+    Strikethrough = 2,
+}
+
+impl From<DecorationKind> for FfiDecorationKind {
+    fn from(kind: DecorationKind) -> Self {
+        match kind {
+            DecorationKind::Underline => Self::Underline,
+            DecorationKind::Overline => Self::Overline,
+            DecorationKind::Strikethrough => Self::Strikethrough,
+        }
+    }
+}
+
+impl From<FfiDecorationKind> for DecorationKind {
+    fn from(kind: FfiDecorationKind) -> Self {
+        match kind {
+            FfiDecorationKind::Underline => Self::Underline,
+            FfiDecorationKind::Overline => Self::Overline,
+            FfiDecorationKind::Strikethrough => Self::Strikethrough,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiDecorationStyle {
+    Solid = 0,
+    Double = 1,
+    Dotted = 2,
+    Dashed = 3,
+    Wavy = 4,
+}
+
+impl From<DecorationStyle> for FfiDecorationStyle {
+    fn from(style: DecorationStyle) -> Self {
+        match style {
+            DecorationStyle::Solid => Self::Solid,
+            DecorationStyle::Double => Self::Double,
+            DecorationStyle::Dotted => Self::Dotted,
+            DecorationStyle::Dashed => Self::Dashed,
+            DecorationStyle::Wavy => Self::Wavy,
+        }
+    }
+}
+
+impl From<FfiDecorationStyle> for DecorationStyle {
+    fn from(style: FfiDecorationStyle) -> Self {
+        match style {
+            FfiDecorationStyle::Solid => Self::Solid,
+            FfiDecorationStyle::Double => Self::Double,
+            FfiDecorationStyle::Dotted => Self::Dotted,
+            FfiDecorationStyle::Dashed => Self::Dashed,
+            FfiDecorationStyle::Wavy => Self::Wavy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiTextTransform {
+    None = 0,
+    Uppercase = 1,
+    Lowercase = 2,
+    Capitalize = 3,
+}
+
+impl From<FfiTextTransform> for TextTransform {
+    fn from(text_transform: FfiTextTransform) -> Self {
+        match text_transform {
+            FfiTextTransform::None => Self::None,
+            FfiTextTransform::Uppercase => Self::Uppercase,
+            FfiTextTransform::Lowercase => Self::Lowercase,
+            FfiTextTransform::Capitalize => Self::Capitalize,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiLineHeight {
+    Metrics = 0,
+    Relative = 1,
+    Exact = 2,
+    Normal = 3,
+    AtLeast = 4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiBaselineShift {
+    Em = 0,
+    Superscript = 1,
+    Subscript = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiShapeRunCacheMode {
+    Disabled = 0,
+    Default = 1,
+    Shared = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiMissingGlyph {
+    None = 0,
+    Char = 1,
+    Glyph = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum FfiGenericFamily {
+    Serif = 0,
+    SansSerif = 1,
+    Cursive = 2,
+    Fantasy = 3,
+    Monospace = 4,
+}
+
+impl From<FfiGenericFamily> for FamilyOwned {
+    fn from(family: FfiGenericFamily) -> Self {
+        match family {
+            FfiGenericFamily::Serif => Self::Serif,
+            FfiGenericFamily::SansSerif => Self::SansSerif,
+            FfiGenericFamily::Cursive => Self::Cursive,
+            FfiGenericFamily::Fantasy => Self::Fantasy,
+            FfiGenericFamily::Monospace => Self::Monospace,
+        }
+    }
+}
+// ---------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum Family<'a> {
+    /// The name of a font family of choice.
+    ///
+    /// This must be a *Typographic Family* (ID 16) or a *Family Name* (ID 1) in terms of TrueType.
+    /// Meaning you have to pass a family without any additional suffixes like _Bold_, _Italic_,
+    /// _Regular_, etc.
+    ///
+    /// Localized names are allowed.
+    Name(&'a str),
+
+    /// Serif fonts represent the formal text style for a script.
+    Serif,
+
+    /// Glyphs in sans-serif fonts, as the term is used in CSS, are generally low contrast
+    /// and have stroke endings that are plain — without any flaring, cross stroke,
+    /// or other ornamentation.
+    SansSerif,
+
+    /// Glyphs in cursive fonts generally use a more informal script style,
+    /// and the result looks more like handwritten pen or brush writing than printed letterwork.
+    Cursive,
+
+    /// Fantasy fonts are primarily decorative or expressive fonts that
+    /// contain decorative or expressive representations of characters.
+    Fantasy,
+
+    /// The sole criterion of a monospace font is that all glyphs have the same fixed width.
+    Monospace,
+}
+
+// Attrs ---------------------------------------------------------
+#[no_mangle]
+pub extern "C" fn attrs_new() -> *mut AttrsOwned {
+    let attrs = AttrsOwned::new(Attrs::new());
+    let ctx: Box<_> = Box::new(attrs);
+    Box::into_raw(ctx)
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_free(ctx: *mut AttrsOwned) {
+    if ctx.is_null() {
+        return;
+    }
+    unsafe {
+        Box::from_raw(ctx);
+    }
+}
+
+/// Use the font family named by the UTF-16 `family`, or the serif family if it is empty
+#[no_mangle]
+pub extern "C" fn attrs_set_family(ctx: *mut AttrsOwned, family: *const u16, family_len: usize) {
+    let attrs = unsafe { &mut *ctx };
+    if family_len == 0 {
+        attrs.family_owned = FamilyOwned::Serif;
+        return;
+    }
+    let family = unsafe { std::slice::from_raw_parts(family, family_len) };
+    let family = String::from_utf16_lossy(family);
+    attrs.family_owned = FamilyOwned::Name(family);
+}
+
+/// Use a generic font family, like the monospace family of the system
+#[no_mangle]
+pub extern "C" fn attrs_set_generic_family(ctx: *mut AttrsOwned, family: FfiGenericFamily) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.family_owned = family.into();
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_color(ctx: *mut AttrsOwned, color: Color) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.color_opt = Some(color);
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_color(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.color_opt = None;
+}
+
+/// Draw a drop shadow behind the text, offset by `x` and `y` and blurred by `blur` pixels
+#[no_mangle]
+pub extern "C" fn attrs_set_shadow(ctx: *mut AttrsOwned, color: Color, x: f32, y: f32, blur: f32) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.shadow_opt = Some(Shadow::new(color, x, y, blur));
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_shadow(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.shadow_opt = None;
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_background_color(ctx: *mut AttrsOwned, color: Color) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.background_color_opt = Some(color);
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_background_color(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.background_color_opt = None;
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_stretch(ctx: *mut AttrsOwned, stretch: FfiStretch) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.stretch = stretch.into();
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_style(ctx: *mut AttrsOwned, style: FfiStyle) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.style = style.into();
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_weight(ctx: *mut AttrsOwned, weight: FfiWeight) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.weight = weight.into();
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_metadata(ctx: *mut AttrsOwned, metadata: usize) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.metadata = metadata;
+}
+
+/// Set the font size in pixels, overriding the font size of the buffer, font sizes that are not
+/// finite and positive are ignored
+#[no_mangle]
+pub extern "C" fn attrs_set_font_size(ctx: *mut AttrsOwned, font_size: f32) {
+    let attrs = unsafe { &mut *ctx };
+    if font_size.is_finite() && font_size > 0.0 {
+        attrs.font_size_opt = Some(FontSize(font_size));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_font_size(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.font_size_opt = None;
+}
+
+/// Set extra advance added after every character, in em of the font size
+#[no_mangle]
+pub extern "C" fn attrs_set_letter_spacing(ctx: *mut AttrsOwned, em: f32) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.letter_spacing_opt = Some(LetterSpacing(em));
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_letter_spacing(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.letter_spacing_opt = None;
+}
+
+/// Keep the text from making its line taller than the rest of the line needs
+#[no_mangle]
+pub extern "C" fn attrs_set_clamp_line_height(ctx: *mut AttrsOwned, clamp_line_height: bool) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.clamp_line_height = clamp_line_height;
+}
+
+/// Set the opaque id of a custom fill such as a gradient or texture, 0 for none
+#[no_mangle]
+pub extern "C" fn attrs_set_fill_id(ctx: *mut AttrsOwned, fill_id: u64) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.fill_id = fill_id;
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_set_cache_key_flags(ctx: *mut AttrsOwned, cache_key_flags: CacheKeyFlags) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.cache_key_flags = cache_key_flags;
+}
+
+/// Set the BCP 47 language tag used for shaping, such as `tr`
+#[no_mangle]
+pub extern "C" fn attrs_set_lang(ctx: *mut AttrsOwned, lang: *const u16, lang_len: usize) {
+    let attrs = unsafe { &mut *ctx };
+    let lang = unsafe { std::slice::from_raw_parts(lang, lang_len) };
+    attrs.lang_opt = Some(String::from_utf16_lossy(lang));
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_lang(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.lang_opt = None;
+}
+
+/// Set the script used for shaping from its UTF-16 ISO 15924 code, such as `Hani`, returns
+/// false if the code is unknown
+#[no_mangle]
+pub extern "C" fn attrs_set_script(ctx: *mut AttrsOwned, script: *const u16, script_len: usize) -> bool {
+    let attrs = unsafe { &mut *ctx };
+    let script = unsafe { std::slice::from_raw_parts(script, script_len) };
+    match Script::from_short_name(&String::from_utf16_lossy(script)) {
+        Some(script) => {
+            attrs.script_opt = Some(script);
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_script(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.script_opt = None;
+}
+
+/// Synthesize italic by skewing glyphs by `angle` degrees, leaning the other way in
+/// right-to-left text if `mirror_rtl` is set
+#[no_mangle]
+pub extern "C" fn attrs_set_oblique(ctx: *mut AttrsOwned, angle: f32, mirror_rtl: bool) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.oblique_opt = Some(Oblique::new(angle).mirror_rtl(mirror_rtl));
+    attrs.cache_key_flags |= CacheKeyFlags::FAKE_ITALIC;
+}
+
+/// Skew synthesized italic by the default angle again, keeping `CacheKeyFlags::FAKE_ITALIC`
+#[no_mangle]
+pub extern "C" fn attrs_clear_oblique(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.oblique_opt = None;
+}
+
+/// Use the named instance of variable fonts with this UTF-16 name, like `Display Light`
+#[no_mangle]
+pub extern "C" fn attrs_set_font_instance_name(ctx: *mut AttrsOwned, name: *const u16, name_len: usize) {
+    let attrs = unsafe { &mut *ctx };
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    attrs.font_instance_opt = Some(FontInstanceOwned::Name(String::from_utf16_lossy(name)));
+}
+
+/// Use the named instance of variable fonts at this index of their `fvar` table
+#[no_mangle]
+pub extern "C" fn attrs_set_font_instance_index(ctx: *mut AttrsOwned, index: u16) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.font_instance_opt = Some(FontInstanceOwned::Index(index));
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_font_instance(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.font_instance_opt = None;
+}
+
+/// Add an OpenType feature setting from its 4 byte tag and value, like `liga` with 0 to disable
+/// ligatures
+#[no_mangle]
+pub extern "C" fn attrs_add_font_feature(ctx: *mut AttrsOwned, tag: *const u8, value: u32) {
+    let attrs = unsafe { &mut *ctx };
+    let tag = unsafe { std::slice::from_raw_parts(tag, 4) };
+    attrs
+        .font_features
+        .push(FontFeature::new([tag[0], tag[1], tag[2], tag[3]], value));
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_font_features(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.font_features.clear();
+}
+
+/// Use small capitals for lowercase letters, synthesized if the font lacks them
+#[no_mangle]
+pub extern "C" fn attrs_set_small_caps(ctx: *mut AttrsOwned, small_caps: bool) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.small_caps = small_caps;
+}
+
+/// Raise or lower the text, `em` is the distance in em of the font size for `Em` and is ignored
+/// otherwise
+#[no_mangle]
+pub extern "C" fn attrs_set_baseline_shift(
+    ctx: *mut AttrsOwned,
+    baseline_shift: FfiBaselineShift,
+    em: f32,
+) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.baseline_shift_opt = Some(match baseline_shift {
+        FfiBaselineShift::Em => BaselineShift::Em(em),
+        FfiBaselineShift::Superscript => BaselineShift::Superscript,
+        FfiBaselineShift::Subscript => BaselineShift::Subscript,
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_baseline_shift(ctx: *mut AttrsOwned) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.baseline_shift_opt = None;
+}
+
+/// Use the font's vertical alternates, from the `vert` and `vrt2` features
+#[no_mangle]
+pub extern "C" fn attrs_set_vertical_forms(ctx: *mut AttrsOwned, vertical_forms: bool) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.vertical_forms = vertical_forms;
+}
+
+/// Set the text upright across vertical lines in the space of one character, like tate-chu-yoko
+#[no_mangle]
+pub extern "C" fn attrs_set_combine_upright(ctx: *mut AttrsOwned, combine_upright: bool) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.combine_upright = combine_upright;
+}
+
+/// Show the text in another case when shaping, without changing the text
+#[no_mangle]
+pub extern "C" fn attrs_set_text_transform(ctx: *mut AttrsOwned, text_transform: FfiTextTransform) {
+    let attrs = unsafe { &mut *ctx };
+    attrs.text_transform = text_transform.into();
+}
+
+/// Draw a decoration line with a style, in `color` if `has_color` is set or else in the color of
+/// the text
+#[no_mangle]
+pub extern "C" fn attrs_set_decoration(
+    ctx: *mut AttrsOwned,
+    kind: FfiDecorationKind,
+    style: FfiDecorationStyle,
+    has_color: bool,
+    color: Color,
+) {
+    let attrs = unsafe { &mut *ctx };
+    let line = DecorationLine {
+        style: style.into(),
+        color_opt: has_color.then_some(color),
+    };
+    match kind.into() {
+        DecorationKind::Underline => attrs.decoration.underline = Some(line),
+        DecorationKind::Overline => attrs.decoration.overline = Some(line),
+        DecorationKind::Strikethrough => attrs.decoration.strikethrough = Some(line),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn attrs_clear_decoration(ctx: *mut AttrsOwned, kind: FfiDecorationKind) {
+    let attrs = unsafe { &mut *ctx };
+    match kind.into() {
+        DecorationKind::Underline => attrs.decoration.underline = None,
+        DecorationKind::Overline => attrs.decoration.overline = None,
+        DecorationKind::Strikethrough => attrs.decoration.strikethrough = None,
+    }
+}
+
+/// A span of rich text, pairing UTF-16 text with an [`AttrsOwned`] handle from `attrs_new`
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RichTextSpan {
+    pub text: *const u16,
+    pub text_len: usize,
+    pub attrs: *const AttrsOwned,
+}
+// ---------------------------------------------------------
+
+// Buffer ---------------------------------------------------------
+#[no_mangle]
+pub extern "C" fn buffer_new(font_system: *mut FontSystem, metrics: Metrics) -> *mut Buffer {
+    let font_system = unsafe { &mut *font_system };
+    let buffer = Buffer::new(font_system, metrics);
+    let ctx: Box<_> = Box::new(buffer);
+    Box::into_raw(ctx)
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_free(ctx: *mut Buffer) {
+    if ctx.is_null() {
+        return;
+    }
+    unsafe {
+        Box::from_raw(ctx);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_metrics(ctx: *const Buffer) -> Metrics {
+    let buffer = unsafe { &*ctx };
+    buffer.metrics()
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_set_metrics(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    metrics: Metrics,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_metrics(font_system, metrics);
 }
 
 #[no_mangle]
-pub extern "C" fn buffer_set_text(ctx: *mut Buffer, font_system: *mut FontSystem, text: *const u16, len: usize, prim_attrs: PrimAttrs, shaping: Shaping) {
+pub extern "C" fn buffer_set_size(ctx: *mut Buffer, font_system: *mut FontSystem, width: f32, height: f32) {
     let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_size(font_system, width, height);
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_wrap(ctx: *const Buffer) -> FfiWrap {
+    let buffer = unsafe { &*ctx };
+    buffer.wrap().into()
+}
 
-    let family_str;
+/// Set the wrapping mode, `Optimal` keeps the parameters of the buffer if it already wraps
+/// optimally
+#[no_mangle]
+pub extern "C" fn buffer_set_wrap(ctx: *mut Buffer, font_system: *mut FontSystem, wrap: FfiWrap) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let wrap = match (wrap, buffer.wrap()) {
+        #[cfg(feature = "optimal-wrap")]
+        (FfiWrap::Optimal, Wrap::Optimal(optimal)) => Wrap::Optimal(optimal),
+        (wrap, _) => wrap.into(),
+    };
+    buffer.set_wrap(font_system, wrap);
+}
 
-    let font_family: fontdb::Family = match prim_attrs.family_len {
-        0 => fontdb::Family::Serif,
+/// Write the parameters of optimal wrapping to `out_optimal`, returns false and leaves it
+/// unchanged if the buffer does not wrap optimally
+#[no_mangle]
+pub extern "C" fn buffer_get_optimal_wrap(ctx: *const Buffer, out_optimal: *mut FfiOptimalWrap) -> bool {
+    let buffer = unsafe { &*ctx };
+    match buffer.wrap() {
+        #[cfg(feature = "optimal-wrap")]
+        Wrap::Optimal(optimal) => {
+            unsafe { *out_optimal = optimal.into() };
+            true
+        }
         _ => {
-            let family_text = unsafe { std::slice::from_raw_parts(prim_attrs.family, prim_attrs.family_len) };
-            family_str = String::from_utf16(family_text).unwrap();
-            fontdb::Family::Name(&family_str)
+            let _ = out_optimal;
+            false
+        }
+    }
+}
+
+/// Wrap optimally with the given parameters, or like `WordOrGlyph` without the `optimal-wrap`
+/// feature
+#[no_mangle]
+pub extern "C" fn buffer_set_optimal_wrap(ctx: *mut Buffer, font_system: *mut FontSystem, optimal: FfiOptimalWrap) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    #[cfg(feature = "optimal-wrap")]
+    buffer.set_wrap(font_system, Wrap::Optimal(optimal.into()));
+    #[cfg(not(feature = "optimal-wrap"))]
+    {
+        let _ = optimal;
+        buffer.set_wrap(font_system, Wrap::WordOrGlyph);
+    }
+}
+
+/// Get the number of columns and write the gap between them to `out_gap`
+#[no_mangle]
+pub extern "C" fn buffer_get_columns(ctx: *const Buffer, out_gap: *mut f32) -> usize {
+    let buffer = unsafe { &*ctx };
+    let (columns, gap) = buffer.columns();
+    unsafe { *out_gap = gap };
+    columns
+}
+
+/// Set the number of columns and the gap between them, `columns` is clamped to at least 1
+#[no_mangle]
+pub extern "C" fn buffer_set_columns(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    columns: usize,
+    gap: f32,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_columns(font_system, columns.max(1), gap);
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_tabular_digits(ctx: *const Buffer) -> bool {
+    let buffer = unsafe { &*ctx };
+    buffer.tabular_digits()
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_set_tabular_digits(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    tabular_digits: bool,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_tabular_digits(font_system, tabular_digits);
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_hanging_punctuation(ctx: *const Buffer) -> bool {
+    let buffer = unsafe { &*ctx };
+    buffer.hanging_punctuation()
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_set_hanging_punctuation(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    hanging_punctuation: bool,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_hanging_punctuation(font_system, hanging_punctuation);
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_vertical_align(ctx: *const Buffer) -> FfiVerticalAlign {
+    let buffer = unsafe { &*ctx };
+    buffer.vertical_align().into()
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_set_vertical_align(ctx: *mut Buffer, vertical_align: FfiVerticalAlign) {
+    let buffer = unsafe { &mut *ctx };
+    buffer.set_vertical_align(vertical_align.into());
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_leading_trim(ctx: *const Buffer) -> bool {
+    let buffer = unsafe { &*ctx };
+    buffer.leading_trim()
+}
+
+/// Set if the space above the cap height of the first line and below the baseline of the last
+/// line is trimmed
+#[no_mangle]
+pub extern "C" fn buffer_set_leading_trim(ctx: *mut Buffer, leading_trim: bool) {
+    let buffer = unsafe { &mut *ctx };
+    buffer.set_leading_trim(leading_trim);
+}
+
+/// Set if text set or inserted afterwards is converted to Unicode Normalization Form C
+#[cfg(feature = "nfc-normalization")]
+#[no_mangle]
+pub extern "C" fn buffer_set_nfc_normalization(ctx: *mut Buffer, nfc_normalization: bool) {
+    let buffer = unsafe { &mut *ctx };
+    buffer.set_nfc_normalization(nfc_normalization);
+}
+
+/// Get the space trimmed from the top and the bottom of the text
+#[no_mangle]
+pub extern "C" fn buffer_get_leading_trim_amounts(
+    ctx: *const Buffer,
+    out_top: *mut f32,
+    out_bottom: *mut f32,
+) {
+    let buffer = unsafe { &*ctx };
+    let leading_trim = buffer.leading_trim_amounts();
+    unsafe {
+        *out_top = leading_trim.top;
+        *out_bottom = leading_trim.bottom;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_vertical_metrics(ctx: *const Buffer) -> FfiVerticalMetrics {
+    let buffer = unsafe { &*ctx };
+    buffer.vertical_metrics().into()
+}
+
+/// Place the baseline of lines with typographic or GDI metrics, see `font_line_height_em` for a
+/// matching line height
+#[no_mangle]
+pub extern "C" fn buffer_set_vertical_metrics(ctx: *mut Buffer, vertical_metrics: FfiVerticalMetrics) {
+    let buffer = unsafe { &mut *ctx };
+    buffer.set_vertical_metrics(vertical_metrics.into());
+}
+
+/// Get the distance between lines, from the metrics and the line height mode
+#[no_mangle]
+pub extern "C" fn buffer_get_line_height(ctx: *const Buffer) -> f32 {
+    let buffer = unsafe { &*ctx };
+    buffer.line_height()
+}
+
+/// Get how the height of each line is resolved, `out_value` is set for `Relative`, `Exact`, and
+/// `AtLeast`
+#[no_mangle]
+pub extern "C" fn buffer_get_line_height_mode(ctx: *const Buffer, out_value: *mut f32) -> FfiLineHeight {
+    let buffer = unsafe { &*ctx };
+    let (mode, value) = match buffer.line_height_mode() {
+        LineHeight::Metrics => (FfiLineHeight::Metrics, 0.0),
+        LineHeight::Relative(factor) => (FfiLineHeight::Relative, factor),
+        LineHeight::Exact(line_height) => (FfiLineHeight::Exact, line_height),
+        LineHeight::Normal => (FfiLineHeight::Normal, 0.0),
+        LineHeight::AtLeast(line_height) => (FfiLineHeight::AtLeast, line_height),
+    };
+    unsafe { *out_value = value };
+    mode
+}
+
+/// Set how the height of each line is resolved, `value` is the factor for `Relative` and the
+/// height in pixels for `Exact` and `AtLeast`, and is ignored otherwise
+#[no_mangle]
+pub extern "C" fn buffer_set_line_height_mode(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    mode: FfiLineHeight,
+    value: f32,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let line_height_mode = match mode {
+        FfiLineHeight::Metrics => LineHeight::Metrics,
+        FfiLineHeight::Relative => LineHeight::Relative(value),
+        FfiLineHeight::Exact => LineHeight::Exact(value),
+        FfiLineHeight::Normal => LineHeight::Normal,
+        FfiLineHeight::AtLeast => LineHeight::AtLeast(value),
+    };
+    buffer.set_line_height_mode(font_system, line_height_mode);
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_baseline_align(ctx: *const Buffer) -> FfiBaselineAlign {
+    let buffer = unsafe { &*ctx };
+    buffer.baseline_align().into()
+}
+
+/// Set how the baseline of lines that mix fonts is placed
+#[no_mangle]
+pub extern "C" fn buffer_set_baseline_align(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    baseline_align: FfiBaselineAlign,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_baseline_align(font_system, baseline_align.into());
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_get_line_break_strictness(ctx: *const Buffer) -> FfiLineBreakStrictness {
+    let buffer = unsafe { &*ctx };
+    buffer.line_break_strictness().into()
+}
+
+/// Set the strictness of the rules for breaking lines of Chinese and Japanese text
+#[no_mangle]
+pub extern "C" fn buffer_set_line_break_strictness(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    strictness: FfiLineBreakStrictness,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_line_break_strictness(font_system, strictness.into());
+}
+
+/// Get the shape run cache used by the buffer, `out_shared_id` is set for `Shared`. Returns
+/// `Default` without the `shape-run-cache` feature
+#[no_mangle]
+pub extern "C" fn buffer_get_shape_run_cache(
+    ctx: *const Buffer,
+    out_shared_id: *mut u32,
+) -> FfiShapeRunCacheMode {
+    let buffer = unsafe { &*ctx };
+    #[cfg(feature = "shape-run-cache")]
+    match buffer.shape_run_cache() {
+        ShapeRunCacheMode::Disabled => FfiShapeRunCacheMode::Disabled,
+        ShapeRunCacheMode::Default => FfiShapeRunCacheMode::Default,
+        ShapeRunCacheMode::Shared(id) => {
+            unsafe { *out_shared_id = id };
+            FfiShapeRunCacheMode::Shared
         }
+    }
+    #[cfg(not(feature = "shape-run-cache"))]
+    {
+        let _ = (buffer, out_shared_id);
+        FfiShapeRunCacheMode::Default
+    }
+}
+
+/// Set the shape run cache used when shaping lines of the buffer, `shared_id` selects the cache
+/// for `Shared` and is ignored otherwise. Does nothing without the `shape-run-cache` feature
+#[no_mangle]
+pub extern "C" fn buffer_set_shape_run_cache(
+    ctx: *mut Buffer,
+    mode: FfiShapeRunCacheMode,
+    shared_id: u32,
+) {
+    let buffer = unsafe { &mut *ctx };
+    #[cfg(feature = "shape-run-cache")]
+    buffer.set_shape_run_cache(match mode {
+        FfiShapeRunCacheMode::Disabled => ShapeRunCacheMode::Disabled,
+        FfiShapeRunCacheMode::Default => ShapeRunCacheMode::Default,
+        FfiShapeRunCacheMode::Shared => ShapeRunCacheMode::Shared(shared_id),
+    });
+    #[cfg(not(feature = "shape-run-cache"))]
+    let _ = (buffer, mode, shared_id);
+}
+
+/// Set what replaces characters that no font supports, `c` is used for `Char`, and `font` from
+/// `fontsystem_get_font` and `glyph_id` for `Glyph`. Returns false if `c` is not a valid
+/// character or `font` is null
+#[no_mangle]
+pub extern "C" fn buffer_set_missing_glyph(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    kind: FfiMissingGlyph,
+    c: u32,
+    font: *const Font,
+    glyph_id: u16,
+) -> bool {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let missing_glyph = match kind {
+        FfiMissingGlyph::None => None,
+        FfiMissingGlyph::Char => match char::from_u32(c) {
+            Some(c) => Some(MissingGlyph::Char(c)),
+            None => return false,
+        },
+        FfiMissingGlyph::Glyph => match unsafe { font.as_ref() } {
+            Some(font) => Some(MissingGlyph::Glyph(font.id(), glyph_id)),
+            None => return false,
+        },
+    };
+    buffer.set_missing_glyph(font_system, missing_glyph);
+    true
+}
+
+/// Get the number of clusters in the shaped lines with characters that no font supports
+#[no_mangle]
+pub extern "C" fn buffer_missing_glyphs(ctx: *const Buffer) -> usize {
+    let buffer = unsafe { &*ctx };
+    buffer.missing_glyphs()
+}
+
+/// Set the UTF-16 language tag of the hyphenation patterns used to break words, an empty tag
+/// stops breaking words with hyphens
+#[cfg(feature = "hyphenation")]
+#[no_mangle]
+pub extern "C" fn buffer_set_hyphenation(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    lang: *const u16,
+    lang_len: usize,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let lang = if lang_len == 0 {
+        None
+    } else {
+        let lang = unsafe { std::slice::from_raw_parts(lang, lang_len) };
+        Some(String::from_utf16_lossy(lang))
     };
+    buffer.set_hyphenation(font_system, lang.as_deref());
+}
+
+/// Set the alignment of every line, `has_align` false restores the default alignment
+#[no_mangle]
+pub extern "C" fn buffer_set_align(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    has_align: bool,
+    align: FfiAlign,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let align = has_align.then(|| align.into());
+    for line in buffer.lines.iter_mut() {
+        line.set_align(align);
+    }
+    buffer.shape_until_scroll(font_system, false);
+}
+
+/// Paragraph attributes of a line, `has_align` and `has_direction` false use the defaults
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiParagraphAttrs {
+    pub has_align: bool,
+    pub align: FfiAlign,
+    pub indent: f32,
+    pub has_direction: bool,
+    pub direction: FfiDirection,
+    pub space_before: f32,
+    pub space_after: f32,
+    pub hanging_indent: f32,
+    pub drop_cap: usize,
+}
+
+impl From<ParagraphAttrs> for FfiParagraphAttrs {
+    fn from(attrs: ParagraphAttrs) -> Self {
+        Self {
+            has_align: attrs.align.is_some(),
+            align: attrs.align.unwrap_or(Align::Left).into(),
+            indent: attrs.indent,
+            has_direction: attrs.direction.is_some(),
+            direction: attrs.direction.unwrap_or(Direction::LeftToRight).into(),
+            space_before: attrs.space_before,
+            space_after: attrs.space_after,
+            hanging_indent: attrs.hanging_indent,
+            drop_cap: attrs.drop_cap,
+        }
+    }
+}
+
+impl From<FfiParagraphAttrs> for ParagraphAttrs {
+    fn from(attrs: FfiParagraphAttrs) -> Self {
+        Self {
+            align: attrs.has_align.then(|| attrs.align.into()),
+            indent: attrs.indent,
+            direction: attrs.has_direction.then(|| attrs.direction.into()),
+            space_before: attrs.space_before,
+            space_after: attrs.space_after,
+            hanging_indent: attrs.hanging_indent,
+            drop_cap: attrs.drop_cap,
+        }
+    }
+}
+
+/// Set the paragraph attributes of every line
+#[no_mangle]
+pub extern "C" fn buffer_set_paragraph_attrs(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    attrs: FfiParagraphAttrs,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    buffer.set_paragraph_attrs(font_system, attrs.into());
+}
+
+/// Get the paragraph attributes of a line, returns false if there is no line `line_i`
+#[no_mangle]
+pub extern "C" fn buffer_get_line_paragraph_attrs(
+    ctx: *const Buffer,
+    line_i: usize,
+    out_attrs: *mut FfiParagraphAttrs,
+) -> bool {
+    let buffer = unsafe { &*ctx };
+    match buffer.lines.get(line_i) {
+        Some(line) => {
+            unsafe { *out_attrs = line.paragraph_attrs().into() };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Set the paragraph attributes of a line, returns false if there is no line `line_i`
+#[no_mangle]
+pub extern "C" fn buffer_set_line_paragraph_attrs(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    line_i: usize,
+    attrs: FfiParagraphAttrs,
+) -> bool {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    match buffer.lines.get_mut(line_i) {
+        Some(line) => {
+            if line.set_paragraph_attrs(attrs.into()) {
+                buffer.set_redraw(true);
+                buffer.shape_until_scroll(font_system, false);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiRuby {
+    /// Byte range of the base text in the line
+    pub start: usize,
+    pub end: usize,
+    /// UTF-16 text of the annotation
+    pub text: *const u16,
+    pub text_len: usize,
+}
 
-    let attrs = Attrs {
-        color_opt: Some(prim_attrs.color),
-        family: font_family,
-        stretch: prim_attrs.stretch,
-        style: prim_attrs.style,
-        weight: prim_attrs.weight,
-        metadata: prim_attrs.metadata,
-        cache_key_flags: prim_attrs.cache_key_flags,
+/// Set the ruby text of a line, returns false if there is no line `line_i`
+#[no_mangle]
+pub extern "C" fn buffer_set_line_ruby(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    line_i: usize,
+    ruby: *const FfiRuby,
+    ruby_len: usize,
+) -> bool {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let ruby = if ruby_len == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ruby, ruby_len) }
     };
+    let ruby = ruby
+        .iter()
+        .map(|ruby| {
+            let text = unsafe { std::slice::from_raw_parts(ruby.text, ruby.text_len) };
+            Ruby::new(ruby.start..ruby.end, String::from_utf16_lossy(text))
+        })
+        .collect();
+    match buffer.lines.get_mut(line_i) {
+        Some(line) => {
+            if line.set_ruby(ruby) {
+                buffer.set_redraw(true);
+                buffer.shape_until_scroll(font_system, false);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Set the list marker of a line from UTF-16 text, or clear it if `text` is null, returns false
+/// if there is no line `line_i`
+#[no_mangle]
+pub extern "C" fn buffer_set_line_marker(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    line_i: usize,
+    text: *const u16,
+    text_len: usize,
+    gap: f32,
+) -> bool {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let marker_opt = (!text.is_null()).then(|| {
+        let text = unsafe { std::slice::from_raw_parts(text, text_len) };
+        ListMarker::new(String::from_utf16_lossy(text)).gap(gap)
+    });
+    match buffer.lines.get_mut(line_i) {
+        Some(line) => {
+            if line.set_marker(marker_opt) {
+                buffer.set_redraw(true);
+                buffer.shape_until_scroll(font_system, false);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Convert a byte offset in a line to an offset in UTF-16 code units, returns false if there is
+/// no line `line_i`
+#[no_mangle]
+pub extern "C" fn buffer_line_utf8_to_utf16(
+    ctx: *mut Buffer,
+    line_i: usize,
+    index: usize,
+    out_offset: *mut usize,
+) -> bool {
+    let buffer = unsafe { &mut *ctx };
+    match buffer.lines.get_mut(line_i) {
+        Some(line) => {
+            unsafe { *out_offset = line.utf16_map().to_utf16(index) };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Convert an offset in UTF-16 code units in a line to a byte offset, returns false if there is
+/// no line `line_i`
+#[no_mangle]
+pub extern "C" fn buffer_line_utf16_to_utf8(
+    ctx: *mut Buffer,
+    line_i: usize,
+    offset: usize,
+    out_index: *mut usize,
+) -> bool {
+    let buffer = unsafe { &mut *ctx };
+    match buffer.lines.get_mut(line_i) {
+        Some(line) => {
+            unsafe { *out_index = line.utf16_map().to_utf8(offset) };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Convert a cursor to an offset in UTF-16 code units into the text of `buffer_get_text`
+#[no_mangle]
+pub extern "C" fn buffer_cursor_to_utf16(ctx: *mut Buffer, cursor: FfiCursor) -> usize {
+    let buffer = unsafe { &mut *ctx };
+    buffer.cursor_to_utf16(cursor.into())
+}
+
+/// Convert an offset in UTF-16 code units into the text of `buffer_get_text` to a cursor
+#[no_mangle]
+pub extern "C" fn buffer_cursor_from_utf16(ctx: *mut Buffer, offset: usize) -> FfiCursor {
+    let buffer = unsafe { &mut *ctx };
+    buffer.cursor_from_utf16(offset).into()
+}
+
+/// Get the line ending used by `buffer_get_text`, returns false if each line keeps its own
+#[no_mangle]
+pub extern "C" fn buffer_get_line_ending(ctx: *const Buffer, out_line_ending: *mut FfiLineEnding) -> bool {
+    let buffer = unsafe { &*ctx };
+    match buffer.line_ending() {
+        Some(line_ending) => {
+            unsafe { *out_line_ending = line_ending.into(); }
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_set_line_ending(ctx: *mut Buffer, line_ending: FfiLineEnding) {
+    let buffer = unsafe { &mut *ctx };
+    buffer.set_line_ending(Some(line_ending.into()));
+}
+
+/// Keep the line ending of each line in `buffer_get_text`
+#[no_mangle]
+pub extern "C" fn buffer_clear_line_ending(ctx: *mut Buffer) {
+    let buffer = unsafe { &mut *ctx };
+    buffer.set_line_ending(None);
+}
+
+/// Get the text of all lines joined by their line endings, as UTF-16
+#[no_mangle]
+pub extern "C" fn buffer_get_text(ctx: *const Buffer, callback: extern "C" fn(*const u16, usize)) {
+    let buffer = unsafe { &*ctx };
+    let text: Vec<u16> = buffer.text().encode_utf16().collect();
+    callback(text.as_ptr(), text.len());
+}
+
+#[no_mangle]
+pub extern "C" fn buffer_set_text(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    text: *const u16,
+    len: usize,
+    attrs: *const AttrsOwned,
+    shaping: FfiShaping,
+) {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let attrs = unsafe { &*attrs };
 
-    let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
-    let str = String::from_utf16(slice).unwrap();
+    let text = unsafe { std::slice::from_raw_parts(text, len) };
+    let text = String::from_utf16_lossy(text);
+
+    buffer.set_text(font_system, &text, attrs.as_attrs(), shaping.into());
+}
 
+#[no_mangle]
+pub extern "C" fn buffer_set_rich_text(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    spans: *const RichTextSpan,
+    spans_len: usize,
+    default_attrs: *const AttrsOwned,
+    shaping: FfiShaping,
+) {
+    let buffer = unsafe { &mut *ctx };
     let font_system = unsafe { &mut *font_system };
-    buffer.set_text(font_system, &str, attrs.clone(), shaping);
+    let default_attrs = unsafe { &*default_attrs };
+
+    let spans = unsafe { std::slice::from_raw_parts(spans, spans_len) };
+    let spans: Vec<(String, &AttrsOwned)> = spans
+        .iter()
+        .map(|span| {
+            let text = unsafe { std::slice::from_raw_parts(span.text, span.text_len) };
+            (String::from_utf16_lossy(text), unsafe { &*span.attrs })
+        })
+        .collect();
+
+    buffer.set_rich_text(
+        font_system,
+        spans
+            .iter()
+            .map(|(text, attrs)| (text.as_str(), attrs.as_attrs())),
+        default_attrs.as_attrs(),
+        shaping.into(),
+    );
 }
 
 #[no_mangle]
@@ -469,6 +2740,137 @@ pub extern "C" fn buffer_layout_runs(ctx: *mut Buffer, callback: extern "C" fn(*
     }
 }
 
+/// A layout run copied into an arena by `buffer_layout_runs_arena`
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiLayoutRun {
+    pub line_i: usize,
+    pub text: *const u8,
+    pub text_len: usize,
+    pub rtl: bool,
+    pub glyphs: *const LayoutGlyph,
+    pub glyphs_len: usize,
+    pub line_y: f32,
+    pub line_top: f32,
+    pub line_w: f32,
+    pub line_x: f32,
+    pub column: usize,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+/// Copy the layout runs and their glyphs into `arena`, returning the runs and writing their count
+/// to `out_len`
+///
+/// The text of each run points into the buffer, and stays valid until the buffer is changed.
+#[no_mangle]
+pub extern "C" fn buffer_layout_runs_arena(
+    ctx: *const Buffer,
+    arena: *mut FfiArena,
+    out_len: *mut usize,
+) -> *const FfiLayoutRun {
+    let buffer = unsafe { &*ctx };
+    let arena = unsafe { &mut *arena };
+    let runs: Vec<FfiLayoutRun> = buffer
+        .layout_runs()
+        .map(|run| FfiLayoutRun {
+            line_i: run.line_i,
+            text: run.text.as_ptr(),
+            text_len: run.text.len(),
+            rtl: run.rtl,
+            glyphs: arena.alloc_slice(run.glyphs),
+            glyphs_len: run.glyphs.len(),
+            line_y: run.line_y,
+            line_top: run.line_top,
+            line_w: run.line_w,
+            line_x: run.line_x,
+            column: run.column,
+            ascent: run.ascent,
+            descent: run.descent,
+        })
+        .collect();
+    unsafe { *out_len = runs.len() };
+    arena.alloc_slice(&runs)
+}
+
+/// A page copied into an arena by `buffer_paginate_arena`, see [`PageRange`]
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiPageRange {
+    pub start_line: usize,
+    pub start_layout: i32,
+    pub end_line: usize,
+    pub end_layout: i32,
+    pub y: f32,
+    pub height: f32,
+}
+
+/// Split the text into pages `height` tall and copy them into `arena`, returning the pages and
+/// writing their count to `out_len`
+#[no_mangle]
+pub extern "C" fn buffer_paginate_arena(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    height: f32,
+    arena: *mut FfiArena,
+    out_len: *mut usize,
+) -> *const FfiPageRange {
+    let buffer = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    let arena = unsafe { &mut *arena };
+    let pages: Vec<FfiPageRange> = buffer
+        .paginate(font_system, height)
+        .into_iter()
+        .map(|page| FfiPageRange {
+            start_line: page.start.line,
+            start_layout: page.start.layout,
+            end_line: page.end.line,
+            end_layout: page.end.layout,
+            y: page.y,
+            height: page.height,
+        })
+        .collect();
+    unsafe { *out_len = pages.len() };
+    arena.alloc_slice(&pages)
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct FfiGlyphBatch {
+    pub font_id: ID,
+    pub font_size: f32,
+    pub has_color: bool,
+    pub color: Color,
+    pub fill_id: u64,
+    pub glyphs: *const BatchGlyph,
+    pub glyphs_len: usize,
+}
+
+/// Glyphs of each batch are in reading order, or sorted by cache key if `sort_by_cache_key` is set
+#[no_mangle]
+pub extern "C" fn buffer_glyph_batches(
+    ctx: *const Buffer,
+    sort_by_cache_key: bool,
+    callback: extern "C" fn(*const FfiGlyphBatch),
+) {
+    let buffer = unsafe { &*ctx };
+    for mut batch in buffer.glyph_batches() {
+        if sort_by_cache_key {
+            batch.sort_by_cache_key();
+        }
+        let ffi_batch = FfiGlyphBatch {
+            font_id: batch.font_id,
+            font_size: batch.font_size,
+            has_color: batch.color_opt.is_some(),
+            color: batch.color_opt.unwrap_or(Color(0)),
+            fill_id: batch.fill_id,
+            glyphs: batch.glyphs.as_ptr(),
+            glyphs_len: batch.glyphs.len(),
+        };
+        callback(&ffi_batch);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn buffer_draw(ctx: *mut Buffer, font_system: *mut FontSystem, swash_cache: *mut SwashCache, color: Color, callback: extern "C" fn(i32, i32, u32, u32, Color)) {
     let buffer = unsafe { &mut *ctx };
@@ -479,11 +2881,255 @@ pub extern "C" fn buffer_draw(ctx: *mut Buffer, font_system: *mut FontSystem, sw
     });
 }
 
+/// Like `buffer_draw`, skipping glyphs outside of `clip` and cutting rectangles at its edges
+#[no_mangle]
+pub extern "C" fn buffer_draw_clipped(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    swash_cache: *mut SwashCache,
+    color: Color,
+    clip: ClipRect,
+    callback: extern "C" fn(i32, i32, u32, u32, Color),
+) {
+    let buffer = unsafe { &mut *ctx };
+    let swash_cache = unsafe { &mut *swash_cache };
+    let font_system = unsafe { &mut *font_system };
+    buffer.draw_clipped(
+        font_system,
+        swash_cache,
+        color,
+        clip,
+        |x, y, w, h, color| {
+            callback(x, y, w, h, color);
+        },
+    );
+}
+
+/// A rectangle drawn by `buffer_draw_arena`
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+    pub color: Color,
+}
+
+/// Like `buffer_draw`, returning all rectangles at once from `arena` and writing their count to
+/// `out_len`
+#[no_mangle]
+pub extern "C" fn buffer_draw_arena(
+    ctx: *mut Buffer,
+    font_system: *mut FontSystem,
+    swash_cache: *mut SwashCache,
+    arena: *mut FfiArena,
+    color: Color,
+    out_len: *mut usize,
+) -> *const FfiRect {
+    let buffer = unsafe { &mut *ctx };
+    let swash_cache = unsafe { &mut *swash_cache };
+    let font_system = unsafe { &mut *font_system };
+    let arena = unsafe { &mut *arena };
+    let mut rects = Vec::new();
+    buffer.draw(font_system, swash_cache, color, |x, y, w, h, color| {
+        rects.push(FfiRect { x, y, w, h, color });
+    });
+    unsafe { *out_len = rects.len() };
+    arena.alloc_slice(&rects)
+}
+
 
 
 // ---------------------------------------------------------
 
 
+// Editor ---------------------------------------------------------
+
+/// Keyboard modifiers held during a mouse action. Shift extends the selection, the others are
+/// left to platform conventions of the host and currently ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct FfiModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+/// Create an editor owning a new buffer, see `editor_buffer`
+#[no_mangle]
+pub extern "C" fn editor_new(
+    font_system: *mut FontSystem,
+    metrics: Metrics,
+) -> *mut Editor<'static> {
+    let font_system = unsafe { &mut *font_system };
+    let editor = Editor::new(Buffer::new(font_system, metrics));
+    let ctx: Box<_> = Box::new(editor);
+    Box::into_raw(ctx)
+}
+
+#[no_mangle]
+pub extern "C" fn editor_free(ctx: *mut Editor<'static>) {
+    if ctx.is_null() {
+        return;
+    }
+    unsafe {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// Get the buffer of the editor for use with the buffer functions, valid until `editor_free`
+#[no_mangle]
+pub extern "C" fn editor_buffer(ctx: *mut Editor<'static>) -> *mut Buffer {
+    let editor = unsafe { &mut *ctx };
+    editor.with_buffer_mut(|buffer| buffer as *mut Buffer)
+}
+
+#[no_mangle]
+pub extern "C" fn editor_get_cursor(ctx: *const Editor<'static>) -> FfiCursor {
+    let editor = unsafe { &*ctx };
+    editor.cursor().into()
+}
+
+/// Get the start and end of the selection, returns false if nothing is selected
+#[no_mangle]
+pub extern "C" fn editor_get_selection_bounds(
+    ctx: *const Editor<'static>,
+    out_start: *mut FfiCursor,
+    out_end: *mut FfiCursor,
+) -> bool {
+    let editor = unsafe { &*ctx };
+    match editor.selection_bounds() {
+        Some((start, end)) => {
+            unsafe {
+                *out_start = start.into();
+                *out_end = end.into();
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Shape lines until scroll, after adjusting scroll if the cursor moved
+#[no_mangle]
+pub extern "C" fn editor_shape_as_needed(
+    ctx: *mut Editor<'static>,
+    font_system: *mut FontSystem,
+    prune: bool,
+) {
+    let editor = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    editor.shape_as_needed(font_system, prune);
+}
+
+// Perform a click action, or with shift extend the selection from its anchor to the position
+// in the selection mode of the click
+fn ffi_editor_click(
+    editor: &mut Editor<'static>,
+    font_system: &mut FontSystem,
+    action: Action,
+    modifiers: FfiModifiers,
+) {
+    let (x, y) = match action {
+        Action::Click { x, y } | Action::DoubleClick { x, y } | Action::TripleClick { x, y } => {
+            (x, y)
+        }
+        _ => return,
+    };
+    if !modifiers.shift {
+        editor.action(font_system, action);
+        return;
+    }
+
+    let anchor = match editor.selection() {
+        Selection::None => editor.cursor(),
+        Selection::Normal(anchor) | Selection::Line(anchor) | Selection::Word(anchor) => anchor,
+    };
+    editor.set_selection(match action {
+        Action::DoubleClick { .. } => Selection::Word(anchor),
+        Action::TripleClick { .. } => Selection::Line(anchor),
+        _ => Selection::Normal(anchor),
+    });
+    editor.action(font_system, Action::Drag { x, y });
+}
+
+/// Click at a position in pixels relative to the buffer, moving the cursor or with shift
+/// extending the selection
+#[no_mangle]
+pub extern "C" fn editor_click(
+    ctx: *mut Editor<'static>,
+    font_system: *mut FontSystem,
+    x: i32,
+    y: i32,
+    modifiers: FfiModifiers,
+) {
+    let editor = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    ffi_editor_click(editor, font_system, Action::Click { x, y }, modifiers);
+}
+
+/// Double click at a position in pixels, selecting a word or with shift extending the selection
+/// by words
+#[no_mangle]
+pub extern "C" fn editor_double_click(
+    ctx: *mut Editor<'static>,
+    font_system: *mut FontSystem,
+    x: i32,
+    y: i32,
+    modifiers: FfiModifiers,
+) {
+    let editor = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    ffi_editor_click(editor, font_system, Action::DoubleClick { x, y }, modifiers);
+}
+
+/// Triple click at a position in pixels, selecting a line or with shift extending the selection
+/// by lines
+#[no_mangle]
+pub extern "C" fn editor_triple_click(
+    ctx: *mut Editor<'static>,
+    font_system: *mut FontSystem,
+    x: i32,
+    y: i32,
+    modifiers: FfiModifiers,
+) {
+    let editor = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    ffi_editor_click(editor, font_system, Action::TripleClick { x, y }, modifiers);
+}
+
+/// Drag to a position in pixels while the button is held, extending the selection in the mode of
+/// the click that started it
+#[no_mangle]
+pub extern "C" fn editor_drag(
+    ctx: *mut Editor<'static>,
+    font_system: *mut FontSystem,
+    x: i32,
+    y: i32,
+    _modifiers: FfiModifiers,
+) {
+    let editor = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    editor.action(font_system, Action::Drag { x, y });
+}
+
+/// Scroll by a number of layout lines, negative values scroll up
+#[no_mangle]
+pub extern "C" fn editor_scroll(
+    ctx: *mut Editor<'static>,
+    font_system: *mut FontSystem,
+    lines: i32,
+    _modifiers: FfiModifiers,
+) {
+    let editor = unsafe { &mut *ctx };
+    let font_system = unsafe { &mut *font_system };
+    editor.action(font_system, Action::Scroll { lines });
+}
+
+// ---------------------------------------------------------
+
 // LayoutRun ---------------------------------------------------------
 
 #[no_mangle]
@@ -522,6 +3168,12 @@ pub extern "C" fn layout_get_glyphs_len(ctx: *const LayoutRun) -> usize {
     run.glyphs.len()
 }
 
+#[no_mangle]
+pub extern "C" fn layout_get_line_x(ctx: *const LayoutRun) -> f32 {
+    let run = unsafe { &*ctx };
+    run.line_x
+}
+
 #[no_mangle]
 pub extern "C" fn layout_get_line_y(ctx: *const LayoutRun) -> f32 {
     let run = unsafe { &*ctx };
@@ -540,4 +3192,494 @@ pub extern "C" fn layout_get_line_w(ctx: *const LayoutRun) -> f32 {
     run.line_w
 }
 
+#[no_mangle]
+pub extern "C" fn layout_get_column(ctx: *const LayoutRun) -> usize {
+    let run = unsafe { &*ctx };
+    run.column
+}
+
+#[no_mangle]
+pub extern "C" fn layout_get_ruby_len(ctx: *const LayoutRun) -> usize {
+    let run = unsafe { &*ctx };
+    run.ruby.len()
+}
+
+/// Get the glyphs of the ruby text `ruby_i` of the run and write their count to `out_len`, and
+/// the index of the annotation in its line to `out_ruby_i`
+#[no_mangle]
+pub extern "C" fn layout_get_ruby_glyphs(
+    ctx: *const LayoutRun,
+    ruby_i: usize,
+    out_ruby_i: *mut usize,
+    out_len: *mut usize,
+) -> *const LayoutGlyph {
+    let run = unsafe { &*ctx };
+    let ruby = &run.ruby[ruby_i];
+    unsafe {
+        *out_ruby_i = ruby.ruby_i;
+        *out_len = ruby.glyphs.len();
+    }
+    ruby.glyphs.as_ptr()
+}
+
+/// Get the glyphs of the list marker of the run and write their count to `out_len`
+#[no_mangle]
+pub extern "C" fn layout_get_marker_glyphs(ctx: *const LayoutRun, out_len: *mut usize) -> *const LayoutGlyph {
+    let run = unsafe { &*ctx };
+    unsafe { *out_len = run.marker.len() };
+    run.marker.as_ptr()
+}
+
+/// Get the selection box of `start_cursor` to `end_cursor` within the run, returns false if the
+/// range does not intersect it
+#[no_mangle]
+pub extern "C" fn layout_run_highlight(
+    ctx: *const LayoutRun,
+    start_cursor: FfiCursor,
+    end_cursor: FfiCursor,
+    out_x: *mut f32,
+    out_w: *mut f32,
+) -> bool {
+    let run = unsafe { &*ctx };
+    match run.highlight(start_cursor.into(), end_cursor.into()) {
+        Some((x, w)) => {
+            unsafe {
+                *out_x = x;
+                *out_w = w;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Get the caret positions of the run from left to right, with byte offsets into the run's text
+#[no_mangle]
+pub extern "C" fn layout_run_caret_stops(
+    ctx: *const LayoutRun,
+    callback: extern "C" fn(*const CaretStop, usize),
+) {
+    let run = unsafe { &*ctx };
+    let stops = run.caret_stops();
+    callback(stops.as_ptr(), stops.len());
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct FfiDecorationSpan {
+    pub kind: FfiDecorationKind,
+    pub style: FfiDecorationStyle,
+    pub has_color: bool,
+    pub color: Color,
+    pub x: f32,
+    pub w: f32,
+    /// Top of the line relative to the baseline, positive below it
+    pub y: f32,
+    pub thickness: f32,
+}
+
+/// Get the decoration lines of the run, for hosts drawing them instead of using `buffer_draw`
+#[no_mangle]
+pub extern "C" fn layout_run_decorations(
+    ctx: *const LayoutRun,
+    font_system: *mut FontSystem,
+    callback: extern "C" fn(*const FfiDecorationSpan, usize),
+) {
+    let run = unsafe { &*ctx };
+    let font_system = unsafe { &mut *font_system };
+    let spans: Vec<FfiDecorationSpan> = run
+        .decorations(font_system)
+        .into_iter()
+        .map(|span| FfiDecorationSpan {
+            kind: span.kind.into(),
+            style: span.style.into(),
+            has_color: span.color_opt.is_some(),
+            color: span.color_opt.unwrap_or(Color(0)),
+            x: span.x,
+            w: span.w,
+            y: span.y,
+            thickness: span.thickness,
+        })
+        .collect();
+    callback(spans.as_ptr(), spans.len());
+}
+
+/// Get the position of the top of the underline of the run relative to the baseline, positive
+/// below it, and its thickness, returning false if no glyph of the run is underlined
+#[no_mangle]
+pub extern "C" fn layout_run_underline_metrics(
+    ctx: *const LayoutRun,
+    font_system: *mut FontSystem,
+    out_y: *mut f32,
+    out_thickness: *mut f32,
+) -> bool {
+    let run = unsafe { &*ctx };
+    let font_system = unsafe { &mut *font_system };
+    match run.underline_metrics(font_system) {
+        Some((y, thickness)) => {
+            unsafe {
+                *out_y = y;
+                *out_thickness = thickness;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Get the position of the top of the overline of the run relative to the baseline, negative above
+/// it, and its thickness, returning false if no glyph of the run is overlined
+#[no_mangle]
+pub extern "C" fn layout_run_overline_metrics(
+    ctx: *const LayoutRun,
+    font_system: *mut FontSystem,
+    out_y: *mut f32,
+    out_thickness: *mut f32,
+) -> bool {
+    let run = unsafe { &*ctx };
+    let font_system = unsafe { &mut *font_system };
+    match run.overline_metrics(font_system) {
+        Some((y, thickness)) => {
+            unsafe {
+                *out_y = y;
+                *out_thickness = thickness;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Get the position of the top of the strikethrough of the run relative to the baseline, negative
+/// above it, and its thickness, returning false if no glyph of the run is struck through
+#[no_mangle]
+pub extern "C" fn layout_run_strikethrough_metrics(
+    ctx: *const LayoutRun,
+    font_system: *mut FontSystem,
+    out_y: *mut f32,
+    out_thickness: *mut f32,
+) -> bool {
+    let run = unsafe { &*ctx };
+    let font_system = unsafe { &mut *font_system };
+    match run.strikethrough_metrics(font_system) {
+        Some((y, thickness)) => {
+            unsafe {
+                *out_y = y;
+                *out_thickness = thickness;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct FfiVisualOrder {
+    /// Byte offset of the cluster of each glyph from left to right
+    pub visual_to_logical: *const usize,
+    pub visual_to_logical_len: usize,
+    /// Byte offset of the first entry of `logical_to_visual`
+    pub start: usize,
+    /// Index in `visual_to_logical` of the glyph showing each byte, starting at `start`
+    pub logical_to_visual: *const usize,
+    pub logical_to_visual_len: usize,
+}
+
+/// Get the mapping between glyphs from left to right and byte offsets into the run's text
+#[no_mangle]
+pub extern "C" fn layout_run_visual_order(
+    ctx: *const LayoutRun,
+    callback: extern "C" fn(*const FfiVisualOrder),
+) {
+    let run = unsafe { &*ctx };
+    let visual_order = run.visual_order();
+    let ffi_visual_order = FfiVisualOrder {
+        visual_to_logical: visual_order.visual_to_logical.as_ptr(),
+        visual_to_logical_len: visual_order.visual_to_logical.len(),
+        start: visual_order.start,
+        logical_to_visual: visual_order.logical_to_visual.as_ptr(),
+        logical_to_visual_len: visual_order.logical_to_visual.len(),
+    };
+    callback(&ffi_visual_order);
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct FfiScriptRun {
+    /// ISO 15924 tag of the script, such as `Latn`, as a big-endian integer
+    pub script_tag: u32,
+    pub rtl: bool,
+    pub glyph_start: usize,
+    pub glyph_end: usize,
+}
+
+#[no_mangle]
+pub extern "C" fn layout_run_script_runs(
+    ctx: *const LayoutRun,
+    callback: extern "C" fn(*const FfiScriptRun),
+) {
+    let run = unsafe { &*ctx };
+    for script_run in run.script_runs() {
+        let ffi_script_run = FfiScriptRun {
+            script_tag: script_run.script.as_iso15924_tag(),
+            rtl: script_run.rtl,
+            glyph_start: script_run.glyphs.start,
+            glyph_end: script_run.glyphs.end,
+        };
+        callback(&ffi_script_run);
+    }
+}
+
+/// Get the ISO 15924 tag of a glyph's script, such as `Latn`, as a big-endian integer
+#[no_mangle]
+pub extern "C" fn layout_glyph_script_tag(glyph: *const LayoutGlyph) -> u32 {
+    let glyph = unsafe { &*glyph };
+    glyph.script_tag
+}
+
+// ---------------------------------------------------------
+
+// LineEnding ---------------------------------------------------------
+/// Detect the line endings used by UTF-16 text
+#[no_mangle]
+pub extern "C" fn line_ending_detect(text: *const u16, text_len: usize) -> FfiDetectedLineEnding {
+    let text = unsafe { std::slice::from_raw_parts(text, text_len) };
+    let text = String::from_utf16_lossy(text);
+    LineEnding::detect(&text).into()
+}
+
+// ---------------------------------------------------------
+
+// BidiParagraphs ---------------------------------------------------------
+/// A paragraph of text, as split by a [`Buffer`]
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiBidiParagraph {
+    /// Start of the paragraph, in UTF-16 code units
+    pub start: usize,
+    /// End of the paragraph, excluding the newline that ends it, in UTF-16 code units
+    pub end: usize,
+    /// The base direction of the paragraph is right-to-left
+    pub rtl: bool,
+}
+
+/// Split UTF-16 text into paragraphs the way a [`Buffer`] splits it into lines
+#[no_mangle]
+pub extern "C" fn bidi_paragraphs(
+    text: *const u16,
+    text_len: usize,
+    callback: extern "C" fn(*const FfiBidiParagraph),
+) {
+    let text = unsafe { std::slice::from_raw_parts(text, text_len) };
+    let text = String::from_utf16_lossy(text);
+
+    // Convert byte offsets to UTF-16 offsets, moving forward as paragraphs are found
+    let mut byte_offset = 0;
+    let mut utf16_offset = 0;
+    let mut to_utf16 = |byte: usize| {
+        utf16_offset += text[byte_offset..byte].chars().map(char::len_utf16).sum::<usize>();
+        byte_offset = byte;
+        utf16_offset
+    };
+
+    let mut paragraphs = BidiParagraphs::new(&text);
+    while let Some((range, rtl)) = paragraphs.next_range() {
+        let ffi_paragraph = FfiBidiParagraph {
+            start: to_utf16(range.start),
+            end: to_utf16(range.end),
+            rtl,
+        };
+        callback(&ffi_paragraph);
+    }
+}
+
+// ---------------------------------------------------------
+
+// LayoutText ---------------------------------------------------------
+/// A glyph positioned by `layout_text_glyphs`
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FfiPositionedGlyph {
+    /// Cache key to rasterize the glyph, such as with `swashcache_get_image_uncached`
+    pub cache_key: CacheKey,
+    /// X position of the glyph origin, in pixels
+    pub x: i32,
+    /// Y position of the glyph origin on the baseline, in pixels
+    pub y: i32,
+    /// Advance width of the glyph
+    pub w: f32,
+    /// Start of the text of the glyph, in UTF-16 code units
+    pub start: usize,
+    /// End of the text of the glyph, in UTF-16 code units
+    pub end: usize,
+    /// Index of the paragraph of the glyph
+    pub line_i: usize,
+    /// Y offset to baseline of line
+    pub line_y: f32,
+    pub has_color: bool,
+    pub color: Color,
+    pub metadata: usize,
+    pub fill_id: u64,
+    pub has_shadow: bool,
+    pub shadow_color: Color,
+    pub shadow_x: f32,
+    pub shadow_y: f32,
+    pub shadow_blur: f32,
+}
+
+fn ffi_layout_text(
+    font_system: &mut FontSystem,
+    text: &[u16],
+    attrs: &AttrsOwned,
+    metrics: Metrics,
+    max_width: Option<f32>,
+) -> Vec<FfiPositionedGlyph> {
+    let text = String::from_utf16_lossy(text);
+    let glyphs = crate::layout_text(font_system, &text, attrs.as_attrs(), metrics, max_width);
+
+    // UTF-16 offset of each byte offset, as glyphs of RTL text are in visual order
+    let mut utf16_offsets = vec![0; text.len() + 1];
+    let mut utf16_offset = 0;
+    for (byte, c) in text.char_indices() {
+        utf16_offsets[byte] = utf16_offset;
+        utf16_offset += c.len_utf16();
+    }
+    utf16_offsets[text.len()] = utf16_offset;
+    let to_utf16 = |byte: usize| utf16_offsets[byte];
+    glyphs
+        .iter()
+        .map(|positioned| {
+            let physical_glyph = positioned.physical();
+            FfiPositionedGlyph {
+                cache_key: physical_glyph.cache_key,
+                x: physical_glyph.x,
+                y: physical_glyph.y,
+                w: positioned.glyph.w,
+                start: to_utf16(positioned.glyph.start),
+                end: to_utf16(positioned.glyph.end),
+                line_i: positioned.line_i,
+                line_y: positioned.line_y,
+                has_color: positioned.glyph.color_opt.is_some(),
+                color: positioned.glyph.color_opt.unwrap_or(Color(0)),
+                metadata: positioned.glyph.metadata,
+                fill_id: positioned.glyph.fill_id,
+                has_shadow: positioned.glyph.has_shadow,
+                shadow_color: positioned.glyph.shadow_color,
+                shadow_x: positioned.glyph.shadow_x,
+                shadow_y: positioned.glyph.shadow_y,
+                shadow_blur: positioned.glyph.shadow_blur,
+            }
+        })
+        .collect()
+}
+
+/// Shape, wrap, and position UTF-16 text in one call, passing all glyphs to `callback` at once,
+/// `has_max_width` false disables wrapping
+#[no_mangle]
+pub extern "C" fn layout_text_glyphs(
+    font_system: *mut FontSystem,
+    text: *const u16,
+    text_len: usize,
+    attrs: *const AttrsOwned,
+    metrics: Metrics,
+    has_max_width: bool,
+    max_width: f32,
+    callback: extern "C" fn(*const FfiPositionedGlyph, usize),
+) {
+    let font_system = unsafe { &mut *font_system };
+    let attrs = unsafe { &*attrs };
+    let text = unsafe { std::slice::from_raw_parts(text, text_len) };
+
+    let max_width = has_max_width.then_some(max_width);
+    let ffi_glyphs = ffi_layout_text(font_system, text, attrs, metrics, max_width);
+    callback(ffi_glyphs.as_ptr(), ffi_glyphs.len());
+}
+
+/// Like `layout_text_glyphs`, returning the glyphs from `arena` and writing their count to
+/// `out_len`
+#[no_mangle]
+pub extern "C" fn layout_text_glyphs_arena(
+    font_system: *mut FontSystem,
+    arena: *mut FfiArena,
+    text: *const u16,
+    text_len: usize,
+    attrs: *const AttrsOwned,
+    metrics: Metrics,
+    has_max_width: bool,
+    max_width: f32,
+    out_len: *mut usize,
+) -> *const FfiPositionedGlyph {
+    let font_system = unsafe { &mut *font_system };
+    let arena = unsafe { &mut *arena };
+    let attrs = unsafe { &*attrs };
+    let text = unsafe { std::slice::from_raw_parts(text, text_len) };
+
+    let max_width = has_max_width.then_some(max_width);
+    let ffi_glyphs = ffi_layout_text(font_system, text, attrs, metrics, max_width);
+    unsafe { *out_len = ffi_glyphs.len() };
+    arena.alloc_slice(&ffi_glyphs)
+}
+
+// ---------------------------------------------------------
+
+// Color ---------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[no_mangle]
+pub extern "C" fn color_from_hex(text: *const u16, len: usize, out_color: *mut Color) -> bool {
+    let text = unsafe { std::slice::from_raw_parts(text, len) };
+    let color = String::from_utf16(text)
+        .ok()
+        .and_then(|text| Color::from_hex(&text));
+    match color {
+        Some(color) => {
+            unsafe {
+                *out_color = color;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn color_hsva(h: f32, s: f32, v: f32, a: u8) -> Color {
+    Color::hsva(h, s, v, a)
+}
+
+#[no_mangle]
+pub extern "C" fn color_hsla(h: f32, s: f32, l: f32, a: u8) -> Color {
+    Color::hsla(h, s, l, a)
+}
+
+#[no_mangle]
+pub extern "C" fn color_premultiplied(color: Color) -> Color {
+    color.premultiplied()
+}
+
+#[no_mangle]
+pub extern "C" fn color_unpremultiplied(color: Color) -> Color {
+    color.unpremultiplied()
+}
+
+#[no_mangle]
+pub extern "C" fn color_to_linear(color: Color) -> LinearColor {
+    let [r, g, b, a] = color.to_linear();
+    LinearColor { r, g, b, a }
+}
+
+#[no_mangle]
+pub extern "C" fn color_from_linear(linear: LinearColor) -> Color {
+    Color::from_linear([linear.r, linear.g, linear.b, linear.a])
+}
+
 // ---------------------------------------------------------
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use unicode_script::{Script, UnicodeScript};
+
+/// Strictness of the rules for breaking lines of Chinese and Japanese text (kinsoku shori), like
+/// the CSS `line-break` property, see [`crate::Buffer::set_line_break_strictness`]
+///
+/// Lines never start with closing punctuation like `、。」）` or end with opening punctuation
+/// like `「（`, whatever the strictness. Looser rules allow breaks before more characters that
+/// follow kana or ideographs.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineBreakStrictness {
+    /// Also allow breaks before iteration marks like `々ゝ`, CJK hyphens like `〜゠`, and
+    /// ellipses, for narrow lines like those of newspapers
+    Loose,
+    /// Also allow breaks before small kana like `っゃ` and the prolonged sound mark `ー`
+    Normal,
+    /// Follow the default rules of Unicode line breaking, where lines never start with small
+    /// kana or the prolonged sound mark
+    #[default]
+    Strict,
+}
+
+impl LineBreakStrictness {
+    /// Check if a break is allowed before `c` when it follows kana or an ideograph, even though
+    /// Unicode line breaking does not allow it
+    fn allows_break_before(self, c: char) -> bool {
+        match self {
+            Self::Strict => false,
+            Self::Normal => is_conditional_japanese_starter(c),
+            Self::Loose => {
+                is_conditional_japanese_starter(c)
+                    || matches!(
+                        c,
+                        // Iteration marks
+                        '\u{3005}' | '\u{303B}' | '\u{309D}' | '\u{309E}' | '\u{30FD}' | '\u{30FE}'
+                        // Hyphens
+                        | '\u{2010}' | '\u{2013}' | '\u{301C}' | '\u{30A0}'
+                        // Inseparable characters
+                        | '\u{2025}' | '\u{2026}'
+                    )
+            }
+        }
+    }
+}
+
+/// Check if a character has the conditional Japanese starter (CJ) line break class, like small
+/// kana and the prolonged sound mark
+fn is_conditional_japanese_starter(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3041}' | '\u{3043}' | '\u{3045}' | '\u{3047}' | '\u{3049}' | '\u{3063}' | '\u{3083}'
+            | '\u{3085}' | '\u{3087}' | '\u{308E}' | '\u{3095}' | '\u{3096}' | '\u{30A1}'
+            | '\u{30A3}' | '\u{30A5}' | '\u{30A7}' | '\u{30A9}' | '\u{30C3}' | '\u{30E3}'
+            | '\u{30E5}' | '\u{30E7}' | '\u{30EE}' | '\u{30F5}' | '\u{30F6}' | '\u{30FC}'
+            | '\u{31F0}'..='\u{31FF}'
+            | '\u{FF67}'..='\u{FF70}'
+    )
+}
+
+/// Finds the places where lines may break, see [`crate::FontSystem::set_line_breaker`]
+///
+/// The text passed to a line breaker is a span of a line with a single direction. Lines wrap
+/// only at the returned offsets, and between words found by line break dictionaries.
+pub trait LineBreaker: Send + Sync {
+    /// Get the byte offsets in `text` after which a line may break, in increasing order
+    ///
+    /// Offsets must be on character boundaries. The end of the text is a break whether it is
+    /// returned or not, and whitespace before a break hangs at the end of the line.
+    fn line_breaks(&self, text: &str, strictness: LineBreakStrictness) -> Vec<usize>;
+}
+
+/// The default [`LineBreaker`], following Unicode line breaking (UAX #14) with the rules of
+/// [`LineBreakStrictness`] for Chinese and Japanese text
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct UnicodeLineBreaker;
+
+impl LineBreaker for UnicodeLineBreaker {
+    fn line_breaks(&self, text: &str, strictness: LineBreakStrictness) -> Vec<usize> {
+        unicode_line_breaks(text, strictness)
+    }
+}
+
+/// Get the offsets in `text` after which a line may break with `line_breaker`, or with
+/// [`UnicodeLineBreaker`] if there is none
+///
+/// Offsets that are out of order or not on character boundaries are skipped, and the end of the
+/// text is always included.
+pub(crate) fn line_breaks(
+    line_breaker_opt: Option<&Arc<dyn LineBreaker>>,
+    text: &str,
+    strictness: LineBreakStrictness,
+) -> Vec<usize> {
+    let Some(line_breaker) = line_breaker_opt else {
+        return unicode_line_breaks(text, strictness);
+    };
+
+    let mut breaks = Vec::new();
+    for offset in line_breaker.line_breaks(text, strictness) {
+        if offset > breaks.last().copied().unwrap_or(0)
+            && offset <= text.len()
+            && text.is_char_boundary(offset)
+        {
+            breaks.push(offset);
+        }
+    }
+    if breaks.last() != Some(&text.len()) {
+        breaks.push(text.len());
+    }
+    breaks
+}
+
+/// Get the offsets in `text` after which a line may break, with `strictness`
+fn unicode_line_breaks(text: &str, strictness: LineBreakStrictness) -> Vec<usize> {
+    let mut breaks: Vec<usize> = unicode_linebreak::linebreaks(text)
+        .map(|(offset, _)| offset)
+        .collect();
+    if strictness == LineBreakStrictness::Strict {
+        return breaks;
+    }
+
+    let is_cjk = |c: char| {
+        matches!(
+            c.script(),
+            Script::Han | Script::Hiragana | Script::Katakana
+        )
+    };
+    let mut prev_opt = None;
+    for (i, c) in text.char_indices() {
+        if prev_opt.map_or(false, is_cjk) && strictness.allows_break_before(c) {
+            breaks.push(i);
+        }
+        prev_opt = Some(c);
+    }
+    breaks.sort_unstable();
+    breaks.dedup();
+    breaks
+}
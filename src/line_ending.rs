@@ -12,6 +12,16 @@ pub enum LineEnding {
     Cr,
     /// Use `\n\r` for line ending (some legacy systems)
     LfCr,
+    /// Use U+0085 NEXT LINE for line ending (EBCDIC systems)
+    Nel,
+    /// Use U+2029 PARAGRAPH SEPARATOR for line ending
+    ParagraphSeparator,
+    /// Use U+001C FILE SEPARATOR for line ending
+    FileSeparator,
+    /// Use U+001D GROUP SEPARATOR for line ending
+    GroupSeparator,
+    /// Use U+001E RECORD SEPARATOR for line ending
+    RecordSeparator,
     /// No line ending
     None,
 }
@@ -24,9 +34,71 @@ impl LineEnding {
             Self::CrLf => "\r\n",
             Self::Cr => "\r",
             Self::LfCr => "\n\r",
+            Self::Nel => "\u{85}",
+            Self::ParagraphSeparator => "\u{2029}",
+            Self::FileSeparator => "\u{1C}",
+            Self::GroupSeparator => "\u{1D}",
+            Self::RecordSeparator => "\u{1E}",
             Self::None => "",
         }
     }
+
+    /// Detect the line endings used by a document
+    pub fn detect(text: &str) -> DetectedLineEnding {
+        // Counts of Lf, CrLf, Cr, and LfCr
+        let mut counts = [0usize; 4];
+        for (_range, ending) in LineIter::new(text) {
+            match ending {
+                Self::Lf => counts[0] += 1,
+                Self::CrLf => counts[1] += 1,
+                Self::Cr => counts[2] += 1,
+                Self::LfCr => counts[3] += 1,
+                // Only `\r` and `\n` are found by `LineIter`
+                Self::Nel
+                | Self::ParagraphSeparator
+                | Self::FileSeparator
+                | Self::GroupSeparator
+                | Self::RecordSeparator
+                | Self::None => {}
+            }
+        }
+
+        let endings = [Self::Lf, Self::CrLf, Self::Cr, Self::LfCr];
+        let used = counts.iter().filter(|count| **count > 0).count();
+        // The first ending wins ties, preferring Lf
+        let mut most_i = 0;
+        for (i, count) in counts.iter().enumerate() {
+            if *count > counts[most_i] {
+                most_i = i;
+            }
+        }
+        match used {
+            0 => DetectedLineEnding::None,
+            1 => DetectedLineEnding::Uniform(endings[most_i]),
+            _ => DetectedLineEnding::Mixed(endings[most_i]),
+        }
+    }
+}
+
+/// Line endings used by a document, see [`LineEnding::detect`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DetectedLineEnding {
+    /// The document has a single line
+    None,
+    /// All lines end with the same line ending
+    Uniform(LineEnding),
+    /// Lines end with different line endings, the most common one is included
+    Mixed(LineEnding),
+}
+
+impl DetectedLineEnding {
+    /// Get the line ending to use for new lines, the default if the document has a single line
+    pub fn preferred(&self) -> LineEnding {
+        match self {
+            Self::None => LineEnding::default(),
+            Self::Uniform(ending) | Self::Mixed(ending) => *ending,
+        }
+    }
 }
 
 /// Iterator over lines terminated by [`LineEnding`]
@@ -96,3 +168,17 @@ fn test_line_iter() {
     assert_eq!(iter.next(), Some((12..16, LineEnding::LfCr)));
     assert_eq!(iter.next(), Some((18..22, LineEnding::None)));
 }
+
+#[test]
+fn test_line_ending_detect() {
+    assert_eq!(LineEnding::detect("one line"), DetectedLineEnding::None);
+    assert_eq!(
+        LineEnding::detect("a\r\nb\r\n"),
+        DetectedLineEnding::Uniform(LineEnding::CrLf)
+    );
+    assert_eq!(
+        LineEnding::detect("a\r\nb\nc\r\n"),
+        DetectedLineEnding::Mixed(LineEnding::CrLf)
+    );
+    assert_eq!(LineEnding::detect("a\r\nb\nc").preferred(), LineEnding::Lf);
+}
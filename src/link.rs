@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A rectangle covering part of a link in one layout run, see [`LinkHit`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkRect {
+    /// X position of the left of the rectangle, including [`crate::LayoutRun::line_x`]
+    pub x: f32,
+    /// Y position of the top of the rectangle, which is [`crate::LayoutRun::line_top`]
+    pub y: f32,
+    /// Width of the rectangle
+    pub w: f32,
+    /// Height of the rectangle, which is the line height
+    pub h: f32,
+}
+
+/// A link under a point, see [`crate::Buffer::hit_link`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkHit<'a> {
+    /// Target of the link from [`crate::Attrs::link`]
+    pub link: &'a str,
+    /// Index of the line containing the link
+    pub line_i: usize,
+    /// Byte range of the text of the link in its line
+    pub range: Range<usize>,
+    /// Rectangles covering the link in every layout run it is part of, for hover highlighting
+    pub rects: Vec<LinkRect>,
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+/// Marker of a list item, like a bullet or an ordinal, laid out before the first line of its
+/// paragraph, see [`crate::BufferLine::set_marker`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListMarker {
+    /// Text of the marker, shaped with the attributes at the start of the paragraph
+    pub text: String,
+    /// Space in pixels between the end of the marker and the start of the first line
+    pub gap: f32,
+}
+
+impl ListMarker {
+    /// Create a marker with the given text and no gap
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            gap: 0.0,
+        }
+    }
+
+    /// Set [`Self::gap`]
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+}
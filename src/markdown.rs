@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use fontdb::Family;
+
+use crate::{Attrs, Color, ParagraphAttrs, RichText, Style, Weight};
+
+/// Attributes of the elements of Markdown, see [`RichText::from_markdown`]
+#[derive(Clone, Copy, Debug)]
+pub struct MarkdownStyle<'a> {
+    /// Font sizes in pixels of headings from level 1 to 6
+    pub heading_font_sizes: [f32; 6],
+    /// Family of code spans
+    pub code_family: Family<'a>,
+    /// Color of links, or None to keep the color of the text
+    pub link_color: Option<Color>,
+    /// Underline links
+    pub link_underline: bool,
+    /// Space below every paragraph and heading, in pixels
+    pub paragraph_spacing: f32,
+}
+
+impl<'a> MarkdownStyle<'a> {
+    /// Create a style for text with the given font size, with headings scaled from it
+    pub fn new(font_size: f32) -> Self {
+        Self {
+            heading_font_sizes: [2.0, 1.5, 1.25, 1.0, 0.875, 0.85].map(|scale| scale * font_size),
+            code_family: Family::Monospace,
+            link_color: None,
+            link_underline: true,
+            paragraph_spacing: font_size / 2.0,
+        }
+    }
+}
+
+impl<'a> RichText<'a> {
+    /// Parse a subset of Markdown into rich text, using `attrs` for plain text
+    ///
+    /// Supported are ATX headings (`# Title`), `**bold**` and `__bold__`, `*italic*` and
+    /// `_italic_`, `` `code` ``, `[links](target)` set with [`Attrs::link`], backslash escapes
+    /// and hard line breaks. Paragraphs are separated by blank lines and their lines are joined
+    /// with spaces. Inline elements do not span lines, and other syntax is kept as text.
+    pub fn from_markdown(markdown: &'a str, attrs: Attrs<'a>, style: &MarkdownStyle<'a>) -> Self {
+        let paragraph_attrs = ParagraphAttrs {
+            space_after: style.paragraph_spacing,
+            ..ParagraphAttrs::new()
+        };
+
+        let mut rich_text = RichText::new(attrs);
+        // Text between this line and the previous one, if they are in the same paragraph
+        let mut break_opt = None;
+        for line in markdown.lines() {
+            let line = line.trim_start();
+            if line.is_empty() {
+                break_opt = None;
+                continue;
+            }
+
+            if let Some((level, text)) = heading(line) {
+                let heading_attrs = attrs
+                    .weight(Weight::BOLD)
+                    .font_size(style.heading_font_sizes[level - 1]);
+                rich_text = rich_text.paragraph(paragraph_attrs);
+                rich_text = Inline::new(heading_attrs, style).parse(rich_text, text);
+                break_opt = None;
+                continue;
+            }
+
+            // Lines of a paragraph are joined with a space, or a line break after two trailing
+            // spaces or a backslash
+            match break_opt {
+                Some(line_break) => rich_text = rich_text.text(line_break),
+                None => rich_text = rich_text.paragraph(paragraph_attrs),
+            }
+            let text = line.trim_end();
+            let (text, line_break) = match text.strip_suffix('\\') {
+                Some(text) => (text, "\n"),
+                None if line.ends_with("  ") => (text, "\n"),
+                None => (text, " "),
+            };
+            rich_text = Inline::new(attrs, style).parse(rich_text, text);
+            break_opt = Some(line_break);
+        }
+        rich_text
+    }
+}
+
+/// Get the level and text of an ATX heading line
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with(' ')) {
+        return None;
+    }
+    // Remove the optional closing sequence
+    let text = text.trim();
+    let without_closing = text.trim_end_matches('#');
+    if without_closing.is_empty() || without_closing.ends_with(' ') {
+        Some((level, without_closing.trim_end()))
+    } else {
+        Some((level, text))
+    }
+}
+
+/// Get the length of the content of a code span starting after a run of `ticks` backticks
+fn code_span_len(text: &str, ticks: usize) -> Option<usize> {
+    let mut i = 0;
+    while let Some(offset) = text[i..].find('`') {
+        let start = i + offset;
+        let run = text[start..].len() - text[start..].trim_start_matches('`').len();
+        if run == ticks {
+            return Some(start);
+        }
+        i = start + run;
+    }
+    None
+}
+
+/// State of the inline elements of a line of Markdown
+struct Inline<'a, 'b> {
+    attrs: Attrs<'a>,
+    style: &'b MarkdownStyle<'a>,
+    bold: bool,
+    italic: bool,
+    link_opt: Option<&'a str>,
+}
+
+impl<'a, 'b> Inline<'a, 'b> {
+    fn new(attrs: Attrs<'a>, style: &'b MarkdownStyle<'a>) -> Self {
+        Self {
+            attrs,
+            style,
+            bold: false,
+            italic: false,
+            link_opt: None,
+        }
+    }
+
+    /// Attributes of text with the current emphasis and link
+    fn attrs(&self, code: bool) -> Attrs<'a> {
+        let mut attrs = self.attrs;
+        if self.bold {
+            attrs = attrs.weight(Weight::BOLD);
+        }
+        if self.italic {
+            attrs = attrs.style(Style::Italic);
+        }
+        if code {
+            attrs = attrs.family(self.style.code_family);
+        }
+        if let Some(link) = self.link_opt {
+            attrs = attrs.link(link);
+            if let Some(color) = self.style.link_color {
+                attrs = attrs.color(color);
+            }
+            if self.style.link_underline {
+                attrs = attrs.underline(true);
+            }
+        }
+        attrs
+    }
+
+    /// Append text with the current attributes, unless it is empty
+    fn push(&self, rich_text: RichText<'a>, text: &'a str, code: bool) -> RichText<'a> {
+        if text.is_empty() {
+            rich_text
+        } else {
+            rich_text.span(text, self.attrs(code))
+        }
+    }
+
+    /// Append the pieces of a line of Markdown
+    fn parse(mut self, mut rich_text: RichText<'a>, text: &'a str) -> RichText<'a> {
+        // Start of the text not appended yet
+        let mut start = 0;
+        // Position of the `]` ending the text of the current link, and of the end of its target
+        let mut link_end_opt: Option<(usize, usize)> = None;
+        let mut i = 0;
+        while let Some(c) = text[i..].chars().next() {
+            let rest = &text[i..];
+            if let Some((close, end)) = link_end_opt {
+                if i == close {
+                    rich_text = self.push(rich_text, &text[start..i], false);
+                    self.link_opt = None;
+                    link_end_opt = None;
+                    i = end;
+                    start = end;
+                    continue;
+                }
+            }
+
+            match c {
+                '\\' if rest[1..].starts_with(|c: char| c.is_ascii_punctuation()) => {
+                    // The escaped character starts the next piece of text
+                    rich_text = self.push(rich_text, &text[start..i], false);
+                    start = i + 1;
+                    i += 2;
+                    continue;
+                }
+                '`' => {
+                    let ticks = rest.len() - rest.trim_start_matches('`').len();
+                    match code_span_len(&rest[ticks..], ticks) {
+                        Some(len) => {
+                            rich_text = self.push(rich_text, &text[start..i], false);
+                            let code = &rest[ticks..ticks + len];
+                            let code = match code
+                                .strip_prefix(' ')
+                                .and_then(|code| code.strip_suffix(' '))
+                            {
+                                Some(stripped) if !code.trim().is_empty() => stripped,
+                                _ => code,
+                            };
+                            rich_text = self.push(rich_text, code, true);
+                            i += 2 * ticks + len;
+                            start = i;
+                        }
+                        None => i += ticks,
+                    }
+                    continue;
+                }
+                '*' | '_' => {
+                    let delim = if rest[1..].starts_with(c) {
+                        &rest[..2]
+                    } else {
+                        &rest[..1]
+                    };
+                    let after = &rest[delim.len()..];
+                    let prev_opt = text[..i].chars().next_back();
+                    let next_opt = after.chars().next();
+                    // Underscores inside words are not emphasis
+                    let intraword = c == '_'
+                        && prev_opt.map_or(false, char::is_alphanumeric)
+                        && next_opt.map_or(false, char::is_alphanumeric);
+                    let active = if delim.len() == 2 {
+                        self.bold
+                    } else {
+                        self.italic
+                    };
+                    let toggle = !intraword
+                        && if active {
+                            prev_opt.map_or(false, |prev| !prev.is_whitespace())
+                        } else {
+                            next_opt.map_or(false, |next| !next.is_whitespace())
+                                && after.contains(delim)
+                        };
+                    if toggle {
+                        rich_text = self.push(rich_text, &text[start..i], false);
+                        if delim.len() == 2 {
+                            self.bold = !self.bold;
+                        } else {
+                            self.italic = !self.italic;
+                        }
+                        start = i + delim.len();
+                    }
+                    i += delim.len();
+                    continue;
+                }
+                '[' if self.link_opt.is_none() => {
+                    let target_opt = rest.find("](").and_then(|close| {
+                        let target = &rest[close + 2..];
+                        let len = target.find(')')?;
+                        Some((close, &target[..len]))
+                    });
+                    if let Some((close, target)) = target_opt {
+                        rich_text = self.push(rich_text, &text[start..i], false);
+                        self.link_opt = Some(target.trim());
+                        link_end_opt = Some((i + close, i + close + 2 + target.len() + 1));
+                        start = i + 1;
+                    }
+                }
+                _ => {}
+            }
+            i += c.len_utf8();
+        }
+        self.push(rich_text, &text[start..], false)
+    }
+}
@@ -0,0 +1,361 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use fontdb::Family;
+
+use crate::{
+    Attrs, BaselineShift, Color, DecorationLine, DecorationStyle, RichText, Style, Weight,
+};
+
+/// Kind of a [`MarkupError`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MarkupErrorKind {
+    /// A tag is not closed with `>` before the end of the markup
+    UnexpectedEnd,
+    /// A tag or one of its attributes is not well-formed
+    InvalidTag,
+    /// The element is not supported
+    UnknownElement,
+    /// The attribute is not supported by its element
+    UnknownAttribute,
+    /// The value of an attribute cannot be parsed
+    InvalidValue,
+    /// The entity is not supported or is not closed with `;`
+    InvalidEntity,
+    /// A closing tag does not match the last open element
+    MismatchedClose,
+    /// An element is not closed before the end of the markup
+    UnclosedElement,
+}
+
+/// Error in markup passed to [`RichText::from_markup`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MarkupError {
+    pub kind: MarkupErrorKind,
+    /// Byte offset of the error in the markup
+    pub offset: usize,
+}
+
+impl MarkupError {
+    const fn new(kind: MarkupErrorKind, offset: usize) -> Self {
+        Self { kind, offset }
+    }
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            MarkupErrorKind::UnexpectedEnd => "unexpected end of markup",
+            MarkupErrorKind::InvalidTag => "invalid tag",
+            MarkupErrorKind::UnknownElement => "unknown element",
+            MarkupErrorKind::UnknownAttribute => "unknown attribute",
+            MarkupErrorKind::InvalidValue => "invalid attribute value",
+            MarkupErrorKind::InvalidEntity => "invalid entity",
+            MarkupErrorKind::MismatchedClose => "closing tag does not match open element",
+            MarkupErrorKind::UnclosedElement => "element is not closed",
+        };
+        write!(f, "{} at byte {}", message, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MarkupError {}
+
+impl<'a> RichText<'a> {
+    /// Parse markup in the style of Pango into rich text, using `attrs` outside of elements
+    ///
+    /// The elements `<b>`, `<i>`, `<s>`, `<u>`, `<tt>`, `<sup>` and `<sub>` are supported, as
+    /// well as `<span>` with these attributes:
+    ///
+    /// - `font_family` or `face`: a family name, or `serif`, `sans`, `monospace`, `cursive` or
+    ///   `fantasy`
+    /// - `font_size` or `size`: a size in pixels, like `20` or `20px`
+    /// - `font_weight` or `weight`: a number, or `thin`, `ultralight`, `light`, `normal`,
+    ///   `medium`, `semibold`, `bold`, `ultrabold` or `heavy`
+    /// - `font_style` or `style`: `normal`, `italic` or `oblique`
+    /// - `foreground`, `fgcolor` or `color`, and `background` or `bgcolor`: `#rgb`, `#rrggbb` or
+    ///   `#rrggbbaa`
+    /// - `underline`: `none`, `single`, `double`, `low` or `error`
+    /// - `overline`: `none` or `single`
+    /// - `strikethrough`: `true` or `false`
+    /// - `underline_color`, `overline_color` and `strikethrough_color`
+    ///
+    /// Text may contain the entities `&amp;`, `&lt;`, `&gt;`, `&quot;` and `&apos;`, and
+    /// numeric character references. Attribute values are not unescaped.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MarkupError`] at the first malformed or unsupported part of the markup.
+    pub fn from_markup(markup: &'a str, attrs: Attrs<'a>) -> Result<Self, MarkupError> {
+        let mut rich_text = RichText::new(attrs);
+        // Open elements with their names, attributes and offsets
+        let mut elements: Vec<(&str, Attrs<'a>, usize)> = Vec::new();
+        let mut text = String::new();
+        let mut i = 0;
+        while i < markup.len() {
+            let rest = &markup[i..];
+            match rest.find(['<', '&']) {
+                Some(0) => {}
+                Some(len) => {
+                    text.push_str(&rest[..len]);
+                    i += len;
+                    continue;
+                }
+                None => {
+                    text.push_str(rest);
+                    break;
+                }
+            }
+
+            if rest.starts_with('&') {
+                let invalid_entity = MarkupError::new(MarkupErrorKind::InvalidEntity, i);
+                let end = rest.find(';').ok_or(invalid_entity)?;
+                text.push(entity(&rest[1..end]).ok_or(invalid_entity)?);
+                i += end + 1;
+                continue;
+            }
+
+            let end = rest
+                .find('>')
+                .ok_or(MarkupError::new(MarkupErrorKind::UnexpectedEnd, i))?;
+            let tag = &rest[1..end];
+            // Text before the tag keeps the attributes of the elements it is in
+            let current = elements.last().map_or(attrs, |&(_, attrs, _)| attrs);
+            if !text.is_empty() {
+                rich_text = rich_text.span(core::mem::take(&mut text), current);
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                match elements.pop() {
+                    Some((open, _, _)) if open == name.trim_end() => {}
+                    _ => return Err(MarkupError::new(MarkupErrorKind::MismatchedClose, i)),
+                }
+            } else {
+                let (tag, empty) = match tag.strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let (name, element_attrs) = element(tag, current, i + 1)?;
+                if !empty {
+                    elements.push((name, element_attrs, i));
+                }
+            }
+            i += end + 1;
+        }
+
+        if let Some(&(_, _, offset)) = elements.last() {
+            return Err(MarkupError::new(MarkupErrorKind::UnclosedElement, offset));
+        }
+        if !text.is_empty() {
+            rich_text = rich_text.span(text, attrs);
+        }
+        Ok(rich_text)
+    }
+}
+
+/// Get the character of an entity, without its `&` and `;`
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// Parse the name and attributes of an opening tag, without its `<` and `>`, that starts at
+/// `offset` in the markup
+fn element<'a>(
+    tag: &'a str,
+    attrs: Attrs<'a>,
+    offset: usize,
+) -> Result<(&'a str, Attrs<'a>), MarkupError> {
+    let name_len = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let (name, mut rest) = tag.split_at(name_len);
+    let mut attrs = match name {
+        "b" => attrs.weight(Weight::BOLD),
+        "i" => attrs.style(Style::Italic),
+        "s" => attrs.strikethrough(true),
+        "u" => attrs.underline(true),
+        "tt" => attrs.family(Family::Monospace),
+        "sup" => attrs.baseline_shift(BaselineShift::Superscript),
+        "sub" => attrs.baseline_shift(BaselineShift::Subscript),
+        "span" => attrs,
+        "" => return Err(MarkupError::new(MarkupErrorKind::InvalidTag, offset)),
+        _ => return Err(MarkupError::new(MarkupErrorKind::UnknownElement, offset)),
+    };
+
+    // Colors of decoration lines apply once all lines are set
+    let mut line_colors: [Option<Color>; 3] = [None; 3];
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let attribute_offset = offset + tag.len() - rest.len();
+        let invalid_tag = MarkupError::new(MarkupErrorKind::InvalidTag, attribute_offset);
+
+        // Attributes look like name="value" or name='value'
+        let (key, value_rest) = rest.split_once('=').ok_or(invalid_tag)?;
+        let value_rest = value_rest.trim_start();
+        let quote = value_rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\''))
+            .ok_or(invalid_tag)?;
+        let (value, after) = value_rest[1..].split_once(quote).ok_or(invalid_tag)?;
+        rest = after;
+
+        let key = key.trim_end();
+        if name != "span" {
+            return Err(MarkupError::new(
+                MarkupErrorKind::UnknownAttribute,
+                attribute_offset,
+            ));
+        }
+        attrs = match key {
+            "underline_color" => {
+                line_colors[0] = Some(hex_color(value).ok_or(invalid_value(attribute_offset))?);
+                attrs
+            }
+            "overline_color" => {
+                line_colors[1] = Some(hex_color(value).ok_or(invalid_value(attribute_offset))?);
+                attrs
+            }
+            "strikethrough_color" => {
+                line_colors[2] = Some(hex_color(value).ok_or(invalid_value(attribute_offset))?);
+                attrs
+            }
+            _ => span_attribute(attrs, key, value)
+                .map_err(|kind| MarkupError::new(kind, attribute_offset))?,
+        };
+    }
+
+    let decoration = &mut attrs.decoration;
+    let lines = [
+        &mut decoration.underline,
+        &mut decoration.overline,
+        &mut decoration.strikethrough,
+    ];
+    for (line_opt, color_opt) in lines.into_iter().zip(line_colors) {
+        if let (Some(line), Some(color)) = (line_opt, color_opt) {
+            line.color_opt = Some(color);
+        }
+    }
+    Ok((name, attrs))
+}
+
+fn invalid_value(offset: usize) -> MarkupError {
+    MarkupError::new(MarkupErrorKind::InvalidValue, offset)
+}
+
+/// Apply an attribute of a `<span>` other than the colors of decoration lines
+fn span_attribute<'a>(
+    attrs: Attrs<'a>,
+    key: &str,
+    value: &'a str,
+) -> Result<Attrs<'a>, MarkupErrorKind> {
+    let invalid = MarkupErrorKind::InvalidValue;
+    let line = |style: DecorationStyle| Some(DecorationLine::new(style));
+    Ok(match key {
+        "font_family" | "face" => attrs.family(match value {
+            "serif" => Family::Serif,
+            "sans" | "sans-serif" => Family::SansSerif,
+            "monospace" => Family::Monospace,
+            "cursive" => Family::Cursive,
+            "fantasy" => Family::Fantasy,
+            _ => Family::Name(value),
+        }),
+        "font_size" | "size" => {
+            let size: f32 = value
+                .strip_suffix("px")
+                .unwrap_or(value)
+                .parse()
+                .map_err(|_| invalid)?;
+            if size.is_nan() || size <= 0.0 {
+                return Err(invalid);
+            }
+            attrs.font_size(size)
+        }
+        "font_weight" | "weight" => attrs.weight(match value {
+            "thin" => Weight::THIN,
+            "ultralight" => Weight::EXTRA_LIGHT,
+            "light" => Weight::LIGHT,
+            "normal" => Weight::NORMAL,
+            "medium" => Weight::MEDIUM,
+            "semibold" => Weight::SEMIBOLD,
+            "bold" => Weight::BOLD,
+            "ultrabold" => Weight::EXTRA_BOLD,
+            "heavy" => Weight::BLACK,
+            _ => Weight(value.parse().map_err(|_| invalid)?),
+        }),
+        "font_style" | "style" => attrs.style(match value {
+            "normal" => Style::Normal,
+            "italic" => Style::Italic,
+            "oblique" => Style::Oblique,
+            _ => return Err(invalid),
+        }),
+        "foreground" | "fgcolor" | "color" => attrs.color(hex_color(value).ok_or(invalid)?),
+        "background" | "bgcolor" => attrs.background_color(hex_color(value).ok_or(invalid)?),
+        "underline" => {
+            let mut attrs = attrs;
+            attrs.decoration.underline = match value {
+                "none" => None,
+                "single" | "low" => line(DecorationStyle::Solid),
+                "double" => line(DecorationStyle::Double),
+                "error" => line(DecorationStyle::Wavy),
+                _ => return Err(invalid),
+            };
+            attrs
+        }
+        "overline" => match value {
+            "none" => attrs.overline(false),
+            "single" => attrs.overline(true),
+            _ => return Err(invalid),
+        },
+        "strikethrough" => match value {
+            "false" => attrs.strikethrough(false),
+            "true" => attrs.strikethrough(true),
+            _ => return Err(invalid),
+        },
+        _ => return Err(MarkupErrorKind::UnknownAttribute),
+    })
+}
+
+/// Parse a color like `#rgb`, `#rrggbb` or `#rrggbbaa`
+pub(crate) fn hex_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16);
+    match hex.len() {
+        3 => {
+            let [r, g, b] = [0, 1, 2].map(|i| channel(i, 1).map(|c| c * 17));
+            Some(Color::rgb(r.ok()?, g.ok()?, b.ok()?))
+        }
+        6 => Some(Color::rgb(
+            channel(0, 2).ok()?,
+            channel(1, 2).ok()?,
+            channel(2, 2).ok()?,
+        )),
+        8 => Some(Color::rgba(
+            channel(0, 2).ok()?,
+            channel(1, 2).ok()?,
+            channel(2, 2).ok()?,
+            channel(3, 2).ok()?,
+        )),
+        _ => None,
+    }
+}
@@ -1,5 +1,23 @@
 #[cfg(not(feature = "std"))]
-pub use libm::{roundf, truncf};
+pub use libm::{ceilf, floorf, powf, roundf, truncf};
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn ceilf(x: f32) -> f32 {
+    x.ceil()
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn floorf(x: f32) -> f32 {
+    x.floor()
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn powf(x: f32, y: f32) -> f32 {
+    x.powf(y)
+}
 
 #[cfg(feature = "std")]
 #[inline]
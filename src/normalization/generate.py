@@ -0,0 +1,138 @@
+#!/usr/bin/env python3
+# SPDX-License-Identifier: MIT OR Apache-2.0
+
+# Generates tables.rs from the Unicode Character Database of Python's unicodedata module, run
+# from this directory with a Python whose unicodedata has the version below, like Python 3.11:
+#
+#     python3 generate.py > tables.rs
+
+import sys
+import unicodedata
+
+UNICODE_VERSION = "14.0.0"
+
+HANGUL_S_BASE = 0xAC00
+HANGUL_S_COUNT = 11172
+HANGUL_L_BASE = 0x1100
+HANGUL_T_BASE = 0x11A7
+HANGUL_T_COUNT = 28
+
+
+def hex_code(c):
+    return "0x%04X" % c
+
+
+def is_hangul_syllable(c):
+    return HANGUL_S_BASE <= c < HANGUL_S_BASE + HANGUL_S_COUNT
+
+
+def is_hangul_jamo(c):
+    return HANGUL_L_BASE <= c < HANGUL_T_BASE + HANGUL_T_COUNT
+
+
+def canonical_decomposition(c):
+    decomposition = unicodedata.decomposition(chr(c))
+    if not decomposition or decomposition.startswith("<"):
+        return None
+    return [int(part, 16) for part in decomposition.split()]
+
+
+def packed(items, indent="    ", width=100):
+    lines = []
+    line = ""
+    for item in items:
+        item += ","
+        if line and len(indent) + len(line) + 1 + len(item) > width:
+            lines.append(indent + line)
+            line = item
+        else:
+            line = line + " " + item if line else item
+    if line:
+        lines.append(indent + line)
+    return lines
+
+
+def main():
+    if unicodedata.unidata_version != UNICODE_VERSION:
+        sys.exit(
+            "unicodedata has version %s instead of %s"
+            % (unicodedata.unidata_version, UNICODE_VERSION)
+        )
+
+    combining_class = [unicodedata.combining(chr(c)) for c in range(0x110000)]
+    decompositions = []
+    compositions = []
+    for c in range(0x110000):
+        if is_hangul_syllable(c):
+            continue
+        parts = canonical_decomposition(c)
+        if parts is None:
+            continue
+        decompositions.append((c, parts[0], parts[1] if len(parts) > 1 else 0))
+        # Primary composites are the pairs that normalization composes again
+        if len(parts) == 2 and unicodedata.normalize("NFC", chr(c)) == chr(c):
+            compositions.append((parts[0], parts[1], c))
+    compositions.sort()
+
+    combining_classes = []
+    for c in range(0x110000):
+        class_ = combining_class[c]
+        if class_ == 0:
+            continue
+        last = combining_classes[-1] if combining_classes else None
+        if last and last[1] == c - 1 and last[2] == class_:
+            last[1] = c
+        else:
+            combining_classes.append([c, c, class_])
+
+    # Starters that combine with the characters before them, or decompose to non-starters
+    non_segment_starts = set(
+        second for _, second, _ in compositions if combining_class[second] == 0
+    )
+    for c, first, _ in decompositions:
+        if combining_class[c] == 0 and combining_class[first] != 0:
+            non_segment_starts.add(c)
+    non_segment_starts = sorted(c for c in non_segment_starts if not is_hangul_jamo(c))
+
+    out = [
+        "// SPDX-License-Identifier: MIT OR Apache-2.0",
+        "",
+        "// Generated by generate.py from the Unicode Character Database %s" % UNICODE_VERSION,
+        "",
+        "/// Canonical decompositions into one or two characters, sorted by character, with 0 for no",
+        "/// second character. Hangul syllables are decomposed algorithmically.",
+        "pub static DECOMPOSITIONS: &[(u32, u32, u32)] = &[",
+    ]
+    for c, first, second in decompositions:
+        second = hex_code(second) if second else "0"
+        out.append("    (%s, %s, %s)," % (hex_code(c), hex_code(first), second))
+    out += [
+        "];",
+        "",
+        "/// Primary composites of pairs of characters, sorted by pair",
+        "pub static COMPOSITIONS: &[(u32, u32, u32)] = &[",
+    ]
+    for first, second, c in compositions:
+        out.append("    (%s, %s, %s)," % (hex_code(first), hex_code(second), hex_code(c)))
+    out += [
+        "];",
+        "",
+        "/// Ranges of characters with a nonzero canonical combining class, sorted by start",
+        "pub static COMBINING_CLASSES: &[(u32, u32, u8)] = &[",
+    ]
+    for start, end, class_ in combining_classes:
+        out.append("    (%s, %s, %d)," % (hex_code(start), hex_code(end), class_))
+    out += [
+        "];",
+        "",
+        "/// Characters with a combining class of 0 that may still combine with the characters",
+        "/// before them, sorted. Hangul jamo are handled algorithmically.",
+        "pub static NON_SEGMENT_STARTS: &[u32] = &[",
+    ]
+    out += packed(hex_code(c) for c in non_segment_starts)
+    out.append("];")
+    print("\n".join(out))
+
+
+if __name__ == "__main__":
+    main()
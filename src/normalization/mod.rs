@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::cmp::Ordering;
+use core::ops::Range;
+
+use crate::AttrsList;
+
+use self::tables::{COMBINING_CLASSES, COMPOSITIONS, DECOMPOSITIONS, NON_SEGMENT_STARTS};
+
+mod tables;
+
+const HANGUL_S_BASE: u32 = 0xAC00;
+const HANGUL_L_BASE: u32 = 0x1100;
+const HANGUL_V_BASE: u32 = 0x1161;
+const HANGUL_T_BASE: u32 = 0x11A7;
+const HANGUL_L_COUNT: u32 = 19;
+const HANGUL_V_COUNT: u32 = 21;
+const HANGUL_T_COUNT: u32 = 28;
+const HANGUL_N_COUNT: u32 = HANGUL_V_COUNT * HANGUL_T_COUNT;
+const HANGUL_S_COUNT: u32 = HANGUL_L_COUNT * HANGUL_N_COUNT;
+
+/// Get the canonical combining class of a character
+fn combining_class(c: char) -> u8 {
+    let c = c as u32;
+    COMBINING_CLASSES
+        .binary_search_by(|&(start, end, _)| {
+            if end < c {
+                Ordering::Less
+            } else if start > c {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .map_or(0, |i| COMBINING_CLASSES[i].2)
+}
+
+/// Append the full canonical decomposition of a character
+fn decompose(c: char, decomposed: &mut Vec<char>) {
+    let s = (c as u32).wrapping_sub(HANGUL_S_BASE);
+    if s < HANGUL_S_COUNT {
+        let l = HANGUL_L_BASE + s / HANGUL_N_COUNT;
+        let v = HANGUL_V_BASE + (s % HANGUL_N_COUNT) / HANGUL_T_COUNT;
+        let t = HANGUL_T_BASE + s % HANGUL_T_COUNT;
+        decomposed.extend([l, v].into_iter().filter_map(char::from_u32));
+        if t != HANGUL_T_BASE {
+            decomposed.extend(char::from_u32(t));
+        }
+        return;
+    }
+
+    match DECOMPOSITIONS.binary_search_by_key(&(c as u32), |&(c, _, _)| c) {
+        Ok(i) => {
+            let (_, first, second) = DECOMPOSITIONS[i];
+            for part in [first, second] {
+                if let Some(part) = char::from_u32(part).filter(|_| part != 0) {
+                    decompose(part, decomposed);
+                }
+            }
+        }
+        Err(_) => decomposed.push(c),
+    }
+}
+
+/// Get the primary composite of a pair of characters
+fn compose(first: char, second: char) -> Option<char> {
+    let (first, second) = (first as u32, second as u32);
+    let l = first.wrapping_sub(HANGUL_L_BASE);
+    let v = second.wrapping_sub(HANGUL_V_BASE);
+    if l < HANGUL_L_COUNT && v < HANGUL_V_COUNT {
+        return char::from_u32(HANGUL_S_BASE + (l * HANGUL_V_COUNT + v) * HANGUL_T_COUNT);
+    }
+    let s = first.wrapping_sub(HANGUL_S_BASE);
+    let t = second.wrapping_sub(HANGUL_T_BASE);
+    if s < HANGUL_S_COUNT && s % HANGUL_T_COUNT == 0 && t > 0 && t < HANGUL_T_COUNT {
+        return char::from_u32(first + t);
+    }
+
+    COMPOSITIONS
+        .binary_search_by_key(&(first, second), |&(first, second, _)| (first, second))
+        .ok()
+        .and_then(|i| char::from_u32(COMPOSITIONS[i].2))
+}
+
+/// Check if a character can not combine with the characters before it, so that normalization
+/// may start over at it
+fn is_segment_start(c: char) -> bool {
+    let jamo_v_or_t = (HANGUL_V_BASE..HANGUL_T_BASE + HANGUL_T_COUNT).contains(&(c as u32));
+    combining_class(c) == 0
+        && !jamo_v_or_t
+        && NON_SEGMENT_STARTS.binary_search(&(c as u32)).is_err()
+}
+
+/// Append a segment of text in Normalization Form C, using `scratch` for its decomposition
+fn normalize_segment(segment: &str, normalized: &mut String, scratch: &mut Vec<char>) {
+    scratch.clear();
+    for c in segment.chars() {
+        decompose(c, scratch);
+    }
+
+    // Put combining marks in canonical order, keeping the order of marks of the same class
+    for i in 1..scratch.len() {
+        let class = combining_class(scratch[i]);
+        if class == 0 {
+            continue;
+        }
+        let mut j = i;
+        while j > 0 && combining_class(scratch[j - 1]) > class {
+            scratch.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+
+    // Compose each character with the last starter, unless a character between them blocks it
+    let mut starter_opt: Option<usize> = None;
+    let mut last_class = 0;
+    let mut len = 0;
+    for i in 0..scratch.len() {
+        let c = scratch[i];
+        let class = combining_class(c);
+        if let Some(starter) = starter_opt {
+            let blocked = len > starter + 1 && (last_class == 0 || last_class >= class);
+            if !blocked {
+                if let Some(composite) = compose(scratch[starter], c) {
+                    scratch[starter] = composite;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter_opt = Some(len);
+        }
+        last_class = class;
+        scratch[len] = c;
+        len += 1;
+    }
+    normalized.extend(&scratch[..len]);
+}
+
+/// Text converted to Unicode Normalization Form C, with a mapping between byte offsets in it and
+/// in the text it was converted from
+///
+/// Decomposed text, such as an `e` followed by a combining acute accent from some input methods
+/// or file names on macOS, becomes the precomposed `é`, so that it shows and compares the same as
+/// text typed precomposed. See [`crate::Buffer::set_nfc_normalization`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NfcText {
+    text: String,
+    /// Ranges of the changed parts of the text, in the normalized and in the original text
+    changes: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl NfcText {
+    /// Convert text to Normalization Form C
+    pub fn new(original: &str) -> Self {
+        let mut text = String::with_capacity(original.len());
+        let mut changes = Vec::new();
+        let mut scratch = Vec::new();
+        let mut start = 0;
+        let mut segments = original.char_indices().peekable();
+        while let Some((_, first)) = segments.next() {
+            let mut end = start + first.len_utf8();
+            while let Some(&(i, c)) = segments.peek() {
+                if is_segment_start(c) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                segments.next();
+            }
+
+            let segment = &original[start..end];
+            let normalized_start = text.len();
+            if segment.is_ascii() {
+                text.push_str(segment);
+            } else {
+                normalize_segment(segment, &mut text, &mut scratch);
+                if text[normalized_start..] != *segment {
+                    changes.push((normalized_start..text.len(), start..end));
+                }
+            }
+            start = end;
+        }
+        Self { text, changes }
+    }
+
+    /// Get the normalized text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Take the normalized text
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Check if normalization changed the text
+    pub fn is_changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Map the spans of attributes of the original text to the normalized text
+    pub fn normalize_attrs_list(&self, attrs_list: &AttrsList) -> AttrsList {
+        let mut normalized = AttrsList::new(attrs_list.defaults());
+        for (range, attrs) in attrs_list.spans() {
+            let range = self.normalized_index(range.start)..self.normalized_index(range.end);
+            normalized.add_span(range, attrs.as_attrs());
+        }
+        normalized
+    }
+
+    /// Get the byte offset in the original text of a byte offset in the normalized text
+    ///
+    /// Offsets inside a changed sequence of characters map to the start of that sequence.
+    pub fn original_index(&self, index: usize) -> usize {
+        map_index(
+            self.changes
+                .iter()
+                .map(|(normalized, original)| (normalized, original)),
+            index,
+        )
+    }
+
+    /// Get the byte offset in the normalized text of a byte offset in the original text
+    ///
+    /// Offsets inside a changed sequence of characters map to the start of that sequence.
+    pub fn normalized_index(&self, original_index: usize) -> usize {
+        map_index(
+            self.changes
+                .iter()
+                .map(|(normalized, original)| (original, normalized)),
+            original_index,
+        )
+    }
+}
+
+/// Map an offset through sorted pairs of changed ranges, from the first range of each pair to
+/// the second
+fn map_index<'a>(
+    changes: impl Iterator<Item = (&'a Range<usize>, &'a Range<usize>)>,
+    index: usize,
+) -> usize {
+    let mut mapped = index;
+    for (from, to) in changes {
+        if from.start > index {
+            break;
+        }
+        mapped = if index < from.end {
+            to.start
+        } else {
+            index - from.end + to.end
+        };
+    }
+    mapped
+}
+
+#[test]
+fn test_nfc_text() {
+    // Composition, reordering of combining marks, Hangul, and singletons
+    let nfc = NfcText::new("e\u{301}a\u{323}\u{302}\u{1100}\u{1161}\u{11A8}\u{212B}x");
+    assert_eq!(nfc.text(), "\u{E9}\u{1EAD}\u{AC01}\u{C5}x");
+    assert!(nfc.is_changed());
+    assert_eq!(nfc.original_index(0), 0);
+    assert_eq!(nfc.original_index(2), 3);
+    assert_eq!(nfc.original_index(5), 8);
+    assert_eq!(nfc.original_index(8), 17);
+    assert_eq!(nfc.original_index(10), 20);
+    assert_eq!(nfc.original_index(11), 21);
+    assert_eq!(nfc.normalized_index(3), 2);
+    assert_eq!(nfc.normalized_index(4), 2);
+    assert_eq!(nfc.normalized_index(21), 11);
+
+    // Blocked marks and marks without a composite are kept
+    let nfc = NfcText::new("a\u{301}\u{301}\u{1E0B}\u{323}");
+    assert_eq!(nfc.text(), "\u{E1}\u{301}\u{1E0D}\u{307}");
+
+    let nfc = NfcText::new("caf\u{E9}");
+    assert!(!nfc.is_changed());
+    assert_eq!(nfc.normalized_index(3), 3);
+}
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+/// Parameters of [`crate::Wrap::Optimal`], which chooses the line breaks of a whole paragraph
+/// with the total-fit algorithm of Knuth and Plass
+///
+/// Each line is given demerits growing with how much its spaces would have to stretch to fill the
+/// width, and the breaks with the least total demerits are chosen. Spaces may stretch by half of
+/// their width, and lines are never wider than the width.
+#[derive(Clone, Copy, Debug)]
+pub struct OptimalWrap {
+    /// Largest ratio of the stretch of the spaces of a line to what they may stretch, above which
+    /// a break is not considered. Lines without spaces are as loose as it allows. Paragraphs that
+    /// cannot be broken within it are wrapped like [`crate::Wrap::WordOrGlyph`]
+    pub tolerance: f32,
+    /// Demerits added to each line, higher values prefer fewer lines
+    pub line_penalty: f32,
+    /// Penalty of a break at a hyphenation point, higher values prefer breaking between words
+    pub hyphen_penalty: f32,
+    /// Demerits added when two lines in a row end with a hyphen
+    pub consecutive_hyphen_demerits: f32,
+    /// Number of lines to add to, or remove from when negative, the best breaks if possible
+    pub looseness: i32,
+}
+
+impl OptimalWrap {
+    /// Create the parameters with the defaults
+    pub const fn new() -> Self {
+        Self {
+            tolerance: 3.0,
+            line_penalty: 10.0,
+            hyphen_penalty: 50.0,
+            consecutive_hyphen_demerits: 3000.0,
+            looseness: 0,
+        }
+    }
+
+    /// Set [`Self::tolerance`]
+    pub const fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set [`Self::line_penalty`]
+    pub const fn line_penalty(mut self, line_penalty: f32) -> Self {
+        self.line_penalty = line_penalty;
+        self
+    }
+
+    /// Set [`Self::hyphen_penalty`]
+    pub const fn hyphen_penalty(mut self, hyphen_penalty: f32) -> Self {
+        self.hyphen_penalty = hyphen_penalty;
+        self
+    }
+
+    /// Set [`Self::consecutive_hyphen_demerits`]
+    pub const fn consecutive_hyphen_demerits(mut self, consecutive_hyphen_demerits: f32) -> Self {
+        self.consecutive_hyphen_demerits = consecutive_hyphen_demerits;
+        self
+    }
+
+    /// Set [`Self::looseness`]
+    pub const fn looseness(mut self, looseness: i32) -> Self {
+        self.looseness = looseness;
+        self
+    }
+}
+
+impl Default for OptimalWrap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for OptimalWrap {
+    fn eq(&self, other: &Self) -> bool {
+        self.tolerance.to_bits() == other.tolerance.to_bits()
+            && self.line_penalty.to_bits() == other.line_penalty.to_bits()
+            && self.hyphen_penalty.to_bits() == other.hyphen_penalty.to_bits()
+            && self.consecutive_hyphen_demerits.to_bits()
+                == other.consecutive_hyphen_demerits.to_bits()
+            && self.looseness == other.looseness
+    }
+}
+
+impl Eq for OptimalWrap {}
+
+/// An element of a paragraph to break into lines
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum BreakItem {
+    /// Content that is never broken, like a word
+    Box { width: f32 },
+    /// A space, where the line may break if it follows a box
+    Glue { width: f32 },
+    /// A possible break between boxes, adding `width` to the line that ends there
+    Penalty { width: f32, hyphen: bool },
+}
+
+/// A feasible break and the best way to reach it
+#[derive(Clone, Copy, Debug)]
+struct BreakNode {
+    /// Index of the item the line breaks at, or the number of items at the end
+    position: usize,
+    /// Number of lines before the break
+    lines: usize,
+    demerits: f32,
+    hyphen: bool,
+    prev: Option<usize>,
+}
+
+/// Totals of the items before an index
+#[derive(Clone, Copy, Debug, Default)]
+struct BreakSums {
+    width: f32,
+    stretch: f32,
+}
+
+impl OptimalWrap {
+    /// Find the items to break the lines at, or `None` if the paragraph cannot be broken within
+    /// the tolerance. `line_width` gives the width available to each line from its index.
+    pub(crate) fn breaks(
+        &self,
+        items: &[BreakItem],
+        line_width: &mut dyn FnMut(usize) -> f32,
+    ) -> Option<Vec<usize>> {
+        let mut sums = Vec::with_capacity(items.len() + 1);
+        let mut sum = BreakSums::default();
+        sums.push(sum);
+        for item in items {
+            match *item {
+                BreakItem::Box { width } => sum.width += width,
+                BreakItem::Glue { width } => {
+                    sum.width += width;
+                    sum.stretch += width / 2.0;
+                }
+                BreakItem::Penalty { .. } => {}
+            }
+            sums.push(sum);
+        }
+
+        let mut nodes = vec![BreakNode {
+            position: 0,
+            lines: 0,
+            demerits: 0.0,
+            hyphen: false,
+            prev: None,
+        }];
+        let mut active = vec![0];
+        // Spaces at the end of the paragraph are not counted in the width of its last line
+        let trailing_glue = items
+            .iter()
+            .rev()
+            .take_while(|item| matches!(item, BreakItem::Glue { .. }))
+            .count();
+        for position in 1..=items.len() {
+            let last = position == items.len();
+            let (break_width, hyphen, penalty) = match items.get(position) {
+                None => (0.0, false, 0.0),
+                Some(BreakItem::Glue { .. })
+                    if matches!(items[position - 1], BreakItem::Box { .. }) =>
+                {
+                    (0.0, false, 0.0)
+                }
+                Some(&BreakItem::Penalty { width, hyphen }) => {
+                    let penalty = if hyphen { self.hyphen_penalty } else { 0.0 };
+                    (width, hyphen, penalty)
+                }
+                Some(_) => continue,
+            };
+
+            // Best break found for each number of lines
+            let mut found: Vec<BreakNode> = Vec::new();
+            active.retain(|&node_i| {
+                let node: BreakNode = nodes[node_i];
+                // Lines start after the break they follow
+                let start = if node.position == 0 {
+                    sums[0]
+                } else {
+                    sums[node.position + 1]
+                };
+                let end = if last {
+                    sums[position - trailing_glue]
+                } else {
+                    sums[position]
+                };
+                let width = end.width - start.width + break_width;
+                let available = line_width(node.lines);
+                if width > available {
+                    // Lines that are already too wide only get wider
+                    return false;
+                }
+                let stretch = end.stretch - start.stretch;
+                let ratio = if last || width == available {
+                    0.0
+                } else if stretch > 0.0 {
+                    (available - width) / stretch
+                } else {
+                    self.tolerance
+                };
+
+                if ratio <= self.tolerance {
+                    let badness = 100.0 * ratio * ratio * ratio;
+                    let line_demerits = self.line_penalty + badness;
+                    let mut demerits =
+                        line_demerits * line_demerits + penalty * penalty + node.demerits;
+                    if hyphen && node.hyphen {
+                        demerits += self.consecutive_hyphen_demerits;
+                    }
+                    let lines = node.lines + 1;
+                    match found.iter_mut().find(|found| found.lines == lines) {
+                        Some(found) if found.demerits <= demerits => {}
+                        Some(found) => {
+                            found.demerits = demerits;
+                            found.prev = Some(node_i);
+                        }
+                        None => found.push(BreakNode {
+                            position,
+                            lines,
+                            demerits,
+                            hyphen,
+                            prev: Some(node_i),
+                        }),
+                    }
+                }
+                !last
+            });
+
+            for node in found {
+                active.push(nodes.len());
+                nodes.push(node);
+            }
+            if active.is_empty() && !last {
+                return None;
+            }
+        }
+
+        // Choose the number of lines closest to the best plus the looseness
+        let ends = nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.position == items.len() && node.prev.is_some());
+        let best_lines = ends
+            .clone()
+            .min_by(|(_, a), (_, b)| a.demerits.total_cmp(&b.demerits))?
+            .1
+            .lines as i64;
+        let target = best_lines + i64::from(self.looseness);
+        let (mut node_i, _) = ends.min_by(|(_, a), (_, b)| {
+            (a.lines as i64 - target)
+                .abs()
+                .cmp(&(b.lines as i64 - target).abs())
+                .then(a.demerits.total_cmp(&b.demerits))
+        })?;
+
+        let mut breaks = Vec::new();
+        while let Some(prev) = nodes[node_i].prev {
+            breaks.push(nodes[node_i].position);
+            node_i = prev;
+        }
+        breaks.reverse();
+        // The end of the paragraph is not a break
+        breaks.pop();
+        Some(breaks)
+    }
+}
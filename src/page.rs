@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Scroll;
+
+/// The layout lines that fit on one page, see [`crate::Buffer::paginate`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PageRange {
+    /// Position of the first layout line of the page, which shows the page when set with
+    /// [`crate::Buffer::set_scroll`]
+    pub start: Scroll,
+    /// Position of the first layout line after the page, or the line after the last line of the
+    /// buffer for the last page
+    pub end: Scroll,
+    /// Distance from the top of the text to the top of the page, if the pages were stacked
+    pub y: f32,
+    /// Height of the lines on the page, including paragraph spacing between them
+    pub height: f32,
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{Align, Direction};
+
+/// Attributes of a whole paragraph, as opposed to the character [`crate::Attrs`] of its spans
+///
+/// Each [`crate::BufferLine`] has its own, see [`crate::BufferLine::set_paragraph_attrs`] and
+/// [`crate::Buffer::set_rich_paragraphs`]. Lengths are in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ParagraphAttrs {
+    /// Alignment, or None to align to the start of the paragraph direction
+    pub align: Option<Align>,
+    /// Indent of the first line from the start of the paragraph direction, negative values
+    /// letting it hang out of the paragraph
+    pub indent: f32,
+    /// Indent of the lines after the first from the start of the paragraph direction, for
+    /// example to line up wrapped lines of a list item with the text after its bullet
+    pub hanging_indent: f32,
+    /// Base direction, or None to detect it from the first strong character
+    pub direction: Option<Direction>,
+    /// Space above the first line
+    pub space_before: f32,
+    /// Space below the last line
+    pub space_after: f32,
+    /// Number of lines spanned by a drop cap made of the first grapheme, or 0 or 1 for none
+    ///
+    /// The initial is scaled so that its ascent reaches from the top of the first line to the
+    /// baseline of the last line it spans, and those lines are inset by its width. Drop caps are
+    /// only laid out by [`crate::Buffer`] in horizontal lines, see [`crate::LayoutRun::drop_cap`].
+    pub drop_cap: usize,
+}
+
+impl ParagraphAttrs {
+    /// Create new paragraph attributes with default values
+    pub const fn new() -> Self {
+        Self {
+            align: None,
+            indent: 0.0,
+            hanging_indent: 0.0,
+            direction: None,
+            space_before: 0.0,
+            space_after: 0.0,
+            drop_cap: 0,
+        }
+    }
+
+    /// Set [Align]
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    /// Set first line indent
+    pub fn indent(mut self, indent: f32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Set the indent of the lines after the first
+    pub fn hanging_indent(mut self, hanging_indent: f32) -> Self {
+        self.hanging_indent = hanging_indent;
+        self
+    }
+
+    /// Set [Direction]
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Set space above and below the paragraph
+    pub fn spacing(mut self, space_before: f32, space_after: f32) -> Self {
+        self.space_before = space_before;
+        self.space_after = space_after;
+        self
+    }
+
+    /// Set the number of lines spanned by a drop cap made of the first grapheme
+    pub fn drop_cap(mut self, lines: usize) -> Self {
+        self.drop_cap = lines;
+        self
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::sync::Arc;
+use core::{
+    any::Any,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// Value of any type attached to a span with [`crate::Attrs::payload`], with its id carried to
+/// its glyphs in [`crate::LayoutGlyph::payload_id`]
+///
+/// Payloads are shared, so cloning one is cheap. Two payloads are equal only if they are clones
+/// of the same payload, whatever their values.
+#[derive(Clone)]
+pub struct Payload(Arc<dyn Any + Send + Sync>);
+
+impl Payload {
+    /// Create a payload holding a value, like a diagnostic id or a widget handle
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// Check if the value is of type `T`
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    /// Get the value if it is of type `T`
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+
+    /// Id of the payload, the address of its shared value, which is never 0
+    ///
+    /// Get the payload of an id from the attributes list it was set in with
+    /// [`crate::AttrsList::payload`].
+    pub fn id(&self) -> usize {
+        Arc::as_ptr(&self.0).cast::<()>() as usize
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Payload({:#x})", self.id())
+    }
+}
+
+impl PartialEq for Payload {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Eq for Payload {}
+
+impl Hash for Payload {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Attrs, LayoutRun, Script, ShapeGlyph};
+
+/// An inline box that takes the place of the text of its span, see [`crate::Attrs::placeholder`]
+///
+/// Sizes are in em of the font size, like the advances of glyphs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Placeholder {
+    /// Identifier of the object drawn in the box, like an image or a widget
+    pub id: usize,
+    /// Advance of the box
+    pub width: f32,
+    /// Height of the box above the baseline
+    pub ascent: f32,
+    /// Depth of the box below the baseline
+    pub descent: f32,
+}
+
+impl Placeholder {
+    /// Create a placeholder with the given id and size in em
+    pub const fn new(id: usize, width: f32, ascent: f32, descent: f32) -> Self {
+        Self {
+            id,
+            width,
+            ascent,
+            descent,
+        }
+    }
+
+    /// Create the glyph that stands for the placeholder in the text from `start` to `end`
+    pub(crate) fn glyph(self, attrs: &Attrs, start: usize, end: usize) -> ShapeGlyph {
+        ShapeGlyph {
+            start,
+            end,
+            x_advance: self.width,
+            y_advance: 0.0,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            ascent: self.ascent,
+            descent: self.descent,
+            win_ascent: self.ascent,
+            win_descent: self.descent,
+            font_monospace_em_width: None,
+            font_id: fontdb::ID::dummy(),
+            font_instance_opt: None,
+            glyph_id: 0,
+            color_opt: attrs.color_opt,
+            background_color_opt: attrs.background_color_opt,
+            shadow_opt: attrs.shadow_opt,
+            font_size_opt: None,
+            clamp_line_height: attrs.clamp_line_height,
+            decoration: attrs.decoration,
+            metadata: attrs.metadata,
+            payload_opt: attrs.payload_opt.cloned(),
+            fill_id: attrs.fill_id,
+            cache_key_flags: attrs.cache_key_flags,
+            oblique_opt: attrs.oblique_opt,
+            kashida_opt: None,
+            script: Script::Unknown,
+            tabular_em_width: None,
+            font_size_scale: 1.0,
+            synthetic_small_caps: false,
+            baseline_shift: 0.0,
+            placeholder_opt: Some(self),
+        }
+    }
+}
+
+impl PartialEq for Placeholder {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.width.to_bits() == other.width.to_bits()
+            && self.ascent.to_bits() == other.ascent.to_bits()
+            && self.descent.to_bits() == other.descent.to_bits()
+    }
+}
+
+impl Eq for Placeholder {}
+
+impl core::hash::Hash for Placeholder {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.width.to_bits().hash(state);
+        self.ascent.to_bits().hash(state);
+        self.descent.to_bits().hash(state);
+    }
+}
+
+/// Where a [`Placeholder`] was laid out in a [`LayoutRun`], see [`LayoutRun::placeholders`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlaceholderBox {
+    /// Identifier of the placeholder
+    pub id: usize,
+    /// X position of the left of the box
+    pub x: f32,
+    /// Y position of the top of the box, relative to the baseline with positive values below it
+    pub y: f32,
+    /// Width of the box
+    pub w: f32,
+    /// Height of the box
+    pub h: f32,
+}
+
+impl<'a> LayoutRun<'a> {
+    /// Get the boxes of the placeholders in this run, in the order of their glyphs
+    pub fn placeholders(&self) -> Vec<PlaceholderBox> {
+        self.glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let placeholder = glyph.placeholder_opt()?;
+                Some(PlaceholderBox {
+                    id: placeholder.id,
+                    x: glyph.x,
+                    y: glyph.y - placeholder.ascent * glyph.font_size,
+                    w: glyph.w,
+                    h: (placeholder.ascent + placeholder.descent) * glyph.font_size,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::Attrs;
+
+/// Pseudo-localization settings, used to test layouts without real translations
+///
+/// When set on a [`crate::Buffer`], text passed to [`crate::Buffer::set_text`] and
+/// [`crate::Buffer::set_rich_text`] is transformed before shaping, so that truncation and
+/// layout issues can be spotted early.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PseudoLocale {
+    /// Replace ASCII letters with accented look-alikes
+    pub accents: bool,
+    /// Surround every line with `[` and `]`, to make truncation visible
+    pub brackets: bool,
+    /// Fraction of the line length to append as padding, `0.3` adds 30%
+    pub expansion: f32,
+}
+
+impl Default for PseudoLocale {
+    fn default() -> Self {
+        Self {
+            accents: true,
+            brackets: true,
+            expansion: 0.3,
+        }
+    }
+}
+
+impl PseudoLocale {
+    const PADDING: char = '~';
+
+    /// Get the accented look-alike of a character, or the character itself
+    pub fn accent(c: char) -> char {
+        match c {
+            'A' => 'Å',
+            'B' => 'Ɓ',
+            'C' => 'Ç',
+            'D' => 'Đ',
+            'E' => 'É',
+            'F' => 'Ƒ',
+            'G' => 'Ĝ',
+            'H' => 'Ĥ',
+            'I' => 'Î',
+            'J' => 'Ĵ',
+            'K' => 'Ķ',
+            'L' => 'Ļ',
+            'M' => 'Ṁ',
+            'N' => 'Ñ',
+            'O' => 'Ö',
+            'P' => 'Þ',
+            'Q' => 'Ǫ',
+            'R' => 'Ŕ',
+            'S' => 'Š',
+            'T' => 'Ŧ',
+            'U' => 'Û',
+            'V' => 'Ṽ',
+            'W' => 'Ŵ',
+            'X' => 'Ẋ',
+            'Y' => 'Ý',
+            'Z' => 'Ž',
+            'a' => 'å',
+            'b' => 'ƀ',
+            'c' => 'ç',
+            'd' => 'đ',
+            'e' => 'é',
+            'f' => 'ƒ',
+            'g' => 'ĝ',
+            'h' => 'ĥ',
+            'i' => 'î',
+            'j' => 'ĵ',
+            'k' => 'ķ',
+            'l' => 'ļ',
+            'm' => 'ṁ',
+            'n' => 'ñ',
+            'o' => 'ö',
+            'p' => 'þ',
+            'q' => 'ǫ',
+            'r' => 'ŕ',
+            's' => 'š',
+            't' => 'ŧ',
+            'u' => 'û',
+            'v' => 'ṽ',
+            'w' => 'ŵ',
+            'x' => 'ẋ',
+            'y' => 'ý',
+            'z' => 'ž',
+            _ => c,
+        }
+    }
+
+    /// Transform text, keeping line endings in place
+    pub fn transform(&self, text: &str) -> String {
+        self.transform_spans([(text, Attrs::new())])
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    }
+
+    /// Transform styled spans (pairs of text and attributes), keeping line endings in place
+    ///
+    /// Brackets and padding take on the attributes of the span they are adjacent to.
+    pub fn transform_spans<'s, 'a, I>(&self, spans: I) -> Vec<(String, Attrs<'a>)>
+    where
+        I: IntoIterator<Item = (&'s str, Attrs<'a>)>,
+    {
+        let mut transformed: Vec<(String, Attrs<'a>)> = Vec::new();
+        // Number of characters in the current line, None if no line is open
+        let mut line_chars: Option<usize> = None;
+        for (text, attrs) in spans {
+            let mut string = String::with_capacity(text.len() * 2);
+            for c in text.chars() {
+                if c == '\n' || c == '\r' {
+                    if let Some(count) = line_chars.take() {
+                        self.close_line(&mut string, count);
+                    }
+                    string.push(c);
+                    continue;
+                }
+
+                let count = line_chars.get_or_insert_with(|| {
+                    if self.brackets {
+                        string.push('[');
+                    }
+                    0
+                });
+                *count += 1;
+                string.push(if self.accents { Self::accent(c) } else { c });
+            }
+            transformed.push((string, attrs));
+        }
+
+        if let Some(count) = line_chars {
+            if let Some((string, _)) = transformed.last_mut() {
+                self.close_line(string, count);
+            }
+        }
+
+        transformed
+    }
+
+    fn close_line(&self, string: &mut String, count: usize) {
+        let padding = libm::ceilf(count as f32 * self.expansion.max(0.0)) as usize;
+        string.extend(core::iter::repeat(Self::PADDING).take(padding));
+        if self.brackets {
+            string.push(']');
+        }
+    }
+}
+
+#[test]
+fn test_pseudo_locale() {
+    let pseudo = PseudoLocale::default();
+    assert_eq!(pseudo.transform("Hello"), "[Ĥéļļö~~]");
+    assert_eq!(pseudo.transform("ab\r\n\ncd"), "[åƀ~]\r\n\n[çđ~]");
+    assert_eq!(pseudo.transform(""), "");
+
+    let plain = PseudoLocale {
+        accents: false,
+        brackets: false,
+        expansion: 0.0,
+    };
+    assert_eq!(plain.transform("Hello\nWorld"), "Hello\nWorld");
+
+    let spans = pseudo.transform_spans([("Hello, ", Attrs::new()), ("world\nAgain", Attrs::new())]);
+    let joined: String = spans.iter().map(|(text, _)| text.as_str()).collect();
+    assert_eq!(joined, "[Ĥéļļö, ŵöŕļđ~~~~]\n[Åĝåîñ~~]");
+}
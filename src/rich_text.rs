@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::borrow::Cow;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Attrs, ParagraphAttrs};
+
+/// Text of a [`RichText`] piece with its attributes
+type Span<'a> = (Cow<'a, str>, Attrs<'a>);
+
+/// Builder of styled text, made of paragraphs of `(text, Attrs)` pieces and set on a
+/// [`crate::Buffer`] with [`crate::Buffer::set_rich`]
+///
+/// Pieces are appended in order, so there are no byte ranges to get wrong. Newlines in the text
+/// of a piece start new lines of the same paragraph.
+///
+/// ```
+/// # use cosmic_text::{Align, Attrs, Buffer, FontSystem, Metrics, ParagraphAttrs, RichText};
+/// # use cosmic_text::{Shaping, Weight};
+/// # let mut font_system = FontSystem::new();
+/// let mut buffer = Buffer::new_empty(Metrics::new(32.0, 44.0));
+/// let rich_text = RichText::new(Attrs::new())
+///     .paragraph(ParagraphAttrs::new().align(Align::Center))
+///     .styled("Title", |attrs| attrs.weight(Weight::BOLD))
+///     .paragraph(ParagraphAttrs::new())
+///     .text("Body with ")
+///     .styled("bold", |attrs| attrs.weight(Weight::BOLD))
+///     .text(" text");
+/// buffer.set_rich(&mut font_system, &rich_text, Shaping::Advanced);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RichText<'a> {
+    default_attrs: Attrs<'a>,
+    paragraphs: Vec<(Vec<Span<'a>>, ParagraphAttrs)>,
+}
+
+impl<'a> RichText<'a> {
+    /// Create empty rich text, with `default_attrs` used by [`RichText::text`] and for empty
+    /// lines
+    pub fn new(default_attrs: Attrs<'a>) -> Self {
+        Self {
+            default_attrs,
+            paragraphs: Vec::new(),
+        }
+    }
+
+    /// Get the attributes used by default
+    pub fn default_attrs(&self) -> Attrs<'a> {
+        self.default_attrs
+    }
+
+    /// Start a new paragraph with [`ParagraphAttrs`] for all of its lines
+    ///
+    /// Text appended before the first paragraph is started goes in a paragraph with default
+    /// [`ParagraphAttrs`].
+    pub fn paragraph(mut self, paragraph_attrs: ParagraphAttrs) -> Self {
+        self.paragraphs.push((Vec::new(), paragraph_attrs));
+        self
+    }
+
+    /// Append text with the default attributes
+    pub fn text(self, text: impl Into<Cow<'a, str>>) -> Self {
+        let attrs = self.default_attrs;
+        self.span(text, attrs)
+    }
+
+    /// Append text with attributes derived from the default attributes, so that it only has to
+    /// set what differs from them
+    pub fn styled(
+        self,
+        text: impl Into<Cow<'a, str>>,
+        f: impl FnOnce(Attrs<'a>) -> Attrs<'a>,
+    ) -> Self {
+        let attrs = f(self.default_attrs);
+        self.span(text, attrs)
+    }
+
+    /// Append text with its own attributes
+    pub fn span(mut self, text: impl Into<Cow<'a, str>>, attrs: Attrs<'a>) -> Self {
+        if self.paragraphs.is_empty() {
+            self.paragraphs.push((Vec::new(), ParagraphAttrs::new()));
+        }
+        if let Some((spans, _)) = self.paragraphs.last_mut() {
+            spans.push((text.into(), attrs));
+        }
+        self
+    }
+
+    /// Check if there is no text
+    pub fn is_empty(&self) -> bool {
+        self.paragraphs
+            .iter()
+            .all(|(spans, _)| spans.iter().all(|(text, _)| text.is_empty()))
+    }
+
+    /// Iterate over the paragraphs, each with its pieces and [`ParagraphAttrs`], in the form
+    /// taken by [`crate::Buffer::set_rich_paragraphs`]
+    pub fn paragraphs(
+        &self,
+    ) -> impl Iterator<Item = (impl Iterator<Item = (&str, Attrs<'a>)> + '_, ParagraphAttrs)> + '_
+    {
+        self.paragraphs.iter().map(|(spans, paragraph_attrs)| {
+            (
+                spans.iter().map(|(text, attrs)| (text.as_ref(), *attrs)),
+                *paragraph_attrs,
+            )
+        })
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::LayoutGlyph;
+
+/// Size of ruby text relative to the size of its base
+pub const RUBY_SCALE: f32 = 0.5;
+
+/// Ruby text annotating part of a line, like furigana over kanji, see
+/// [`crate::BufferLine::set_ruby`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ruby {
+    /// Byte range of the base text in the line
+    pub range: Range<usize>,
+    /// Text of the annotation, shaped with the attributes at the start of the base
+    pub text: String,
+}
+
+impl Ruby {
+    /// Create an annotation of the base text in `range`
+    pub fn new<T: Into<String>>(range: Range<usize>, text: T) -> Self {
+        Self {
+            range,
+            text: text.into(),
+        }
+    }
+}
+
+/// Ruby text laid out with its base, see [`crate::LayoutRun::ruby`]
+///
+/// Annotations are centered over their base, or beside it on the right in vertical lines, on the
+/// first line their base is laid out on.
+#[derive(Clone, Debug)]
+pub struct RubyRun {
+    /// Index of the annotation in [`crate::BufferLine::ruby`]
+    pub ruby_i: usize,
+    /// Glyphs of the annotation, placed relative to the line like the glyphs of the line and drawn
+    /// with the same [`crate::LayoutRun::line_y`]
+    pub glyphs: Vec<LayoutGlyph>,
+}
@@ -13,9 +13,14 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use crate::fallback::FontFallbackIter;
 use crate::{
-    math, Align, AttrsList, CacheKeyFlags, Color, Font, FontSystem, LayoutGlyph, LayoutLine,
-    ShapePlanCache, Wrap,
+    kashida, line_break::line_breaks, math, Align, AttrsList, CacheKeyFlags, Color, Decoration,
+    Direction, FallbackEvent, FamilyOwned, Font, FontSize, FontSystem, LayoutGlyph, LayoutLine,
+    LayoutOptions, LetterSpacing, Oblique, Payload, Placeholder, Shadow, ShapeHook, ShapeHookRun,
+    ShapePlanCache, TextTransform, VerticalMetrics, Weight, WordSpacing, Wrap,
+    DEFAULT_OBLIQUE_ANGLE,
 };
+#[cfg(feature = "optimal-wrap")]
+use crate::{BreakItem, OptimalWrap};
 
 /// The shaping strategy of some text.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -49,6 +54,15 @@ impl Shaping {
         end_run: usize,
         span_rtl: bool,
     ) {
+        // Placeholders are not shaped, their whole run is replaced by a box
+        let attrs = attrs_list.get_span(start_run);
+        if let Some(placeholder) = attrs.placeholder_opt {
+            if start_run < end_run {
+                glyphs.push(placeholder.glyph(&attrs, start_run, end_run));
+            }
+            return;
+        }
+
         match self {
             #[cfg(feature = "swash")]
             Self::Basic => shape_skip(font_system, glyphs, line, attrs_list, start_run, end_run),
@@ -101,18 +115,20 @@ fn shape_fallback(
     scratch: &mut ShapeBuffer,
     glyphs: &mut Vec<ShapeGlyph>,
     shape_plan_cache: &mut ShapePlanCache,
+    shape_hook: Option<&ShapeHook>,
     font: &Font,
     line: &str,
     attrs_list: &AttrsList,
     start_run: usize,
     end_run: usize,
     span_rtl: bool,
+    vertical_forms: bool,
 ) -> Vec<usize> {
     let run = &line[start_run..end_run];
 
     let font_scale = font.rustybuzz().units_per_em() as f32;
-    let ascent = font.rustybuzz().ascender() as f32 / font_scale;
-    let descent = -font.rustybuzz().descender() as f32 / font_scale;
+    let (ascent, descent) = font.ascent_descent_em(VerticalMetrics::Typographic);
+    let (win_ascent, win_descent) = font.ascent_descent_em(VerticalMetrics::Gdi);
 
     let mut buffer = scratch.rustybuzz_buffer.take().unwrap_or_default();
     buffer.set_direction(if span_rtl {
@@ -120,27 +136,103 @@ fn shape_fallback(
     } else {
         rustybuzz::Direction::LeftToRight
     });
-    buffer.push_str(run);
+    let run_attrs = attrs_list.get_span(start_run);
+    let (shift_scale, baseline_shift) = run_attrs
+        .baseline_shift_opt
+        .map_or((1.0, 0.0), |baseline_shift| {
+            font.baseline_shift_em(baseline_shift)
+        });
+    // Without smcp in the font, small caps are shaped as capitals and scaled down
+    let synthesize_small_caps = run_attrs.small_caps && !font.has_small_caps();
+    let text_transform = run_attrs.text_transform;
+    if synthesize_small_caps || text_transform != TextTransform::None {
+        for (i, c) in run.char_indices() {
+            let prev_opt = line[..start_run + i].chars().next_back();
+            text_transform.apply(c, prev_opt, |c| {
+                if synthesize_small_caps && is_small_cap(c) {
+                    for upper in c.to_uppercase() {
+                        buffer.add(upper, i as u32);
+                    }
+                } else {
+                    buffer.add(c, i as u32);
+                }
+            });
+        }
+    } else {
+        buffer.push_str(run);
+    }
+    if let Some(language) = run_attrs
+        .lang_opt
+        .and_then(|lang| lang.parse::<rustybuzz::Language>().ok())
+    {
+        buffer.set_language(language);
+    }
     buffer.guess_segment_properties();
+    if let Some(script) = run_attrs.script_opt.and_then(rustybuzz_script) {
+        buffer.set_script(script);
+    }
 
     let rtl = matches!(buffer.direction(), rustybuzz::Direction::RightToLeft);
     assert_eq!(rtl, span_rtl);
 
-    let shape_plan = shape_plan_cache.get(font, &buffer);
-    let glyph_buffer = rustybuzz::shape_with_plan(font.rustybuzz(), shape_plan, buffer);
-    let glyph_infos = glyph_buffer.glyph_infos();
-    let glyph_positions = glyph_buffer.glyph_positions();
+    let instance_opt = run_attrs
+        .font_instance_opt
+        .and_then(|font_instance| font.find_instance(font_instance));
+    let instance_face_opt = instance_opt.and_then(|instance| font.rustybuzz_instance(instance));
+    let shape_plan = shape_plan_cache.get(
+        font,
+        instance_opt,
+        &buffer,
+        run_attrs.small_caps && font.has_small_caps(),
+        run_attrs.vertical_forms || vertical_forms,
+        run_attrs.font_features,
+    );
+    let glyph_buffer = rustybuzz::shape_with_plan(
+        instance_face_opt.as_ref().unwrap_or(font.rustybuzz()),
+        shape_plan,
+        buffer,
+    );
+    let hook_glyphs = shape_hook.map(|shape_hook| {
+        let mut glyph_infos = glyph_buffer.glyph_infos().to_vec();
+        let mut glyph_positions = glyph_buffer.glyph_positions().to_vec();
+        let hook_run = ShapeHookRun {
+            font,
+            line,
+            range: start_run..end_run,
+            rtl,
+            attrs: run_attrs,
+        };
+        shape_hook(&hook_run, &mut glyph_infos, &mut glyph_positions);
+        (glyph_infos, glyph_positions)
+    });
+    let (glyph_infos, glyph_positions) = match &hook_glyphs {
+        Some((glyph_infos, glyph_positions)) => (&glyph_infos[..], &glyph_positions[..]),
+        None => (glyph_buffer.glyph_infos(), glyph_buffer.glyph_positions()),
+    };
+
+    let mark_offsets = fallback_mark_offsets(font, run, glyph_infos, glyph_positions, rtl);
 
     let mut missing = Vec::new();
     glyphs.reserve(glyph_infos.len());
     let glyph_start = glyphs.len();
-    for (info, pos) in glyph_infos.iter().zip(glyph_positions.iter()) {
-        let x_advance = pos.x_advance as f32 / font_scale;
-        let y_advance = pos.y_advance as f32 / font_scale;
-        let x_offset = pos.x_offset as f32 / font_scale;
-        let y_offset = pos.y_offset as f32 / font_scale;
-
+    for (glyph_i, (info, pos)) in glyph_infos.iter().zip(glyph_positions.iter()).enumerate() {
         let start_glyph = start_run + info.cluster as usize;
+        let (mark_x_offset, mark_y_offset) = mark_offsets.get(glyph_i).copied().unwrap_or_default();
+
+        let synthetic_small_caps = synthesize_small_caps
+            && line[start_glyph..].chars().next().map_or(false, |c| {
+                let prev_opt = line[..start_glyph].chars().next_back();
+                is_small_cap(transformed_char(text_transform, c, prev_opt))
+            });
+        let font_size_scale = if synthetic_small_caps {
+            SMALL_CAPS_SCALE * shift_scale
+        } else {
+            shift_scale
+        };
+        let x_advance = font_size_scale * pos.x_advance as f32 / font_scale;
+        let y_advance = font_size_scale * pos.y_advance as f32 / font_scale;
+        let x_offset = font_size_scale * (pos.x_offset + mark_x_offset) as f32 / font_scale;
+        let y_offset = font_size_scale * (pos.y_offset + mark_y_offset) as f32 / font_scale;
 
         if info.glyph_id == 0 {
             missing.push(start_glyph);
@@ -154,15 +246,33 @@ fn shape_fallback(
             y_advance,
             x_offset,
             y_offset,
-            ascent,
-            descent,
+            ascent: ascent * shift_scale,
+            descent: descent * shift_scale,
+            win_ascent: win_ascent * shift_scale,
+            win_descent: win_descent * shift_scale,
             font_monospace_em_width: font.monospace_em_width(),
             font_id: font.id(),
+            font_instance_opt: instance_opt,
             glyph_id: info.glyph_id.try_into().expect("failed to cast glyph ID"),
             //TODO: color should not be related to shaping
             color_opt: attrs.color_opt,
+            background_color_opt: attrs.background_color_opt,
+            shadow_opt: attrs.shadow_opt,
+            font_size_opt: attrs.font_size_opt.map(|FontSize(font_size)| font_size),
+            clamp_line_height: attrs.clamp_line_height,
+            decoration: attrs.decoration,
             metadata: attrs.metadata,
+            payload_opt: attrs.payload_opt.cloned(),
+            fill_id: attrs.fill_id,
             cache_key_flags: attrs.cache_key_flags,
+            oblique_opt: attrs.oblique_opt,
+            kashida_opt: None,
+            script: Script::Unknown,
+            tabular_em_width: None,
+            font_size_scale,
+            synthetic_small_caps,
+            baseline_shift,
+            placeholder_opt: None,
         });
     }
 
@@ -191,12 +301,187 @@ fn shape_fallback(
         }
     }
 
+    // Mark digits that can be laid out with tabular widths
+    if let Some(digit_em_width) = font.digit_em_width() {
+        for glyph in glyphs[glyph_start..].iter_mut() {
+            if is_ascii_digit_cluster(&line[glyph.start..glyph.end]) {
+                glyph.tabular_em_width = Some(digit_em_width);
+            }
+        }
+    }
+
+    // Mark clusters that can be elongated with kashida when justifying
+    if rtl {
+        let tatweel = font
+            .rustybuzz()
+            .glyph_index(kashida::TATWEEL)
+            .and_then(|id| {
+                let advance = font.rustybuzz().glyph_hor_advance(id)?;
+                Some((id.0, advance as f32 / font_scale))
+            });
+        if let Some(tatweel) = tatweel {
+            for offset in kashida::opportunities(run) {
+                let next_start = start_run + offset;
+                if glyphs[glyph_start..]
+                    .iter()
+                    .any(|glyph| glyph.start == next_start)
+                {
+                    for glyph in glyphs[glyph_start..]
+                        .iter_mut()
+                        .filter(|glyph| glyph.end == next_start)
+                    {
+                        glyph.kashida_opt = Some(tatweel);
+                    }
+                }
+            }
+        }
+    }
+
     // Restore the buffer to save an allocation.
     scratch.rustybuzz_buffer = Some(glyph_buffer.clear());
 
     missing
 }
 
+/// Find offsets in font units for combining marks the font does not position, centering them
+/// above or below their base glyph using glyph bounding boxes
+///
+/// Returns no offsets if there are no such marks. Marks are recognized as glyphs without an
+/// advance or offset that follow a base glyph in the same cluster, and are stacked on top of
+/// or below earlier marks on the same base. Only marks on Latin, Greek, and Cyrillic letters
+/// are positioned, as other scripts often rely on marks drawn in place by the font.
+fn fallback_mark_offsets(
+    font: &Font,
+    run: &str,
+    glyph_infos: &[rustybuzz::GlyphInfo],
+    glyph_positions: &[rustybuzz::GlyphPosition],
+    rtl: bool,
+) -> Vec<(i32, i32)> {
+    let face = font.rustybuzz();
+    let y_gap = face.units_per_em() / 16;
+
+    // Pen position of each glyph, in visual order
+    let mut pens = Vec::with_capacity(glyph_positions.len());
+    let mut pen = 0;
+    for pos in glyph_positions {
+        pens.push(pen);
+        pen += pos.x_advance;
+    }
+
+    struct Base {
+        cluster: u32,
+        center_x: i32,
+        top: i32,
+        bottom: i32,
+    }
+
+    let mut offsets = Vec::new();
+    let mut base_opt: Option<Base> = None;
+    for logical_i in 0..glyph_infos.len() {
+        let i = if rtl {
+            glyph_infos.len() - 1 - logical_i
+        } else {
+            logical_i
+        };
+        let info = &glyph_infos[i];
+        let pos = &glyph_positions[i];
+        let glyph_id = rustybuzz::ttf_parser::GlyphId(info.glyph_id as u16);
+        let bbox_opt = face.glyph_bounding_box(glyph_id);
+
+        let same_cluster = base_opt
+            .as_ref()
+            .map_or(false, |base| base.cluster == info.cluster);
+        if !same_cluster || pos.x_advance != 0 {
+            let base_script = run[info.cluster as usize..]
+                .chars()
+                .next()
+                .map(|c| c.script());
+            if !matches!(
+                base_script,
+                Some(Script::Latin | Script::Greek | Script::Cyrillic)
+            ) {
+                base_opt = None;
+                continue;
+            }
+            base_opt = bbox_opt.map(|bbox| Base {
+                cluster: info.cluster,
+                center_x: pens[i] + (i32::from(bbox.x_min) + i32::from(bbox.x_max)) / 2,
+                top: bbox.y_max.into(),
+                bottom: bbox.y_min.into(),
+            });
+            continue;
+        }
+
+        let unpositioned = info.glyph_id != 0 && pos.x_offset == 0 && pos.y_offset == 0;
+        let mark_class = !matches!(
+            face.tables().gdef.and_then(|gdef| gdef.glyph_class(glyph_id)),
+            Some(class) if class != rustybuzz::ttf_parser::gdef::GlyphClass::Mark
+        );
+        let (Some(base), Some(bbox), true, true) =
+            (base_opt.as_mut(), bbox_opt, unpositioned, mark_class)
+        else {
+            continue;
+        };
+
+        let (x_min, x_max) = (i32::from(bbox.x_min), i32::from(bbox.x_max));
+        let (y_min, y_max) = (i32::from(bbox.y_min), i32::from(bbox.y_max));
+        let x_offset = base.center_x - (pens[i] + (x_min + x_max) / 2);
+        let y_offset = if y_min + y_max >= 0 {
+            // Above the base
+            let y_offset = base.top + y_gap - y_min;
+            base.top = y_max + y_offset;
+            y_offset
+        } else {
+            // Below the base
+            let y_offset = base.bottom - y_gap - y_max;
+            base.bottom = y_min + y_offset;
+            y_offset
+        };
+
+        if offsets.is_empty() {
+            offsets.resize(glyph_infos.len(), (0, 0));
+        }
+        offsets[i] = (x_offset, y_offset);
+    }
+    offsets
+}
+
+/// Size of synthesized small caps relative to capitals
+const SMALL_CAPS_SCALE: f32 = 0.7;
+
+/// Get the first character that `c` is shown as with a [`TextTransform`]
+fn transformed_char(text_transform: TextTransform, c: char, prev_opt: Option<char>) -> char {
+    let mut shown_opt = None;
+    text_transform.apply(c, prev_opt, |shown| {
+        shown_opt = shown_opt.or(Some(shown));
+    });
+    shown_opt.unwrap_or(c)
+}
+
+/// Check if a character is shaped as a scaled down capital when synthesizing small caps
+fn is_small_cap(c: char) -> bool {
+    c.is_lowercase() && !c.to_uppercase().eq(core::iter::once(c))
+}
+
+fn is_ascii_digit_cluster(cluster: &str) -> bool {
+    matches!(cluster.as_bytes(), [b'0'..=b'9'])
+}
+
+/// Get the script of rustybuzz for a script, from its ISO 15924 code
+fn rustybuzz_script(script: Script) -> Option<rustybuzz::Script> {
+    rustybuzz::Script::from_iso15924_tag(rustybuzz::ttf_parser::Tag::from_bytes_lossy(
+        script.short_name().as_bytes(),
+    ))
+}
+
+/// Check if a character may hang past the end of a line, like stops, commas, and closing quotes
+fn is_hanging_punctuation(c: char) -> bool {
+    matches!(
+        c,
+        '.' | ',' | '\'' | '"' | '’' | '”' | '»' | '›' | '、' | '。' | '，' | '．' | '｡' | '､'
+    )
+}
+
 fn shape_run(
     scratch: &mut ShapeBuffer,
     glyphs: &mut Vec<ShapeGlyph>,
@@ -213,8 +498,13 @@ fn shape_run(
         scripts.clear();
         scripts
     };
-    for c in line[start_run..end_run].chars() {
-        match c.script() {
+    let attrs = attrs_list.get_span(start_run);
+    // Fallback fonts are chosen for the script of the span, if it is set
+    let run_scripts = line[start_run..end_run]
+        .chars()
+        .map(|c| attrs.script_opt.unwrap_or_else(|| c.script()));
+    for script in run_scripts {
+        match script {
             Script::Common | Script::Inherited | Script::Latin | Script::Unknown => (),
             script => {
                 if !scripts.contains(&script) {
@@ -226,9 +516,9 @@ fn shape_run(
 
     log::trace!("      Run {:?}: '{}'", &scripts, &line[start_run..end_run],);
 
-    let attrs = attrs_list.get_span(start_run);
-
     let fonts = font_system.get_font_matches(attrs);
+    let shape_hook = font_system.shape_hook().cloned();
+    let vertical_forms = font_system.vertical_forms;
 
     let default_families = [&attrs.family];
     let mut font_iter = FontFallbackIter::new(
@@ -236,6 +526,7 @@ fn shape_run(
         &fonts,
         &default_families,
         &scripts,
+        attrs.lang_opt,
         &line[start_run..end_run],
     );
 
@@ -246,12 +537,14 @@ fn shape_run(
         scratch,
         glyphs,
         font_iter.shape_plan_cache(),
+        shape_hook.as_ref(),
         &font,
         line,
         attrs_list,
         start_run,
         end_run,
         span_rtl,
+        vertical_forms,
     );
 
     //TODO: improve performance!
@@ -270,12 +563,14 @@ fn shape_run(
             scratch,
             &mut fb_glyphs,
             font_iter.shape_plan_cache(),
+            shape_hook.as_ref(),
             &font,
             line,
             attrs_list,
             start_run,
             end_run,
             span_rtl,
+            vertical_forms,
         );
 
         // Insert all matching glyphs
@@ -336,6 +631,47 @@ fn shape_run(
     // Debug missing font fallbacks
     font_iter.check_missing(&line[start_run..end_run]);
 
+    // Record the clusters that the first font does not have
+    if let Some(fallback_log) = font_system.fallback_log_mut() {
+        let mut last_start_opt = None;
+        for glyph in &glyphs[glyph_start..] {
+            let fell_back = glyph.font_id != font.id() || missing.contains(&glyph.start);
+            if !fell_back || glyph.placeholder_opt.is_some() || last_start_opt == Some(glyph.start)
+            {
+                continue;
+            }
+            last_start_opt = Some(glyph.start);
+            if let Some(codepoint) = line[glyph.start..].chars().next() {
+                fallback_log.push(FallbackEvent {
+                    codepoint,
+                    family: FamilyOwned::new(attrs.family),
+                    font_id: font.id(),
+                    fallback_id_opt: (glyph.font_id != font.id()).then_some(glyph.font_id),
+                });
+            }
+        }
+    }
+
+    // Synthesize bold for glyphs of fonts lighter than the text
+    if font_system.synthesize_bold() && attrs.weight >= Weight::SEMIBOLD {
+        let mut light_opt: Option<(fontdb::ID, bool)> = None;
+        for glyph in glyphs[glyph_start..].iter_mut() {
+            let light = match light_opt {
+                Some((font_id, light)) if font_id == glyph.font_id => light,
+                _ => {
+                    let light = font_system
+                        .get_font(glyph.font_id)
+                        .map_or(false, |font| font.weight() < Weight::SEMIBOLD);
+                    light_opt = Some((glyph.font_id, light));
+                    light
+                }
+            };
+            if light {
+                glyph.cache_key_flags |= CacheKeyFlags::FAKE_BOLD;
+            }
+        }
+    }
+
     /*
     for glyph in glyphs.iter() {
         log::trace!("'{}': {}, {}, {}, {}", &line[glyph.start..glyph.end], glyph.x_advance, glyph.y_advance, glyph.x_offset, glyph.y_offset);
@@ -357,31 +693,38 @@ fn shape_run_cached(
     end_run: usize,
     span_rtl: bool,
 ) {
-    use crate::{AttrsOwned, ShapeRunKey};
+    use crate::{AttrsOwned, ShapeRunCacheMode, ShapeRunKey};
+
+    if font_system.shape_run_cache_mode == ShapeRunCacheMode::Disabled {
+        shape_run(
+            scratch,
+            glyphs,
+            font_system,
+            line,
+            attrs_list,
+            start_run,
+            end_run,
+            span_rtl,
+        );
+        return;
+    }
 
     let run_range = start_run..end_run;
     let mut key = ShapeRunKey {
         text: line[run_range.clone()].to_string(),
         default_attrs: AttrsOwned::new(attrs_list.defaults()),
         attrs_spans: Vec::new(),
+        vertical_forms: font_system.vertical_forms,
     };
-    for (attrs_range, attrs) in attrs_list.spans.overlapping(&run_range) {
-        if attrs == &key.default_attrs {
-            // Skip if attrs matches default attrs
-            continue;
-        }
-        let start = max(attrs_range.start, start_run)
-            .checked_sub(start_run)
-            .unwrap_or(0);
-        let end = min(attrs_range.end, end_run)
-            .checked_sub(start_run)
-            .unwrap_or(0);
-        if end > start {
-            let range = start..end;
-            key.attrs_spans.push((range, attrs.clone()));
-        }
+    // Spans are resolved with the layers of the list, which change the attributes too
+    for (attrs_range, attrs) in attrs_list.resolved_spans(&run_range) {
+        let range = attrs_range.start - start_run..attrs_range.end - start_run;
+        key.attrs_spans.push((range, attrs));
     }
-    if let Some(cache_glyphs) = font_system.shape_run_cache.get(&key) {
+    if let Some(cache_glyphs) = font_system
+        .active_shape_run_cache()
+        .and_then(|cache| cache.get(&key))
+    {
         for mut glyph in cache_glyphs.iter().cloned() {
             // Adjust glyph start and end to match run position
             glyph.start += start_run;
@@ -409,7 +752,9 @@ fn shape_run_cached(
         glyph.start -= start_run;
         glyph.end -= start_run;
     }
-    font_system.shape_run_cache.insert(key, cache_glyphs);
+    if let Some(cache) = font_system.active_shape_run_cache() {
+        cache.insert(key, cache_glyphs);
+    }
 }
 
 #[cfg(feature = "swash")]
@@ -425,11 +770,17 @@ fn shape_skip(
     let fonts = font_system.get_font_matches(attrs);
 
     let default_families = [&attrs.family];
-    let mut font_iter = FontFallbackIter::new(font_system, &fonts, &default_families, &[], "");
+    let mut font_iter =
+        FontFallbackIter::new(font_system, &fonts, &default_families, &[], None, "");
 
     let font = font_iter.next().expect("no default font found");
     let font_id = font.id();
+    let fake_bold = font_system.synthesize_bold()
+        && attrs.weight >= Weight::SEMIBOLD
+        && font.weight() < Weight::SEMIBOLD;
     let font_monospace_em_width = font.monospace_em_width();
+    let font_digit_em_width = font.digit_em_width();
+    let (win_ascent, win_descent) = font.ascent_descent_em(VerticalMetrics::Gdi);
     let font = font.as_swash();
 
     let charmap = font.charmap();
@@ -439,14 +790,22 @@ fn shape_skip(
     let ascent = metrics.ascent / f32::from(metrics.units_per_em);
     let descent = metrics.descent / f32::from(metrics.units_per_em);
 
+    let mut prev_opt = line[..start_run].chars().next_back();
     glyphs.extend(
         line[start_run..end_run]
             .chars()
             .enumerate()
             .map(|(i, codepoint)| {
-                let glyph_id = charmap.map(codepoint);
-                let x_advance = glyph_metrics.advance_width(glyph_id);
                 let attrs = attrs_list.get_span(i);
+                let codepoint =
+                    transformed_char(attrs.text_transform, codepoint, prev_opt.replace(codepoint));
+                // Hide soft hyphens like shaping does, with an empty space glyph
+                let (glyph_id, x_advance) = if codepoint == SOFT_HYPHEN {
+                    (charmap.map(' '), 0.0)
+                } else {
+                    let glyph_id = charmap.map(codepoint);
+                    (glyph_id, glyph_metrics.advance_width(glyph_id))
+                };
 
                 ShapeGlyph {
                     start: i,
@@ -457,12 +816,34 @@ fn shape_skip(
                     y_offset: 0.0,
                     ascent,
                     descent,
+                    win_ascent,
+                    win_descent,
                     font_monospace_em_width,
                     font_id,
+                    font_instance_opt: None,
                     glyph_id,
                     color_opt: attrs.color_opt,
+                    background_color_opt: attrs.background_color_opt,
+                    shadow_opt: attrs.shadow_opt,
+                    font_size_opt: attrs.font_size_opt.map(|FontSize(font_size)| font_size),
+                    clamp_line_height: attrs.clamp_line_height,
+                    decoration: attrs.decoration,
                     metadata: attrs.metadata,
-                    cache_key_flags: attrs.cache_key_flags,
+                    payload_opt: attrs.payload_opt.cloned(),
+                    fill_id: attrs.fill_id,
+                    cache_key_flags: if fake_bold {
+                        attrs.cache_key_flags | CacheKeyFlags::FAKE_BOLD
+                    } else {
+                        attrs.cache_key_flags
+                    },
+                    oblique_opt: attrs.oblique_opt,
+                    kashida_opt: None,
+                    script: Script::Unknown,
+                    tabular_em_width: font_digit_em_width.filter(|_| codepoint.is_ascii_digit()),
+                    font_size_scale: 1.0,
+                    synthetic_small_caps: false,
+                    baseline_shift: 0.0,
+                    placeholder_opt: None,
                 }
             }),
     );
@@ -479,16 +860,51 @@ pub struct ShapeGlyph {
     pub y_offset: f32,
     pub ascent: f32,
     pub descent: f32,
+    /// Ascent from the `usWinAscent` of the font, see [`VerticalMetrics::Gdi`]
+    pub win_ascent: f32,
+    /// Descent from the `usWinDescent` of the font, see [`VerticalMetrics::Gdi`]
+    pub win_descent: f32,
     pub font_monospace_em_width: Option<f32>,
     pub font_id: fontdb::ID,
+    /// Named instance of the font, see [`crate::Attrs::font_instance`]
+    pub font_instance_opt: Option<u16>,
     pub glyph_id: u16,
     pub color_opt: Option<Color>,
+    pub background_color_opt: Option<Color>,
+    pub shadow_opt: Option<Shadow>,
+    /// Font size in pixels from [`crate::Attrs::font_size`], applied when laying out
+    pub font_size_opt: Option<f32>,
+    /// Keep the glyph from making its line taller, see [`crate::Attrs::clamp_line_height`]
+    pub clamp_line_height: bool,
+    pub decoration: Decoration,
     pub metadata: usize,
+    pub payload_opt: Option<Payload>,
+    pub fill_id: u64,
     pub cache_key_flags: CacheKeyFlags,
+    /// Skew of synthesized italic, resolved with the bidirectional level when laid out
+    pub oblique_opt: Option<Oblique>,
+    /// Tatweel glyph ID and advance, if a kashida may be inserted after this glyph's cluster
+    /// when justifying
+    pub kashida_opt: Option<(u16, f32)>,
+    /// Script of the glyph's cluster, resolved once the whole word is shaped
+    pub script: Script,
+    /// Advance of the font's widest digit, set if this glyph is an ASCII digit
+    pub tabular_em_width: Option<f32>,
+    /// Factor applied to the font size when rendering, for synthesized small caps
+    ///
+    /// Advances and offsets are already scaled by it.
+    pub font_size_scale: f32,
+    /// Scaled down capital standing for a small capital the font lacks
+    pub synthetic_small_caps: bool,
+    /// Distance the glyph is raised above the baseline, in em of the font size, see
+    /// [`crate::Attrs::baseline_shift`]
+    pub baseline_shift: f32,
+    /// Inline box that the glyph stands for, instead of a glyph of its font
+    pub placeholder_opt: Option<Placeholder>,
 }
 
 impl ShapeGlyph {
-    fn layout(
+    pub(crate) fn layout(
         &self,
         font_size: f32,
         x: f32,
@@ -496,25 +912,217 @@ impl ShapeGlyph {
         w: f32,
         level: unicode_bidi::Level,
     ) -> LayoutGlyph {
+        let shadow = self
+            .shadow_opt
+            .unwrap_or(Shadow::new(Color(0), 0.0, 0.0, 0.0));
+        let placeholder = self.placeholder_opt.unwrap_or_default();
         LayoutGlyph {
             start: self.start,
             end: self.end,
-            font_size,
+            font_size: font_size * self.font_size_scale,
             font_id: self.font_id,
             glyph_id: self.glyph_id,
             x,
             y,
             w,
             level,
-            x_offset: self.x_offset,
-            y_offset: self.y_offset,
+            // Offsets are relative to the glyph's own font size
+            x_offset: self.x_offset / self.font_size_scale,
+            y_offset: (self.y_offset + self.baseline_shift) / self.font_size_scale,
             color_opt: self.color_opt,
             metadata: self.metadata,
+            fill_id: self.fill_id,
             cache_key_flags: self.cache_key_flags,
+            synthetic_small_caps: self.synthetic_small_caps,
+            oblique_angle: self
+                .oblique_opt
+                .map_or(DEFAULT_OBLIQUE_ANGLE, |oblique| oblique.angle(level)),
+            has_shadow: self.shadow_opt.is_some(),
+            shadow_color: shadow.color,
+            shadow_x: shadow.x,
+            shadow_y: shadow.y,
+            shadow_blur: shadow.blur,
+            underline: self.decoration.underline.into(),
+            overline: self.decoration.overline.into(),
+            strikethrough: self.decoration.strikethrough.into(),
+            script_tag: self.script.as_iso15924_tag(),
+            has_placeholder: self.placeholder_opt.is_some(),
+            placeholder_id: placeholder.id,
+            placeholder_width: placeholder.width,
+            placeholder_ascent: placeholder.ascent,
+            placeholder_descent: placeholder.descent,
+            payload_id: self.payload_opt.as_ref().map_or(0, Payload::id),
+            has_font_instance: self.font_instance_opt.is_some(),
+            font_instance: self.font_instance_opt.unwrap_or_default(),
+            has_background_color: self.background_color_opt.is_some(),
+            background_color: self.background_color_opt.unwrap_or(Color(0)),
+        }
+    }
+}
+
+impl ShapeGlyph {
+    /// Scale the glyph from `font_size` to its own font size, if it has one, returning the
+    /// factor applied
+    fn apply_font_size(&mut self, font_size: f32) -> f32 {
+        let Some(glyph_font_size) = self.font_size_opt.take() else {
+            return 1.0;
+        };
+        let scale = glyph_font_size / font_size;
+        self.x_advance *= scale;
+        self.y_advance *= scale;
+        self.x_offset *= scale;
+        self.y_offset *= scale;
+        self.ascent *= scale;
+        self.descent *= scale;
+        self.win_ascent *= scale;
+        self.win_descent *= scale;
+        self.font_size_scale *= scale;
+        self.baseline_shift *= scale;
+        self.tabular_em_width = self.tabular_em_width.map(|em_width| em_width * scale);
+        self.kashida_opt = self
+            .kashida_opt
+            .map(|(glyph_id, x_advance)| (glyph_id, x_advance * scale));
+        scale
+    }
+
+    /// Get the advance in em, using the widest digit of the font for digits if `tabular_digits`
+    /// is set
+    fn advance_em(&self, tabular_digits: bool) -> f32 {
+        match self.tabular_em_width {
+            Some(tabular_em_width) if tabular_digits => tabular_em_width,
+            _ => self.x_advance,
+        }
+    }
+
+    /// Lay out tatweel glyphs filling `width` after this glyph's cluster, for kashida
+    /// justification
+    ///
+    /// Several tatweels are used when one is not wide enough, overlapping so that the stroke
+    /// stays continuous.
+    fn layout_kashida(
+        &self,
+        font_size: f32,
+        width: f32,
+        rtl: bool,
+        x: &mut f32,
+        y: f32,
+        level: unicode_bidi::Level,
+        glyphs: &mut Vec<LayoutGlyph>,
+    ) {
+        let Some((glyph_id, x_advance)) = self.kashida_opt else {
+            return;
+        };
+        let count = if x_advance > 0.0 {
+            math::ceilf(width / (font_size * x_advance)).max(1.0) as usize
+        } else {
+            1
+        };
+        let w = width / count as f32;
+        let tatweel = ShapeGlyph {
+            glyph_id,
+            x_advance,
+            y_advance: 0.0,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            kashida_opt: None,
+            ..self.clone()
+        };
+        for _ in 0..count {
+            if rtl {
+                *x -= w;
+            }
+            glyphs.push(tatweel.layout(font_size, *x, y, w, level));
+            if !rtl {
+                *x += w;
+            }
+        }
+    }
+}
+
+/// Replace clusters with glyphs that no font supports with the [`crate::MissingGlyph`] of the
+/// font system, if it has one, returning the number of such clusters
+fn replace_missing_glyphs(
+    font_system: &mut FontSystem,
+    attrs_list: &AttrsList,
+    glyphs: &mut Vec<ShapeGlyph>,
+) -> usize {
+    let mut missing = 0;
+    let mut i = 0;
+    while i < glyphs.len() {
+        let start = glyphs[i].start;
+        let cluster_end = i + glyphs[i..]
+            .iter()
+            .take_while(|glyph| glyph.start == start)
+            .count();
+        if glyphs[i..cluster_end]
+            .iter()
+            .any(|glyph| glyph.glyph_id == 0 && glyph.placeholder_opt.is_none())
+        {
+            missing += 1;
+            let replacement_opt = font_system.missing_glyph.and_then(|missing_glyph| {
+                missing_glyph_replacement(font_system, attrs_list, missing_glyph, &glyphs[i])
+            });
+            if let Some(replacement) = replacement_opt {
+                glyphs.splice(i..cluster_end, [replacement]);
+                i += 1;
+                continue;
+            }
         }
+        i = cluster_end;
     }
+    missing
+}
+
+/// Create a glyph from `missing_glyph` to replace the cluster of `glyph`, or None if no font has
+/// its character
+fn missing_glyph_replacement(
+    font_system: &mut FontSystem,
+    attrs_list: &AttrsList,
+    missing_glyph: crate::MissingGlyph,
+    glyph: &ShapeGlyph,
+) -> Option<ShapeGlyph> {
+    let (font, glyph_id) = match missing_glyph {
+        crate::MissingGlyph::Char(c) => {
+            let font_matches = font_system.get_font_matches(attrs_list.get_span(glyph.start));
+            let font_ids = core::iter::once(glyph.font_id).chain(font_matches.iter().map(|m| m.id));
+            font_ids
+                .filter_map(|id| font_system.get_font(id))
+                .find_map(|font| {
+                    let glyph_id = font.rustybuzz().glyph_index(c)?;
+                    Some((font, glyph_id.0))
+                })?
+        }
+        crate::MissingGlyph::Glyph(font_id, glyph_id) => (font_system.get_font(font_id)?, glyph_id),
+    };
+
+    let font_scale = font.rustybuzz().units_per_em() as f32;
+    let advance = font
+        .rustybuzz()
+        .glyph_hor_advance(rustybuzz::ttf_parser::GlyphId(glyph_id))?;
+    let (ascent, descent) = font.ascent_descent_em(VerticalMetrics::Typographic);
+    let (win_ascent, win_descent) = font.ascent_descent_em(VerticalMetrics::Gdi);
+    Some(ShapeGlyph {
+        x_advance: glyph.font_size_scale * advance as f32 / font_scale,
+        y_advance: 0.0,
+        x_offset: 0.0,
+        y_offset: 0.0,
+        ascent,
+        descent,
+        win_ascent,
+        win_descent,
+        font_monospace_em_width: font.monospace_em_width(),
+        font_id: font.id(),
+        font_instance_opt: None,
+        glyph_id,
+        kashida_opt: None,
+        tabular_em_width: None,
+        ..glyph.clone()
+    })
 }
 
+/// Invisible character that allows a word to wrap with a hyphen
+const SOFT_HYPHEN: char = '\u{AD}';
+
 /// A shaped word (for word wrapping)
 #[derive(Clone, Debug)]
 pub struct ShapeWord {
@@ -522,6 +1130,14 @@ pub struct ShapeWord {
     pub glyphs: Vec<ShapeGlyph>,
     pub x_advance: f32,
     pub y_advance: f32,
+    /// Number of clusters that no font supports, see [`crate::MissingGlyph`]
+    pub missing: usize,
+    /// Hyphen shown at the end of a line that wraps after this word, set for all but the last
+    /// part of a word split at hyphenation points
+    pub hyphen_opt: Option<ShapeGlyph>,
+    /// Advance in em of the punctuation at the end of the word, which may hang past the end of a
+    /// line, see [`crate::Buffer::set_hanging_punctuation`]
+    pub hang: f32,
 }
 
 impl ShapeWord {
@@ -603,6 +1219,66 @@ impl ShapeWord {
             );
         }
 
+        let missing = replace_missing_glyphs(font_system, attrs_list, &mut glyphs);
+
+        // Add letter spacing to the first glyph of every cluster, and word spacing to the first
+        // glyph of whitespace clusters
+        let mut last_start_opt = None;
+        for glyph in glyphs.iter_mut() {
+            if last_start_opt.replace(glyph.start) == Some(glyph.start) {
+                continue;
+            }
+            let attrs = attrs_list.get_span(glyph.start);
+            if let Some(LetterSpacing(letter_spacing)) = attrs.letter_spacing_opt {
+                glyph.x_advance += letter_spacing;
+            }
+            let Some(WordSpacing(word_spacing)) = attrs.word_spacing_opt else {
+                continue;
+            };
+            if line[glyph.start..glyph.end]
+                .chars()
+                .all(char::is_whitespace)
+            {
+                glyph.x_advance += word_spacing;
+            }
+        }
+
+        // Only elongate the last joined cluster of a word
+        let kashida_start = glyphs
+            .iter()
+            .filter(|glyph| glyph.kashida_opt.is_some())
+            .map(|glyph| glyph.start)
+            .max();
+        for glyph in glyphs.iter_mut() {
+            if Some(glyph.start) != kashida_start {
+                glyph.kashida_opt = None;
+            }
+        }
+
+        // Resolve the script of each cluster, common characters like punctuation take the script
+        // of the word
+        let is_common = |script: &Script| matches!(script, Script::Common | Script::Inherited);
+        let word_script = word
+            .chars()
+            .map(|c| c.script())
+            .find(|script| !is_common(script))
+            .unwrap_or(Script::Common);
+        for glyph in glyphs.iter_mut() {
+            if let Some(script) = attrs_list.get_span(glyph.start).script_opt {
+                glyph.script = script;
+                continue;
+            }
+            glyph.script = line
+                .get(glyph.start..glyph.end)
+                .and_then(|cluster| {
+                    cluster
+                        .chars()
+                        .map(|c| c.script())
+                        .find(|script| !is_common(script))
+                })
+                .unwrap_or(word_script);
+        }
+
         let mut x_advance = 0.0;
         let mut y_advance = 0.0;
         for glyph in &glyphs {
@@ -610,11 +1286,156 @@ impl ShapeWord {
             y_advance += glyph.y_advance;
         }
 
+        let hang_start = word_range.start + word.trim_end_matches(is_hanging_punctuation).len();
+        let hang = glyphs
+            .iter()
+            .filter(|glyph| glyph.start >= hang_start)
+            .map(|glyph| glyph.x_advance)
+            .sum();
+
         Self {
             blank,
             glyphs,
             x_advance,
             y_advance,
+            missing,
+            hyphen_opt: None,
+            hang,
+        }
+    }
+
+    /// Shape a hyphen to show at `end` if a line wraps there, in the font of the character
+    /// before it
+    fn shape_hyphen(
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        attrs_list: &AttrsList,
+        end: usize,
+        level: unicode_bidi::Level,
+        shaping: Shaping,
+    ) -> Option<ShapeGlyph> {
+        let hyphen_attrs = AttrsList::new(attrs_list.get_span(end.saturating_sub(1)));
+        let hyphen = Self::new_in_buffer(
+            scratch,
+            font_system,
+            "-",
+            &hyphen_attrs,
+            0..1,
+            level,
+            false,
+            shaping,
+        );
+        hyphen.glyphs.into_iter().next().map(|glyph| ShapeGlyph {
+            start: end,
+            end,
+            ..glyph
+        })
+    }
+
+    /// Split a left-to-right word at hyphenation points into parts that lines may wrap between
+    ///
+    /// Points inside of a cluster are skipped, and parts keep the advances of the whole word so
+    /// that kerning is not lost. All but the last part get a hyphen in the font of their last
+    /// character.
+    #[cfg(feature = "hyphenation")]
+    #[allow(clippy::too_many_arguments)]
+    fn split_hyphenated(
+        self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        word_range: Range<usize>,
+        level: unicode_bidi::Level,
+        shaping: Shaping,
+        words: &mut Vec<Self>,
+    ) {
+        let points: Vec<usize> = font_system
+            .hyphenation_breaks(&line[word_range.clone()])
+            .into_iter()
+            .map(|offset| word_range.start + offset)
+            .filter(|point| {
+                !self
+                    .glyphs
+                    .iter()
+                    .any(|glyph| glyph.start < *point && *point < glyph.end)
+            })
+            .collect();
+        if points.is_empty() {
+            words.push(self);
+            return;
+        }
+
+        let mut missing = self.missing;
+        let mut last_hyphen_opt = self.hyphen_opt;
+        let last_hang = self.hang;
+        let mut glyphs = self.glyphs.into_iter().peekable();
+        for end in points.into_iter().map(Some).chain(core::iter::once(None)) {
+            let mut part = Self {
+                blank: false,
+                glyphs: Vec::new(),
+                x_advance: 0.0,
+                y_advance: 0.0,
+                missing: mem::take(&mut missing),
+                hyphen_opt: None,
+                hang: 0.0,
+            };
+            while let Some(glyph) =
+                glyphs.next_if(|glyph| end.map_or(true, |end| glyph.start < end))
+            {
+                part.x_advance += glyph.x_advance;
+                part.y_advance += glyph.y_advance;
+                part.glyphs.push(glyph);
+            }
+            part.hyphen_opt = match end {
+                Some(end) => {
+                    Self::shape_hyphen(scratch, font_system, attrs_list, end, level, shaping)
+                }
+                None => {
+                    part.hang = last_hang;
+                    last_hyphen_opt.take()
+                }
+            };
+            words.push(part);
+        }
+    }
+}
+
+impl ShapeWord {
+    /// Get the width in pixels of the hyphen shown if a line wraps after this word
+    fn hyphen_width(&self, font_size: f32) -> f32 {
+        self.hyphen_opt
+            .as_ref()
+            .map_or(0.0, |hyphen| font_size * hyphen.x_advance)
+    }
+
+    /// Get the width in pixels of the punctuation at the end of this word that may hang past
+    /// the end of a line if `hanging_punctuation` is set
+    fn hang_width(&self, font_size: f32, hanging_punctuation: bool) -> f32 {
+        if hanging_punctuation {
+            font_size * self.hang
+        } else {
+            0.0
+        }
+    }
+
+    /// Get the width of the word in pixels, with tabular widths for digits if `tabular_digits`
+    /// is set
+    fn width(&self, font_size: f32, tabular_digits: bool) -> f32 {
+        if tabular_digits
+            && self
+                .glyphs
+                .iter()
+                .any(|glyph| glyph.tabular_em_width.is_some())
+        {
+            font_size
+                * self
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.advance_em(true))
+                    .sum::<f32>()
+        } else {
+            font_size * self.x_advance
         }
     }
 }
@@ -670,7 +1491,12 @@ impl ShapeSpan {
         let mut words = Vec::new();
 
         let mut start_word = 0;
-        for (end_lb, _) in unicode_linebreak::linebreaks(span) {
+        let breaks = line_breaks(
+            font_system.line_breaker(),
+            span,
+            font_system.line_break_strictness,
+        );
+        for end_lb in breaks {
             let mut start_lb = end_lb;
             for (i, c) in span[start_word..end_lb].char_indices().rev() {
                 // TODO: Not all whitespace characters are linebreakable, e.g. 00A0 (No-break
@@ -684,16 +1510,66 @@ impl ShapeSpan {
                 }
             }
             if start_word < start_lb {
-                words.push(ShapeWord::new_in_buffer(
+                // Split words of scripts without spaces, like Thai, using dictionaries
+                #[allow(unused_mut)]
+                let mut start_dict = start_word;
+                #[cfg(feature = "line-break-dictionary")]
+                for end_dict in
+                    font_system.line_break_dictionary_breaks(&span[start_word..start_lb])
+                {
+                    words.push(ShapeWord::new_in_buffer(
+                        scratch,
+                        font_system,
+                        line,
+                        attrs_list,
+                        (span_range.start + start_dict)..(span_range.start + start_word + end_dict),
+                        level,
+                        false,
+                        shaping,
+                    ));
+                    start_dict = start_word + end_dict;
+                }
+                let word_range = (span_range.start + start_dict)..(span_range.start + start_lb);
+                let mut word = ShapeWord::new_in_buffer(
                     scratch,
                     font_system,
                     line,
                     attrs_list,
-                    (span_range.start + start_word)..(span_range.start + start_lb),
+                    word_range.clone(),
                     level,
                     false,
                     shaping,
-                ));
+                );
+                // Soft hyphens are hidden by shaping, but show a hyphen if a line wraps after
+                // them, only in left-to-right lines
+                if !line_rtl && !level.is_rtl() && line[word_range.clone()].ends_with(SOFT_HYPHEN) {
+                    word.hyphen_opt = ShapeWord::shape_hyphen(
+                        scratch,
+                        font_system,
+                        attrs_list,
+                        word_range.end,
+                        level,
+                        shaping,
+                    );
+                }
+                // Split words at hyphenation points, only in left-to-right lines
+                #[cfg(feature = "hyphenation")]
+                if !line_rtl && !level.is_rtl() {
+                    word.split_hyphenated(
+                        scratch,
+                        font_system,
+                        line,
+                        attrs_list,
+                        word_range,
+                        level,
+                        shaping,
+                        &mut words,
+                    );
+                } else {
+                    words.push(word);
+                }
+                #[cfg(not(feature = "hyphenation"))]
+                words.push(word);
             }
             if start_lb < end_lb {
                 for (i, c) in span[start_lb..end_lb].char_indices() {
@@ -745,6 +1621,13 @@ struct VisualLine {
     ranges: Vec<VlRange>,
     spaces: u32,
     w: f32,
+    /// The line wraps after part of a hyphenated word, and ends with its hyphen
+    hyphen: bool,
+    /// Width of the punctuation hanging past the end of the line, included in `w`
+    hang: f32,
+    /// Space left free at the start and the end of the line, included in `w` while wrapping
+    start_inset: f32,
+    end_inset: f32,
 }
 
 impl ShapeLine {
@@ -766,22 +1649,67 @@ impl ShapeLine {
         )
     }
 
-    /// Shape a line into a set of spans, using a scratch buffer. If [`unicode_bidi::BidiInfo`]
-    /// detects multiple paragraphs, they will be joined.
+    /// Shape a line with a base direction, detected from the first strong character when
+    /// `direction` is None
     ///
     /// # Panics
     ///
-    /// Will panic if `line` contains multiple paragraphs that do not have matching direction
+    /// Will panic if `line` contains more than one paragraph.
+    pub fn new_with_direction(
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        direction: Option<Direction>,
+    ) -> Self {
+        Self::new_in_buffer_with_direction(
+            &mut ShapeBuffer::default(),
+            font_system,
+            line,
+            attrs_list,
+            shaping,
+            direction,
+        )
+    }
+
+    /// Shape a line into a set of spans, using a scratch buffer. If [`unicode_bidi::BidiInfo`]
+    /// detects multiple paragraphs, they will be joined.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `line` contains multiple paragraphs that do not have matching direction
     pub fn new_in_buffer(
         scratch: &mut ShapeBuffer,
         font_system: &mut FontSystem,
         line: &str,
         attrs_list: &AttrsList,
         shaping: Shaping,
+    ) -> Self {
+        Self::new_in_buffer_with_direction(scratch, font_system, line, attrs_list, shaping, None)
+    }
+
+    /// Shape a line into a set of spans with a base direction, using a scratch buffer
+    ///
+    /// The base direction is detected from the first strong character when `direction` is None.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `line` contains multiple paragraphs that do not have matching direction
+    pub fn new_in_buffer_with_direction(
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        direction: Option<Direction>,
     ) -> Self {
         let mut spans = Vec::new();
 
-        let bidi = unicode_bidi::BidiInfo::new(line, None);
+        let default_level = direction.map(|direction| match direction {
+            Direction::LeftToRight => unicode_bidi::Level::ltr(),
+            Direction::RightToLeft => unicode_bidi::Level::rtl(),
+        });
+        let bidi = unicode_bidi::BidiInfo::new(line, default_level);
         let rtl = if bidi.paragraphs.is_empty() {
             false
         } else {
@@ -893,6 +1821,31 @@ impl ShapeLine {
         levels
     }
 
+    /// Get the word a visual line ends with, if it does not end inside of a word
+    fn visual_line_last_word(&self, visual_line: &VisualLine) -> Option<&ShapeWord> {
+        let &(span_index, _, (ending_word, ending_glyph)) = visual_line.ranges.last()?;
+        if ending_glyph != 0 {
+            return None;
+        }
+        self.spans[span_index]
+            .words
+            .get(ending_word.checked_sub(1)?)
+    }
+
+    /// Get the hyphen and its level if a visual line wraps after part of a hyphenated word
+    fn visual_line_hyphen(
+        &self,
+        visual_line: &VisualLine,
+    ) -> Option<(&ShapeGlyph, unicode_bidi::Level)> {
+        let &(span_index, _, (ending_word, ending_glyph)) = visual_line.ranges.last()?;
+        if ending_glyph != 0 {
+            return None;
+        }
+        let span = &self.spans[span_index];
+        let word = span.words.get(ending_word.checked_sub(1)?)?;
+        word.hyphen_opt.as_ref().map(|hyphen| (hyphen, span.level))
+    }
+
     // A modified version of second part of unicode_bidi::bidi_info::visual run
     fn reorder(&self, line_range: &[VlRange]) -> Vec<Range<usize>> {
         let line: Vec<unicode_bidi::Level> = line_range
@@ -957,6 +1910,62 @@ impl ShapeLine {
         runs
     }
 
+    /// Get the number of clusters that no font supports, see [`crate::MissingGlyph`]
+    pub fn missing_glyphs(&self) -> usize {
+        self.spans
+            .iter()
+            .flat_map(|span| span.words.iter())
+            .map(|word| word.missing)
+            .sum()
+    }
+
+    /// Give no advance to the glyphs of the text in `range`, so that other content can be laid
+    /// out in their place, like a drop cap
+    pub(crate) fn clear_advances(&mut self, range: Range<usize>) {
+        for word in self.spans.iter_mut().flat_map(|span| span.words.iter_mut()) {
+            for glyph in word.glyphs.iter_mut() {
+                if glyph.start < range.end && glyph.end > range.start {
+                    word.x_advance -= glyph.x_advance;
+                    glyph.x_advance = 0.0;
+                    glyph.tabular_em_width = None;
+                }
+            }
+        }
+    }
+
+    /// Check if any glyph has its own font size, see [`crate::Attrs::font_size`]
+    pub(crate) fn has_font_sizes(&self) -> bool {
+        self.spans
+            .iter()
+            .flat_map(|span| span.words.iter())
+            .flat_map(|word| word.glyphs.iter())
+            .any(|glyph| glyph.font_size_opt.is_some())
+    }
+
+    /// Scale glyphs with their own font size from `font_size` to it, along with the advances of
+    /// their words
+    ///
+    /// Glyphs are laid out at the font size passed to layout until this is called with the same
+    /// font size, after which they no longer have their own font size.
+    pub fn apply_font_sizes(&mut self, font_size: f32) {
+        for word in self.spans.iter_mut().flat_map(|span| span.words.iter_mut()) {
+            // Hanging punctuation ends the word, so it takes the size of the last glyph
+            let mut last = (0, 1.0);
+            for glyph in word.glyphs.iter_mut() {
+                let scale = glyph.apply_font_size(font_size);
+                if glyph.start >= last.0 {
+                    last = (glyph.start, scale);
+                }
+            }
+            if let Some(hyphen) = &mut word.hyphen_opt {
+                hyphen.apply_font_size(font_size);
+            }
+            word.x_advance = word.glyphs.iter().map(|glyph| glyph.x_advance).sum();
+            word.y_advance = word.glyphs.iter().map(|glyph| glyph.y_advance).sum();
+            word.hang *= last.1;
+        }
+    }
+
     pub fn layout(
         &self,
         font_size: f32,
@@ -988,6 +1997,92 @@ impl ShapeLine {
         layout_lines: &mut Vec<LayoutLine>,
         match_mono_width: Option<f32>,
     ) {
+        self.layout_to_buffer_with_options(
+            scratch,
+            font_size,
+            line_width,
+            wrap,
+            align,
+            layout_lines,
+            &LayoutOptions {
+                match_mono_width,
+                ..LayoutOptions::new()
+            },
+        );
+    }
+
+    /// Lay out the line with [`LayoutOptions`]
+    pub fn layout_with_options(
+        &self,
+        font_size: f32,
+        line_width: f32,
+        wrap: Wrap,
+        align: Option<Align>,
+        options: &LayoutOptions,
+    ) -> Vec<LayoutLine> {
+        let mut lines = Vec::with_capacity(1);
+        self.layout_to_buffer_with_options(
+            &mut ShapeBuffer::default(),
+            font_size,
+            line_width,
+            wrap,
+            align,
+            &mut lines,
+            options,
+        );
+        lines
+    }
+
+    /// Lay out the line with [`LayoutOptions`], using a scratch buffer
+    pub fn layout_to_buffer_with_options(
+        &self,
+        scratch: &mut ShapeBuffer,
+        font_size: f32,
+        line_width: f32,
+        wrap: Wrap,
+        align: Option<Align>,
+        layout_lines: &mut Vec<LayoutLine>,
+        options: &LayoutOptions,
+    ) {
+        self.layout_to_buffer_with_insets(
+            scratch,
+            font_size,
+            line_width,
+            wrap,
+            align,
+            &mut |line_i| {
+                if line_i == 0 {
+                    (options.indent, 0.0)
+                } else {
+                    (options.hanging_indent, 0.0)
+                }
+            },
+            layout_lines,
+            options,
+        );
+    }
+
+    /// Lay out the line with the space left free at the start and the end of each line, given
+    /// by `insets` from the index of the line
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn layout_to_buffer_with_insets(
+        &self,
+        scratch: &mut ShapeBuffer,
+        font_size: f32,
+        line_width: f32,
+        wrap: Wrap,
+        align: Option<Align>,
+        insets: &mut dyn FnMut(usize) -> (f32, f32),
+        layout_lines: &mut Vec<LayoutLine>,
+        options: &LayoutOptions,
+    ) {
+        let LayoutOptions {
+            match_mono_width,
+            tabular_digits,
+            hanging_punctuation,
+            ..
+        } = *options;
+
         // For each visual line a list of  (span index,  and range of words in that span)
         // Note that a BiDi visual line could have multiple spans or parts of them
         // let mut vl_range_of_spans = Vec::with_capacity(1);
@@ -997,6 +2092,359 @@ impl ShapeLine {
             visual_lines
         };
 
+        let wrap_width = if wrap == Wrap::Balanced {
+            self.balanced_width(
+                &mut visual_lines,
+                font_size,
+                line_width,
+                insets,
+                tabular_digits,
+                hanging_punctuation,
+            )
+        } else {
+            line_width
+        };
+        self.wrap_visual_lines(
+            &mut visual_lines,
+            font_size,
+            wrap_width,
+            wrap,
+            insets,
+            tabular_digits,
+            hanging_punctuation,
+        );
+
+        // Lines that wrap after part of a hyphenated word end with its hyphen
+        for visual_line in visual_lines.iter_mut() {
+            if let Some((hyphen, _)) = self.visual_line_hyphen(visual_line) {
+                visual_line.w += font_size * hyphen.x_advance;
+                visual_line.hyphen = true;
+            } else if let Some(word) = self.visual_line_last_word(visual_line) {
+                // Punctuation at the end of the line hangs past it, and is not aligned
+                visual_line.hang = word.hang_width(font_size, hanging_punctuation);
+            }
+        }
+
+        // Create the LayoutLines using the ranges inside visual lines
+        let align = align.unwrap_or({
+            if self.rtl {
+                Align::Right
+            } else {
+                Align::Left
+            }
+        });
+
+        let start_x = if self.rtl { line_width } else { 0.0 };
+
+        let number_of_visual_lines = visual_lines.len();
+        for (index, visual_line) in visual_lines.iter().enumerate() {
+            if visual_line.ranges.is_empty() {
+                continue;
+            }
+            let new_order = self.reorder(&visual_line.ranges);
+            let mut glyphs = Vec::with_capacity(1);
+            let mut x = start_x;
+            let mut y = 0.;
+            let mut max_ascent: f32 = 0.;
+            let mut max_descent: f32 = 0.;
+            let mut max_win_ascent: f32 = 0.;
+            let mut max_win_descent: f32 = 0.;
+            // Ascents and descents of glyphs clamped to their line, used if it has no others
+            let mut clamped_max = [0.0f32; 4];
+            // The insets of a line are not aligned with its glyphs, but move the start and the end
+            // of the space they are aligned in
+            let insets = visual_line.start_inset + visual_line.end_inset;
+            let (line_width, visual_line_w) = (
+                line_width - insets,
+                visual_line.w - insets - visual_line.hang,
+            );
+            let alignment_correction = visual_line.start_inset
+                + match (align, self.rtl) {
+                    (Align::Left, true) => line_width - visual_line_w,
+                    (Align::Left, false) => 0.,
+                    (Align::Right, true) => 0.,
+                    (Align::Right, false) => line_width - visual_line_w,
+                    (Align::Center, _) => (line_width - visual_line_w) / 2.0,
+                    (Align::End, _) => line_width - visual_line_w,
+                    (Align::Justified, _) => 0.,
+                };
+
+            if self.rtl {
+                x -= alignment_correction;
+            } else {
+                x += alignment_correction;
+            }
+
+            // TODO: Only certain `is_whitespace` chars are typically expanded but this is what is
+            // currently used to compute `visual_line.spaces`.
+            //
+            // https://www.unicode.org/reports/tr14/#Introduction
+            // > When expanding or compressing interword space according to common
+            // > typographical practice, only the spaces marked by U+0020 SPACE and U+00A0
+            // > NO-BREAK SPACE are subject to compression, and only spaces marked by U+0020
+            // > SPACE, U+00A0 NO-BREAK SPACE, and occasionally spaces marked by U+2009 THIN
+            // > SPACE are subject to expansion. All other space characters normally have
+            // > fixed width.
+            //
+            // (also some spaces aren't followed by potential linebreaks but they could
+            //  still be expanded)
+
+            // Arabic words that can be elongated with kashida, at most one cluster per word
+            let kashidas = visual_line
+                .ranges
+                .iter()
+                .map(
+                    |&(span_index, (starting_word, _), (ending_word, ending_glyph))| {
+                        let span = &self.spans[span_index];
+                        span.words[starting_word..ending_word + usize::from(ending_glyph != 0)]
+                            .iter()
+                            .filter(|word| word.glyphs.iter().any(|g| g.kashida_opt.is_some()))
+                            .count()
+                    },
+                )
+                .sum::<usize>();
+
+            // Amount of extra width added to each blank space, or to each kashida if the line
+            // has any, within a line.
+            let (justification_expansion, kashida_expansion) = if matches!(align, Align::Justified)
+                // Don't justify the last line in a paragraph.
+                && index != number_of_visual_lines - 1
+            {
+                if kashidas > 0 {
+                    (0., (line_width - visual_line_w) / kashidas as f32)
+                } else if visual_line.spaces > 0 {
+                    ((line_width - visual_line_w) / visual_line.spaces as f32, 0.)
+                } else {
+                    (0., 0.)
+                }
+            } else {
+                (0., 0.)
+            };
+
+            let mut process_range = |range: Range<usize>| {
+                for &(span_index, (starting_word, starting_glyph), (ending_word, ending_glyph)) in
+                    visual_line.ranges[range.clone()].iter()
+                {
+                    let span = &self.spans[span_index];
+                    // If ending_glyph is not 0 we need to include glyphs from the ending_word
+                    for i in starting_word..ending_word + usize::from(ending_glyph != 0) {
+                        let word = &span.words[i];
+                        let included_glyphs = match (i == starting_word, i == ending_word) {
+                            (false, false) => &word.glyphs[..],
+                            (true, false) => &word.glyphs[starting_glyph..],
+                            (false, true) => &word.glyphs[..ending_glyph],
+                            (true, true) => &word.glyphs[starting_glyph..ending_glyph],
+                        };
+
+                        let match_mono_em_width = match_mono_width.map(|w| w / font_size);
+
+                        for (glyph_i, glyph) in included_glyphs.iter().enumerate() {
+                            // Kashida goes visually left of its cluster, which is before the
+                            // cluster in LTR lines and after it in RTL lines
+                            let kashida = kashida_expansion > 0. && glyph.kashida_opt.is_some();
+                            let kashida_before = kashida
+                                && !self.rtl
+                                && (glyph_i == 0
+                                    || included_glyphs[glyph_i - 1].start != glyph.start);
+                            let kashida_after = kashida
+                                && self.rtl
+                                && included_glyphs
+                                    .get(glyph_i + 1)
+                                    .map_or(true, |next| next.start != glyph.start);
+
+                            let glyph_font_size = match (
+                                match_mono_em_width,
+                                glyph.font_monospace_em_width,
+                            ) {
+                                (Some(match_em_width), Some(glyph_em_width))
+                                    if glyph_em_width != match_em_width =>
+                                {
+                                    let glyph_to_match_factor = glyph_em_width / match_em_width;
+                                    let glyph_font_size = math::roundf(glyph_to_match_factor)
+                                        .max(1.0)
+                                        / glyph_to_match_factor
+                                        * font_size;
+                                    log::trace!("Adjusted glyph font size ({font_size} => {glyph_font_size})");
+                                    glyph_font_size
+                                }
+                                _ => font_size,
+                            };
+
+                            let advance_em = glyph.advance_em(tabular_digits);
+                            let x_advance = glyph_font_size * advance_em
+                                + if word.blank {
+                                    justification_expansion
+                                } else {
+                                    0.0
+                                };
+                            if kashida_before {
+                                glyph.layout_kashida(
+                                    glyph_font_size,
+                                    kashida_expansion,
+                                    self.rtl,
+                                    &mut x,
+                                    y,
+                                    span.level,
+                                    &mut glyphs,
+                                );
+                            }
+                            if self.rtl {
+                                x -= x_advance;
+                            }
+                            let y_advance = glyph_font_size * glyph.y_advance;
+                            let mut layout_glyph =
+                                glyph.layout(glyph_font_size, x, y, x_advance, span.level);
+                            // Center digits widened to tabular width
+                            layout_glyph.x_offset += (advance_em - glyph.x_advance) / 2.0;
+                            glyphs.push(layout_glyph);
+                            if !self.rtl {
+                                x += x_advance;
+                            }
+                            y += y_advance;
+                            if kashida_after {
+                                glyph.layout_kashida(
+                                    glyph_font_size,
+                                    kashida_expansion,
+                                    self.rtl,
+                                    &mut x,
+                                    y,
+                                    span.level,
+                                    &mut glyphs,
+                                );
+                            }
+                            // Shifted glyphs may need more room above or below the baseline
+                            let shift = glyph.baseline_shift;
+                            if glyph.clamp_line_height {
+                                let metrics = [
+                                    glyph.ascent + shift,
+                                    glyph.descent - shift,
+                                    glyph.win_ascent + shift,
+                                    glyph.win_descent - shift,
+                                ];
+                                for (max, metric) in clamped_max.iter_mut().zip(metrics) {
+                                    *max = max.max(metric);
+                                }
+                            } else {
+                                max_ascent = max_ascent.max(glyph.ascent + shift);
+                                max_descent = max_descent.max(glyph.descent - shift);
+                                max_win_ascent = max_win_ascent.max(glyph.win_ascent + shift);
+                                max_win_descent = max_win_descent.max(glyph.win_descent - shift);
+                            }
+                        }
+                    }
+                }
+            };
+
+            if self.rtl {
+                for range in new_order.into_iter().rev() {
+                    process_range(range);
+                }
+            } else {
+                /* LTR */
+                for range in new_order {
+                    process_range(range);
+                }
+            }
+
+            // A line of only clamped glyphs still needs room for them
+            if max_ascent + max_descent == 0.0 {
+                [max_ascent, max_descent, max_win_ascent, max_win_descent] = clamped_max;
+            }
+
+            if visual_line.hyphen {
+                if let Some((hyphen, level)) = self.visual_line_hyphen(visual_line) {
+                    let x_advance = font_size * hyphen.x_advance;
+                    glyphs.push(hyphen.layout(font_size, x, y, x_advance, level));
+                    x += x_advance;
+                }
+            }
+
+            layout_lines.push(LayoutLine {
+                w: if align != Align::Justified {
+                    visual_line.w - visual_line.end_inset
+                } else if self.rtl {
+                    start_x - x
+                } else {
+                    x
+                },
+                max_ascent: max_ascent * font_size,
+                max_descent: max_descent * font_size,
+                max_win_ascent: max_win_ascent * font_size,
+                max_win_descent: max_win_descent * font_size,
+                primary_ascent: 0.0,
+                primary_descent: 0.0,
+                primary_win_ascent: 0.0,
+                primary_win_descent: 0.0,
+                glyphs,
+                ruby: Vec::new(),
+                ruby_height: 0.0,
+                extra_height: 0.0,
+                max_cap_height: 0.0,
+                drop_cap: Vec::new(),
+                marker: Vec::new(),
+            });
+        }
+
+        // This is used to create a visual line for empty lines (e.g. lines with only a <CR>)
+        if layout_lines.is_empty() {
+            layout_lines.push(LayoutLine {
+                w: 0.0,
+                max_ascent: 0.0,
+                max_descent: 0.0,
+                max_win_ascent: 0.0,
+                max_win_descent: 0.0,
+                primary_ascent: 0.0,
+                primary_descent: 0.0,
+                primary_win_ascent: 0.0,
+                primary_win_descent: 0.0,
+                glyphs: Default::default(),
+                ruby: Vec::new(),
+                ruby_height: 0.0,
+                extra_height: 0.0,
+                max_cap_height: 0.0,
+                drop_cap: Vec::new(),
+                marker: Vec::new(),
+            });
+        }
+
+        // Restore the buffer to the scratch set to prevent reallocations.
+        scratch.visual_lines = visual_lines;
+    }
+
+    /// Break the line into visual lines that fit in `line_width`, replacing `visual_lines`
+    fn wrap_visual_lines(
+        &self,
+        visual_lines: &mut Vec<VisualLine>,
+        font_size: f32,
+        line_width: f32,
+        wrap: Wrap,
+        insets: &mut dyn FnMut(usize) -> (f32, f32),
+        tabular_digits: bool,
+        hanging_punctuation: bool,
+    ) {
+        visual_lines.clear();
+        #[cfg(feature = "optimal-wrap")]
+        if let Wrap::Optimal(optimal) = wrap {
+            if self.optimal_visual_lines(
+                visual_lines,
+                font_size,
+                line_width,
+                optimal,
+                insets,
+                tabular_digits,
+            ) {
+                return;
+            }
+        }
+        // Balanced lines are broken like this at a narrower width, and optimal lines when they
+        // cannot be broken within the tolerance
+        let wrap = match wrap {
+            Wrap::Balanced => Wrap::WordOrGlyph,
+            #[cfg(feature = "optimal-wrap")]
+            Wrap::Optimal(_) => Wrap::WordOrGlyph,
+            wrap => wrap,
+        };
+
         fn add_to_visual_line(
             vl: &mut VisualLine,
             span_index: usize,
@@ -1018,14 +2466,29 @@ impl ShapeLine {
         // If one span is too large, this variable will hold the range of words inside that span
         // that fits on a line.
         // let mut current_visual_line: Vec<VlRange> = Vec::with_capacity(1);
-        let mut current_visual_line = VisualLine::default();
+        // Lines start out as wide as their insets, so that wrapping leaves room for them. Lines
+        // that end up empty are not laid out, so they do not count as a line for the insets.
+        let mut next_line = |visual_lines: &[VisualLine]| {
+            let line_i = visual_lines
+                .iter()
+                .filter(|visual_line| !visual_line.ranges.is_empty())
+                .count();
+            let (start_inset, end_inset) = insets(line_i);
+            VisualLine {
+                w: start_inset + end_inset,
+                start_inset,
+                end_inset,
+                ..VisualLine::default()
+            }
+        };
+        let mut current_visual_line = next_line(visual_lines);
 
         if wrap == Wrap::None {
             for (span_index, span) in self.spans.iter().enumerate() {
                 let mut word_range_width = 0.;
                 let mut number_of_blanks: u32 = 0;
                 for word in span.words.iter() {
-                    let word_width = font_size * word.x_advance;
+                    let word_width = word.width(font_size, tabular_digits);
                     word_range_width += word_width;
                     if word.blank {
                         number_of_blanks += 1;
@@ -1051,12 +2514,13 @@ impl ShapeLine {
                     // incongruent directions
                     let mut fitting_start = (span.words.len(), 0);
                     for (i, word) in span.words.iter().enumerate().rev() {
-                        let word_width = font_size * word.x_advance;
+                        let word_width = word.width(font_size, tabular_digits);
+                        let hang_width = word.hang_width(font_size, hanging_punctuation);
 
                         // Addition in the same order used to compute the final width, so that
                         // relayouts with that width as the `line_width` will produce the same
                         // wrapping results.
-                        if current_visual_line.w + (word_range_width + word_width)
+                        if current_visual_line.w + (word_range_width + word_width - hang_width)
                             <= line_width
                             // Include one blank word over the width limit since it won't be
                             // counted in the final width
@@ -1089,7 +2553,7 @@ impl ShapeLine {
                                 );
 
                                 visual_lines.push(current_visual_line);
-                                current_visual_line = VisualLine::default();
+                                current_visual_line = next_line(visual_lines);
 
                                 number_of_blanks = 0;
                                 word_range_width = 0.;
@@ -1098,7 +2562,7 @@ impl ShapeLine {
                             }
 
                             for (glyph_i, glyph) in word.glyphs.iter().enumerate().rev() {
-                                let glyph_width = font_size * glyph.x_advance;
+                                let glyph_width = font_size * glyph.advance_em(tabular_digits);
                                 if current_visual_line.w + (word_range_width + glyph_width)
                                     <= line_width
                                 {
@@ -1114,7 +2578,7 @@ impl ShapeLine {
                                         number_of_blanks,
                                     );
                                     visual_lines.push(current_visual_line);
-                                    current_visual_line = VisualLine::default();
+                                    current_visual_line = next_line(visual_lines);
 
                                     number_of_blanks = 0;
                                     word_range_width = glyph_width;
@@ -1154,7 +2618,7 @@ impl ShapeLine {
                                 }
 
                                 visual_lines.push(current_visual_line);
-                                current_visual_line = VisualLine::default();
+                                current_visual_line = next_line(visual_lines);
                                 number_of_blanks = 0;
                             }
 
@@ -1179,8 +2643,13 @@ impl ShapeLine {
                     // congruent direction
                     let mut fitting_start = (0, 0);
                     for (i, word) in span.words.iter().enumerate() {
-                        let word_width = font_size * word.x_advance;
-                        if current_visual_line.w + (word_range_width + word_width)
+                        let word_width = word.width(font_size, tabular_digits);
+                        // Part of a hyphenated word must fit with its hyphen, in case the line
+                        // wraps after it, while punctuation at the end of a word may hang past
+                        // the end of the line
+                        if current_visual_line.w
+                            + (word_range_width + word_width + word.hyphen_width(font_size)
+                                - word.hang_width(font_size, hanging_punctuation))
                             <= line_width
                             // Include one blank word over the width limit since it won't be
                             // counted in the final width.
@@ -1213,7 +2682,7 @@ impl ShapeLine {
                                 );
 
                                 visual_lines.push(current_visual_line);
-                                current_visual_line = VisualLine::default();
+                                current_visual_line = next_line(visual_lines);
 
                                 number_of_blanks = 0;
                                 word_range_width = 0.;
@@ -1222,7 +2691,7 @@ impl ShapeLine {
                             }
 
                             for (glyph_i, glyph) in word.glyphs.iter().enumerate() {
-                                let glyph_width = font_size * glyph.x_advance;
+                                let glyph_width = font_size * glyph.advance_em(tabular_digits);
                                 if current_visual_line.w + (word_range_width + glyph_width)
                                     <= line_width
                                 {
@@ -1238,7 +2707,7 @@ impl ShapeLine {
                                         number_of_blanks,
                                     );
                                     visual_lines.push(current_visual_line);
-                                    current_visual_line = VisualLine::default();
+                                    current_visual_line = next_line(visual_lines);
 
                                     number_of_blanks = 0;
                                     word_range_width = glyph_width;
@@ -1276,7 +2745,7 @@ impl ShapeLine {
                                 }
 
                                 visual_lines.push(current_visual_line);
-                                current_visual_line = VisualLine::default();
+                                current_visual_line = next_line(visual_lines);
                                 number_of_blanks = 0;
                             }
 
@@ -1304,164 +2773,147 @@ impl ShapeLine {
         if !current_visual_line.ranges.is_empty() {
             visual_lines.push(current_visual_line);
         }
+    }
 
-        // Create the LayoutLines using the ranges inside visual lines
-        let align = align.unwrap_or({
-            if self.rtl {
-                Align::Right
-            } else {
-                Align::Left
+    /// Break the line into visual lines with the breaks of [`Wrap::Optimal`], returning false if
+    /// it cannot be broken within the tolerance
+    #[cfg(feature = "optimal-wrap")]
+    fn optimal_visual_lines(
+        &self,
+        visual_lines: &mut Vec<VisualLine>,
+        font_size: f32,
+        line_width: f32,
+        optimal: OptimalWrap,
+        insets: &mut dyn FnMut(usize) -> (f32, f32),
+        tabular_digits: bool,
+    ) -> bool {
+        // Items in logical order, with the span and the word of each box and glue
+        let mut items = Vec::new();
+        let mut item_words: Vec<Option<(usize, usize)>> = Vec::new();
+        for (span_index, span) in self.spans.iter().enumerate() {
+            for (word_i, word) in span.words.iter().enumerate() {
+                let width = word.width(font_size, tabular_digits);
+                if word.blank {
+                    items.push(BreakItem::Glue { width });
+                } else {
+                    // Words without a space between them may break after the first one, with
+                    // its hyphen if it is part of a hyphenated word
+                    if let Some(&Some((prev_span, prev_word))) = item_words.last() {
+                        let prev = &self.spans[prev_span].words[prev_word];
+                        if !prev.blank {
+                            items.push(BreakItem::Penalty {
+                                width: prev.hyphen_width(font_size),
+                                hyphen: prev.hyphen_opt.is_some(),
+                            });
+                            item_words.push(None);
+                        }
+                    }
+                    items.push(BreakItem::Box { width });
+                }
+                item_words.push(Some((span_index, word_i)));
             }
-        });
+        }
 
-        let start_x = if self.rtl { line_width } else { 0.0 };
+        let mut line_insets = Vec::new();
+        let mut available = |line_i: usize| {
+            while line_insets.len() <= line_i {
+                line_insets.push(insets(line_insets.len()));
+            }
+            let (start_inset, end_inset) = line_insets[line_i];
+            line_width - start_inset - end_inset
+        };
+        let Some(breaks) = optimal.breaks(&items, &mut available) else {
+            return false;
+        };
 
-        let number_of_visual_lines = visual_lines.len();
-        for (index, visual_line) in visual_lines.iter().enumerate() {
-            if visual_line.ranges.is_empty() {
+        let mut next_line = |line_i: usize| {
+            let (start_inset, end_inset) = match line_insets.get(line_i) {
+                Some(&line_insets) => line_insets,
+                None => insets(line_i),
+            };
+            VisualLine {
+                w: start_inset + end_inset,
+                start_inset,
+                end_inset,
+                ..VisualLine::default()
+            }
+        };
+        let mut breaks = breaks.into_iter().peekable();
+        let mut visual_line = next_line(0);
+        for (item_i, word_opt) in item_words.into_iter().enumerate() {
+            // The space or the penalty a line breaks at is not part of either line
+            if breaks.next_if_eq(&item_i).is_some() {
+                visual_lines.push(visual_line);
+                visual_line = next_line(visual_lines.len());
                 continue;
             }
-            let new_order = self.reorder(&visual_line.ranges);
-            let mut glyphs = Vec::with_capacity(1);
-            let mut x = start_x;
-            let mut y = 0.;
-            let mut max_ascent: f32 = 0.;
-            let mut max_descent: f32 = 0.;
-            let alignment_correction = match (align, self.rtl) {
-                (Align::Left, true) => line_width - visual_line.w,
-                (Align::Left, false) => 0.,
-                (Align::Right, true) => 0.,
-                (Align::Right, false) => line_width - visual_line.w,
-                (Align::Center, _) => (line_width - visual_line.w) / 2.0,
-                (Align::End, _) => line_width - visual_line.w,
-                (Align::Justified, _) => 0.,
+            let Some((span_index, word_i)) = word_opt else {
+                continue;
             };
-
-            if self.rtl {
-                x -= alignment_correction;
-            } else {
-                x += alignment_correction;
+            let word = &self.spans[span_index].words[word_i];
+            match visual_line.ranges.last_mut() {
+                Some((last_span, _, end)) if *last_span == span_index && *end == (word_i, 0) => {
+                    *end = (word_i + 1, 0);
+                }
+                _ => visual_line
+                    .ranges
+                    .push((span_index, (word_i, 0), (word_i + 1, 0))),
             }
+            visual_line.w += word.width(font_size, tabular_digits);
+            if word.blank {
+                visual_line.spaces += 1;
+            }
+        }
+        visual_lines.push(visual_line);
+        true
+    }
 
-            // TODO: Only certain `is_whitespace` chars are typically expanded but this is what is
-            // currently used to compute `visual_line.spaces`.
-            //
-            // https://www.unicode.org/reports/tr14/#Introduction
-            // > When expanding or compressing interword space according to common
-            // > typographical practice, only the spaces marked by U+0020 SPACE and U+00A0
-            // > NO-BREAK SPACE are subject to compression, and only spaces marked by U+0020
-            // > SPACE, U+00A0 NO-BREAK SPACE, and occasionally spaces marked by U+2009 THIN
-            // > SPACE are subject to expansion. All other space characters normally have
-            // > fixed width.
-            //
-            // (also some spaces aren't followed by potential linebreaks but they could
-            //  still be expanded)
-
-            // Amount of extra width added to each blank space within a line.
-            let justification_expansion = if matches!(align, Align::Justified)
-                && visual_line.spaces > 0
-                // Don't justify the last line in a paragraph.
-                && index != number_of_visual_lines - 1
-            {
-                (line_width - visual_line.w) / visual_line.spaces as f32
-            } else {
-                0.
-            };
-
-            let mut process_range = |range: Range<usize>| {
-                for &(span_index, (starting_word, starting_glyph), (ending_word, ending_glyph)) in
-                    visual_line.ranges[range.clone()].iter()
-                {
-                    let span = &self.spans[span_index];
-                    // If ending_glyph is not 0 we need to include glyphs from the ending_word
-                    for i in starting_word..ending_word + usize::from(ending_glyph != 0) {
-                        let word = &span.words[i];
-                        let included_glyphs = match (i == starting_word, i == ending_word) {
-                            (false, false) => &word.glyphs[..],
-                            (true, false) => &word.glyphs[starting_glyph..],
-                            (false, true) => &word.glyphs[..ending_glyph],
-                            (true, true) => &word.glyphs[starting_glyph..ending_glyph],
-                        };
-
-                        let match_mono_em_width = match_mono_width.map(|w| w / font_size);
-
-                        for glyph in included_glyphs {
-                            let glyph_font_size = match (
-                                match_mono_em_width,
-                                glyph.font_monospace_em_width,
-                            ) {
-                                (Some(match_em_width), Some(glyph_em_width))
-                                    if glyph_em_width != match_em_width =>
-                                {
-                                    let glyph_to_match_factor = glyph_em_width / match_em_width;
-                                    let glyph_font_size = math::roundf(glyph_to_match_factor)
-                                        .max(1.0)
-                                        / glyph_to_match_factor
-                                        * font_size;
-                                    log::trace!("Adjusted glyph font size ({font_size} => {glyph_font_size})");
-                                    glyph_font_size
-                                }
-                                _ => font_size,
-                            };
-
-                            let x_advance = glyph_font_size * glyph.x_advance
-                                + if word.blank {
-                                    justification_expansion
-                                } else {
-                                    0.0
-                                };
-                            if self.rtl {
-                                x -= x_advance;
-                            }
-                            let y_advance = glyph_font_size * glyph.y_advance;
-                            glyphs.push(glyph.layout(glyph_font_size, x, y, x_advance, span.level));
-                            if !self.rtl {
-                                x += x_advance;
-                            }
-                            y += y_advance;
-                            max_ascent = max_ascent.max(glyph.ascent);
-                            max_descent = max_descent.max(glyph.descent);
-                        }
-                    }
-                }
-            };
+    /// Find the narrowest width at which the line breaks into as many visual lines as at
+    /// `line_width`, so that they are about as wide as each other, see [`Wrap::Balanced`]
+    fn balanced_width(
+        &self,
+        visual_lines: &mut Vec<VisualLine>,
+        font_size: f32,
+        line_width: f32,
+        insets: &mut dyn FnMut(usize) -> (f32, f32),
+        tabular_digits: bool,
+        hanging_punctuation: bool,
+    ) -> f32 {
+        // Longer paragraphs are left ragged, as balancing them costs a layout per step
+        const MAX_LINES: usize = 6;
+        // Stop searching when the candidate widths are this close, in pixels
+        const TOLERANCE: f32 = 0.5;
+
+        let mut count_lines = |visual_lines: &mut Vec<VisualLine>, width: f32| {
+            self.wrap_visual_lines(
+                visual_lines,
+                font_size,
+                width,
+                Wrap::Balanced,
+                insets,
+                tabular_digits,
+                hanging_punctuation,
+            );
+            visual_lines
+                .iter()
+                .filter(|visual_line| !visual_line.ranges.is_empty())
+                .count()
+        };
 
-            if self.rtl {
-                for range in new_order.into_iter().rev() {
-                    process_range(range);
-                }
+        let lines = count_lines(visual_lines, line_width);
+        if !(2..=MAX_LINES).contains(&lines) {
+            return line_width;
+        }
+        let mut low = 0.0;
+        let mut high = line_width;
+        while high - low > TOLERANCE {
+            let mid = (low + high) / 2.0;
+            if count_lines(visual_lines, mid) > lines {
+                low = mid;
             } else {
-                /* LTR */
-                for range in new_order {
-                    process_range(range);
-                }
+                high = mid;
             }
-
-            layout_lines.push(LayoutLine {
-                w: if align != Align::Justified {
-                    visual_line.w
-                } else if self.rtl {
-                    start_x - x
-                } else {
-                    x
-                },
-                max_ascent: max_ascent * font_size,
-                max_descent: max_descent * font_size,
-                glyphs,
-            });
         }
-
-        // This is used to create a visual line for empty lines (e.g. lines with only a <CR>)
-        if layout_lines.is_empty() {
-            layout_lines.push(LayoutLine {
-                w: 0.0,
-                max_ascent: 0.0,
-                max_descent: 0.0,
-                glyphs: Default::default(),
-            });
-        }
-
-        // Restore the buffer to the scratch set to prevent reallocations.
-        scratch.visual_lines = visual_lines;
+        high
     }
 }
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use rustybuzz::{GlyphInfo, GlyphPosition};
+
+use crate::{Attrs, Font};
+
+/// A run of text shaped with one font, passed to a [`ShapeHook`]
+#[derive(Debug)]
+pub struct ShapeHookRun<'a> {
+    /// The font the run was shaped with
+    pub font: &'a Font,
+    /// The text of the whole line
+    pub line: &'a str,
+    /// Byte range of the run in the line, the clusters of glyph infos are relative to its start
+    pub range: Range<usize>,
+    /// The run is right-to-left, and its glyphs are in visual order
+    pub rtl: bool,
+    /// Attributes at the start of the run
+    pub attrs: Attrs<'a>,
+}
+
+impl<'a> ShapeHookRun<'a> {
+    /// The text of the run
+    pub fn text(&self) -> &'a str {
+        &self.line[self.range.clone()]
+    }
+}
+
+/// Callback that adjusts the glyphs of a run after shaping and before layout, see
+/// [`crate::FontSystem::set_shape_hook`]
+///
+/// Glyphs may be changed, added, or removed, as long as there is a position for each info and
+/// clusters stay inside of the run. Glyphs set to glyph ID 0 are shaped again with fallback fonts.
+pub type ShapeHook =
+    Arc<dyn Fn(&ShapeHookRun, &mut Vec<GlyphInfo>, &mut Vec<GlyphPosition>) + Send + Sync>;
@@ -3,49 +3,118 @@ use hashbrown::hash_map::Entry;
 #[cfg(feature = "std")]
 use std::collections::hash_map::Entry;
 
-use crate::{Font, HashMap};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{CacheStats, Font, FontFeature, HashMap};
 
 /// Key for caching shape plans.
 #[derive(Debug, Hash, PartialEq, Eq)]
 struct ShapePlanKey {
     font_id: fontdb::ID,
+    instance_opt: Option<u16>,
     direction: rustybuzz::Direction,
     script: rustybuzz::Script,
     language: Option<rustybuzz::Language>,
+    small_caps: bool,
+    vertical_forms: bool,
+    features: Vec<FontFeature>,
 }
 
 /// A helper structure for caching rustybuzz shape plans.
 #[derive(Default)]
-pub struct ShapePlanCache(HashMap<ShapePlanKey, rustybuzz::ShapePlan>);
+pub struct ShapePlanCache {
+    plans: HashMap<ShapePlanKey, rustybuzz::ShapePlan>,
+    stats: CacheStats,
+}
 
 impl ShapePlanCache {
-    pub fn get(&mut self, font: &Font, buffer: &rustybuzz::UnicodeBuffer) -> &rustybuzz::ShapePlan {
+    /// Get the shape plan for a font, or its named instance if `instance_opt` is set, and the
+    /// properties of a buffer, with the `smcp` feature
+    /// enabled if `small_caps` is set, and the `vert` and `vrt2` features enabled if
+    /// `vertical_forms` is set, followed by `features`
+    pub fn get(
+        &mut self,
+        font: &Font,
+        instance_opt: Option<u16>,
+        buffer: &rustybuzz::UnicodeBuffer,
+        small_caps: bool,
+        vertical_forms: bool,
+        features: &[FontFeature],
+    ) -> &rustybuzz::ShapePlan {
         let key = ShapePlanKey {
             font_id: font.id(),
+            instance_opt,
             direction: buffer.direction(),
             script: buffer.script(),
             language: buffer.language(),
+            small_caps,
+            vertical_forms,
+            features: features.to_vec(),
         };
-        match self.0.entry(key) {
-            Entry::Occupied(occ) => occ.into_mut(),
+        match self.plans.entry(key) {
+            Entry::Occupied(occ) => {
+                self.stats.hits += 1;
+                occ.into_mut()
+            }
             Entry::Vacant(vac) => {
+                self.stats.misses += 1;
                 let ShapePlanKey {
                     direction,
                     script,
                     language,
+                    small_caps,
+                    vertical_forms,
+                    features,
                     ..
                 } = vac.key();
+                let feature = |tag, value| {
+                    rustybuzz::Feature::new(rustybuzz::ttf_parser::Tag::from_bytes(tag), value, ..)
+                };
+                let mut plan_features = Vec::with_capacity(3 + features.len());
+                if *small_caps {
+                    plan_features.push(feature(b"smcp", 1));
+                }
+                if *vertical_forms {
+                    plan_features.push(feature(b"vert", 1));
+                    plan_features.push(feature(b"vrt2", 1));
+                }
+                plan_features.extend(
+                    features
+                        .iter()
+                        .map(|font_feature| feature(&font_feature.tag, font_feature.value)),
+                );
+                // Feature variations of the plan depend on the axis values of the face
+                let instance_face_opt = instance_opt.and_then(|i| font.rustybuzz_instance(i));
                 let plan = rustybuzz::ShapePlan::new(
-                    font.rustybuzz(),
+                    instance_face_opt.as_ref().unwrap_or(font.rustybuzz()),
                     *direction,
                     Some(*script),
                     language.as_ref(),
-                    &[],
+                    &plan_features,
                 );
                 vac.insert(plan)
             }
         }
     }
+
+    /// Get hit and miss counters since the last [`Self::reset_stats`], and the entry count
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.plans.len(),
+            ..self.stats
+        }
+    }
+
+    /// Remove all shape plans, keeping hit and miss counters
+    pub fn clear(&mut self) {
+        self.plans.clear();
+    }
+
+    /// Reset hit and miss counters
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
 }
 
 impl core::fmt::Debug for ShapePlanCache {
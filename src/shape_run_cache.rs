@@ -2,7 +2,7 @@
 use alloc::{string::String, vec::Vec};
 use core::ops::Range;
 
-use crate::{AttrsOwned, HashMap, ShapeGlyph};
+use crate::{AttrsOwned, CacheStats, HashMap, ShapeGlyph};
 
 /// Key for caching shape runs.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -10,6 +10,21 @@ pub struct ShapeRunKey {
     pub text: String,
     pub default_attrs: AttrsOwned,
     pub attrs_spans: Vec<(Range<usize>, AttrsOwned)>,
+    pub vertical_forms: bool,
+}
+
+/// Which shape run cache a [`crate::Buffer`] uses, set with
+/// [`crate::Buffer::set_shape_run_cache`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ShapeRunCacheMode {
+    /// Shape runs without a cache, for text that changes constantly
+    Disabled,
+    /// Use the cache of the [`crate::FontSystem`]
+    #[default]
+    Default,
+    /// Use a cache shared by buffers with the same ID, created when first used, see
+    /// [`crate::FontSystem::shared_shape_run_cache`]
+    Shared(u32),
 }
 
 /// A helper structure for caching shape runs.
@@ -17,15 +32,23 @@ pub struct ShapeRunKey {
 pub struct ShapeRunCache {
     age: u64,
     cache: HashMap<ShapeRunKey, (u64, Vec<ShapeGlyph>)>,
+    stats: CacheStats,
 }
 
 impl ShapeRunCache {
     /// Get cache item, updating age if found
     pub fn get(&mut self, key: &ShapeRunKey) -> Option<&Vec<ShapeGlyph>> {
-        self.cache.get_mut(key).map(|(age, glyphs)| {
-            *age = self.age;
-            &*glyphs
-        })
+        match self.cache.get_mut(key) {
+            Some((age, glyphs)) => {
+                self.stats.hits += 1;
+                *age = self.age;
+                Some(&*glyphs)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
     }
 
     /// Insert cache item with current age
@@ -40,6 +63,38 @@ impl ShapeRunCache {
         // Increase age
         self.age += 1;
     }
+
+    /// Remove all cache items, keeping hit and miss counters
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Get hit and miss counters since the last [`Self::reset_stats`], and the entry count
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.cache.len(),
+            ..self.stats
+        }
+    }
+
+    /// Reset hit and miss counters
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Get the number of entries by age, where index 0 counts entries used since the last trim,
+    /// index 1 entries last used before that trim, and so on
+    pub fn age_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for (age, _glyphs) in self.cache.values() {
+            let index = (self.age - age) as usize;
+            if histogram.len() <= index {
+                histogram.resize(index + 1, 0);
+            }
+            histogram[index] += 1;
+        }
+        histogram
+    }
 }
 
 impl core::fmt::Debug for ShapeRunCache {
@@ -47,3 +102,33 @@ impl core::fmt::Debug for ShapeRunCache {
         f.debug_tuple("ShapeRunCache").finish()
     }
 }
+
+#[test]
+fn test_shape_run_cache_stats() {
+    let key = |text: &str| ShapeRunKey {
+        text: text.into(),
+        default_attrs: AttrsOwned::new(crate::Attrs::new()),
+        attrs_spans: Vec::new(),
+        vertical_forms: false,
+    };
+
+    let mut cache = ShapeRunCache::default();
+    assert!(cache.get(&key("a")).is_none());
+    cache.insert(key("a"), Vec::new());
+    cache.insert(key("b"), Vec::new());
+    cache.trim(2);
+    assert!(cache.get(&key("a")).is_some());
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 1,
+            misses: 1,
+            entries: 2
+        }
+    );
+    assert_eq!(cache.age_histogram(), [1, 1]);
+
+    cache.reset_stats();
+    assert_eq!(cache.stats().hits, 0);
+    assert_eq!(cache.stats().entries, 2);
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    Align, Attrs, AttrsList, BidiParagraphs, FontSystem, LayoutGlyph, Metrics, PositionedGlyph,
+    ShapeLine, Shaping, Wrap,
+};
+
+/// A run of glyphs with the same font and direction, from [`shape_text`]
+#[derive(Clone, Debug)]
+pub struct ShapedRun {
+    /// Byte range of the text shaped into this run
+    pub range: Range<usize>,
+    /// True if the run is right-to-left
+    pub rtl: bool,
+    /// Font of all glyphs in the run
+    pub font_id: fontdb::ID,
+    /// X position of the run, in em
+    pub x: f32,
+    /// Width of the run, in em
+    pub w: f32,
+    /// Glyphs from left to right, laid out with a font size of 1
+    ///
+    /// Use [`LayoutGlyph::physical`] with the font size as scale to rasterize them.
+    pub glyphs: Vec<LayoutGlyph>,
+}
+
+/// Text shaped into a single line by [`shape_text`]
+#[derive(Clone, Debug, Default)]
+pub struct ShapedText {
+    /// Runs from left to right
+    pub runs: Vec<ShapedRun>,
+    /// Width of the text, in em
+    pub w: f32,
+    /// Maximum ascent of the glyphs, in em
+    pub max_ascent: f32,
+    /// Maximum descent of the glyphs, in em
+    pub max_descent: f32,
+}
+
+/// Shape text into runs of glyphs on a single line, without a [`crate::Buffer`]
+///
+/// This is useful to measure text or to build a custom layout. The text is not wrapped, and
+/// paragraphs are placed one after another with their separators left out, each ordered by its
+/// own base direction.
+pub fn shape_text(
+    font_system: &mut FontSystem,
+    text: &str,
+    attrs: Attrs,
+    shaping: Shaping,
+) -> ShapedText {
+    let attrs_list = AttrsList::new(attrs);
+    let mut scratch = core::mem::take(&mut font_system.scratch);
+    let mut layout_lines = Vec::with_capacity(1);
+    let mut shaped = ShapedText::default();
+
+    let mut paragraphs = BidiParagraphs::new(text);
+    while let Some((range, _rtl)) = paragraphs.next_range() {
+        let mut line = ShapeLine::new_in_buffer(
+            &mut scratch,
+            font_system,
+            &text[range.clone()],
+            &attrs_list,
+            shaping,
+        );
+        line.apply_font_sizes(1.0);
+        layout_lines.clear();
+        line.layout_to_buffer(
+            &mut scratch,
+            1.0,
+            f32::MAX,
+            Wrap::None,
+            Some(Align::Left),
+            &mut layout_lines,
+            None,
+        );
+
+        for layout_line in layout_lines.drain(..) {
+            let line_x = shaped.w;
+            for mut glyph in layout_line.glyphs {
+                glyph.start += range.start;
+                glyph.end += range.start;
+                glyph.x += line_x;
+
+                let rtl = glyph.level.is_rtl();
+                match shaped.runs.last_mut() {
+                    Some(run) if run.font_id == glyph.font_id && run.rtl == rtl => {
+                        run.range.start = run.range.start.min(glyph.start);
+                        run.range.end = run.range.end.max(glyph.end);
+                        run.w = glyph.x + glyph.w - run.x;
+                        run.glyphs.push(glyph);
+                    }
+                    _ => shaped.runs.push(ShapedRun {
+                        range: glyph.start..glyph.end,
+                        rtl,
+                        font_id: glyph.font_id,
+                        x: glyph.x,
+                        w: glyph.w,
+                        glyphs: vec![glyph],
+                    }),
+                }
+            }
+            shaped.w += layout_line.w;
+            shaped.max_ascent = shaped.max_ascent.max(layout_line.max_ascent);
+            shaped.max_descent = shaped.max_descent.max(layout_line.max_descent);
+        }
+    }
+
+    font_system.scratch = scratch;
+    shaped
+}
+
+/// Shape, wrap, and position text in one call, without a [`crate::Buffer`]
+///
+/// This is meant for immediate mode interfaces that lay out text every frame. Lines are wrapped
+/// to `max_width` like a buffer with [`Wrap::WordOrGlyph`] and aligned to the natural direction
+/// of each paragraph, or not wrapped and aligned left if `max_width` is `None`. Scratch buffers
+/// are kept in the [`FontSystem`] between calls.
+pub fn layout_text(
+    font_system: &mut FontSystem,
+    text: &str,
+    attrs: Attrs,
+    metrics: Metrics,
+    max_width: Option<f32>,
+) -> Vec<PositionedGlyph> {
+    let attrs_list = AttrsList::new(attrs);
+    let mut scratch = core::mem::take(&mut font_system.scratch);
+    let mut layout_lines = Vec::with_capacity(1);
+    let mut glyphs = Vec::with_capacity(text.len());
+    let (line_width, wrap, align) = match max_width {
+        Some(max_width) => (max_width, Wrap::WordOrGlyph, None),
+        None => (f32::MAX, Wrap::None, Some(Align::Left)),
+    };
+
+    let mut line_top = 0.0;
+    let mut paragraphs = BidiParagraphs::new(text);
+    let mut line_i = 0;
+    while let Some((range, _rtl)) = paragraphs.next_range() {
+        let mut line = ShapeLine::new_in_buffer(
+            &mut scratch,
+            font_system,
+            &text[range.clone()],
+            &attrs_list,
+            Shaping::Advanced,
+        );
+        line.apply_font_sizes(metrics.font_size);
+        layout_lines.clear();
+        line.layout_to_buffer(
+            &mut scratch,
+            metrics.font_size,
+            line_width,
+            wrap,
+            align,
+            &mut layout_lines,
+            None,
+        );
+
+        for layout_line in layout_lines.drain(..) {
+            let glyph_height = layout_line.max_ascent + layout_line.max_descent;
+            let centering_offset = (metrics.line_height - glyph_height) / 2.0;
+            let line_y = line_top + centering_offset + layout_line.max_ascent;
+            glyphs.extend(layout_line.glyphs.into_iter().map(|mut glyph| {
+                glyph.start += range.start;
+                glyph.end += range.start;
+                PositionedGlyph {
+                    glyph,
+                    line_i,
+                    line_y,
+                }
+            }));
+            line_top += metrics.line_height;
+        }
+        line_i += 1;
+    }
+
+    font_system.scratch = scratch;
+    glyphs
+}
@@ -1,20 +1,106 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use core::fmt;
 use swash::scale::{image::Content, ScaleContext};
 use swash::scale::{Render, Source, StrikeWith};
 use swash::zeno::{Format, Vector};
 
-use crate::{CacheKey, CacheKeyFlags, Color, FontSystem, HashMap};
+use crate::{math, CacheKey, CacheKeyFlags, Color, Font, FontSystem, HashMap, Shadow};
 
 pub use swash::scale::image::{Content as SwashContent, Image as SwashImage};
 pub use swash::zeno::{Angle, Command, Placement, Transform};
 
+/// Subpixel rendering of glyph masks, for displays with a known order of subpixels
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SubpixelMode {
+    /// Render an alpha mask, as [`SwashContent::Mask`]
+    #[default]
+    None,
+    /// Render an RGBA mask for horizontal RGB subpixels, as [`SwashContent::SubpixelMask`]
+    Rgb,
+    /// Render an RGBA mask for horizontal BGR subpixels, as [`SwashContent::SubpixelMask`]
+    Bgr,
+}
+
+/// Options for rasterizing glyphs, see [`SwashCache::set_raster_options`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RasterOptions {
+    /// Hint outlines to the pixel grid
+    pub hinting: bool,
+    /// Subpixel rendering of masks
+    pub subpixel_mode: SubpixelMode,
+    /// Gamma applied to the coverage of masks, where 1.0 leaves coverage unchanged and higher
+    /// values make text look bolder
+    ///
+    /// Values that are not finite and above 0.0 are replaced with 1.0 by
+    /// [`SwashCache::set_raster_options`].
+    pub gamma: f32,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            hinting: true,
+            subpixel_mode: SubpixelMode::None,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl RasterOptions {
+    /// Table mapping coverage to coverage with gamma applied
+    fn gamma_table(&self) -> Option<[u8; 256]> {
+        if self.gamma == 1.0 || !(self.gamma.is_finite() && self.gamma > 0.0) {
+            return None;
+        }
+        let mut table = [0; 256];
+        for (i, value) in table.iter_mut().enumerate() {
+            *value = math::roundf(math::powf(i as f32 / 255.0, 1.0 / self.gamma) * 255.0) as u8;
+        }
+        Some(table)
+    }
+}
+
+/// Axis values of the named instance of a glyph, empty for the default instance
+fn instance_coords(font: &Font, cache_key: CacheKey) -> &[([u8; 4], f32)] {
+    cache_key
+        .font_instance_opt
+        .and_then(|instance| font.named_instances().get(usize::from(instance)))
+        .map_or(&[], |named_instance| &named_instance.coords)
+}
+
+/// Strength of synthesized bold relative to the font size, widening outlines by a 24th of an em
+const FAKE_BOLD_STRENGTH: f32 = 1.0 / 24.0;
+
+/// Strength of the emboldening of a glyph, 0 unless it has [`CacheKeyFlags::FAKE_BOLD`]
+fn embolden_strength(cache_key: CacheKey) -> f32 {
+    if cache_key.flags.contains(CacheKeyFlags::FAKE_BOLD) {
+        f32::from_bits(cache_key.font_size_bits) * FAKE_BOLD_STRENGTH
+    } else {
+        0.0
+    }
+}
+
+/// Skew of a glyph with [`CacheKeyFlags::FAKE_ITALIC`]
+fn oblique_transform(cache_key: CacheKey) -> Option<Transform> {
+    cache_key
+        .flags
+        .contains(CacheKeyFlags::FAKE_ITALIC)
+        .then(|| {
+            Transform::skew(
+                Angle::from_degrees(f32::from_bits(cache_key.oblique_angle_bits)),
+                Angle::from_degrees(0.0),
+            )
+        })
+}
+
 fn swash_image(
     font_system: &mut FontSystem,
     context: &mut ScaleContext,
+    options: &RasterOptions,
+    gamma_table: Option<&[u8; 256]>,
     cache_key: CacheKey,
 ) -> Option<SwashImage> {
     let font = match font_system.get_font(cache_key.font_id) {
@@ -29,13 +115,20 @@ fn swash_image(
     let mut scaler = context
         .builder(font.as_swash())
         .size(f32::from_bits(cache_key.font_size_bits))
-        .hint(true)
+        .hint(options.hinting)
+        .variations(instance_coords(&font, cache_key))
         .build();
 
     // Compute the fractional offset-- you'll likely want to quantize this
     // in a real renderer
     let offset = Vector::new(cache_key.x_bin.as_float(), cache_key.y_bin.as_float());
 
+    let mut transform = oblique_transform(cache_key);
+    if cache_key.flags.contains(CacheKeyFlags::SIDEWAYS) {
+        let rotation = Transform::rotation(Angle::from_degrees(-90.0));
+        transform = Some(transform.map_or(rotation, |transform| transform.then(&rotation)));
+    }
+
     // Select our source order
     Render::new(&[
         // Color outline with the first palette
@@ -46,19 +139,27 @@ fn swash_image(
         Source::Outline,
     ])
     // Select a subpixel format
-    .format(Format::Alpha)
+    .format(match options.subpixel_mode {
+        SubpixelMode::None => Format::Alpha,
+        SubpixelMode::Rgb => Format::Subpixel,
+        SubpixelMode::Bgr => Format::subpixel_bgra(),
+    })
     // Apply the fractional offset
     .offset(offset)
-    .transform(if cache_key.flags.contains(CacheKeyFlags::FAKE_ITALIC) {
-        Some(Transform::skew(
-            Angle::from_degrees(14.0),
-            Angle::from_degrees(0.0),
-        ))
-    } else {
-        None
-    })
+    .transform(transform)
+    .embolden(embolden_strength(cache_key))
     // Render the image
     .render(&mut scaler, cache_key.glyph_id)
+    .map(|mut image| {
+        if let Some(gamma_table) = gamma_table {
+            if image.content != Content::Color {
+                for value in image.data.iter_mut() {
+                    *value = gamma_table[*value as usize];
+                }
+            }
+        }
+        image
+    })
 }
 
 fn swash_outline_commands(
@@ -80,12 +181,20 @@ fn swash_outline_commands(
     let mut scaler = context
         .builder(font.as_swash())
         .size(f32::from_bits(cache_key.font_size_bits))
+        .variations(instance_coords(&font, cache_key))
         .build();
 
     // Scale the outline
-    let outline = scaler
+    let mut outline = scaler
         .scale_outline(cache_key.glyph_id)
         .or_else(|| scaler.scale_color_outline(cache_key.glyph_id))?;
+    let strength = embolden_strength(cache_key);
+    if strength != 0.0 {
+        outline.embolden(strength, strength);
+    }
+    if let Some(transform) = oblique_transform(cache_key) {
+        outline.transform(&transform);
+    }
 
     // Get the path information of the outline
     let path = outline.path();
@@ -94,9 +203,52 @@ fn swash_outline_commands(
     Some(path.commands().collect())
 }
 
+/// Number and size of images of one [`SwashContent`] type, see [`SwashCacheStats`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SwashContentStats {
+    /// Number of images
+    pub images: usize,
+    /// Total bytes of pixel data
+    pub bytes: usize,
+}
+
+impl SwashContentStats {
+    fn add(&mut self, image: &SwashImage) {
+        self.images += 1;
+        self.bytes += image.data.len();
+    }
+}
+
+/// Statistics of the images in a [`SwashCache`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SwashCacheStats {
+    /// Alpha mask images
+    pub mask: SwashContentStats,
+    /// Subpixel mask images
+    pub subpixel_mask: SwashContentStats,
+    /// Color images
+    pub color: SwashContentStats,
+    /// Number of cache entries for glyphs without an image, such as spaces
+    pub empty: usize,
+}
+
+impl SwashCacheStats {
+    /// Total number of images
+    pub fn images(&self) -> usize {
+        self.mask.images + self.subpixel_mask.images + self.color.images
+    }
+
+    /// Total bytes of pixel data
+    pub fn bytes(&self) -> usize {
+        self.mask.bytes + self.subpixel_mask.bytes + self.color.bytes
+    }
+}
+
 /// Cache for rasterizing with the swash scaler
 pub struct SwashCache {
     context: ScaleContext,
+    options: RasterOptions,
+    gamma_table: Option<[u8; 256]>,
     pub image_cache: HashMap<CacheKey, Option<SwashImage>>,
     pub outline_command_cache: HashMap<CacheKey, Option<Vec<swash::zeno::Command>>>,
 }
@@ -112,18 +264,46 @@ impl SwashCache {
     pub fn new() -> Self {
         Self {
             context: ScaleContext::new(),
+            options: RasterOptions::default(),
+            gamma_table: None,
             image_cache: HashMap::default(),
             outline_command_cache: HashMap::default(),
         }
     }
 
+    /// Get the current [`RasterOptions`]
+    pub fn raster_options(&self) -> RasterOptions {
+        self.options
+    }
+
+    /// Set the [`RasterOptions`] used for images rasterized after this call, clearing cached
+    /// images if they change
+    ///
+    /// A gamma that is not a finite number above 0.0 is replaced with 1.0.
+    pub fn set_raster_options(&mut self, mut options: RasterOptions) {
+        if !(options.gamma.is_finite() && options.gamma > 0.0) {
+            options.gamma = 1.0;
+        }
+        if options != self.options {
+            self.options = options;
+            self.gamma_table = options.gamma_table();
+            self.image_cache.clear();
+        }
+    }
+
     /// Create a swash Image from a cache key, without caching results
     pub fn get_image_uncached(
         &mut self,
         font_system: &mut FontSystem,
         cache_key: CacheKey,
     ) -> Option<SwashImage> {
-        swash_image(font_system, &mut self.context, cache_key)
+        swash_image(
+            font_system,
+            &mut self.context,
+            &self.options,
+            self.gamma_table.as_ref(),
+            cache_key,
+        )
     }
 
     /// Create a swash Image from a cache key, caching results
@@ -132,9 +312,39 @@ impl SwashCache {
         font_system: &mut FontSystem,
         cache_key: CacheKey,
     ) -> &Option<SwashImage> {
+        self.image_cache.entry(cache_key).or_insert_with(|| {
+            swash_image(
+                font_system,
+                &mut self.context,
+                &self.options,
+                self.gamma_table.as_ref(),
+                cache_key,
+            )
+        })
+    }
+
+    /// Get the number and size of cached images, by content type
+    pub fn image_stats(&self) -> SwashCacheStats {
+        let mut stats = SwashCacheStats::default();
+        for image_opt in self.image_cache.values() {
+            match image_opt {
+                Some(image) => match image.content {
+                    Content::Mask => stats.mask.add(image),
+                    Content::SubpixelMask => stats.subpixel_mask.add(image),
+                    Content::Color => stats.color.add(image),
+                },
+                None => stats.empty += 1,
+            }
+        }
+        stats
+    }
+
+    /// Iterate over the cache keys of cached images, in arbitrary order
+    pub fn image_cache_keys(&self) -> impl Iterator<Item = CacheKey> + '_ {
         self.image_cache
-            .entry(cache_key)
-            .or_insert_with(|| swash_image(font_system, &mut self.context, cache_key))
+            .iter()
+            .filter(|(_cache_key, image_opt)| image_opt.is_some())
+            .map(|(cache_key, _image_opt)| *cache_key)
     }
 
     pub fn get_outline_commands(
@@ -149,6 +359,9 @@ impl SwashCache {
     }
 
     /// Enumerate pixels in an Image, use `with_image` for better performance
+    ///
+    /// Subpixel masks are drawn with the average coverage of their subpixels, use
+    /// [`Self::get_image`] to blend each subpixel.
     pub fn with_pixels<F: FnMut(i32, i32, Color)>(
         &mut self,
         font_system: &mut FontSystem,
@@ -195,9 +408,161 @@ impl SwashCache {
                     }
                 }
                 Content::SubpixelMask => {
-                    log::warn!("TODO: SubpixelMask");
+                    let mut i = 0;
+                    for off_y in 0..image.placement.height as i32 {
+                        for off_x in 0..image.placement.width as i32 {
+                            // Pixels have a single color, so the coverage of the subpixels is
+                            // averaged
+                            let coverage = (u32::from(image.data[i])
+                                + u32::from(image.data[i + 1])
+                                + u32::from(image.data[i + 2]))
+                                / 3;
+                            f(
+                                x + off_x,
+                                y + off_y,
+                                Color((coverage << 24) | base.0 & 0xFF_FF_FF),
+                            );
+                            i += 4;
+                        }
+                    }
                 }
             }
         }
     }
+
+    /// Enumerate pixels of the drop shadow of a glyph, blurred by the radius of the shadow and
+    /// moved by its offset
+    ///
+    /// The shadow is the coverage of the glyph in the color of the shadow, so color glyphs cast
+    /// the shadow of their shape.
+    pub fn with_shadow_pixels<F: FnMut(i32, i32, Color)>(
+        &mut self,
+        font_system: &mut FontSystem,
+        cache_key: CacheKey,
+        shadow: Shadow,
+        mut f: F,
+    ) {
+        let Some(image) = self.get_image(font_system, cache_key) else {
+            return;
+        };
+        let radius = math::roundf(shadow.blur.max(0.0)) as usize;
+        let width = image.placement.width as usize + 2 * radius;
+        let height = image.placement.height as usize + 2 * radius;
+        let x = image.placement.left - radius as i32 + math::roundf(shadow.x) as i32;
+        let y = -image.placement.top - radius as i32 + math::roundf(shadow.y) as i32;
+
+        // Coverage of each pixel, with room around the glyph for the blur
+        let mut coverage = vec![0.0; width * height];
+        let bytes = match image.content {
+            Content::Mask => 1,
+            Content::Color | Content::SubpixelMask => 4,
+        };
+        for (i, pixel) in image.data.chunks_exact(bytes).enumerate() {
+            let (off_x, off_y) = (
+                i % image.placement.width as usize,
+                i / image.placement.width as usize,
+            );
+            coverage[(off_y + radius) * width + off_x + radius] = match image.content {
+                Content::Mask => f32::from(pixel[0]),
+                Content::Color => f32::from(pixel[3]),
+                Content::SubpixelMask => {
+                    (f32::from(pixel[0]) + f32::from(pixel[1]) + f32::from(pixel[2])) / 3.0
+                }
+            };
+        }
+        if radius > 0 {
+            box_blur(&mut coverage, width, height, radius);
+        }
+
+        let alpha = f32::from(shadow.color.a()) / 255.0;
+        for (i, coverage) in coverage.into_iter().enumerate() {
+            let a = math::roundf(coverage * alpha) as u32;
+            if a > 0 {
+                f(
+                    x + (i % width) as i32,
+                    y + (i / width) as i32,
+                    Color((a.min(0xFF) << 24) | shadow.color.0 & 0xFF_FF_FF),
+                );
+            }
+        }
+    }
+}
+
+/// Blur `values`, rows of `width`, with a box of `radius` pixels around each pixel, horizontally
+/// and then vertically
+fn box_blur(values: &mut [f32], width: usize, height: usize, radius: usize) {
+    let size = (2 * radius + 1) as f32;
+    let mut line = Vec::new();
+    for (len, stride, count, step) in [(width, 1, height, width), (height, width, width, 1)] {
+        for j in 0..count {
+            let start = j * step;
+            line.clear();
+            line.extend((0..len).map(|i| values[start + i * stride]));
+            let mut sum: f32 = line.iter().take(radius + 1).sum();
+            for i in 0..len {
+                values[start + i * stride] = sum / size;
+                if i + radius + 1 < len {
+                    sum += line[i + radius + 1];
+                }
+                if i >= radius {
+                    sum -= line[i - radius];
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_gamma_table() {
+    assert!(RasterOptions::default().gamma_table().is_none());
+
+    let table = RasterOptions {
+        gamma: 2.2,
+        ..RasterOptions::default()
+    }
+    .gamma_table()
+    .unwrap();
+    assert_eq!(table[0], 0);
+    assert_eq!(table[255], 255);
+    // Partial coverage is raised, which makes text look bolder
+    assert!(table[128] > 128);
+    assert!(table.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    for gamma in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        let options = RasterOptions {
+            gamma,
+            ..RasterOptions::default()
+        };
+        assert!(options.gamma_table().is_none());
+    }
+}
+
+#[test]
+fn test_raster_options_invalid_gamma() {
+    let mut cache = SwashCache::new();
+    for gamma in [f32::NAN, f32::INFINITY, -2.0] {
+        cache.set_raster_options(RasterOptions {
+            gamma,
+            ..RasterOptions::default()
+        });
+        assert_eq!(cache.raster_options(), RasterOptions::default());
+    }
+
+    // Setting a NaN gamma again keeps cached images
+    cache.image_cache.insert(
+        CacheKey::new(
+            fontdb::ID::dummy(),
+            0,
+            16.0,
+            (0.0, 0.0),
+            CacheKeyFlags::empty(),
+        )
+        .0,
+        None,
+    );
+    cache.set_raster_options(RasterOptions {
+        gamma: f32::NAN,
+        ..RasterOptions::default()
+    });
+    assert_eq!(cache.image_cache.len(), 1);
 }
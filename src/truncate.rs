@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    Align, AttrsList, BufferLine, FontSystem, LayoutGlyph, LayoutLine, ShapeBuffer, ShapeWord,
+};
+
+/// Character shown where text is elided
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Where text is elided when it does not fit in the lines of a buffer, see
+/// [`crate::Buffer::set_max_lines`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Truncate {
+    /// Keep the first lines, ending the last one with an ellipsis
+    #[default]
+    End,
+    /// Keep lines from the start and the end, joined by an ellipsis in the middle line
+    Middle,
+    /// Keep the last lines, starting the first one with an ellipsis
+    Start,
+}
+
+/// A line with an ellipsis that replaces the layout lines removed by truncation
+#[derive(Clone, Debug)]
+pub(crate) struct Truncation {
+    /// Indices of the replaced layout lines, counted across all lines of the buffer
+    pub hidden: Range<usize>,
+    /// Index of the line that the glyphs belong to
+    pub line_i: usize,
+    pub layout: LayoutLine,
+}
+
+/// Check if a glyph's cluster is whitespace, or empty like the hyphen of a wrapped word
+fn is_blank(text: &str, glyph: &LayoutGlyph) -> bool {
+    text.get(glyph.start..glyph.end)
+        .map_or(true, |cluster| cluster.chars().all(char::is_whitespace))
+}
+
+/// Get the clusters of a line in logical order, as ranges of glyphs sorted by start
+fn clusters(glyphs: &[LayoutGlyph]) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..glyphs.len()).collect();
+    order.sort_by_key(|&i| glyphs[i].start);
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+    for i in order {
+        match clusters.last_mut() {
+            Some(cluster) if glyphs[cluster[0]].start == glyphs[i].start => cluster.push(i),
+            _ => clusters.push(vec![i]),
+        }
+    }
+    clusters
+}
+
+/// Keep whole clusters from the start of `clusters` that fit in `width`, without a blank cluster
+/// at the end, returning the glyph indices kept and their width
+fn keep_clusters<'a>(
+    text: &str,
+    glyphs: &[LayoutGlyph],
+    clusters: impl Iterator<Item = &'a Vec<usize>>,
+    width: f32,
+) -> (Vec<usize>, f32) {
+    let cluster_w = |cluster: &Vec<usize>| cluster.iter().map(|&i| glyphs[i].w).sum::<f32>();
+    let mut kept: Vec<&Vec<usize>> = Vec::new();
+    let mut kept_w = 0.0;
+    for cluster in clusters {
+        let w = cluster_w(cluster);
+        if kept_w + w > width {
+            break;
+        }
+        kept_w += w;
+        kept.push(cluster);
+    }
+    while let Some(cluster) = kept.last() {
+        if !is_blank(text, &glyphs[cluster[0]]) {
+            break;
+        }
+        kept_w -= cluster_w(cluster);
+        kept.pop();
+    }
+    (kept.into_iter().flatten().copied().collect(), kept_w)
+}
+
+/// Lay out a line of `line` with an ellipsis, keeping the start of `head_opt` before it and the
+/// end of `tail_opt` after it, so that the line fits in `width`
+///
+/// The kept glyphs are moved next to the ellipsis and the line is aligned again.
+pub(crate) fn ellipsize(
+    scratch: &mut ShapeBuffer,
+    font_system: &mut FontSystem,
+    line: &BufferLine,
+    head_opt: Option<&LayoutLine>,
+    tail_opt: Option<&LayoutLine>,
+    font_size: f32,
+    width: f32,
+) -> LayoutLine {
+    let text = line.text();
+    let rtl = line.shape_opt().as_ref().map_or(false, |shape| shape.rtl);
+    let level = if rtl {
+        unicode_bidi::Level::rtl()
+    } else {
+        unicode_bidi::Level::ltr()
+    };
+
+    let head_clusters = head_opt.map(|head| clusters(&head.glyphs));
+    let tail_clusters = tail_opt.map(|tail| clusters(&tail.glyphs));
+
+    // The ellipsis takes the attributes of the start of the kept text, or of its end if only the
+    // end is kept
+    let attrs_index = match (head_opt, &head_clusters, tail_opt, &tail_clusters) {
+        (Some(head), Some(clusters), _, _) if !clusters.is_empty() => {
+            head.glyphs[clusters[0][0]].start
+        }
+        (_, _, Some(tail), Some(clusters)) if !clusters.is_empty() => {
+            tail.glyphs[clusters[clusters.len() - 1][0]].start
+        }
+        _ => 0,
+    };
+    let ellipsis_attrs = AttrsList::new(line.attrs_list().get_span(attrs_index));
+    let ellipsis = ShapeWord::new_in_buffer(
+        scratch,
+        font_system,
+        ELLIPSIS,
+        &ellipsis_attrs,
+        0..ELLIPSIS.len(),
+        level,
+        false,
+        line.shaping(),
+    );
+    let ellipsis_w = font_size * ellipsis.x_advance;
+    let available = (width - ellipsis_w).max(0.0);
+
+    // Text before the ellipsis gets half of the space if there is text after it
+    let (head_kept, head_w) = match (head_opt, &head_clusters) {
+        (Some(head), Some(clusters)) => {
+            let head_width = if tail_opt.is_some() {
+                available / 2.0
+            } else {
+                available
+            };
+            keep_clusters(text, &head.glyphs, clusters.iter(), head_width)
+        }
+        _ => (Vec::new(), 0.0),
+    };
+    let head_end = head_opt.and_then(|head| head_kept.iter().map(|&i| head.glyphs[i].end).max());
+    let (tail_kept, tail_w) = match (tail_opt, &tail_clusters) {
+        (Some(tail), Some(clusters)) => keep_clusters(
+            text,
+            &tail.glyphs,
+            clusters.iter().rev().take_while(|cluster| {
+                head_end.map_or(true, |end| tail.glyphs[cluster[0]].start >= end)
+            }),
+            available - head_w,
+        ),
+        _ => (Vec::new(), 0.0),
+    };
+    let tail_start =
+        tail_opt.and_then(|tail| tail_kept.iter().map(|&i| tail.glyphs[i].start).min());
+
+    // Where the elided text was, for cursor positions on the ellipsis
+    let cut = head_end
+        .or_else(|| head_opt.and_then(|head| head.glyphs.iter().map(|glyph| glyph.start).min()))
+        .or(tail_start)
+        .or_else(|| tail_opt.and_then(|tail| tail.glyphs.iter().map(|glyph| glyph.end).max()))
+        .unwrap_or(0);
+
+    let line_w = head_w + ellipsis_w + tail_w;
+    let align = line
+        .align()
+        .unwrap_or(if rtl { Align::Right } else { Align::Left });
+    let mut x = match (align, rtl) {
+        (Align::Left, _) | (Align::Justified, false) | (Align::End, true) => 0.0,
+        (Align::Right, _) | (Align::Justified, true) | (Align::End, false) => width - line_w,
+        (Align::Center, _) => (width - line_w) / 2.0,
+    };
+
+    // Kept glyphs in visual order, with the head in reading order before the tail
+    let visual = |layout_opt: Option<&LayoutLine>, mut kept: Vec<usize>| -> Vec<LayoutGlyph> {
+        let Some(layout) = layout_opt else {
+            return Vec::new();
+        };
+        kept.sort_by(|&a, &b| {
+            layout.glyphs[a]
+                .x
+                .total_cmp(&layout.glyphs[b].x)
+                .then(a.cmp(&b))
+        });
+        kept.into_iter().map(|i| layout.glyphs[i].clone()).collect()
+    };
+    let head_glyphs = visual(head_opt, head_kept);
+    let tail_glyphs = visual(tail_opt, tail_kept);
+    let ellipsis_glyphs: Vec<LayoutGlyph> = ellipsis
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            let mut glyph = glyph.layout(font_size, 0.0, 0.0, font_size * glyph.x_advance, level);
+            glyph.start = cut;
+            glyph.end = cut;
+            glyph
+        })
+        .collect();
+    let parts = if rtl {
+        [tail_glyphs, ellipsis_glyphs, head_glyphs]
+    } else {
+        [head_glyphs, ellipsis_glyphs, tail_glyphs]
+    };
+
+    let mut glyphs = Vec::with_capacity(parts.iter().map(Vec::len).sum());
+    for mut glyph in parts.into_iter().flatten() {
+        glyph.x = x;
+        x += glyph.w;
+        glyphs.push(glyph);
+    }
+
+    let mut layout_line = LayoutLine {
+        w: line_w,
+        max_ascent: 0.0,
+        max_descent: 0.0,
+        max_win_ascent: 0.0,
+        max_win_descent: 0.0,
+        primary_ascent: 0.0,
+        primary_descent: 0.0,
+        primary_win_ascent: 0.0,
+        primary_win_descent: 0.0,
+        glyphs,
+        ruby: Vec::new(),
+        ruby_height: 0.0,
+        extra_height: 0.0,
+        max_cap_height: 0.0,
+        drop_cap: Vec::new(),
+        marker: Vec::new(),
+    };
+    for source in [head_opt, tail_opt].into_iter().flatten() {
+        layout_line.max_ascent = layout_line.max_ascent.max(source.max_ascent);
+        layout_line.max_descent = layout_line.max_descent.max(source.max_descent);
+        layout_line.max_win_ascent = layout_line.max_win_ascent.max(source.max_win_ascent);
+        layout_line.max_win_descent = layout_line.max_win_descent.max(source.max_win_descent);
+        layout_line.extra_height = layout_line.extra_height.max(source.extra_height);
+        layout_line.max_cap_height = layout_line.max_cap_height.max(source.max_cap_height);
+    }
+    if let Some(source) = head_opt.or(tail_opt) {
+        layout_line.primary_ascent = source.primary_ascent;
+        layout_line.primary_descent = source.primary_descent;
+        layout_line.primary_win_ascent = source.primary_win_ascent;
+        layout_line.primary_win_descent = source.primary_win_descent;
+    }
+    for glyph in ellipsis.glyphs.iter() {
+        layout_line.max_ascent = layout_line.max_ascent.max(font_size * glyph.ascent);
+        layout_line.max_descent = layout_line.max_descent.max(font_size * glyph.descent);
+        layout_line.max_win_ascent = layout_line.max_win_ascent.max(font_size * glyph.win_ascent);
+        layout_line.max_win_descent = layout_line
+            .max_win_descent
+            .max(font_size * glyph.win_descent);
+    }
+    layout_line
+}
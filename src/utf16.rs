@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// A character that is not a single byte in UTF-8
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Utf16Char {
+    /// Byte offset of the character
+    utf8: usize,
+    /// Offset of the character in UTF-16 code units
+    utf16: usize,
+    /// Length of the character in bytes
+    len_utf8: u8,
+    /// Length of the character in UTF-16 code units
+    len_utf16: u8,
+}
+
+/// Maps byte offsets in UTF-8 text to offsets in UTF-16 code units, and back
+///
+/// Only characters outside of ASCII are stored, so offsets are found with a binary search over
+/// those. Offsets inside of a character map to the start of the character, and offsets past the
+/// end of the text map to its end.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Utf16Map {
+    chars: Vec<Utf16Char>,
+    len_utf8: usize,
+    len_utf16: usize,
+}
+
+impl Utf16Map {
+    /// Create a map of the given text
+    pub fn new(text: &str) -> Self {
+        let mut chars = Vec::new();
+        let mut utf16 = 0;
+        for (utf8, c) in text.char_indices() {
+            if !c.is_ascii() {
+                chars.push(Utf16Char {
+                    utf8,
+                    utf16,
+                    len_utf8: c.len_utf8() as u8,
+                    len_utf16: c.len_utf16() as u8,
+                });
+            }
+            utf16 += c.len_utf16();
+        }
+        Self {
+            chars,
+            len_utf8: text.len(),
+            len_utf16: utf16,
+        }
+    }
+
+    /// Length of the text in bytes
+    pub fn len_utf8(&self) -> usize {
+        self.len_utf8
+    }
+
+    /// Length of the text in UTF-16 code units
+    pub fn len_utf16(&self) -> usize {
+        self.len_utf16
+    }
+
+    /// Convert a byte offset to an offset in UTF-16 code units
+    pub fn to_utf16(&self, index: usize) -> usize {
+        let index = index.min(self.len_utf8);
+        let i = self.chars.partition_point(|c| c.utf8 <= index);
+        match i.checked_sub(1).map(|i| self.chars[i]) {
+            Some(c) if index < c.utf8 + c.len_utf8 as usize => c.utf16,
+            Some(c) => c.utf16 + c.len_utf16 as usize + (index - c.utf8 - c.len_utf8 as usize),
+            None => index,
+        }
+    }
+
+    /// Convert an offset in UTF-16 code units to a byte offset
+    pub fn to_utf8(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len_utf16);
+        let i = self.chars.partition_point(|c| c.utf16 <= offset);
+        match i.checked_sub(1).map(|i| self.chars[i]) {
+            Some(c) if offset < c.utf16 + c.len_utf16 as usize => c.utf8,
+            Some(c) => c.utf8 + c.len_utf8 as usize + (offset - c.utf16 - c.len_utf16 as usize),
+            None => offset,
+        }
+    }
+}
+
+#[test]
+fn test_utf16_map() {
+    let text = "aé€\u{1F600}b";
+    let map = Utf16Map::new(text);
+    assert_eq!(map.len_utf8(), text.len());
+    assert_eq!(map.len_utf16(), text.encode_utf16().count());
+    for (index, c) in text.char_indices() {
+        let offset = text[..index].encode_utf16().count();
+        assert_eq!(map.to_utf16(index), offset);
+        assert_eq!(map.to_utf8(offset), index);
+        // Offsets inside of a character map to its start
+        for inner in 1..c.len_utf8() {
+            assert_eq!(map.to_utf16(index + inner), offset);
+        }
+        for inner in 1..c.len_utf16() {
+            assert_eq!(map.to_utf8(offset + inner), index);
+        }
+    }
+    assert_eq!(map.to_utf16(text.len()), map.len_utf16());
+    assert_eq!(map.to_utf8(map.len_utf16() + 5), text.len());
+
+    let ascii = Utf16Map::new("abc");
+    assert_eq!(ascii.to_utf16(2), 2);
+    assert_eq!(ascii.to_utf8(2), 2);
+}
@@ -0,0 +1,54 @@
+use cosmic_text::{
+    AnsiConverter, AnsiPalette, Attrs, AttrsList, Color, DecorationLine, DecorationStyle, Style,
+    Weight,
+};
+
+// Tests that SGR sequences set attributes with colors from the palette, 256 colors and true
+// colors, and that other escape sequences are removed.
+#[test]
+fn ansi_sgr_spans() {
+    let palette = AnsiPalette::default();
+    let mut converter = AnsiConverter::new(palette.clone());
+    let attrs = Attrs::new();
+    let (text, attrs_list) = converter.convert(
+        "a\x1b[1;31mb\x1b[22;3;4:3mc\x1b[0m\x1b]0;title\x07d\x1b[2K\
+         \x1b[38;5;196;48:2::0:0:255me\x1b[38:2:1:2:3;7mf\x1b[mg",
+        attrs,
+    );
+    assert_eq!(text, "abcdefg");
+    let red = palette.colors[1];
+    let mut underlined = attrs.color(red).style(Style::Italic);
+    underlined.decoration.underline = Some(DecorationLine::new(DecorationStyle::Wavy));
+    let blue = Color::rgb(0, 0, 255);
+    let mut expected = AttrsList::new(attrs);
+    expected.add_span(1..2, attrs.color(red).weight(Weight::BOLD));
+    expected.add_span(2..3, underlined);
+    expected.add_span(
+        4..5,
+        attrs.color(Color::rgb(255, 0, 0)).background_color(blue),
+    );
+    expected.add_span(
+        5..6,
+        attrs.color(blue).background_color(Color::rgb(1, 2, 3)),
+    );
+    assert_eq!(attrs_list, expected);
+    assert_eq!(palette.colors[196], Color::rgb(255, 0, 0));
+    assert_eq!(palette.colors[244], Color::rgb(128, 128, 128));
+}
+
+// Tests that the rendition carries over to the next conversion until it is reset.
+#[test]
+fn ansi_state_across_lines() {
+    let mut converter = AnsiConverter::default();
+    let attrs = Attrs::new();
+    let green = converter.palette().colors[10];
+    let (text, _) = converter.convert("\x1b[92mfirst", attrs);
+    assert_eq!(text, "first");
+    let (text, attrs_list) = converter.convert("second", attrs);
+    assert_eq!(text, "second");
+    assert_eq!(attrs_list.get_span(0), attrs.color(green));
+
+    converter.reset();
+    let (_, attrs_list) = converter.convert("third", attrs);
+    assert_eq!(attrs_list, AttrsList::new(attrs));
+}
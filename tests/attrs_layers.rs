@@ -0,0 +1,95 @@
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, AttrsOverride, Buffer, Color, FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that layers of overrides are applied over the spans of an attributes list in order,
+// and only change the attributes they set.
+#[test]
+fn attrs_layers_resolve() {
+    let attrs = Attrs::new().color(Color::rgb(0, 0, 0));
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(0..4, attrs.weight(Weight::BOLD));
+    attrs_list.add_layer_span(2, 2..6, AttrsOverride::new().color(Color::rgb(255, 0, 0)));
+    attrs_list.add_layer_span(
+        1,
+        0..8,
+        AttrsOverride::new()
+            .color(Color::rgb(0, 255, 0))
+            .background_color(Color::rgb(0, 0, 255)),
+    );
+    assert_eq!(attrs_list.layers(), vec![1, 2]);
+
+    let span = attrs_list.get_span(0);
+    assert_eq!(span.weight, Weight::BOLD);
+    assert_eq!(span.color_opt, Some(Color::rgb(0, 255, 0)));
+    assert_eq!(span.background_color_opt, Some(Color::rgb(0, 0, 255)));
+    let span = attrs_list.get_span(3);
+    assert_eq!(span.weight, Weight::BOLD);
+    assert_eq!(span.color_opt, Some(Color::rgb(255, 0, 0)));
+    assert_eq!(span.background_color_opt, Some(Color::rgb(0, 0, 255)));
+    let span = attrs_list.get_span(8);
+    assert_eq!(span, attrs);
+
+    // Splitting keeps the layers on both sides
+    let after = attrs_list.split_off(4);
+    assert_eq!(after.get_span(0).color_opt, Some(Color::rgb(255, 0, 0)));
+    assert_eq!(after.get_span(0).weight, Weight::NORMAL);
+    assert_eq!(after.get_span(3).color_opt, Some(Color::rgb(0, 255, 0)));
+    assert_eq!(after.get_span(4), attrs);
+
+    attrs_list.clear_layer(2);
+    attrs_list.clear_layer(1);
+    assert!(attrs_list.layers().is_empty());
+    assert_eq!(attrs_list.get_span(3), attrs.weight(Weight::BOLD));
+}
+
+// Tests that setting a layer of a buffer only shapes again the lines where the layer changed.
+#[test]
+fn attrs_layers_buffer() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 300.0, 200.0);
+    buffer.set_text(
+        &mut font_system,
+        "one\ntwo\nthree",
+        attrs,
+        Shaping::Advanced,
+    );
+    assert!(buffer.lines.iter().all(|line| line.shape_opt().is_some()));
+
+    let red = AttrsOverride::new().color(Color::rgb(255, 0, 0));
+    buffer.set_attrs_layer(&mut font_system, 0, [(1, 0..3, red.clone())]);
+    let run = buffer.layout_runs().nth(1).unwrap();
+    assert!(run
+        .glyphs
+        .iter()
+        .all(|glyph| glyph.color_opt == Some(Color::rgb(255, 0, 0))));
+    assert_eq!(
+        buffer.layout_runs().next().unwrap().glyphs[0].color_opt,
+        None
+    );
+
+    // Moving the span from the second line to the third changes only those lines
+    let mut borrowed = buffer.borrow_with(&mut font_system);
+    borrowed.set_attrs_layer(0, [(2, 0..5, red.clone())]);
+    assert!(buffer.lines[1].attrs_list().layer_spans(0).is_empty());
+    assert_eq!(buffer.lines[2].attrs_list().layer_spans(0).len(), 1);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs[1].glyphs[0].color_opt, None);
+    assert_eq!(runs[2].glyphs[4].color_opt, Some(Color::rgb(255, 0, 0)));
+
+    // Lines are only reset when their layer changes
+    assert!(!buffer.lines[0].set_attrs_layer(0, []));
+    assert!(buffer.lines[0].shape_opt().is_some());
+    assert!(!buffer.lines[2].set_attrs_layer(0, [(0..5, red.clone())]));
+    assert!(buffer.lines[2].shape_opt().is_some());
+    assert!(buffer.lines[1].set_attrs_layer(0, [(0..1, red)]));
+    assert!(buffer.lines[1].shape_opt().is_none());
+}
@@ -0,0 +1,72 @@
+use cosmic_text::{
+    fontdb, Attrs, BackgroundRect, Buffer, Color, FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that neighboring glyphs with the same background color share a rectangle covering the
+// height of their line, and that the rectangles are drawn behind the text.
+#[test]
+fn background_color_rects() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let yellow = Color::rgb(0xFF, 0xFF, 0);
+    let green = Color::rgb(0, 0xFF, 0);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("x\n", attrs),
+            ("ab", attrs.background_color(yellow)),
+            ("cd", attrs.background_color(yellow)),
+            (" ", attrs),
+            ("ef", attrs.background_color(green)),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs[0].backgrounds(30.0).is_empty());
+    let run = &runs[1];
+    let glyphs = run.glyphs;
+    assert_eq!(
+        run.backgrounds(30.0),
+        [
+            BackgroundRect {
+                color: yellow,
+                x: glyphs[0].x,
+                y: 30.0,
+                w: glyphs[3].x + glyphs[3].w - glyphs[0].x,
+                h: 30.0,
+            },
+            BackgroundRect {
+                color: green,
+                x: glyphs[5].x,
+                y: 30.0,
+                w: glyphs[6].x + glyphs[6].w - glyphs[5].x,
+                h: 30.0,
+            },
+        ]
+    );
+
+    let mut rects = Vec::new();
+    let mut swash_cache = cosmic_text::SwashCache::new();
+    buffer.draw(
+        &mut font_system,
+        &mut swash_cache,
+        Color::rgb(0, 0, 0),
+        |x, y, w, h, color| {
+            if color == yellow || color == green {
+                rects.push((x, y, w, h));
+            }
+        },
+    );
+    assert_eq!(rects.len(), 2);
+    assert_eq!(rects[0], (glyphs[0].x as i32, 30, rects[0].2, 30));
+}
@@ -0,0 +1,41 @@
+use cosmic_text::{fontdb, Align, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that balanced wrapping keeps the number of lines of word wrapping while making them about
+// as wide as each other, still aligned within the width of the buffer.
+#[test]
+fn balanced_wrap_evens_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(200.0, 100.0);
+    buffer.set_text(
+        "aaa bbb ccc ddd eee",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let widths: Vec<f32> = buffer.layout_runs().map(|run| run.line_w).collect();
+    assert_eq!(widths.len(), 2);
+    let advance = widths[1] / 3.0;
+    assert!((widths[0] - 15.0 * advance).abs() < 0.01);
+
+    buffer.set_wrap(Wrap::Balanced);
+    let widths: Vec<f32> = buffer.layout_runs().map(|run| run.line_w).collect();
+    assert_eq!(widths.len(), 2);
+    assert!((widths[0] - 11.0 * advance).abs() < 0.01);
+    assert!((widths[1] - 7.0 * advance).abs() < 0.01);
+
+    buffer.lines[0].set_align(Some(Align::Center));
+    buffer.shape_until_scroll(false);
+    let run = buffer.layout_runs().next().unwrap();
+    assert!((run.glyphs[0].x - (200.0 - 11.0 * advance) / 2.0).abs() < 0.01);
+
+    // Text that fits on one line is left as is
+    buffer.set_size(300.0, 100.0);
+    assert_eq!(buffer.layout_runs().count(), 1);
+}
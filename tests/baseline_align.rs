@@ -0,0 +1,72 @@
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, BaselineAlign, Buffer, FontSystem, Metrics, Shaping, VerticalMetrics,
+    Weight,
+};
+
+fn font_system() -> FontSystem {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    for path in ["fonts/FiraMono-Medium.ttf", "fonts/NotoSans-Regular.ttf"] {
+        let font = std::fs::read(path).unwrap();
+        font_system.db_mut().load_font_data(font);
+    }
+    font_system
+}
+
+// Tests that the baseline of a line mixing fonts is placed with the metrics of the primary font
+// or of the whole line, and that the ascent and descent used are reported on the layout run.
+#[test]
+fn baseline_align_mixed_fonts() {
+    let mut font_system = font_system();
+    let mono = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let sans = Attrs::new().family(fontdb::Family::Name("Noto Sans"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 40.0));
+    buffer.set_size(&mut font_system, 300.0, 200.0);
+    buffer.set_text(&mut font_system, "Hello World", mono, Shaping::Advanced);
+    let mut attrs_list = AttrsList::new(mono);
+    attrs_list.add_span(6..11, sans);
+    buffer.lines[0].set_attrs_list(attrs_list);
+    assert_eq!(buffer.baseline_align(), BaselineAlign::LineMax);
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    // Ascent and descent of the font of each glyph
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph_metrics: Vec<(f32, f32)> = run
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            let (ascent, descent) = font_system
+                .get_font(glyph.font_id)
+                .unwrap()
+                .ascent_descent_em(VerticalMetrics::Typographic);
+            (ascent * glyph.font_size, descent * glyph.font_size)
+        })
+        .collect();
+    let max_ascent = glyph_metrics.iter().map(|m| m.0).fold(0.0, f32::max);
+    let max_descent = glyph_metrics.iter().map(|m| m.1).fold(0.0, f32::max);
+    let (primary_ascent, primary_descent) = glyph_metrics[0];
+    let glyph_ys: Vec<f32> = run.glyphs.iter().map(|glyph| glyph.y).collect();
+
+    let line_y = |ascent: f32, descent: f32| (40.0 - ascent - descent) / 2.0 + ascent;
+    assert!((run.ascent - max_ascent).abs() < 0.001);
+    assert!((run.descent - max_descent).abs() < 0.001);
+    assert!((run.line_y - line_y(max_ascent, max_descent)).abs() < 0.001);
+
+    buffer.set_baseline_align(&mut font_system, BaselineAlign::Primary);
+    let run = buffer.layout_runs().next().unwrap();
+    assert!((run.ascent - primary_ascent).abs() < 0.001);
+    assert!((run.descent - primary_descent).abs() < 0.001);
+    assert!((run.line_y - line_y(primary_ascent, primary_descent)).abs() < 0.001);
+
+    // Glyphs of fonts with shorter descents move down to the bottom of the line
+    buffer.set_baseline_align(&mut font_system, BaselineAlign::Ideographic);
+    let run = buffer.layout_runs().next().unwrap();
+    assert!((run.ascent - max_ascent).abs() < 0.001);
+    assert!((run.descent - max_descent).abs() < 0.001);
+    for ((glyph, y), metrics) in run.glyphs.iter().zip(glyph_ys).zip(glyph_metrics) {
+        assert!((glyph.y - (y + max_descent - metrics.1)).abs() < 0.001);
+    }
+}
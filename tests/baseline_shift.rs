@@ -0,0 +1,50 @@
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, BaselineShift, Buffer, FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that superscripts and subscripts are scaled down and moved above and below the
+// baseline, and that shifted text makes room for itself in its line.
+#[test]
+fn baseline_shift_superscript_subscript() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 60.0));
+    buffer.set_size(&mut font_system, 300.0, 200.0);
+    buffer.set_text(&mut font_system, "H2O x2 y", attrs, Shaping::Advanced);
+    let run = buffer.layout_runs().next().unwrap();
+    let (ascent, descent) = (run.ascent, run.descent);
+
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(1..2, attrs.baseline_shift(BaselineShift::Subscript));
+    attrs_list.add_span(5..6, attrs.baseline_shift(BaselineShift::Superscript));
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph = |start: usize| run.glyphs.iter().find(|g| g.start == start).unwrap();
+    assert_eq!(glyph(0).font_size, 20.0);
+    assert_eq!(glyph(0).y_offset, 0.0);
+    assert!(glyph(1).font_size < 20.0);
+    assert!(glyph(1).y_offset < 0.0);
+    assert!(glyph(5).font_size < 20.0);
+    assert!(glyph(5).y_offset > 0.0);
+    assert!(glyph(0).physical((0.0, 0.0), 1.0).y < glyph(1).physical((0.0, 0.0), 1.0).y);
+    assert!(glyph(0).physical((0.0, 0.0), 1.0).y > glyph(5).physical((0.0, 0.0), 1.0).y);
+
+    // Text raised by a whole em needs that much more room above the baseline
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(7..8, attrs.baseline_shift(BaselineShift::Em(1.0)));
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let run = buffer.layout_runs().next().unwrap();
+    assert!((run.ascent - (ascent + 20.0)).abs() < 0.001);
+    assert!((run.descent - descent).abs() < 0.001);
+    let glyph = run.glyphs.iter().find(|g| g.start == 7).unwrap();
+    assert_eq!(glyph.font_size, 20.0);
+    assert!((glyph.y_offset - 1.0).abs() < 0.001);
+}
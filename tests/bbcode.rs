@@ -0,0 +1,76 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, Color, FontSystem, Metrics, RichText, Shaping, Style, Weight,
+};
+
+fn pieces<'a>(rich_text: &'a RichText) -> Vec<(&'a str, Attrs<'a>)> {
+    rich_text
+        .paragraphs()
+        .flat_map(|(spans, _)| spans)
+        .collect()
+}
+
+// Tests that supported tags set attributes, including nested tags, colors and links.
+#[test]
+fn bbcode_tags() {
+    let attrs = Attrs::new();
+    let rich_text = RichText::from_bbcode(
+        "a [b]bold [I]both[/I][/b] [color=#ff0000]red[/color] [color=blue][size=30]big[/size][/color] \
+         [url]https://example.com[/url] [url=\"https://example.org\"]site[/url] \
+         [code][b]as is[/code]",
+        attrs,
+    );
+    let bold = attrs.weight(Weight::BOLD);
+    let blue = attrs.color(Color::rgb(0, 0, 255));
+    assert_eq!(
+        pieces(&rich_text),
+        [
+            ("a ", attrs),
+            ("bold ", bold),
+            ("both", bold.style(Style::Italic)),
+            (" ", attrs),
+            ("red", attrs.color(Color::rgb(255, 0, 0))),
+            (" ", attrs),
+            ("big", blue.font_size(30.0)),
+            (" ", attrs),
+            ("https://example.com", attrs.link("https://example.com")),
+            (" ", attrs),
+            ("site", attrs.link("https://example.org")),
+            (" ", attrs),
+            ("[b]as is", attrs.family(fontdb::Family::Monospace)),
+        ]
+    );
+}
+
+// Tests that unbalanced and unknown tags are handled without errors, keeping stray tags as text.
+#[test]
+fn bbcode_unbalanced_tags() {
+    let attrs = Attrs::new();
+    let bold = attrs.weight(Weight::BOLD);
+    let italic_bold = bold.style(Style::Italic);
+
+    // A closing tag closes the tags opened after it, and stray closing tags are text
+    let rich_text = RichText::from_bbcode("[b]a[i]b[/b]c[/i][[wave]d[color=nope]e", attrs);
+    assert_eq!(
+        pieces(&rich_text),
+        [
+            ("a", bold),
+            ("b", italic_bold),
+            ("c[/i][[wave]d[color=nope]e", attrs),
+        ]
+    );
+
+    // Tags left open apply to the end of the text, and an unclosed bracket is text
+    let rich_text = RichText::from_bbcode("x[b]y[i]z [", attrs);
+    assert_eq!(
+        pieces(&rich_text),
+        [("x", attrs), ("y", bold), ("z [", italic_bold)]
+    );
+
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_bbcode(&mut font_system, "[b]one\ntwo", attrs, Shaping::Advanced);
+    let texts: Vec<&str> = buffer.lines.iter().map(|line| line.text()).collect();
+    assert_eq!(texts, ["one", "two"]);
+    assert_eq!(buffer.lines[1].attrs_list().get_span(0), bold);
+}
@@ -0,0 +1,47 @@
+use cosmic_text::{fontdb, Attrs, Buffer, CaretStop, FontSystem, Metrics, Shaping};
+
+fn caret_stops(font_system: &mut FontSystem, text: &str) -> (Vec<CaretStop>, f32) {
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text(
+        text,
+        Attrs::new().family(fontdb::Family::Name("Noto Sans")),
+        Shaping::Advanced,
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    (run.caret_stops(), run.line_w)
+}
+
+// Tests that a ligature has a caret stop between each of its letters.
+#[test]
+fn caret_stops_ligature() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSans-Regular.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let (stops, line_w) = caret_stops(&mut font_system, "fix");
+    let indices: Vec<usize> = stops.iter().map(|stop| stop.index).collect();
+    assert_eq!(indices, [0, 1, 2, 3]);
+    assert_eq!(stops[0].x, 0.0);
+    assert_eq!(stops[3].x, line_w);
+    assert!(stops.windows(2).all(|pair| pair[0].x < pair[1].x));
+}
+
+// Tests that stops of right-to-left text are listed from left to right, ending at its start.
+#[test]
+fn caret_stops_rtl() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSans-Regular.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let font = std::fs::read("fonts/NotoSansHebrew.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let text = "\u{05D0}\u{05D1}";
+    let (stops, _line_w) = caret_stops(&mut font_system, text);
+    let indices: Vec<usize> = stops.iter().map(|stop| stop.index).collect();
+    assert_eq!(indices, [4, 2, 0]);
+    assert!(stops.windows(2).all(|pair| pair[0].x < pair[1].x));
+}
@@ -0,0 +1,52 @@
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, BaselineShift, Buffer, FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that text clamped to its line does not make the line taller, while the same text left
+// unclamped does.
+#[test]
+fn clamp_line_height_large_text() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 300.0, 200.0);
+    buffer.set_text(&mut font_system, "Hello\nWorld", attrs, Shaping::Advanced);
+    let run_metrics = |buffer: &Buffer| {
+        buffer
+            .layout_runs()
+            .map(|run| (run.line_top, run.line_y, run.ascent, run.descent))
+            .collect::<Vec<_>>()
+    };
+    let plain = run_metrics(&buffer);
+
+    for clamp in [false, true] {
+        for span_attrs in [
+            attrs.font_size(40.0),
+            attrs.baseline_shift(BaselineShift::Em(1.0)),
+        ] {
+            let mut attrs_list = AttrsList::new(attrs);
+            attrs_list.add_span(1..3, span_attrs.clamp_line_height(clamp));
+            buffer.lines[0].set_attrs_list(attrs_list);
+            buffer.shape_until_scroll(&mut font_system, false);
+            let metrics = run_metrics(&buffer);
+            if clamp {
+                assert_eq!(metrics, plain);
+            } else {
+                assert!(metrics[0].2 > plain[0].2);
+            }
+        }
+    }
+
+    // A line of only clamped text still has room for it
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(0..5, attrs.clamp_line_height(true));
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(run_metrics(&buffer), plain);
+}
@@ -0,0 +1,63 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, ClipRect, Color, FontSystem, Metrics, Shaping, SwashCache, Weight,
+};
+
+// Tests that clipped drawing only emits the parts of rectangles inside the clip.
+#[test]
+fn clip_rect_draw() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let mut swash_cache = SwashCache::new();
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 400.0, 200.0);
+    buffer.set_text(
+        &mut font_system,
+        "Hello world\nsecond line\nthird line\nfourth line",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let color = Color::rgb(0, 0, 0);
+    let mut all = Vec::new();
+    buffer.draw(
+        &mut font_system,
+        &mut swash_cache,
+        color,
+        |x, y, w, h, _| {
+            all.push((x, y, w, h));
+        },
+    );
+
+    let clip = ClipRect::new(30, 30, 50, 20);
+    let mut clipped = Vec::new();
+    buffer.draw_clipped(
+        &mut font_system,
+        &mut swash_cache,
+        color,
+        clip,
+        |x, y, w, h, _| clipped.push((x, y, w, h)),
+    );
+
+    assert!(!clipped.is_empty());
+    for &(x, y, w, h) in clipped.iter() {
+        assert!(x >= clip.x && x + w as i32 <= clip.x + clip.w as i32);
+        assert!(y >= clip.y && y + h as i32 <= clip.y + clip.h as i32);
+    }
+
+    // The same pixels as cutting every unclipped rectangle
+    let mut expected: Vec<_> = all
+        .iter()
+        .filter_map(|&(x, y, w, h)| clip.intersect(x, y, w, h))
+        .collect();
+    expected.sort();
+    clipped.sort();
+    assert_eq!(clipped, expected);
+
+    assert_eq!(clip.intersect(0, 0, 10, 10), None);
+    assert_eq!(clip.intersect(70, 40, 20, 20), Some((70, 40, 10, 10)));
+}
@@ -0,0 +1,55 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that lines wrap at the width of a column and fill each column from the top before moving
+// to the next one, placed one column width and gap apart.
+#[test]
+fn columns_distribute_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(220.0, 48.0);
+    buffer.set_wrap(Wrap::Glyph);
+    buffer.set_columns(2, 20.0);
+    assert_eq!(buffer.column_width(), 100.0);
+    buffer.set_text(
+        "abcdefghijklmnopqrstuvwxyz",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 4);
+    let placed: Vec<(usize, f32, f32)> = runs
+        .iter()
+        .map(|run| (run.column, run.line_x, run.line_top))
+        .collect();
+    assert_eq!(
+        placed,
+        [
+            (0, 0.0, 0.0),
+            (0, 0.0, 24.0),
+            (1, 120.0, 0.0),
+            (1, 120.0, 24.0)
+        ]
+    );
+    for run in runs.iter() {
+        assert!(run.line_w <= 100.0);
+    }
+
+    // Hit testing finds the column at x, then the position within it
+    let glyph = &runs[2].glyphs[1];
+    let start = glyph.start;
+    let hit = buffer.hit(120.0 + glyph.x + 1.0, 10.0).unwrap();
+    assert_eq!((hit.line, hit.index), (0, start));
+
+    buffer.set_columns(1, 0.0);
+    assert!(buffer
+        .layout_runs()
+        .all(|run| run.column == 0 && run.line_x == 0.0));
+}
@@ -0,0 +1,259 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, Color, Decoration, DecorationKind, DecorationLine, DecorationStyle,
+    FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that decorated glyphs share spans per line and color, placed with the font's metrics.
+#[test]
+fn decorations_spans() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let underline = Decoration {
+        underline: Some(DecorationLine::new(DecorationStyle::Wavy).color(Color::rgb(0xFF, 0, 0))),
+        ..Decoration::default()
+    };
+    let strikethrough = Decoration {
+        strikethrough: Some(DecorationLine::new(DecorationStyle::Solid)),
+        ..Decoration::default()
+    };
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("ab", attrs.decoration(underline)),
+            ("cd", attrs.decoration(underline)),
+            (" ", attrs),
+            ("ef", attrs.decoration(strikethrough)),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let glyphs = run.glyphs.to_vec();
+    let spans = run.decorations(&mut font_system);
+    assert_eq!(spans.len(), 2);
+
+    let underline = &spans[0];
+    assert_eq!(underline.kind, DecorationKind::Underline);
+    assert_eq!(underline.style, DecorationStyle::Wavy);
+    assert_eq!(underline.color_opt, Some(Color::rgb(0xFF, 0, 0)));
+    assert_eq!(underline.x, glyphs[0].x);
+    assert_eq!(underline.w, glyphs[3].x + glyphs[3].w - glyphs[0].x);
+    assert!(underline.y > 0.0);
+    assert!(underline.thickness > 0.0);
+
+    let strikethrough = &spans[1];
+    assert_eq!(strikethrough.kind, DecorationKind::Strikethrough);
+    assert_eq!(strikethrough.color_opt, None);
+    assert_eq!(strikethrough.x, glyphs[5].x);
+    assert!(strikethrough.y < 0.0);
+}
+
+// Tests that underlines across fonts are placed at the lowest position and with the largest
+// thickness of the fonts, from the metrics of the run.
+#[test]
+fn decorations_underline_across_fonts() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    for path in ["fonts/FiraMono-Medium.ttf", "fonts/NotoSans-Regular.ttf"] {
+        let font = std::fs::read(path).unwrap();
+        font_system.db_mut().load_font_data(font);
+    }
+    let mono = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let sans = Attrs::new().family(fontdb::Family::Name("Noto Sans"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("ab", mono.underline(true)),
+            ("cd", sans.underline(true)),
+            ("ef", mono),
+        ],
+        mono,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let expected = run.glyphs[..4]
+        .iter()
+        .map(|glyph| {
+            let (position, thickness) = font_system
+                .get_font(glyph.font_id)
+                .unwrap()
+                .decoration_em(DecorationKind::Underline);
+            (-position * glyph.font_size, thickness * glyph.font_size)
+        })
+        .fold((f32::MIN, 0.0f32), |(y, t), (gy, gt)| {
+            (y.max(gy), t.max(gt))
+        });
+    assert_eq!(run.underline_metrics(&mut font_system), Some(expected));
+
+    let spans = run.decorations(&mut font_system);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].kind, DecorationKind::Underline);
+    assert_eq!(spans[0].style, DecorationStyle::Solid);
+    assert_eq!((spans[0].y, spans[0].thickness), expected);
+    assert_eq!(
+        spans[0].w,
+        run.glyphs[3].x + run.glyphs[3].w - run.glyphs[0].x
+    );
+
+    buffer.set_text(
+        &mut font_system,
+        "ab",
+        mono.underline(false),
+        Shaping::Advanced,
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    assert_eq!(run.underline_metrics(&mut font_system), None);
+}
+
+// Tests that the strikethrough of a run is placed with the metrics of its font, matching the
+// span drawn for it.
+#[test]
+fn decorations_strikethrough_metrics() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [("done ", attrs.strikethrough(true)), ("todo", attrs)],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let font = font_system.get_font(run.glyphs[0].font_id).unwrap();
+    let (position, thickness) = font.decoration_em(DecorationKind::Strikethrough);
+    let expected = (-position * 20.0, thickness * 20.0);
+    assert!(expected.0 < 0.0);
+    assert_eq!(run.strikethrough_metrics(&mut font_system), Some(expected));
+    assert_eq!(run.underline_metrics(&mut font_system), None);
+
+    let spans = run.decorations(&mut font_system);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].kind, DecorationKind::Strikethrough);
+    assert_eq!((spans[0].y, spans[0].thickness), expected);
+    assert_eq!(
+        spans[0].w,
+        run.glyphs[4].x + run.glyphs[4].w - run.glyphs[0].x
+    );
+}
+
+// Tests that overlines across fonts sit at the highest ascent of the fonts, from the metrics of
+// the run.
+#[test]
+fn decorations_overline_across_fonts() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    for path in ["fonts/FiraMono-Medium.ttf", "fonts/NotoSans-Regular.ttf"] {
+        let font = std::fs::read(path).unwrap();
+        font_system.db_mut().load_font_data(font);
+    }
+    let mono = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let sans = Attrs::new().family(fontdb::Family::Name("Noto Sans"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [("ab", mono.overline(true)), ("cd", sans.overline(true))],
+        mono,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let expected = run
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            let (position, thickness) = font_system
+                .get_font(glyph.font_id)
+                .unwrap()
+                .decoration_em(DecorationKind::Overline);
+            (-position * glyph.font_size, thickness * glyph.font_size)
+        })
+        .fold((f32::MAX, 0.0f32), |(y, t), (gy, gt)| {
+            (y.min(gy), t.max(gt))
+        });
+    assert!(expected.0 < 0.0);
+    assert_eq!(run.overline_metrics(&mut font_system), Some(expected));
+
+    let spans = run.decorations(&mut font_system);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].kind, DecorationKind::Overline);
+    assert_eq!((spans[0].y, spans[0].thickness), expected);
+}
+
+// Tests that the style and color of each decoration line are reported on its span, with lines of
+// different styles in separate spans.
+#[test]
+fn decorations_styles_and_colors() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let blue = Color::rgb(0, 0, 0xFF);
+    let underline = |style: DecorationStyle| Decoration {
+        underline: Some(DecorationLine::new(style).color(blue)),
+        ..Decoration::default()
+    };
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("a", attrs.decoration(underline(DecorationStyle::Double))),
+            ("b", attrs.decoration(underline(DecorationStyle::Dotted))),
+            ("c", attrs.decoration(underline(DecorationStyle::Dashed))),
+            ("d", attrs.decoration(underline(DecorationStyle::Wavy))),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let spans = run.decorations(&mut font_system);
+    let styles: Vec<DecorationStyle> = spans.iter().map(|span| span.style).collect();
+    assert_eq!(
+        styles,
+        [
+            DecorationStyle::Double,
+            DecorationStyle::Dotted,
+            DecorationStyle::Dashed,
+            DecorationStyle::Wavy,
+        ]
+    );
+    for (span, glyph) in spans.iter().zip(run.glyphs.iter()) {
+        assert_eq!(span.color_opt, Some(blue));
+        assert_eq!((span.x, span.w), (glyph.x, glyph.w));
+        assert_eq!(span.y, spans[0].y);
+        assert_eq!(glyph.decoration(), underline(span.style));
+    }
+}
@@ -0,0 +1,50 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, Metrics, ParagraphAttrs, Shaping, Weight, Wrap,
+};
+
+// Tests that a drop cap is laid out at the start of the first line, reaching down to the
+// baseline of the last line it spans, and that those lines are inset by its width.
+#[test]
+fn drop_cap_spans_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(200.0, 1000.0);
+    buffer.set_wrap(Wrap::Glyph);
+    buffer.set_text(
+        "Once upon a time there was a cat that lived in a house by the sea",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let plain: Vec<usize> = buffer.layout_runs().map(|run| run.glyphs.len()).collect();
+    assert!(buffer.layout_runs().all(|run| run.drop_cap.is_empty()));
+
+    buffer.set_paragraph_attrs(ParagraphAttrs::new().drop_cap(3));
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > 3 && runs.len() >= plain.len());
+    let initial = &runs[0].drop_cap;
+    assert_eq!(initial.len(), 1);
+    assert_eq!((initial[0].start, initial[0].end), (0, 1));
+    assert!(initial[0].font_size > 2.0 * 20.0);
+    assert_eq!(initial[0].x, 0.0);
+    assert_eq!(initial[0].y, 2.0 * 24.0);
+    assert_eq!(runs[0].glyphs[0].start, 1);
+    for run in runs[..3].iter() {
+        assert!((run.glyphs[0].x - initial[0].w).abs() < 0.01);
+    }
+    for run in runs[1..].iter() {
+        assert!(run.drop_cap.is_empty());
+    }
+    assert_eq!(runs[3].glyphs[0].x, 0.0);
+
+    buffer.set_paragraph_attrs(ParagraphAttrs::new());
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs[0].drop_cap.is_empty());
+    assert_eq!(runs[0].glyphs[0].start, 0);
+}
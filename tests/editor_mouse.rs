@@ -0,0 +1,70 @@
+use cosmic_text::{
+    editor_buffer, editor_click, editor_double_click, editor_drag, editor_free, editor_get_cursor,
+    editor_get_selection_bounds, editor_new, fontdb, Attrs, FfiCursor, FfiModifiers, FontSystem,
+    Metrics, Shaping, Weight,
+};
+
+const NONE: FfiModifiers = FfiModifiers {
+    shift: false,
+    ctrl: false,
+    alt: false,
+    logo: false,
+};
+const SHIFT: FfiModifiers = FfiModifiers {
+    shift: true,
+    ..NONE
+};
+
+fn selection(editor: *const cosmic_text::Editor<'static>) -> Option<(usize, usize)> {
+    let mut start = FfiCursor::from(cosmic_text::Cursor::default());
+    let mut end = start;
+    editor_get_selection_bounds(editor, &mut start, &mut end).then(|| (start.index, end.index))
+}
+
+// Tests that mouse actions move the cursor and select like a native text box.
+#[test]
+fn editor_mouse_actions() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let editor = editor_new(&mut font_system, Metrics::new(20.0, 24.0));
+    let buffer = unsafe { &mut *editor_buffer(editor) };
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "hello world",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let glyph_x = |i: usize| {
+        let run = buffer.layout_runs().next().unwrap();
+        (run.glyphs[i].x + 1.0) as i32
+    };
+    let (x_1, x_4, x_8) = (glyph_x(1), glyph_x(4), glyph_x(8));
+
+    editor_click(editor, &mut font_system, x_1, 10, NONE);
+    assert_eq!(editor_get_cursor(editor).index, 1);
+    assert_eq!(selection(editor), None);
+
+    // Shift extends the selection from the cursor, and again from the same anchor
+    editor_click(editor, &mut font_system, x_4, 10, SHIFT);
+    assert_eq!(selection(editor), Some((1, 4)));
+    editor_click(editor, &mut font_system, x_8, 10, SHIFT);
+    assert_eq!(selection(editor), Some((1, 8)));
+
+    // A click without shift clears the selection, and a drag starts a new one
+    editor_click(editor, &mut font_system, x_4, 10, NONE);
+    assert_eq!(selection(editor), None);
+    editor_drag(editor, &mut font_system, x_8, 10, NONE);
+    assert_eq!(selection(editor), Some((4, 8)));
+
+    // Double click selects the word under the cursor
+    editor_double_click(editor, &mut font_system, x_8, 10, NONE);
+    assert_eq!(selection(editor), Some((6, 11)));
+
+    editor_free(editor);
+}
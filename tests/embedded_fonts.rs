@@ -0,0 +1,31 @@
+#![cfg(feature = "embedded-fonts")]
+
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+// Tests that the bundled fonts provide the generic families without any system fonts.
+#[test]
+fn embedded_fonts_generic_families() {
+    let mut db = fontdb::Database::new();
+    db.set_sans_serif_family("Missing Sans");
+    FontSystem::load_embedded_fonts(&mut db);
+    assert!(db.len() >= 2);
+    for family in [
+        fontdb::Family::SansSerif,
+        fontdb::Family::Serif,
+        fontdb::Family::Monospace,
+    ] {
+        let query = fontdb::Query {
+            families: &[family],
+            ..Default::default()
+        };
+        assert!(db.query(&query).is_some(), "{:?}", family);
+    }
+
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), db);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text("Hello", Attrs::new(), Shaping::Advanced);
+    let run = buffer.layout_runs().next().unwrap();
+    assert!(run.glyphs.iter().all(|glyph| glyph.glyph_id != 0));
+}
@@ -0,0 +1,61 @@
+use cosmic_text::{fontdb, Attrs, Buffer, Exclusion, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that lines next to exclusions are narrowed to the space beside them, on either side,
+// and that lines below them get the whole width back.
+#[test]
+fn exclusion_text_flows_around() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(300.0, 1000.0);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_text(
+        "The quick brown fox jumps over the lazy dog.\nPack my box with five dozen liquor jugs, \
+         then pack another.",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let lines_before = buffer.layout_runs().count();
+
+    let start = |run: &cosmic_text::LayoutRun| run.glyphs.first().map_or(0.0, |glyph| glyph.x);
+    let end = |run: &cosmic_text::LayoutRun| {
+        run.glyphs
+            .iter()
+            .filter(|glyph| glyph.start < glyph.end && run.text[glyph.start..glyph.end] != *" ")
+            .map(|glyph| glyph.x + glyph.w)
+            .fold(0.0, f32::max)
+    };
+
+    // An exclusion on the left of the first two lines, and one on the right of the fourth
+    buffer.set_exclusions(vec![
+        Exclusion::new(0.0, 0.0, 100.0, 40.0),
+        Exclusion::new(200.0, 80.0, 150.0, 10.0),
+    ]);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > lines_before);
+    for (i, run) in runs.iter().enumerate() {
+        let top = i as f32 * 24.0;
+        if top < 40.0 {
+            assert!(start(run) >= 100.0);
+        } else {
+            assert_eq!(start(run), 0.0);
+        }
+        if top < 90.0 && top + 24.0 > 80.0 {
+            assert!(end(run) <= 200.0);
+        } else {
+            assert!(end(run) <= 300.0);
+        }
+    }
+    // The second paragraph starts below the first one
+    let second = runs.iter().position(|run| run.line_i == 1).unwrap();
+    assert!(second >= 2);
+
+    buffer.set_exclusions(Vec::new());
+    assert_eq!(buffer.layout_runs().count(), lines_before);
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FallbackEvent, FamilyOwned, FontSystem, Metrics, Shaping, Weight,
+};
+
+fn font_id(font_system: &FontSystem, family: &str) -> fontdb::ID {
+    font_system
+        .db()
+        .faces()
+        .find(|face| face.families.iter().any(|(name, _)| name == family))
+        .unwrap()
+        .id
+}
+
+fn shape(font_system: &mut FontSystem, text: &str) {
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(font_system, 500.0, 100.0);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+}
+
+// Tests that the fallback log records the characters that the font of their text does not have,
+// with the font chosen by fallback or None if no font has them.
+#[test]
+fn fallback_log() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    for path in ["fonts/FiraMono-Medium.ttf", "fonts/NotoSans-Regular.ttf"] {
+        let font = std::fs::read(path).unwrap();
+        font_system.db_mut().load_font_data(font);
+    }
+    let fira = font_id(&font_system, "FiraMono");
+    let noto = font_id(&font_system, "Noto Sans");
+
+    // Make `b` missing from Fira Mono, so that it falls back to Noto Sans
+    font_system.set_shape_hook(Some(Arc::new(move |run, infos, _| {
+        if run.font.id() != fira {
+            return;
+        }
+        for info in infos.iter_mut() {
+            if run.text()[info.cluster as usize..].starts_with('b') {
+                info.glyph_id = 0;
+            }
+        }
+    })));
+
+    // Nothing is recorded until the log is enabled
+    shape(&mut font_system, "ab\u{10FFFD}");
+    assert!(!font_system.fallback_log_enabled());
+    assert!(font_system.fallback_log().is_empty());
+
+    font_system.set_fallback_log(true);
+    shape(&mut font_system, "ab\u{10FFFD}");
+    let family = FamilyOwned::Name("FiraMono".into());
+    assert_eq!(
+        font_system.fallback_log(),
+        [
+            FallbackEvent {
+                codepoint: 'b',
+                family: family.clone(),
+                font_id: fira,
+                fallback_id_opt: Some(noto),
+            },
+            FallbackEvent {
+                codepoint: '\u{10FFFD}',
+                family,
+                font_id: fira,
+                fallback_id_opt: None,
+            },
+        ]
+    );
+
+    // Taking the log leaves it empty, and text the font has is not recorded
+    assert_eq!(font_system.take_fallback_log().len(), 2);
+    shape(&mut font_system, "ac");
+    assert!(font_system.fallback_log().is_empty());
+
+    font_system.set_fallback_log(false);
+    shape(&mut font_system, "b");
+    assert!(font_system.take_fallback_log().is_empty());
+}
@@ -0,0 +1,64 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FallbackRule, FallbackTarget, FontSystem, Metrics, Script, Shaping,
+};
+
+fn font_id(font_system: &FontSystem, family: &str) -> fontdb::ID {
+    font_system
+        .db()
+        .faces()
+        .find(|face| face.families.iter().any(|(name, _)| name == family))
+        .unwrap()
+        .id
+}
+
+fn shaped_font_id(font_system: &mut FontSystem, text: &str) -> fontdb::ID {
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(font_system, 300.0, 100.0);
+    let attrs = Attrs::new().family(fontdb::Family::Name("Missing Family"));
+    buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+    let run = buffer.layout_runs().next().unwrap();
+    run.glyphs[0].font_id
+}
+
+// Tests that fallback rules choose the family of the characters they apply to before the
+// fallback families of the platform, and leave other characters alone.
+#[test]
+fn fallback_rules() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    for path in ["fonts/FiraMono-Medium.ttf", "fonts/NotoSans-Regular.ttf"] {
+        let font = std::fs::read(path).unwrap();
+        font_system.db_mut().load_font_data(font);
+    }
+    let fira = font_id(&font_system, "FiraMono");
+    let noto = font_id(&font_system, "Noto Sans");
+
+    // Noto Sans is a common fallback family
+    assert_eq!(shaped_font_id(&mut font_system, "text"), noto);
+
+    font_system.add_fallback_rule(FallbackRule::new(
+        FallbackTarget::Range('\u{4E00}'..='\u{9FFF}'),
+        "Noto Sans",
+    ));
+    font_system.add_fallback_rule(FallbackRule::new(
+        FallbackTarget::Range('a'..='z'),
+        "FiraMono",
+    ));
+    assert_eq!(shaped_font_id(&mut font_system, "text"), fira);
+    assert_eq!(shaped_font_id(&mut font_system, "1234"), noto);
+
+    font_system.clear_fallback_rules();
+    assert!(font_system.fallback_rules().is_empty());
+    assert_eq!(shaped_font_id(&mut font_system, "text"), noto);
+
+    // Rules for families that are not in the database are skipped
+    font_system.add_fallback_rule(FallbackRule::new(
+        FallbackTarget::Script(Script::Latin),
+        "Missing Family",
+    ));
+    font_system.add_fallback_rule(FallbackRule::new(
+        FallbackTarget::Script(Script::Latin),
+        "FiraMono",
+    ));
+    assert_eq!(shaped_font_id(&mut font_system, "text"), fira);
+}
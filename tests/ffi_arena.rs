@@ -0,0 +1,62 @@
+use cosmic_text::{
+    buffer_layout_runs_arena, fontdb, Attrs, Buffer, FfiArena, FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that arrays stay intact until a reset, and that memory is reused after it.
+#[test]
+fn ffi_arena_reuse() {
+    let mut arena = FfiArena::default();
+    let bytes = arena.alloc_slice(&[1u8, 2, 3]);
+    let words = arena.alloc_slice(&[4u64, 5]);
+    assert_eq!(words as usize % std::mem::align_of::<u64>(), 0);
+    let big = arena.alloc_slice(&vec![6u32; 100_000]);
+    unsafe {
+        assert_eq!(std::slice::from_raw_parts(bytes, 3), &[1, 2, 3]);
+        assert_eq!(std::slice::from_raw_parts(words, 2), &[4, 5]);
+        assert!(std::slice::from_raw_parts(big, 100_000)
+            .iter()
+            .all(|x| *x == 6));
+    }
+
+    let capacity = arena.capacity();
+    arena.reset();
+    assert_eq!(arena.alloc_slice(&[7u8, 8, 9]), bytes);
+    arena.alloc_slice(&vec![10u32; 100_000]);
+    assert_eq!(arena.capacity(), capacity);
+}
+
+// Tests that layout runs copied into an arena match the runs of the buffer.
+#[test]
+fn ffi_arena_layout_runs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "Hello\nworld",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let mut arena = FfiArena::default();
+    let mut len = 0;
+    let runs = buffer_layout_runs_arena(&buffer, &mut arena, &mut len);
+    let runs = unsafe { std::slice::from_raw_parts(runs, len) };
+    assert_eq!(runs.len(), 2);
+    for (ffi_run, run) in runs.iter().zip(buffer.layout_runs()) {
+        assert_eq!(ffi_run.line_i, run.line_i);
+        assert_eq!(ffi_run.line_y, run.line_y);
+        let glyphs = unsafe { std::slice::from_raw_parts(ffi_run.glyphs, ffi_run.glyphs_len) };
+        assert_eq!(glyphs.len(), run.glyphs.len());
+        for (ffi_glyph, glyph) in glyphs.iter().zip(run.glyphs.iter()) {
+            assert_eq!(ffi_glyph.glyph_id, glyph.glyph_id);
+            assert_eq!(ffi_glyph.x, glyph.x);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that the fill id of each span is copied to its glyphs, separately from metadata, and
+// that glyphs with different fills are batched apart.
+#[test]
+fn fill_id_glyphs_and_batches() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM)
+        .metadata(7);
+    assert_eq!(attrs.fill_id, 0);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("ab", attrs),
+            ("cd", attrs.fill_id(u64::MAX)),
+            ("ef", attrs.fill_id(42)),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let fill_ids: Vec<u64> = run.glyphs.iter().map(|glyph| glyph.fill_id).collect();
+    assert_eq!(fill_ids, [0, 0, u64::MAX, u64::MAX, 42, 42]);
+    assert!(run.glyphs.iter().all(|glyph| glyph.metadata == 7));
+
+    let batches = buffer.glyph_batches();
+    let batch_fills: Vec<(u64, usize)> = batches
+        .iter()
+        .map(|batch| (batch.fill_id, batch.glyphs.len()))
+        .collect();
+    assert_eq!(batch_fills, [(0, 2), (u64::MAX, 2), (42, 2)]);
+}
@@ -0,0 +1,77 @@
+use cosmic_text::{fontdb, Attrs, AttrsList, Buffer, FontFeature, FontSystem, Metrics, Shaping};
+
+// Tests that feature settings are parsed from tags with on, off, numeric and signed values.
+#[test]
+fn font_features_parse() {
+    assert_eq!(
+        FontFeature::parse("liga off"),
+        Some(FontFeature::disable(*b"liga"))
+    );
+    assert_eq!(
+        FontFeature::parse("tnum on"),
+        Some(FontFeature::enable(*b"tnum"))
+    );
+    assert_eq!(
+        FontFeature::parse("smcp"),
+        Some(FontFeature::enable(*b"smcp"))
+    );
+    assert_eq!(
+        FontFeature::parse(" cv01 3 "),
+        Some(FontFeature::new(*b"cv01", 3))
+    );
+    assert_eq!(
+        FontFeature::parse("ss01=2"),
+        Some(FontFeature::new(*b"ss01", 2))
+    );
+    assert_eq!(
+        FontFeature::parse("+kern"),
+        Some(FontFeature::enable(*b"kern"))
+    );
+    assert_eq!(
+        FontFeature::parse("-liga"),
+        Some(FontFeature::disable(*b"liga"))
+    );
+    assert_eq!(FontFeature::parse("lig off"), None);
+    assert_eq!(FontFeature::parse("liga maybe"), None);
+    assert_eq!(FontFeature::parse("-liga 1"), None);
+    assert_eq!(FontFeature::parse(""), None);
+}
+
+// Tests that features of a span are used when shaping it, so that disabling ligatures keeps
+// the letters of a ligature apart.
+#[test]
+fn font_features_liga() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSans-Regular.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new().family(fontdb::Family::Name("Noto Sans"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 500.0, 200.0);
+    buffer.set_text(&mut font_system, "office office", attrs, Shaping::Advanced);
+    let glyphs = |buffer: &Buffer| -> Vec<(usize, usize)> {
+        let run = buffer.layout_runs().next().unwrap();
+        run.glyphs
+            .iter()
+            .map(|glyph| (glyph.start, glyph.end))
+            .collect()
+    };
+    let ligated = glyphs(&buffer);
+    assert!(ligated.iter().any(|&(start, end)| end - start > 1));
+
+    let no_liga = [FontFeature::disable(*b"liga")];
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(7..13, attrs.font_features(&no_liga));
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let glyphs = glyphs(&buffer);
+    assert!(glyphs
+        .iter()
+        .any(|&(start, end)| start < 7 && end - start > 1));
+    assert!(glyphs
+        .iter()
+        .filter(|&&(start, _)| start >= 7)
+        .all(|&(start, end)| end - start == 1));
+    assert_eq!(glyphs.iter().filter(|&&(start, _)| start >= 7).count(), 6);
+}
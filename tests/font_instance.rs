@@ -0,0 +1,126 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontInstance, FontSystem, Metrics, NamedInstance, Shaping, Weight,
+};
+
+/// Add an `fvar` table with a `wght` axis and instances named by the given name ids and weights
+fn with_fvar(font: &[u8], instances: &[(u16, f32)]) -> Vec<u8> {
+    let mut fvar = Vec::new();
+    let instance_size = 4 + 4 + 2;
+    for value in [1u16, 0, 16, 2, 1, 20, instances.len() as u16, instance_size] {
+        fvar.extend_from_slice(&value.to_be_bytes());
+    }
+    fvar.extend_from_slice(b"wght");
+    for value in [100.0f32, 400.0, 900.0] {
+        fvar.extend_from_slice(&((value * 65536.0) as i32).to_be_bytes());
+    }
+    fvar.extend_from_slice(&[0, 0, 1, 0]);
+    for &(name_id, weight) in instances {
+        fvar.extend_from_slice(&name_id.to_be_bytes());
+        fvar.extend_from_slice(&[0, 0]);
+        fvar.extend_from_slice(&((weight * 65536.0) as i32).to_be_bytes());
+        fvar.extend_from_slice(&0xFFFFu16.to_be_bytes());
+    }
+
+    // Move the tables after the longer directory and append the new table, keeping the
+    // records sorted by tag
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let mut records: Vec<[u8; 16]> = (0..num_tables)
+        .map(|i| font[12 + 16 * i..28 + 16 * i].try_into().unwrap())
+        .collect();
+    for record in records.iter_mut() {
+        let offset = u32::from_be_bytes(record[8..12].try_into().unwrap()) + 16;
+        record[8..12].copy_from_slice(&offset.to_be_bytes());
+    }
+    let mut data = font[12 + 16 * num_tables..].to_vec();
+    while data.len() % 4 != 0 {
+        data.push(0);
+    }
+    let mut record = [0; 16];
+    record[..4].copy_from_slice(b"fvar");
+    let offset = (12 + 16 * (num_tables + 1) + data.len()) as u32;
+    record[8..12].copy_from_slice(&offset.to_be_bytes());
+    record[12..].copy_from_slice(&(fvar.len() as u32).to_be_bytes());
+    records.push(record);
+    records.sort_by_key(|record| <[u8; 4]>::try_from(&record[..4]).unwrap());
+    data.extend_from_slice(&fvar);
+
+    let mut patched = font[..12].to_vec();
+    patched[4..6].copy_from_slice(&((num_tables + 1) as u16).to_be_bytes());
+    for record in records {
+        patched.extend_from_slice(&record);
+    }
+    patched.extend_from_slice(&data);
+    patched
+}
+
+// Tests that named instances are read from the `fvar` table, found by name or index, and
+// carried to the glyphs and cache keys of text that selects them.
+#[test]
+fn font_instance_named() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    // Name the instances with the PostScript name and the family name of the font
+    font_system
+        .db_mut()
+        .load_font_data(with_fvar(&font, &[(6, 300.0), (1, 700.0)]));
+    let face = font_system.db().faces().next().unwrap();
+    let (id, post_script_name) = (face.id, face.post_script_name.clone());
+    let family = face.families[0].0.clone();
+
+    let font = font_system.get_font(id).unwrap();
+    assert_eq!(
+        font.named_instances(),
+        &[
+            NamedInstance {
+                name: post_script_name.clone(),
+                postscript_name_opt: None,
+                coords: vec![(*b"wght", 300.0)],
+            },
+            NamedInstance {
+                name: family.clone(),
+                postscript_name_opt: None,
+                coords: vec![(*b"wght", 700.0)],
+            },
+        ]
+    );
+    let lowercase = post_script_name.to_lowercase();
+    assert_eq!(font.find_instance(FontInstance::Name(&lowercase)), Some(0));
+    assert_eq!(font.find_instance(FontInstance::Name(&family)), Some(1));
+    assert_eq!(font.find_instance(FontInstance::Name("Missing")), None);
+    assert_eq!(font.find_instance(FontInstance::Index(1)), Some(1));
+    assert_eq!(font.find_instance(FontInstance::Index(2)), None);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name(&family))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 300.0, 200.0);
+    for (font_instance, expected) in [
+        (FontInstance::Name(&family), Some(1)),
+        (FontInstance::Index(0), Some(0)),
+        (FontInstance::Index(2), None),
+    ] {
+        let attrs = attrs.font_instance(font_instance);
+        buffer.set_text(&mut font_system, "Hello", attrs, Shaping::Advanced);
+        let run = buffer.layout_runs().next().unwrap();
+        assert!(run
+            .glyphs
+            .iter()
+            .all(|glyph| glyph.font_instance_opt() == expected
+                && glyph.physical((0.0, 0.0), 1.0).cache_key.font_instance_opt == expected));
+    }
+}
+
+// Tests that fonts without an `fvar` table have no named instances.
+#[test]
+fn font_instance_static() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let id = font_system.db().faces().next().unwrap().id;
+    let font = font_system.get_font(id).unwrap();
+    assert!(font.named_instances().is_empty());
+    assert_eq!(font.find_instance(FontInstance::Index(0)), None);
+}
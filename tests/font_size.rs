@@ -0,0 +1,50 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that spans with their own font size keep it whatever the font size of the buffer, make
+// their line taller, and are hit by the cursor over their glyphs.
+#[test]
+fn font_size_spans() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("ab", attrs),
+            ("CD", attrs.font_size(40.0)),
+            ("\nef", attrs),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    let sizes: Vec<f32> = runs[0].glyphs.iter().map(|glyph| glyph.font_size).collect();
+    assert_eq!(sizes, [20.0, 20.0, 40.0, 40.0]);
+    let glyphs = runs[0].glyphs;
+    assert!((glyphs[2].w - 2.0 * glyphs[0].w).abs() < 0.001);
+    assert!((glyphs[2].x - (glyphs[1].x + glyphs[1].w)).abs() < 0.001);
+    assert!((runs[0].line_w - (glyphs[3].x + glyphs[3].w)).abs() < 0.001);
+
+    // The line with text twice as large is twice as tall
+    assert_eq!(runs[0].line_top, 15.0);
+    assert_eq!(runs[1].line_top, 60.0);
+
+    let x = glyphs[3].x + glyphs[3].w * 0.25;
+    let cursor = buffer.hit(x, runs[0].line_y).unwrap();
+    assert_eq!((cursor.line, cursor.index), (0, 3));
+
+    // The size of the span does not follow the font size of the buffer
+    buffer.set_metrics(&mut font_system, Metrics::new(10.0, 15.0));
+    let run = buffer.layout_runs().next().unwrap();
+    let sizes: Vec<f32> = run.glyphs.iter().map(|glyph| glyph.font_size).collect();
+    assert_eq!(sizes, [10.0, 10.0, 40.0, 40.0]);
+    assert!((run.glyphs[2].w - 4.0 * run.glyphs[0].w).abs() < 0.001);
+}
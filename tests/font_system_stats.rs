@@ -0,0 +1,41 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that stats reflect parsed fonts and caches, and that trimming releases them.
+#[test]
+fn font_system_stats() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    let font_len = font.len();
+    font_system.db_mut().load_font_data(font);
+
+    let stats = font_system.stats();
+    assert_eq!(stats.faces, 1);
+    assert_eq!(stats.parsed_fonts, 0);
+    assert_eq!(stats.font_data_bytes, 0);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "Hello",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let stats = font_system.stats();
+    assert_eq!(stats.parsed_fonts, 1);
+    assert_eq!(stats.font_data_bytes, font_len);
+    assert!(stats.font_matches > 0);
+    assert!(stats.shape_plan_cache.entries > 0);
+
+    font_system.trim_caches();
+    let stats = font_system.stats();
+    assert_eq!(stats.faces, 1);
+    assert_eq!(stats.parsed_fonts, 0);
+    assert_eq!(stats.font_data_bytes, 0);
+    assert_eq!(stats.font_matches, 0);
+    assert_eq!(stats.shape_plan_cache.entries, 0);
+}
@@ -0,0 +1,76 @@
+use cosmic_text::{fontdb, Attrs, Buffer, Color, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that glyphs are grouped by color, keeping reading order inside each batch.
+#[test]
+fn glyph_batches() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let red = attrs.color(Color::rgb(0xFF, 0x00, 0x00));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(500.0, 100.0);
+    buffer.set_rich_text(
+        [("ab", attrs), ("cd", red), ("ef", attrs), ("\ngh", red)],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let batches = buffer.glyph_batches();
+    assert_eq!(batches.len(), 2);
+
+    assert_eq!(batches[0].color_opt, None);
+    let starts: Vec<_> = batches[0].glyphs.iter().map(|g| g.glyph.start).collect();
+    assert_eq!(starts, [0, 1, 4, 5]);
+
+    assert_eq!(batches[1].color_opt, Some(Color::rgb(0xFF, 0x00, 0x00)));
+    let positions: Vec<_> = batches[1]
+        .glyphs
+        .iter()
+        .map(|g| (g.line_i, g.glyph.start))
+        .collect();
+    assert_eq!(positions, [(0, 2), (0, 3), (1, 0), (1, 1)]);
+    assert!(batches[1].glyphs[2].line_y > batches[1].glyphs[0].line_y);
+    let logical: Vec<_> = batches[1].glyphs.iter().map(|g| g.logical_i).collect();
+    assert_eq!(logical, [2, 3, 6, 7]);
+}
+
+// Tests that sorting by cache key keeps a mapping back to reading order.
+#[test]
+fn glyph_batches_sorted() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(500.0, 100.0);
+    buffer.set_text("baba", attrs, Shaping::Advanced);
+
+    let mut batches = buffer.glyph_batches();
+    assert_eq!(batches.len(), 1);
+    let batch = &mut batches[0];
+    let logical = batch.glyphs.clone();
+    batch.sort_by_cache_key();
+
+    let keys: Vec<_> = batch
+        .glyphs
+        .iter()
+        .map(|g| g.glyph.physical((0., 0.), 1.0).cache_key)
+        .collect();
+    assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+    for glyph in batch.glyphs.iter() {
+        assert_eq!(glyph.glyph.start, logical[glyph.logical_i].glyph.start);
+    }
+}
@@ -0,0 +1,44 @@
+use cosmic_text::{
+    fontdb, Align, Attrs, Buffer, FontSystem, Metrics, ParagraphAttrs, Shaping, Weight,
+};
+
+// Tests that a stop at the end of a line may hang past the width of the buffer, and that it is
+// not counted when aligning the line.
+#[test]
+fn hanging_punctuation_past_width() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 1000.0);
+    buffer.set_text("a", attrs, Shaping::Advanced);
+    let advance = buffer.layout_runs().next().unwrap().glyphs[0].w;
+
+    let width = 9.5 * advance;
+    buffer.set_size(width, 1000.0);
+    buffer.set_text("aaaa aaaa. bbb", attrs, Shaping::Advanced);
+    let lines: Vec<&str> = buffer
+        .layout_runs()
+        .map(|run| &run.text[run.glyphs[0].start..run.glyphs.last().unwrap().end])
+        .collect();
+    assert_eq!(lines, ["aaaa", "aaaa. bbb"]);
+
+    buffer.set_hanging_punctuation(true);
+    let lines: Vec<&str> = buffer
+        .layout_runs()
+        .map(|run| &run.text[run.glyphs[0].start..run.glyphs.last().unwrap().end])
+        .collect();
+    assert_eq!(lines, ["aaaa aaaa.", "bbb"]);
+
+    buffer.set_paragraph_attrs(ParagraphAttrs::new().align(Align::Right));
+    let run = buffer.layout_runs().next().unwrap();
+    let stop = run.glyphs.last().unwrap();
+    assert!((stop.x - width).abs() < 0.01);
+    assert!(stop.x + stop.w > width);
+}
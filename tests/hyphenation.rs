@@ -0,0 +1,57 @@
+#![cfg(feature = "hyphenation")]
+
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Hyphenator, Metrics, Shaping, Weight};
+
+// Tests that words wrap at hyphenation points of the buffer's language, with a hyphen at the
+// end of the line.
+#[test]
+fn hyphenation_wraps_words() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    font_system.add_hyphenator(Hyphenator::parse(
+        "en",
+        "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n",
+    ));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 140.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "a hyphenation",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let run_texts = |buffer: &Buffer| -> Vec<String> {
+        buffer
+            .layout_runs()
+            .map(|run| {
+                run.glyphs
+                    .iter()
+                    .map(|glyph| &run.text[glyph.start..glyph.end])
+                    .collect()
+            })
+            .collect()
+    };
+    assert_eq!(run_texts(&buffer), ["a", "hyphenation"]);
+
+    buffer.set_hyphenation(&mut font_system, Some("en-US"));
+    assert_eq!(buffer.hyphenation(), Some("en-US"));
+    assert_eq!(run_texts(&buffer), ["a hyphen", "ation"]);
+
+    // The hyphen is an extra glyph at the break, counted in the width of the line
+    let run = buffer.layout_runs().next().unwrap();
+    let hyphen = run.glyphs.last().unwrap();
+    assert_eq!((hyphen.start, hyphen.end), (8, 8));
+    assert!(hyphen.w > 0.0);
+    let end = hyphen.x + hyphen.w;
+    assert!((run.line_w - end).abs() < 0.01);
+    assert!(run.line_w <= 140.0);
+
+    // Languages without patterns are not hyphenated
+    buffer.set_hyphenation(&mut font_system, Some("de"));
+    assert_eq!(run_texts(&buffer), ["a", "hyphenation"]);
+}
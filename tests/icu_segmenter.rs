@@ -0,0 +1,42 @@
+#![cfg(feature = "icu-segmenter")]
+
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, LineBreakDictionary, Metrics, Shaping, Weight,
+};
+
+fn words(buffer: &mut Buffer, font_system: &mut FontSystem) -> Vec<String> {
+    let text = buffer.lines[0].text().to_string();
+    let shape = buffer.line_shape(font_system, 0).unwrap();
+    shape.spans[0]
+        .words
+        .iter()
+        .filter(|word| !word.blank)
+        .map(|word| {
+            let start = word.glyphs.iter().map(|glyph| glyph.start).min().unwrap();
+            let end = word.glyphs.iter().map(|glyph| glyph.end).max().unwrap();
+            text[start..end].to_string()
+        })
+        .collect()
+}
+
+// Tests that Thai text with no spaces is split into words by the built-in dictionary, and that
+// an added dictionary for the script takes its place.
+#[test]
+fn icu_segmenter_thai() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    buffer.set_text(&mut font_system, "สวัสดีครับ", attrs, Shaping::Advanced);
+    assert_eq!(words(&mut buffer, &mut font_system), ["สวัสดี", "ครับ"]);
+
+    font_system.add_line_break_dictionary(LineBreakDictionary::from_words(["สวัสดีครับ"]));
+    buffer.set_text(&mut font_system, "สวัสดีครับ", attrs, Shaping::Advanced);
+    assert_eq!(words(&mut buffer, &mut font_system), ["สวัสดีครับ"]);
+}
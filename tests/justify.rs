@@ -0,0 +1,55 @@
+use cosmic_text::{fontdb, Align, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that justified lines fill the width by widening spaces, except the last line of each
+// paragraph.
+#[test]
+fn justify_fills_width() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let width = 230.0;
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, width, 1000.0);
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    buffer.set_text(
+        &mut font_system,
+        "The quick brown fox jumps over the lazy dog\nand runs away",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    for line in buffer.lines.iter_mut() {
+        line.set_align(Some(Align::Justified));
+    }
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    let first_line_runs = runs.iter().filter(|run| run.line_i == 0).count();
+    assert!(first_line_runs > 1);
+    for (run_i, run) in runs.iter().enumerate() {
+        let last_of_paragraph = runs
+            .get(run_i + 1)
+            .map_or(true, |next| next.line_i != run.line_i);
+        // Glyph advances are widened, so the glyphs reach the right edge
+        let end = run
+            .glyphs
+            .iter()
+            .filter(|glyph| !run.text[glyph.start..glyph.end].trim().is_empty())
+            .map(|glyph| glyph.x + glyph.w)
+            .fold(0.0, f32::max);
+        if last_of_paragraph {
+            assert!(end < width - 1.0);
+        } else {
+            assert!((end - width).abs() < 0.1, "{} != {}", end, width);
+            let space = run
+                .glyphs
+                .iter()
+                .find(|glyph| &run.text[glyph.start..glyph.end] == " ")
+                .unwrap();
+            assert!(space.w > run.glyphs[0].w);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use cosmic_text::{fontdb, Align, Attrs, AttrsList, FontSystem, ShapeLine, Shaping, Wrap};
+
+// Tests that justified Arabic lines are filled with kashida instead of wider spaces.
+#[test]
+fn kashida_justification() {
+    let font_size = 36.0;
+    let width = 300.0;
+    let attrs = AttrsList::new(Attrs::new().family(fontdb::Family::Name("Noto Sans Arabic")));
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSansArabic.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let text = "الثعلب البني السريع يقفز فوق الكلب الكسول";
+    let line = ShapeLine::new(&mut font_system, text, &attrs, Shaping::Advanced);
+    let left = line.layout(font_size, width, Wrap::Word, Some(Align::Left), None);
+    let justified = line.layout(font_size, width, Wrap::Word, Some(Align::Justified), None);
+    assert!(left.len() > 1);
+    assert_eq!(left.len(), justified.len());
+
+    // The last line is not justified
+    let last = left.len() - 1;
+    for (left, justified) in left[..last].iter().zip(justified[..last].iter()) {
+        assert!((justified.w - width).abs() < 0.1);
+        // Tatweel glyphs were inserted
+        assert!(justified.glyphs.len() > left.glyphs.len());
+        // Spaces keep their width
+        for glyph in justified.glyphs.iter() {
+            if text[glyph.start..glyph.end]
+                .chars()
+                .all(char::is_whitespace)
+            {
+                let left_glyph = left.glyphs.iter().find(|g| g.start == glyph.start);
+                assert_eq!(left_glyph.map(|g| g.w), Some(glyph.w));
+            }
+        }
+    }
+    assert_eq!(left[last].glyphs.len(), justified[last].glyphs.len());
+}
@@ -0,0 +1,94 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, Color, FontSystem, LastResort, LayoutGlyph, Metrics, Shaping,
+    SwashCache, Weight,
+};
+
+fn layout(font_system: &mut FontSystem, text: &str) -> Vec<LayoutGlyph> {
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text(
+        text,
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    run.glyphs.to_vec()
+}
+
+// Tests that the last resort face is used for characters no other font supports, and only for
+// those characters.
+#[test]
+fn last_resort_font() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let font = std::fs::read("fonts/NotoSansHebrew.ttf").unwrap();
+    let last_resort_id = font_system
+        .db_mut()
+        .load_font_source(fontdb::Source::Binary(std::sync::Arc::new(font)))[0];
+    font_system.set_last_resort(LastResort::Font(last_resort_id));
+
+    let glyphs = layout(&mut font_system, "a \u{05D0}");
+    assert_eq!(glyphs.len(), 3);
+    assert_ne!(glyphs[0].font_id, last_resort_id);
+    assert_eq!(glyphs[2].font_id, last_resort_id);
+    assert!(glyphs.iter().all(|glyph| glyph.glyph_id != 0));
+}
+
+// Tests that missing characters are drawn as boxes instead of the .notdef glyph.
+#[test]
+fn last_resort_hex_box() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let glyphs = layout(&mut font_system, "\u{05D0}");
+    assert_eq!(glyphs.len(), 1);
+    assert_eq!(glyphs[0].glyph_id, 0);
+
+    let mut draw = |font_system: &mut FontSystem| {
+        let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+        let mut buffer = buffer.borrow_with(font_system);
+        buffer.set_size(1000.0, 100.0);
+        buffer.set_text(
+            "\u{05D0}",
+            Attrs::new()
+                .family(fontdb::Family::Name("FiraMono"))
+                .weight(Weight::MEDIUM),
+            Shaping::Advanced,
+        );
+        let mut cache = SwashCache::new();
+        let mut rects = Vec::new();
+        buffer.draw(&mut cache, Color::rgb(0, 0, 0), |x, y, w, h, _color| {
+            rects.push((x, y, w, h));
+        });
+        rects
+    };
+
+    let notdef = draw(&mut font_system);
+    font_system.set_last_resort(LastResort::HexBox);
+    let hex_box = draw(&mut font_system);
+    assert_ne!(notdef, hex_box);
+
+    // The box spans the advance of the glyph, and rests on the baseline
+    let glyph = &glyphs[0];
+    let left = hex_box.iter().map(|(x, ..)| *x).min().unwrap();
+    let right = hex_box
+        .iter()
+        .map(|(x, _, w, _)| x + *w as i32)
+        .max()
+        .unwrap();
+    let bottom = hex_box
+        .iter()
+        .map(|(_, y, _, h)| y + *h as i32)
+        .max()
+        .unwrap();
+    assert_eq!(left, glyph.physical((0.0, 0.0), 1.0).x);
+    assert_eq!(right - left, glyph.w.round() as i32);
+    assert!(bottom <= 20);
+}
@@ -0,0 +1,52 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, LeadingTrim, Metrics, Shaping, VerticalAlign, Weight,
+};
+
+// Tests that trimming leading moves the cap height of the first line to the top of the buffer,
+// and that the trim amounts match the metrics of the font.
+#[test]
+fn leading_trim_cap_height() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let font_id = font_system.db().faces().next().unwrap().id;
+    let cap_height = font_system.get_font(font_id).unwrap().cap_height_em() * 20.0;
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(300.0, 200.0);
+    buffer.set_text(
+        "Hello\nWorld",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.leading_trim_amounts(), LeadingTrim::default());
+    let runs: Vec<(f32, f32)> = buffer
+        .layout_runs()
+        .map(|run| (run.line_top, run.line_y))
+        .collect();
+
+    assert!(cap_height > 0.0);
+
+    buffer.set_leading_trim(true);
+    let trim = buffer.leading_trim_amounts();
+    assert!((trim.top - (runs[0].1 - cap_height)).abs() < 0.001);
+    assert!((trim.bottom - (runs[1].0 + 30.0 - runs[1].1)).abs() < 0.001);
+    let trimmed: Vec<(f32, f32)> = buffer
+        .layout_runs()
+        .map(|run| (run.line_top, run.line_y))
+        .collect();
+    for (run, trimmed) in runs.iter().zip(trimmed.iter()) {
+        assert!((trimmed.0 - (run.0 - trim.top)).abs() < 0.001);
+        assert!((trimmed.1 - (run.1 - trim.top)).abs() < 0.001);
+    }
+    assert!((trimmed[0].1 - cap_height).abs() < 0.001);
+
+    // The baseline of the last line is at the bottom when aligned to it
+    buffer.set_vertical_align(VerticalAlign::Bottom);
+    let last = buffer.layout_runs().last().unwrap();
+    assert!((last.line_y - 200.0).abs() < 0.001);
+}
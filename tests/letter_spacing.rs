@@ -0,0 +1,57 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that letter spacing widens every character in its span only, and that wrapping accounts
+// for it.
+#[test]
+fn letter_spacing_widens_characters() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("AB ", attrs.letter_spacing(0.25)),
+            ("CD", attrs),
+            ("EF", attrs.letter_spacing(0.5).font_size(40.0)),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let widths: Vec<f32> = run.glyphs.iter().map(|glyph| glyph.w).collect();
+    let em = widths[3];
+    // Spaced characters are a quarter em wider, in the font size of their span
+    for width in &widths[0..3] {
+        assert!((width - em - 5.0).abs() < 0.01);
+    }
+    assert_eq!(widths[4], em);
+    for width in &widths[5..7] {
+        assert!((width - 2.0 * em - 20.0).abs() < 0.01);
+    }
+    assert!((run.line_w - (9.0 * em + 55.0)).abs() < 0.01);
+
+    // A line that fits without letter spacing wraps with it
+    let width = 7.0 * em + 1.0;
+    buffer.set_size(&mut font_system, width, 1000.0);
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    buffer.set_text(&mut font_system, "ABC DEF", attrs, Shaping::Advanced);
+    assert_eq!(buffer.layout_runs().count(), 1);
+    buffer.set_text(
+        &mut font_system,
+        "ABC DEF",
+        attrs.letter_spacing(0.25),
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.layout_runs().count(), 2);
+    for run in buffer.layout_runs() {
+        assert!(run.line_w <= width);
+    }
+}
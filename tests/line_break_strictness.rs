@@ -0,0 +1,57 @@
+use cosmic_text::{
+    fontdb, Attrs, BorrowedWithFontSystem, Buffer, FontSystem, LineBreakStrictness, Metrics,
+    Shaping, Weight,
+};
+
+// Tests that looser line breaking allows breaks before small kana, the prolonged sound mark, and
+// iteration marks, but never before closing punctuation or after opening punctuation.
+#[test]
+fn line_break_strictness_kana() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 1000.0);
+    buffer.set_text(
+        "きゃっと「ねこ」。人々ー",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let words = |buffer: &mut BorrowedWithFontSystem<Buffer>| {
+        let text = buffer.lines[0].text().to_string();
+        let shape = buffer.line_shape(0).unwrap();
+        shape.spans[0]
+            .words
+            .iter()
+            .map(|word| {
+                let start = word.glyphs.iter().map(|glyph| glyph.start).min().unwrap();
+                let end = word.glyphs.iter().map(|glyph| glyph.end).max().unwrap();
+                text[start..end].to_string()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        words(&mut buffer),
+        ["きゃっ", "と", "「ね", "こ」。", "人々ー"]
+    );
+
+    buffer.set_line_break_strictness(LineBreakStrictness::Normal);
+    assert_eq!(buffer.line_break_strictness(), LineBreakStrictness::Normal);
+    assert_eq!(
+        words(&mut buffer),
+        ["き", "ゃ", "っ", "と", "「ね", "こ」。", "人々", "ー"]
+    );
+
+    buffer.set_line_break_strictness(LineBreakStrictness::Loose);
+    assert_eq!(
+        words(&mut buffer),
+        ["き", "ゃ", "っ", "と", "「ね", "こ」。", "人", "々", "ー"]
+    );
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, LineBreakStrictness, LineBreaker, Metrics, Shaping, Weight,
+};
+
+/// Allows breaks after every character, like a terminal
+struct CharBreaker;
+
+impl LineBreaker for CharBreaker {
+    fn line_breaks(&self, text: &str, _strictness: LineBreakStrictness) -> Vec<usize> {
+        text.char_indices().map(|(i, c)| i + c.len_utf8()).collect()
+    }
+}
+
+/// Allows no breaks, leaving the end of the text implied
+struct NoBreaker;
+
+impl LineBreaker for NoBreaker {
+    fn line_breaks(&self, _text: &str, _strictness: LineBreakStrictness) -> Vec<usize> {
+        Vec::new()
+    }
+}
+
+fn words(buffer: &mut Buffer, font_system: &mut FontSystem) -> Vec<String> {
+    let text = buffer.lines[0].text().to_string();
+    let shape = buffer.line_shape(font_system, 0).unwrap();
+    shape.spans[0]
+        .words
+        .iter()
+        .map(|word| {
+            let start = word.glyphs.iter().map(|glyph| glyph.start).min().unwrap();
+            let end = word.glyphs.iter().map(|glyph| glyph.end).max().unwrap();
+            text[start..end].to_string()
+        })
+        .collect()
+}
+
+// Tests that a custom line breaker decides where words are split, and that clearing it returns to
+// Unicode line breaking.
+#[test]
+fn line_breaker_custom() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    buffer.set_text(&mut font_system, "ab cd", attrs, Shaping::Advanced);
+    assert_eq!(words(&mut buffer, &mut font_system), ["ab", " ", "cd"]);
+
+    font_system.set_line_breaker(Some(Arc::new(CharBreaker)));
+    buffer.set_text(&mut font_system, "ab cd", attrs, Shaping::Advanced);
+    assert_eq!(
+        words(&mut buffer, &mut font_system),
+        ["a", "b", " ", "c", "d"]
+    );
+
+    font_system.set_line_breaker(Some(Arc::new(NoBreaker)));
+    buffer.set_text(&mut font_system, "ab cd", attrs, Shaping::Advanced);
+    assert_eq!(words(&mut buffer, &mut font_system), ["ab cd"]);
+
+    font_system.set_line_breaker(None);
+    buffer.set_text(&mut font_system, "ab cd", attrs, Shaping::Advanced);
+    assert_eq!(words(&mut buffer, &mut font_system), ["ab", " ", "cd"]);
+}
+
+// Tests that a line that cannot break with the line breaker overflows instead of wrapping.
+#[test]
+fn line_breaker_wrap() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    font_system.set_line_breaker(Some(Arc::new(NoBreaker)));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_wrap(&mut font_system, cosmic_text::Wrap::Word);
+    buffer.set_size(&mut font_system, 50.0, 1000.0);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    buffer.set_text(&mut font_system, "ab cd ef gh", attrs, Shaping::Advanced);
+    assert_eq!(buffer.layout_runs().count(), 1);
+}
@@ -0,0 +1,64 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, LineEnding, Metrics, Shaping, Weight};
+
+// Tests that text round-trips through a buffer with its line endings, and that a line ending
+// preference normalizes them.
+#[test]
+fn line_endings_round_trip() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 1000.0);
+
+    let text = "one\r\ntwo\nthree\rfour\r\n";
+    buffer.set_text(text, attrs, Shaping::Advanced);
+    assert_eq!(buffer.text(), text);
+
+    // Rich text keeps line endings too, including a `\r\n` split across spans
+    buffer.set_rich_text(
+        [("one\r", attrs), ("\ntwo\n", attrs), ("three", attrs)],
+        attrs,
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.lines.len(), 3);
+    assert_eq!(buffer.lines[0].text(), "one");
+    assert_eq!(buffer.text(), "one\r\ntwo\nthree");
+
+    buffer.set_line_ending(Some(LineEnding::CrLf));
+    assert_eq!(buffer.text(), "one\r\ntwo\r\nthree");
+}
+
+// Tests that every paragraph separator splitting rich text into lines round-trips, not just `\r`
+// and `\n`.
+#[test]
+fn line_endings_round_trip_separators() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+
+    let text = "a\n\rb\n\r\nc\u{85}d\u{2029}e\u{1C}f\u{1D}g\u{1E}h\n\r";
+    buffer.set_rich_text([(text, Attrs::new())], Attrs::new(), Shaping::Advanced);
+    let endings: Vec<_> = buffer.lines.iter().map(|line| line.ending()).collect();
+    assert_eq!(
+        endings,
+        [
+            LineEnding::LfCr,
+            LineEnding::Lf,
+            LineEnding::CrLf,
+            LineEnding::Nel,
+            LineEnding::ParagraphSeparator,
+            LineEnding::FileSeparator,
+            LineEnding::GroupSeparator,
+            LineEnding::RecordSeparator,
+            LineEnding::LfCr,
+        ]
+    );
+    assert_eq!(buffer.text(), text);
+}
@@ -0,0 +1,101 @@
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, Buffer, FontSystem, LineHeight, Metrics, Shaping, VerticalMetrics,
+    Weight,
+};
+
+fn font_system() -> FontSystem {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let font = std::fs::read("fonts/NotoSans-Regular.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    font_system
+}
+
+fn line_tops(buffer: &Buffer) -> Vec<f32> {
+    buffer.layout_runs().map(|run| run.line_top).collect()
+}
+
+// Tests that lines are placed a multiple of the font size or an exact height apart.
+#[test]
+fn line_height_uniform() {
+    let mut font_system = font_system();
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(300.0, 1000.0);
+    buffer.set_text(
+        "One\nTwo\nThree",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.line_height(), 24.0);
+    assert_eq!(line_tops(&buffer), [0.0, 24.0, 48.0]);
+
+    buffer.set_line_height_mode(LineHeight::Relative(1.5));
+    assert_eq!(buffer.line_height(), 30.0);
+    assert_eq!(line_tops(&buffer), [0.0, 30.0, 60.0]);
+
+    buffer.set_line_height_mode(LineHeight::Exact(18.0));
+    assert_eq!(line_tops(&buffer), [0.0, 18.0, 36.0]);
+}
+
+// Tests that lines resolve their height from their tallest font, and that a minimum height is
+// kept for lines whose fonts need less.
+#[test]
+fn line_height_from_fonts() {
+    let mut font_system = font_system();
+    let mono = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let sans = Attrs::new().family(fontdb::Family::Name("Noto Sans"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 300.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "One\nTwo three\nFour",
+        mono,
+        Shaping::Advanced,
+    );
+    let mut attrs_list = AttrsList::new(mono);
+    attrs_list.add_span(4..9, sans);
+    buffer.lines[1].set_attrs_list(attrs_list);
+
+    // Height of the tallest font of each line
+    buffer.set_line_height_mode(&mut font_system, LineHeight::Normal);
+    let font_heights: Vec<f32> = buffer
+        .layout_runs()
+        .map(|run| {
+            run.glyphs
+                .iter()
+                .map(|glyph| {
+                    font_system
+                        .get_font(glyph.font_id)
+                        .unwrap()
+                        .line_height_em(VerticalMetrics::Typographic)
+                        * glyph.font_size
+                })
+                .fold(0.0, f32::max)
+        })
+        .collect::<Vec<_>>();
+    let font_heights: Vec<f32> = font_heights.into_iter().map(|h| h.max(20.0)).collect();
+    let tops = line_tops(&buffer);
+    assert_eq!(tops[0], (font_heights[0] - 20.0) / 2.0);
+    for i in 1..3 {
+        let distance = (font_heights[i - 1] + font_heights[i]) / 2.0;
+        assert!((tops[i] - tops[i - 1] - distance).abs() < 0.001);
+    }
+
+    // Room is only added when a font needs more than the minimum
+    let at_least = font_heights.iter().copied().fold(0.0, f32::max) + 10.0;
+    buffer.set_line_height_mode(&mut font_system, LineHeight::AtLeast(at_least));
+    assert_eq!(line_tops(&buffer), [0.0, at_least, 2.0 * at_least]);
+    let at_least = font_heights[0] - 4.0;
+    buffer.set_line_height_mode(&mut font_system, LineHeight::AtLeast(at_least));
+    let tops = line_tops(&buffer);
+    let distance = (font_heights[0] + font_heights[1].max(at_least)) / 2.0;
+    assert!((tops[1] - tops[0] - distance).abs() < 0.001);
+}
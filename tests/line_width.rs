@@ -0,0 +1,48 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, LineWidth, Metrics, Shaping, Weight, Wrap};
+
+// Tests that each visual line wraps to the width given for its index, counted across paragraphs,
+// and that clearing the callback wraps to the buffer width again.
+#[test]
+fn line_width_narrows_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(300.0, 1000.0);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_text(
+        "The quick brown fox jumps over the lazy dog.\nPack my box with five dozen liquor jugs, \
+         then pack another.",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let lines_before = buffer.layout_runs().count();
+
+    let end = |run: &cosmic_text::LayoutRun| {
+        run.glyphs
+            .iter()
+            .filter(|glyph| glyph.start < glyph.end && run.text[glyph.start..glyph.end] != *" ")
+            .map(|glyph| glyph.x + glyph.w)
+            .fold(0.0, f32::max)
+    };
+
+    // The first three lines are narrowed, as if next to an image
+    let width = |line_i: usize| if line_i < 3 { 150.0 } else { 300.0 };
+    buffer.set_line_width(Some(LineWidth::new(width)));
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > lines_before);
+    for (i, run) in runs.iter().enumerate() {
+        assert_eq!(run.glyphs.first().map_or(0.0, |glyph| glyph.x), 0.0);
+        assert!(end(run) <= width(i));
+    }
+    // Lines after the third one use the whole width
+    assert!(runs[3..].iter().any(|run| end(run) > 150.0));
+
+    buffer.set_line_width(None);
+    assert_eq!(buffer.layout_runs().count(), lines_before);
+}
@@ -0,0 +1,54 @@
+use cosmic_text::{fontdb, Attrs, AttrsList, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that the link under a point is found with its whole range, and that a link wrapped
+// across lines gets a rectangle in each of them.
+#[test]
+fn hit_link_wrapped() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "See the docs page",
+        attrs,
+        Shaping::Advanced,
+    );
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(8..12, attrs.link("https://example.com/docs"));
+    attrs_list.add_span(12..17, attrs.link("https://example.com/docs"));
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let run = buffer.layout_runs().next().unwrap();
+    let em = run.glyphs[0].w;
+    assert_eq!(buffer.hit_link(1.5 * em, 12.0), None);
+    assert_eq!(buffer.hit_link(9.5 * em, 30.0), None);
+    let hit = buffer.hit_link(9.5 * em, 12.0).unwrap();
+    assert_eq!(hit.link, "https://example.com/docs");
+    assert_eq!(hit.line_i, 0);
+    assert_eq!(hit.range, 8..17);
+    assert_eq!(hit.rects.len(), 1);
+    let rect = hit.rects[0];
+    assert!((rect.x - 8.0 * em).abs() < 0.01);
+    assert!((rect.w - 9.0 * em).abs() < 0.01);
+    assert_eq!((rect.y, rect.h), (0.0, 24.0));
+
+    // Wrapped after "docs ", the link continues on the next line
+    buffer.set_size(&mut font_system, 13.5 * em, 1000.0);
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    let hit = buffer.hit_link(1.5 * em, 36.0).unwrap();
+    assert_eq!(hit.range, 8..17);
+    assert_eq!(hit.rects.len(), 2);
+    assert_eq!(hit.rects[0].y, 0.0);
+    assert!((hit.rects[0].x - 8.0 * em).abs() < 0.01);
+    assert_eq!(hit.rects[1].y, 24.0);
+    assert!((hit.rects[1].x).abs() < 0.01);
+    assert!((hit.rects[1].w - 4.0 * em).abs() < 0.01);
+}
@@ -0,0 +1,57 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, Direction, FontSystem, ListMarker, Metrics, ParagraphAttrs, Shaping,
+    Weight,
+};
+
+// Tests that a list marker is laid out on the baseline of the first line of its paragraph,
+// ending its gap before the start of the line.
+#[test]
+fn list_marker_in_indent() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(200.0, 1000.0);
+    buffer.set_text(
+        "one two three four five six seven",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    buffer.set_paragraph_attrs(ParagraphAttrs::new().indent(40.0).hanging_indent(40.0));
+    buffer.lines[0].set_marker(Some(ListMarker::new("1.").gap(6.0)));
+    buffer.shape_until_scroll(false);
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > 1);
+    let marker = runs[0].marker;
+    assert_eq!(marker.len(), 2);
+    assert_eq!(runs[0].glyphs[0].x, 40.0);
+    assert!((marker[1].x + marker[1].w - 34.0).abs() < 0.01);
+    assert!(marker
+        .iter()
+        .all(|glyph| glyph.y == runs[0].glyphs[0].y && glyph.font_size == 20.0));
+    assert!(runs[1..].iter().all(|run| run.marker.is_empty()));
+
+    // Markers of right-to-left paragraphs are on the right
+    buffer.set_paragraph_attrs(
+        ParagraphAttrs::new()
+            .indent(40.0)
+            .direction(Direction::RightToLeft),
+    );
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    let start = runs[0]
+        .marker
+        .iter()
+        .map(|glyph| glyph.x)
+        .fold(f32::MAX, f32::min);
+    assert!((start - 166.0).abs() < 0.01);
+
+    buffer.lines[0].set_marker(None);
+    buffer.shape_until_scroll(false);
+    assert!(buffer.layout_runs().all(|run| run.marker.is_empty()));
+}
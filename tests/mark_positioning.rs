@@ -0,0 +1,59 @@
+use cosmic_text::rustybuzz::ttf_parser::GlyphId;
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that combining marks are centered over their base, whether the font positions them or
+// they are positioned from glyph bounding boxes.
+#[test]
+fn mark_positioning() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    // Vietnamese e with circumflex and acute, and Greek alpha with psili and oxia
+    for text in ["e\u{0302}\u{0301}", "\u{03B1}\u{0313}\u{0301}"] {
+        let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+        let mut buffer = buffer.borrow_with(&mut font_system);
+        buffer.set_size(1000.0, 100.0);
+        buffer.set_text(
+            text,
+            Attrs::new()
+                .family(fontdb::Family::Name("FiraMono"))
+                .weight(Weight::MEDIUM),
+            Shaping::Advanced,
+        );
+        let glyphs = buffer.layout_runs().next().unwrap().glyphs.to_vec();
+        let font = font_system.get_font(glyphs[0].font_id).unwrap();
+        let face = font.rustybuzz();
+        let upem = face.units_per_em() as f32;
+
+        let bbox = |glyph_id: u16| face.glyph_bounding_box(GlyphId(glyph_id)).unwrap();
+        let base = &glyphs[0];
+        let base_bbox = bbox(base.glyph_id);
+        let base_center = base.x
+            + base.font_size
+                * (base.x_offset
+                    + (base_bbox.x_min as f32 + base_bbox.width() as f32 / 2.0) / upem);
+        for mark in &glyphs[1..] {
+            assert_eq!(mark.w, 0.0, "{:?}", text);
+            let mark_bbox = bbox(mark.glyph_id);
+            let mark_center = mark.x
+                + mark.font_size
+                    * (mark.x_offset
+                        + (mark_bbox.x_min as f32 + mark_bbox.width() as f32 / 2.0) / upem);
+            assert!(
+                (mark_center - base_center).abs() < base.w / 4.0,
+                "{:?}: mark centered at {} over base centered at {}",
+                text,
+                mark_center,
+                base_center
+            );
+            // Above the baseline
+            assert!(
+                mark_bbox.y_min as f32 / upem + mark.y_offset > 0.0,
+                "{:?}",
+                text
+            );
+        }
+    }
+}
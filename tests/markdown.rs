@@ -0,0 +1,84 @@
+#![cfg(feature = "markdown")]
+
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, MarkdownStyle, Metrics, RichText, Shaping, Style, Weight,
+};
+
+fn pieces<'a>(rich_text: &'a RichText) -> Vec<Vec<(&'a str, Attrs<'a>)>> {
+    rich_text
+        .paragraphs()
+        .map(|(spans, _)| spans.collect())
+        .collect()
+}
+
+// Tests that emphasis, code, links and headings of Markdown are parsed into pieces of text with
+// matching attributes, and that other text is kept as is.
+#[test]
+fn markdown_inline_elements() {
+    let attrs = Attrs::new();
+    let style = MarkdownStyle::new(20.0);
+    let bold = attrs.weight(Weight::BOLD);
+    let italic = attrs.style(Style::Italic);
+    let code = attrs.family(fontdb::Family::Monospace);
+
+    let rich_text = RichText::from_markdown(
+        "# Title #\n\
+         Some **bold**, *italic* and `code`\n\
+         snake_case and 2 * 3 \\*not italic\\*\n\
+         \n\
+         A [__bold link__](https://example.com) `` a`b ``",
+        attrs,
+        &style,
+    );
+    let link = attrs.link("https://example.com").underline(true);
+    assert_eq!(
+        pieces(&rich_text),
+        [
+            vec![("Title", bold.font_size(40.0))],
+            vec![
+                ("Some ", attrs),
+                ("bold", bold),
+                (", ", attrs),
+                ("italic", italic),
+                (" and ", attrs),
+                ("code", code),
+                (" ", attrs),
+                ("snake_case and 2 * 3 ", attrs),
+                ("*not italic", attrs),
+                ("*", attrs),
+            ],
+            vec![
+                ("A ", attrs),
+                ("bold link", link.weight(Weight::BOLD)),
+                (" ", attrs),
+                ("a`b", code),
+            ],
+        ]
+    );
+}
+
+// Tests that Markdown set on a buffer makes a line for every paragraph and hard line break.
+#[test]
+fn markdown_buffer_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let attrs = Attrs::new();
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_markdown(
+        &mut font_system,
+        "## Heading\nfirst line  \nsecond\\\nthird\nwrapped\n\n\nlast",
+        attrs,
+        &MarkdownStyle::new(20.0),
+        Shaping::Advanced,
+    );
+    let texts: Vec<&str> = buffer.lines.iter().map(|line| line.text()).collect();
+    assert_eq!(
+        texts,
+        ["Heading", "first line", "second", "third wrapped", "last"]
+    );
+    assert_eq!(
+        buffer.lines[0].attrs_list().get_span(0),
+        attrs.weight(Weight::BOLD).font_size(30.0)
+    );
+    assert_eq!(buffer.lines[1].paragraph_attrs().space_after, 10.0);
+}
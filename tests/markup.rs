@@ -0,0 +1,110 @@
+use cosmic_text::{
+    fontdb, Attrs, BaselineShift, Buffer, Color, DecorationLine, DecorationStyle, FontSystem,
+    MarkupError, MarkupErrorKind, Metrics, RichText, Shaping, Style, Weight,
+};
+
+// Tests that elements and span attributes of markup are parsed into pieces of text with matching
+// attributes, and that entities are unescaped.
+#[test]
+fn markup_elements() {
+    let attrs = Attrs::new();
+    let rich_text = RichText::from_markup(
+        "a <b>bold <i>and italic</i></b> &amp; \
+         <span face=\"Fira Sans\" size=\"20px\" weight=\"300\" color=\"#f80\" \
+         underline='double' underline_color='#00ff0080'>span</span>\
+         x<sup>2</sup>&#x21;<span/>",
+        attrs,
+    )
+    .unwrap();
+    let pieces: Vec<(&str, Attrs)> = rich_text
+        .paragraphs()
+        .flat_map(|(spans, _)| spans)
+        .collect();
+    let bold = attrs.weight(Weight::BOLD);
+    let mut span = attrs
+        .family(fontdb::Family::Name("Fira Sans"))
+        .font_size(20.0)
+        .weight(Weight(300))
+        .color(Color::rgb(0xff, 0x88, 0x00));
+    span.decoration.underline = Some(DecorationLine {
+        style: DecorationStyle::Double,
+        color_opt: Some(Color::rgba(0x00, 0xff, 0x00, 0x80)),
+    });
+    assert_eq!(
+        pieces,
+        [
+            ("a ", attrs),
+            ("bold ", bold),
+            ("and italic", bold.style(Style::Italic)),
+            (" & ", attrs),
+            ("span", span),
+            ("x", attrs),
+            ("2", attrs.baseline_shift(BaselineShift::Superscript)),
+            ("!", attrs),
+        ]
+    );
+}
+
+// Tests that malformed markup is reported with the kind and offset of the error, and leaves the
+// buffer unchanged.
+#[test]
+fn markup_errors() {
+    let error = |markup: &str| RichText::from_markup(markup, Attrs::new()).unwrap_err();
+    let expected = |kind: MarkupErrorKind, offset: usize| MarkupError { kind, offset };
+    assert_eq!(error("a <b"), expected(MarkupErrorKind::UnexpectedEnd, 2));
+    assert_eq!(
+        error("<blink>a</blink>"),
+        expected(MarkupErrorKind::UnknownElement, 1)
+    );
+    assert_eq!(
+        error("<span size=20>a</span>"),
+        expected(MarkupErrorKind::InvalidTag, 6)
+    );
+    assert_eq!(
+        error("<b size='20'>a</b>"),
+        expected(MarkupErrorKind::UnknownAttribute, 3)
+    );
+    assert_eq!(
+        error("<span rise='20'>a</span>"),
+        expected(MarkupErrorKind::UnknownAttribute, 6)
+    );
+    assert_eq!(
+        error("<span color='red'>a</span>"),
+        expected(MarkupErrorKind::InvalidValue, 6)
+    );
+    assert_eq!(
+        error("a &nbsp; b"),
+        expected(MarkupErrorKind::InvalidEntity, 2)
+    );
+    assert_eq!(
+        error("<b><i>a</b></i>"),
+        expected(MarkupErrorKind::MismatchedClose, 7)
+    );
+    assert_eq!(
+        error("a <b>b"),
+        expected(MarkupErrorKind::UnclosedElement, 2)
+    );
+    assert_eq!(
+        error("a <b>b").to_string(),
+        "element is not closed at byte 2"
+    );
+
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer
+        .set_markup(
+            &mut font_system,
+            "<b>a</b>\nb",
+            Attrs::new(),
+            Shaping::Advanced,
+        )
+        .unwrap();
+    let texts: Vec<&str> = buffer.lines.iter().map(|line| line.text()).collect();
+    assert_eq!(texts, ["a", "b"]);
+    assert!(buffer
+        .set_markup(&mut font_system, "<b>c", Attrs::new(), Shaping::Advanced)
+        .is_err());
+    let texts: Vec<&str> = buffer.lines.iter().map(|line| line.text()).collect();
+    assert_eq!(texts, ["a", "b"]);
+}
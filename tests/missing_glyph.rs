@@ -0,0 +1,46 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, MissingGlyph, Shaping, Weight};
+
+// Tests that characters no font supports are counted, and drawn with the buffer's replacement.
+#[test]
+fn missing_glyph_replacement() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "a\u{6F22}b",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let glyph_ids = |buffer: &Buffer| -> Vec<u16> {
+        let run = buffer.layout_runs().next().unwrap();
+        run.glyphs.iter().map(|glyph| glyph.glyph_id).collect()
+    };
+    assert_eq!(buffer.missing_glyphs(), 1);
+    assert_eq!(glyph_ids(&buffer)[1], 0);
+
+    buffer.set_missing_glyph(&mut font_system, Some(MissingGlyph::Char('?')));
+    assert_eq!(buffer.missing_glyphs(), 1);
+    let question_id = {
+        let run = buffer.layout_runs().next().unwrap();
+        let glyph = &run.glyphs[1];
+        assert_eq!((glyph.start, glyph.end), (1, 4));
+        assert!(glyph.w > 0.0);
+        glyph.glyph_id
+    };
+    assert_ne!(question_id, 0);
+
+    let font_id = buffer.layout_runs().next().unwrap().glyphs[0].font_id;
+    let a_id = glyph_ids(&buffer)[0];
+    buffer.set_missing_glyph(&mut font_system, Some(MissingGlyph::Glyph(font_id, a_id)));
+    assert_eq!(glyph_ids(&buffer)[..2], [a_id, a_id]);
+
+    buffer.set_missing_glyph(&mut font_system, None);
+    assert_eq!(glyph_ids(&buffer)[1], 0);
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "nfc-normalization")]
+
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, Buffer, Color, Cursor, Edit, Editor, FontSystem, Metrics, Shaping,
+    Weight,
+};
+
+// Tests that decomposed text is set and inserted precomposed when normalization is enabled, with
+// cursors mapped back to the text that was set.
+#[test]
+fn nfc_normalization() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 300.0, 100.0);
+    buffer.set_text(&mut font_system, "cafe\u{301}", attrs, Shaping::Advanced);
+    assert_eq!(buffer.lines[0].text(), "cafe\u{301}");
+    assert!(buffer.nfc_text().is_none());
+
+    buffer.set_nfc_normalization(true);
+    let text = "cafe\u{301}\nnaI\u{308}ve";
+    buffer.set_text(&mut font_system, text, attrs, Shaping::Advanced);
+    assert_eq!(buffer.lines[0].text(), "caf\u{E9}");
+    assert_eq!(buffer.lines[1].text(), "na\u{CF}ve");
+    assert!(buffer.nfc_text().unwrap().is_changed());
+    assert_eq!(buffer.cursor_to_original(Cursor::new(0, 3)), 3);
+    assert_eq!(buffer.cursor_to_original(Cursor::new(0, 5)), 6);
+    assert_eq!(
+        buffer.cursor_to_original(Cursor::new(1, 4)),
+        text.find("ve").unwrap()
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    assert_eq!(run.glyphs.len(), 4);
+
+    // Inserted text is normalized, keeping its spans of attributes on the same characters
+    let mut editor = Editor::new(buffer);
+    editor.set_cursor(Cursor::new(1, 0));
+    let mut attrs_list = AttrsList::new(attrs);
+    let red = attrs.color(Color::rgb(0xFF, 0, 0));
+    attrs_list.add_span(3..7, red);
+    editor.insert_string("o\u{302}te\u{301} ", Some(attrs_list));
+    editor.with_buffer(|buffer| {
+        let line = &buffer.lines[1];
+        assert_eq!(line.text(), "\u{F4}t\u{E9} na\u{CF}ve");
+        assert_eq!(line.attrs_list().get_span(0), attrs);
+        assert_eq!(line.attrs_list().get_span(2), red);
+        assert_eq!(line.attrs_list().get_span(3), red);
+        assert_eq!(line.attrs_list().get_span(5), attrs);
+    });
+    assert_eq!(editor.cursor(), Cursor::new(1, 6));
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+// Tests that a font system without system fonts only uses the fonts it is given.
+#[test]
+fn no_system_fonts_registered_only() {
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    let mut font_system =
+        FontSystem::new_without_system_fonts([fontdb::Source::Binary(Arc::new(font))]);
+    if !cfg!(feature = "embedded-fonts") {
+        assert_eq!(font_system.db().len(), 1);
+    }
+    let id = font_system
+        .db()
+        .faces()
+        .find(|face| face.post_script_name == "FiraMono-Medium")
+        .unwrap()
+        .id;
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "Hello",
+        Attrs::new().family(fontdb::Family::Serif),
+        Shaping::Advanced,
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    assert!(run.glyphs.iter().all(|glyph| glyph.font_id == id));
+}
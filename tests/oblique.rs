@@ -0,0 +1,80 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, CacheKeyFlags, Command, FontSystem, Metrics, Oblique, Shaping,
+    SwashCache, Weight, DEFAULT_OBLIQUE_ANGLE,
+};
+
+// Tests that the angle of synthesized italic is carried from the attributes to the cache key,
+// mirrored in right-to-left text if asked, and applied to rasterized images and outlines.
+#[test]
+fn oblique_angle() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(40.0, 50.0));
+    buffer.set_size(&mut font_system, 300.0, 100.0);
+    let mut cache_key = |font_system: &mut FontSystem, text: &str, attrs: Attrs| {
+        buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+        let run = buffer.layout_runs().next().unwrap();
+        run.glyphs[0].physical((0.0, 0.0), 1.0).cache_key
+    };
+
+    let upright = cache_key(&mut font_system, "I", attrs);
+    assert!(!upright.flags.contains(CacheKeyFlags::FAKE_ITALIC));
+    let default = cache_key(
+        &mut font_system,
+        "I",
+        attrs.cache_key_flags(CacheKeyFlags::FAKE_ITALIC),
+    );
+    assert_eq!(
+        f32::from_bits(default.oblique_angle_bits),
+        DEFAULT_OBLIQUE_ANGLE
+    );
+
+    let oblique = Oblique::new(30.0).mirror_rtl(true);
+    let ltr = cache_key(&mut font_system, "I", attrs.oblique(oblique));
+    assert!(ltr.flags.contains(CacheKeyFlags::FAKE_ITALIC));
+    assert_eq!(f32::from_bits(ltr.oblique_angle_bits), 30.0);
+    let rtl = cache_key(&mut font_system, "\u{5D0}", attrs.oblique(oblique));
+    assert_eq!(f32::from_bits(rtl.oblique_angle_bits), -30.0);
+    let rtl = cache_key(
+        &mut font_system,
+        "\u{5D0}",
+        attrs.oblique(Oblique::new(30.0)),
+    );
+    assert_eq!(f32::from_bits(rtl.oblique_angle_bits), 30.0);
+
+    // A steeper angle makes the image of an upright stem wider
+    let mut swash_cache = SwashCache::new();
+    let width = |font_system: &mut FontSystem, swash_cache: &mut SwashCache, cache_key| {
+        let image = swash_cache
+            .get_image_uncached(font_system, cache_key)
+            .unwrap();
+        image.placement.width
+    };
+    let upright_width = width(&mut font_system, &mut swash_cache, upright);
+    let default_width = width(&mut font_system, &mut swash_cache, default);
+    let ltr_width = width(&mut font_system, &mut swash_cache, ltr);
+    assert!(upright_width < default_width);
+    assert!(default_width < ltr_width);
+
+    // Outlines are skewed the same way, so the top of the glyph moves right
+    let max_x = |font_system: &mut FontSystem, swash_cache: &mut SwashCache, cache_key| {
+        swash_cache
+            .get_outline_commands(font_system, cache_key)
+            .unwrap()
+            .iter()
+            .filter_map(|command| match command {
+                Command::MoveTo(point) | Command::LineTo(point) => Some(point.x),
+                _ => None,
+            })
+            .fold(f32::MIN, f32::max)
+    };
+    let upright_max_x = max_x(&mut font_system, &mut swash_cache, upright);
+    let ltr_max_x = max_x(&mut font_system, &mut swash_cache, ltr);
+    assert!(upright_max_x < ltr_max_x);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "optimal-wrap")]
+
+use cosmic_text::{
+    buffer_get_optimal_wrap, buffer_set_optimal_wrap, buffer_set_wrap, fontdb, Attrs, Buffer,
+    FfiOptimalWrap, FfiWrap, FontSystem, Metrics, OptimalWrap, Shaping, Weight, Wrap,
+};
+
+// Tests that optimal wrapping chooses the breaks of the whole paragraph that leave the least
+// space at the end of its lines, where word wrapping fills each line in turn.
+#[test]
+fn optimal_wrap_evens_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_wrap(Wrap::None);
+    buffer.set_text("a", attrs, Shaping::Advanced);
+    let advance = buffer.layout_runs().next().unwrap().line_w;
+
+    buffer.set_size(11.0 * advance + 0.5, 100.0);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_text("aa b cccc d eee ffff gggg", attrs, Shaping::Advanced);
+    let lines = |buffer: &Buffer| -> Vec<String> {
+        buffer
+            .layout_runs()
+            .map(|run| {
+                let start = run.glyphs.first().unwrap().start;
+                let end = run.glyphs.last().unwrap().end;
+                run.text[start..end].trim().to_string()
+            })
+            .collect()
+    };
+    assert_eq!(lines(&buffer), ["aa b cccc d", "eee ffff", "gggg"]);
+
+    buffer.set_wrap(Wrap::Optimal(OptimalWrap::new()));
+    assert_eq!(lines(&buffer), ["aa b cccc", "d eee ffff", "gggg"]);
+
+    // Looseness asks for one more line
+    buffer.set_wrap(Wrap::Optimal(OptimalWrap::new().looseness(1)));
+    assert_eq!(buffer.layout_runs().count(), 4);
+
+    // Paragraphs that cannot be broken within the tolerance are wrapped word by word
+    buffer.set_text("aa bbbbbbbbbbbbbbbb cc", attrs, Shaping::Advanced);
+    let optimal_lines = lines(&buffer);
+    buffer.set_wrap(Wrap::WordOrGlyph);
+    assert_eq!(optimal_lines, lines(&buffer));
+}
+
+// Tests that optimal wrapping parameters set over FFI are kept when the wrap mode is set again.
+#[test]
+fn optimal_wrap_ffi() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut optimal = FfiOptimalWrap {
+        tolerance: 0.0,
+        line_penalty: 0.0,
+        hyphen_penalty: 0.0,
+        consecutive_hyphen_demerits: 0.0,
+        looseness: 0,
+    };
+    assert!(!buffer_get_optimal_wrap(&buffer, &mut optimal));
+
+    let params = OptimalWrap::new().line_penalty(100.0).looseness(1);
+    buffer_set_optimal_wrap(&mut buffer, &mut font_system, params.into());
+    assert_eq!(buffer.wrap(), Wrap::Optimal(params));
+    buffer_set_wrap(&mut buffer, &mut font_system, FfiWrap::Optimal);
+    assert_eq!(buffer.wrap(), Wrap::Optimal(params));
+    assert!(buffer_get_optimal_wrap(&buffer, &mut optimal));
+    assert_eq!(OptimalWrap::from(optimal), params);
+
+    buffer_set_wrap(&mut buffer, &mut font_system, FfiWrap::Word);
+    buffer_set_wrap(&mut buffer, &mut font_system, FfiWrap::Optimal);
+    assert_eq!(buffer.wrap(), Wrap::Optimal(OptimalWrap::default()));
+}
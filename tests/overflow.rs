@@ -0,0 +1,30 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that the first glyph past the width of a line that does not wrap is found.
+#[test]
+fn overflow_first_clipped_glyph() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_wrap(Wrap::None);
+    buffer.set_text(
+        "abcdefgh",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph_w = run.glyphs[0].w;
+    assert_eq!(run.overflow(run.line_w), None);
+    // The glyph that is cut in half is the first one clipped
+    assert_eq!(run.overflow(glyph_w * 3.5), Some(3));
+    assert_eq!(run.overflow(glyph_w * 3.0), Some(3));
+    assert_eq!(run.overflow(0.0), Some(0));
+}
@@ -0,0 +1,52 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, Metrics, ParagraphAttrs, Scroll, Shaping, Weight, Wrap,
+};
+
+// Tests that pages hold the layout lines that fit in their height, keeping paragraph spacing at
+// their top, and that setting the scroll to the start of a page shows its first line.
+#[test]
+fn pagination_splits_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(100.0, 60.0);
+    buffer.set_wrap(Wrap::Glyph);
+    buffer.set_text(
+        "abcdefghijklmnopqrst\nuvwxyz",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let pages = buffer.paginate(60.0);
+    let ranges: Vec<_> = pages.iter().map(|page| (page.start, page.end)).collect();
+    assert_eq!(
+        ranges,
+        [
+            (Scroll::new(0, 0), Scroll::new(0, 2)),
+            (Scroll::new(0, 2), Scroll::new(2, 0)),
+        ]
+    );
+    let offsets: Vec<_> = pages.iter().map(|page| (page.y, page.height)).collect();
+    assert_eq!(offsets, [(0.0, 48.0), (48.0, 48.0)]);
+
+    // Spacing that no longer fits moves the paragraph to the next page
+    buffer.lines[1].set_paragraph_attrs(ParagraphAttrs::new().spacing(20.0, 0.0));
+    let pages = buffer.paginate(60.0);
+    assert_eq!(pages.len(), 3);
+    assert_eq!(pages[1].end, Scroll::new(1, 0));
+    assert_eq!(
+        (pages[2].start, pages[2].y, pages[2].height),
+        (Scroll::new(1, 0), 72.0, 44.0)
+    );
+
+    buffer.set_scroll(pages[1].start);
+    let run = buffer.layout_runs().next().unwrap();
+    assert_eq!((run.line_i, run.line_top), (0, 0.0));
+    assert_eq!(run.glyphs.len(), 4);
+}
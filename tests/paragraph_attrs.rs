@@ -0,0 +1,156 @@
+use cosmic_text::{
+    fontdb, Align, Attrs, AttrsList, Buffer, Direction, FontSystem, LayoutOptions, Metrics,
+    ParagraphAttrs, ShapeLine, Shaping, Weight, Wrap,
+};
+
+// Tests that each paragraph of rich text is laid out with its own attributes.
+#[test]
+fn paragraph_attrs_rich_paragraphs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 200.0, 1000.0);
+    buffer.set_rich_paragraphs(
+        &mut font_system,
+        [
+            (
+                [("Title", attrs)],
+                ParagraphAttrs::new()
+                    .align(Align::Center)
+                    .spacing(0.0, 10.0),
+            ),
+            (
+                [("one two three four five six", attrs)],
+                ParagraphAttrs::new().indent(30.0).spacing(5.0, 0.0),
+            ),
+            (
+                [("abc", attrs)],
+                ParagraphAttrs::new().direction(Direction::RightToLeft),
+            ),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.lines.len(), 3);
+    assert_eq!(buffer.lines[0].align(), Some(Align::Center));
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > 3);
+
+    let title = &runs[0];
+    let title_x = title.glyphs[0].x;
+    assert!((title_x - (200.0 - title.line_w) / 2.0).abs() < 0.01);
+    assert_eq!(title.line_top, 0.0);
+
+    // The first line is indented and shifted down by the space between paragraphs
+    let first = &runs[1];
+    assert_eq!(first.line_i, 1);
+    assert_eq!(first.glyphs[0].x, 30.0);
+    assert_eq!(first.line_top, 24.0 + 10.0 + 5.0);
+    assert!(first.line_w <= 200.0);
+    let second = &runs[2];
+    assert_eq!(second.line_i, 1);
+    assert_eq!(second.glyphs[0].x, 0.0);
+    assert_eq!(second.line_top, 2.0 * 24.0 + 10.0 + 5.0);
+
+    // Left to right text in a right to left paragraph is aligned to the right
+    let last = runs.last().unwrap();
+    assert_eq!(last.line_i, 2);
+    assert!(last.rtl);
+    let end = last
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.x + glyph.w)
+        .fold(0.0, f32::max);
+    assert!((end - 200.0).abs() < 0.01);
+
+    // Changing the attributes of a line lays it out again
+    buffer.lines[1].set_paragraph_attrs(ParagraphAttrs::new());
+    buffer.shape_until_scroll(&mut font_system, false);
+    let first = buffer.layout_runs().nth(1).unwrap();
+    assert_eq!(first.glyphs[0].x, 0.0);
+    assert_eq!(first.line_top, 24.0 + 10.0);
+}
+
+// Tests that lines after the first start at the hanging indent, and that wrapping leaves room
+// for it.
+#[test]
+fn paragraph_attrs_hanging_indent() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 200.0, 1000.0);
+    buffer.set_rich_paragraphs(
+        &mut font_system,
+        [(
+            [("- one two three four five six seven", attrs)],
+            ParagraphAttrs::new().hanging_indent(24.0),
+        )],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > 2);
+    assert_eq!(runs[0].glyphs[0].x, 0.0);
+    for run in runs[1..].iter() {
+        assert_eq!(run.glyphs[0].x, 24.0);
+        let end = run.glyphs.last().map_or(0.0, |glyph| glyph.x + glyph.w);
+        assert!(end <= 200.0);
+    }
+
+    // A negative first line indent hangs the bullet out of the paragraph
+    buffer.lines[0].set_paragraph_attrs(ParagraphAttrs::new().indent(-24.0));
+    buffer.shape_until_scroll(&mut font_system, false);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs[0].glyphs[0].x, -24.0);
+    assert_eq!(runs[1].glyphs[0].x, 0.0);
+}
+
+// Tests that a shape line is laid out with the indents of its layout options.
+#[test]
+fn paragraph_attrs_layout_options() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = AttrsList::new(
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+    );
+    let line = ShapeLine::new(
+        &mut font_system,
+        "one two three four five six",
+        &attrs,
+        Shaping::Advanced,
+    );
+    let options = LayoutOptions::new().indent(30.0).hanging_indent(12.0);
+    let lines = line.layout_with_options(20.0, 150.0, Wrap::Word, None, &options);
+    assert!(lines.len() > 2);
+    assert_eq!(lines[0].glyphs[0].x, 30.0);
+    for line in &lines[1..] {
+        assert_eq!(line.glyphs[0].x, 12.0);
+        assert!(line.w <= 150.0);
+    }
+
+    // Default options lay out lines like the functions without options
+    let plain = line.layout(20.0, 150.0, Wrap::Word, None, None);
+    let defaults = line.layout_with_options(20.0, 150.0, Wrap::Word, None, &LayoutOptions::new());
+    assert_eq!(plain.len(), defaults.len());
+    assert_eq!(plain[0].glyphs[0].x, 0.0);
+    assert_eq!(defaults[0].glyphs[0].x, 0.0);
+}
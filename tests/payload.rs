@@ -0,0 +1,80 @@
+use cosmic_text::{
+    fontdb, Attrs, AttrsList, AttrsOverride, Buffer, FontSystem, Metrics, Payload, Shaping, Weight,
+};
+
+#[derive(Debug, PartialEq)]
+struct Diagnostic {
+    id: u32,
+    message: &'static str,
+}
+
+// Tests that the ids of payloads of spans are carried to their glyphs, and that the payloads of
+// the ids can be downcast to their type.
+#[test]
+fn payload_layout_glyphs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let diagnostic = Payload::new(Diagnostic {
+        id: 7,
+        message: "unused variable",
+    });
+    let widget = Payload::new(42u64);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 300.0, 200.0);
+    buffer.set_text(&mut font_system, "let x = 1;", attrs, Shaping::Advanced);
+    let mut attrs_list = AttrsList::new(attrs);
+    attrs_list.add_span(4..5, attrs.payload(&diagnostic));
+    attrs_list.add_span(8..9, attrs.payload(&widget));
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let attrs_list = buffer.lines[0].attrs_list();
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph = |start: usize| run.glyphs.iter().find(|g| g.start == start).unwrap();
+    assert_eq!(glyph(0).payload_id, 0);
+    assert_eq!(attrs_list.payload(glyph(0).payload_id), None);
+    assert_eq!(glyph(4).payload_id, diagnostic.id());
+    let payload = attrs_list.payload(glyph(4).payload_id).unwrap();
+    assert_eq!(payload, &diagnostic);
+    assert!(payload.is::<Diagnostic>());
+    assert_eq!(
+        payload.downcast_ref::<Diagnostic>(),
+        Some(&Diagnostic {
+            id: 7,
+            message: "unused variable"
+        })
+    );
+    let payload = attrs_list.payload(glyph(8).payload_id).unwrap();
+    assert_eq!(payload.downcast_ref::<u64>(), Some(&42));
+    assert_eq!(payload.downcast_ref::<Diagnostic>(), None);
+
+    // Layers may attach payloads over the spans below them
+    buffer.set_attrs_layer(
+        &mut font_system,
+        0,
+        [(0, 0..3, AttrsOverride::new().payload(diagnostic.clone()))],
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    let payload_id = run.glyphs[0].payload_id;
+    assert_eq!(
+        buffer.lines[0].attrs_list().payload(payload_id),
+        Some(&diagnostic)
+    );
+}
+
+// Tests that payloads are equal only if they are clones of the same payload.
+#[test]
+fn payload_identity() {
+    let a = Payload::new(1u32);
+    let b = Payload::new(1u32);
+    assert_eq!(a, a.clone());
+    assert_ne!(a, b);
+    assert_ne!(Attrs::new().payload(&a), Attrs::new().payload(&b));
+}
@@ -0,0 +1,59 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Placeholder, Shaping, Weight, Wrap};
+
+// Tests that a placeholder is laid out as a box with its size, wraps like a word, and raises
+// the height of its line.
+#[test]
+fn placeholder_inline_box() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let icon = attrs.placeholder(Placeholder::new(7, 2.0, 1.5, 0.5));
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 1000.0);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_rich_text(
+        [("ab ", attrs), ("\u{FFFC}", icon), (" cd", attrs)],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let boxes = run.placeholders();
+    assert_eq!(boxes.len(), 1);
+    let placeholder = boxes[0];
+    assert_eq!(placeholder.id, 7);
+    assert_eq!(placeholder.w, 40.0);
+    assert_eq!(placeholder.y, -30.0);
+    assert_eq!(placeholder.h, 40.0);
+    let glyph = run
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.has_placeholder)
+        .unwrap();
+    assert_eq!((glyph.start, glyph.end), (3, 6));
+    assert_eq!(placeholder.x, glyph.x);
+    // The glyphs after the placeholder start at its end
+    let next = run.glyphs.iter().find(|glyph| glyph.start == 6).unwrap();
+    assert_eq!(next.x, glyph.x + 40.0);
+    // The placeholder is not drawn with the glyphs
+    assert!(buffer
+        .glyph_batches()
+        .iter()
+        .flat_map(|batch| batch.glyphs.iter())
+        .all(|batch_glyph| !batch_glyph.glyph.has_placeholder));
+
+    // The placeholder wraps to the next line when it does not fit
+    let ab_w = run.glyphs[0].w * 3.0;
+    buffer.set_size(ab_w + 20.0, 1000.0);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.len() > 1);
+    assert!(runs[0].placeholders().is_empty());
+    assert_eq!(runs[1].placeholders().len(), 1);
+    assert_eq!(runs[1].placeholders()[0].x, 0.0);
+}
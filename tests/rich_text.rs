@@ -0,0 +1,65 @@
+use cosmic_text::{
+    fontdb, Align, Attrs, AttrsList, Buffer, FontSystem, Metrics, ParagraphAttrs, RichText,
+    Shaping, Weight,
+};
+
+// Tests that rich text sets the same lines, spans and paragraph attributes as the equivalent
+// call to set_rich_paragraphs, with styled pieces inheriting the default attributes.
+#[test]
+fn rich_text_paragraphs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let bold = attrs.weight(Weight::BOLD);
+    let centered = ParagraphAttrs::new().align(Align::Center);
+
+    let rich_text = RichText::new(attrs)
+        .styled("Title", |attrs| attrs.weight(Weight::BOLD))
+        .paragraph(centered)
+        .text("Some ")
+        .span(String::from("bold"), bold)
+        .text(" text\nand more");
+    assert!(!rich_text.is_empty());
+    assert!(RichText::new(attrs).paragraph(centered).is_empty());
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_rich(&mut font_system, &rich_text, Shaping::Advanced);
+    let texts: Vec<&str> = buffer.lines.iter().map(|line| line.text()).collect();
+    assert_eq!(texts, ["Title", "Some bold text", "and more"]);
+    assert_eq!(buffer.lines[0].attrs_list().get_span(0), bold);
+    assert_eq!(buffer.lines[0].paragraph_attrs(), ParagraphAttrs::new());
+    assert_eq!(buffer.lines[1].attrs_list().get_span(5), bold);
+    assert_eq!(buffer.lines[1].attrs_list().get_span(9), attrs);
+    assert_eq!(buffer.lines[1].paragraph_attrs(), centered);
+    assert_eq!(buffer.lines[2].paragraph_attrs(), centered);
+
+    let mut expected = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    expected.set_rich_paragraphs(
+        &mut font_system,
+        [
+            (vec![("Title", bold)], ParagraphAttrs::new()),
+            (
+                vec![("Some ", attrs), ("bold", bold), (" text\nand more", attrs)],
+                centered,
+            ),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+    for (line, expected_line) in buffer.lines.iter().zip(expected.lines.iter()) {
+        assert_eq!(line.text(), expected_line.text());
+        assert_eq!(
+            line.attrs_list().spans(),
+            expected_line.attrs_list().spans()
+        );
+    }
+
+    // Empty rich text still leaves a line with the default attributes
+    buffer.set_rich(&mut font_system, &RichText::new(bold), Shaping::Advanced);
+    assert_eq!(buffer.lines.len(), 1);
+    assert_eq!(buffer.lines[0].attrs_list(), &AttrsList::new(bold));
+}
@@ -0,0 +1,64 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, Metrics, Ruby, Shaping, TextOrientation, Weight,
+    WritingMode, RUBY_SCALE,
+};
+
+// Tests that ruby text is shaped at half the size of its base, centered over it or beside it in
+// vertical lines, and that its line is moved down to make room for it.
+#[test]
+fn ruby_over_base() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(400.0, 200.0);
+    buffer.set_text_orientation(TextOrientation::Upright);
+    buffer.set_text(
+        "abc def\nghi",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    buffer.lines[0].set_ruby(vec![Ruby::new(4..7, "xyz")]);
+    buffer.shape_until_scroll(false);
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs[0].ruby.len(), 1);
+    let ruby = &runs[0].ruby[0];
+    assert_eq!(ruby.ruby_i, 0);
+    assert_eq!(ruby.glyphs.len(), 3);
+    let base = &runs[0].glyphs[4..7];
+    let base_start = base[0].x;
+    let base_end = base[2].x + base[2].w;
+    let ruby_start = ruby.glyphs[0].x;
+    let ruby_end = ruby.glyphs[2].x + ruby.glyphs[2].w;
+    assert!((ruby_end - ruby_start - (base_end - base_start) / 2.0).abs() < 0.01);
+    assert!(((ruby_start + ruby_end) / 2.0 - (base_start + base_end) / 2.0).abs() < 0.01);
+    for glyph in ruby.glyphs.iter() {
+        assert_eq!(glyph.font_size, 10.0);
+        assert!(glyph.y < -base[0].font_size / 2.0);
+    }
+    let ruby_height = runs[0].line_top;
+    assert!(ruby_height > 0.0);
+    assert!(runs[1].ruby.is_empty());
+    assert_eq!(runs[1].line_top, ruby_height + 24.0);
+
+    buffer.set_writing_mode(WritingMode::VerticalRl);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    let ruby = &runs[0].ruby[0];
+    let base = &runs[0].glyphs[4..7];
+    // Ruby glyphs are moved up so their smaller ascent is drawn from the same line_y
+    let shift = runs[0].line_y * (1.0 - RUBY_SCALE);
+    let ruby_start = ruby.glyphs[0].y + shift;
+    let ruby_end = ruby.glyphs[2].y + ruby.glyphs[2].w + shift;
+    let (base_start, base_end) = (base[0].y, base[2].y + base[2].w);
+    assert!(((ruby_start + ruby_end) / 2.0 - (base_start + base_end) / 2.0).abs() < 0.01);
+    for glyph in ruby.glyphs.iter() {
+        assert!(glyph.x > base[0].w / 2.0 - 0.01);
+    }
+    assert_eq!(runs[0].line_x, 400.0 - ruby_height - 12.0);
+}
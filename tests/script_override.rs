@@ -0,0 +1,46 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Script, Shaping};
+
+// Tests that the script of a span replaces the detected one when shaping, so that Arabic
+// shaped as Latin does not get its joining forms and ligatures, and that glyphs report the
+// script of the span.
+#[test]
+fn script_override_shaping() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSansArabic.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new().family(fontdb::Family::Name("Noto Sans Arabic"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 500.0, 200.0);
+    let glyphs = |buffer: &Buffer| -> Vec<(u16, Script)> {
+        let run = buffer.layout_runs().next().unwrap();
+        run.glyphs
+            .iter()
+            .map(|glyph| (glyph.glyph_id, glyph.script()))
+            .collect()
+    };
+
+    buffer.set_text(&mut font_system, "سلام", attrs, Shaping::Advanced);
+    let joined = glyphs(&buffer);
+    assert!(joined.iter().all(|&(_, script)| script == Script::Arabic));
+
+    buffer.set_text(
+        &mut font_system,
+        "سلام",
+        attrs.script(Script::Latin),
+        Shaping::Advanced,
+    );
+    let isolated = glyphs(&buffer);
+    assert!(isolated.iter().all(|&(_, script)| script == Script::Latin));
+    assert_ne!(
+        joined
+            .iter()
+            .map(|&(glyph_id, _)| glyph_id)
+            .collect::<Vec<_>>(),
+        isolated
+            .iter()
+            .map(|&(glyph_id, _)| glyph_id)
+            .collect::<Vec<_>>()
+    );
+}
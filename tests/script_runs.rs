@@ -0,0 +1,39 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Script, Shaping, Weight};
+
+// Tests that glyphs report their script, and that layout runs are split by script with spaces
+// and punctuation joining the preceding range.
+#[test]
+fn script_runs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text(
+        "Hello, мир! 42",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let scripts: Vec<Script> = run.glyphs.iter().map(|glyph| glyph.script()).collect();
+    assert_eq!(scripts[0], Script::Latin);
+    assert_eq!(scripts[5], Script::Latin);
+    assert_eq!(scripts[6], Script::Common);
+    assert_eq!(scripts[7], Script::Cyrillic);
+    assert_eq!(scripts[10], Script::Cyrillic);
+    assert_eq!(scripts[12], Script::Common);
+
+    let script_runs = run.script_runs();
+    assert_eq!(script_runs.len(), 2);
+    assert_eq!(script_runs[0].script, Script::Latin);
+    assert_eq!(script_runs[0].glyphs, 0..7);
+    assert_eq!(script_runs[1].script, Script::Cyrillic);
+    assert_eq!(script_runs[1].glyphs, 7..run.glyphs.len());
+    assert!(script_runs.iter().all(|script_run| !script_run.rtl));
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use cosmic_text::{
+    fontdb, Attrs, Buffer, Color, FontSystem, Metrics, Shadow, Shaping, SwashCache, Weight,
+};
+
+// Tests that shadows are passed through to glyphs and drawn under them, moved by their offset
+// and spread by their blur.
+#[test]
+fn shadow_draw() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let text_color = Color::rgb(0, 0, 0);
+    let shadow_color = Color::rgb(0xFF, 0, 0);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 200.0, 100.0);
+    let mut swash_cache = SwashCache::new();
+    let mut draw = |buffer: &mut Buffer, shadow: Shadow| {
+        buffer.set_text(
+            &mut font_system,
+            "H",
+            attrs.shadow(shadow),
+            Shaping::Advanced,
+        );
+        let glyph = buffer.layout_runs().next().unwrap().glyphs[0].clone();
+        assert_eq!(glyph.shadow_opt(), Some(shadow));
+
+        // Pixels of the text and its shadow, and the order they were drawn in
+        let mut text = HashSet::new();
+        let mut shadows = HashSet::new();
+        let mut shadow_first = true;
+        buffer.draw(
+            &mut font_system,
+            &mut swash_cache,
+            text_color,
+            |x, y, _, _, color| {
+                if color.a() == 0 {
+                    return;
+                }
+                if color.0 & 0xFF_FF_FF == shadow_color.0 & 0xFF_FF_FF {
+                    shadow_first &= text.is_empty();
+                    shadows.insert((x, y));
+                } else {
+                    text.insert((x, y));
+                }
+            },
+        );
+        assert!(shadow_first);
+        (text, shadows)
+    };
+
+    let (text, shadows) = draw(&mut buffer, Shadow::new(shadow_color, 3.0, 4.0, 0.0));
+    assert!(!text.is_empty());
+    let moved: HashSet<(i32, i32)> = text.iter().map(|&(x, y)| (x + 3, y + 4)).collect();
+    assert_eq!(shadows, moved);
+
+    // Blurred shadows spread past the shape of the glyph
+    let (text, shadows) = draw(&mut buffer, Shadow::new(shadow_color, 0.0, 0.0, 2.0));
+    let bounds = |pixels: &HashSet<(i32, i32)>| {
+        let min_x = pixels.iter().map(|p| p.0).min().unwrap();
+        let max_x = pixels.iter().map(|p| p.0).max().unwrap();
+        (min_x, max_x)
+    };
+    let (text_min, text_max) = bounds(&text);
+    let (shadow_min, shadow_max) = bounds(&shadows);
+    assert_eq!(shadow_min, text_min - 2);
+    assert_eq!(shadow_max, text_max + 2);
+}
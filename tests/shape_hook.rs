@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that the shape hook can remap glyphs and change their positions before layout.
+#[test]
+fn shape_hook_remap_glyphs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let runs = Arc::new(Mutex::new(Vec::new()));
+    let hook_runs = runs.clone();
+    font_system.set_shape_hook(Some(Arc::new(move |run, infos, positions| {
+        hook_runs.lock().unwrap().push(run.text().to_string());
+        let a = run.font.rustybuzz().glyph_index('a').unwrap().0 as u32;
+        for (info, position) in infos.iter_mut().zip(positions.iter_mut()) {
+            if &run.text()[info.cluster as usize..][..1] == "b" {
+                info.glyph_id = a;
+                position.x_advance *= 2;
+            }
+        }
+    })));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "abc",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    assert_eq!(*runs.lock().unwrap(), ["abc"]);
+
+    let run = buffer.layout_runs().next().unwrap();
+    let glyphs = run.glyphs;
+    assert_eq!(glyphs[1].glyph_id, glyphs[0].glyph_id);
+    assert_ne!(glyphs[2].glyph_id, glyphs[0].glyph_id);
+    assert_eq!((glyphs[1].start, glyphs[1].end), (1, 2));
+    assert!((glyphs[1].w - 2.0 * glyphs[0].w).abs() < 0.01);
+
+    // Without the hook, text is shaped as before
+    font_system.set_shape_hook(None);
+    buffer.set_text(
+        &mut font_system,
+        "abc",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::NORMAL),
+        Shaping::Advanced,
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    assert_ne!(run.glyphs[1].glyph_id, run.glyphs[0].glyph_id);
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "shape-run-cache")]
+
+use cosmic_text::{
+    fontdb, Attrs, AttrsOverride, Buffer, Color, FontSystem, Metrics, ShapeRunCacheMode, Shaping,
+    Weight,
+};
+
+fn shape(font_system: &mut FontSystem, shape_run_cache: ShapeRunCacheMode, text: &str) {
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_shape_run_cache(shape_run_cache);
+    buffer.set_size(font_system, 500.0, 100.0);
+    buffer.set_text(
+        font_system,
+        text,
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+}
+
+// Tests that each buffer fills only the shape run cache it selects.
+#[test]
+fn shape_run_cache_per_buffer() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    shape(&mut font_system, ShapeRunCacheMode::Disabled, "Debug 1");
+    assert_eq!(font_system.shape_run_cache.stats().entries, 0);
+    assert_eq!(font_system.shared_shape_run_cache(1).stats().entries, 0);
+
+    shape(&mut font_system, ShapeRunCacheMode::Default, "Label");
+    assert_eq!(font_system.shape_run_cache.stats().entries, 1);
+
+    shape(&mut font_system, ShapeRunCacheMode::Shared(1), "Label");
+    shape(&mut font_system, ShapeRunCacheMode::Shared(1), "Label");
+    let stats = font_system.shared_shape_run_cache(1).stats();
+    assert_eq!(stats.entries, 1);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(font_system.shape_run_cache.stats().entries, 1);
+    assert_eq!(font_system.shared_shape_run_cache(2).stats().entries, 0);
+
+    font_system.remove_shared_shape_run_cache(1);
+    assert_eq!(font_system.shared_shape_run_cache(1).stats().entries, 0);
+}
+
+// Tests that runs with layers of overrides are cached with the attributes the layers resolve to.
+#[test]
+fn shape_run_cache_layers() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    shape(&mut font_system, ShapeRunCacheMode::Default, "Label");
+    assert_eq!(font_system.shape_run_cache.stats().entries, 1);
+
+    let red = Color::rgb(255, 0, 0);
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.set_text(
+        &mut font_system,
+        "Label",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    buffer.set_attrs_layer(
+        &mut font_system,
+        0,
+        [(0, 1..3, AttrsOverride::new().color(red))],
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    let colors: Vec<_> = run.glyphs.iter().map(|glyph| glyph.color_opt).collect();
+    assert_eq!(colors, [None, Some(red), Some(red), None, None]);
+    assert_eq!(font_system.shape_run_cache.stats().entries, 2);
+}
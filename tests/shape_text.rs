@@ -0,0 +1,92 @@
+use cosmic_text::{
+    fontdb, layout_text, shape_text, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight,
+};
+
+// Tests that shaping without a buffer matches the layout of a buffer, scaled by the font size.
+#[test]
+fn shape_text_matches_buffer() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let text = "Hello world\nagain";
+    let shaped = shape_text(&mut font_system, text, attrs, Shaping::Advanced);
+    assert_eq!(shaped.runs.len(), 1);
+    let run = &shaped.runs[0];
+    assert!(!run.rtl);
+    assert_eq!(run.range, 0..text.len());
+    assert_eq!(run.glyphs.len(), "Hello worldagain".len());
+    assert!((run.w - shaped.w).abs() < 1e-4);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text("Hello world", attrs, Shaping::Advanced);
+    let layout_run = buffer.layout_runs().next().unwrap();
+    assert!((shaped.runs[0].glyphs[10].x * 20.0 - layout_run.glyphs[10].x).abs() < 1e-3);
+    assert!((shaped.runs[0].glyphs[11].x * 20.0 - layout_run.line_w).abs() < 1e-3);
+    for (shaped_glyph, glyph) in run.glyphs.iter().zip(layout_run.glyphs.iter()) {
+        assert_eq!(shaped_glyph.glyph_id, glyph.glyph_id);
+        assert_eq!(shaped_glyph.start, glyph.start);
+    }
+    assert_eq!(run.glyphs[11].start, "Hello world\n".len());
+}
+
+// Tests that one-shot layout positions glyphs where a buffer draws them, including wrapping.
+#[test]
+fn layout_text_matches_buffer() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let metrics = Metrics::new(20.0, 24.0);
+
+    let text = "Hello world, this wraps\nsecond paragraph";
+    let glyphs = layout_text(&mut font_system, text, attrs, metrics, Some(120.0));
+
+    let mut buffer = Buffer::new_empty(metrics);
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(120.0, 1000.0);
+    buffer.set_text(text, attrs, Shaping::Advanced);
+    let mut expected = Vec::new();
+    for run in buffer.layout_runs() {
+        for glyph in run.glyphs.iter() {
+            let physical_glyph = glyph.physical((0.0, 0.0), 1.0);
+            expected.push((
+                run.line_i,
+                physical_glyph.cache_key,
+                physical_glyph.x,
+                run.line_y as i32 + physical_glyph.y,
+            ));
+        }
+    }
+    assert!(buffer.layout_runs().count() > 2);
+
+    let actual: Vec<_> = glyphs
+        .iter()
+        .map(|positioned| {
+            let physical_glyph = positioned.physical();
+            (
+                positioned.line_i,
+                physical_glyph.cache_key,
+                physical_glyph.x,
+                physical_glyph.y,
+            )
+        })
+        .collect();
+    assert_eq!(actual, expected);
+    assert_eq!(glyphs.last().unwrap().glyph.end, text.len());
+
+    // Without a maximum width, each paragraph is a single line
+    let glyphs = layout_text(&mut font_system, text, attrs, metrics, None);
+    let mut line_ys: Vec<_> = glyphs.iter().map(|positioned| positioned.line_y).collect();
+    line_ys.dedup();
+    assert_eq!(line_ys.len(), 2);
+}
@@ -0,0 +1,106 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that shrink_to_fit picks the largest size that fits, and that the text actually fits.
+#[test]
+fn shrink_to_fit() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_text(
+        "Shrink this label until it fits",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let metrics = buffer.shrink_to_fit(120.0, 60.0, 4.0, 64.0);
+    assert!(metrics.font_size > 4.0 && metrics.font_size < 64.0);
+    assert!((metrics.line_height - metrics.font_size * 20.0 / 14.0).abs() < 0.001);
+    assert_eq!(buffer.size(), (120.0, 60.0));
+
+    let lines = buffer.layout_runs().count();
+    let max_w = buffer
+        .layout_runs()
+        .fold(0.0f32, |w, run| w.max(run.line_w));
+    assert!(max_w <= 120.0);
+    assert!(lines as f32 * metrics.line_height <= 60.0);
+
+    // Plenty of room uses the maximum size
+    let metrics = buffer.shrink_to_fit(10000.0, 10000.0, 4.0, 64.0);
+    assert_eq!(metrics.font_size, 64.0);
+}
+
+// Tests that text hidden by the maximum number of lines does not fit, even if the lines left
+// fit in the size.
+#[test]
+fn shrink_to_fit_max_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_max_lines(Some(1));
+    let text = "Shrink this label until it fits";
+    buffer.set_text(
+        text,
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let metrics = buffer.shrink_to_fit(120.0, 60.0, 4.0, 64.0);
+    assert!(metrics.font_size > 4.0 && metrics.font_size < 64.0);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 1);
+    let end = runs[0].glyphs.iter().map(|glyph| glyph.end).max().unwrap();
+    assert_eq!(end, text.len());
+    assert!(runs[0].line_w <= 120.0);
+}
+
+// Tests that text in columns fits when it fills no more than the columns of one page.
+#[test]
+fn shrink_to_fit_columns() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_columns(2, 20.0);
+    buffer.set_text(
+        "Shrink this label until it fits in two columns",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let metrics = buffer.shrink_to_fit(220.0, 60.0, 4.0, 64.0);
+    assert!(metrics.font_size > 4.0 && metrics.font_size < 64.0);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert!(runs.iter().any(|run| run.column == 1));
+    assert!(runs.iter().all(|run| run.line_w <= 100.0));
+    assert!(runs
+        .iter()
+        .all(|run| run.line_top + metrics.line_height <= 60.0));
+    let end = runs
+        .last()
+        .unwrap()
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.end)
+        .max();
+    assert_eq!(end, Some(runs[0].text.len()));
+}
@@ -0,0 +1,53 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, LayoutGlyph, Metrics, Shaping, Weight};
+
+// Tests that small caps either use the font's small capitals or scaled down capitals, marked as
+// synthesized, leaving capitals and other characters untouched.
+#[test]
+fn small_caps() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut layout = |text: &str, small_caps: bool| -> Vec<LayoutGlyph> {
+        let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+        let mut buffer = buffer.borrow_with(&mut font_system);
+        buffer.set_size(1000.0, 100.0);
+        buffer.set_text(
+            text,
+            Attrs::new()
+                .family(fontdb::Family::Name("FiraMono"))
+                .weight(Weight::MEDIUM)
+                .small_caps(small_caps),
+            Shaping::Advanced,
+        );
+        let run = buffer.layout_runs().next().unwrap();
+        run.glyphs.to_vec()
+    };
+
+    let plain = layout("Ab1", false);
+    assert!(plain.iter().all(|glyph| !glyph.synthetic_small_caps));
+    let capitals = layout("AB1", false);
+    let small_caps = layout("Ab1", true);
+    assert_eq!(small_caps.len(), 3);
+
+    // Capitals and digits are unchanged
+    for i in [0, 2] {
+        assert_eq!(small_caps[i].glyph_id, plain[i].glyph_id);
+        assert_eq!(small_caps[i].font_size, plain[i].font_size);
+        assert!(!small_caps[i].synthetic_small_caps);
+    }
+
+    let b = &small_caps[1];
+    if !b.synthetic_small_caps {
+        // The font's own small capital
+        assert_eq!(b.font_size, plain[1].font_size);
+        assert_ne!(b.glyph_id, plain[1].glyph_id);
+    } else {
+        // A synthesized small capital
+        assert_eq!(b.glyph_id, capitals[1].glyph_id);
+        assert!(b.font_size < plain[1].font_size);
+        assert!(b.w < capitals[1].w);
+    }
+    assert_eq!((b.start, b.end), (1, 2));
+}
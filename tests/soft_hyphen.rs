@@ -0,0 +1,55 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight};
+
+// Tests that soft hyphens are hidden, unless a line wraps after them.
+#[test]
+fn soft_hyphen_wrap() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 500.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "a hy\u{AD}phen\u{AD}ation",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let run_texts = |buffer: &Buffer| -> Vec<String> {
+        buffer
+            .layout_runs()
+            .map(|run| {
+                run.glyphs
+                    .iter()
+                    .map(|glyph| &run.text[glyph.start..glyph.end])
+                    .collect()
+            })
+            .collect()
+    };
+
+    // Without wrapping, soft hyphens take no space
+    assert_eq!(run_texts(&buffer), ["a hy\u{AD}phen\u{AD}ation"]);
+    let run = buffer.layout_runs().next().unwrap();
+    let char_w = run.glyphs[0].w;
+    for glyph in run.glyphs.iter() {
+        if &run.text[glyph.start..glyph.end] == "\u{AD}" {
+            assert_eq!(glyph.w, 0.0);
+        }
+    }
+    assert!((run.line_w - 13.0 * char_w).abs() < 0.01);
+
+    // The line wraps at the last soft hyphen that fits, and shows a hyphen there
+    buffer.set_size(&mut font_system, 140.0, 1000.0);
+    assert_eq!(run_texts(&buffer), ["a hy\u{AD}phen\u{AD}", "ation"]);
+    let run = buffer.layout_runs().next().unwrap();
+    let hyphen = run.glyphs.last().unwrap();
+    assert_eq!(
+        (hyphen.start, hyphen.end),
+        (run.text.len() - 5, run.text.len() - 5)
+    );
+    assert!(hyphen.w > 0.0);
+    assert!((run.line_w - 9.0 * char_w).abs() < 0.01);
+}
@@ -0,0 +1,53 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, Color, FontSystem, Metrics, RasterOptions, Shaping, SubpixelMode,
+    SwashCache, Weight,
+};
+
+// Tests that glyphs rasterized as subpixel masks are still drawn, with the average coverage of
+// their subpixels.
+#[test]
+fn subpixel_draw() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 200.0, 100.0);
+    buffer.set_text(&mut font_system, "H", attrs, Shaping::Advanced);
+
+    let mut draw = |subpixel_mode| {
+        let mut swash_cache = SwashCache::new();
+        swash_cache.set_raster_options(RasterOptions {
+            subpixel_mode,
+            ..RasterOptions::default()
+        });
+        let mut pixels = 0;
+        buffer.draw(
+            &mut font_system,
+            &mut swash_cache,
+            Color::rgb(0x12, 0x34, 0x56),
+            |_, _, _, _, color| {
+                if color.a() > 0 {
+                    assert_eq!(color.0 & 0xFF_FF_FF, 0x12_34_56);
+                    pixels += 1;
+                }
+            },
+        );
+        pixels
+    };
+
+    let alpha = draw(SubpixelMode::None);
+    assert!(alpha > 0);
+    for subpixel_mode in [SubpixelMode::Rgb, SubpixelMode::Bgr] {
+        let subpixel = draw(subpixel_mode);
+        // Subpixel masks spread coverage to the neighbouring subpixels
+        assert!(
+            subpixel >= alpha / 2,
+            "{subpixel_mode:?}: {subpixel} < {alpha}"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, CacheKeyFlags, FontSystem, Metrics, Shaping, SwashCache, Weight,
+};
+
+// Tests that bold text set with a face that is not bold gets synthesized bold, drawn with more
+// ink but the same advances, and that synthesis can be disabled.
+#[test]
+fn synthesize_bold_fallback() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new().family(fontdb::Family::Name("FiraMono"));
+
+    let mut buffer = Buffer::new_empty(Metrics::new(24.0, 30.0));
+    buffer.set_size(&mut font_system, 300.0, 100.0);
+    let mut shape = |font_system: &mut FontSystem, weight: Weight| {
+        buffer.set_text(font_system, "H", attrs.weight(weight), Shaping::Advanced);
+        let run = buffer.layout_runs().next().unwrap();
+        let glyph = run.glyphs[0].clone();
+        let cache_key = glyph.physical((0.0, 0.0), 1.0).cache_key;
+        let mut swash_cache = SwashCache::new();
+        let image = swash_cache
+            .get_image_uncached(font_system, cache_key)
+            .unwrap();
+        let ink: u32 = image.data.iter().map(|&value| u32::from(value)).sum();
+        (glyph.w, cache_key.flags, ink)
+    };
+
+    let (regular_w, regular_flags, regular_ink) = shape(&mut font_system, Weight::NORMAL);
+    assert!(!regular_flags.contains(CacheKeyFlags::FAKE_BOLD));
+    let (bold_w, bold_flags, bold_ink) = shape(&mut font_system, Weight::BOLD);
+    assert!(bold_flags.contains(CacheKeyFlags::FAKE_BOLD));
+    assert_eq!(bold_w, regular_w);
+    assert!(bold_ink > regular_ink);
+
+    assert!(font_system.synthesize_bold());
+    font_system.set_synthesize_bold(false);
+    let (_, flags, ink) = shape(&mut font_system, Weight::BOLD);
+    assert!(!flags.contains(CacheKeyFlags::FAKE_BOLD));
+    assert_eq!(ink, regular_ink);
+}
@@ -0,0 +1,40 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+// Tests that tabular digits make numbers with different digits equally wide, without changing
+// other glyphs.
+#[test]
+fn tabular_digits() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSans-Regular.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut line_widths = |text: &str, tabular_digits: bool| {
+        let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+        let mut buffer = buffer.borrow_with(&mut font_system);
+        buffer.set_size(1000.0, 100.0);
+        buffer.set_tabular_digits(tabular_digits);
+        buffer.set_text(
+            text,
+            Attrs::new().family(fontdb::Family::Name("Noto Sans")),
+            Shaping::Advanced,
+        );
+        let run = buffer.layout_runs().next().unwrap();
+        let glyph_widths: Vec<f32> = run.glyphs.iter().map(|glyph| glyph.w).collect();
+        (run.line_w, glyph_widths)
+    };
+
+    let (ones_w, ones) = line_widths("Score: 1111", true);
+    let (eights_w, eights) = line_widths("Score: 8888", true);
+    assert_eq!(ones_w, eights_w);
+    assert_eq!(ones[7..], eights[7..]);
+    assert!(ones[7..].iter().all(|w| *w == ones[7]));
+
+    // Letters keep their width, digits are never narrower than without tabular digits
+    let (_, proportional) = line_widths("Score: 1111", false);
+    assert_eq!(ones[..7], proportional[..7]);
+    assert!(ones[7..]
+        .iter()
+        .zip(&proportional[7..])
+        .all(|(tabular, proportional)| tabular >= proportional));
+}
@@ -0,0 +1,90 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, LayoutGlyph, Metrics, Shaping, TextTransform, Weight,
+};
+
+fn layout(
+    font_system: &mut FontSystem,
+    text: &str,
+    text_transform: TextTransform,
+    shaping: Shaping,
+) -> Vec<LayoutGlyph> {
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM)
+        .text_transform(text_transform);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(font_system, 1000.0, 100.0);
+    buffer.set_text(font_system, text, attrs, shaping);
+    assert_eq!(buffer.lines[0].text(), text);
+    let run = buffer.layout_runs().next().unwrap();
+    run.glyphs.to_vec()
+}
+
+fn glyph_ids(glyphs: &[LayoutGlyph]) -> Vec<u16> {
+    glyphs.iter().map(|glyph| glyph.glyph_id).collect()
+}
+
+// Tests that text transforms change the glyphs shown without changing the text, with glyphs
+// keeping the byte indices of the original characters.
+#[test]
+fn text_transform() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    for shaping in [Shaping::Advanced, Shaping::Basic] {
+        let none = TextTransform::None;
+        let upper = layout(&mut font_system, "DON'T STOP", none, shaping);
+        let lower = layout(&mut font_system, "don't stop", none, shaping);
+        let capital = layout(&mut font_system, "Don't Stop", none, shaping);
+
+        let glyphs = layout(
+            &mut font_system,
+            "Don't Stop",
+            TextTransform::Lowercase,
+            shaping,
+        );
+        assert_eq!(glyph_ids(&glyphs), glyph_ids(&lower));
+        let glyphs = layout(
+            &mut font_system,
+            "don't stop",
+            TextTransform::Capitalize,
+            shaping,
+        );
+        assert_eq!(glyph_ids(&glyphs), glyph_ids(&capital));
+
+        let glyphs = layout(
+            &mut font_system,
+            "don't stop",
+            TextTransform::Uppercase,
+            shaping,
+        );
+        assert_eq!(glyph_ids(&glyphs), glyph_ids(&upper));
+        assert_ne!(glyph_ids(&glyphs), glyph_ids(&lower));
+        for (glyph, lower_glyph) in glyphs.iter().zip(lower.iter()) {
+            assert_eq!(
+                (glyph.start, glyph.end),
+                (lower_glyph.start, lower_glyph.end)
+            );
+        }
+    }
+
+    // A character may be shown as several, all mapped to the same character of the text
+    let glyphs = layout(
+        &mut font_system,
+        "\u{DF}",
+        TextTransform::Uppercase,
+        Shaping::Advanced,
+    );
+    let expected = layout(
+        &mut font_system,
+        "SS",
+        TextTransform::None,
+        Shaping::Advanced,
+    );
+    assert_eq!(glyph_ids(&glyphs), glyph_ids(&expected));
+    assert!(glyphs
+        .iter()
+        .all(|glyph| (glyph.start, glyph.end) == (0, 2)));
+}
@@ -0,0 +1,75 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, FontSystem, LayoutRun, Metrics, Shaping, Truncate, Weight, Wrap,
+};
+
+fn ellipsis_index(run: &LayoutRun) -> Option<usize> {
+    run.glyphs.iter().position(|glyph| glyph.start == glyph.end)
+}
+
+// Tests that text that does not fit in the maximum number of lines is elided with an ellipsis
+// at the end, the middle, or the start.
+#[test]
+fn truncate_max_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let text = "The quick brown fox jumps over the lazy dog";
+    let width = 150.0;
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, width, 1000.0);
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    buffer.set_text(
+        &mut font_system,
+        text,
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    let all_lines = buffer.layout_runs().count();
+    assert!(all_lines > 2);
+
+    // The last line shown ends with an ellipsis
+    buffer.set_max_lines(&mut font_system, Some(2));
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(ellipsis_index(&runs[0]), None);
+    assert_eq!(ellipsis_index(&runs[1]), Some(runs[1].glyphs.len() - 1));
+    assert!(runs[1].line_w <= width);
+    assert!(runs[1].glyphs[0].start >= runs[0].glyphs.last().unwrap().end);
+    for pair in runs[1].glyphs.windows(2) {
+        assert!((pair[0].x + pair[0].w - pair[1].x).abs() < 0.01);
+    }
+
+    // The first line shown starts with an ellipsis
+    buffer.set_truncate(&mut font_system, Truncate::Start);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(ellipsis_index(&runs[0]), Some(0));
+    assert_eq!(runs[1].glyphs.last().unwrap().end, text.len());
+
+    // A single line that is too wide keeps both of its ends
+    buffer.set_wrap(&mut font_system, Wrap::None);
+    buffer.set_max_lines(&mut font_system, Some(1));
+    buffer.set_truncate(&mut font_system, Truncate::Middle);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 1);
+    let ellipsis_i = ellipsis_index(&runs[0]).unwrap();
+    assert!(ellipsis_i > 0 && ellipsis_i < runs[0].glyphs.len() - 1);
+    assert_eq!(runs[0].glyphs[0].start, 0);
+    assert_eq!(runs[0].glyphs.last().unwrap().end, text.len());
+    assert!(runs[0].line_w <= width);
+
+    // Text that fits is not elided
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(ellipsis_index(&runs[0]), None);
+
+    buffer.set_size(&mut font_system, width, 1000.0);
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    buffer.set_max_lines(&mut font_system, None);
+    assert_eq!(buffer.layout_runs().count(), all_lines);
+}
@@ -0,0 +1,48 @@
+use cosmic_text::{Attrs, AttrsList, Buffer, BufferLine, Cursor, LineEnding, Metrics, Shaping};
+
+// Tests that cursors convert to offsets into the UTF-16 text of the buffer, and back.
+#[test]
+fn utf16_buffer_cursors() {
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    for (text, ending) in [
+        ("h\u{E9}llo", LineEnding::CrLf),
+        ("\u{1F600}!", LineEnding::Lf),
+        ("end", LineEnding::None),
+    ] {
+        buffer.lines.push(BufferLine::new(
+            text,
+            ending,
+            AttrsList::new(Attrs::new()),
+            Shaping::Advanced,
+        ));
+    }
+    let text: Vec<u16> = buffer.text().encode_utf16().collect();
+
+    assert_eq!(buffer.lines[0].utf16_map().to_utf16(3), 2);
+    assert_eq!(buffer.lines[1].utf16_map().to_utf8(2), 4);
+
+    let cursors = [
+        (Cursor::new(0, 0), 0),
+        (Cursor::new(0, 3), 2),
+        (Cursor::new(0, 6), 5),
+        (Cursor::new(1, 0), 7),
+        (Cursor::new(1, 4), 9),
+        (Cursor::new(2, 0), 11),
+        (Cursor::new(2, 3), 14),
+    ];
+    for (cursor, offset) in cursors {
+        assert_eq!(buffer.cursor_to_utf16(cursor), offset);
+        assert_eq!(buffer.cursor_from_utf16(offset), cursor);
+    }
+    assert_eq!(text.len(), 14);
+    assert_eq!(text[11], u16::from(b'e'));
+
+    // Offsets in line endings and past the end are clamped
+    assert_eq!(buffer.cursor_from_utf16(6), Cursor::new(0, 6));
+    assert_eq!(buffer.cursor_from_utf16(100), Cursor::new(2, 3));
+
+    // Changing the text of a line updates its map
+    buffer.lines[0].set_text("hello", LineEnding::CrLf, AttrsList::new(Attrs::new()));
+    assert_eq!(buffer.cursor_to_utf16(Cursor::new(1, 4)), 9);
+    assert_eq!(buffer.lines[0].utf16_map().to_utf16(3), 3);
+}
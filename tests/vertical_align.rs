@@ -0,0 +1,45 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, VerticalAlign, Weight};
+
+// Tests that lines are moved down to the middle or the bottom of the buffer, and stay at the top
+// when they do not fit.
+#[test]
+fn vertical_align_offsets_lines() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(300.0, 200.0);
+    buffer.set_text(
+        "One\nTwo\nThree",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let tops = |buffer: &Buffer| {
+        buffer
+            .layout_runs()
+            .map(|run| (run.line_top, run.line_y - run.line_top))
+            .collect::<Vec<_>>()
+    };
+    let top = tops(&buffer);
+    assert_eq!(top[0].0, 0.0);
+
+    buffer.set_vertical_align(VerticalAlign::Middle);
+    for (i, (line_top, baseline)) in tops(&buffer).into_iter().enumerate() {
+        assert_eq!(line_top, (200.0 - 3.0 * 24.0) / 2.0 + i as f32 * 24.0);
+        assert_eq!(baseline, top[i].1);
+    }
+
+    buffer.set_vertical_align(VerticalAlign::Bottom);
+    let bottom = tops(&buffer);
+    assert_eq!(bottom[2].0 + 24.0, 200.0);
+
+    // Text taller than the buffer is placed at the top
+    buffer.set_size(300.0, 50.0);
+    assert_eq!(tops(&buffer)[0].0, 0.0);
+}
@@ -0,0 +1,41 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, VerticalMetrics, Weight};
+
+// Tests that the baseline of lines is placed with the ascent and descent of the chosen convention.
+#[test]
+fn vertical_metrics_line_y() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let font_id = font_system.db().faces().next().unwrap().id;
+    let font = font_system.get_font(font_id).unwrap();
+
+    let metrics = Metrics::new(20.0, 30.0);
+    let mut buffer = Buffer::new_empty(metrics);
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text(
+        "Hello\nworld",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    for vertical_metrics in [VerticalMetrics::Typographic, VerticalMetrics::Gdi] {
+        buffer.set_vertical_metrics(vertical_metrics);
+        let (ascent, descent) = font.ascent_descent_em(vertical_metrics);
+        let (ascent, descent) = (ascent * metrics.font_size, descent * metrics.font_size);
+        for (i, run) in buffer.layout_runs().enumerate() {
+            let line_top = i as f32 * metrics.line_height;
+            let expected = line_top + (metrics.line_height - ascent - descent) / 2.0 + ascent;
+            assert!((run.line_y - expected).abs() < 1e-3);
+        }
+    }
+
+    let (win_ascent, win_descent) = font.ascent_descent_em(VerticalMetrics::Gdi);
+    assert_eq!(
+        font.line_height_em(VerticalMetrics::Gdi),
+        win_ascent + win_descent
+    );
+}
@@ -0,0 +1,75 @@
+use cosmic_text::{
+    fontdb, Attrs, Buffer, CacheKeyFlags, FontSystem, Metrics, Shaping, SwashCache,
+    TextOrientation, VerticalOrientation, Weight, WritingMode,
+};
+
+// Tests that characters get their orientation in vertical lines from the Unicode
+// Vertical_Orientation property.
+#[test]
+fn vertical_orientation_of_chars() {
+    assert_eq!(VerticalOrientation::of('a'), VerticalOrientation::Rotated);
+    assert_eq!(
+        VerticalOrientation::of('\u{6F22}'),
+        VerticalOrientation::Upright
+    );
+    assert_eq!(
+        VerticalOrientation::of('\u{3042}'),
+        VerticalOrientation::Upright
+    );
+    assert_eq!(
+        VerticalOrientation::of('\u{3001}'),
+        VerticalOrientation::TransformedUpright
+    );
+    assert_eq!(
+        VerticalOrientation::of('\u{300C}'),
+        VerticalOrientation::TransformedRotated
+    );
+    assert_eq!(
+        VerticalOrientation::of('\u{FF61}'),
+        VerticalOrientation::Rotated
+    );
+}
+
+// Tests that Latin glyphs in vertical lines are turned sideways and drawn rotated clockwise,
+// unless the text orientation keeps them upright.
+#[test]
+fn vertical_orientation_sideways_glyphs() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let mut swash_cache = SwashCache::new();
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 200.0, 200.0);
+    buffer.set_writing_mode(&mut font_system, WritingMode::VerticalRl);
+    buffer.set_text(
+        &mut font_system,
+        "ll",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    assert_eq!(run.glyphs.len(), 2);
+    assert!(run.glyphs[1].y > run.glyphs[0].y);
+    for glyph in run.glyphs.iter() {
+        assert!(glyph.cache_key_flags.contains(CacheKeyFlags::SIDEWAYS));
+    }
+    // The rotated glyph is wider than it is tall, with its top on the right
+    let physical = run.glyphs[0].physical((run.line_x, 0.0), 1.0);
+    let image = swash_cache
+        .get_image_uncached(&mut font_system, physical.cache_key)
+        .unwrap();
+    assert!(image.placement.width > image.placement.height);
+    assert!(image.placement.left > -5);
+
+    buffer.set_text_orientation(&mut font_system, TextOrientation::Upright);
+    let run = buffer.layout_runs().next().unwrap();
+    for glyph in run.glyphs.iter() {
+        assert!(!glyph.cache_key_flags.contains(CacheKeyFlags::SIDEWAYS));
+        assert_eq!(glyph.x, -glyph.w / 2.0);
+    }
+}
@@ -0,0 +1,32 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, VisualOrder};
+
+// Tests that a right-to-left word inside left-to-right text is mapped in reverse.
+#[test]
+fn visual_order_mixed() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/NotoSans-Regular.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let font = std::fs::read("fonts/NotoSansHebrew.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    // "a אב b", where the Hebrew letters take two bytes each
+    buffer.set_text(
+        "a \u{05D0}\u{05D1} b",
+        Attrs::new().family(fontdb::Family::Name("Noto Sans")),
+        Shaping::Advanced,
+    );
+    let run = buffer.layout_runs().next().unwrap();
+    let VisualOrder {
+        visual_to_logical,
+        start,
+        logical_to_visual,
+    } = run.visual_order();
+
+    assert_eq!(visual_to_logical, [0, 1, 4, 2, 6, 7]);
+    assert_eq!(start, 0);
+    assert_eq!(logical_to_visual, [0, 1, 3, 3, 2, 2, 4, 5]);
+}
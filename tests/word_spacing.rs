@@ -0,0 +1,48 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping, Weight, Wrap};
+
+// Tests that word spacing widens spaces in its span only, and that wrapping accounts for it.
+#[test]
+fn word_spacing_widens_spaces() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [("a b ", attrs.word_spacing(0.5)), ("c d", attrs)],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let widths: Vec<f32> = run.glyphs.iter().map(|glyph| glyph.w).collect();
+    // Letters keep their width, spaces in the span are half an em wider
+    assert_eq!(widths[0], widths[2]);
+    assert!((widths[1] - widths[0] - 10.0).abs() < 0.01);
+    assert!((widths[3] - widths[0] - 10.0).abs() < 0.01);
+    assert_eq!(widths[5], widths[0]);
+    assert!((run.line_w - (7.0 * widths[0] + 20.0)).abs() < 0.01);
+
+    // A line that fits without word spacing wraps with it
+    let width = 7.0 * widths[0] + 1.0;
+    buffer.set_size(&mut font_system, width, 1000.0);
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    buffer.set_text(&mut font_system, "a b c d", attrs, Shaping::Advanced);
+    assert_eq!(buffer.layout_runs().count(), 1);
+    buffer.set_text(
+        &mut font_system,
+        "a b c d",
+        attrs.word_spacing(0.5),
+        Shaping::Advanced,
+    );
+    assert!(buffer.layout_runs().count() > 1);
+    for run in buffer.layout_runs() {
+        assert!(run.line_w <= width);
+    }
+}
@@ -0,0 +1,181 @@
+use cosmic_text::{
+    fontdb, Affinity, Attrs, Buffer, Cursor, FontSystem, Metrics, Motion, Shaping, TextOrientation,
+    Weight, Wrap, WritingMode,
+};
+
+// Tests that vertical lines wrap at the height of the buffer and are placed from right to left
+// or from left to right, with glyphs along them.
+#[test]
+fn writing_mode_vertical_columns() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(200.0, 100.0);
+    buffer.set_wrap(Wrap::Glyph);
+    buffer.set_text_orientation(TextOrientation::Upright);
+    buffer.set_text(
+        "abcdefghij",
+        Attrs::new()
+            .family(fontdb::Family::Name("FiraMono"))
+            .weight(Weight::MEDIUM),
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.layout_runs().count(), 1);
+
+    buffer.set_writing_mode(WritingMode::VerticalRl);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].line_x, 200.0 - 12.0);
+    assert_eq!(runs[1].line_x, 200.0 - 36.0);
+    for run in runs.iter() {
+        assert!(run.line_w <= 100.0);
+        for pair in run.glyphs.windows(2) {
+            assert!((pair[0].y + pair[0].w - pair[1].y).abs() < 0.01);
+        }
+        for glyph in run.glyphs.iter() {
+            assert_eq!(glyph.x, -glyph.w / 2.0);
+        }
+    }
+
+    // Hit testing finds the closest line, then the glyph along it
+    let glyph = &runs[1].glyphs[1];
+    let (line_x, line_i, start) = (runs[1].line_x, runs[1].line_i, glyph.start);
+    let hit = buffer.hit(line_x + 5.0, glyph.y + 1.0).unwrap();
+    assert_eq!((hit.line, hit.index), (line_i, start));
+    assert_eq!(
+        buffer.hit(-100.0, -100.0).map(|cursor| cursor.index),
+        Some(runs[1].glyphs[0].start)
+    );
+
+    buffer.set_writing_mode(WritingMode::VerticalLr);
+    let line_x: Vec<f32> = buffer.layout_runs().map(|run| run.line_x).collect();
+    assert_eq!(line_x, [12.0, 36.0]);
+
+    buffer.set_writing_mode(WritingMode::HorizontalTb);
+    assert_eq!(buffer.layout_runs().count(), 1);
+    let hit = buffer.hit(1.0, 10.0).unwrap();
+    assert_eq!((hit.line, hit.index), (0, 0));
+}
+
+// Tests that spans combined upright stay horizontal in one em of a vertical line, centered on it
+// and scaled down to fit, with the glyphs after them following the em.
+#[test]
+fn writing_mode_combine_upright() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(200.0, 200.0);
+    buffer.set_writing_mode(WritingMode::VerticalRl);
+    buffer.set_text_orientation(TextOrientation::Upright);
+    buffer.set_rich_text(
+        [
+            ("a", attrs),
+            ("12", attrs.combine_upright(true)),
+            ("b", attrs),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let glyphs = run.glyphs;
+    assert_eq!(glyphs.len(), 4);
+    let advance = glyphs[0].w;
+    assert!(2.0 * advance > 20.0);
+
+    // The two digits are wider than one em, so they are scaled down to fit it
+    let scale = 20.0 / (2.0 * advance);
+    for glyph in &glyphs[1..3] {
+        assert_eq!(glyph.y, advance);
+        assert_eq!(glyph.w, 20.0);
+        assert!((glyph.font_size - 20.0 * scale).abs() < 0.01);
+    }
+    assert!((glyphs[1].x + 10.0).abs() < 0.01);
+    assert!(glyphs[2].x.abs() < 0.01);
+    assert!((glyphs[3].y - (advance + 20.0)).abs() < 0.01);
+    assert!((run.line_w - (2.0 * advance + 20.0)).abs() < 0.01);
+
+    // Horizontal lines are not changed
+    buffer.set_writing_mode(WritingMode::HorizontalTb);
+    let run = buffer.layout_runs().next().unwrap();
+    assert_eq!(run.glyphs[2].x, 2.0 * advance);
+    assert_eq!(run.glyphs[2].font_size, 20.0);
+}
+
+// Tests that cursor motion and highlights follow vertical lines, with up and down moving along a
+// line and left and right moving to the next line in the direction of the lines.
+#[test]
+fn writing_mode_vertical_motion_and_highlight() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let font = std::fs::read("fonts/FiraMono-Medium.ttf").unwrap();
+    font_system.db_mut().load_font_data(font);
+    let attrs = Attrs::new()
+        .family(fontdb::Family::Name("FiraMono"))
+        .weight(Weight::MEDIUM);
+
+    let mut buffer = Buffer::new_empty(Metrics::new(20.0, 24.0));
+    buffer.set_size(&mut font_system, 200.0, 100.0);
+    buffer.set_wrap(&mut font_system, Wrap::Glyph);
+    buffer.set_writing_mode(&mut font_system, WritingMode::VerticalRl);
+    buffer.set_text(
+        &mut font_system,
+        "abcdefghij",
+        attrs.underline(true),
+        Shaping::Advanced,
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let advance = run.glyphs[0].w;
+    assert_eq!(run.glyph_position(&run.glyphs[1]), advance);
+    let highlight = run.highlight(Cursor::new(0, 1), Cursor::new(0, 3));
+    assert_eq!(highlight, Some((advance, 2.0 * advance)));
+    assert_eq!(
+        run.line_rect(advance, 3.0 * advance, 24.0),
+        (run.line_x - 12.0, advance, 24.0, 2.0 * advance)
+    );
+
+    // Decorations are not drawn along vertical lines
+    assert!(run.decorations(&mut font_system).is_empty());
+
+    let second_start = buffer.layout_runs().nth(1).unwrap().glyphs[0].start;
+    let mut motion = |buffer: &mut Buffer, index: usize, motion: Motion| {
+        buffer
+            .cursor_motion(
+                &mut font_system,
+                Cursor::new_with_affinity(0, index, Affinity::After),
+                None,
+                motion,
+            )
+            .map(|(cursor, _)| cursor.index)
+    };
+    assert_eq!(motion(&mut buffer, 1, Motion::Down), Some(2));
+    assert_eq!(motion(&mut buffer, 1, Motion::Up), Some(0));
+    assert_eq!(motion(&mut buffer, 0, Motion::Left), Some(second_start));
+    assert_eq!(motion(&mut buffer, second_start, Motion::Right), Some(0));
+
+    buffer.set_writing_mode(&mut font_system, WritingMode::VerticalLr);
+    let mut motion = |buffer: &mut Buffer, index: usize, motion: Motion| {
+        buffer
+            .cursor_motion(
+                &mut font_system,
+                Cursor::new_with_affinity(0, index, Affinity::After),
+                None,
+                motion,
+            )
+            .map(|(cursor, _)| cursor.index)
+    };
+    assert_eq!(motion(&mut buffer, 0, Motion::Right), Some(second_start));
+    assert_eq!(motion(&mut buffer, second_start, Motion::Left), Some(0));
+}